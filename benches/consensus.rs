@@ -0,0 +1,271 @@
+//! Criterion benchmarks for the consensus hot path, measured over
+//! synthetic blocks of 1k–10k signed transactions: merkle construction,
+//! full contextual block validation, block connect (validation plus
+//! UTXO application in one atomic batch — subtract the validation
+//! number to isolate application), and mempool admission.
+//!
+//! CI catches regressions by comparing against a saved baseline:
+//!
+//! ```text
+//! cargo bench --bench consensus -- --save-baseline release
+//! cargo bench --bench consensus -- --baseline release
+//! ```
+//!
+//! Groups run with a 5% noise threshold so scheduler jitter on shared
+//! runners does not trip the comparison.
+
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use pali_coin::blockchain::{Blockchain, GenesisConfig, PremineAllocation, COINBASE_MATURITY};
+use pali_coin::hash;
+use pali_coin::math;
+use pali_coin::mempool::Mempool;
+use pali_coin::types::{
+    block_reward, Address, Block, BlockHeader, Hash256, Transaction, COINBASE_ADDRESS, COIN,
+};
+use pali_coin::{crypto, MAINNET_CHAIN_ID};
+use secp256k1::{Secp256k1, SecretKey};
+
+/// Transaction counts a realistic full block might carry.
+const BLOCK_SIZES: [usize; 2] = [1_000, 10_000];
+
+/// A funded chain plus one solved, fully valid block of `n` signed
+/// transactions waiting at the tip.
+struct Fixture {
+    chain: Blockchain,
+    block: Block,
+    _dir: std::path::PathBuf,
+}
+
+fn deterministic_keys(count: usize) -> Vec<(SecretKey, Address)> {
+    let secp = Secp256k1::new();
+    (1..=count as u64)
+        .map(|i| {
+            let mut bytes = [0u8; 32];
+            bytes[24..].copy_from_slice(&i.to_be_bytes());
+            let secret = SecretKey::from_slice(&bytes).expect("nonzero scalar");
+            let public = secret.public_key(&secp);
+            (secret, hash::pubkey_to_address(&public.serialize()))
+        })
+        .collect()
+}
+
+/// Grinds the easy benchmark difficulty; a handful of attempts at
+/// MAX_BITS.
+fn solve(header: &mut BlockHeader) {
+    while !math::hash_meets_target(&header.hash(), header.bits) {
+        header.nonce = header.nonce.wrapping_add(1);
+    }
+}
+
+fn coinbase(height: u64, fees: u64) -> Transaction {
+    Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: COINBASE_ADDRESS,
+        to: [0x11; 20],
+        amount: block_reward(height) + fees,
+        fee: 0,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+fn seal(chain: &Blockchain, transactions: Vec<Transaction>, timestamp: u64) -> Block {
+    let hashes: Vec<Hash256> = transactions.iter().map(|tx| tx.hash()).collect();
+    let mut header = BlockHeader {
+        version: 1,
+        prev_hash: chain.best_hash(),
+        merkle_root: hash::merkle_root(&hashes),
+        timestamp,
+        bits: chain.next_bits().expect("next bits"),
+        nonce: 0,
+        height: chain.height() + 1,
+    };
+    solve(&mut header);
+    Block {
+        header,
+        transactions,
+    }
+}
+
+/// Builds a chain whose genesis premines every benchmark sender, mines
+/// the premine past coinbase maturity, and prepares one block of `n`
+/// independently signed single-transfer transactions.
+fn fixture(n: usize) -> Fixture {
+    static FIXTURES: std::sync::atomic::AtomicU32 = std::sync::atomic::AtomicU32::new(0);
+    let unique = FIXTURES.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    let dir = std::env::temp_dir().join(format!(
+        "pali-bench-{}-{}-{}",
+        std::process::id(),
+        n,
+        unique
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    let keys = deterministic_keys(n);
+    let genesis_timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("clock after epoch")
+        .as_secs()
+        - (COINBASE_MATURITY + 10) * 180;
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: genesis_timestamp,
+        message: "bench".to_string(),
+        bits: math::MAX_BITS,
+        premine: keys
+            .iter()
+            .map(|(_, address)| PremineAllocation {
+                address: hex::encode(address),
+                amount: 2 * COIN,
+            })
+            .collect(),
+    };
+    let mut chain = Blockchain::init_chain(&dir, &config).expect("init bench chain");
+
+    // The premine sits in coinbase outputs; mine empty blocks until it
+    // matures and the synthetic block can spend it.
+    for height in 1..=COINBASE_MATURITY {
+        let block = seal(
+            &chain,
+            vec![coinbase(height, 0)],
+            genesis_timestamp + height * 180,
+        );
+        chain
+            .add_block(&block, MAINNET_CHAIN_ID)
+            .expect("connect maturity block");
+    }
+
+    let height = chain.height() + 1;
+    let mut transactions = Vec::with_capacity(n + 1);
+    let mut fees = 0u64;
+    transactions.push(coinbase(height, 0));
+    for (secret, address) in &keys {
+        let mut tx = Transaction {
+            chain_id: MAINNET_CHAIN_ID,
+            nonce: 0,
+            from: *address,
+            to: [0xEE; 20],
+            amount: COIN,
+            fee: 1_000,
+            data: Vec::new(),
+            replaceable: false,
+            lock_time: 0,
+            signature: Vec::new(),
+            public_key: Vec::new(),
+        };
+        crypto::sign_transaction(&mut tx, secret).expect("sign bench tx");
+        fees += tx.fee;
+        transactions.push(tx);
+    }
+    transactions[0] = coinbase(height, fees);
+    let block = seal(&chain, transactions, genesis_timestamp + height * 180);
+    chain
+        .validate_block(&block, MAINNET_CHAIN_ID)
+        .expect("bench block validates");
+    Fixture {
+        chain,
+        block,
+        _dir: dir,
+    }
+}
+
+fn bench_merkle_root(c: &mut Criterion) {
+    let mut group = c.benchmark_group("merkle_root");
+    group.noise_threshold(0.05);
+    for n in BLOCK_SIZES {
+        let hashes: Vec<Hash256> = (0..n as u64)
+            .map(|i| hash::sha256(&i.to_be_bytes()))
+            .collect();
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(n), &hashes, |b, hashes| {
+            b.iter(|| hash::merkle_root(std::hint::black_box(hashes)));
+        });
+    }
+    group.finish();
+}
+
+fn bench_block_validation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("block_validation");
+    group.noise_threshold(0.05);
+    group.sample_size(10);
+    for n in BLOCK_SIZES {
+        let fixture = fixture(n);
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_function(BenchmarkId::from_parameter(n), |b| {
+            b.iter(|| {
+                fixture
+                    .chain
+                    .validate_block(std::hint::black_box(&fixture.block), MAINNET_CHAIN_ID)
+                    .expect("bench block validates")
+            });
+        });
+    }
+    group.finish();
+}
+
+fn bench_block_connect(c: &mut Criterion) {
+    let mut group = c.benchmark_group("block_connect");
+    group.noise_threshold(0.05);
+    group.sample_size(10);
+    for n in BLOCK_SIZES {
+        let mut fixture = fixture(n);
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_function(BenchmarkId::from_parameter(n), |b| {
+            // Connect is timed; the disconnect that resets the tip for
+            // the next iteration is not.
+            b.iter_custom(|iters| {
+                let mut total = Duration::ZERO;
+                for _ in 0..iters {
+                    let started = Instant::now();
+                    fixture
+                        .chain
+                        .add_block(&fixture.block, MAINNET_CHAIN_ID)
+                        .expect("connect bench block");
+                    total += started.elapsed();
+                    fixture.chain.disconnect_tip().expect("reset tip");
+                }
+                total
+            });
+        });
+    }
+    group.finish();
+}
+
+fn bench_mempool_insert(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mempool_insert");
+    group.noise_threshold(0.05);
+    group.sample_size(10);
+    for n in BLOCK_SIZES {
+        let fixture = fixture(n);
+        let transactions: Vec<Transaction> = fixture.block.transactions[1..].to_vec();
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_function(BenchmarkId::from_parameter(n), |b| {
+            b.iter_batched(
+                || transactions.clone(),
+                |transactions| {
+                    let mut pool = Mempool::new();
+                    for tx in transactions {
+                        pool.insert(tx, 0).expect("admit bench tx");
+                    }
+                    pool
+                },
+                BatchSize::LargeInput,
+            );
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_merkle_root,
+    bench_block_validation,
+    bench_block_connect,
+    bench_mempool_insert
+);
+criterion_main!(benches);
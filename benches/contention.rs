@@ -0,0 +1,121 @@
+//! Criterion benchmarks for tip-state read contention.
+//!
+//! Read-mostly paths (peer handshakes, sync checks, height polling)
+//! answer from the lock-free snapshot in the snapshot module instead
+//! of the blockchain mutex. This benchmark measures what that buys:
+//! the cost of one height read through the mutex versus through the
+//! snapshot, first on an idle chain and then while a writer thread
+//! grabs the mutex at the cadence of a busy block connect. The mutex
+//! numbers collapse under the writer; the snapshot numbers should not
+//! move.
+//!
+//! ```text
+//! cargo bench --bench contention -- --save-baseline release
+//! cargo bench --bench contention -- --baseline release
+//! ```
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use pali_coin::blockchain::{Blockchain, GenesisConfig};
+use pali_coin::snapshot::TipView;
+use pali_coin::{math, MAINNET_CHAIN_ID};
+
+/// How long the simulated connect holds the chain mutex. Full-block
+/// connects in the consensus benchmark run well past this; the figure
+/// is deliberately conservative.
+const WRITER_HOLD: Duration = Duration::from_micros(50);
+
+/// Off-lock gap between simulated connects, so readers get a window.
+const WRITER_GAP: Duration = Duration::from_micros(50);
+
+fn open_chain() -> Arc<Mutex<Blockchain>> {
+    let dir = std::env::temp_dir().join(format!("pali-contention-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "contention bench".to_string(),
+        bits: math::MAX_BITS,
+        premine: Vec::new(),
+    };
+    Arc::new(Mutex::new(
+        Blockchain::init_chain(&dir, &config).expect("open bench chain"),
+    ))
+}
+
+fn spin(duration: Duration) {
+    let start = Instant::now();
+    while start.elapsed() < duration {
+        std::hint::spin_loop();
+    }
+}
+
+/// A background thread hammering the chain mutex the way block
+/// connects do, stopped and joined when dropped.
+struct Writer {
+    stop: Arc<AtomicBool>,
+    handle: Option<std::thread::JoinHandle<()>>,
+}
+
+impl Writer {
+    fn start(chain: Arc<Mutex<Blockchain>>, view: Arc<TipView>) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let handle = std::thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                {
+                    let chain = chain.lock().expect("chain lock poisoned");
+                    spin(WRITER_HOLD);
+                    view.refresh(&chain);
+                }
+                spin(WRITER_GAP);
+            }
+        });
+        Writer {
+            stop,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for Writer {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn bench_tip_reads(c: &mut Criterion) {
+    let chain = open_chain();
+    let view = Arc::new(TipView::new(&chain.lock().expect("chain lock poisoned")));
+
+    let mut group = c.benchmark_group("tip_reads");
+    group.noise_threshold(0.05);
+
+    group.bench_function("mutex/idle", |b| {
+        b.iter(|| black_box(chain.lock().expect("chain lock poisoned").height()))
+    });
+    group.bench_function("snapshot/idle", |b| {
+        b.iter(|| black_box(view.current().height))
+    });
+
+    {
+        let _writer = Writer::start(chain.clone(), view.clone());
+        group.bench_function("mutex/contended", |b| {
+            b.iter(|| black_box(chain.lock().expect("chain lock poisoned").height()))
+        });
+        group.bench_function("snapshot/contended", |b| {
+            b.iter(|| black_box(view.current().height))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_tip_reads);
+criterion_main!(benches);
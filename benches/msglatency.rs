@@ -0,0 +1,163 @@
+//! Criterion benchmarks for peer message-handling latency under
+//! blocking database work.
+//!
+//! `handle_network_message` is synchronous and can hit RocksDB, so
+//! `process_queue` runs it on the blocking pool instead of the async
+//! workers. This benchmark measures the handler latencies that move
+//! was judged on: one call for a chain-free message (Ping) and one
+//! that reads the database (an Inv announcing an unknown block), first
+//! on an idle node and then while a background job holds the chain
+//! lock for compaction-sized stretches. The contended Inv numbers are
+//! the stalls that used to land on the async workers; Ping should
+//! barely move.
+//!
+//! Before/after comparisons work the same way as the other benches:
+//! save a baseline on the commit under test, then rerun against it.
+//!
+//! ```text
+//! cargo bench --bench msglatency -- --save-baseline before
+//! cargo bench --bench msglatency -- --baseline before
+//! ```
+
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use pali_coin::blockchain::{Blockchain, GenesisConfig};
+use pali_coin::mempool::Mempool;
+use pali_coin::network::NetworkMessage;
+use pali_coin::node::{Node, PeerInfo};
+use pali_coin::{math, MAINNET_CHAIN_ID};
+use tokio::sync::mpsc;
+
+/// How long the simulated compaction holds the chain lock per stretch.
+/// Real compactions and scrub chunks hold it for longer; the figure
+/// keeps the benchmark's worst-case iteration bounded.
+const JOB_HOLD: Duration = Duration::from_millis(5);
+
+/// Off-lock gap between stretches, so handlers get a window.
+const JOB_GAP: Duration = Duration::from_millis(1);
+
+fn open_node() -> Node {
+    let dir = std::env::temp_dir().join(format!("pali-msglatency-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "msglatency bench".to_string(),
+        bits: math::MAX_BITS,
+        premine: Vec::new(),
+    };
+    let chain = Blockchain::init_chain(&dir, &config).expect("open bench chain");
+    Node::new(
+        Arc::new(Mutex::new(chain)),
+        Arc::new(Mutex::new(Mempool::new())),
+        MAINNET_CHAIN_ID,
+    )
+}
+
+/// Registers a peer whose replies are drained by a detached thread, so
+/// Pong and GetData answers never pile up in the channel.
+fn drained_peer(node: &Node, addr: SocketAddr) {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    node.peers.lock().expect("peers lock poisoned").insert(
+        addr,
+        PeerInfo {
+            addr,
+            inbound: true,
+            version: pali_coin::network::PROTOCOL_VERSION,
+            user_agent: String::new(),
+            best_height: 0,
+            connected_at: 0,
+            last_seen: 0,
+            ping_time: None,
+            pending_ping: None,
+            sender: tx,
+        },
+    );
+    std::thread::spawn(move || while rx.blocking_recv().is_some() {});
+}
+
+fn spin(duration: Duration) {
+    let start = Instant::now();
+    while start.elapsed() < duration {
+        std::hint::spin_loop();
+    }
+}
+
+/// A background thread holding the chain lock in compaction-sized
+/// stretches, stopped and joined when dropped.
+struct DbJob {
+    stop: Arc<AtomicBool>,
+    handle: Option<std::thread::JoinHandle<()>>,
+}
+
+impl DbJob {
+    fn start(chain: Arc<Mutex<Blockchain>>) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stopped = stop.clone();
+        let handle = std::thread::spawn(move || {
+            while !stopped.load(Ordering::Relaxed) {
+                {
+                    let _chain = chain.lock().expect("chain lock poisoned");
+                    spin(JOB_HOLD);
+                }
+                spin(JOB_GAP);
+            }
+        });
+        DbJob {
+            stop,
+            handle: Some(handle),
+        }
+    }
+}
+
+impl Drop for DbJob {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn bench_message_handling(c: &mut Criterion) {
+    let node = open_node();
+    let addr: SocketAddr = "192.0.2.99:7777".parse().expect("bench peer addr");
+    drained_peer(&node, addr);
+
+    let ping = || NetworkMessage::Ping(7);
+    // An unknown hash forces a database miss and a GetData reply: the
+    // cheapest handler that still has to wait on the chain lock.
+    let inv = || NetworkMessage::Inv {
+        blocks: vec![[0xAB; 32]],
+        txs: Vec::new(),
+    };
+
+    let mut group = c.benchmark_group("message_handling");
+    group.noise_threshold(0.05);
+
+    group.bench_function("ping/idle", |b| {
+        b.iter(|| node.handle_network_message(addr, black_box(ping())).expect("handler"))
+    });
+    group.bench_function("inv/idle", |b| {
+        b.iter(|| node.handle_network_message(addr, black_box(inv())).expect("handler"))
+    });
+
+    {
+        let _job = DbJob::start(node.chain.clone());
+        group.bench_function("ping/contended", |b| {
+            b.iter(|| node.handle_network_message(addr, black_box(ping())).expect("handler"))
+        });
+        group.bench_function("inv/contended", |b| {
+            b.iter(|| node.handle_network_message(addr, black_box(inv())).expect("handler"))
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_message_handling);
+criterion_main!(benches);
@@ -0,0 +1,176 @@
+//! Known-peer address management with eclipse resistance.
+//!
+//! Two defenses from the eclipse-attack literature, sized for a small
+//! network. First, gossiped addresses are bucketed by the *pair* of
+//! network groups (the advertised address's and the advertising
+//! peer's), with each bucket capped, so one peer — or one /16 — can
+//! only ever own a bounded slice of the table no matter how many addrs
+//! it floods. Second, the last few outbound peers that served us
+//! honestly are persisted as "anchors" and reconnected first after a
+//! restart, so an attacker who fills the table while we are down still
+//! has to displace live connections rather than inherit empty slots.
+
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+
+use rand::seq::SliceRandom;
+
+/// Buckets in the new-address table.
+pub const ADDR_BUCKETS: usize = 64;
+
+/// Addresses one bucket holds; the oldest entry is displaced beyond
+/// this.
+pub const ADDR_BUCKET_SIZE: usize = 16;
+
+/// Outbound peers persisted for reconnection after a restart.
+pub const ANCHOR_COUNT: usize = 2;
+
+/// The network group an address belongs to: /16 for IPv4, /32 for
+/// IPv6. Sybils within one hosting range share a group.
+pub fn net_group(ip: IpAddr) -> u64 {
+    match ip {
+        IpAddr::V4(v4) => {
+            let octets = v4.octets();
+            u64::from_be_bytes([0, 0, 0, 0, 0, 0, octets[0], octets[1]])
+        }
+        IpAddr::V6(v6) => {
+            let octets = v6.octets();
+            u64::from_be_bytes([
+                1, 0, octets[0], octets[1], octets[2], octets[3], 0, 0,
+            ])
+        }
+    }
+}
+
+/// One gossiped address with the peer that told us about it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct AddrEntry {
+    addr: SocketAddr,
+    source: SocketAddr,
+    /// Insertion order within the table, for oldest-first displacement.
+    seq: u64,
+}
+
+/// Bucketed table of gossiped peer addresses plus the anchor file.
+pub struct AddrManager {
+    buckets: Vec<Vec<AddrEntry>>,
+    next_seq: u64,
+    /// Most recently confirmed-good outbound peers, newest last.
+    anchors: Vec<SocketAddr>,
+    /// When set, anchor changes are persisted here immediately.
+    anchor_path: Option<PathBuf>,
+}
+
+impl Default for AddrManager {
+    fn default() -> Self {
+        AddrManager {
+            buckets: vec![Vec::new(); ADDR_BUCKETS],
+            next_seq: 0,
+            anchors: Vec::new(),
+            anchor_path: None,
+        }
+    }
+}
+
+impl AddrManager {
+    pub fn new() -> Self {
+        AddrManager::default()
+    }
+
+    /// Deterministic bucket for an (address, source) pair. Keyed by
+    /// both net groups so neither the flooded addresses nor the
+    /// flooding peer can spread beyond a few buckets.
+    fn bucket_index(addr: &SocketAddr, source: &SocketAddr) -> usize {
+        let mut input = Vec::with_capacity(16);
+        input.extend_from_slice(&net_group(addr.ip()).to_be_bytes());
+        input.extend_from_slice(&net_group(source.ip()).to_be_bytes());
+        let digest = crate::hash::sha256(&input);
+        let raw = u64::from_be_bytes(digest[..8].try_into().expect("digest is 32 bytes"));
+        (raw % ADDR_BUCKETS as u64) as usize
+    }
+
+    /// Records a gossiped address. Re-announcements refresh the entry;
+    /// a full bucket displaces its oldest member.
+    pub fn insert(&mut self, addr: SocketAddr, source: SocketAddr) {
+        let bucket = &mut self.buckets[Self::bucket_index(&addr, &source)];
+        bucket.retain(|entry| entry.addr != addr);
+        let entry = AddrEntry {
+            addr,
+            source,
+            seq: self.next_seq,
+        };
+        self.next_seq += 1;
+        bucket.push(entry);
+        if bucket.len() > ADDR_BUCKET_SIZE {
+            if let Some(oldest) = bucket
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, e)| e.seq)
+                .map(|(i, _)| i)
+            {
+                bucket.remove(oldest);
+            }
+        }
+    }
+
+    /// Total addresses across all buckets.
+    pub fn len(&self) -> usize {
+        self.buckets.iter().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buckets.iter().all(Vec::is_empty)
+    }
+
+    /// Up to `count` addresses sampled across the whole table, so a
+    /// caller never sees only one flooder's view.
+    pub fn sample(&self, count: usize) -> Vec<SocketAddr> {
+        let mut all: Vec<SocketAddr> = self
+            .buckets
+            .iter()
+            .flatten()
+            .map(|entry| entry.addr)
+            .collect();
+        all.shuffle(&mut rand::thread_rng());
+        all.truncate(count);
+        all
+    }
+
+    /// Persist anchors under `path` from now on.
+    pub fn set_anchor_path<P: AsRef<Path>>(&mut self, path: P) {
+        self.anchor_path = Some(path.as_ref().to_path_buf());
+    }
+
+    /// Marks an outbound peer as confirmed good (handshake completed),
+    /// rotating it to the front of the anchor set.
+    pub fn record_good(&mut self, addr: SocketAddr) {
+        self.anchors.retain(|a| *a != addr);
+        self.anchors.push(addr);
+        if self.anchors.len() > ANCHOR_COUNT {
+            self.anchors.remove(0);
+        }
+        if let Some(path) = &self.anchor_path {
+            let lines: Vec<String> = self.anchors.iter().map(|a| a.to_string()).collect();
+            if let Err(e) = std::fs::write(path, format!("{}\n", lines.join("\n"))) {
+                log::warn!("failed to write anchor file {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    pub fn anchors(&self) -> &[SocketAddr] {
+        &self.anchors
+    }
+
+    /// Anchors persisted by the previous session, newest last. A
+    /// missing or unreadable file just means no anchors.
+    pub fn load_anchors<P: AsRef<Path>>(path: P) -> Vec<SocketAddr> {
+        let Ok(contents) = std::fs::read_to_string(path) else {
+            return Vec::new();
+        };
+        contents
+            .lines()
+            .filter_map(|line| line.trim().parse().ok())
+            .take(ANCHOR_COUNT)
+            .collect()
+    }
+}
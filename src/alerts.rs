@@ -0,0 +1,175 @@
+//! Signed network alerts.
+//!
+//! Developers holding the alert keys published in the chain parameters
+//! can broadcast a short message — a critical upgrade notice, a
+//! vulnerability disclosure — that every node relays, logs and reports
+//! through `getinfo` until it expires. A single leaked key must not be
+//! able to speak for the project, so acceptance requires signatures
+//! from a threshold of distinct configured keys; nodes with no alert
+//! keys configured ignore the mechanism entirely.
+
+use std::collections::HashMap;
+
+use secp256k1::{ecdsa::Signature, Message, PublicKey, Secp256k1, SecretKey};
+use serde::{Deserialize, Serialize};
+
+use crate::consensus::ChainParams;
+use crate::hash;
+use crate::types::Hash256;
+
+/// Domain tag alert signatures commit to; keeps them disjoint from
+/// transaction signatures under the same keys.
+pub const ALERT_SIGNING_DOMAIN_TAG: &[u8] = b"pali-coin/alert-signature/v1";
+
+/// The message itself: what gets signed and displayed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Alert {
+    /// Unique id; nodes relay each id once.
+    pub id: u64,
+    /// Unix time after which the alert is neither shown nor relayed.
+    pub expires: u64,
+    /// Human-readable notice, logged and returned by `getinfo`.
+    pub message: String,
+}
+
+/// An alert plus the developer signatures vouching for it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedAlert {
+    pub alert: Alert,
+    /// Compact ECDSA signatures, one per cooperating alert key.
+    pub signatures: Vec<Vec<u8>>,
+}
+
+/// Canonical signing payload: every field in declaration order,
+/// integers big-endian, the message length-prefixed — same discipline
+/// as the transaction payload in the crypto module.
+fn signing_payload(alert: &Alert) -> Vec<u8> {
+    let mut out = Vec::with_capacity(24 + alert.message.len());
+    out.extend_from_slice(&alert.id.to_be_bytes());
+    out.extend_from_slice(&alert.expires.to_be_bytes());
+    out.extend_from_slice(&(alert.message.len() as u64).to_be_bytes());
+    out.extend_from_slice(alert.message.as_bytes());
+    out
+}
+
+/// The digest alert signatures commit to (tagged like transaction
+/// signing hashes: `SHA256(tag_hash ‖ tag_hash ‖ payload)`).
+pub fn signing_hash(alert: &Alert) -> Hash256 {
+    let tag_hash = hash::sha256(ALERT_SIGNING_DOMAIN_TAG);
+    let payload = signing_payload(alert);
+    let mut input = Vec::with_capacity(64 + payload.len());
+    input.extend_from_slice(&tag_hash);
+    input.extend_from_slice(&tag_hash);
+    input.extend_from_slice(&payload);
+    hash::sha256(&input)
+}
+
+impl SignedAlert {
+    pub fn new(alert: Alert) -> Self {
+        SignedAlert {
+            alert,
+            signatures: Vec::new(),
+        }
+    }
+
+    /// Appends one developer's signature over the alert.
+    pub fn sign(&mut self, key: &SecretKey) {
+        let secp = Secp256k1::signing_only();
+        let msg = Message::from_digest(signing_hash(&self.alert));
+        let sig = secp.sign_ecdsa(&msg, key);
+        self.signatures.push(sig.serialize_compact().to_vec());
+    }
+
+    /// Checks the alert against the chain parameters: enough distinct
+    /// configured keys must have signed it. A threshold of zero means
+    /// every configured key, so a chain that never set one gets the
+    /// strictest reading rather than the loosest.
+    pub fn verify(&self, params: &ChainParams) -> Result<(), String> {
+        if params.alert_keys.is_empty() {
+            return Err("no alert keys configured".to_string());
+        }
+        let keys: Vec<PublicKey> = params
+            .alert_keys
+            .iter()
+            .map(|hex| {
+                hex::decode(hex)
+                    .map_err(|e| format!("bad alert key '{}': {}", hex, e))
+                    .and_then(|bytes| {
+                        PublicKey::from_slice(&bytes)
+                            .map_err(|e| format!("bad alert key '{}': {}", hex, e))
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+        let threshold = match params.alert_threshold {
+            0 => keys.len(),
+            t => (t as usize).min(keys.len()),
+        };
+
+        let secp = Secp256k1::verification_only();
+        let msg = Message::from_digest(signing_hash(&self.alert));
+        let mut signed = vec![false; keys.len()];
+        for bytes in &self.signatures {
+            let Ok(sig) = Signature::from_compact(bytes) else {
+                continue;
+            };
+            for (i, key) in keys.iter().enumerate() {
+                if !signed[i] && secp.verify_ecdsa(&msg, &sig, key).is_ok() {
+                    signed[i] = true;
+                    break;
+                }
+            }
+        }
+        let count = signed.iter().filter(|s| **s).count();
+        if count < threshold {
+            return Err(format!(
+                "alert carries {} of {} required signatures",
+                count, threshold
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Alerts a node has accepted and is displaying, keyed by id.
+#[derive(Default)]
+pub struct AlertStore {
+    alerts: HashMap<u64, SignedAlert>,
+}
+
+impl AlertStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Admits a relayed alert. `Ok(true)` means it is new and should be
+    /// relayed on; `Ok(false)` means we already hold it.
+    pub fn accept(
+        &mut self,
+        signed: SignedAlert,
+        params: &ChainParams,
+        now: u64,
+    ) -> Result<bool, String> {
+        signed.verify(params)?;
+        if signed.alert.expires <= now {
+            return Err("alert has expired".to_string());
+        }
+        self.alerts.retain(|_, held| held.alert.expires > now);
+        if self.alerts.contains_key(&signed.alert.id) {
+            return Ok(false);
+        }
+        self.alerts.insert(signed.alert.id, signed);
+        Ok(true)
+    }
+
+    /// Unexpired alerts, oldest id first.
+    pub fn active(&self, now: u64) -> Vec<&Alert> {
+        let mut alerts: Vec<&Alert> = self
+            .alerts
+            .values()
+            .map(|signed| &signed.alert)
+            .filter(|alert| alert.expires > now)
+            .collect();
+        alerts.sort_by_key(|alert| alert.id);
+        alerts
+    }
+}
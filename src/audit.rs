@@ -0,0 +1,131 @@
+//! Account-level extended public keys for auditors.
+//!
+//! An export carries the wallet's public key and a chain code — never
+//! a private key — from which anyone can derive the wallet's receive
+//! addresses by index and track incoming funds read-only. Derivation
+//! is non-hardened and tag-separated: each child key is the account
+//! key tweaked by a tagged digest over the chain code, the public key
+//! and the index, so the holder of the export and the holder of the
+//! private key arrive at the same addresses, and a digest from any
+//! other protocol can never collide with one. The chain code is itself
+//! derived from the private key, so re-exports are identical and the
+//! export needs no extra state in the wallet file.
+
+use secp256k1::{PublicKey, Scalar, Secp256k1, SecretKey};
+use serde::{Deserialize, Serialize};
+
+use crate::hash;
+use crate::types::{Address, Hash256};
+
+/// Domain tag every derivation digest commits to.
+pub const XPUB_DOMAIN_TAG: &[u8] = b"pali-coin/account-xpub/v1";
+
+/// Bump when the export layout changes incompatibly.
+pub const XPUB_VERSION: u32 = 1;
+
+/// Most addresses one import derives and watches; auditors tracking
+/// deeper gaps import again with a higher count.
+pub const MAX_XPUB_IMPORT: u64 = 1_000;
+
+/// The exported account key: everything needed to derive receive
+/// addresses, nothing that can spend.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountXpub {
+    pub version: u32,
+    /// SEC-encoded account public key (33 bytes).
+    pub public_key: Vec<u8>,
+    /// Public derivation entropy; reveals addresses, never keys.
+    pub chain_code: Hash256,
+}
+
+impl AccountXpub {
+    /// Short identifier auditors file the export under: the first four
+    /// bytes of the public key's hash, hex-encoded.
+    pub fn fingerprint(&self) -> String {
+        hex::encode(&hash::sha256(&self.public_key)[..4])
+    }
+}
+
+/// `SHA256(tag_hash ‖ tag_hash ‖ payload)` with the xpub domain tag,
+/// matching the tagged-hash construction transaction signing uses.
+fn tagged(payload: &[u8]) -> Hash256 {
+    let tag_hash = hash::sha256(XPUB_DOMAIN_TAG);
+    let mut input = Vec::with_capacity(64 + payload.len());
+    input.extend_from_slice(&tag_hash);
+    input.extend_from_slice(&tag_hash);
+    input.extend_from_slice(payload);
+    hash::sha256(&input)
+}
+
+/// The chain code belonging to `secret`: deterministic, so every
+/// export of the same wallet is identical, and one-way, so the export
+/// reveals nothing about the key.
+pub fn chain_code_for(secret: &SecretKey) -> Hash256 {
+    let mut payload = Vec::with_capacity(43);
+    payload.extend_from_slice(b"chain-code");
+    payload.extend_from_slice(&secret.secret_bytes());
+    tagged(&payload)
+}
+
+/// The scalar child `index` is tweaked by, computable from public data
+/// alone.
+fn child_tweak(xpub: &AccountXpub, index: u32) -> Result<Scalar, String> {
+    let mut payload = Vec::with_capacity(42 + xpub.public_key.len());
+    payload.extend_from_slice(b"child");
+    payload.extend_from_slice(&xpub.chain_code);
+    payload.extend_from_slice(&xpub.public_key);
+    payload.extend_from_slice(&index.to_be_bytes());
+    Scalar::from_be_bytes(tagged(&payload))
+        .map_err(|_| format!("child {} is unusable; skip to the next index", index))
+}
+
+/// Public key of receive slot `index`, derived without any secret.
+pub fn derive_child_pubkey(xpub: &AccountXpub, index: u32) -> Result<PublicKey, String> {
+    let secp = Secp256k1::verification_only();
+    let account = PublicKey::from_slice(&xpub.public_key)
+        .map_err(|e| format!("malformed account public key: {}", e))?;
+    account
+        .add_exp_tweak(&secp, &child_tweak(xpub, index)?)
+        .map_err(|e| format!("child derivation failed: {}", e))
+}
+
+/// Receive address of slot `index`.
+pub fn derive_receive_address(xpub: &AccountXpub, index: u32) -> Result<Address, String> {
+    Ok(hash::pubkey_to_address(
+        &derive_child_pubkey(xpub, index)?.serialize(),
+    ))
+}
+
+/// The signing key for receive slot `index` — the wallet-side half of
+/// the derivation, proving funds sent to derived addresses stay
+/// spendable by the key holder.
+pub fn derive_child_secret(
+    secret: &SecretKey,
+    xpub: &AccountXpub,
+    index: u32,
+) -> Result<SecretKey, String> {
+    secret
+        .add_tweak(&child_tweak(xpub, index)?)
+        .map_err(|e| format!("child derivation failed: {}", e))
+}
+
+/// Serializes an export the way proofs travel: bincode, hex-encoded.
+pub fn encode(xpub: &AccountXpub) -> String {
+    hex::encode(bincode::serialize(xpub).expect("xpub serialization cannot fail"))
+}
+
+/// Inverse of [`encode`], refusing exports this build cannot interpret.
+pub fn decode(s: &str) -> Result<AccountXpub, String> {
+    let bytes = hex::decode(s).map_err(|e| format!("bad hex: {}", e))?;
+    let xpub: AccountXpub =
+        bincode::deserialize(&bytes).map_err(|e| format!("malformed export: {}", e))?;
+    if xpub.version > XPUB_VERSION {
+        return Err(format!(
+            "export version {} is newer than this build supports ({})",
+            xpub.version, XPUB_VERSION
+        ));
+    }
+    PublicKey::from_slice(&xpub.public_key)
+        .map_err(|e| format!("malformed account public key: {}", e))?;
+    Ok(xpub)
+}
@@ -0,0 +1,809 @@
+//! Palicoin full node entry point.
+
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use clap::{Parser, Subcommand};
+
+use pali_coin::backup;
+use pali_coin::blockchain::{self, Blockchain};
+use pali_coin::consensus::ChainParams;
+use pali_coin::deposits::DepositTracker;
+#[cfg(feature = "faucet")]
+use pali_coin::faucet;
+#[cfg(feature = "libp2p")]
+use pali_coin::libp2p_transport;
+use pali_coin::mempool::{self, Mempool};
+use pali_coin::node::Node;
+use pali_coin::preflight;
+use pali_coin::rpc::{self, RpcContext};
+use pali_coin::notify;
+use pali_coin::rpc_auth::AuthConfig;
+use pali_coin::sim;
+use pali_coin::MAINNET_CHAIN_ID;
+
+#[derive(Parser)]
+#[command(name = "pali-node", about = "Palicoin full node")]
+struct Args {
+    /// Data directory for the chain database.
+    #[arg(long, default_value = "palicoin-data")]
+    datadir: PathBuf,
+    /// Chain id to run (1 = mainnet, 2 = testnet, 3 = regtest).
+    #[arg(long, default_value_t = MAINNET_CHAIN_ID)]
+    chain_id: u8,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+// Clap builds this once at startup; the variant size spread is fine.
+#[allow(clippy::large_enum_variant)]
+enum Command {
+    /// Run the node (default).
+    Run {
+        /// RPC listen address.
+        #[arg(long, default_value = "127.0.0.1:8536")]
+        rpc_bind: std::net::SocketAddr,
+        /// P2P listen address.
+        #[arg(long, default_value = "0.0.0.0:8535")]
+        p2p_bind: std::net::SocketAddr,
+        /// Peers to connect to at startup.
+        #[arg(long = "connect")]
+        connect: Vec<std::net::SocketAddr>,
+        /// Hours between background database compactions (0 disables).
+        #[arg(long, default_value_t = 24)]
+        compact_interval_hours: u64,
+        /// Cold storage directory for old block bodies (for example a
+        /// slower disk or network mount); unset keeps everything hot.
+        #[arg(long)]
+        colddir: Option<PathBuf>,
+        /// Move blocks older than this many days to the cold directory.
+        #[arg(long, default_value_t = 30)]
+        cold_after_days: u64,
+        /// Directory receiving rolling verified backups (ideally a
+        /// different disk); unset disables the scheduled backup task.
+        #[arg(long)]
+        backupdir: Option<PathBuf>,
+        /// Seconds between scheduled backups.
+        #[arg(long, default_value_t = backup::DEFAULT_BACKUP_INTERVAL_SECS)]
+        backup_interval_secs: u64,
+        /// Minimum relay fee rate in units per byte.
+        #[arg(long, default_value_t = mempool::MIN_RELAY_FEE_RATE)]
+        min_relay_fee_rate: f64,
+        /// Largest relayed transaction in serialized bytes.
+        #[arg(long, default_value_t = mempool::MAX_STANDARD_TX_BYTES)]
+        max_tx_bytes: usize,
+        /// Longest pooled nonce chain per sender.
+        #[arg(long, default_value_t = mempool::MAX_ANCESTOR_CHAIN)]
+        max_ancestors: usize,
+        /// Smallest relayed transfer amount in base units.
+        #[arg(long, default_value_t = mempool::DUST_THRESHOLD)]
+        dust_threshold: u64,
+        /// Most relayed data-carrier payload bytes.
+        #[arg(long, default_value_t = mempool::MAX_DATA_CARRIER_BYTES)]
+        max_data_bytes: usize,
+        /// Hex-encoded compressed public key authorized to sign
+        /// network alerts; repeatable, empty disables alerts.
+        #[arg(long = "alert-key")]
+        alert_keys: Vec<String>,
+        /// Distinct alert-key signatures an alert needs; 0 means all
+        /// configured keys must sign.
+        #[arg(long, default_value_t = 0)]
+        alert_threshold: u32,
+        /// Accumulated chain work the best chain must reach before this
+        /// node stops reporting itself as syncing and serves mining
+        /// work; 0 disables the gate.
+        #[arg(long, default_value_t = 0)]
+        minimum_chain_work: u128,
+        /// Chain parameter override file (chainparams.json) for
+        /// private consortium networks. Replaces the compiled-in
+        /// consensus values, the individual consensus flags and — when
+        /// it carries a chain id — the --chain-id flag.
+        #[arg(long)]
+        chainparams: Option<PathBuf>,
+        /// RPC basic-auth user name (requires --rpc-password).
+        #[arg(long)]
+        rpc_user: Option<String>,
+        /// RPC basic-auth password.
+        #[arg(long)]
+        rpc_password: Option<String>,
+        /// Bearer token as scope:token (read, wallet or admin); repeatable.
+        #[arg(long = "rpc-token")]
+        rpc_tokens: Vec<String>,
+        /// Source IPs allowed to use the RPC interface; repeatable,
+        /// empty means all.
+        #[arg(long = "rpc-allow-ip")]
+        rpc_allow_ips: Vec<std::net::IpAddr>,
+        /// Shell command run when a wallet-relevant transaction is
+        /// seen, confirmed or reorged out; %s becomes the txid.
+        #[arg(long)]
+        walletnotify: Option<String>,
+        /// Shell command run on every new best block; %s becomes the
+        /// block hash.
+        #[arg(long)]
+        blocknotify: Option<String>,
+        /// Shell command run on node alerts (safe mode, consensus
+        /// anomalies); %s becomes the alert message.
+        #[arg(long)]
+        alertnotify: Option<String>,
+        /// URL notification events are POSTed to as JSON.
+        #[arg(long)]
+        webhook_url: Option<String>,
+        /// URL of a signed version manifest to poll daily; unset
+        /// disables the update check entirely.
+        #[arg(long)]
+        updatecheck_url: Option<String>,
+        /// Multiaddr the experimental libp2p transport listens on
+        /// (for example /ip4/0.0.0.0/tcp/8538); unset disables it.
+        #[cfg(feature = "libp2p")]
+        #[arg(long)]
+        libp2p_listen: Option<String>,
+        /// libp2p bootstrap peer multiaddrs; repeatable.
+        #[cfg(feature = "libp2p")]
+        #[arg(long = "libp2p-bootstrap")]
+        libp2p_bootstrap: Vec<String>,
+        /// Address the testnet faucet listens on; unset disables it.
+        #[cfg(feature = "faucet")]
+        #[arg(long)]
+        faucet_bind: Option<std::net::SocketAddr>,
+        /// Wallet file the faucet pays from (prompts for its password).
+        #[cfg(feature = "faucet")]
+        #[arg(long)]
+        faucet_wallet: Option<PathBuf>,
+        /// Faucet payout per claim, in base units.
+        #[cfg(feature = "faucet")]
+        #[arg(long)]
+        faucet_payout: Option<u64>,
+        /// Seconds an IP or address waits between faucet claims.
+        #[cfg(feature = "faucet")]
+        #[arg(long)]
+        faucet_cooldown_secs: Option<u64>,
+        /// Access token faucet claims must present.
+        #[cfg(feature = "faucet")]
+        #[arg(long)]
+        faucet_token: Option<String>,
+    },
+    /// Write a full database backup to a compressed archive.
+    Backup {
+        /// Output archive path (.tar.gz).
+        output: PathBuf,
+    },
+    /// Restore a backup archive into an empty data directory.
+    Restore {
+        /// Archive produced by `pali-node backup`.
+        archive: PathBuf,
+    },
+    /// Create a custom genesis block for a private or test network.
+    InitChain {
+        /// Genesis timestamp as a unix time (defaults to now).
+        #[arg(long)]
+        timestamp: Option<u64>,
+        /// Message embedded in the genesis coinbase.
+        #[arg(long, default_value = "Palicoin private network")]
+        message: String,
+        /// Initial difficulty in compact bits form.
+        #[arg(long, default_value_t = pali_coin::math::MAX_BITS)]
+        bits: u32,
+        /// Premine allocation as hexaddress:amount; repeatable.
+        #[arg(long = "premine")]
+        premine: Vec<String>,
+    },
+    /// Fast-forward a synthetic chain to validate the emission model.
+    Sim {
+        /// Blocks to simulate.
+        #[arg(long, default_value_t = 10_000)]
+        blocks: u64,
+        /// Synthetic transactions per block.
+        #[arg(long, default_value_t = 50)]
+        txs_per_block: u64,
+        /// Fee per synthetic transaction in base units.
+        #[arg(long, default_value_t = 10_000)]
+        fee_per_tx: u64,
+        /// Starting network hashrate in hashes per second.
+        #[arg(long, default_value_t = 1_000_000.0)]
+        hashrate: f64,
+        /// Hashrate multiplier applied at every retarget window.
+        #[arg(long, default_value_t = 1.0)]
+        hashrate_growth: f64,
+        /// Blocks between halvings (defaults to the consensus value).
+        #[arg(long)]
+        halving_interval: Option<u64>,
+        /// Sample interval in blocks.
+        #[arg(long, default_value_t = 1_000)]
+        sample_every: u64,
+        /// Emit the full report as JSON instead of a table.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Live terminal dashboard for a running node.
+    Monitor {
+        /// RPC endpoint of the node to watch.
+        #[arg(long, default_value = "http://127.0.0.1:8536/")]
+        rpc_url: String,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    pali_coin::logbuffer::init();
+    let args = Args::parse();
+
+    match args.command.unwrap_or(Command::Run {
+        rpc_bind: "127.0.0.1:8536".parse().expect("valid default address"),
+        p2p_bind: "0.0.0.0:8535".parse().expect("valid default address"),
+        connect: Vec::new(),
+        compact_interval_hours: 24,
+        colddir: None,
+        cold_after_days: 30,
+        backupdir: None,
+        backup_interval_secs: backup::DEFAULT_BACKUP_INTERVAL_SECS,
+        min_relay_fee_rate: mempool::MIN_RELAY_FEE_RATE,
+        max_tx_bytes: mempool::MAX_STANDARD_TX_BYTES,
+        max_ancestors: mempool::MAX_ANCESTOR_CHAIN,
+        dust_threshold: mempool::DUST_THRESHOLD,
+        max_data_bytes: mempool::MAX_DATA_CARRIER_BYTES,
+        alert_keys: Vec::new(),
+        alert_threshold: 0,
+        minimum_chain_work: 0,
+        chainparams: None,
+        rpc_user: None,
+        rpc_password: None,
+        rpc_tokens: Vec::new(),
+        rpc_allow_ips: Vec::new(),
+        walletnotify: None,
+        blocknotify: None,
+        alertnotify: None,
+        webhook_url: None,
+        updatecheck_url: None,
+        #[cfg(feature = "libp2p")]
+        libp2p_listen: None,
+        #[cfg(feature = "libp2p")]
+        libp2p_bootstrap: Vec::new(),
+        #[cfg(feature = "faucet")]
+        faucet_bind: None,
+        #[cfg(feature = "faucet")]
+        faucet_wallet: None,
+        #[cfg(feature = "faucet")]
+        faucet_payout: None,
+        #[cfg(feature = "faucet")]
+        faucet_cooldown_secs: None,
+        #[cfg(feature = "faucet")]
+        faucet_token: None,
+    }) {
+        Command::Run {
+            rpc_bind,
+            p2p_bind,
+            connect,
+            compact_interval_hours,
+            colddir,
+            cold_after_days,
+            backupdir,
+            backup_interval_secs,
+            min_relay_fee_rate,
+            max_tx_bytes,
+            max_ancestors,
+            dust_threshold,
+            max_data_bytes,
+            alert_keys,
+            alert_threshold,
+            minimum_chain_work,
+            chainparams,
+            rpc_user,
+            rpc_password,
+            rpc_tokens,
+            rpc_allow_ips,
+            walletnotify,
+            blocknotify,
+            alertnotify,
+            webhook_url,
+            updatecheck_url,
+            #[cfg(feature = "libp2p")]
+            libp2p_listen,
+            #[cfg(feature = "libp2p")]
+            libp2p_bootstrap,
+            #[cfg(feature = "faucet")]
+            faucet_bind,
+            #[cfg(feature = "faucet")]
+            faucet_wallet,
+            #[cfg(feature = "faucet")]
+            faucet_payout,
+            #[cfg(feature = "faucet")]
+            faucet_cooldown_secs,
+            #[cfg(feature = "faucet")]
+            faucet_token,
+        } => {
+            let auth = match AuthConfig::from_args(rpc_user, rpc_password, rpc_tokens, rpc_allow_ips) {
+                Ok(auth) => auth,
+                Err(e) => fail(&e.to_string()),
+            };
+            let file_params = chainparams.map(|path| match ChainParams::from_file(&path) {
+                Ok(params) => params,
+                Err(e) => fail(&e),
+            });
+            let chain_id = file_params
+                .as_ref()
+                .and_then(|params| params.chain_id)
+                .unwrap_or(args.chain_id);
+            run_node(
+                &args.datadir,
+                chain_id,
+                rpc_bind,
+                p2p_bind,
+                connect,
+                compact_interval_hours,
+                colddir,
+                cold_after_days,
+                backupdir,
+                backup_interval_secs,
+                mempool::Policy {
+                    min_relay_fee_rate,
+                    max_tx_bytes,
+                    max_ancestors,
+                    dust_threshold,
+                    max_data_bytes,
+                },
+                alert_keys,
+                alert_threshold,
+                minimum_chain_work,
+                file_params,
+                notify::HookConfig {
+                    walletnotify,
+                    blocknotify,
+                    alertnotify,
+                    webhook_url,
+                },
+                updatecheck_url,
+                auth,
+                #[cfg(feature = "libp2p")]
+                libp2p_listen,
+                #[cfg(feature = "libp2p")]
+                libp2p_bootstrap,
+                #[cfg(feature = "faucet")]
+                faucet_bind,
+                #[cfg(feature = "faucet")]
+                faucet_wallet,
+                #[cfg(feature = "faucet")]
+                faucet_payout,
+                #[cfg(feature = "faucet")]
+                faucet_cooldown_secs,
+                #[cfg(feature = "faucet")]
+                faucet_token,
+            )
+            .await
+        }
+        Command::Backup { output } => {
+            let chain = open_chain(&args.datadir, args.chain_id);
+            match backup::create_backup(&chain, args.chain_id, &output) {
+                Ok(manifest) => println!(
+                    "backup complete: height {} best {}",
+                    manifest.height, manifest.best_hash
+                ),
+                Err(e) => fail(&e.to_string()),
+            }
+        }
+        Command::Restore { archive } => match backup::restore_backup(&archive, &args.datadir) {
+            Ok(manifest) => println!(
+                "restore verified: height {} best {}",
+                manifest.height, manifest.best_hash
+            ),
+            Err(e) => fail(&e.to_string()),
+        },
+        Command::InitChain {
+            timestamp,
+            message,
+            bits,
+            premine,
+        } => {
+            let mut allocations = Vec::with_capacity(premine.len());
+            for entry in premine {
+                let (address, amount) = match entry.split_once(':') {
+                    Some(parts) => parts,
+                    None => fail(&format!(
+                        "bad --premine '{}'; expected hexaddress:amount",
+                        entry
+                    )),
+                };
+                let amount = match amount.parse::<u64>() {
+                    Ok(amount) => amount,
+                    Err(_) => fail(&format!("bad premine amount in '{}'", entry)),
+                };
+                allocations.push(blockchain::PremineAllocation {
+                    address: address.to_string(),
+                    amount,
+                });
+            }
+            let config = blockchain::GenesisConfig {
+                chain_id: args.chain_id,
+                timestamp: timestamp.unwrap_or_else(|| {
+                    std::time::SystemTime::now()
+                        .duration_since(std::time::UNIX_EPOCH)
+                        .expect("clock after epoch")
+                        .as_secs()
+                }),
+                message,
+                bits,
+                premine: allocations,
+            };
+            let chain = match Blockchain::init_chain(&args.datadir, &config) {
+                Ok(chain) => chain,
+                Err(e) => fail(&e.to_string()),
+            };
+            let params_path = args.datadir.join("chainparams.json");
+            if let Err(e) = std::fs::write(
+                &params_path,
+                serde_json::to_string_pretty(&config).expect("config serializes"),
+            ) {
+                fail(&format!("failed to write {}: {}", params_path.display(), e));
+            }
+            println!(
+                "chain initialized: id {} genesis {} premined {}",
+                config.chain_id,
+                hex::encode(chain.best_hash()),
+                chain.state().circulating_supply
+            );
+            println!("params written to {}", params_path.display());
+        }
+        Command::Sim {
+            blocks,
+            txs_per_block,
+            fee_per_tx,
+            hashrate,
+            hashrate_growth,
+            halving_interval,
+            sample_every,
+            json,
+        } => {
+            let mut config = sim::SimConfig {
+                blocks,
+                txs_per_block,
+                fee_per_tx,
+                hashrate,
+                hashrate_growth,
+                sample_every,
+                ..sim::SimConfig::default()
+            };
+            if let Some(interval) = halving_interval {
+                config.halving_interval = interval;
+            }
+            let report = sim::run(&config);
+            if json {
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&report).expect("report serializes")
+                );
+            } else {
+                print!("{}", sim::format_report(&report));
+            }
+        }
+        Command::Monitor { rpc_url } => {
+            if let Err(e) = pali_coin::monitor::run(&rpc_url).await {
+                fail(&e.to_string());
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_node(
+    datadir: &Path,
+    chain_id: u8,
+    rpc_bind: std::net::SocketAddr,
+    p2p_bind: std::net::SocketAddr,
+    connect: Vec<std::net::SocketAddr>,
+    compact_interval_hours: u64,
+    colddir: Option<PathBuf>,
+    cold_after_days: u64,
+    backupdir: Option<PathBuf>,
+    backup_interval_secs: u64,
+    policy: mempool::Policy,
+    alert_keys: Vec<String>,
+    alert_threshold: u32,
+    minimum_chain_work: u128,
+    file_params: Option<ChainParams>,
+    hooks: notify::HookConfig,
+    updatecheck_url: Option<String>,
+    auth: AuthConfig,
+    #[cfg(feature = "libp2p")] libp2p_listen: Option<String>,
+    #[cfg(feature = "libp2p")] libp2p_bootstrap: Vec<String>,
+    #[cfg(feature = "faucet")] faucet_bind: Option<std::net::SocketAddr>,
+    #[cfg(feature = "faucet")] faucet_wallet: Option<PathBuf>,
+    #[cfg(feature = "faucet")] faucet_payout: Option<u64>,
+    #[cfg(feature = "faucet")] faucet_cooldown_secs: Option<u64>,
+    #[cfg(feature = "faucet")] faucet_token: Option<String>,
+) {
+    // Held for the life of the process; dropping it releases the
+    // data-dir lock.
+    let _dir_lock = match preflight::run_checks(datadir) {
+        Ok(lock) => lock,
+        Err(e) => fail(&e.to_string()),
+    };
+    let mut chain = open_chain(datadir, chain_id);
+    if let Some(colddir) = &colddir {
+        if let Err(e) = chain.attach_cold_store(colddir) {
+            fail(&e.to_string());
+        }
+        log::info!("cold block store attached at {}", colddir.display());
+    }
+    log::info!(
+        "chain loaded: height {} best {}",
+        chain.height(),
+        hex::encode(chain.best_hash())
+    );
+
+    let chain = Arc::new(Mutex::new(chain));
+    let mempool = Arc::new(Mutex::new(Mempool::with_policy(policy)));
+    let mut node = Node::new(chain.clone(), mempool.clone(), chain_id);
+    node.params.alert_keys = alert_keys;
+    node.params.alert_threshold = alert_threshold;
+    node.params.minimum_chain_work = minimum_chain_work;
+    if let Some(params) = file_params {
+        log::info!(
+            "chain parameters loaded: pow {:?}, {}s blocks, {} PoA miner keys",
+            params.pow_algorithm,
+            params.target_block_time_secs,
+            params.miner_keys.len()
+        );
+        node.params = params;
+    }
+    {
+        let mut chain = chain.lock().expect("chain lock poisoned");
+        chain.set_pow_algorithm(node.params.pow_algorithm.algorithm());
+        chain.set_chain_params(node.params.clone());
+    }
+    // Deposit registrations must survive restarts: a reorg spanning a
+    // node outage still has to produce its clawback events.
+    node.deposits = Arc::new(Mutex::new(DepositTracker::with_path(
+        datadir.join("deposits.dat"),
+    )));
+    // Pool shares likewise: a settlement ledger that forgets on
+    // restart cheats whoever mined right before the outage.
+    node.pool = Arc::new(Mutex::new(pali_coin::pool::ShareLedger::with_path(
+        datadir.join("shares.dat"),
+    )));
+    // Peer statistics span restarts too; a relay reputation that
+    // resets every boot never accumulates anything worth reading.
+    node.peerstats = Arc::new(Mutex::new(pali_coin::peerstats::PeerStatsBook::with_path(
+        datadir.join("peerstats.dat"),
+    )));
+    if hooks.is_enabled() {
+        let (notifier, rx) = notify::Notifier::new(hooks.clone());
+        node.notifier = Arc::new(notifier);
+        tokio::spawn(notify::Notifier::run(hooks, rx));
+    }
+    #[cfg(feature = "libp2p")]
+    let libp2p = match start_libp2p(libp2p_listen, libp2p_bootstrap, chain_id) {
+        Ok(Some((service, commands, events, relay))) => {
+            node.external_relay = Some(relay);
+            Some((service, commands, events))
+        }
+        Ok(None) => None,
+        Err(e) => fail(&e.to_string()),
+    };
+    let node = Arc::new(node);
+    #[cfg(feature = "libp2p")]
+    if let Some((service, commands, events)) = libp2p {
+        log::info!("libp2p transport running as {}", service.peer_id());
+        tokio::spawn(service.run());
+        tokio::spawn(bridge_libp2p(node.clone(), commands, events));
+    }
+
+    // Reconnect to the previous session's anchor peers first, before
+    // any operator-specified peers, as eclipse protection.
+    let anchor_path = datadir.join("anchors.txt");
+    let anchors = pali_coin::addrman::AddrManager::load_anchors(&anchor_path);
+    node.addrman
+        .lock()
+        .expect("addrman lock poisoned")
+        .set_anchor_path(&anchor_path);
+    for addr in anchors {
+        log::info!("reconnecting to anchor peer {}", addr);
+        let node = node.clone();
+        tokio::spawn(async move {
+            if let Err(e) = node.connect(addr).await {
+                log::warn!("anchor reconnect failed: {}", e);
+            }
+        });
+    }
+
+    tokio::spawn(node.clone().listen(p2p_bind));
+    tokio::spawn(node.clone().ping_loop());
+    tokio::spawn(node.clone().sync_loop());
+    tokio::spawn(node.clone().dandelion_loop());
+    tokio::spawn(node.clone().rebroadcast_loop());
+    tokio::spawn(pali_coin::peerstats::flush_loop(node.clone()));
+    tokio::spawn(pali_coin::reputation::review_loop(node.clone()));
+    if let Some(url) = updatecheck_url {
+        tokio::spawn(pali_coin::updates::check_loop(node.clone(), url));
+    }
+    if let Some(backupdir) = backupdir {
+        log::info!("scheduled backups enabled into {}", backupdir.display());
+        tokio::spawn(backup::backup_loop(
+            node.clone(),
+            Box::new(backup::DirectoryTarget::new(backupdir)),
+            backup_interval_secs,
+            backup::RetentionPolicy::default(),
+        ));
+    }
+    let cold_after = colddir
+        .is_some()
+        .then(|| std::time::Duration::from_secs(cold_after_days * 24 * 3600));
+    tokio::spawn(node.clone().maintenance_loop(
+        std::time::Duration::from_secs(compact_interval_hours * 3600),
+        cold_after,
+    ));
+    for addr in connect {
+        let node = node.clone();
+        tokio::spawn(async move {
+            if let Err(e) = node.connect(addr).await {
+                log::warn!("{}", e);
+            }
+        });
+    }
+
+    if auth.is_open() {
+        log::warn!("RPC authentication is not configured; keep the RPC bind on localhost");
+    }
+    let ctx = RpcContext {
+        chain,
+        mempool,
+        node: Some(node),
+        chain_id,
+        auth: Arc::new(auth),
+    };
+
+    #[cfg(feature = "faucet")]
+    if let Some(bind) = faucet_bind {
+        match start_faucet(&ctx, faucet_wallet, faucet_payout, faucet_cooldown_secs, faucet_token) {
+            Ok(state) => {
+                tokio::spawn(faucet::serve(state, bind));
+            }
+            Err(e) => fail(&e.to_string()),
+        }
+    }
+
+    if let Err(e) = rpc::serve(ctx, rpc_bind).await {
+        fail(&e);
+    }
+}
+
+/// Loads the faucet wallet and assembles the service state.
+#[cfg(feature = "faucet")]
+fn start_faucet(
+    ctx: &RpcContext,
+    wallet_path: Option<PathBuf>,
+    payout: Option<u64>,
+    cooldown_secs: Option<u64>,
+    token: Option<String>,
+) -> Result<Arc<faucet::Faucet>, String> {
+    let wallet_path = wallet_path.ok_or_else(|| "--faucet-bind requires --faucet-wallet".to_string())?;
+    let password = prompt_password("Faucet wallet password: ")?;
+    let wallet = pali_coin::wallet::Wallet::from_file(&wallet_path, &password)?;
+    log::info!("faucet paying from {}", hex::encode(wallet.address()));
+    let mut config = faucet::FaucetConfig {
+        token,
+        ..faucet::FaucetConfig::default()
+    };
+    if let Some(payout) = payout {
+        config.payout = payout;
+    }
+    if let Some(cooldown) = cooldown_secs {
+        config.cooldown_secs = cooldown;
+    }
+    Ok(Arc::new(faucet::Faucet::new(ctx.clone(), wallet, config)))
+}
+
+#[cfg(feature = "faucet")]
+fn prompt_password(prompt: &str) -> Result<String, String> {
+    eprint!("{}", prompt);
+    let mut line = String::new();
+    std::io::stdin()
+        .read_line(&mut line)
+        .map_err(|e| format!("failed to read password: {}", e))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Builds the optional libp2p service plus the relay channel the node
+/// mirrors its broadcasts into.
+#[cfg(feature = "libp2p")]
+#[allow(clippy::type_complexity)]
+fn start_libp2p(
+    listen: Option<String>,
+    bootstrap: Vec<String>,
+    chain_id: u8,
+) -> Result<
+    Option<(
+        libp2p_transport::Libp2pService,
+        tokio::sync::mpsc::UnboundedSender<libp2p_transport::Outbound>,
+        tokio::sync::mpsc::UnboundedReceiver<libp2p_transport::Inbound>,
+        tokio::sync::mpsc::UnboundedSender<pali_coin::network::NetworkMessage>,
+    )>,
+    String,
+> {
+    let Some(listen) = listen else {
+        return Ok(None);
+    };
+    let listen = listen
+        .parse()
+        .map_err(|e| format!("bad --libp2p-listen multiaddr: {}", e))?;
+    let mut peers = Vec::with_capacity(bootstrap.len());
+    for addr in bootstrap {
+        peers.push(
+            addr.parse()
+                .map_err(|e| format!("bad --libp2p-bootstrap multiaddr '{}': {}", addr, e))?,
+        );
+    }
+    let (service, commands, events) = libp2p_transport::Libp2pService::new(
+        libp2p_transport::Libp2pConfig {
+            listen,
+            bootstrap: peers,
+            chain_id,
+        },
+    )?;
+    // Node broadcasts arrive as plain messages; republish them on
+    // their gossip topics.
+    let (relay, mut mirrored) = tokio::sync::mpsc::unbounded_channel();
+    let publisher = commands.clone();
+    tokio::spawn(async move {
+        while let Some(message) = mirrored.recv().await {
+            let _ = publisher.send(libp2p_transport::Outbound::Publish(message));
+        }
+    });
+    Ok(Some((service, commands, events, relay)))
+}
+
+/// Feeds libp2p gossip into the node and answers peer queries.
+#[cfg(feature = "libp2p")]
+async fn bridge_libp2p(
+    node: Arc<Node>,
+    commands: tokio::sync::mpsc::UnboundedSender<libp2p_transport::Outbound>,
+    mut events: tokio::sync::mpsc::UnboundedReceiver<libp2p_transport::Inbound>,
+) {
+    use pali_coin::network::NetworkMessage;
+
+    // Gossip carries no reply address; handlers that answer by socket
+    // address see an unreachable peer, which announcements never need.
+    let synthetic: std::net::SocketAddr = "0.0.0.0:0".parse().expect("valid placeholder");
+    while let Some(event) = events.recv().await {
+        match event {
+            libp2p_transport::Inbound::Gossip { peer, message } => {
+                if let message @ (NetworkMessage::Block(_)
+                | NetworkMessage::Blocks(_)
+                | NetworkMessage::Transaction(_)
+                | NetworkMessage::StemTransaction(_)) = message
+                {
+                    if let Err(e) = node.handle_network_message(synthetic, message) {
+                        log::debug!("libp2p gossip from {} rejected: {}", peer, e);
+                    }
+                }
+            }
+            libp2p_transport::Inbound::Request {
+                message, channel, ..
+            } => {
+                let response = {
+                    let chain = node.chain.lock().expect("chain lock poisoned");
+                    libp2p_transport::answer_query(&chain, &message)
+                };
+                if let Some(message) = response {
+                    let _ = commands.send(libp2p_transport::Outbound::Respond { channel, message });
+                }
+            }
+            // Nothing in the node requests over libp2p yet.
+            libp2p_transport::Inbound::Response { .. } => {}
+        }
+    }
+}
+
+fn open_chain(datadir: &Path, chain_id: u8) -> Blockchain {
+    match Blockchain::open(datadir, chain_id) {
+        Ok(chain) => chain,
+        Err(e) => {
+            eprintln!("failed to open chain database: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{}", message);
+    std::process::exit(1)
+}
@@ -0,0 +1,238 @@
+//! P2P protocol conformance tester.
+//!
+//! Connects to a running node and exercises the message matrix —
+//! handshake edge cases, oversized frames, malformed payloads and
+//! slow-loris sends — asserting the node answers well-formed traffic
+//! and drops abusive connections. Safe to point at any staging
+//! deployment; it never submits blocks or transactions.
+
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use clap::Parser;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpStream;
+
+use pali_coin::network::{self, NetworkMessage, MAX_MESSAGE_SIZE, NETWORK_MAGIC, PROTOCOL_VERSION};
+use pali_coin::MAINNET_CHAIN_ID;
+
+#[derive(Parser)]
+#[command(name = "pali-protocol-tester", about = "Palicoin P2P conformance tester")]
+struct Args {
+    /// P2P address of the node under test.
+    #[arg(long, default_value = "127.0.0.1:8535")]
+    addr: SocketAddr,
+    /// Chain id the node runs.
+    #[arg(long, default_value_t = MAINNET_CHAIN_ID)]
+    chain_id: u8,
+    /// Seconds to wait for the node to drop an abusive connection.
+    #[arg(long, default_value_t = 15)]
+    drop_timeout: u64,
+}
+
+#[tokio::main]
+async fn main() {
+    let args = Args::parse();
+    let drop_timeout = Duration::from_secs(args.drop_timeout);
+
+    let checks: Vec<(&str, Result<(), String>)> = vec![
+        ("handshake-ok", handshake_ok(&args).await),
+        ("wrong-chain-dropped", wrong_chain(&args, drop_timeout).await),
+        (
+            "premature-message-dropped",
+            premature_message(&args, drop_timeout).await,
+        ),
+        (
+            "oversized-frame-dropped",
+            oversized_frame(&args, drop_timeout).await,
+        ),
+        ("bad-magic-dropped", bad_magic(&args, drop_timeout).await),
+        (
+            "bad-checksum-dropped",
+            bad_checksum(&args, drop_timeout).await,
+        ),
+        (
+            "malformed-payload-dropped",
+            malformed_payload(&args, drop_timeout).await,
+        ),
+        ("slow-loris-dropped", slow_loris(&args, drop_timeout).await),
+    ];
+
+    let mut failed = 0;
+    for (name, result) in &checks {
+        match result {
+            Ok(()) => println!("PASS {}", name),
+            Err(e) => {
+                println!("FAIL {}: {}", name, e);
+                failed += 1;
+            }
+        }
+    }
+    println!("{}/{} checks passed", checks.len() - failed, checks.len());
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+/// Full happy-path handshake plus a ping/pong round trip.
+async fn handshake_ok(args: &Args) -> Result<(), String> {
+    let mut stream = connect(args).await?;
+    match network::read_message(&mut stream).await? {
+        NetworkMessage::Version { .. } => {}
+        other => return Err(format!("expected Version, got {:?}", other)),
+    }
+    send_version(&mut stream, args.chain_id).await?;
+    match network::read_message(&mut stream).await? {
+        NetworkMessage::VerAck => {}
+        other => return Err(format!("expected VerAck, got {:?}", other)),
+    }
+    network::write_message(&mut stream, &NetworkMessage::Ping(0x70616c69)).await?;
+    loop {
+        match network::read_message(&mut stream).await? {
+            NetworkMessage::Pong(0x70616c69) => return Ok(()),
+            NetworkMessage::Pong(nonce) => {
+                return Err(format!("pong carried wrong nonce {:#x}", nonce))
+            }
+            // The node may interleave its own pings or announcements.
+            _ => {}
+        }
+    }
+}
+
+/// A Version for the wrong chain must end the connection.
+async fn wrong_chain(args: &Args, drop_timeout: Duration) -> Result<(), String> {
+    let mut stream = connect(args).await?;
+    network::read_message(&mut stream).await?;
+    send_version(&mut stream, args.chain_id.wrapping_add(1)).await?;
+    expect_drop(&mut stream, drop_timeout).await
+}
+
+/// Anything but Version as the first message must end the connection.
+async fn premature_message(args: &Args, drop_timeout: Duration) -> Result<(), String> {
+    let mut stream = connect(args).await?;
+    network::read_message(&mut stream).await?;
+    network::write_message(&mut stream, &NetworkMessage::Ping(1)).await?;
+    expect_drop(&mut stream, drop_timeout).await
+}
+
+/// A frame length beyond MAX_MESSAGE_SIZE must end the connection.
+async fn oversized_frame(args: &Args, drop_timeout: Duration) -> Result<(), String> {
+    let mut stream = connect(args).await?;
+    network::read_message(&mut stream).await?;
+    let mut header = NETWORK_MAGIC.to_vec();
+    header.extend(2u32.to_be_bytes()); // Ping
+    header.extend((MAX_MESSAGE_SIZE + 1).to_be_bytes());
+    header.extend([0u8; 4]);
+    stream
+        .write_all(&header)
+        .await
+        .map_err(|e| format!("write failed: {}", e))?;
+    expect_drop(&mut stream, drop_timeout).await
+}
+
+/// A frame not opening with the network magic must end the connection.
+async fn bad_magic(args: &Args, drop_timeout: Duration) -> Result<(), String> {
+    let mut stream = connect(args).await?;
+    network::read_message(&mut stream).await?;
+    let payload = bincode::serialize(&NetworkMessage::Ping(1)).map_err(|e| e.to_string())?;
+    let mut frame = network::frame_header(2, &payload).to_vec();
+    frame[0] ^= 0xFF;
+    frame.extend(payload);
+    stream
+        .write_all(&frame)
+        .await
+        .map_err(|e| format!("write failed: {}", e))?;
+    expect_drop(&mut stream, drop_timeout).await
+}
+
+/// A single corrupted payload byte must fail the checksum and end the
+/// connection.
+async fn bad_checksum(args: &Args, drop_timeout: Duration) -> Result<(), String> {
+    let mut stream = connect(args).await?;
+    network::read_message(&mut stream).await?;
+    let payload = bincode::serialize(&NetworkMessage::Ping(1)).map_err(|e| e.to_string())?;
+    let mut frame = network::frame_header(2, &payload).to_vec();
+    frame.extend(payload);
+    let last = frame.len() - 1;
+    frame[last] ^= 0x01;
+    stream
+        .write_all(&frame)
+        .await
+        .map_err(|e| format!("write failed: {}", e))?;
+    expect_drop(&mut stream, drop_timeout).await
+}
+
+/// A correctly framed and checksummed frame of garbage bytes must end
+/// the connection at the deserializer.
+async fn malformed_payload(args: &Args, drop_timeout: Duration) -> Result<(), String> {
+    let mut stream = connect(args).await?;
+    network::read_message(&mut stream).await?;
+    let garbage = [0xFFu8; 16];
+    let mut frame = network::frame_header(2, &garbage).to_vec();
+    frame.extend(garbage);
+    stream
+        .write_all(&frame)
+        .await
+        .map_err(|e| format!("write failed: {}", e))?;
+    expect_drop(&mut stream, drop_timeout).await
+}
+
+/// Trickling the handshake one byte at a time must trip the node's
+/// handshake timeout rather than pin a connection slot forever.
+async fn slow_loris(args: &Args, drop_timeout: Duration) -> Result<(), String> {
+    let mut stream = connect(args).await?;
+    network::read_message(&mut stream).await?;
+    let message = version_message(args.chain_id);
+    let payload = bincode::serialize(&message).map_err(|e| e.to_string())?;
+    let mut frame = network::frame_header(message.wire_id(), &payload).to_vec();
+    frame.extend(payload);
+    let deadline = tokio::time::Instant::now() + drop_timeout;
+    for byte in frame {
+        if tokio::time::Instant::now() >= deadline {
+            return Err("node kept the trickled connection open".to_string());
+        }
+        if stream.write_all(&[byte]).await.is_err() {
+            // Write failure means the node already hung up on us.
+            return Ok(());
+        }
+        tokio::time::sleep(Duration::from_secs(1)).await;
+    }
+    expect_drop(&mut stream, drop_timeout).await
+}
+
+async fn connect(args: &Args) -> Result<TcpStream, String> {
+    TcpStream::connect(args.addr)
+        .await
+        .map_err(|e| format!("failed to connect to {}: {}", args.addr, e))
+}
+
+fn version_message(chain_id: u8) -> NetworkMessage {
+    NetworkMessage::Version {
+        version: PROTOCOL_VERSION,
+        chain_id,
+        height: 0,
+        user_agent: "/pali-protocol-tester/".to_string(),
+    }
+}
+
+async fn send_version(stream: &mut TcpStream, chain_id: u8) -> Result<(), String> {
+    network::write_message(stream, &version_message(chain_id)).await
+}
+
+/// Succeeds once the node closes the connection; reads until EOF or a
+/// protocol error, failing if the node keeps talking past the deadline.
+async fn expect_drop(stream: &mut TcpStream, drop_timeout: Duration) -> Result<(), String> {
+    let deadline = tokio::time::Instant::now() + drop_timeout;
+    loop {
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        if remaining.is_zero() {
+            return Err("node did not drop the connection in time".to_string());
+        }
+        match tokio::time::timeout(remaining, network::read_message(stream)).await {
+            Err(_) => return Err("node did not drop the connection in time".to_string()),
+            Ok(Err(_)) => return Ok(()),
+            // VerAck or other chatter before the drop is tolerated.
+            Ok(Ok(_)) => {}
+        }
+    }
+}
@@ -0,0 +1,968 @@
+//! Palicoin wallet command-line tool. Talks to a running node over RPC.
+
+use std::path::{Path, PathBuf};
+
+use clap::{Parser, Subcommand};
+use serde_json::{json, Value};
+
+use pali_coin::client::RpcClient;
+use pali_coin::pairing;
+use pali_coin::types::Transaction;
+use pali_coin::wallet::{payment_settles, CreditedPayment, SendRequest, Wallet};
+use pali_coin::wallet_store::{Direction, TxRecord, TxStatus, WalletStore};
+use pali_coin::MAINNET_CHAIN_ID;
+
+#[derive(Parser)]
+#[command(name = "pali-wallet", about = "Palicoin wallet")]
+struct Args {
+    /// Wallet file.
+    #[arg(long, default_value = "wallet.dat")]
+    wallet: PathBuf,
+    /// RPC endpoint of the node.
+    #[arg(long, default_value = "http://127.0.0.1:8536/")]
+    rpc_url: String,
+    /// Chain id (1 = mainnet, 2 = testnet, 3 = regtest).
+    #[arg(long, default_value_t = MAINNET_CHAIN_ID)]
+    chain_id: u8,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create a new wallet file.
+    Create,
+    /// Print the wallet address.
+    Address,
+    /// Query the confirmed balance from the node.
+    Balance,
+    /// Send coins to an address.
+    Send {
+        /// Destination address (hex).
+        to: String,
+        /// Amount in base units.
+        amount: u64,
+        /// Fee preset (slow, normal, fast — resolved against the
+        /// node's estimator) or an explicit rate in base units per
+        /// byte.
+        #[arg(long, default_value = "normal")]
+        fee: String,
+        /// Print the planned transaction — outputs, size, fee and
+        /// effective rate — as JSON without signing or broadcasting.
+        #[arg(long)]
+        dry_run: bool,
+        /// Signal replace-by-fee on the transaction.
+        #[arg(long)]
+        replaceable: bool,
+        /// Optional label recorded in the local history.
+        #[arg(long, default_value = "")]
+        label: String,
+        /// Refuse to send to an address that already has on-chain
+        /// history instead of just warning.
+        #[arg(long)]
+        forbid_reuse: bool,
+    },
+    /// Merge fragmented balance: spend every mature output back into a
+    /// single one. Dust accumulates from mining and faucet payouts;
+    /// merging while fees are low keeps it spendable.
+    Consolidate {
+        /// Outputs at or below this amount (base units) count as dust
+        /// when reporting what the merge cleans up.
+        #[arg(long, default_value_t = 100_000)]
+        dust_threshold: u64,
+        /// Fee preset (slow, normal, fast) or an explicit rate in base
+        /// units per byte. Consolidation is never urgent, so the
+        /// default rides the cheapest estimate.
+        #[arg(long, default_value = "slow")]
+        fee: String,
+        /// Only consolidate once the resolved fee rate is at or below
+        /// this many base units per byte.
+        #[arg(long)]
+        max_fee_rate: Option<f64>,
+        /// With --max-fee-rate: instead of giving up, re-check the fee
+        /// estimator every this many seconds until the rate qualifies,
+        /// then send automatically.
+        #[arg(long)]
+        watch_secs: Option<u64>,
+        /// Print the plan — outputs merged, dust count, fee — as JSON
+        /// without signing or broadcasting.
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Redeem a paper wallet: move a private key's entire balance
+    /// (minus fee) into this wallet.
+    Sweep {
+        /// Private key as WIF or 64-character hex.
+        key: String,
+        /// Fee in base units.
+        #[arg(long, default_value_t = 10_000)]
+        fee: u64,
+    },
+    /// Block until a payment to an address reaches its confirmation
+    /// target, then print the txid — built for shell-scripted merchant
+    /// flows like `txid=$(pali-wallet await <addr> --amount 100000)`.
+    Await {
+        /// Address to watch (hex).
+        address: String,
+        /// Confirmations the payment must reach before it counts.
+        #[arg(long, default_value_t = 1)]
+        min_conf: u64,
+        /// Only settle for a payment of at least this many base units;
+        /// smaller payments are reported but keep the wait going.
+        #[arg(long)]
+        amount: Option<u64>,
+        /// Seconds between polls of the node's deposit events.
+        #[arg(long, default_value_t = 2)]
+        poll_secs: u64,
+    },
+    /// Sign a proof-of-reserves attestation over the wallet's unspent
+    /// outputs and print it as hex, for verification against any node
+    /// with the verifyreserveproof RPC. No funds move.
+    ProveReserves {
+        /// Auditor-supplied challenge (a nonce, a date) bound into
+        /// every signature so the proof cannot be reused across audits.
+        #[arg(long)]
+        claim: String,
+    },
+    /// Export the account-level extended public key as hex, for an
+    /// auditor to derive every receive address read-only (import on a
+    /// node with the importaccountxpub RPC). Reveals addresses, never
+    /// keys; no funds move.
+    ExportXpub,
+    /// Show the local transaction history, refreshed against the node.
+    History {
+        /// Emit CSV instead of a table.
+        #[arg(long)]
+        csv: bool,
+    },
+    /// Lock an unspent output so the node refuses to spend it.
+    Lock {
+        /// Transaction id of the output (hex).
+        txid: String,
+        /// Output index within the transaction.
+        #[arg(default_value_t = 0)]
+        vout: u32,
+    },
+    /// Release a locked output (or every lock with --all).
+    Unlock {
+        /// Transaction id of the output (hex).
+        txid: Option<String>,
+        /// Output index within the transaction.
+        #[arg(default_value_t = 0)]
+        vout: u32,
+        /// Release every locked output.
+        #[arg(long)]
+        all: bool,
+    },
+    /// List the outputs currently locked on the node.
+    Locked,
+    /// Export an encrypted state bundle (policy limits, labels, tx
+    /// history) for another device, printing its one-time pairing code.
+    ExportState {
+        /// Where to write the bundle.
+        #[arg(long, default_value = "wallet.sync")]
+        out: PathBuf,
+    },
+    /// Import a state bundle exported on another device and merge it
+    /// into the local history.
+    ImportState {
+        /// Bundle file written by export-state.
+        bundle: PathBuf,
+        /// Pairing code shown by the exporting device.
+        code: String,
+    },
+    /// Manage OS-keystore storage of the wallet password.
+    Keystore {
+        #[command(subcommand)]
+        action: KeystoreAction,
+    },
+    /// Manage machine binding: wrap the wallet file with a secret held
+    /// in this machine's secure store, so the password alone cannot
+    /// decrypt it elsewhere.
+    Bind {
+        #[command(subcommand)]
+        action: BindAction,
+    },
+}
+
+#[derive(Subcommand)]
+enum KeystoreAction {
+    /// Store the wallet password in the OS keystore so future commands
+    /// skip the prompt. Only recommended for low-value wallets.
+    Enable,
+    /// Remove the stored password from the OS keystore.
+    Disable,
+}
+
+#[derive(Subcommand)]
+enum BindAction {
+    /// Bind the wallet to this machine and print the recovery code.
+    Enable,
+    /// Remove the binding, leaving a password-only wallet file.
+    Disable,
+    /// Unbind a wallet on a different (or rebuilt) machine using the
+    /// recovery code printed when binding was enabled.
+    Recover {
+        /// 64-character hex recovery code.
+        code: String,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::init();
+    let args = Args::parse();
+    if let Err(e) = run(args).await {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(args: Args) -> Result<(), String> {
+    let client = RpcClient::new(args.rpc_url.clone());
+    match args.command {
+        Command::Create => {
+            if args.wallet.exists() {
+                return Err(format!("{} already exists", args.wallet.display()));
+            }
+            let password = prompt_password("New wallet password: ")?;
+            let mut wallet = Wallet::new();
+            wallet.save_to_file(&args.wallet, &password)?;
+            println!("address: {}", hex::encode(wallet.address()));
+            Ok(())
+        }
+        Command::Address => {
+            let wallet = load_wallet(&args.wallet)?;
+            println!("{}", hex::encode(wallet.address()));
+            Ok(())
+        }
+        Command::Balance => {
+            let wallet = load_wallet(&args.wallet)?;
+            let balance = client.call("getbalance",
+                json!([hex::encode(wallet.address())]),
+            )
+            .await?;
+            println!("{}", balance);
+            Ok(())
+        }
+        Command::Send {
+            to,
+            amount,
+            fee,
+            dry_run,
+            replaceable,
+            label,
+            forbid_reuse,
+        } => {
+            let mut wallet = load_wallet(&args.wallet)?;
+            let to_addr: [u8; 20] = hex::decode(&to)
+                .ok()
+                .and_then(|b| b.try_into().ok())
+                .ok_or_else(|| "malformed destination address".to_string())?;
+            let reused = client.call("getaddressinfo", json!([to]))
+                .await
+                .ok()
+                .and_then(|info| info.get("seen").and_then(Value::as_bool))
+                .unwrap_or(false);
+            if reused {
+                if forbid_reuse {
+                    return Err(format!(
+                        "{} already has on-chain history; refusing to reuse it (--forbid-reuse)",
+                        to
+                    ));
+                }
+                eprintln!(
+                    "warning: {} already has on-chain history; reusing addresses links payments",
+                    to
+                );
+            }
+            let nonce = client.call("getnonce",
+                json!([hex::encode(wallet.address())]),
+            )
+            .await?
+            .as_u64()
+            .ok_or_else(|| "bad getnonce response".to_string())?;
+            let tip = client.call("getblockcount", Value::Null)
+                .await?
+                .as_u64()
+                .ok_or_else(|| "bad getblockcount response".to_string())?;
+            let rate = resolve_fee_rate(&client, &fee).await?;
+            // Signature and public key widths are fixed, so a draft
+            // with placeholders measures the final signed size exactly.
+            let draft = Transaction {
+                chain_id: args.chain_id,
+                nonce,
+                from: wallet.address(),
+                to: to_addr,
+                amount,
+                fee: 0,
+                data: Vec::new(),
+                replaceable,
+                lock_time: pali_coin::wallet::anti_fee_sniping_locktime(tip),
+                signature: vec![0; 64],
+                public_key: vec![0; 33],
+            };
+            let size = draft.size();
+            let fee = (rate * size as f64).ceil() as u64;
+            if dry_run {
+                let balance = client.call("getbalance",
+                    json!([hex::encode(wallet.address())]),
+                )
+                .await?
+                .as_u64()
+                .unwrap_or(0);
+                let plan = json!({
+                    "dry_run": true,
+                    "from": hex::encode(wallet.address()),
+                    "nonce": nonce,
+                    "outputs": [{ "to": to, "amount": amount }],
+                    "size": size,
+                    "fee": fee,
+                    "fee_rate": rate,
+                    "effective_fee_rate": fee as f64 / size as f64,
+                    "lock_time": draft.lock_time,
+                    "replaceable": replaceable,
+                    "total_debit": amount + fee,
+                    "spendable_balance": balance,
+                });
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&plan).expect("plan serializes")
+                );
+                return Ok(());
+            }
+            let tx = wallet.create_transaction(SendRequest {
+                to: to_addr,
+                amount,
+                fee,
+                nonce,
+                chain_id: args.chain_id,
+                replaceable,
+                tip_height: tip,
+            })?;
+            let tx_hex = hex::encode(bincode::serialize(&tx).expect("serialize"));
+            let txid = client.call("sendtransaction", json!([tx_hex])).await?;
+
+            let mut store = open_store(&args.wallet)?;
+            store.upsert(TxRecord {
+                tx_hash: tx.hash(),
+                direction: Direction::Sent,
+                amount,
+                fee,
+                counterparty: to_addr,
+                status: TxStatus::Pending,
+                first_seen: unix_now(),
+                label,
+            });
+            store.save()?;
+            println!("{}", txid.as_str().unwrap_or_default());
+            Ok(())
+        }
+        Command::Consolidate {
+            dust_threshold,
+            fee,
+            max_fee_rate,
+            watch_secs,
+            dry_run,
+        } => {
+            let mut wallet = load_wallet(&args.wallet)?;
+            let address_hex = hex::encode(wallet.address());
+            let mut rate = resolve_fee_rate(&client, &fee).await?;
+            if let Some(ceiling) = max_fee_rate {
+                while rate > ceiling {
+                    let Some(secs) = watch_secs else {
+                        return Err(format!(
+                            "fee rate {:.2} is above the {:.2} ceiling; retry later or add --watch-secs",
+                            rate, ceiling
+                        ));
+                    };
+                    eprintln!(
+                        "fee rate {:.2} above the {:.2} ceiling; checking again in {}s",
+                        rate, ceiling, secs
+                    );
+                    tokio::time::sleep(std::time::Duration::from_secs(secs.max(1))).await;
+                    rate = resolve_fee_rate(&client, &fee).await?;
+                }
+            }
+            let unspent = client.call("listunspent", json!([address_hex])).await?;
+            let amounts: Vec<u64> = unspent
+                .as_array()
+                .ok_or_else(|| "bad listunspent response".to_string())?
+                .iter()
+                .filter(|utxo| utxo["mature"].as_bool().unwrap_or(false))
+                .filter_map(|utxo| utxo["amount"].as_u64())
+                .collect();
+            let nonce = client.call("getnonce", json!([address_hex]))
+                .await?
+                .as_u64()
+                .ok_or_else(|| "bad getnonce response".to_string())?;
+            let tip = client.call("getblockcount", Value::Null)
+                .await?
+                .as_u64()
+                .ok_or_else(|| "bad getblockcount response".to_string())?;
+            // Size first, fee from it: the account model consumes
+            // inputs implicitly, so a consolidation is always one
+            // fixed-size transaction no matter how many outputs merge.
+            let draft = Transaction {
+                chain_id: args.chain_id,
+                nonce,
+                from: wallet.address(),
+                to: wallet.address(),
+                amount: 0,
+                fee: 0,
+                data: Vec::new(),
+                replaceable: false,
+                lock_time: pali_coin::wallet::anti_fee_sniping_locktime(tip),
+                signature: vec![0; 64],
+                public_key: vec![0; 33],
+            };
+            let size = draft.size();
+            let fee = (rate * size as f64).ceil() as u64;
+            let plan = pali_coin::wallet::plan_consolidation(&amounts, dust_threshold, fee)?;
+            if dry_run {
+                // The estimates show whether waiting would be cheaper —
+                // consolidation rarely cares about the next block.
+                let estimates = client
+                    .call("getfeehistogram", Value::Null)
+                    .await?
+                    .get("estimates")
+                    .cloned()
+                    .unwrap_or(Value::Null);
+                let out = json!({
+                    "dry_run": true,
+                    "address": address_hex,
+                    "plan": plan,
+                    "size": size,
+                    "fee_rate": rate,
+                    "estimates": estimates,
+                });
+                println!(
+                    "{}",
+                    serde_json::to_string_pretty(&out).expect("plan serializes")
+                );
+                return Ok(());
+            }
+            let tx = wallet.create_transaction(SendRequest {
+                to: wallet.address(),
+                amount: plan.amount,
+                fee: plan.fee,
+                nonce,
+                chain_id: args.chain_id,
+                replaceable: false,
+                tip_height: tip,
+            })?;
+            let tx_hex = hex::encode(bincode::serialize(&tx).expect("serialize"));
+            let txid = client.call("sendtransaction", json!([tx_hex])).await?;
+
+            let mut store = open_store(&args.wallet)?;
+            store.upsert(TxRecord {
+                tx_hash: tx.hash(),
+                direction: Direction::Sent,
+                amount: plan.amount,
+                fee: plan.fee,
+                counterparty: wallet.address(),
+                status: TxStatus::Pending,
+                first_seen: unix_now(),
+                label: "consolidation".to_string(),
+            });
+            store.save()?;
+            eprintln!(
+                "merging {} outputs ({} dust) for a {} fee",
+                plan.utxos, plan.dust_utxos, plan.fee
+            );
+            println!("{}", txid.as_str().unwrap_or_default());
+            Ok(())
+        }
+        Command::Sweep { key, fee } => {
+            let wallet = load_wallet(&args.wallet)?;
+            let secret_key = pali_coin::crypto::parse_private_key(key.trim())?;
+            let secp = secp256k1::Secp256k1::new();
+            let public_key = secp256k1::PublicKey::from_secret_key(&secp, &secret_key);
+            let swept_addr = pali_coin::hash::pubkey_to_address(&public_key.serialize());
+            let swept_hex = hex::encode(swept_addr);
+
+            let balance = client.call("getbalance", json!([swept_hex]))
+                .await?
+                .as_u64()
+                .ok_or_else(|| "bad getbalance response".to_string())?;
+            if balance <= fee {
+                return Err(format!(
+                    "address {} holds {} which does not cover the fee {}",
+                    swept_hex, balance, fee
+                ));
+            }
+            let nonce = client.call("getnonce", json!([swept_hex]))
+                .await?
+                .as_u64()
+                .ok_or_else(|| "bad getnonce response".to_string())?;
+            let tip = client.call("getblockcount", Value::Null)
+                .await?
+                .as_u64()
+                .ok_or_else(|| "bad getblockcount response".to_string())?;
+
+            let amount = balance - fee;
+            let mut tx = pali_coin::types::Transaction {
+                chain_id: args.chain_id,
+                nonce,
+                from: swept_addr,
+                to: wallet.address(),
+                amount,
+                fee,
+                data: Vec::new(),
+                replaceable: false,
+                lock_time: pali_coin::wallet::anti_fee_sniping_locktime(tip),
+                signature: Vec::new(),
+                public_key: Vec::new(),
+            };
+            pali_coin::crypto::sign_transaction(&mut tx, &secret_key)?;
+            let tx_hex = hex::encode(bincode::serialize(&tx).expect("serialize"));
+            let txid = client.call("sendtransaction", json!([tx_hex])).await?;
+
+            let mut store = open_store(&args.wallet)?;
+            store.upsert(TxRecord {
+                tx_hash: tx.hash(),
+                direction: Direction::Received,
+                amount,
+                fee,
+                counterparty: swept_addr,
+                status: TxStatus::Pending,
+                first_seen: unix_now(),
+                label: "sweep".to_string(),
+            });
+            store.save()?;
+            println!("swept {} from {}", amount, swept_hex);
+            println!("{}", txid.as_str().unwrap_or_default());
+            Ok(())
+        }
+        Command::Await {
+            address,
+            min_conf,
+            amount,
+            poll_secs,
+        } => {
+            let watched: [u8; 20] = hex::decode(&address)
+                .ok()
+                .and_then(|b| b.try_into().ok())
+                .ok_or_else(|| "malformed address".to_string())?;
+            // Take the cursor before registering so anything the node
+            // credited earlier is history, not the awaited payment.
+            let mut cursor = client
+                .call("getdepositevents", Value::Null)
+                .await?
+                .get("cursor")
+                .and_then(Value::as_u64)
+                .ok_or_else(|| "bad getdepositevents response".to_string())?;
+            let registration = client
+                .call("registerdeposit", json!([address, min_conf]))
+                .await?;
+            // Only tear down a registration this command created; an
+            // operator-managed one stays in place after we exit.
+            let fresh =
+                registration.get("already_registered").and_then(Value::as_bool) == Some(false);
+            eprintln!(
+                "watching {} for a payment with {} confirmation(s)",
+                address, min_conf
+            );
+            loop {
+                let reply = client.call("getdepositevents", json!([cursor])).await?;
+                cursor = reply
+                    .get("cursor")
+                    .and_then(Value::as_u64)
+                    .ok_or_else(|| "bad getdepositevents response".to_string())?;
+                let events = reply.get("events").and_then(Value::as_array);
+                for event in events.into_iter().flatten() {
+                    // A re-credit after a reorg is still the payment
+                    // arriving; clawbacks never settle anything.
+                    let kind = event.get("kind").and_then(Value::as_str);
+                    if !matches!(kind, Some("credited" | "recredited")) {
+                        continue;
+                    }
+                    let payment = CreditedPayment {
+                        txid: event
+                            .get("txid")
+                            .and_then(Value::as_str)
+                            .and_then(|s| hex::decode(s).ok())
+                            .and_then(|b| b.try_into().ok())
+                            .ok_or_else(|| "bad deposit event txid".to_string())?,
+                        address: event
+                            .get("address")
+                            .and_then(Value::as_str)
+                            .and_then(|s| hex::decode(s).ok())
+                            .and_then(|b| b.try_into().ok())
+                            .ok_or_else(|| "bad deposit event address".to_string())?,
+                        amount: event.get("amount").and_then(Value::as_u64).unwrap_or(0),
+                        confirmations: event
+                            .get("confirmations")
+                            .and_then(Value::as_u64)
+                            .unwrap_or(0),
+                    };
+                    if payment_settles(&payment, &watched, min_conf, amount) {
+                        if fresh {
+                            let _ = client.call("unregisterdeposit", json!([address])).await;
+                        }
+                        eprintln!(
+                            "received {} with {} confirmation(s)",
+                            payment.amount, payment.confirmations
+                        );
+                        println!("{}", hex::encode(payment.txid));
+                        return Ok(());
+                    }
+                    if payment.address == watched {
+                        eprintln!(
+                            "payment of {} is below the {} minimum; still waiting",
+                            payment.amount,
+                            amount.unwrap_or(0)
+                        );
+                    }
+                }
+                tokio::time::sleep(std::time::Duration::from_secs(poll_secs.max(1))).await;
+            }
+        }
+        Command::ProveReserves { claim } => {
+            let mut wallet = load_wallet(&args.wallet)?;
+            let address_hex = hex::encode(wallet.address());
+            let height = client.call("getblockcount", Value::Null)
+                .await?
+                .as_u64()
+                .ok_or_else(|| "bad getblockcount response".to_string())?;
+            let block_hash: pali_coin::types::Hash256 = client
+                .call("getblockhash", json!([height]))
+                .await?
+                .as_str()
+                .and_then(|s| hex::decode(s).ok())
+                .and_then(|b| b.try_into().ok())
+                .ok_or_else(|| "bad getblockhash response".to_string())?;
+            let unspent = client.call("listunspent", json!([address_hex])).await?;
+            let mut utxos = Vec::new();
+            for utxo in unspent
+                .as_array()
+                .ok_or_else(|| "bad listunspent response".to_string())?
+            {
+                let tx_hash: pali_coin::types::Hash256 = utxo["txid"]
+                    .as_str()
+                    .and_then(|s| hex::decode(s).ok())
+                    .and_then(|b| b.try_into().ok())
+                    .ok_or_else(|| "bad txid in listunspent response".to_string())?;
+                let index = utxo["vout"]
+                    .as_u64()
+                    .ok_or_else(|| "bad vout in listunspent response".to_string())?
+                    as u32;
+                let amount = utxo["amount"]
+                    .as_u64()
+                    .ok_or_else(|| "bad amount in listunspent response".to_string())?;
+                utxos.push((pali_coin::types::OutPoint { tx_hash, index }, amount));
+            }
+            if utxos.is_empty() {
+                return Err("no unspent outputs to attest".to_string());
+            }
+            let proof = wallet.prove_reserves(block_hash, height, &claim, &utxos)?;
+            eprintln!(
+                "attesting {} outputs totalling {} base units at height {}",
+                proof.utxos.len(),
+                proof.total(),
+                height
+            );
+            println!("{}", hex::encode(bincode::serialize(&proof).expect("serialize")));
+            Ok(())
+        }
+        Command::ExportXpub => {
+            let mut wallet = load_wallet(&args.wallet)?;
+            let xpub = wallet.export_account_xpub()?;
+            eprintln!(
+                "account {} fingerprint {}",
+                hex::encode(wallet.address()),
+                xpub.fingerprint()
+            );
+            println!("{}", pali_coin::audit::encode(&xpub));
+            Ok(())
+        }
+        Command::Bind { action } => match action {
+            BindAction::Enable => {
+                if Wallet::file_is_machine_bound(&args.wallet)? {
+                    return Err("wallet is already machine-bound".to_string());
+                }
+                let password = prompt_password("Wallet password: ")?;
+                let mut wallet = Wallet::from_file(&args.wallet, &password)?;
+                let secret = pali_coin::keystore::machine_secret()?;
+                wallet.save_to_file_bound(&args.wallet, &password, Some(&secret))?;
+                println!("wallet is now bound to this machine");
+                println!("recovery code: {}", hex::encode(secret));
+                println!(
+                    "store the recovery code offline; it is the only way to open \
+                     this wallet if the machine or its secure store is lost"
+                );
+                Ok(())
+            }
+            BindAction::Disable => {
+                if !Wallet::file_is_machine_bound(&args.wallet)? {
+                    return Err("wallet is not machine-bound".to_string());
+                }
+                let password = prompt_password("Wallet password: ")?;
+                let secret = pali_coin::keystore::machine_secret()?;
+                let mut wallet =
+                    Wallet::from_file_bound(&args.wallet, &password, Some(&secret))?;
+                wallet.save_to_file(&args.wallet, &password)?;
+                println!("machine binding removed");
+                Ok(())
+            }
+            BindAction::Recover { code } => {
+                if !Wallet::file_is_machine_bound(&args.wallet)? {
+                    return Err("wallet is not machine-bound".to_string());
+                }
+                let secret: [u8; 32] = hex::decode(code.trim())
+                    .ok()
+                    .and_then(|b| b.try_into().ok())
+                    .ok_or_else(|| "recovery code must be 64 hex characters".to_string())?;
+                let password = prompt_password("Wallet password: ")?;
+                let mut wallet =
+                    Wallet::from_file_bound(&args.wallet, &password, Some(&secret))?;
+                wallet.save_to_file(&args.wallet, &password)?;
+                println!("machine binding removed; re-run `bind enable` to rebind");
+                Ok(())
+            }
+        },
+        Command::Keystore { action } => match action {
+            KeystoreAction::Enable => {
+                let password = prompt_password("Wallet password: ")?;
+                // Verify before storing so a typo does not get persisted.
+                Wallet::from_file(&args.wallet, &password)?;
+                pali_coin::keystore::store_password(&args.wallet, &password)?;
+                println!("password stored in OS keystore");
+                Ok(())
+            }
+            KeystoreAction::Disable => {
+                pali_coin::keystore::forget_password(&args.wallet)?;
+                println!("keystore entry removed");
+                Ok(())
+            }
+        },
+        Command::History { csv } => {
+            let mut store = open_store(&args.wallet)?;
+            let tip = client.call("getblockcount", Value::Null)
+                .await
+                .ok()
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0);
+            refresh_statuses(&client, &mut store).await?;
+            store.save()?;
+            if csv {
+                print!("{}", store.to_csv(tip));
+            } else {
+                for record in store.history() {
+                    let status = match record.status {
+                        TxStatus::Pending => "pending".to_string(),
+                        TxStatus::Confirmed { .. } => {
+                            format!("{} conf", record.confirmations(tip))
+                        }
+                        TxStatus::Conflicted => "conflicted".to_string(),
+                    };
+                    println!(
+                        "{} {} {:>12} fee {:>8} {} {} {}",
+                        hex::encode(record.tx_hash),
+                        match record.direction {
+                            Direction::Sent => "->",
+                            Direction::Received => "<-",
+                        },
+                        record.amount,
+                        record.fee,
+                        hex::encode(record.counterparty),
+                        status,
+                        record.label,
+                    );
+                }
+            }
+            Ok(())
+        }
+        Command::Lock { txid, vout } => {
+            let result = client.call("lockunspent", json!([txid, vout, true])).await?;
+            if result.get("changed").and_then(Value::as_bool) == Some(true) {
+                println!("locked {}:{}", txid, vout);
+            } else {
+                println!("{}:{} was already locked", txid, vout);
+            }
+            Ok(())
+        }
+        Command::Unlock { txid, vout, all } => {
+            if all {
+                let locked = client.call("listlockunspent", Value::Null).await?;
+                let entries = locked.as_array().cloned().unwrap_or_default();
+                for entry in &entries {
+                    if let (Some(txid), Some(vout)) = (
+                        entry.get("txid").and_then(Value::as_str),
+                        entry.get("vout").and_then(Value::as_u64),
+                    ) {
+                        client
+                            .call("lockunspent", json!([txid, vout, false]))
+                            .await?;
+                    }
+                }
+                println!("released {} locks", entries.len());
+                return Ok(());
+            }
+            let txid = txid.ok_or_else(|| "txid required unless --all is given".to_string())?;
+            let result = client
+                .call("lockunspent", json!([txid, vout, false]))
+                .await?;
+            if result.get("changed").and_then(Value::as_bool) == Some(true) {
+                println!("unlocked {}:{}", txid, vout);
+            } else {
+                println!("{}:{} was not locked", txid, vout);
+            }
+            Ok(())
+        }
+        Command::Locked => {
+            let locked = client.call("listlockunspent", Value::Null).await?;
+            let entries = locked.as_array().cloned().unwrap_or_default();
+            if entries.is_empty() {
+                println!("no locked outputs");
+            }
+            for entry in entries {
+                println!(
+                    "{}:{} {:>12} {}",
+                    entry.get("txid").and_then(Value::as_str).unwrap_or(""),
+                    entry.get("vout").and_then(Value::as_u64).unwrap_or(0),
+                    entry.get("amount").and_then(Value::as_u64).unwrap_or(0),
+                    entry.get("address").and_then(Value::as_str).unwrap_or(""),
+                );
+            }
+            Ok(())
+        }
+        Command::ExportState { out } => {
+            let wallet = load_wallet(&args.wallet)?;
+            let store = open_store(&args.wallet)?;
+            let bundle = pairing::StateBundle::capture(&wallet, &store);
+            let code = pairing::generate_pairing_code();
+            let sealed = pairing::seal_bundle(&bundle, &code)?;
+            std::fs::write(&out, sealed)
+                .map_err(|e| format!("failed to write {}: {}", out.display(), e))?;
+            println!(
+                "wrote {} ({} transaction records)",
+                out.display(),
+                bundle.records.len()
+            );
+            println!("pairing code: {}", code);
+            println!(
+                "enter the code on the other device with `import-state`; \
+                 the code is only good for this bundle"
+            );
+            Ok(())
+        }
+        Command::ImportState { bundle, code } => {
+            let wallet = load_wallet(&args.wallet)?;
+            let bytes = std::fs::read(&bundle)
+                .map_err(|e| format!("failed to read {}: {}", bundle.display(), e))?;
+            let state = pairing::open_bundle(&bytes, &code)?;
+            if state.address != wallet.address() {
+                return Err(format!(
+                    "bundle belongs to wallet {}, not this one",
+                    hex::encode(state.address)
+                ));
+            }
+            let mut store = open_store(&args.wallet)?;
+            let changed = store.merge(&state.records);
+            store.save()?;
+            println!(
+                "merged {} of {} records into the local history",
+                changed,
+                state.records.len()
+            );
+            if state.policy.max_per_tx.is_some()
+                || state.policy.daily_cap.is_some()
+                || state.policy.allowlist.is_some()
+                || state.policy.approval_threshold.is_some()
+            {
+                println!(
+                    "bundle carries spend-policy limits; spend policies are \
+                     configured per session and were not changed"
+                );
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Maps a `--fee` preset onto the node's estimator, or parses an
+/// explicit rate in base units per byte.
+async fn resolve_fee_rate(client: &RpcClient, fee: &str) -> Result<f64, String> {
+    let estimate = match fee {
+        "fast" => Some("next_block"),
+        "normal" => Some("30_min"),
+        "slow" => Some("1_hour"),
+        _ => None,
+    };
+    match estimate {
+        Some(key) => client
+            .call("getfeehistogram", Value::Null)
+            .await?
+            .get("estimates")
+            .and_then(|estimates| estimates.get(key))
+            .and_then(Value::as_f64)
+            .ok_or_else(|| "bad getfeehistogram response".to_string()),
+        None => {
+            let rate: f64 = fee.parse().map_err(|_| {
+                format!(
+                    "--fee takes slow, normal, fast or a rate in base units per byte, not '{}'",
+                    fee
+                )
+            })?;
+            if !rate.is_finite() || rate <= 0.0 {
+                return Err(format!("fee rate must be positive, not '{}'", fee));
+            }
+            Ok(rate)
+        }
+    }
+}
+
+/// Re-checks every pending transaction against the node.
+async fn refresh_statuses(client: &RpcClient, store: &mut WalletStore) -> Result<(), String> {
+    for tx_hash in store.pending() {
+        let status = client
+            .call("gettransactionstatus", json!([hex::encode(tx_hash)]))
+            .await?;
+        match status.get("status").and_then(Value::as_str) {
+            Some("confirmed") => {
+                let height = status.get("height").and_then(Value::as_u64).unwrap_or(0);
+                store.set_status(&tx_hash, TxStatus::Confirmed { height });
+            }
+            Some("unknown") => store.set_status(&tx_hash, TxStatus::Conflicted),
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn load_wallet(path: &Path) -> Result<Wallet, String> {
+    let binding = if Wallet::file_is_machine_bound(path)? {
+        Some(pali_coin::keystore::machine_secret()?)
+    } else {
+        None
+    };
+    if let Some(password) = pali_coin::keystore::get_password(path) {
+        match Wallet::from_file_bound(path, &password, binding.as_ref()) {
+            Ok(wallet) => return Ok(wallet),
+            Err(_) => eprintln!("keystore password rejected; falling back to prompt"),
+        }
+    }
+    let password = prompt_password("Wallet password: ")?;
+    Wallet::from_file_bound(path, &password, binding.as_ref()).map_err(String::from)
+}
+
+fn open_store(wallet_path: &Path) -> Result<WalletStore, String> {
+    WalletStore::open(wallet_path.with_extension("history.json"))
+}
+
+fn prompt_password(prompt: &str) -> Result<String, String> {
+    eprint!("{}", prompt);
+    let mut line = String::new();
+    std::io::stdin()
+        .read_line(&mut line)
+        .map_err(|e| format!("failed to read password: {}", e))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
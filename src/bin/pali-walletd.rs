@@ -0,0 +1,331 @@
+//! Standalone wallet daemon: holds the keys, talks to a node.
+//!
+//! The daemon loads a wallet file, connects to a (possibly remote)
+//! node as an ordinary RPC client, and exposes its own authenticated
+//! JSON-RPC surface for building, signing and broadcasting
+//! transactions. Keys never leave this process, so the public-facing
+//! node can run on a separate machine with no wallet at all.
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use axum::extract::State;
+use axum::routing::post;
+use axum::{Json, Router};
+use clap::Parser;
+use serde_json::{json, Value};
+
+use pali_coin::client::{Auth, RpcClient};
+use pali_coin::rpc_auth::{AuthConfig, Scope};
+use pali_coin::types::Transaction;
+use pali_coin::wallet::{SendRequest, Wallet, DEFAULT_UNLOCK_SECS};
+use pali_coin::MAINNET_CHAIN_ID;
+
+#[derive(Parser)]
+#[command(name = "pali-walletd", about = "Palicoin wallet daemon")]
+struct Args {
+    /// Wallet file.
+    #[arg(long, default_value = "wallet.dat")]
+    wallet: PathBuf,
+    /// RPC endpoint of the node to relay through.
+    #[arg(long, default_value = "http://127.0.0.1:8536/")]
+    node_url: String,
+    /// Bearer token for authenticating to the node.
+    #[arg(long)]
+    node_token: Option<String>,
+    /// Address to serve the wallet RPC on.
+    #[arg(long, default_value = "127.0.0.1:8537")]
+    bind: std::net::SocketAddr,
+    /// Chain id (1 = mainnet, 2 = testnet, 3 = regtest).
+    #[arg(long, default_value_t = MAINNET_CHAIN_ID)]
+    chain_id: u8,
+    /// RPC basic-auth user name (requires --rpc-password).
+    #[arg(long)]
+    rpc_user: Option<String>,
+    /// RPC basic-auth password.
+    #[arg(long)]
+    rpc_password: Option<String>,
+    /// Bearer token as scope:token; repeatable.
+    #[arg(long = "rpc-token")]
+    rpc_tokens: Vec<String>,
+    /// Source IPs allowed to connect; repeatable. Empty allows all.
+    #[arg(long = "rpc-allow-ip")]
+    rpc_allow_ips: Vec<std::net::IpAddr>,
+}
+
+/// Shared handles the wallet RPC layer operates on.
+#[derive(Clone)]
+struct WalletdContext {
+    wallet: Arc<Mutex<Wallet>>,
+    node: Arc<RpcClient>,
+    chain_id: u8,
+    auth: Arc<AuthConfig>,
+}
+
+#[tokio::main]
+async fn main() {
+    env_logger::init();
+    let args = Args::parse();
+    if let Err(e) = run(args).await {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(args: Args) -> Result<(), String> {
+    let auth = AuthConfig::from_args(
+        args.rpc_user,
+        args.rpc_password,
+        args.rpc_tokens,
+        args.rpc_allow_ips,
+    )?;
+    // The node's RPC may reasonably run open behind a firewall; a
+    // daemon holding keys may not.
+    if auth.is_open() && !args.bind.ip().is_loopback() {
+        return Err(format!(
+            "refusing to serve wallet RPC on {} without credentials — configure \
+             --rpc-user/--rpc-password or --rpc-token, or bind to localhost",
+            args.bind
+        ));
+    }
+
+    let binding = if Wallet::file_is_machine_bound(&args.wallet)? {
+        Some(pali_coin::keystore::machine_secret()?)
+    } else {
+        None
+    };
+    let password = prompt_password("Wallet password: ")?;
+    let wallet = Wallet::from_file_bound(&args.wallet, &password, binding.as_ref())?;
+    log::info!("loaded wallet {}", hex::encode(wallet.address()));
+
+    let mut node = RpcClient::new(args.node_url);
+    if let Some(token) = args.node_token {
+        node = node.with_auth(Auth::Token(token));
+    }
+
+    let ctx = WalletdContext {
+        wallet: Arc::new(Mutex::new(wallet)),
+        node: Arc::new(node),
+        chain_id: args.chain_id,
+        auth: Arc::new(auth),
+    };
+    let app = Router::new()
+        .route("/", post(handle_request))
+        .with_state(ctx.clone())
+        .layer(axum::middleware::from_fn_with_state(ctx, auth_middleware));
+    let listener = tokio::net::TcpListener::bind(args.bind)
+        .await
+        .map_err(|e| format!("failed to bind wallet RPC listener: {}", e))?;
+    log::info!("wallet RPC listening on {}", args.bind);
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await
+    .map_err(|e| format!("wallet RPC server error: {}", e))
+}
+
+/// Least scope a wallet-daemon method requires. The method set is
+/// disjoint from the node's, so this stays local rather than widening
+/// the shared table.
+fn walletd_method_scope(method: &str) -> Scope {
+    match method {
+        "createtransaction" | "sign" | "broadcast" | "unlock" | "lock" => Scope::Wallet,
+        m if m.starts_with("get") => Scope::ReadOnly,
+        _ => Scope::Admin,
+    }
+}
+
+/// Mirrors the node's RPC auth middleware for the daemon's surface.
+async fn auth_middleware(
+    State(ctx): State<WalletdContext>,
+    axum::extract::ConnectInfo(remote): axum::extract::ConnectInfo<std::net::SocketAddr>,
+    mut request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+
+    if !ctx.auth.ip_allowed(remote.ip()) {
+        return (StatusCode::FORBIDDEN, "source address not allowed").into_response();
+    }
+    let header = request
+        .headers()
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned);
+    match ctx.auth.authenticate(header.as_deref()) {
+        Some(scope) => {
+            request.extensions_mut().insert(scope);
+            next.run(request).await
+        }
+        None => (StatusCode::UNAUTHORIZED, "invalid credentials").into_response(),
+    }
+}
+
+async fn handle_request(
+    State(ctx): State<WalletdContext>,
+    axum::Extension(scope): axum::Extension<Scope>,
+    Json(req): Json<Value>,
+) -> Json<Value> {
+    let id = req.get("id").cloned().unwrap_or(Value::Null);
+    let method = req.get("method").and_then(Value::as_str).unwrap_or("");
+    let params = req.get("params").cloned().unwrap_or(Value::Null);
+    if scope < walletd_method_scope(method) {
+        return Json(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -32001, "message": format!("scope does not permit '{}'", method) }
+        }));
+    }
+    match dispatch(&ctx, method, &params).await {
+        Ok(result) => Json(json!({ "jsonrpc": "2.0", "id": id, "result": result })),
+        Err(message) => Json(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -1, "message": message }
+        })),
+    }
+}
+
+/// Routes a single wallet RPC call. Node queries happen before any
+/// wallet lock is taken so a slow node never holds the keys hostage.
+async fn dispatch(ctx: &WalletdContext, method: &str, params: &Value) -> Result<Value, String> {
+    match method {
+        "getaddress" => {
+            let wallet = ctx.wallet.lock().map_err(|_| "wallet lock poisoned")?;
+            Ok(json!(hex::encode(wallet.address())))
+        }
+        "getwalletinfo" => {
+            let address = {
+                let wallet = ctx.wallet.lock().map_err(|_| "wallet lock poisoned")?;
+                wallet.address()
+            };
+            let balance = ctx
+                .node
+                .call("getbalance", json!([hex::encode(address)]))
+                .await?;
+            let locked = {
+                let mut wallet = ctx.wallet.lock().map_err(|_| "wallet lock poisoned")?;
+                wallet.is_locked()
+            };
+            Ok(json!({
+                "address": hex::encode(address),
+                "balance": balance,
+                "locked": locked,
+                "chain_id": ctx.chain_id,
+            }))
+        }
+        "unlock" => {
+            let password = param_str(params, 0)?;
+            let timeout = params
+                .get(1)
+                .and_then(Value::as_u64)
+                .unwrap_or(DEFAULT_UNLOCK_SECS);
+            let mut wallet = ctx.wallet.lock().map_err(|_| "wallet lock poisoned")?;
+            wallet.unlock(&password, timeout)?;
+            Ok(json!({ "unlocked_for": timeout }))
+        }
+        "lock" => {
+            let mut wallet = ctx.wallet.lock().map_err(|_| "wallet lock poisoned")?;
+            wallet.lock();
+            Ok(json!(true))
+        }
+        // Builds and signs in one step; the result is not broadcast.
+        "createtransaction" => {
+            let to = param_address(params, 0)?;
+            let amount = params
+                .get(1)
+                .and_then(Value::as_u64)
+                .ok_or_else(|| "missing amount".to_string())?;
+            let fee = params.get(2).and_then(Value::as_u64).unwrap_or(10_000);
+            let replaceable = params.get(3).and_then(Value::as_bool).unwrap_or(false);
+            let from = {
+                let wallet = ctx.wallet.lock().map_err(|_| "wallet lock poisoned")?;
+                wallet.address()
+            };
+            let nonce = ctx
+                .node
+                .call("getnonce", json!([hex::encode(from)]))
+                .await?
+                .as_u64()
+                .ok_or_else(|| "bad getnonce response".to_string())?;
+            let tip = ctx
+                .node
+                .call("getblockcount", Value::Null)
+                .await?
+                .as_u64()
+                .ok_or_else(|| "bad getblockcount response".to_string())?;
+            let mut wallet = ctx.wallet.lock().map_err(|_| "wallet lock poisoned")?;
+            let tx = wallet.create_transaction(SendRequest {
+                to,
+                amount,
+                fee,
+                nonce,
+                chain_id: ctx.chain_id,
+                replaceable,
+                tip_height: tip,
+            })?;
+            Ok(json!({
+                "txid": hex::encode(tx.hash()),
+                "hex": hex::encode(bincode::serialize(&tx).expect("serialize")),
+            }))
+        }
+        // Signs an externally built transaction that spends from this
+        // wallet; the spend policy applies exactly as for sends.
+        "sign" => {
+            let mut tx = param_tx(params, 0)?;
+            if tx.chain_id != ctx.chain_id {
+                return Err(format!(
+                    "transaction is for chain {} but this daemon serves chain {}",
+                    tx.chain_id, ctx.chain_id
+                ));
+            }
+            let mut wallet = ctx.wallet.lock().map_err(|_| "wallet lock poisoned")?;
+            wallet.sign_transaction(&mut tx)?;
+            Ok(json!({
+                "txid": hex::encode(tx.hash()),
+                "hex": hex::encode(bincode::serialize(&tx).expect("serialize")),
+            }))
+        }
+        "broadcast" => {
+            let hex = param_str(params, 0)?;
+            // Decode locally first so malformed input fails here with a
+            // useful message instead of a relayed node error.
+            param_tx(params, 0)?;
+            ctx.node.call("sendtransaction", json!([hex])).await
+        }
+        _ => Err(format!("unknown method '{}'", method)),
+    }
+}
+
+fn param_str(params: &Value, index: usize) -> Result<String, String> {
+    params
+        .get(index)
+        .and_then(Value::as_str)
+        .map(str::to_owned)
+        .ok_or_else(|| format!("missing string parameter {}", index))
+}
+
+fn param_address(params: &Value, index: usize) -> Result<[u8; 20], String> {
+    let s = param_str(params, index)?;
+    hex::decode(&s)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| "malformed address".to_string())
+}
+
+fn param_tx(params: &Value, index: usize) -> Result<Transaction, String> {
+    let s = param_str(params, index)?;
+    let bytes = hex::decode(&s).map_err(|e| format!("bad transaction hex: {}", e))?;
+    bincode::deserialize(&bytes).map_err(|e| format!("malformed transaction: {}", e))
+}
+
+fn prompt_password(prompt: &str) -> Result<String, String> {
+    eprint!("{}", prompt);
+    let mut line = String::new();
+    std::io::stdin()
+        .read_line(&mut line)
+        .map_err(|e| format!("failed to read password: {}", e))?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
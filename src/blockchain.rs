@@ -0,0 +1,1914 @@
+//! Persistent chain state: block storage, UTXO set and validation.
+
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rocksdb::{ColumnFamilyDescriptor, Options, DB};
+use serde::{Deserialize, Serialize};
+
+use crate::crypto;
+use crate::error::{PaliError, StorageError, StorageErrorKind};
+use crate::hash;
+use crate::math;
+use crate::consensus::ChainParams;
+use crate::cfilters;
+use crate::journal::{self, Event, EventKind};
+use crate::poa;
+use crate::pow::{DoubleSha256, PowAlgorithm};
+use crate::rejection::RejectionReason;
+use crate::types::{
+    Address, Block, BlockHeader, Hash256, OutPoint, Transaction, UtxoEntry,
+    COINBASE_ADDRESS, MAX_COINBASE_DATA, MAX_SUPPLY,
+};
+
+pub const CF_BLOCKS: &str = "blocks";
+pub const CF_HEIGHTS: &str = "heights";
+pub const CF_UTXOS: &str = "utxos";
+pub const CF_TXINDEX: &str = "txindex";
+pub const CF_STATE: &str = "state";
+pub const CF_DIFFICULTY: &str = "difficulty";
+pub const CF_SPENT: &str = "spent";
+pub const CF_UNDO: &str = "undo";
+pub const CF_FILTERS: &str = "filters";
+pub const CF_EVENTS: &str = "events";
+
+const STATE_KEY: &[u8] = b"chain_state";
+
+/// CF_STATE key tracking the next height the cold-tier migration will
+/// consider; everything below it already moved.
+const COLD_CURSOR_KEY: &[u8] = b"cold_cursor";
+
+/// CF_FILTERS key of a block's compact filter (see the cfilters
+/// module).
+fn cfilter_key(block_hash: &Hash256) -> [u8; 33] {
+    let mut key = [b'f'; 33];
+    key[1..].copy_from_slice(block_hash);
+    key
+}
+
+/// CF_FILTERS key of a block's chained filter header.
+fn cfheader_key(block_hash: &Hash256) -> [u8; 33] {
+    let mut key = [b'h'; 33];
+    key[1..].copy_from_slice(block_hash);
+    key
+}
+
+/// CF_STATE key holding the validator-set rotation history on
+/// proof-of-authority chains: a list of `(height, set)` pairs, one per
+/// rotation transaction connected. Absent on PoW chains.
+const POA_VALIDATORS_KEY: &[u8] = b"poa_validators";
+
+/// Difficulty retarget window, in blocks.
+pub const RETARGET_WINDOW: u64 = 60;
+
+/// Coinbase outputs may not be spent for this many blocks.
+pub const COINBASE_MATURITY: u64 = 100;
+
+/// Summary of the best chain, persisted under CF_STATE.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChainState {
+    pub best_hash: Hash256,
+    pub height: u64,
+    pub total_work: u128,
+    pub circulating_supply: u64,
+}
+
+/// Everything needed to mint a custom genesis block for a private or
+/// test network. Serialized as `chainparams.json` in the data directory
+/// by `pali-node init-chain` so operators can audit what they deployed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenesisConfig {
+    pub chain_id: u8,
+    pub timestamp: u64,
+    /// Human-readable message embedded in the genesis coinbase.
+    pub message: String,
+    /// Initial difficulty in compact bits form.
+    pub bits: u32,
+    /// Addresses funded directly in the genesis block.
+    pub premine: Vec<PremineAllocation>,
+}
+
+/// One genesis funding entry: hex address and amount in base units.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PremineAllocation {
+    pub address: String,
+    pub amount: u64,
+}
+
+/// One integrity problem the scrubber found (see `scrub_blocks`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrubFinding {
+    pub height: u64,
+    pub detail: String,
+    /// Whether the damaged entry could be rewritten in place.
+    pub repaired: bool,
+}
+
+/// Result of one bounded scrub pass over consecutive heights.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScrubBatch {
+    pub checked: u64,
+    /// Height the next pass should start from; wraps to 0 after the
+    /// tip so the scrubber circles the chain forever.
+    pub next_height: u64,
+    pub findings: Vec<ScrubFinding>,
+}
+
+/// Most recent findings a [`ScrubStatus`] retains; a rotten disk must
+/// not grow the list without bound.
+pub const SCRUB_FINDINGS_KEPT: usize = 100;
+
+/// Running totals the integrity scrubber accumulates across passes.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScrubStatus {
+    /// Height the next batch starts from.
+    pub cursor: u64,
+    pub blocks_checked: u64,
+    /// Full circles of the chain completed so far.
+    pub passes_completed: u64,
+    /// Newest findings last, capped at [`SCRUB_FINDINGS_KEPT`].
+    pub findings: Vec<ScrubFinding>,
+}
+
+impl ScrubStatus {
+    /// Folds one batch into the running totals.
+    pub fn absorb(&mut self, batch: &ScrubBatch) {
+        self.blocks_checked += batch.checked;
+        if batch.next_height == 0 && batch.checked > 0 {
+            self.passes_completed += 1;
+        }
+        self.cursor = batch.next_height;
+        self.findings.extend(batch.findings.iter().cloned());
+        if self.findings.len() > SCRUB_FINDINGS_KEPT {
+            let excess = self.findings.len() - SCRUB_FINDINGS_KEPT;
+            self.findings.drain(..excess);
+        }
+    }
+}
+
+/// Summary of a full UTXO set scan (see `utxo_set_info`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UtxoSetInfo {
+    pub height: u64,
+    pub best_hash: Hash256,
+    pub txouts: u64,
+    pub total_amount: u64,
+    /// Chained SHA256 over the serialized set in key order.
+    pub hash_serialized: Hash256,
+}
+
+/// Per-block difficulty sample stored at connect time, keyed by height
+/// in CF_DIFFICULTY, powering the history and hashrate endpoints.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DifficultyRecord {
+    pub bits: u32,
+    pub difficulty: f64,
+    /// Seconds between this block's timestamp and its parent's.
+    pub solve_time: u64,
+    pub work: u128,
+    pub timestamp: u64,
+}
+
+/// Per-column-family storage figures sampled from RocksDB properties.
+/// All sizes are estimates and may lag recent writes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CfStorageStats {
+    pub name: String,
+    /// Bytes of SST files on disk.
+    pub sst_files_size: u64,
+    /// Estimated bytes of live (non-obsolete) data.
+    pub live_data_size: u64,
+    pub num_keys: u64,
+    /// Bytes compaction still needs to rewrite to reach a steady state.
+    pub pending_compaction_bytes: u64,
+}
+
+impl CfStorageStats {
+    /// Space amplification: bytes on disk per byte of live data.
+    pub fn space_amplification(&self) -> f64 {
+        if self.live_data_size == 0 {
+            1.0
+        } else {
+            self.sst_files_size as f64 / self.live_data_size as f64
+        }
+    }
+}
+
+/// Where a spent output was consumed, stored in CF_SPENT so explorers
+/// can answer spender lookups without scanning the chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendingInfo {
+    pub spending_tx: Hash256,
+    pub height: u64,
+}
+
+/// Everything needed to disconnect one block in O(block size),
+/// stored in CF_UNDO under the block hash at connect time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockUndo {
+    /// Outputs this block spent, with their prior values.
+    pub spent: Vec<(OutPoint, UtxoEntry)>,
+    /// Outputs this block created.
+    pub created: Vec<OutPoint>,
+    /// Sender nonces as they stood before this block, first write per
+    /// address only.
+    pub prior_nonces: Vec<(Address, u64)>,
+}
+
+/// Location of a confirmed transaction, stored in the tx index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TxLocation {
+    pub block_hash: Hash256,
+    pub height: u64,
+    pub index: u32,
+}
+
+/// Pending writes for one block connect, applied to the database in a
+/// single atomic WriteBatch. The overlay maps let reads during connect
+/// observe the block's own earlier effects before anything is written.
+struct ConnectBatch {
+    batch: rocksdb::WriteBatch,
+    added_utxos: HashMap<OutPoint, UtxoEntry>,
+    /// Outpoints deleted by this block, with the address each one paid.
+    removed_utxos: HashMap<OutPoint, Address>,
+    /// Undo record accumulated alongside the writes.
+    undo: BlockUndo,
+}
+
+impl ConnectBatch {
+    fn new() -> Self {
+        ConnectBatch {
+            batch: rocksdb::WriteBatch::default(),
+            added_utxos: HashMap::new(),
+            removed_utxos: HashMap::new(),
+            undo: BlockUndo {
+                spent: Vec::new(),
+                created: Vec::new(),
+                prior_nonces: Vec::new(),
+            },
+        }
+    }
+}
+
+pub struct Blockchain {
+    db: DB,
+    /// Optional cold tier: a second database (typically on a slower or
+    /// networked disk) holding old block bodies. Lookups fall through
+    /// to it transparently; only CF_BLOCKS data ever moves there.
+    cold: Option<DB>,
+    state: ChainState,
+    /// Secondary index over the UTXO set, rebuilt at open time and
+    /// maintained on every connect, so per-address lookups do not scan
+    /// the whole column family.
+    address_index: HashMap<Address, HashSet<OutPoint>>,
+    /// Proof-of-work scheme this chain runs under; double-SHA256
+    /// unless the chain parameters select otherwise.
+    pow: &'static dyn PowAlgorithm,
+    /// Chain parameters validation consults (reward schedule and
+    /// friends); mainnet defaults unless the daemon loads overrides.
+    params: ChainParams,
+    /// On-chain validator-set rotation history for proof-of-authority
+    /// chains, newest last, mirrored from CF_STATE. Empty under PoW.
+    poa_rotations: Vec<(u64, Vec<String>)>,
+}
+
+impl Blockchain {
+    /// Opens (or creates) the chain database at `path`, writing the
+    /// genesis block on first run.
+    pub fn open<P: AsRef<Path>>(path: P, chain_id: u8) -> Result<Self, PaliError> {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let cfs = Self::column_families()
+            .iter()
+            .map(|name| ColumnFamilyDescriptor::new(*name, Options::default()))
+            .collect::<Vec<_>>();
+        let db = DB::open_cf_descriptors(&opts, path, cfs)
+            .map_err(|e| StorageError::database(e).context("failed to open chain database"))?;
+
+        let state = match db
+            .get_cf(db.cf_handle(CF_STATE).expect("state cf exists"), STATE_KEY)
+            .map_err(StorageError::database)?
+        {
+            Some(bytes) => bincode::deserialize(&bytes)
+                .map_err(|e| StorageError::corrupt("corrupt chain state", e))?,
+            None => {
+                let genesis = Self::genesis_block(chain_id);
+                let state = ChainState {
+                    best_hash: genesis.hash(),
+                    height: 0,
+                    total_work: math::block_work(genesis.header.bits),
+                    circulating_supply: 0,
+                };
+                let chain = Blockchain {
+                    db,
+                    cold: None,
+                    state,
+                    address_index: HashMap::new(),
+                    pow: &DoubleSha256,
+                    params: ChainParams::default(),
+                    poa_rotations: Vec::new(),
+                };
+                let mut batch = rocksdb::WriteBatch::default();
+                chain.store_block(&genesis, &mut batch)?;
+                chain.db.write(batch).map_err(StorageError::database)?;
+                chain.persist_state()?;
+                return Ok(chain);
+            }
+        };
+        let mut chain = Blockchain {
+            db,
+            cold: None,
+            state,
+            address_index: HashMap::new(),
+            pow: &DoubleSha256,
+            params: ChainParams::default(),
+            poa_rotations: Vec::new(),
+        };
+        chain.build_address_index()?;
+        chain.poa_rotations = chain.load_poa_rotations()?;
+        Ok(chain)
+    }
+
+    /// Selects the proof-of-work scheme blocks are validated and
+    /// work-weighted under. Call once at startup, before any block is
+    /// connected; changing algorithms mid-chain invalidates the work
+    /// accounting already on disk.
+    pub fn set_pow_algorithm(&mut self, algorithm: &'static dyn PowAlgorithm) {
+        self.pow = algorithm;
+    }
+
+    /// Adopts loaded chain parameters (reward schedule, PoA miner
+    /// set). Call at startup, alongside
+    /// [`set_pow_algorithm`](Self::set_pow_algorithm).
+    pub fn set_chain_params(&mut self, params: ChainParams) {
+        self.params = params;
+    }
+
+    /// The chain parameters validation currently runs under.
+    pub fn chain_params(&self) -> &ChainParams {
+        &self.params
+    }
+
+    /// The proof-of-authority validator set in force at the tip: the
+    /// most recent on-chain rotation, or the configured initial set if
+    /// none has connected. Empty on PoW chains.
+    pub fn validator_set(&self) -> &[String] {
+        self.poa_rotations
+            .last()
+            .map(|(_, set)| set.as_slice())
+            .unwrap_or(&self.params.validators)
+    }
+
+    /// Reads the persisted rotation history back at open time.
+    fn load_poa_rotations(&self) -> Result<Vec<(u64, Vec<String>)>, PaliError> {
+        let cf = self.db.cf_handle(CF_STATE).expect("state cf exists");
+        match self
+            .db
+            .get_cf(cf, POA_VALIDATORS_KEY)
+            .map_err(StorageError::database)?
+        {
+            Some(bytes) => Ok(bincode::deserialize(&bytes)
+                .map_err(|e| StorageError::corrupt("corrupt validator rotation history", e))?),
+            None => Ok(Vec::new()),
+        }
+    }
+
+    /// One-time scan of the UTXO column family populating the
+    /// per-address index.
+    fn build_address_index(&mut self) -> Result<(), PaliError> {
+        let cf = self.db.cf_handle(CF_UTXOS).expect("utxos cf exists");
+        for item in self.db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+            let (key, value) = item.map_err(StorageError::database)?;
+            let outpoint: OutPoint =
+                bincode::deserialize(&key).map_err(|e| StorageError::corrupt("corrupt utxo key", e))?;
+            let entry: UtxoEntry =
+                bincode::deserialize(&value).map_err(|e| StorageError::corrupt("corrupt utxo", e))?;
+            self.address_index
+                .entry(entry.address)
+                .or_default()
+                .insert(outpoint);
+        }
+        Ok(())
+    }
+
+    fn column_families() -> &'static [&'static str] {
+        &[
+            CF_BLOCKS,
+            CF_HEIGHTS,
+            CF_UTXOS,
+            CF_TXINDEX,
+            CF_STATE,
+            CF_DIFFICULTY,
+            CF_SPENT,
+            CF_UNDO,
+            CF_FILTERS,
+            CF_EVENTS,
+        ]
+    }
+
+    /// Builds the genesis block described by `config`. Premine
+    /// allocations become unsigned transactions after the coinbase, one
+    /// per allocation, funded out of thin air exactly once at height 0.
+    pub fn build_genesis(config: &GenesisConfig) -> Result<Block, PaliError> {
+        let message = config.message.as_bytes().to_vec();
+        if message.len() > MAX_COINBASE_DATA {
+            return Err(PaliError::Invalid(format!(
+                "genesis message exceeds {} bytes",
+                MAX_COINBASE_DATA
+            )));
+        }
+        if config.bits > math::MAX_BITS {
+            return Err(PaliError::Invalid(format!(
+                "initial bits 0x{:08x} is easier than the floor 0x{:08x}",
+                config.bits,
+                math::MAX_BITS
+            )));
+        }
+        let mut transactions = vec![Transaction {
+            chain_id: config.chain_id,
+            nonce: 0,
+            from: COINBASE_ADDRESS,
+            to: COINBASE_ADDRESS,
+            amount: 0,
+            fee: 0,
+            data: message,
+            replaceable: false,
+            lock_time: 0,
+            signature: Vec::new(),
+            public_key: Vec::new(),
+        }];
+        let mut total = 0u64;
+        for (i, allocation) in config.premine.iter().enumerate() {
+            let address: Address = hex::decode(&allocation.address)
+                .ok()
+                .and_then(|b| b.try_into().ok())
+                .ok_or_else(|| {
+                    PaliError::Invalid(format!("bad premine address '{}'", allocation.address))
+                })?;
+            total = total
+                .checked_add(allocation.amount)
+                .ok_or_else(|| PaliError::Invalid("premine total overflows".to_string()))?;
+            transactions.push(Transaction {
+                chain_id: config.chain_id,
+                nonce: (i + 1) as u64,
+                from: COINBASE_ADDRESS,
+                to: address,
+                amount: allocation.amount,
+                fee: 0,
+                data: Vec::new(),
+                replaceable: false,
+                lock_time: 0,
+                signature: Vec::new(),
+                public_key: Vec::new(),
+            });
+        }
+        if total > MAX_SUPPLY {
+            return Err(PaliError::Invalid(format!(
+                "premine total {} exceeds MAX_SUPPLY",
+                total
+            )));
+        }
+        let hashes: Vec<Hash256> = transactions.iter().map(|tx| tx.hash()).collect();
+        Ok(Block {
+            header: BlockHeader {
+                version: 1,
+                prev_hash: [0u8; 32],
+                merkle_root: hash::merkle_root(&hashes),
+                timestamp: config.timestamp,
+                bits: config.bits,
+                nonce: 0,
+                height: 0,
+            },
+            transactions,
+        })
+    }
+
+    /// Creates a fresh chain database at `path` seeded with the custom
+    /// genesis block for `config`. Fails if a chain already exists
+    /// there; an existing chain's genesis cannot be replaced.
+    pub fn init_chain<P: AsRef<Path>>(path: P, config: &GenesisConfig) -> Result<Self, PaliError> {
+        let genesis = Self::build_genesis(config)?;
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let cfs = Self::column_families()
+            .iter()
+            .map(|name| ColumnFamilyDescriptor::new(*name, Options::default()))
+            .collect::<Vec<_>>();
+        let db = DB::open_cf_descriptors(&opts, path, cfs)
+            .map_err(|e| StorageError::database(e).context("failed to open chain database"))?;
+        if db
+            .get_cf(db.cf_handle(CF_STATE).expect("state cf exists"), STATE_KEY)
+            .map_err(StorageError::database)?
+            .is_some()
+        {
+            return Err(PaliError::Invalid(
+                "data directory already holds a chain".to_string(),
+            ));
+        }
+        let premine_total = genesis
+            .transactions
+            .iter()
+            .map(|tx| tx.amount)
+            .sum::<u64>();
+        let mut chain = Blockchain {
+            db,
+            cold: None,
+            state: ChainState {
+                best_hash: genesis.hash(),
+                height: 0,
+                total_work: math::block_work(genesis.header.bits),
+                circulating_supply: premine_total,
+            },
+            address_index: HashMap::new(),
+            pow: &DoubleSha256,
+            params: ChainParams::default(),
+            poa_rotations: Vec::new(),
+        };
+        let mut connect = ConnectBatch::new();
+        chain.store_block(&genesis, &mut connect.batch)?;
+        for tx in &genesis.transactions {
+            if tx.amount == 0 {
+                continue;
+            }
+            let outpoint = OutPoint {
+                tx_hash: tx.hash(),
+                index: 0,
+            };
+            let entry = UtxoEntry {
+                address: tx.to,
+                amount: tx.amount,
+                height: 0,
+                is_coinbase: true,
+            };
+            chain.put_utxo(&mut connect, &outpoint, &entry)?;
+        }
+        chain.db.write(connect.batch).map_err(StorageError::database)?;
+        chain.persist_state()?;
+        for (outpoint, entry) in connect.added_utxos {
+            chain
+                .address_index
+                .entry(entry.address)
+                .or_default()
+                .insert(outpoint);
+        }
+        Ok(chain)
+    }
+
+    /// Deterministic genesis block for the given network.
+    pub fn genesis_block(chain_id: u8) -> Block {
+        let message = b"Palicoin genesis - 3 minute blocks, 21M supply".to_vec();
+        let coinbase = Transaction {
+            chain_id,
+            nonce: 0,
+            from: COINBASE_ADDRESS,
+            to: COINBASE_ADDRESS,
+            amount: 0,
+            fee: 0,
+            data: message,
+            replaceable: false,
+            lock_time: 0,
+            signature: Vec::new(),
+            public_key: Vec::new(),
+        };
+        let merkle_root = hash::merkle_root(&[coinbase.hash()]);
+        Block {
+            header: BlockHeader {
+                version: 1,
+                prev_hash: [0u8; 32],
+                merkle_root,
+                timestamp: 1_700_000_000,
+                bits: math::MAX_BITS,
+                nonce: 0,
+                height: 0,
+            },
+            transactions: vec![coinbase],
+        }
+    }
+
+    pub fn state(&self) -> &ChainState {
+        &self.state
+    }
+
+    pub fn height(&self) -> u64 {
+        self.state.height
+    }
+
+    pub fn best_hash(&self) -> Hash256 {
+        self.state.best_hash
+    }
+
+    pub fn get_block(&self, hash: &Hash256) -> Result<Option<Block>, PaliError> {
+        let cf = self.db.cf_handle(CF_BLOCKS).expect("blocks cf exists");
+        let bytes = match self.db.get_cf(cf, hash).map_err(StorageError::database)? {
+            Some(bytes) => Some(bytes),
+            // Miss in the hot tier: the block may have been migrated.
+            None => match &self.cold {
+                Some(cold) => {
+                    let cf = cold.cf_handle(CF_BLOCKS).expect("cold blocks cf exists");
+                    cold.get_cf(cf, hash).map_err(StorageError::database)?
+                }
+                None => None,
+            },
+        };
+        match bytes {
+            Some(bytes) => Ok(Some(
+                bincode::deserialize(&bytes).map_err(|e| StorageError::corrupt("corrupt block", e))?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Opens (or creates) the cold block store at `path`. Call before
+    /// any migration; a chain without one keeps everything hot.
+    pub fn attach_cold_store<P: AsRef<Path>>(&mut self, path: P) -> Result<(), PaliError> {
+        let mut opts = Options::default();
+        opts.create_if_missing(true);
+        opts.create_missing_column_families(true);
+        let cfs = vec![ColumnFamilyDescriptor::new(CF_BLOCKS, Options::default())];
+        let cold = DB::open_cf_descriptors(&opts, path, cfs)
+            .map_err(|e| StorageError::database(e).context("failed to open cold block store"))?;
+        self.cold = Some(cold);
+        Ok(())
+    }
+
+    /// Next height the cold migration will consider.
+    pub fn cold_cursor(&self) -> Result<u64, PaliError> {
+        let cf = self.db.cf_handle(CF_STATE).expect("state cf exists");
+        match self.db.get_cf(cf, COLD_CURSOR_KEY).map_err(StorageError::database)? {
+            Some(bytes) => {
+                let raw: [u8; 8] = bytes
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| StorageError::corrupt_msg("corrupt cold cursor"))?;
+                Ok(u64::from_be_bytes(raw))
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// Moves up to `max_blocks` block bodies older than `min_age_secs`
+    /// into the cold store, walking heights from the saved cursor.
+    /// Each block is written to the cold tier before it leaves the hot
+    /// one, so a crash mid-migration at worst duplicates a block.
+    /// Returns the number moved; zero without a cold store.
+    pub fn migrate_to_cold(&mut self, min_age_secs: u64, max_blocks: usize) -> Result<u64, PaliError> {
+        if self.cold.is_none() {
+            return Ok(0);
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let cutoff = now.saturating_sub(min_age_secs);
+        let mut cursor = self.cold_cursor()?;
+        let mut moved = 0u64;
+        while (moved as usize) < max_blocks && cursor < self.state.height {
+            let Some(hash) = self.get_block_hash(cursor)? else {
+                break;
+            };
+            let hot_cf = self.db.cf_handle(CF_BLOCKS).expect("blocks cf exists");
+            // A hot-tier miss means the block is already cold (a rerun
+            // after a crash): just advance.
+            if let Some(bytes) = self.db.get_cf(hot_cf, hash).map_err(StorageError::database)? {
+                let block: Block =
+                    bincode::deserialize(&bytes).map_err(|e| StorageError::corrupt("corrupt block", e))?;
+                if block.header.timestamp >= cutoff {
+                    break;
+                }
+                let cold = self.cold.as_ref().expect("cold store attached");
+                let cold_cf = cold.cf_handle(CF_BLOCKS).expect("cold blocks cf exists");
+                cold.put_cf(cold_cf, hash, &bytes).map_err(StorageError::database)?;
+                self.db.delete_cf(hot_cf, hash).map_err(StorageError::database)?;
+                moved += 1;
+            }
+            cursor += 1;
+        }
+        let cf = self.db.cf_handle(CF_STATE).expect("state cf exists");
+        self.db
+            .put_cf(cf, COLD_CURSOR_KEY, cursor.to_be_bytes())
+            .map_err(StorageError::database)?;
+        Ok(moved)
+    }
+
+    pub fn get_block_hash(&self, height: u64) -> Result<Option<Hash256>, PaliError> {
+        let cf = self.db.cf_handle(CF_HEIGHTS).expect("heights cf exists");
+        match self
+            .db
+            .get_cf(cf, height.to_be_bytes())
+            .map_err(StorageError::database)?
+        {
+            Some(bytes) => {
+                let mut hash = [0u8; 32];
+                hash.copy_from_slice(&bytes);
+                Ok(Some(hash))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_block_by_height(&self, height: u64) -> Result<Option<Block>, PaliError> {
+        match self.get_block_hash(height)? {
+            Some(hash) => self.get_block(&hash),
+            None => Ok(None),
+        }
+    }
+
+    /// Block locator for sync requests: hashes from the tip backwards,
+    /// the last ten stepping by one, the stride doubling after that,
+    /// always ending at genesis. A peer scans it front to back; the
+    /// first hash on its main chain is the fork point, found in
+    /// O(log height) entries however deep the chains diverged.
+    pub fn block_locator(&self) -> Result<Vec<Hash256>, PaliError> {
+        let mut locator = Vec::new();
+        let mut height = self.state.height;
+        let mut step: u64 = 1;
+        loop {
+            if let Some(hash) = self.get_block_hash(height)? {
+                locator.push(hash);
+            }
+            if height == 0 {
+                break;
+            }
+            if locator.len() >= 10 {
+                step *= 2;
+            }
+            height = height.saturating_sub(step);
+        }
+        Ok(locator)
+    }
+
+    /// Highest locator entry on our main chain, if any. A hash we hold
+    /// only as a stale fork block does not count — blocks after it
+    /// would not connect for the requester.
+    pub fn locate_fork(&self, locator: &[Hash256]) -> Result<Option<Hash256>, PaliError> {
+        for hash in locator {
+            if let Some(block) = self.get_block(hash)? {
+                if self.get_block_hash(block.header.height)? == Some(*hash) {
+                    return Ok(Some(*hash));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Looks up where a transaction was confirmed.
+    pub fn get_tx_location(&self, tx_hash: &Hash256) -> Result<Option<TxLocation>, PaliError> {
+        let cf = self.db.cf_handle(CF_TXINDEX).expect("txindex cf exists");
+        match self.db.get_cf(cf, tx_hash).map_err(StorageError::database)? {
+            Some(bytes) => Ok(Some(
+                bincode::deserialize(&bytes).map_err(|e| StorageError::corrupt("corrupt tx index", e))?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_transaction(&self, tx_hash: &Hash256) -> Result<Option<Transaction>, PaliError> {
+        match self.get_tx_location(tx_hash)? {
+            Some(loc) => {
+                let block = self
+                    .get_block(&loc.block_hash)?
+                    .ok_or_else(|| StorageError::corrupt_msg("tx index points at missing block"))?;
+                Ok(block.transactions.into_iter().nth(loc.index as usize))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn get_utxo(&self, outpoint: &OutPoint) -> Result<Option<UtxoEntry>, PaliError> {
+        let cf = self.db.cf_handle(CF_UTXOS).expect("utxos cf exists");
+        let key = bincode::serialize(outpoint).expect("outpoint serialization cannot fail");
+        match self.db.get_cf(cf, key).map_err(StorageError::database)? {
+            Some(bytes) => Ok(Some(
+                bincode::deserialize(&bytes).map_err(|e| StorageError::corrupt("corrupt utxo", e))?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// All UTXOs held by `address`, answered from the address index in
+    /// O(outputs of that address).
+    pub fn get_utxos_for_address(&self, address: &Address) -> Result<Vec<(OutPoint, UtxoEntry)>, PaliError> {
+        let Some(outpoints) = self.address_index.get(address) else {
+            return Ok(Vec::new());
+        };
+        let mut out = Vec::with_capacity(outpoints.len());
+        for outpoint in outpoints {
+            let entry = self
+                .get_utxo(outpoint)?
+                .ok_or_else(|| StorageError::corrupt_msg("address index references a missing utxo"))?;
+            out.push((*outpoint, entry));
+        }
+        Ok(out)
+    }
+
+    /// Overlay-aware variant of [`get_utxos_for_address`] used while a
+    /// block connect is in flight: merges the committed index with the
+    /// pending additions and removals of the current batch.
+    ///
+    /// [`get_utxos_for_address`]: Self::get_utxos_for_address
+    fn utxos_for_address_at(
+        &self,
+        connect: &ConnectBatch,
+        address: &Address,
+    ) -> Result<Vec<(OutPoint, UtxoEntry)>, PaliError> {
+        let mut out = Vec::new();
+        if let Some(outpoints) = self.address_index.get(address) {
+            for outpoint in outpoints {
+                if connect.removed_utxos.contains_key(outpoint)
+                    || connect.added_utxos.contains_key(outpoint)
+                {
+                    continue;
+                }
+                let entry = self
+                    .get_utxo(outpoint)?
+                    .ok_or_else(|| StorageError::corrupt_msg("address index references a missing utxo"))?;
+                out.push((*outpoint, entry));
+            }
+        }
+        for (outpoint, entry) in &connect.added_utxos {
+            if entry.address == *address {
+                out.push((*outpoint, entry.clone()));
+            }
+        }
+        Ok(out)
+    }
+
+    /// Confirmed balance of an address.
+    pub fn get_balance(&self, address: &Address) -> Result<u64, PaliError> {
+        Ok(self
+            .get_utxos_for_address(address)?
+            .iter()
+            .map(|(_, e)| e.amount)
+            .sum())
+    }
+
+    /// Whether `address` already appears on chain, either holding
+    /// unspent outputs or having sent before (nonce above zero). An
+    /// address whose every output was spent by others can escape this
+    /// check; it is a privacy nudge, not a consensus rule.
+    pub fn address_has_history(&self, address: &Address) -> Result<bool, PaliError> {
+        if self.get_nonce(address)? > 0 {
+            return Ok(true);
+        }
+        Ok(!self.get_utxos_for_address(address)?.is_empty())
+    }
+
+    /// Next account nonce expected from `address`, derived from the tx index.
+    pub fn get_nonce(&self, address: &Address) -> Result<u64, PaliError> {
+        let cf = self.db.cf_handle(CF_STATE).expect("state cf exists");
+        let mut key = b"nonce_".to_vec();
+        key.extend_from_slice(address);
+        match self.db.get_cf(cf, key).map_err(StorageError::database)? {
+            Some(bytes) => {
+                let mut buf = [0u8; 8];
+                buf.copy_from_slice(&bytes);
+                Ok(u64::from_be_bytes(buf))
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// Validates a transaction against the current UTXO set and nonce
+    /// state, without mutating anything. Coinbase transactions are only
+    /// valid inside blocks and are rejected here.
+    pub fn validate_transaction(
+        &self,
+        tx: &Transaction,
+        chain_id: u8,
+    ) -> Result<u64, RejectionReason> {
+        if tx.is_coinbase() {
+            return Err(RejectionReason::CoinbaseOutsideBlock);
+        }
+        if tx.chain_id != chain_id {
+            return Err(RejectionReason::WrongChain);
+        }
+        if tx.amount == 0 {
+            return Err(RejectionReason::ValueOverflow);
+        }
+        if tx.lock_time > self.state.height + 1 {
+            return Err(RejectionReason::NonFinal);
+        }
+        // The signature scheme must be active at the height this
+        // transaction would connect; gate it before verification so a
+        // cached pass can never stand in for activation.
+        self.params
+            .check_signature_type(tx, self.state.height + 1)
+            .map_err(|_| RejectionReason::SignatureTypeNotActive)?;
+        crypto::verify_transaction_signature(tx).map_err(|_| RejectionReason::BadSignature)?;
+        // A payment to the rotation address on a PoA chain is a
+        // validator-set change; only current validators may send one,
+        // and a garbled payload must not connect and then be skipped.
+        if self.params.poa_active() && tx.to == poa::VALIDATOR_ROTATION_ADDRESS {
+            poa::check_rotation(tx, self.validator_set())?;
+        }
+        let expected_nonce = self.get_nonce(&tx.from)?;
+        if tx.nonce != expected_nonce {
+            return Err(RejectionReason::BadNonce {
+                expected: expected_nonce,
+                got: tx.nonce,
+            });
+        }
+        let balance = self.get_balance(&tx.from)?;
+        let needed = tx
+            .amount
+            .checked_add(tx.fee)
+            .ok_or(RejectionReason::ValueOverflow)?;
+        if balance < needed {
+            return Err(RejectionReason::InsufficientFunds);
+        }
+        Ok(tx.fee)
+    }
+
+    /// Full contextual block validation against the current tip.
+    pub fn validate_block(&self, block: &Block, chain_id: u8) -> Result<(), RejectionReason> {
+        let header = &block.header;
+        if header.prev_hash != self.state.best_hash {
+            return Err(RejectionReason::UnknownPrevBlock);
+        }
+        if header.height != self.state.height + 1 {
+            return Err(RejectionReason::BadHeight);
+        }
+        // Under proof-of-authority there is no work to grind or
+        // retarget; the scheduled validator's signature, checked below
+        // once the coinbase is in hand, stands in for both.
+        if !self.params.poa_active() {
+            if header.bits != self.next_bits()? {
+                return Err(RejectionReason::BadDifficultyBits);
+            }
+            if !self.pow.check(header) {
+                return Err(RejectionReason::BadPow);
+            }
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        if header.timestamp > now + 2 * 60 * 60 {
+            return Err(RejectionReason::TimeTooNew);
+        }
+        let tx_hashes: Vec<Hash256> = block.transactions.iter().map(|tx| tx.hash()).collect();
+        if hash::merkle_root(&tx_hashes) != header.merkle_root {
+            return Err(RejectionReason::BadMerkleRoot);
+        }
+        let coinbase = block.coinbase().ok_or(RejectionReason::MissingCoinbase)?;
+        if block.transactions.iter().skip(1).any(|tx| tx.is_coinbase()) {
+            return Err(RejectionReason::MultipleCoinbase);
+        }
+        if coinbase.data.len() > MAX_COINBASE_DATA {
+            return Err(RejectionReason::OversizedCoinbaseData);
+        }
+        // Permissioned PoA: on chains with a configured miner set,
+        // only coinbases paying an authorized key's address connect.
+        if !self.params.miner_allowed(&coinbase.to) {
+            return Err(RejectionReason::UnauthorizedMiner);
+        }
+        if self.params.poa_active() {
+            poa::verify_block(block, self.validator_set())?;
+        }
+        let mut total_fees: u64 = 0;
+        for tx in block.transactions.iter().skip(1) {
+            total_fees = total_fees
+                .checked_add(self.validate_transaction(tx, chain_id)?)
+                .ok_or(RejectionReason::ValueOverflow)?;
+        }
+        let allowed = self.params.block_reward_at(header.height)
+            .checked_add(total_fees)
+            .ok_or(RejectionReason::ValueOverflow)?;
+        if coinbase.amount > allowed {
+            return Err(RejectionReason::BadCoinbaseValue);
+        }
+        // Consensus rule: newly minted coins may never push the
+        // circulating supply past the hard cap.
+        let minted = coinbase.amount.saturating_sub(total_fees);
+        let new_supply = self
+            .state
+            .circulating_supply
+            .checked_add(minted)
+            .ok_or(RejectionReason::SupplyOverflow)?;
+        if new_supply > MAX_SUPPLY {
+            return Err(RejectionReason::SupplyOverflow);
+        }
+        Ok(())
+    }
+
+    /// Difficulty bits the next block must carry.
+    pub fn next_bits(&self) -> Result<u32, PaliError> {
+        let height = self.state.height;
+        if height == 0 || !(height + 1).is_multiple_of(RETARGET_WINDOW) {
+            let tip = self
+                .get_block(&self.state.best_hash)?
+                .ok_or_else(|| StorageError::corrupt_msg("missing tip block"))?;
+            return Ok(tip.header.bits);
+        }
+        let tip = self
+            .get_block(&self.state.best_hash)?
+            .ok_or_else(|| StorageError::corrupt_msg("missing tip block"))?;
+        let window_start = self
+            .get_block_by_height(height + 1 - RETARGET_WINDOW)?
+            .ok_or_else(|| StorageError::corrupt_msg("missing retarget window start"))?;
+        let timespan = tip
+            .header
+            .timestamp
+            .saturating_sub(window_start.header.timestamp)
+            .max(1);
+        Ok(math::next_bits(tip.header.bits, timespan, RETARGET_WINDOW - 1))
+    }
+
+    /// Validates and connects a block to the tip.
+    pub fn add_block(&mut self, block: &Block, chain_id: u8) -> Result<(), RejectionReason> {
+        self.validate_block(block, chain_id)?;
+        self.add_block_internal(block).map_err(RejectionReason::from)
+    }
+
+    /// Connects an already-validated block. Every write — block
+    /// storage, UTXO mutations, indexes and the chain state — lands in
+    /// one atomic WriteBatch, so a crash at any point leaves the
+    /// database at either the old tip or the new one, never in between.
+    fn add_block_internal(&mut self, block: &Block) -> Result<(), PaliError> {
+        let mut connect = ConnectBatch::new();
+        self.store_block(block, &mut connect.batch)?;
+        self.update_utxo_set(block, &mut connect)?;
+        self.store_difficulty_record(block, &mut connect.batch)?;
+        // Record a validator rotation, if the block carries one (the
+        // last in a block wins). Validation already vetted the sender
+        // and payload against the pre-block set.
+        let new_rotations = if self.params.poa_active() {
+            block
+                .transactions
+                .iter()
+                .skip(1)
+                .rev()
+                .find_map(poa::decode_rotation)
+                .map(|rotation| {
+                    let mut history = self.poa_rotations.clone();
+                    history.push((block.header.height, rotation.validators));
+                    history
+                })
+        } else {
+            None
+        };
+        if let Some(history) = &new_rotations {
+            let state_cf = self.db.cf_handle(CF_STATE).expect("state cf exists");
+            connect.batch.put_cf(
+                state_cf,
+                POA_VALIDATORS_KEY,
+                bincode::serialize(history).expect("rotation serialization cannot fail"),
+            );
+        }
+        // The block's compact filter and its chained header ride in
+        // the same atomic batch as the block itself (see the cfilters
+        // module).
+        let filter = cfilters::BlockFilter::build(block);
+        let prev_filter_header = if block.header.height == 0 {
+            [0u8; 32]
+        } else {
+            self.filter_header(&block.header.prev_hash)?
+        };
+        let next_filter_header =
+            cfilters::filter_header(&filter.filter_hash(), &prev_filter_header);
+        let filters_cf = self.db.cf_handle(CF_FILTERS).expect("filters cf exists");
+        connect.batch.put_cf(
+            filters_cf,
+            cfilter_key(&block.hash()),
+            bincode::serialize(&filter).expect("filter serialization cannot fail"),
+        );
+        connect.batch.put_cf(filters_cf, cfheader_key(&block.hash()), next_filter_header);
+        let coinbase_amount = block.coinbase().map(|tx| tx.amount).unwrap_or(0);
+        let fees: u64 = block.transactions.iter().skip(1).map(|tx| tx.fee).sum();
+        let minted = coinbase_amount.saturating_sub(fees);
+        let circulating_supply = self
+            .state
+            .circulating_supply
+            .checked_add(minted)
+            .filter(|supply| *supply <= MAX_SUPPLY)
+            .ok_or(RejectionReason::SupplyOverflow)?;
+        let new_state = ChainState {
+            best_hash: block.hash(),
+            height: block.header.height,
+            total_work: self.state.total_work.saturating_add(self.pow.block_work(block.header.bits)),
+            circulating_supply,
+        };
+        let state_cf = self.db.cf_handle(CF_STATE).expect("state cf exists");
+        connect.batch.put_cf(
+            state_cf,
+            STATE_KEY,
+            bincode::serialize(&new_state).expect("state serialization cannot fail"),
+        );
+
+        let undo_cf = self.db.cf_handle(CF_UNDO).expect("undo cf exists");
+        connect.batch.put_cf(
+            undo_cf,
+            block.hash(),
+            bincode::serialize(&connect.undo).expect("undo serialization cannot fail"),
+        );
+        self.journal_into(
+            &mut connect.batch,
+            EventKind::BlockConnected {
+                hash: block.hash(),
+                height: block.header.height,
+            },
+        )?;
+
+        let ConnectBatch {
+            batch,
+            added_utxos,
+            removed_utxos,
+            ..
+        } = connect;
+        self.db.write(batch).map_err(StorageError::database)?;
+
+        // Only mutate in-memory views once the batch is durable.
+        self.state = new_state;
+        if let Some(history) = new_rotations {
+            self.poa_rotations = history;
+        }
+        for (outpoint, address) in removed_utxos {
+            if let Some(outpoints) = self.address_index.get_mut(&address) {
+                outpoints.remove(&outpoint);
+                if outpoints.is_empty() {
+                    self.address_index.remove(&address);
+                }
+            }
+        }
+        for (outpoint, entry) in added_utxos {
+            self.address_index
+                .entry(entry.address)
+                .or_default()
+                .insert(outpoint);
+        }
+        Ok(())
+    }
+
+    /// The block's compact filter (see the cfilters module). Blocks
+    /// connected before the filter index existed get theirs built and
+    /// cached on first request.
+    pub fn get_filter(
+        &self,
+        block_hash: &Hash256,
+    ) -> Result<Option<cfilters::BlockFilter>, PaliError> {
+        let cf = self.db.cf_handle(CF_FILTERS).expect("filters cf exists");
+        if let Some(bytes) = self
+            .db
+            .get_cf(cf, cfilter_key(block_hash))
+            .map_err(StorageError::database)?
+        {
+            return Ok(Some(bincode::deserialize(&bytes).map_err(|e| {
+                StorageError::corrupt("corrupt block filter", e)
+            })?));
+        }
+        let Some(block) = self.get_block(block_hash)? else {
+            return Ok(None);
+        };
+        let filter = cfilters::BlockFilter::build(&block);
+        self.db
+            .put_cf(
+                cf,
+                cfilter_key(block_hash),
+                bincode::serialize(&filter).expect("filter serialization cannot fail"),
+            )
+            .map_err(StorageError::database)?;
+        Ok(Some(filter))
+    }
+
+    /// The block's chained filter header. For blocks connected before
+    /// the filter index existed, the chain back to the nearest stored
+    /// ancestor (or genesis) is computed and cached in one pass.
+    pub fn filter_header(&self, block_hash: &Hash256) -> Result<Hash256, PaliError> {
+        let cf = self.db.cf_handle(CF_FILTERS).expect("filters cf exists");
+        if let Some(bytes) = self
+            .db
+            .get_cf(cf, cfheader_key(block_hash))
+            .map_err(StorageError::database)?
+        {
+            return bytes
+                .try_into()
+                .map_err(|_| StorageError::corrupt_msg("corrupt filter header").into());
+        }
+        let mut pending = Vec::new();
+        let mut cursor = *block_hash;
+        let mut prev = [0u8; 32];
+        loop {
+            let block = self.get_block(&cursor)?.ok_or_else(|| {
+                StorageError::corrupt_msg("filter header requested for an unknown block")
+            })?;
+            pending.push(cursor);
+            if block.header.height == 0 {
+                break;
+            }
+            if let Some(bytes) = self
+                .db
+                .get_cf(cf, cfheader_key(&block.header.prev_hash))
+                .map_err(StorageError::database)?
+            {
+                prev = bytes
+                    .try_into()
+                    .map_err(|_| StorageError::corrupt_msg("corrupt filter header"))?;
+                break;
+            }
+            cursor = block.header.prev_hash;
+        }
+        for hash in pending.into_iter().rev() {
+            let filter = self
+                .get_filter(&hash)?
+                .expect("block fetched during the backward walk");
+            prev = cfilters::filter_header(&filter.filter_hash(), &prev);
+            self.db
+                .put_cf(cf, cfheader_key(&hash), prev)
+                .map_err(StorageError::database)?;
+        }
+        Ok(prev)
+    }
+
+    /// Appends an event to the journal immediately. Connect and
+    /// disconnect events instead ride in their block's WriteBatch via
+    /// [`journal_into`](Self::journal_into) so the journal can never
+    /// show a block the chain state does not.
+    pub fn journal(&self, kind: EventKind) -> Result<(), PaliError> {
+        let mut batch = rocksdb::WriteBatch::default();
+        self.journal_into(&mut batch, kind)?;
+        self.db.write(batch).map_err(StorageError::database)?;
+        Ok(())
+    }
+
+    /// Stages an event in `batch` under the next free sequence number.
+    fn journal_into(
+        &self,
+        batch: &mut rocksdb::WriteBatch,
+        kind: EventKind,
+    ) -> Result<(), PaliError> {
+        let event = Event {
+            seq: self.next_event_seq()?,
+            timestamp: journal::unix_now(),
+            kind,
+        };
+        let cf = self.db.cf_handle(CF_EVENTS).expect("events cf exists");
+        batch.put_cf(
+            cf,
+            event.seq.to_be_bytes(),
+            bincode::serialize(&event).expect("event serialization cannot fail"),
+        );
+        Ok(())
+    }
+
+    /// One past the highest sequence number on disk. Events are rare
+    /// enough that reading the tail beats carrying a counter that has
+    /// to survive crashes.
+    fn next_event_seq(&self) -> Result<u64, PaliError> {
+        let cf = self.db.cf_handle(CF_EVENTS).expect("events cf exists");
+        match self.db.iterator_cf(cf, rocksdb::IteratorMode::End).next() {
+            Some(item) => {
+                let (key, _) = item.map_err(StorageError::database)?;
+                let seq = u64::from_be_bytes(
+                    key.as_ref()
+                        .try_into()
+                        .map_err(|_| StorageError::corrupt_msg("corrupt journal key"))?,
+                );
+                Ok(seq + 1)
+            }
+            None => Ok(0),
+        }
+    }
+
+    /// Journal entries from `start_seq` on, in write order, capped at
+    /// `limit` and [`journal::MAX_JOURNAL_EVENTS`].
+    pub fn journal_events(&self, start_seq: u64, limit: usize) -> Result<Vec<Event>, PaliError> {
+        let cf = self.db.cf_handle(CF_EVENTS).expect("events cf exists");
+        let mode = rocksdb::IteratorMode::From(
+            &start_seq.to_be_bytes(),
+            rocksdb::Direction::Forward,
+        );
+        let mut events = Vec::new();
+        for item in self.db.iterator_cf(cf, mode) {
+            if events.len() >= limit.min(journal::MAX_JOURNAL_EVENTS) {
+                break;
+            }
+            let (_, value) = item.map_err(StorageError::database)?;
+            events.push(
+                bincode::deserialize(&value)
+                    .map_err(|e| StorageError::corrupt("corrupt journal entry", e))?,
+            );
+        }
+        Ok(events)
+    }
+
+    /// Undo record for a connected block, if one was stored.
+    pub fn get_undo(&self, block_hash: &Hash256) -> Result<Option<BlockUndo>, PaliError> {
+        let cf = self.db.cf_handle(CF_UNDO).expect("undo cf exists");
+        match self.db.get_cf(cf, block_hash).map_err(StorageError::database)? {
+            Some(bytes) => Ok(Some(
+                bincode::deserialize(&bytes).map_err(|e| StorageError::corrupt("corrupt undo record", e))?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    /// Disconnects the tip block using its undo record, restoring the
+    /// spent outputs, deleting the created ones and rewinding nonces
+    /// and chain state — all in one atomic WriteBatch. The block body
+    /// stays in CF_BLOCKS so a reorg can keep serving it. Returns the
+    /// disconnected block.
+    pub fn disconnect_tip(&mut self) -> Result<Block, PaliError> {
+        if self.state.height == 0 {
+            return Err(PaliError::Invalid(
+                "cannot disconnect the genesis block".to_string(),
+            ));
+        }
+        let tip_hash = self.state.best_hash;
+        let block = self
+            .get_block(&tip_hash)?
+            .ok_or_else(|| StorageError::corrupt_msg("tip block missing from storage"))?;
+        let undo = self
+            .get_undo(&tip_hash)?
+            .ok_or_else(|| StorageError::corrupt_msg("tip block has no undo record"))?;
+
+        let mut batch = rocksdb::WriteBatch::default();
+        let utxos = self.db.cf_handle(CF_UTXOS).expect("utxos cf exists");
+        let spent = self.db.cf_handle(CF_SPENT).expect("spent cf exists");
+        for outpoint in &undo.created {
+            batch.delete_cf(
+                utxos,
+                bincode::serialize(outpoint).expect("outpoint serialization cannot fail"),
+            );
+        }
+        for (outpoint, entry) in &undo.spent {
+            let key = bincode::serialize(outpoint).expect("outpoint serialization cannot fail");
+            batch.put_cf(
+                utxos,
+                &key,
+                bincode::serialize(entry).expect("utxo serialization cannot fail"),
+            );
+            batch.delete_cf(spent, &key);
+        }
+        let state_cf = self.db.cf_handle(CF_STATE).expect("state cf exists");
+        for (address, nonce) in &undo.prior_nonces {
+            let mut key = b"nonce_".to_vec();
+            key.extend_from_slice(address);
+            batch.put_cf(state_cf, key, nonce.to_be_bytes());
+        }
+        // Rewind any validator rotation this block carried, restoring
+        // the set its parent ran under.
+        let rewound_rotations = if self
+            .poa_rotations
+            .last()
+            .is_some_and(|(height, _)| *height == block.header.height)
+        {
+            let mut history = self.poa_rotations.clone();
+            history.pop();
+            if history.is_empty() {
+                batch.delete_cf(state_cf, POA_VALIDATORS_KEY);
+            } else {
+                batch.put_cf(
+                    state_cf,
+                    POA_VALIDATORS_KEY,
+                    bincode::serialize(&history).expect("rotation serialization cannot fail"),
+                );
+            }
+            Some(history)
+        } else {
+            None
+        };
+        let heights = self.db.cf_handle(CF_HEIGHTS).expect("heights cf exists");
+        batch.delete_cf(heights, block.header.height.to_be_bytes());
+        let txindex = self.db.cf_handle(CF_TXINDEX).expect("txindex cf exists");
+        for tx in &block.transactions {
+            batch.delete_cf(txindex, tx.hash());
+        }
+        let difficulty = self.db.cf_handle(CF_DIFFICULTY).expect("difficulty cf exists");
+        batch.delete_cf(difficulty, block.header.height.to_be_bytes());
+        let undo_cf = self.db.cf_handle(CF_UNDO).expect("undo cf exists");
+        batch.delete_cf(undo_cf, tip_hash);
+        let filters_cf = self.db.cf_handle(CF_FILTERS).expect("filters cf exists");
+        batch.delete_cf(filters_cf, cfilter_key(&tip_hash));
+        batch.delete_cf(filters_cf, cfheader_key(&tip_hash));
+        self.journal_into(
+            &mut batch,
+            EventKind::BlockDisconnected {
+                hash: tip_hash,
+                height: block.header.height,
+            },
+        )?;
+
+        let coinbase_amount = block.coinbase().map(|tx| tx.amount).unwrap_or(0);
+        let fees: u64 = block.transactions.iter().skip(1).map(|tx| tx.fee).sum();
+        let minted = coinbase_amount.saturating_sub(fees);
+        let new_state = ChainState {
+            best_hash: block.header.prev_hash,
+            height: block.header.height - 1,
+            total_work: self
+                .state
+                .total_work
+                .saturating_sub(self.pow.block_work(block.header.bits)),
+            circulating_supply: self.state.circulating_supply.saturating_sub(minted),
+        };
+        batch.put_cf(
+            state_cf,
+            STATE_KEY,
+            bincode::serialize(&new_state).expect("state serialization cannot fail"),
+        );
+        self.db.write(batch).map_err(StorageError::database)?;
+
+        self.state = new_state;
+        if let Some(history) = rewound_rotations {
+            self.poa_rotations = history;
+        }
+        // Created outputs always pay tx.to (index 0) or return change
+        // to tx.from (index 1), so their index entries are addressable
+        // without a scan.
+        for tx in &block.transactions {
+            let tx_hash = tx.hash();
+            for (index, address) in [(0u32, tx.to), (1u32, tx.from)] {
+                let outpoint = OutPoint { tx_hash, index };
+                if !undo.created.contains(&outpoint) {
+                    continue;
+                }
+                if let Some(outpoints) = self.address_index.get_mut(&address) {
+                    outpoints.remove(&outpoint);
+                    if outpoints.is_empty() {
+                        self.address_index.remove(&address);
+                    }
+                }
+            }
+        }
+        for (outpoint, entry) in &undo.spent {
+            self.address_index
+                .entry(entry.address)
+                .or_default()
+                .insert(*outpoint);
+        }
+        Ok(block)
+    }
+
+    fn store_block(&self, block: &Block, batch: &mut rocksdb::WriteBatch) -> Result<(), PaliError> {
+        let hash = block.hash();
+        let bytes = bincode::serialize(block).expect("block serialization cannot fail");
+        let blocks = self.db.cf_handle(CF_BLOCKS).expect("blocks cf exists");
+        batch.put_cf(blocks, hash, bytes);
+        let heights = self.db.cf_handle(CF_HEIGHTS).expect("heights cf exists");
+        batch.put_cf(heights, block.header.height.to_be_bytes(), hash);
+        let txindex = self.db.cf_handle(CF_TXINDEX).expect("txindex cf exists");
+        for (i, tx) in block.transactions.iter().enumerate() {
+            let loc = TxLocation {
+                block_hash: hash,
+                height: block.header.height,
+                index: i as u32,
+            };
+            batch.put_cf(
+                txindex,
+                tx.hash(),
+                bincode::serialize(&loc).expect("location serialization cannot fail"),
+            );
+        }
+        Ok(())
+    }
+
+    fn store_difficulty_record(
+        &self,
+        block: &Block,
+        batch: &mut rocksdb::WriteBatch,
+    ) -> Result<(), PaliError> {
+        let parent_ts = self
+            .get_block(&block.header.prev_hash)?
+            .map(|b| b.header.timestamp)
+            .unwrap_or(block.header.timestamp);
+        let record = DifficultyRecord {
+            bits: block.header.bits,
+            difficulty: math::difficulty(block.header.bits),
+            solve_time: block.header.timestamp.saturating_sub(parent_ts),
+            work: self.pow.block_work(block.header.bits),
+            timestamp: block.header.timestamp,
+        };
+        let cf = self.db.cf_handle(CF_DIFFICULTY).expect("difficulty cf exists");
+        batch.put_cf(
+            cf,
+            block.header.height.to_be_bytes(),
+            bincode::serialize(&record).expect("record serialization cannot fail"),
+        );
+        Ok(())
+    }
+
+    /// Difficulty samples for heights `start..=end` (capped at the tip).
+    pub fn get_difficulty_history(
+        &self,
+        start: u64,
+        end: u64,
+    ) -> Result<Vec<(u64, DifficultyRecord)>, PaliError> {
+        let cf = self.db.cf_handle(CF_DIFFICULTY).expect("difficulty cf exists");
+        let end = end.min(self.state.height);
+        let mut out = Vec::new();
+        for height in start..=end {
+            if let Some(bytes) = self
+                .db
+                .get_cf(cf, height.to_be_bytes())
+                .map_err(StorageError::database)?
+            {
+                let record: DifficultyRecord = bincode::deserialize(&bytes)
+                    .map_err(|e| StorageError::corrupt("corrupt difficulty record", e))?;
+                out.push((height, record));
+            }
+        }
+        Ok(out)
+    }
+
+    /// Estimated network hashrate over the last `window` blocks.
+    pub fn estimated_hashrate(&self, window: u64) -> Result<f64, PaliError> {
+        let tip = self.state.height;
+        if tip == 0 || window == 0 {
+            return Ok(0.0);
+        }
+        let start = tip.saturating_sub(window.min(tip) - 1);
+        let records = self.get_difficulty_history(start, tip)?;
+        let total_work: u128 = records.iter().map(|(_, r)| r.work).sum();
+        let elapsed: u64 = records.iter().map(|(_, r)| r.solve_time).sum();
+        Ok(math::calculate_hashrate(total_work, elapsed.max(1)))
+    }
+
+    /// Applies a block's transactions to the UTXO set: spends the
+    /// sender's outputs oldest-first, credits the recipient, and returns
+    /// change to the sender.
+    fn update_utxo_set(&mut self, block: &Block, connect: &mut ConnectBatch) -> Result<(), PaliError> {
+        let height = block.header.height;
+        for tx in &block.transactions {
+            let tx_hash = tx.hash();
+            if tx.is_coinbase() {
+                if tx.amount > 0 {
+                    self.put_utxo(
+                        connect,
+                        &OutPoint { tx_hash, index: 0 },
+                        &UtxoEntry {
+                            address: tx.to,
+                            amount: tx.amount,
+                            height,
+                            is_coinbase: true,
+                        },
+                    )?;
+                }
+                continue;
+            }
+            let needed = tx
+                .amount
+                .checked_add(tx.fee)
+                .ok_or(RejectionReason::ValueOverflow)?;
+            let mut utxos = self.utxos_for_address_at(connect, &tx.from)?;
+            utxos.sort_by_key(|(_, e)| e.height);
+            let mut gathered: u64 = 0;
+            for (outpoint, entry) in &utxos {
+                if gathered >= needed {
+                    break;
+                }
+                if entry.is_coinbase && height < entry.height + COINBASE_MATURITY {
+                    continue;
+                }
+                self.delete_utxo(connect, outpoint, entry)?;
+                self.record_spend(&mut connect.batch, outpoint, &tx_hash, height)?;
+                gathered = gathered
+                    .checked_add(entry.amount)
+                    .ok_or(RejectionReason::ValueOverflow)?;
+            }
+            if gathered < needed {
+                return Err(RejectionReason::InsufficientFunds.into());
+            }
+            self.put_utxo(
+                connect,
+                &OutPoint { tx_hash, index: 0 },
+                &UtxoEntry {
+                    address: tx.to,
+                    amount: tx.amount,
+                    height,
+                    is_coinbase: false,
+                },
+            )?;
+            let change = gathered - needed;
+            if change > 0 {
+                self.put_utxo(
+                    connect,
+                    &OutPoint { tx_hash, index: 1 },
+                    &UtxoEntry {
+                        address: tx.from,
+                        amount: change,
+                        height,
+                        is_coinbase: false,
+                    },
+                )?;
+            }
+            if !connect.undo.prior_nonces.iter().any(|(a, _)| *a == tx.from) {
+                connect.undo.prior_nonces.push((tx.from, tx.nonce));
+            }
+            self.bump_nonce(&mut connect.batch, &tx.from, tx.nonce + 1)?;
+        }
+        Ok(())
+    }
+
+    fn put_utxo(
+        &self,
+        connect: &mut ConnectBatch,
+        outpoint: &OutPoint,
+        entry: &UtxoEntry,
+    ) -> Result<(), PaliError> {
+        let cf = self.db.cf_handle(CF_UTXOS).expect("utxos cf exists");
+        connect.batch.put_cf(
+            cf,
+            bincode::serialize(outpoint).expect("outpoint serialization cannot fail"),
+            bincode::serialize(entry).expect("utxo serialization cannot fail"),
+        );
+        connect.added_utxos.insert(*outpoint, entry.clone());
+        connect.removed_utxos.remove(outpoint);
+        connect.undo.created.push(*outpoint);
+        Ok(())
+    }
+
+    fn delete_utxo(
+        &self,
+        connect: &mut ConnectBatch,
+        outpoint: &OutPoint,
+        entry: &UtxoEntry,
+    ) -> Result<(), PaliError> {
+        let cf = self.db.cf_handle(CF_UTXOS).expect("utxos cf exists");
+        connect.batch.delete_cf(
+            cf,
+            bincode::serialize(outpoint).expect("outpoint serialization cannot fail"),
+        );
+        connect.added_utxos.remove(outpoint);
+        connect.removed_utxos.insert(*outpoint, entry.address);
+        connect.undo.spent.push((*outpoint, entry.clone()));
+        Ok(())
+    }
+
+    fn record_spend(
+        &self,
+        batch: &mut rocksdb::WriteBatch,
+        outpoint: &OutPoint,
+        spending_tx: &Hash256,
+        height: u64,
+    ) -> Result<(), PaliError> {
+        let cf = self.db.cf_handle(CF_SPENT).expect("spent cf exists");
+        let info = SpendingInfo {
+            spending_tx: *spending_tx,
+            height,
+        };
+        batch.put_cf(
+            cf,
+            bincode::serialize(outpoint).expect("outpoint serialization cannot fail"),
+            bincode::serialize(&info).expect("spending info serialization cannot fail"),
+        );
+        Ok(())
+    }
+
+    /// Looks up which transaction spent `outpoint`, if any.
+    pub fn get_spending_info(&self, outpoint: &OutPoint) -> Result<Option<SpendingInfo>, PaliError> {
+        let cf = self.db.cf_handle(CF_SPENT).expect("spent cf exists");
+        let key = bincode::serialize(outpoint).expect("outpoint serialization cannot fail");
+        match self.db.get_cf(cf, key).map_err(StorageError::database)? {
+            Some(bytes) => Ok(Some(
+                bincode::deserialize(&bytes)
+                    .map_err(|e| StorageError::corrupt("corrupt spending info", e))?,
+            )),
+            None => Ok(None),
+        }
+    }
+
+    fn bump_nonce(
+        &self,
+        batch: &mut rocksdb::WriteBatch,
+        address: &Address,
+        nonce: u64,
+    ) -> Result<(), PaliError> {
+        let cf = self.db.cf_handle(CF_STATE).expect("state cf exists");
+        let mut key = b"nonce_".to_vec();
+        key.extend_from_slice(address);
+        batch.put_cf(cf, key, nonce.to_be_bytes());
+        Ok(())
+    }
+
+    fn persist_state(&self) -> Result<(), PaliError> {
+        let cf = self.db.cf_handle(CF_STATE).expect("state cf exists");
+        self.db
+            .put_cf(
+                cf,
+                STATE_KEY,
+                bincode::serialize(&self.state).expect("state serialization cannot fail"),
+            )
+            .map_err(StorageError::database)
+            .map_err(PaliError::from)
+    }
+
+    /// Writes a JSON snapshot of the chain state for operator backups.
+    pub fn backup_to_file<P: AsRef<Path>>(&self, path: P) -> Result<(), PaliError> {
+        let snapshot = serde_json::json!({
+            "best_hash": hex::encode(self.state.best_hash),
+            "height": self.state.height,
+            "total_work": self.state.total_work.to_string(),
+            "circulating_supply": self.state.circulating_supply,
+        });
+        std::fs::write(path, serde_json::to_vec_pretty(&snapshot).expect("json"))
+            .map_err(|e| StorageError::io("failed to write backup", e))
+            .map_err(PaliError::from)
+    }
+
+    /// Creates a consistent RocksDB checkpoint (hard-linked SSTs) at
+    /// `path`, suitable for archival by the backup module.
+    pub fn checkpoint_to<P: AsRef<Path>>(&self, path: P) -> Result<(), PaliError> {
+        let checkpoint = rocksdb::checkpoint::Checkpoint::new(&self.db)
+            .map_err(|e| StorageError::database(e).context("failed to create checkpoint object"))?;
+        checkpoint
+            .create_checkpoint(path)
+            .map_err(|e| StorageError::database(e).context("failed to write checkpoint"))
+            .map_err(PaliError::from)
+    }
+
+    /// Verifies up to `max_blocks` consecutive blocks starting at
+    /// `start_height`: the height index, the block body's own hash,
+    /// its merkle commitment, and every transaction's index entry.
+    /// Bodies and hashes can only be reported — nothing else holds the
+    /// data to rebuild them — but a missing or wrong tx-index entry is
+    /// rewritten from the block on the spot, before a reorg or a
+    /// spender lookup trips over it.
+    pub fn scrub_blocks(
+        &self,
+        start_height: u64,
+        max_blocks: usize,
+    ) -> Result<ScrubBatch, PaliError> {
+        let tip = self.state.height;
+        let mut findings = Vec::new();
+        let mut height = start_height.min(tip);
+        let mut checked = 0u64;
+        while checked < max_blocks as u64 && height <= tip {
+            self.scrub_one(height, &mut findings)?;
+            checked += 1;
+            height += 1;
+        }
+        Ok(ScrubBatch {
+            checked,
+            next_height: if height > tip { 0 } else { height },
+            findings,
+        })
+    }
+
+    /// Scrubs a single height, appending findings. Database errors
+    /// still abort — a failing disk is not something to scrub around.
+    fn scrub_one(&self, height: u64, findings: &mut Vec<ScrubFinding>) -> Result<(), PaliError> {
+        let Some(hash) = self.get_block_hash(height)? else {
+            findings.push(ScrubFinding {
+                height,
+                detail: "height index has no entry".to_string(),
+                repaired: false,
+            });
+            return Ok(());
+        };
+        let block = match self.get_block(&hash) {
+            Ok(Some(block)) => block,
+            Ok(None) => {
+                findings.push(ScrubFinding {
+                    height,
+                    detail: "block body missing for indexed hash".to_string(),
+                    repaired: false,
+                });
+                return Ok(());
+            }
+            Err(PaliError::Storage(e)) if e.kind == StorageErrorKind::Corrupt => {
+                findings.push(ScrubFinding {
+                    height,
+                    detail: format!("block body does not deserialize: {}", e),
+                    repaired: false,
+                });
+                return Ok(());
+            }
+            Err(e) => return Err(e),
+        };
+        if block.hash() != hash {
+            findings.push(ScrubFinding {
+                height,
+                detail: "stored body does not hash to its index entry".to_string(),
+                repaired: false,
+            });
+            return Ok(());
+        }
+        if block.header.height != height {
+            findings.push(ScrubFinding {
+                height,
+                detail: format!("body claims height {}", block.header.height),
+                repaired: false,
+            });
+            return Ok(());
+        }
+        let tx_hashes: Vec<Hash256> = block.transactions.iter().map(|tx| tx.hash()).collect();
+        if hash::merkle_root(&tx_hashes) != block.header.merkle_root {
+            findings.push(ScrubFinding {
+                height,
+                detail: "merkle root does not commit to the stored transactions".to_string(),
+                repaired: false,
+            });
+            return Ok(());
+        }
+        let txindex = self.db.cf_handle(CF_TXINDEX).expect("txindex cf exists");
+        for (i, tx_hash) in tx_hashes.iter().enumerate() {
+            let good = matches!(
+                self.get_tx_location(tx_hash),
+                Ok(Some(loc)) if loc.block_hash == hash && loc.height == height && loc.index == i as u32
+            );
+            if good {
+                continue;
+            }
+            let loc = TxLocation {
+                block_hash: hash,
+                height,
+                index: i as u32,
+            };
+            self.db
+                .put_cf(
+                    txindex,
+                    tx_hash,
+                    bincode::serialize(&loc).expect("location serialization cannot fail"),
+                )
+                .map_err(StorageError::database)?;
+            findings.push(ScrubFinding {
+                height,
+                detail: format!("tx index entry for {} rebuilt", hex::encode(tx_hash)),
+                repaired: true,
+            });
+        }
+        Ok(())
+    }
+
+    /// Runs a manual full compaction across all column families.
+    pub fn compact_database(&self) {
+        for name in Self::column_families() {
+            if let Some(cf) = self.db.cf_handle(name) {
+                self.db.compact_range_cf(cf, None::<&[u8]>, None::<&[u8]>);
+            }
+        }
+    }
+
+    /// Samples RocksDB storage properties for every column family.
+    pub fn storage_info(&self) -> Vec<CfStorageStats> {
+        let property = |cf, name| {
+            self.db
+                .property_int_value_cf(cf, name)
+                .ok()
+                .flatten()
+                .unwrap_or(0)
+        };
+        Self::column_families()
+            .iter()
+            .filter_map(|name| {
+                let cf = self.db.cf_handle(name)?;
+                Some(CfStorageStats {
+                    name: name.to_string(),
+                    sst_files_size: property(cf, "rocksdb.total-sst-files-size"),
+                    live_data_size: property(cf, "rocksdb.estimate-live-data-size"),
+                    num_keys: property(cf, "rocksdb.estimate-num-keys"),
+                    pending_compaction_bytes: property(
+                        cf,
+                        "rocksdb.estimate-pending-compaction-bytes",
+                    ),
+                })
+            })
+            .collect()
+    }
+
+    /// Full scan of the UTXO set for supply audits: totals plus a
+    /// chained hash over every (outpoint, entry) pair in key order, so
+    /// two nodes at the same tip can compare sets with one value.
+    pub fn utxo_set_info(&self) -> Result<UtxoSetInfo, PaliError> {
+        let cf = self.db.cf_handle(CF_UTXOS).expect("utxos cf exists");
+        let mut txouts = 0u64;
+        let mut total_amount = 0u64;
+        let mut acc = [0u8; 32];
+        for item in self.db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+            let (key, value) = item.map_err(StorageError::database)?;
+            let entry: UtxoEntry =
+                bincode::deserialize(&value).map_err(|e| StorageError::corrupt("corrupt utxo", e))?;
+            txouts += 1;
+            total_amount = total_amount
+                .checked_add(entry.amount)
+                .ok_or(RejectionReason::ValueOverflow)?;
+            let mut buf = acc.to_vec();
+            buf.extend_from_slice(&key);
+            buf.extend_from_slice(&value);
+            acc = hash::sha256(&buf);
+        }
+        Ok(UtxoSetInfo {
+            height: self.state.height,
+            best_hash: self.state.best_hash,
+            txouts,
+            total_amount,
+            hash_serialized: acc,
+        })
+    }
+
+    /// Mempool-style balance view: confirmed UTXOs only, grouped per
+    /// address, for debugging and explorer endpoints.
+    pub fn utxo_summary(&self) -> Result<HashMap<String, u64>, PaliError> {
+        let cf = self.db.cf_handle(CF_UTXOS).expect("utxos cf exists");
+        let mut out: HashMap<String, u64> = HashMap::new();
+        for item in self.db.iterator_cf(cf, rocksdb::IteratorMode::Start) {
+            let (_, value) = item.map_err(StorageError::database)?;
+            let entry: UtxoEntry =
+                bincode::deserialize(&value).map_err(|e| StorageError::corrupt("corrupt utxo", e))?;
+            *out.entry(hex::encode(entry.address)).or_default() += entry.amount;
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,239 @@
+//! Bidirectional payment channels layered over on-chain transactions.
+//!
+//! Funds are locked at a joint address derived from the EC point sum of
+//! both parties' public keys, so neither side can spend them alone.
+//! Off-chain, the parties exchange balance states signed by both keys
+//! with strictly increasing sequence numbers; only the highest-sequence
+//! mutually-signed state is authoritative. A cooperative close settles
+//! immediately; a unilateral close produces settlement transactions
+//! locked for [`DISPUTE_WINDOW`] blocks, during which the counterparty
+//! can present a higher-sequence state and supersede the closer's.
+
+use secp256k1::{ecdsa::Signature, Message, PublicKey, Secp256k1, SecretKey};
+use serde::{Deserialize, Serialize};
+
+use crate::hash;
+use crate::types::{Address, Hash256, OutPoint, Transaction};
+
+/// Blocks a unilateral close must wait before its settlement matures,
+/// giving the counterparty time to dispute with a newer state.
+pub const DISPUTE_WINDOW: u64 = 144;
+
+/// Domain tag mixed into state digests so a channel-state signature can
+/// never be replayed as a transaction signature (or vice versa).
+const STATE_TAG: &[u8] = b"pali-channel-state";
+
+/// The joint address holding channel funds: the address of the combined
+/// public key `a + b`. Point addition commutes, so both parties derive
+/// the same address regardless of argument order.
+pub fn channel_address(a: &PublicKey, b: &PublicKey) -> Result<Address, String> {
+    let combined = a
+        .combine(b)
+        .map_err(|_| "public keys sum to the point at infinity".to_string())?;
+    Ok(hash::pubkey_to_address(&combined.serialize()))
+}
+
+/// One off-chain balance snapshot. Balances always sum to the channel
+/// capacity; value moves by shifting the split and bumping `sequence`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChannelState {
+    /// Hash of the funding transaction.
+    pub channel_id: Hash256,
+    pub sequence: u64,
+    pub balance_a: u64,
+    pub balance_b: u64,
+}
+
+impl ChannelState {
+    /// The digest both parties sign: domain tag plus the bincode form.
+    pub fn signing_hash(&self) -> Hash256 {
+        let mut buf = STATE_TAG.to_vec();
+        buf.extend(bincode::serialize(self).expect("state serialization cannot fail"));
+        hash::double_sha256(&buf)
+    }
+}
+
+/// A state carrying both parties' signatures over its digest.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignedState {
+    pub state: ChannelState,
+    pub sig_a: Vec<u8>,
+    pub sig_b: Vec<u8>,
+}
+
+impl SignedState {
+    /// Checks both signatures against the parties' public keys.
+    pub fn verify(&self, pubkey_a: &PublicKey, pubkey_b: &PublicKey) -> Result<(), String> {
+        verify_state_signature(&self.state, &self.sig_a, pubkey_a)
+            .map_err(|e| format!("party A: {}", e))?;
+        verify_state_signature(&self.state, &self.sig_b, pubkey_b)
+            .map_err(|e| format!("party B: {}", e))
+    }
+}
+
+/// Signs a channel state, returning the 64-byte compact signature.
+pub fn sign_state(state: &ChannelState, key: &SecretKey) -> Vec<u8> {
+    let secp = Secp256k1::signing_only();
+    let msg = Message::from_digest(state.signing_hash());
+    secp.sign_ecdsa(&msg, key).serialize_compact().to_vec()
+}
+
+/// Verifies one party's signature over a channel state.
+pub fn verify_state_signature(
+    state: &ChannelState,
+    signature: &[u8],
+    pubkey: &PublicKey,
+) -> Result<(), String> {
+    let secp = Secp256k1::verification_only();
+    let sig = Signature::from_compact(signature)
+        .map_err(|e| format!("malformed signature: {}", e))?;
+    let msg = Message::from_digest(state.signing_hash());
+    secp.verify_ecdsa(&msg, &sig, pubkey)
+        .map_err(|_| "invalid state signature".to_string())
+}
+
+/// One party's view of an open channel: the funding outpoint, both
+/// public keys and the best mutually-signed state seen so far.
+#[derive(Debug, Clone)]
+pub struct Channel {
+    pub id: Hash256,
+    pub funding: OutPoint,
+    pub capacity: u64,
+    pub pubkey_a: PublicKey,
+    pub pubkey_b: PublicKey,
+    /// `None` until the first update; the implicit initial split pays
+    /// the whole capacity back to party A (the funder).
+    pub best: Option<SignedState>,
+}
+
+impl Channel {
+    /// Opens a channel over a funding transaction paying the joint
+    /// address of `pubkey_a` (the funder) and `pubkey_b`.
+    pub fn open(
+        funding_tx: &Transaction,
+        pubkey_a: PublicKey,
+        pubkey_b: PublicKey,
+    ) -> Result<Self, String> {
+        let joint = channel_address(&pubkey_a, &pubkey_b)?;
+        if funding_tx.to != joint {
+            return Err("funding transaction does not pay the channel address".to_string());
+        }
+        if funding_tx.amount == 0 {
+            return Err("funding transaction carries no value".to_string());
+        }
+        Ok(Channel {
+            id: funding_tx.hash(),
+            funding: OutPoint {
+                tx_hash: funding_tx.hash(),
+                index: 0,
+            },
+            capacity: funding_tx.amount,
+            pubkey_a,
+            pubkey_b,
+            best: None,
+        })
+    }
+
+    /// The sequence number the next update must carry.
+    pub fn next_sequence(&self) -> u64 {
+        self.best.as_ref().map_or(1, |s| s.state.sequence + 1)
+    }
+
+    /// Drafts the next state with the given split, ready for signing by
+    /// both parties.
+    pub fn propose_update(&self, balance_a: u64, balance_b: u64) -> Result<ChannelState, String> {
+        if balance_a
+            .checked_add(balance_b)
+            .is_none_or(|sum| sum != self.capacity)
+        {
+            return Err(format!(
+                "balances must sum to the channel capacity {}",
+                self.capacity
+            ));
+        }
+        Ok(ChannelState {
+            channel_id: self.id,
+            sequence: self.next_sequence(),
+            balance_a,
+            balance_b,
+        })
+    }
+
+    /// Accepts a fully signed state, replacing the current best. A state
+    /// with a stale or equal sequence is rejected — this is also the
+    /// dispute path: a counterparty answering a unilateral close simply
+    /// applies its newer state.
+    pub fn apply_update(&mut self, signed: SignedState) -> Result<(), String> {
+        if signed.state.channel_id != self.id {
+            return Err("state belongs to a different channel".to_string());
+        }
+        if signed
+            .state
+            .balance_a
+            .checked_add(signed.state.balance_b)
+            .is_none_or(|sum| sum != self.capacity)
+        {
+            return Err("state balances do not sum to the channel capacity".to_string());
+        }
+        if signed.state.sequence < self.next_sequence() {
+            return Err(format!(
+                "stale state: sequence {} is not newer than {}",
+                signed.state.sequence,
+                self.next_sequence() - 1
+            ));
+        }
+        signed.verify(&self.pubkey_a, &self.pubkey_b)?;
+        self.best = Some(signed);
+        Ok(())
+    }
+
+    /// Builds the unsigned settlement transactions paying out `state`.
+    /// A cooperative close passes `close_height: None` (spendable at
+    /// once); a unilateral close passes the current height, pushing the
+    /// lock time past the dispute window. Each party's fee comes out of
+    /// its own balance; spending the joint address requires both keys.
+    pub fn settlement_transactions(
+        &self,
+        state: &ChannelState,
+        chain_id: u8,
+        starting_nonce: u64,
+        fee: u64,
+        close_height: Option<u64>,
+    ) -> Result<Vec<Transaction>, String> {
+        let joint = channel_address(&self.pubkey_a, &self.pubkey_b)?;
+        let lock_time = close_height.map_or(0, |h| h + DISPUTE_WINDOW);
+        let payouts = [
+            (
+                hash::pubkey_to_address(&self.pubkey_a.serialize()),
+                state.balance_a,
+            ),
+            (
+                hash::pubkey_to_address(&self.pubkey_b.serialize()),
+                state.balance_b,
+            ),
+        ];
+        let mut out = Vec::new();
+        for (to, balance) in payouts {
+            if balance == 0 {
+                continue;
+            }
+            let amount = balance
+                .checked_sub(fee)
+                .ok_or_else(|| format!("balance {} cannot cover the fee {}", balance, fee))?;
+            out.push(Transaction {
+                chain_id,
+                nonce: starting_nonce + out.len() as u64,
+                from: joint,
+                to,
+                amount,
+                fee,
+                data: Vec::new(),
+                replaceable: false,
+                lock_time,
+                signature: Vec::new(),
+                public_key: Vec::new(),
+            });
+        }
+        Ok(out)
+    }
+}
@@ -0,0 +1,281 @@
+//! Typed client for the node's JSON-RPC interface.
+//!
+//! Wraps the HTTP plumbing — connection pooling (via the shared
+//! reqwest client), authentication headers and retry with exponential
+//! backoff — behind typed methods, so services integrating Palicoin
+//! don't hand-roll RPC calls. [`RpcClient`] is async; [`BlockingRpcClient`]
+//! wraps it in its own runtime for synchronous callers. `call` remains
+//! available as the untyped escape hatch for methods not mirrored here.
+
+use std::time::Duration;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::types::{Hash256, Transaction};
+
+/// Transport-level retry policy. Retries only connection failures;
+/// RPC-level errors are returned immediately.
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first.
+    pub attempts: u32,
+    /// Delay before the first retry; doubles each time.
+    pub base_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            attempts: 3,
+            base_delay: Duration::from_millis(250),
+        }
+    }
+}
+
+/// Credentials attached to every request.
+#[derive(Debug, Clone, Default)]
+pub enum Auth {
+    #[default]
+    None,
+    /// rpcuser/rpcpassword HTTP Basic pair.
+    Basic(String, String),
+    /// Bearer token issued via --rpc-token.
+    Token(String),
+}
+
+/// `getinfo` response.
+#[derive(Debug, Clone, Deserialize)]
+pub struct NodeInfo {
+    pub chain_id: u8,
+    pub height: u64,
+    pub best_hash: String,
+    pub circulating_supply: u64,
+    pub mempool_txs: u64,
+    pub mempool_bytes: u64,
+}
+
+/// `getaddressinfo` response.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AddressInfo {
+    pub address: String,
+    pub balance: u64,
+    pub nonce: u64,
+    pub utxo_count: u64,
+    pub seen: bool,
+}
+
+/// `gettransactionstatus` response, flattened.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TxStatus {
+    Pending,
+    Confirmed { height: u64, confirmations: u64 },
+    Unknown,
+}
+
+/// One `getpeerinfo` entry.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PeerEntry {
+    pub addr: String,
+    pub inbound: bool,
+    pub version: u32,
+    pub user_agent: String,
+    pub best_height: u64,
+    pub ping_ms: Option<f64>,
+}
+
+/// Async JSON-RPC client. Cheap to clone; clones share the connection
+/// pool.
+#[derive(Clone)]
+pub struct RpcClient {
+    http: reqwest::Client,
+    url: String,
+    auth: Auth,
+    retry: RetryPolicy,
+}
+
+impl RpcClient {
+    pub fn new(url: impl Into<String>) -> Self {
+        RpcClient {
+            http: reqwest::Client::new(),
+            url: url.into(),
+            auth: Auth::None,
+            retry: RetryPolicy::default(),
+        }
+    }
+
+    pub fn with_auth(mut self, auth: Auth) -> Self {
+        self.auth = auth;
+        self
+    }
+
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Raw JSON-RPC call; the typed methods below all route through
+    /// here and inherit auth and retry behavior.
+    pub async fn call(&self, method: &str, params: Value) -> Result<Value, String> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let mut delay = self.retry.base_delay;
+        let mut last_err = String::new();
+        for attempt in 0..self.retry.attempts.max(1) {
+            if attempt > 0 {
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+            let mut request = self.http.post(&self.url).json(&body);
+            request = match &self.auth {
+                Auth::None => request,
+                Auth::Basic(user, password) => request.basic_auth(user, Some(password)),
+                Auth::Token(token) => request.bearer_auth(token),
+            };
+            let response = match request.send().await {
+                Ok(response) => response,
+                Err(e) => {
+                    last_err = format!("RPC unreachable: {}", e);
+                    continue;
+                }
+            };
+            let resp: Value = response
+                .json()
+                .await
+                .map_err(|e| format!("bad RPC response: {}", e))?;
+            if let Some(err) = resp.get("error").filter(|e| !e.is_null()) {
+                return Err(err
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or("RPC error")
+                    .to_string());
+            }
+            return Ok(resp.get("result").cloned().unwrap_or(Value::Null));
+        }
+        Err(last_err)
+    }
+
+    pub async fn get_info(&self) -> Result<NodeInfo, String> {
+        self.typed("getinfo", Value::Null).await
+    }
+
+    pub async fn get_block_count(&self) -> Result<u64, String> {
+        self.call("getblockcount", Value::Null)
+            .await?
+            .as_u64()
+            .ok_or_else(|| "bad getblockcount response".to_string())
+    }
+
+    pub async fn get_block_hash(&self, height: u64) -> Result<Hash256, String> {
+        let value = self.call("getblockhash", json!([height])).await?;
+        parse_hash(&value)
+    }
+
+    pub async fn get_balance(&self, address: &[u8; 20]) -> Result<u64, String> {
+        self.call("getbalance", json!([hex::encode(address)]))
+            .await?
+            .as_u64()
+            .ok_or_else(|| "bad getbalance response".to_string())
+    }
+
+    pub async fn get_nonce(&self, address: &[u8; 20]) -> Result<u64, String> {
+        self.call("getnonce", json!([hex::encode(address)]))
+            .await?
+            .as_u64()
+            .ok_or_else(|| "bad getnonce response".to_string())
+    }
+
+    pub async fn get_address_info(&self, address: &[u8; 20]) -> Result<AddressInfo, String> {
+        self.typed("getaddressinfo", json!([hex::encode(address)]))
+            .await
+    }
+
+    pub async fn get_transaction_status(&self, tx_hash: &Hash256) -> Result<TxStatus, String> {
+        let value = self
+            .call("gettransactionstatus", json!([hex::encode(tx_hash)]))
+            .await?;
+        match value.get("status").and_then(Value::as_str) {
+            Some("pending") => Ok(TxStatus::Pending),
+            Some("confirmed") => Ok(TxStatus::Confirmed {
+                height: value.get("height").and_then(Value::as_u64).unwrap_or(0),
+                confirmations: value
+                    .get("confirmations")
+                    .and_then(Value::as_u64)
+                    .unwrap_or(0),
+            }),
+            Some("unknown") => Ok(TxStatus::Unknown),
+            _ => Err("bad gettransactionstatus response".to_string()),
+        }
+    }
+
+    /// Serializes and submits a signed transaction, returning its hash.
+    pub async fn send_transaction(&self, tx: &Transaction) -> Result<Hash256, String> {
+        let tx_hex = hex::encode(bincode::serialize(tx).map_err(|e| e.to_string())?);
+        let value = self.call("sendtransaction", json!([tx_hex])).await?;
+        parse_hash(&value)
+    }
+
+    pub async fn get_peer_info(&self) -> Result<Vec<PeerEntry>, String> {
+        self.typed("getpeerinfo", Value::Null).await
+    }
+
+    async fn typed<T: serde::de::DeserializeOwned>(
+        &self,
+        method: &str,
+        params: Value,
+    ) -> Result<T, String> {
+        let value = self.call(method, params).await?;
+        serde_json::from_value(value).map_err(|e| format!("bad {} response: {}", method, e))
+    }
+}
+
+fn parse_hash(value: &Value) -> Result<Hash256, String> {
+    value
+        .as_str()
+        .and_then(|s| hex::decode(s).ok())
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| "expected a 32-byte hex hash".to_string())
+}
+
+/// Synchronous wrapper around [`RpcClient`] for services without an
+/// async runtime of their own.
+pub struct BlockingRpcClient {
+    inner: RpcClient,
+    runtime: tokio::runtime::Runtime,
+}
+
+impl BlockingRpcClient {
+    pub fn new(url: impl Into<String>) -> Result<Self, String> {
+        Ok(BlockingRpcClient {
+            inner: RpcClient::new(url),
+            runtime: tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .map_err(|e| format!("failed to start client runtime: {}", e))?,
+        })
+    }
+
+    pub fn with_auth(mut self, auth: Auth) -> Self {
+        self.inner = self.inner.with_auth(auth);
+        self
+    }
+
+    pub fn call(&self, method: &str, params: Value) -> Result<Value, String> {
+        self.runtime.block_on(self.inner.call(method, params))
+    }
+
+    pub fn get_info(&self) -> Result<NodeInfo, String> {
+        self.runtime.block_on(self.inner.get_info())
+    }
+
+    pub fn get_block_count(&self) -> Result<u64, String> {
+        self.runtime.block_on(self.inner.get_block_count())
+    }
+
+    pub fn get_balance(&self, address: &[u8; 20]) -> Result<u64, String> {
+        self.runtime.block_on(self.inner.get_balance(address))
+    }
+
+    pub fn send_transaction(&self, tx: &Transaction) -> Result<Hash256, String> {
+        self.runtime.block_on(self.inner.send_transaction(tx))
+    }
+}
@@ -0,0 +1,89 @@
+//! Manual UTXO locks: outputs the operator has reserved.
+//!
+//! A locked output never counts toward the balance the node will let a
+//! wallet spend — useful for funds earmarked as channel collateral,
+//! pending settlements, or dust the owner refuses to consolidate at
+//! today's fee rates. Locks are node-local, in-memory state in the
+//! bitcoind `lockunspent` tradition: they do not relay, do not persist
+//! across restarts, and say nothing about consensus validity.
+//!
+//! Because transactions spend by account rather than by named input,
+//! a lock reserves the output's value: a spend is refused when it
+//! could only be funded by dipping into locked coins.
+
+use std::collections::HashSet;
+
+use crate::blockchain::Blockchain;
+use crate::error::PaliError;
+use crate::types::{Address, OutPoint};
+
+/// The set of manually locked outpoints.
+#[derive(Debug, Default)]
+pub struct CoinLocks {
+    locked: HashSet<OutPoint>,
+}
+
+impl CoinLocks {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Locks an outpoint. Returns false if it was already locked.
+    pub fn lock(&mut self, outpoint: OutPoint) -> bool {
+        self.locked.insert(outpoint)
+    }
+
+    /// Unlocks an outpoint. Returns false if it was not locked.
+    pub fn unlock(&mut self, outpoint: &OutPoint) -> bool {
+        self.locked.remove(outpoint)
+    }
+
+    /// Drops every lock, returning how many there were.
+    pub fn unlock_all(&mut self) -> usize {
+        let count = self.locked.len();
+        self.locked.clear();
+        count
+    }
+
+    pub fn is_locked(&self, outpoint: &OutPoint) -> bool {
+        self.locked.contains(outpoint)
+    }
+
+    pub fn outpoints(&self) -> impl Iterator<Item = &OutPoint> {
+        self.locked.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.locked.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.locked.is_empty()
+    }
+
+    /// Total locked value standing against `address`. Locks whose
+    /// outputs have since been spent (by a confirmed block, where
+    /// locks cannot intervene) are dropped along the way, so a stale
+    /// lock never reserves value that no longer exists.
+    pub fn locked_amount(
+        &mut self,
+        chain: &Blockchain,
+        address: &Address,
+    ) -> Result<u64, PaliError> {
+        let mut total = 0u64;
+        let mut stale = Vec::new();
+        for outpoint in &self.locked {
+            match chain.get_utxo(outpoint)? {
+                Some(entry) if entry.address == *address => {
+                    total = total.saturating_add(entry.amount);
+                }
+                Some(_) => {}
+                None => stale.push(*outpoint),
+            }
+        }
+        for outpoint in stale {
+            self.locked.remove(&outpoint);
+        }
+        Ok(total)
+    }
+}
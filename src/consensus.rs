@@ -0,0 +1,237 @@
+//! Consensus rule versioning.
+//!
+//! Every rule a block's validity depends on — the bincode wire layout
+//! of transactions and headers, double-SHA256 block hashing, the merkle
+//! construction, compact-bits difficulty checks — is frozen by the
+//! golden vectors under `tests/golden/`. This module gives that frozen
+//! rule set a single version number that peers exchange right after the
+//! handshake, so two builds with incompatible rules part ways at
+//! connect time instead of forking silently at the first block.
+
+/// Version of the consensus rule set this build enforces.
+///
+/// Bump this (and re-bless the golden vectors with a migration plan)
+/// whenever a change alters what blocks are valid: serialization
+/// layout, hashing, merkle construction, difficulty or any validation
+/// rule. Peers advertising a different value are disconnected during
+/// the handshake.
+pub const CHAIN_RULES_VERSION: u32 = 1;
+
+use std::path::Path;
+
+use secp256k1::PublicKey;
+
+use crate::hash;
+use crate::types::Address;
+
+/// Height-scheduled consensus feature activations and chain-wide
+/// tunables. Defaults describe mainnet; private consortium networks
+/// override them through a `chainparams.json` file loaded with
+/// [`ChainParams::from_file`], so no recompile is needed.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ChainParams {
+    /// First height at which v2 (multi-output) transactions are valid
+    /// in blocks; `None` means the format is not scheduled. Below the
+    /// activation height v2 transactions are a consensus violation,
+    /// while v1 transactions stay valid forever.
+    pub v2_tx_activation_height: Option<u64>,
+    /// First height at which transactions may carry BIP340 Schnorr
+    /// signatures (32-byte x-only public keys, including MuSig
+    /// aggregates — see the musig module); `None` keeps the chain
+    /// ECDSA-only. Gated like the v2 format: early Schnorr spends are
+    /// a consensus violation, ECDSA stays valid forever.
+    #[serde(default)]
+    pub schnorr_tx_activation_height: Option<u64>,
+    /// Hex-encoded compressed secp256k1 public keys whose holders may
+    /// sign network alerts (see the alerts module). Empty disables the
+    /// alert mechanism entirely.
+    #[serde(default)]
+    pub alert_keys: Vec<String>,
+    /// Distinct alert-key signatures required before an alert is
+    /// accepted; zero means all configured keys must sign.
+    #[serde(default)]
+    pub alert_threshold: u32,
+    /// Proof-of-work scheme headers are hashed, checked and
+    /// work-weighted under. Fixed for the life of a chain: changing it
+    /// invalidates every proof already on disk.
+    #[serde(default)]
+    pub pow_algorithm: crate::pow::PowAlgorithmId,
+    /// Accumulated work the best chain must reach before this node
+    /// reports itself as synced and starts serving mining work. A
+    /// fresh node below the floor may still be on a long low-work
+    /// chain an attacker fed it; zero (the default) disables the gate.
+    #[serde(default)]
+    pub minimum_chain_work: u128,
+    /// Chain id a parameter file describes; `None` inherits the
+    /// daemon's `--chain-id` flag.
+    #[serde(default)]
+    pub chain_id: Option<u8>,
+    /// Target spacing between blocks, in seconds. Informational for
+    /// now: the retarget window still aims at the compiled-in spacing.
+    #[serde(default = "default_target_block_time")]
+    pub target_block_time_secs: u64,
+    /// Blocks between subsidy halvings.
+    #[serde(default = "default_halving_interval")]
+    pub halving_interval: u64,
+    /// Initial block subsidy in base units.
+    #[serde(default = "default_initial_reward")]
+    pub initial_block_reward: u64,
+    /// Hex-encoded compressed secp256k1 public keys allowed to mine
+    /// (permissioned PoA): every coinbase must pay an address derived
+    /// from one of them. Empty — the default, and mainnet — keeps
+    /// mining open to anyone.
+    #[serde(default)]
+    pub miner_keys: Vec<String>,
+    /// Hex-encoded compressed secp256k1 public keys forming the
+    /// initial proof-of-authority validator set. Non-empty selects the
+    /// PoA engine: blocks carry a round-robin validator signature
+    /// instead of proof-of-work (see the poa module), and this list is
+    /// only the starting point — on-chain rotation transactions
+    /// supersede it. Empty, the default, keeps the chain on PoW.
+    #[serde(default)]
+    pub validators: Vec<String>,
+}
+
+fn default_target_block_time() -> u64 {
+    crate::types::TARGET_BLOCK_TIME
+}
+
+fn default_halving_interval() -> u64 {
+    crate::types::HALVING_INTERVAL
+}
+
+fn default_initial_reward() -> u64 {
+    crate::types::INITIAL_REWARD
+}
+
+impl Default for ChainParams {
+    fn default() -> Self {
+        ChainParams {
+            v2_tx_activation_height: None,
+            schnorr_tx_activation_height: None,
+            alert_keys: Vec::new(),
+            alert_threshold: 0,
+            pow_algorithm: crate::pow::PowAlgorithmId::default(),
+            minimum_chain_work: 0,
+            chain_id: None,
+            target_block_time_secs: default_target_block_time(),
+            halving_interval: default_halving_interval(),
+            initial_block_reward: default_initial_reward(),
+            miner_keys: Vec::new(),
+            validators: Vec::new(),
+        }
+    }
+}
+
+impl ChainParams {
+    /// Loads and validates a `chainparams.json` override file.
+    pub fn from_file(path: &Path) -> Result<ChainParams, String> {
+        let text = std::fs::read_to_string(path)
+            .map_err(|e| format!("failed to read {}: {}", path.display(), e))?;
+        let params: ChainParams = serde_json::from_str(&text)
+            .map_err(|e| format!("malformed chain parameters in {}: {}", path.display(), e))?;
+        params.validate()?;
+        Ok(params)
+    }
+
+    /// Sanity checks a parameter set before a node adopts it; a typo in
+    /// a consortium config should fail startup, not mint a broken chain.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.target_block_time_secs == 0 {
+            return Err("target_block_time_secs must be positive".to_string());
+        }
+        if self.halving_interval == 0 {
+            return Err("halving_interval must be positive".to_string());
+        }
+        for key in self
+            .alert_keys
+            .iter()
+            .chain(&self.miner_keys)
+            .chain(&self.validators)
+        {
+            hex::decode(key)
+                .ok()
+                .and_then(|bytes| PublicKey::from_slice(&bytes).ok())
+                .ok_or_else(|| format!("'{}' is not a compressed secp256k1 public key", key))?;
+        }
+        Ok(())
+    }
+
+    /// Block subsidy at `height` under this chain's halving schedule.
+    pub fn block_reward_at(&self, height: u64) -> u64 {
+        let halvings = height / self.halving_interval;
+        if halvings >= 64 {
+            return 0;
+        }
+        self.initial_block_reward >> halvings
+    }
+
+    /// Permissioned-PoA admission: with `miner_keys` configured, only
+    /// coinbases paying an address derived from one of them pass.
+    pub fn miner_allowed(&self, payout: &Address) -> bool {
+        if self.miner_keys.is_empty() {
+            return true;
+        }
+        self.miner_keys.iter().any(|key| {
+            hex::decode(key)
+                .ok()
+                .filter(|bytes| PublicKey::from_slice(bytes).is_ok())
+                .is_some_and(|bytes| hash::pubkey_to_address(&bytes) == *payout)
+        })
+    }
+
+    /// Whether this chain runs under proof-of-authority rather than
+    /// proof-of-work.
+    pub fn poa_active(&self) -> bool {
+        !self.validators.is_empty()
+    }
+
+    pub fn v2_transactions_active(&self, height: u64) -> bool {
+        self.v2_tx_activation_height
+            .is_some_and(|activation| height >= activation)
+    }
+
+    /// Whether `total_work` clears the configured sync floor.
+    pub fn chain_work_sufficient(&self, total_work: u128) -> bool {
+        total_work >= self.minimum_chain_work
+    }
+
+    pub fn schnorr_signatures_active(&self, height: u64) -> bool {
+        self.schnorr_tx_activation_height
+            .is_some_and(|activation| height >= activation)
+    }
+
+    /// Signature-scheme admission check for a transaction at `height`.
+    pub fn check_signature_type(
+        &self,
+        tx: &crate::types::Transaction,
+        height: u64,
+    ) -> Result<(), String> {
+        match tx.signature_type() {
+            crate::types::SignatureType::Ecdsa => Ok(()),
+            crate::types::SignatureType::Schnorr if self.schnorr_signatures_active(height) => {
+                Ok(())
+            }
+            crate::types::SignatureType::Schnorr => Err(format!(
+                "schnorr signatures are not active at height {}",
+                height
+            )),
+        }
+    }
+
+    /// Format-level admission check for a transaction at `height`.
+    pub fn check_tx_version(
+        &self,
+        tx: &crate::types::AnyTransaction,
+        height: u64,
+    ) -> Result<(), String> {
+        match tx {
+            crate::types::AnyTransaction::V1(_) => Ok(()),
+            crate::types::AnyTransaction::V2(_) if self.v2_transactions_active(height) => Ok(()),
+            crate::types::AnyTransaction::V2(_) => Err(format!(
+                "v2 transactions are not active at height {}",
+                height
+            )),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Dandelion++ transaction relay privacy (stem and fluff phases).
+//!
+//! A new local transaction is forwarded one hop at a time along a
+//! "stem" of peers before being diffused ("fluffed") to everyone, so
+//! the node that first broadcasts widely sits several hops away from
+//! the wallet that created it. Each node keeps one stem successor per
+//! epoch, and every stem transaction carries an embargo timer: if the
+//! fluffed copy never comes back, the holder diffuses it itself so a
+//! broken stem cannot drop transactions.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+
+use crate::types::{Hash256, Transaction};
+
+/// How long one stem-peer selection stays in force.
+pub const EPOCH_DURATION: Duration = Duration::from_secs(600);
+
+/// Probability that a stem transaction continues along the stem at
+/// this hop instead of switching to fluff.
+pub const STEM_PROBABILITY: f64 = 0.9;
+
+/// Embargo timers are drawn uniformly from this range, in seconds.
+const EMBARGO_MIN_SECS: u64 = 10;
+const EMBARGO_MAX_SECS: u64 = 30;
+
+/// Where a stem-phase transaction goes next.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Route {
+    /// Forward along the stem to this peer only.
+    Stem(SocketAddr),
+    /// Diffuse to all peers.
+    Fluff,
+}
+
+/// Per-node Dandelion++ state: the current epoch's stem successor and
+/// the embargoed transactions awaiting their fluffed copy.
+#[derive(Debug, Default)]
+pub struct Dandelion {
+    epoch_started: Option<Instant>,
+    stem_peer: Option<SocketAddr>,
+    embargoed: HashMap<Hash256, (Transaction, Instant)>,
+}
+
+impl Dandelion {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Picks the route for a stem-phase transaction, re-selecting the
+    /// stem successor once per epoch (or when it disconnected). With
+    /// no connected peers this always fluffs.
+    pub fn route(&mut self, peers: &[SocketAddr]) -> Route {
+        self.maybe_rotate_epoch(peers);
+        if rand::thread_rng().gen::<f64>() >= STEM_PROBABILITY {
+            return Route::Fluff;
+        }
+        match self.stem_peer {
+            Some(peer) => Route::Stem(peer),
+            None => Route::Fluff,
+        }
+    }
+
+    fn maybe_rotate_epoch(&mut self, peers: &[SocketAddr]) {
+        let expired = self
+            .epoch_started
+            .is_none_or(|started| started.elapsed() >= EPOCH_DURATION);
+        let successor_gone = self.stem_peer.is_some_and(|peer| !peers.contains(&peer));
+        if expired || successor_gone {
+            self.epoch_started = Some(Instant::now());
+            self.stem_peer = if peers.is_empty() {
+                None
+            } else {
+                Some(peers[rand::thread_rng().gen_range(0..peers.len())])
+            };
+        }
+    }
+
+    /// Holds a stem-forwarded transaction under embargo. If nobody
+    /// fluffs it before the timer fires, the caller diffuses it.
+    pub fn embargo(&mut self, tx: Transaction) {
+        let delay = rand::thread_rng().gen_range(EMBARGO_MIN_SECS..=EMBARGO_MAX_SECS);
+        self.embargoed.insert(
+            tx.hash(),
+            (tx, Instant::now() + Duration::from_secs(delay)),
+        );
+    }
+
+    /// Lifts the embargo once the fluffed copy of a transaction has
+    /// been observed on the diffusion network.
+    pub fn clear(&mut self, tx_hash: &Hash256) {
+        self.embargoed.remove(tx_hash);
+    }
+
+    /// Removes and returns transactions whose embargo has expired; the
+    /// caller is responsible for fluffing them.
+    pub fn expired(&mut self) -> Vec<Transaction> {
+        let now = Instant::now();
+        let due: Vec<Hash256> = self
+            .embargoed
+            .iter()
+            .filter(|(_, (_, deadline))| *deadline <= now)
+            .map(|(hash, _)| *hash)
+            .collect();
+        due.into_iter()
+            .filter_map(|hash| self.embargoed.remove(&hash))
+            .map(|(tx, _)| tx)
+            .collect()
+    }
+}
@@ -0,0 +1,310 @@
+//! Reorg-safe deposit tracking for exchanges and custodial services.
+//!
+//! A service registers the addresses it credits customers for, each
+//! with its own confirmation requirement. The tracker follows every
+//! matching transaction through block connects and disconnects and
+//! emits an event when a deposit crosses the confirmation threshold
+//! (`Credited`), when a reorg unwinds a block it had credited
+//! (`ReorgedOut`), and when the deposit confirms again on the new
+//! branch (`Recredited`). Every event carries an idempotency key that
+//! is stable across redelivery but distinct across credit rounds, so a
+//! service that processes each key exactly once can never double- or
+//! under-credit — the whole point of the module. State persists across
+//! restarts so a reorg spanning a node outage is still caught.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::hash;
+use crate::types::{Address, Block, Hash256};
+
+/// Domain tag deposit-event idempotency keys are derived under.
+pub const DEPOSIT_EVENT_TAG: &[u8] = b"pali-coin/deposit-event/v1";
+
+/// Events kept in the log; consumers are expected to poll well within
+/// this window.
+pub const MAX_EVENT_LOG: usize = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DepositEventKind {
+    /// The deposit reached its confirmation requirement.
+    Credited,
+    /// A reorg unwound the block holding a credited deposit; the
+    /// service must claw the credit back (or freeze it) until a
+    /// `Recredited` event arrives.
+    ReorgedOut,
+    /// A previously reorged-out deposit confirmed again on the new
+    /// best chain.
+    Recredited,
+}
+
+/// One deposit state change. `idempotency_key` identifies the logical
+/// event: redelivering it yields the same key, while a later re-credit
+/// of the same transaction yields a fresh one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepositEvent {
+    /// Node-wide monotonic cursor; poll with `since` > last seen.
+    pub seq: u64,
+    pub kind: DepositEventKind,
+    pub txid: Hash256,
+    pub address: Address,
+    pub amount: u64,
+    /// Confirmations at the moment the event fired; zero for
+    /// `ReorgedOut`.
+    pub confirmations: u64,
+    pub idempotency_key: Hash256,
+}
+
+/// Where a tracked deposit currently stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DepositStatus {
+    /// In the best chain, below the confirmation requirement.
+    Confirming,
+    /// Credited and still in the best chain.
+    Credited,
+    /// Was credited, then its block was disconnected; awaiting
+    /// reconfirmation.
+    ReorgedOut,
+}
+
+/// One tracked deposit: a transaction paying a registered address.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DepositRecord {
+    pub txid: Hash256,
+    pub address: Address,
+    pub amount: u64,
+    /// Height of the block currently holding the deposit; `None`
+    /// while it sits disconnected.
+    pub height: Option<u64>,
+    pub status: DepositStatus,
+    /// How many times this deposit has been credited. Part of the
+    /// idempotency key, separating a re-credit after a reorg from a
+    /// redelivered first credit.
+    pub credit_round: u32,
+}
+
+/// Everything worth surviving a restart, in one bincode blob.
+#[derive(Serialize, Deserialize, Default)]
+struct PersistedState {
+    registrations: Vec<(Address, u64)>,
+    deposits: Vec<DepositRecord>,
+    events: Vec<DepositEvent>,
+    next_seq: u64,
+}
+
+/// The tracker itself: registrations, live deposits, and the event log.
+#[derive(Default)]
+pub struct DepositTracker {
+    /// Confirmation requirement per registered address.
+    registrations: HashMap<Address, u64>,
+    deposits: HashMap<Hash256, DepositRecord>,
+    events: Vec<DepositEvent>,
+    next_seq: u64,
+    path: Option<PathBuf>,
+}
+
+impl DepositTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opens a tracker persisted at `path`; a missing or unreadable
+    /// file just means a fresh tracker that will save there.
+    pub fn with_path<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let state: PersistedState = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| bincode::deserialize(&bytes).ok())
+            .unwrap_or_default();
+        DepositTracker {
+            registrations: state.registrations.into_iter().collect(),
+            deposits: state
+                .deposits
+                .into_iter()
+                .map(|record| (record.txid, record))
+                .collect(),
+            events: state.events,
+            next_seq: state.next_seq,
+            path: Some(path),
+        }
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let state = PersistedState {
+            registrations: self.registrations.iter().map(|(a, c)| (*a, *c)).collect(),
+            deposits: self.deposits.values().cloned().collect(),
+            events: self.events.clone(),
+            next_seq: self.next_seq,
+        };
+        let bytes = bincode::serialize(&state).expect("state serialization cannot fail");
+        if let Err(e) = std::fs::write(path, bytes) {
+            log::warn!("failed to write deposit state {}: {}", path.display(), e);
+        }
+    }
+
+    /// Registers an address with its confirmation requirement,
+    /// replacing any previous registration. Returns false when the
+    /// address was already registered (the requirement still updates).
+    pub fn register(&mut self, address: Address, min_confirmations: u64) -> bool {
+        let fresh = self
+            .registrations
+            .insert(address, min_confirmations.max(1))
+            .is_none();
+        self.save();
+        fresh
+    }
+
+    /// Drops an address; its existing deposits stay tracked to
+    /// completion, but no new ones are picked up.
+    pub fn unregister(&mut self, address: &Address) -> bool {
+        let removed = self.registrations.remove(address).is_some();
+        if removed {
+            self.save();
+        }
+        removed
+    }
+
+    pub fn registrations(&self) -> impl Iterator<Item = (&Address, u64)> {
+        self.registrations.iter().map(|(a, c)| (a, *c))
+    }
+
+    pub fn deposits(&self) -> impl Iterator<Item = &DepositRecord> {
+        self.deposits.values()
+    }
+
+    /// Events with sequence numbers at or above `since`.
+    pub fn events_since(&self, since: u64) -> Vec<DepositEvent> {
+        self.events.iter().filter(|e| e.seq >= since).cloned().collect()
+    }
+
+    /// The next sequence number that will be assigned; consumers resume
+    /// polling from here after draining a batch.
+    pub fn cursor(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// The idempotency key for one logical event: the deposit, the
+    /// credit round it belongs to, and the event kind.
+    fn idempotency_key(record: &DepositRecord, kind: DepositEventKind) -> Hash256 {
+        let tag_hash = hash::sha256(DEPOSIT_EVENT_TAG);
+        let mut input = Vec::with_capacity(64 + 57);
+        input.extend_from_slice(&tag_hash);
+        input.extend_from_slice(&tag_hash);
+        input.extend_from_slice(&record.txid);
+        input.extend_from_slice(&record.address);
+        input.extend_from_slice(&record.credit_round.to_be_bytes());
+        input.push(match kind {
+            DepositEventKind::Credited | DepositEventKind::Recredited => 0,
+            DepositEventKind::ReorgedOut => 1,
+        });
+        hash::sha256(&input)
+    }
+
+    fn emit(&mut self, record: &DepositRecord, kind: DepositEventKind, confirmations: u64) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        self.events.push(DepositEvent {
+            seq,
+            kind,
+            txid: record.txid,
+            address: record.address,
+            amount: record.amount,
+            confirmations,
+            idempotency_key: Self::idempotency_key(record, kind),
+        });
+        if self.events.len() > MAX_EVENT_LOG {
+            let excess = self.events.len() - MAX_EVENT_LOG;
+            self.events.drain(..excess);
+        }
+    }
+
+    /// Feeds a freshly connected best-chain block: picks up new
+    /// deposits paying registered addresses, re-anchors reorged-out
+    /// ones, and credits everything that reaches its confirmation
+    /// requirement at the new tip height.
+    pub fn block_connected(&mut self, block: &Block) {
+        let height = block.header.height;
+        for tx in &block.transactions {
+            let txid = tx.hash();
+            if let Some(record) = self.deposits.get_mut(&txid) {
+                // The same transaction landing again after a reorg.
+                record.height = Some(height);
+                continue;
+            }
+            if self.registrations.contains_key(&tx.to) && !tx.is_coinbase() {
+                self.deposits.insert(
+                    txid,
+                    DepositRecord {
+                        txid,
+                        address: tx.to,
+                        amount: tx.amount,
+                        height: Some(height),
+                        status: DepositStatus::Confirming,
+                        credit_round: 0,
+                    },
+                );
+            }
+        }
+        self.credit_eligible(height);
+        self.save();
+    }
+
+    /// Feeds a disconnected block during a reorg. Credited deposits it
+    /// held are announced as reorged out; uncredited ones silently go
+    /// back to waiting for a block on the new branch.
+    pub fn block_disconnected(&mut self, block: &Block) {
+        let height = block.header.height;
+        let unwound: Vec<Hash256> = self
+            .deposits
+            .values()
+            .filter(|r| r.height == Some(height))
+            .map(|r| r.txid)
+            .collect();
+        for txid in unwound {
+            let mut record = self.deposits.get(&txid).cloned().expect("record exists");
+            record.height = None;
+            if record.status == DepositStatus::Credited {
+                record.status = DepositStatus::ReorgedOut;
+                self.emit(&record, DepositEventKind::ReorgedOut, 0);
+            }
+            self.deposits.insert(txid, record);
+        }
+        self.save();
+    }
+
+    /// Credits every deposit whose confirmation count at `tip_height`
+    /// meets its address's requirement.
+    fn credit_eligible(&mut self, tip_height: u64) {
+        let eligible: Vec<(Hash256, u64)> = self
+            .deposits
+            .values()
+            .filter_map(|record| {
+                if record.status == DepositStatus::Credited {
+                    return None;
+                }
+                let height = record.height?;
+                let required = self.registrations.get(&record.address).copied().unwrap_or(1);
+                let confirmations = tip_height.checked_sub(height)? + 1;
+                (confirmations >= required).then_some((record.txid, confirmations))
+            })
+            .collect();
+        for (txid, confirmations) in eligible {
+            let mut record = self.deposits.get(&txid).cloned().expect("record exists");
+            let kind = match record.status {
+                DepositStatus::ReorgedOut => DepositEventKind::Recredited,
+                _ => DepositEventKind::Credited,
+            };
+            record.credit_round += 1;
+            record.status = DepositStatus::Credited;
+            self.emit(&record, kind, confirmations);
+            self.deposits.insert(txid, record);
+        }
+    }
+}
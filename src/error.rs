@@ -0,0 +1,325 @@
+//! Typed errors for the crate's fallible APIs.
+//!
+//! Early code returned bare `String`s, which read fine in logs but
+//! left callers matching on substrings to tell a corrupt database from
+//! an underfunded wallet. The taxonomy here mirrors the module
+//! boundaries: consensus verdicts stay [`RejectionReason`], and
+//! storage, mempool and wallet failures each get a kind enum plus the
+//! underlying cause, so API consumers can match on what went wrong
+//! while the Display form keeps the old human-readable detail.
+//!
+//! `From` conversions into `String` are kept so call sites that still
+//! speak strings (RPC responses, log lines) migrate one signature at a
+//! time instead of all at once.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::rejection::RejectionReason;
+
+/// Any failure the crate's public APIs can produce, grouped by the
+/// subsystem that judged it.
+#[derive(Debug)]
+pub enum PaliError {
+    /// A consensus verdict on a block or transaction; the existing
+    /// [`RejectionReason`] taxonomy, unchanged.
+    Consensus(RejectionReason),
+    /// Mempool admission failure.
+    Mempool(MempoolError),
+    /// Database, serialization or filesystem failure.
+    Storage(StorageError),
+    /// Wallet operation failure.
+    Wallet(WalletError),
+    /// A request or configuration that is malformed regardless of
+    /// chain or wallet state.
+    Invalid(String),
+}
+
+impl fmt::Display for PaliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaliError::Consensus(reason) => write!(f, "{}", reason),
+            PaliError::Mempool(e) => write!(f, "{}", e),
+            PaliError::Storage(e) => write!(f, "{}", e),
+            PaliError::Wallet(e) => write!(f, "{}", e),
+            PaliError::Invalid(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl Error for PaliError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PaliError::Consensus(_) => None,
+            PaliError::Mempool(e) => e.source(),
+            PaliError::Storage(e) => e.source(),
+            PaliError::Wallet(e) => e.source(),
+            PaliError::Invalid(_) => None,
+        }
+    }
+}
+
+impl From<RejectionReason> for PaliError {
+    fn from(reason: RejectionReason) -> Self {
+        PaliError::Consensus(reason)
+    }
+}
+
+impl From<MempoolError> for PaliError {
+    fn from(e: MempoolError) -> Self {
+        PaliError::Mempool(e)
+    }
+}
+
+impl From<StorageError> for PaliError {
+    fn from(e: StorageError) -> Self {
+        PaliError::Storage(e)
+    }
+}
+
+impl From<WalletError> for PaliError {
+    fn from(e: WalletError) -> Self {
+        PaliError::Wallet(e)
+    }
+}
+
+impl From<PaliError> for String {
+    fn from(e: PaliError) -> Self {
+        e.to_string()
+    }
+}
+
+/// Collapses into the reject-message taxonomy: consensus verdicts pass
+/// through, anything else becomes an internal rejection.
+impl From<PaliError> for RejectionReason {
+    fn from(e: PaliError) -> Self {
+        match e {
+            PaliError::Consensus(reason) => reason,
+            other => RejectionReason::Internal(other.to_string()),
+        }
+    }
+}
+
+/// What kind of storage operation failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StorageErrorKind {
+    /// The database engine returned an error.
+    Database,
+    /// Data read back from disk failed to deserialize or is
+    /// internally inconsistent.
+    Corrupt,
+    /// A plain filesystem operation failed.
+    Io,
+}
+
+/// A database, serialization or filesystem failure, with the
+/// underlying error preserved as the source.
+#[derive(Debug)]
+pub struct StorageError {
+    pub kind: StorageErrorKind,
+    context: Option<String>,
+    source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+impl StorageError {
+    /// A database-engine failure.
+    pub fn database<E: Error + Send + Sync + 'static>(source: E) -> Self {
+        StorageError {
+            kind: StorageErrorKind::Database,
+            context: None,
+            source: Some(Box::new(source)),
+        }
+    }
+
+    /// On-disk data that failed to deserialize or cannot be trusted.
+    pub fn corrupt<E: Error + Send + Sync + 'static>(context: &str, source: E) -> Self {
+        StorageError {
+            kind: StorageErrorKind::Corrupt,
+            context: Some(context.to_string()),
+            source: Some(Box::new(source)),
+        }
+    }
+
+    /// Corruption detected by inspection rather than a failed decode.
+    pub fn corrupt_msg(context: &str) -> Self {
+        StorageError {
+            kind: StorageErrorKind::Corrupt,
+            context: Some(context.to_string()),
+            source: None,
+        }
+    }
+
+    /// A filesystem failure.
+    pub fn io<E: Error + Send + Sync + 'static>(context: &str, source: E) -> Self {
+        StorageError {
+            kind: StorageErrorKind::Io,
+            context: Some(context.to_string()),
+            source: Some(Box::new(source)),
+        }
+    }
+
+    /// Attaches a human-readable context line, keeping the source.
+    pub fn context(mut self, context: &str) -> Self {
+        self.context = Some(context.to_string());
+        self
+    }
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.context, &self.source) {
+            (Some(context), Some(source)) => write!(f, "{}: {}", context, source),
+            (Some(context), None) => write!(f, "{}", context),
+            (None, Some(source)) => write!(f, "{}", source),
+            (None, None) => write!(f, "storage error"),
+        }
+    }
+}
+
+impl Error for StorageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_deref().map(|e| e as &(dyn Error + 'static))
+    }
+}
+
+impl From<StorageError> for String {
+    fn from(e: StorageError) -> Self {
+        e.to_string()
+    }
+}
+
+/// Storage failures inside block connection surface as internal
+/// rejections: a verdict on our disk, not on the block.
+impl From<StorageError> for RejectionReason {
+    fn from(e: StorageError) -> Self {
+        RejectionReason::Internal(e.to_string())
+    }
+}
+
+/// Why the mempool refused a transaction. Policy verdicts, not
+/// consensus ones — a refused transaction may still be valid in a
+/// block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MempoolErrorKind {
+    /// Already in the pool.
+    Duplicate,
+    /// Transaction exceeds the policy size limit.
+    OversizedTx,
+    /// Data carrier exceeds the policy limit.
+    OversizedData,
+    /// Amount below the dust threshold.
+    Dust,
+    /// Sender nonce already pending and the original is not
+    /// replaceable.
+    NonReplaceable,
+    /// Fee below the relay minimum, or a replacement that does not pay
+    /// for itself.
+    FeeTooLow,
+    /// Sender's pending ancestor chain is at the policy limit.
+    ChainTooLong,
+    /// A gap or mismatch in a submitted transaction chain.
+    BrokenChain,
+    /// A package whose shape violates policy: empty, oversized, mixed
+    /// senders or non-contiguous nonces.
+    BadPackage,
+}
+
+/// A mempool admission failure.
+#[derive(Debug)]
+pub struct MempoolError {
+    pub kind: MempoolErrorKind,
+    message: String,
+}
+
+impl MempoolError {
+    pub fn new(kind: MempoolErrorKind, message: impl Into<String>) -> Self {
+        MempoolError {
+            kind,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for MempoolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for MempoolError {}
+
+impl From<MempoolError> for String {
+    fn from(e: MempoolError) -> Self {
+        e.to_string()
+    }
+}
+
+/// What went wrong in a wallet operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WalletErrorKind {
+    /// The wallet is encrypted and no unlock is in effect.
+    Locked,
+    /// Decryption failed; almost always a wrong password.
+    BadPassword,
+    /// Spendable balance cannot cover the request.
+    InsufficientFunds,
+    /// The operation violates a configured spending policy.
+    Policy,
+    /// A request that is malformed regardless of wallet state.
+    InvalidRequest,
+    /// The wallet file failed to load or parse.
+    Corrupt,
+    /// A filesystem failure reading or writing the wallet file.
+    Io,
+}
+
+/// A wallet operation failure.
+#[derive(Debug)]
+pub struct WalletError {
+    pub kind: WalletErrorKind,
+    message: String,
+    source: Option<Box<dyn Error + Send + Sync>>,
+}
+
+impl WalletError {
+    pub fn new(kind: WalletErrorKind, message: impl Into<String>) -> Self {
+        WalletError {
+            kind,
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    pub fn with_source<E: Error + Send + Sync + 'static>(
+        kind: WalletErrorKind,
+        message: impl Into<String>,
+        source: E,
+    ) -> Self {
+        WalletError {
+            kind,
+            message: message.into(),
+            source: Some(Box::new(source)),
+        }
+    }
+}
+
+impl fmt::Display for WalletError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.source {
+            Some(source) => write!(f, "{}: {}", self.message, source),
+            None => write!(f, "{}", self.message),
+        }
+    }
+}
+
+impl Error for WalletError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_deref().map(|e| e as &(dyn Error + 'static))
+    }
+}
+
+impl From<WalletError> for String {
+    fn from(e: WalletError) -> Self {
+        e.to_string()
+    }
+}
@@ -0,0 +1,194 @@
+//! Embedded block explorer: minimal HTML pages served by the node
+//! itself, for deployments that don't want separate explorer infra.
+//! Compiled only with the `explorer` feature.
+
+use axum::extract::{Path, State};
+use axum::response::Html;
+use axum::routing::get;
+use axum::Router;
+
+use crate::rpc::RpcContext;
+use crate::types::{Block, Transaction};
+
+/// Routes mounted under `/explorer` on the RPC listener.
+pub fn router(ctx: RpcContext) -> Router {
+    Router::new()
+        .route("/explorer", get(index))
+        .route("/explorer/block/:hash", get(block_page))
+        .route("/explorer/tx/:hash", get(tx_page))
+        .route("/explorer/address/:addr", get(address_page))
+        .with_state(ctx)
+}
+
+fn page(title: &str, body: String) -> Html<String> {
+    Html(format!(
+        "<!doctype html><html><head><meta charset=\"utf-8\">\
+         <title>{title} - Palicoin Explorer</title>\
+         <style>body{{font-family:monospace;margin:2em}}table{{border-collapse:collapse}}\
+         td,th{{border:1px solid #ccc;padding:4px 8px;text-align:left}}</style>\
+         </head><body><h1><a href=\"/explorer\">Palicoin Explorer</a></h1>\
+         <h2>{title}</h2>{body}</body></html>"
+    ))
+}
+
+fn error_page(message: &str) -> Html<String> {
+    page("Error", format!("<p>{}</p>", html_escape(message)))
+}
+
+async fn index(State(ctx): State<RpcContext>) -> Html<String> {
+    let chain = match ctx.chain.lock() {
+        Ok(chain) => chain,
+        Err(_) => return error_page("chain lock poisoned"),
+    };
+    let height = chain.height();
+    let mut rows = String::new();
+    let start = height.saturating_sub(19);
+    for h in (start..=height).rev() {
+        if let Ok(Some(block)) = chain.get_block_by_height(h) {
+            rows.push_str(&format!(
+                "<tr><td>{}</td><td><a href=\"/explorer/block/{hash}\">{hash}</a></td>\
+                 <td>{}</td><td>{}</td></tr>",
+                h,
+                block.transactions.len(),
+                block.header.timestamp,
+                hash = hex::encode(block.hash()),
+            ));
+        }
+    }
+    page(
+        &format!("Height {}", height),
+        format!(
+            "<table><tr><th>Height</th><th>Hash</th><th>Txs</th><th>Time</th></tr>{}</table>",
+            rows
+        ),
+    )
+}
+
+async fn block_page(State(ctx): State<RpcContext>, Path(hash): Path<String>) -> Html<String> {
+    let chain = match ctx.chain.lock() {
+        Ok(chain) => chain,
+        Err(_) => return error_page("chain lock poisoned"),
+    };
+    let hash_bytes: [u8; 32] = match hex::decode(&hash).ok().and_then(|b| b.try_into().ok()) {
+        Some(h) => h,
+        None => return error_page("malformed block hash"),
+    };
+    match chain.get_block(&hash_bytes) {
+        Ok(Some(block)) => page(&format!("Block {}", block.header.height), block_html(&block)),
+        Ok(None) => error_page("block not found"),
+        Err(e) => error_page(&e.to_string()),
+    }
+}
+
+async fn tx_page(State(ctx): State<RpcContext>, Path(hash): Path<String>) -> Html<String> {
+    let chain = match ctx.chain.lock() {
+        Ok(chain) => chain,
+        Err(_) => return error_page("chain lock poisoned"),
+    };
+    let hash_bytes: [u8; 32] = match hex::decode(&hash).ok().and_then(|b| b.try_into().ok()) {
+        Some(h) => h,
+        None => return error_page("malformed transaction hash"),
+    };
+    match chain.get_transaction(&hash_bytes) {
+        Ok(Some(tx)) => page("Transaction", tx_html(&tx)),
+        Ok(None) => error_page("transaction not found"),
+        Err(e) => error_page(&e.to_string()),
+    }
+}
+
+async fn address_page(State(ctx): State<RpcContext>, Path(addr): Path<String>) -> Html<String> {
+    let chain = match ctx.chain.lock() {
+        Ok(chain) => chain,
+        Err(_) => return error_page("chain lock poisoned"),
+    };
+    let address: [u8; 20] = match hex::decode(&addr).ok().and_then(|b| b.try_into().ok()) {
+        Some(a) => a,
+        None => return error_page("malformed address"),
+    };
+    let balance = match chain.get_balance(&address) {
+        Ok(b) => b,
+        Err(e) => return error_page(&e.to_string()),
+    };
+    let utxos = match chain.get_utxos_for_address(&address) {
+        Ok(u) => u,
+        Err(e) => return error_page(&e.to_string()),
+    };
+    let mut rows = String::new();
+    for (outpoint, entry) in utxos {
+        rows.push_str(&format!(
+            "<tr><td><a href=\"/explorer/tx/{tx}\">{tx}:{}</a></td><td>{}</td><td>{}</td></tr>",
+            outpoint.index,
+            entry.amount,
+            entry.height,
+            tx = hex::encode(outpoint.tx_hash),
+        ));
+    }
+    page(
+        &format!("Address {}", addr),
+        format!(
+            "<p>Balance: {}</p>\
+             <table><tr><th>Outpoint</th><th>Amount</th><th>Height</th></tr>{}</table>",
+            balance, rows
+        ),
+    )
+}
+
+fn block_html(block: &Block) -> String {
+    let coinbase_data = match block.coinbase_data() {
+        Some(data) => format!(
+            "{} ({})",
+            hex::encode(data),
+            html_escape(&String::from_utf8_lossy(data))
+        ),
+        None => "-".to_string(),
+    };
+    let mut txs = String::new();
+    for tx in &block.transactions {
+        txs.push_str(&format!(
+            "<li><a href=\"/explorer/tx/{hash}\">{hash}</a></li>",
+            hash = hex::encode(tx.hash())
+        ));
+    }
+    format!(
+        "<table>\
+         <tr><th>Hash</th><td>{}</td></tr>\
+         <tr><th>Previous</th><td><a href=\"/explorer/block/{prev}\">{prev}</a></td></tr>\
+         <tr><th>Merkle root</th><td>{}</td></tr>\
+         <tr><th>Timestamp</th><td>{}</td></tr>\
+         <tr><th>Bits</th><td>{:#x}</td></tr>\
+         <tr><th>Nonce</th><td>{}</td></tr>\
+         <tr><th>Coinbase data</th><td>{}</td></tr>\
+         </table><h3>Transactions</h3><ul>{}</ul>",
+        hex::encode(block.hash()),
+        hex::encode(block.header.merkle_root),
+        block.header.timestamp,
+        block.header.bits,
+        block.header.nonce,
+        coinbase_data,
+        txs,
+        prev = hex::encode(block.header.prev_hash),
+    )
+}
+
+fn tx_html(tx: &Transaction) -> String {
+    format!(
+        "<table>\
+         <tr><th>Hash</th><td>{}</td></tr>\
+         <tr><th>From</th><td><a href=\"/explorer/address/{from}\">{from}</a></td></tr>\
+         <tr><th>To</th><td><a href=\"/explorer/address/{to}\">{to}</a></td></tr>\
+         <tr><th>Amount</th><td>{}</td></tr>\
+         <tr><th>Fee</th><td>{}</td></tr>\
+         <tr><th>Nonce</th><td>{}</td></tr>\
+         </table>",
+        hex::encode(tx.hash()),
+        tx.amount,
+        tx.fee,
+        tx.nonce,
+        from = hex::encode(tx.from),
+        to = hex::encode(tx.to),
+    )
+}
+
+fn html_escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
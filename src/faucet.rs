@@ -0,0 +1,234 @@
+//! Testnet faucet: rate-limited payouts over HTTP. Compiled only with
+//! the `faucet` feature.
+//!
+//! Runs alongside a testnet node on its own listener, paying from a
+//! funded wallet. Abuse control is two rate limits (one per client IP,
+//! one per recipient address, both on the same cooldown) plus an
+//! optional access token: a static shared secret out of the box, or a
+//! custom verifier hooked in with [`Faucet::set_token_verifier`] — the
+//! seam where a real captcha backend plugs in, in the style of the
+//! wallet's [`ApprovalHook`](crate::wallet::ApprovalHook).
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axum::extract::{ConnectInfo, State};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde_json::{json, Value};
+
+use crate::rejection::RejectionReason;
+use crate::rpc::RpcContext;
+use crate::types::{Address, Hash256, COIN};
+use crate::wallet::{SendRequest, Wallet};
+
+/// Callback that judges an access token; `true` lets the claim through.
+pub type TokenVerifier = Box<dyn Fn(&str) -> bool + Send + Sync>;
+
+/// Payout size and abuse limits.
+#[derive(Debug, Clone)]
+pub struct FaucetConfig {
+    /// Units paid per claim.
+    pub payout: u64,
+    /// Fee attached to each payout transaction.
+    pub fee: u64,
+    /// Seconds an IP or an address must wait between claims.
+    pub cooldown_secs: u64,
+    /// Static access token; `None` leaves the faucet open (or defers
+    /// entirely to a custom verifier).
+    pub token: Option<String>,
+}
+
+impl Default for FaucetConfig {
+    fn default() -> Self {
+        FaucetConfig {
+            payout: 10 * COIN,
+            fee: 10_000,
+            cooldown_secs: 3_600,
+            token: None,
+        }
+    }
+}
+
+/// The faucet service: wallet, node handles and claim history.
+pub struct Faucet {
+    ctx: RpcContext,
+    wallet: Mutex<Wallet>,
+    config: FaucetConfig,
+    verifier: Option<TokenVerifier>,
+    /// Last claim time per limiter key ("ip:…" and "addr:…").
+    recent: Mutex<HashMap<String, u64>>,
+}
+
+impl Faucet {
+    pub fn new(ctx: RpcContext, wallet: Wallet, config: FaucetConfig) -> Self {
+        Faucet {
+            ctx,
+            wallet: Mutex::new(wallet),
+            config,
+            verifier: None,
+            recent: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Replaces the static-token check with a custom verifier (captcha
+    /// backend, signed voucher, …).
+    pub fn set_token_verifier(&mut self, verifier: TokenVerifier) {
+        self.verifier = Some(verifier);
+    }
+
+    /// Gate a claim on its access token.
+    fn authorize(&self, token: Option<&str>) -> Result<(), String> {
+        if let Some(verifier) = &self.verifier {
+            return match token {
+                Some(token) if verifier(token) => Ok(()),
+                _ => Err("access token rejected".to_string()),
+            };
+        }
+        match &self.config.token {
+            None => Ok(()),
+            Some(expected) => match token {
+                Some(token) if crate::rpc_auth::constant_time_eq(token.as_bytes(), expected.as_bytes()) => {
+                    Ok(())
+                }
+                _ => Err("access token rejected".to_string()),
+            },
+        }
+    }
+
+    /// Enforces both cooldowns and, on success, records the claim.
+    fn reserve(&self, ip: IpAddr, address: &Address, now: u64) -> Result<(), String> {
+        let mut recent = self.recent.lock().expect("faucet lock poisoned");
+        // The table only ever holds recent claimants; drop expired
+        // entries whenever it gets large instead of on a timer.
+        if recent.len() > 10_000 {
+            recent.retain(|_, last| now < *last + self.config.cooldown_secs);
+        }
+        let keys = [format!("ip:{}", ip), format!("addr:{}", hex::encode(address))];
+        for key in &keys {
+            if let Some(last) = recent.get(key) {
+                let ready = last + self.config.cooldown_secs;
+                if now < ready {
+                    return Err(format!("cooldown: try again in {}s", ready - now));
+                }
+            }
+        }
+        for key in keys {
+            recent.insert(key, now);
+        }
+        Ok(())
+    }
+
+    /// One claim end to end: token, cooldowns, build, pool, relay.
+    pub fn pay(
+        &self,
+        ip: IpAddr,
+        address: &Address,
+        token: Option<&str>,
+    ) -> Result<Hash256, String> {
+        self.authorize(token)?;
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.reserve(ip, address, now)?;
+
+        let chain = self.ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+        let mut mempool = self.ctx.mempool.lock().map_err(|_| "mempool lock poisoned")?;
+        let mut wallet = self.wallet.lock().map_err(|_| "wallet lock poisoned")?;
+        // Claims between blocks chain on the faucet's pending nonces.
+        let from = wallet.address();
+        let pending = mempool.iter().filter(|(_, e)| e.tx.from == from).count() as u64;
+        let tx = wallet.create_transaction(SendRequest {
+            to: *address,
+            amount: self.config.payout,
+            fee: self.config.fee,
+            nonce: chain.get_nonce(&from)? + pending,
+            chain_id: self.ctx.chain_id,
+            replaceable: false,
+            tip_height: chain.height(),
+        })?;
+        match chain.validate_transaction(&tx, self.ctx.chain_id) {
+            Ok(_) => {}
+            Err(RejectionReason::BadNonce { expected, got }) if got > expected => {
+                let balance = chain.get_balance(&from)?;
+                mempool.validate_chained(&tx, expected, balance)?;
+            }
+            Err(reason) => return Err(reason.to_string()),
+        }
+        let tx_hash = mempool.insert_from_wallet(tx.clone(), chain.height())?;
+        drop(wallet);
+        drop(mempool);
+        drop(chain);
+        if let Some(node) = &self.ctx.node {
+            node.relay_local_transaction(tx);
+        }
+        Ok(tx_hash)
+    }
+
+    /// What the info endpoint reports.
+    fn info(&self) -> Result<Value, String> {
+        let chain = self.ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+        let address = self
+            .wallet
+            .lock()
+            .map_err(|_| "wallet lock poisoned")?
+            .address();
+        Ok(json!({
+            "payout": self.config.payout,
+            "cooldown_secs": self.config.cooldown_secs,
+            "token_required": self.verifier.is_some() || self.config.token.is_some(),
+            "balance": chain.get_balance(&address)?,
+        }))
+    }
+}
+
+/// Serves the faucet on its own listener until the process exits.
+pub async fn serve(faucet: Arc<Faucet>, addr: SocketAddr) -> Result<(), String> {
+    let app = Router::new()
+        .route("/faucet", get(info))
+        .route("/faucet", post(claim))
+        .with_state(faucet);
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("failed to bind faucet listener: {}", e))?;
+    log::info!("faucet listening on {}", addr);
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .map_err(|e| format!("faucet server error: {}", e))
+}
+
+async fn info(State(faucet): State<Arc<Faucet>>) -> Json<Value> {
+    match faucet.info() {
+        Ok(info) => Json(info),
+        Err(e) => Json(json!({ "error": e })),
+    }
+}
+
+async fn claim(
+    State(faucet): State<Arc<Faucet>>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    Json(request): Json<Value>,
+) -> Json<Value> {
+    let Some(address) = request
+        .get("address")
+        .and_then(Value::as_str)
+        .and_then(|hex| hex::decode(hex).ok())
+        .and_then(|bytes| <Address>::try_from(bytes).ok())
+    else {
+        return Json(json!({ "error": "missing or malformed address" }));
+    };
+    let token = request.get("token").and_then(Value::as_str);
+    match faucet.pay(peer.ip(), &address, token) {
+        Ok(tx_hash) => Json(json!({
+            "txid": hex::encode(tx_hash),
+            "amount": faucet.config.payout,
+        })),
+        Err(e) => Json(json!({ "error": e })),
+    }
+}
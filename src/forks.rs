@@ -0,0 +1,131 @@
+//! Fork tip and stale-block monitoring.
+//!
+//! The chain database only ever extends the best tip, so blocks that
+//! lose a race — or arrive on a competing branch — never land in
+//! storage. This monitor keeps an in-memory record of every such tip
+//! with its claimed work, so `getchaintips` can surface competing
+//! branches. A burst of valid forks, or a deep one, is the first
+//! visible sign of a partition or an attempted 51% attack.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::math;
+use crate::types::{Block, Hash256};
+
+/// Cap on remembered fork tips; the lowest branch is evicted beyond
+/// this so a flood of junk blocks cannot grow the map without bound.
+pub const MAX_TRACKED_TIPS: usize = 256;
+
+/// Where a tip stands relative to the active chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TipStatus {
+    /// The best chain's own tip.
+    Active,
+    /// Proof of work checks out but the branch lost (or has not won).
+    ValidFork,
+    /// The branch contains a block that failed validation.
+    Invalid,
+}
+
+/// One non-active branch tip.
+#[derive(Debug, Clone, Serialize)]
+pub struct ForkTip {
+    pub hash: Hash256,
+    pub height: u64,
+    /// Blocks on this branch since it diverged, as far as we observed.
+    pub branch_len: u64,
+    /// Claimed work summed over the observed branch.
+    pub branch_work: u128,
+    pub status: TipStatus,
+    /// Unix time the tip block was first seen.
+    pub first_seen: u64,
+}
+
+/// Running totals since startup, independent of tip eviction.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct ForkStats {
+    pub stale_blocks_seen: u64,
+    pub invalid_blocks_seen: u64,
+}
+
+/// In-memory registry of branches that diverged from the active chain.
+#[derive(Default)]
+pub struct ForkMonitor {
+    tips: HashMap<Hash256, ForkTip>,
+    stats: ForkStats,
+}
+
+impl ForkMonitor {
+    pub fn new() -> Self {
+        ForkMonitor::default()
+    }
+
+    /// Records a block that did not connect to the active tip. A block
+    /// extending a tracked tip replaces it, growing the branch; any
+    /// invalidity poisons the whole branch.
+    pub fn record_stale(&mut self, block: &Block, valid: bool, now: u64) {
+        let hash = block.hash();
+        if self.tips.contains_key(&hash) {
+            return;
+        }
+        if valid {
+            self.stats.stale_blocks_seen += 1;
+        } else {
+            self.stats.invalid_blocks_seen += 1;
+        }
+        let work = math::block_work(block.header.bits);
+        let tip = match self.tips.remove(&block.header.prev_hash) {
+            Some(parent) => ForkTip {
+                hash,
+                height: block.header.height,
+                branch_len: parent.branch_len + 1,
+                branch_work: parent.branch_work.saturating_add(work),
+                status: if valid { parent.status } else { TipStatus::Invalid },
+                first_seen: parent.first_seen,
+            },
+            None => ForkTip {
+                hash,
+                height: block.header.height,
+                branch_len: 1,
+                branch_work: work,
+                status: if valid {
+                    TipStatus::ValidFork
+                } else {
+                    TipStatus::Invalid
+                },
+                first_seen: now,
+            },
+        };
+        self.tips.insert(hash, tip);
+        if self.tips.len() > MAX_TRACKED_TIPS {
+            if let Some(lowest) = self
+                .tips
+                .values()
+                .min_by_key(|tip| (tip.height, tip.first_seen))
+                .map(|tip| tip.hash)
+            {
+                self.tips.remove(&lowest);
+            }
+        }
+    }
+
+    /// Drops a tip that became part of the active chain (after a
+    /// manual rewind and replay, for instance).
+    pub fn record_connected(&mut self, hash: &Hash256) {
+        self.tips.remove(hash);
+    }
+
+    /// Tracked fork tips, highest branch first.
+    pub fn tips(&self) -> Vec<ForkTip> {
+        let mut tips: Vec<ForkTip> = self.tips.values().cloned().collect();
+        tips.sort_by(|a, b| b.height.cmp(&a.height).then(b.branch_work.cmp(&a.branch_work)));
+        tips
+    }
+
+    pub fn stats(&self) -> ForkStats {
+        self.stats
+    }
+}
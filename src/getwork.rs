@@ -0,0 +1,482 @@
+//! Push-based mining work distribution over WebSocket.
+//!
+//! Polling for templates leaves miners grinding stale work for the
+//! whole poll interval after a tip change. The `/work` endpoint
+//! inverts that: the node pushes a fresh template the moment the tip
+//! moves, and miners answer with `(nonce, timestamp, extranonce)`
+//! submissions that the node assembles back into a full block and
+//! validates.
+//!
+//! Frames are JSON text messages:
+//!
+//! - miner → node: `{"type":"subscribe","address":"<40-hex payout>"}`
+//! - node → miner: `{"type":"template","job_id":n,...}` — header
+//!   fields, the compact target and the non-coinbase transaction
+//!   hashes. The merkle root is for extranonce 0; a miner rolling the
+//!   extranonce recomputes it from the coinbase (height ‖ extranonce
+//!   little-endian in `data`, followed by any tagged commitment, which
+//!   must be preserved) and `tx_hashes`.
+//! - miner → node: `{"type":"submit","job_id":n,"nonce":n,"timestamp":n,"extranonce":n}`
+//! - node → miner: `{"type":"result","job_id":n,"accepted":bool,...}`
+
+use axum::extract::ws::{Message, WebSocket};
+use axum::extract::{State, WebSocketUpgrade};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use serde_json::{json, Value};
+
+use crate::blockchain::Blockchain;
+use crate::hash;
+use crate::math;
+use crate::mempool::Mempool;
+use crate::network::NetworkMessage;
+use crate::pool;
+use crate::rpc::RpcContext;
+use crate::rpc_auth::Scope;
+use crate::types::{Address, Block, BlockHeader, Hash256, Transaction, COINBASE_ADDRESS};
+
+/// Serialized-transaction budget per template, leaving header and
+/// framing room under the P2P message cap.
+pub const MAX_TEMPLATE_TX_BYTES: usize = 1024 * 1024;
+
+/// Longest a long-polled `getblocktemplate` request is held open
+/// before answering with the unchanged template.
+pub const LONGPOLL_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(60);
+
+/// Mempool fee growth (relative to the long-poll snapshot) that counts
+/// as a significant template change.
+pub const LONGPOLL_FEE_GROWTH: f64 = 0.10;
+
+/// How often the fee condition is re-checked while long-polling; tip
+/// changes interrupt immediately through the broadcast channel.
+const LONGPOLL_CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Opaque token a miner hands back to long-poll: the tip it built on
+/// and the mempool fees it saw.
+pub fn longpoll_id(chain: &Blockchain, mempool: &Mempool) -> String {
+    format!("{}:{}", hex::encode(chain.best_hash()), mempool.total_fees())
+}
+
+fn parse_longpoll_id(id: &str) -> Result<(Hash256, u64), String> {
+    let (tip, fees) = id
+        .split_once(':')
+        .ok_or_else(|| "malformed longpollid".to_string())?;
+    let tip: Hash256 = hex::decode(tip)
+        .ok()
+        .and_then(|b| b.try_into().ok())
+        .ok_or_else(|| "malformed longpollid".to_string())?;
+    let fees: u64 = fees
+        .parse()
+        .map_err(|_| "malformed longpollid".to_string())?;
+    Ok((tip, fees))
+}
+
+/// Holds a long-polled template request open until the tip moves, the
+/// pool's fees grow past [`LONGPOLL_FEE_GROWTH`], or the timeout
+/// lapses — whichever comes first.
+pub async fn wait_for_template_change(ctx: &RpcContext, longpollid: &str) -> Result<(), String> {
+    let (seen_tip, seen_fees) = parse_longpoll_id(longpollid)?;
+    let fee_threshold = seen_fees.saturating_add((seen_fees as f64 * LONGPOLL_FEE_GROWTH) as u64);
+    let mut tip_changes = ctx.node.as_ref().map(|node| node.tip_changes.subscribe());
+    let deadline = tokio::time::Instant::now() + LONGPOLL_TIMEOUT;
+    loop {
+        {
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            if chain.best_hash() != seen_tip {
+                return Ok(());
+            }
+        }
+        {
+            let mempool = ctx.mempool.lock().map_err(|_| "mempool lock poisoned")?;
+            let fees = mempool.total_fees();
+            // Any fees over an empty snapshot are significant.
+            if fees > fee_threshold || (seen_fees == 0 && fees > 0) {
+                return Ok(());
+            }
+        }
+        let check = tokio::time::sleep(LONGPOLL_CHECK_INTERVAL);
+        match &mut tip_changes {
+            Some(changes) => {
+                tokio::select! {
+                    _ = tokio::time::sleep_until(deadline) => return Ok(()),
+                    _ = check => {}
+                    _ = changes.recv() => {}
+                }
+            }
+            None => {
+                if tokio::time::Instant::now() + LONGPOLL_CHECK_INTERVAL >= deadline {
+                    tokio::time::sleep_until(deadline).await;
+                    return Ok(());
+                }
+                check.await;
+            }
+        }
+    }
+}
+
+/// Marks a sign-to-contract commitment in the coinbase payload. The
+/// tag keeps a commitment distinguishable from a genesis message or
+/// any other operator-chosen coinbase bytes.
+pub const COMMITMENT_TAG: &[u8; 4] = b"s2c:";
+
+/// Coinbase payload: the height pins the txid to this block (so two
+/// blocks paying the same address never collide), the extranonce
+/// extends the miner's search space beyond the header nonce, and an
+/// optional tagged 32-byte commitment anchors an external hash (a
+/// timestamping batch, an audit log root) under this block's proof of
+/// work.
+pub fn coinbase_data(height: u64, extranonce: u64, commitment: Option<&Hash256>) -> Vec<u8> {
+    let mut data = height.to_be_bytes().to_vec();
+    data.extend_from_slice(&extranonce.to_le_bytes());
+    if let Some(commitment) = commitment {
+        data.extend_from_slice(COMMITMENT_TAG);
+        data.extend_from_slice(commitment);
+    }
+    data
+}
+
+/// The commitment embedded in a coinbase payload, if the payload
+/// carries one. The layout is fixed — height, extranonce, tag, hash —
+/// so a stray tag inside a genesis message never parses as one.
+pub fn coinbase_commitment(data: &[u8]) -> Option<Hash256> {
+    if data.len() != 16 + COMMITMENT_TAG.len() + 32 || &data[16..20] != COMMITMENT_TAG {
+        return None;
+    }
+    data[20..].try_into().ok()
+}
+
+/// Builds a block template paying `payout`, with extranonce 0 and the
+/// given coinbase commitment, if any.
+pub fn build_template(
+    chain: &Blockchain,
+    mempool: &Mempool,
+    chain_id: u8,
+    payout: Address,
+    commitment: Option<&Hash256>,
+) -> Result<Block, String> {
+    let height = chain.height() + 1;
+    let bits = chain.next_bits()?;
+    let transactions = mempool.get_transactions_for_block(MAX_TEMPLATE_TX_BYTES);
+    let fees: u64 = transactions.iter().map(|tx| tx.fee).sum();
+    let coinbase = Transaction {
+        chain_id,
+        nonce: 0,
+        from: COINBASE_ADDRESS,
+        to: payout,
+        amount: chain.chain_params().block_reward_at(height) + fees,
+        fee: 0,
+        data: coinbase_data(height, 0, commitment),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    };
+    let mut all = vec![coinbase];
+    all.extend(transactions);
+    let hashes: Vec<Hash256> = all.iter().map(|tx| tx.hash()).collect();
+    Ok(Block {
+        header: BlockHeader {
+            version: 1,
+            prev_hash: chain.best_hash(),
+            merkle_root: hash::merkle_root(&hashes),
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            bits,
+            nonce: 0,
+            height,
+        },
+        transactions: all,
+    })
+}
+
+/// The template's transaction hashes as an [`hash::IncrementalMerkle`],
+/// built once per job so per-submission extranonce rolls only touch the
+/// coinbase path.
+pub fn template_tree(template: &Block) -> hash::IncrementalMerkle {
+    let hashes: Vec<Hash256> = template.transactions.iter().map(|tx| tx.hash()).collect();
+    hash::IncrementalMerkle::new(&hashes)
+}
+
+/// Applies a submission to its template: re-derives the coinbase for
+/// the submitted extranonce, updates the coinbase leaf in the cached
+/// merkle tree and stamps the header. Validation is the caller's
+/// `add_block`.
+pub fn assemble(
+    template: &Block,
+    tree: &mut hash::IncrementalMerkle,
+    nonce: u64,
+    timestamp: u64,
+    extranonce: u64,
+) -> Block {
+    let mut block = template.clone();
+    let commitment = coinbase_commitment(&template.transactions[0].data);
+    block.transactions[0].data =
+        coinbase_data(block.header.height, extranonce, commitment.as_ref());
+    tree.set(0, block.transactions[0].hash());
+    block.header.merkle_root = tree.root();
+    block.header.nonce = nonce;
+    block.header.timestamp = timestamp;
+    block
+}
+
+/// The `/work` WebSocket route, mounted on the RPC listener.
+pub fn router(ctx: RpcContext) -> Router {
+    Router::new().route("/work", get(upgrade)).with_state(ctx)
+}
+
+async fn upgrade(
+    State(ctx): State<RpcContext>,
+    axum::Extension(scope): axum::Extension<Scope>,
+    ws: WebSocketUpgrade,
+) -> axum::response::Response {
+    if scope < Scope::Wallet {
+        return (
+            axum::http::StatusCode::FORBIDDEN,
+            "mining work requires wallet scope",
+        )
+            .into_response();
+    }
+    ws.on_upgrade(move |socket| async move {
+        if let Err(e) = serve_miner(ctx, socket).await {
+            log::debug!("work connection closed: {}", e);
+        }
+    })
+}
+
+fn template_frame(job_id: u64, template: &Block) -> String {
+    json!({
+        "type": "template",
+        "job_id": job_id,
+        "height": template.header.height,
+        "prev_hash": hex::encode(template.header.prev_hash),
+        "merkle_root": hex::encode(template.header.merkle_root),
+        "timestamp": template.header.timestamp,
+        "bits": template.header.bits,
+        "target": hex::encode(math::bits_to_target(template.header.bits)),
+        "coinbase": hex::encode(
+            bincode::serialize(&template.transactions[0]).expect("serialization cannot fail")
+        ),
+        "tx_hashes": template.transactions[1..]
+            .iter()
+            .map(|tx| hex::encode(tx.hash()))
+            .collect::<Vec<_>>(),
+    })
+    .to_string()
+}
+
+async fn serve_miner(ctx: RpcContext, mut socket: WebSocket) -> Result<(), String> {
+    let node = ctx
+        .node
+        .as_ref()
+        .ok_or_else(|| "P2P layer is not running".to_string())?;
+    let mut tip_changes = node.tip_changes.subscribe();
+
+    // First frame must subscribe with a payout address.
+    let payout = loop {
+        match socket.recv().await {
+            Some(Ok(Message::Text(text))) => {
+                let frame: Value =
+                    serde_json::from_str(&text).map_err(|e| format!("bad frame: {}", e))?;
+                if frame.get("type").and_then(Value::as_str) != Some("subscribe") {
+                    return Err("expected a subscribe frame".to_string());
+                }
+                let payout: Address = frame
+                    .get("address")
+                    .and_then(Value::as_str)
+                    .and_then(|s| hex::decode(s).ok())
+                    .and_then(|b| b.try_into().ok())
+                    .ok_or_else(|| "subscribe needs a 20-byte hex address".to_string())?;
+                break payout;
+            }
+            Some(Ok(Message::Close(_))) | None => return Ok(()),
+            Some(Ok(_)) => continue,
+            Some(Err(e)) => return Err(format!("websocket error: {}", e)),
+        }
+    };
+
+    let mut job_id: u64 = 0;
+    let mut template = new_template(&ctx, payout)?;
+    let mut tree = template_tree(&template);
+    socket
+        .send(Message::Text(template_frame(job_id, &template)))
+        .await
+        .map_err(|e| format!("send failed: {}", e))?;
+
+    loop {
+        tokio::select! {
+            changed = tip_changes.recv() => {
+                if changed.is_err() {
+                    return Ok(());
+                }
+                job_id += 1;
+                template = new_template(&ctx, payout)?;
+                tree = template_tree(&template);
+                socket
+                    .send(Message::Text(template_frame(job_id, &template)))
+                    .await
+                    .map_err(|e| format!("send failed: {}", e))?;
+            }
+            frame = socket.recv() => {
+                let text = match frame {
+                    Some(Ok(Message::Text(text))) => text,
+                    Some(Ok(Message::Close(_))) | None => return Ok(()),
+                    Some(Ok(_)) => continue,
+                    Some(Err(e)) => return Err(format!("websocket error: {}", e)),
+                };
+                let frame: Value =
+                    serde_json::from_str(&text).map_err(|e| format!("bad frame: {}", e))?;
+                if frame.get("type").and_then(Value::as_str) != Some("submit") {
+                    continue;
+                }
+                let submitted_job = frame.get("job_id").and_then(Value::as_u64).unwrap_or(0);
+                let nonce = frame.get("nonce").and_then(Value::as_u64).unwrap_or(0);
+                let timestamp = frame.get("timestamp").and_then(Value::as_u64).unwrap_or(0);
+                let extranonce = frame.get("extranonce").and_then(Value::as_u64).unwrap_or(0);
+                let reply = if submitted_job != job_id {
+                    json!({
+                        "type": "result",
+                        "job_id": submitted_job,
+                        "accepted": false,
+                        "reason": "stale job",
+                    })
+                } else {
+                    let block = assemble(&template, &mut tree, nonce, timestamp, extranonce);
+                    // A submission meeting the relaxed share target is
+                    // ledgered for payout accounting even when it falls
+                    // short of a full block.
+                    let bits = block.header.bits;
+                    let pow = node.params.pow_algorithm.algorithm();
+                    let share = math::hash_meets_target(
+                        &pow.pow_hash(&block.header),
+                        pool::share_bits(bits),
+                    );
+                    if share {
+                        node.pool.lock().expect("pool lock poisoned").record_share(
+                            payout,
+                            math::block_work(pool::share_bits(bits)),
+                            block.header.height,
+                            block.header.timestamp,
+                        );
+                    }
+                    match submit_block(&ctx, &block) {
+                        Ok(()) => {
+                            node.pool.lock().expect("pool lock poisoned").record_block();
+                            json!({
+                                "type": "result",
+                                "job_id": submitted_job,
+                                "accepted": true,
+                                "share": share,
+                                "hash": hex::encode(block.hash()),
+                            })
+                        }
+                        Err(reason) => json!({
+                            "type": "result",
+                            "job_id": submitted_job,
+                            "accepted": false,
+                            "share": share,
+                            "reason": reason,
+                        }),
+                    }
+                };
+                socket
+                    .send(Message::Text(reply.to_string()))
+                    .await
+                    .map_err(|e| format!("send failed: {}", e))?;
+            }
+        }
+    }
+}
+
+/// Combined admission check for mining work: the operator may have
+/// paused mining outright, and a still-syncing chain is not worth
+/// mining on (see [`ensure_chain_synced`]).
+pub fn ensure_mining_open(ctx: &RpcContext) -> Result<(), String> {
+    if let Some(node) = &ctx.node {
+        if node.params.poa_active() {
+            return Err(
+                "this chain runs proof-of-authority: blocks are sealed by validators, not mined"
+                    .to_string(),
+            );
+        }
+        if !node.toggles.lock().expect("toggles lock poisoned").mining {
+            return Err("mining is paused by the operator".to_string());
+        }
+    }
+    ensure_chain_synced(ctx)
+}
+
+fn new_template(ctx: &RpcContext, payout: Address) -> Result<Block, String> {
+    ensure_mining_open(ctx)?;
+    let commitment = pending_commitment(ctx);
+    let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+    let mempool = ctx.mempool.lock().map_err(|_| "mempool lock poisoned")?;
+    build_template(&chain, &mempool, ctx.chain_id, payout, commitment.as_ref())
+}
+
+/// The commitment `setblockcommitment` queued for the next block, if
+/// the P2P layer is running and one is pending.
+pub fn pending_commitment(ctx: &RpcContext) -> Option<Hash256> {
+    let node = ctx.node.as_ref()?;
+    *node
+        .next_commitment
+        .lock()
+        .expect("commitment lock poisoned")
+}
+
+/// Refuses mining work until the best chain clears the configured
+/// `minimum_chain_work`: templates built on (and blocks extending) a
+/// partially synced chain would mine on top of whatever a peer fed us,
+/// which may be a long low-work fake.
+pub fn ensure_chain_synced(ctx: &RpcContext) -> Result<(), String> {
+    let Some(node) = &ctx.node else {
+        return Ok(());
+    };
+    // A snapshot read: this runs on every template and submission, and
+    // a marginally stale total only delays opening by one tip change.
+    let total_work = node.tip_view.current().total_work;
+    if node.params.chain_work_sufficient(total_work) {
+        Ok(())
+    } else {
+        Err(format!(
+            "node is syncing: chain work {} is below the required minimum {}",
+            total_work, node.params.minimum_chain_work
+        ))
+    }
+}
+
+/// Connects a mined block, clears its transactions from the pool and
+/// relays it; every work connection gets a fresh template through the
+/// tip-change broadcast.
+pub fn submit_block(ctx: &RpcContext, block: &Block) -> Result<(), String> {
+    ensure_mining_open(ctx)?;
+    let started = std::time::Instant::now();
+    {
+        let mut chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+        chain
+            .add_block(block, ctx.chain_id)
+            .map_err(|reason| reason.to_string())?;
+        let mut mempool = ctx.mempool.lock().map_err(|_| "mempool lock poisoned")?;
+        mempool.remove_confirmed(&block.transactions);
+    }
+    if let Some(node) = &ctx.node {
+        // A queued commitment is one-shot: once a block carrying it
+        // connects, later templates stop repeating it.
+        let mined = block.coinbase().and_then(|cb| coinbase_commitment(&cb.data));
+        if mined.is_some() {
+            let mut next = node
+                .next_commitment
+                .lock()
+                .expect("commitment lock poisoned");
+            if *next == mined {
+                *next = None;
+            }
+        }
+        node.record_block_telemetry(block, None, started.elapsed());
+        node.announce(NetworkMessage::Block(block.clone()));
+        node.notify_tip_change(block);
+    }
+    Ok(())
+}
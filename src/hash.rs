@@ -0,0 +1,208 @@
+//! Hashing primitives: double-SHA256, address derivation and merkle trees.
+
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+
+use crate::types::{Address, Hash256};
+
+pub fn sha256(data: &[u8]) -> Hash256 {
+    let mut out = [0u8; 32];
+    out.copy_from_slice(&Sha256::digest(data));
+    out
+}
+
+pub fn double_sha256(data: &[u8]) -> Hash256 {
+    sha256(&sha256(data))
+}
+
+/// RIPEMD160(SHA256(pubkey)), the standard address derivation.
+pub fn pubkey_to_address(public_key: &[u8]) -> Address {
+    let sha = Sha256::digest(public_key);
+    let mut out = [0u8; 20];
+    out.copy_from_slice(&Ripemd160::digest(sha));
+    out
+}
+
+/// Merkle root over transaction hashes. The last hash is duplicated on
+/// odd levels, matching Bitcoin's construction. An empty set hashes to
+/// all zeroes.
+pub fn merkle_root(hashes: &[Hash256]) -> Hash256 {
+    if hashes.is_empty() {
+        return [0u8; 32];
+    }
+    let mut level: Vec<Hash256> = hashes.to_vec();
+    while level.len() > 1 {
+        if level.len() % 2 == 1 {
+            level.push(*level.last().expect("level is non-empty"));
+        }
+        level = level
+            .chunks(2)
+            .map(|pair| {
+                let mut buf = [0u8; 64];
+                buf[..32].copy_from_slice(&pair[0]);
+                buf[32..].copy_from_slice(&pair[1]);
+                double_sha256(&buf)
+            })
+            .collect();
+    }
+    level[0]
+}
+
+/// Merkle tree with cached interior levels, so editing one leaf — or
+/// appending/removing leaves at the end — refreshes the root in
+/// O(log n) instead of rehashing the whole set.
+///
+/// Block templates churn at exactly those spots: the coinbase (leaf 0)
+/// changes with every extranonce, and mempool transactions join and
+/// leave at the tail between jobs. The construction matches
+/// [`merkle_root`] bit for bit, including the duplicated last hash on
+/// odd levels.
+#[derive(Debug, Clone)]
+pub struct IncrementalMerkle {
+    /// `levels[0]` holds the leaves; each higher level halves (rounding
+    /// up) until a single root remains.
+    levels: Vec<Vec<Hash256>>,
+}
+
+impl IncrementalMerkle {
+    pub fn new(leaves: &[Hash256]) -> Self {
+        let mut levels = vec![leaves.to_vec()];
+        while levels.last().expect("at least one level").len() > 1 {
+            let prev = levels.last().expect("at least one level");
+            let next = (0..prev.len().div_ceil(2))
+                .map(|i| {
+                    let left = prev[2 * i];
+                    let right = if 2 * i + 1 < prev.len() {
+                        prev[2 * i + 1]
+                    } else {
+                        left
+                    };
+                    hash_pair(&left, &right)
+                })
+                .collect();
+            levels.push(next);
+        }
+        IncrementalMerkle { levels }
+    }
+
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels[0].is_empty()
+    }
+
+    /// The current root; all zeroes over no leaves, matching
+    /// [`merkle_root`].
+    pub fn root(&self) -> Hash256 {
+        if self.is_empty() {
+            return [0u8; 32];
+        }
+        self.levels.last().expect("at least one level")[0]
+    }
+
+    /// Replaces the leaf at `index`, rehashing only its path.
+    pub fn set(&mut self, index: usize, leaf: Hash256) {
+        self.levels[0][index] = leaf;
+        self.update_path(index);
+    }
+
+    /// Appends a leaf, rehashing only the rightmost path.
+    pub fn push(&mut self, leaf: Hash256) {
+        self.levels[0].push(leaf);
+        self.update_path(self.levels[0].len() - 1);
+    }
+
+    /// Removes the last leaf, rehashing only the rightmost path.
+    pub fn pop(&mut self) -> Option<Hash256> {
+        let leaf = self.levels[0].pop()?;
+        let index = self.levels[0].len().saturating_sub(1);
+        self.update_path(index);
+        Some(leaf)
+    }
+
+    /// Recomputes the parents above leaf `index`, resizing levels as
+    /// the leaf count moves across power-of-two boundaries. Only the
+    /// last node of a level can appear or vanish, and it always sits on
+    /// the updated path.
+    fn update_path(&mut self, mut index: usize) {
+        let mut level = 0;
+        while self.levels[level].len() > 1 {
+            let len = self.levels[level].len();
+            if self.levels.len() == level + 1 {
+                self.levels.push(Vec::new());
+            }
+            self.levels[level + 1].resize(len.div_ceil(2), [0u8; 32]);
+            let left = self.levels[level][index & !1];
+            let right = if (index | 1) < len {
+                self.levels[level][index | 1]
+            } else {
+                left
+            };
+            self.levels[level + 1][index / 2] = hash_pair(&left, &right);
+            index /= 2;
+            level += 1;
+        }
+        self.levels.truncate(level + 1);
+    }
+}
+
+fn hash_pair(left: &Hash256, right: &Hash256) -> Hash256 {
+    let mut buf = [0u8; 64];
+    buf[..32].copy_from_slice(left);
+    buf[32..].copy_from_slice(right);
+    double_sha256(&buf)
+}
+
+/// One step of a merkle branch: the sibling hash and whether it sits on
+/// the left of the concatenation.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct MerkleStep {
+    pub hash: Hash256,
+    pub is_left: bool,
+}
+
+/// Builds the merkle branch proving `index` within `hashes`.
+pub fn merkle_branch(hashes: &[Hash256], mut index: usize) -> Vec<MerkleStep> {
+    let mut branch = Vec::new();
+    let mut level: Vec<Hash256> = hashes.to_vec();
+    while level.len() > 1 {
+        if level.len() % 2 == 1 {
+            level.push(*level.last().expect("level is non-empty"));
+        }
+        let sibling = index ^ 1;
+        branch.push(MerkleStep {
+            hash: level[sibling],
+            is_left: sibling < index,
+        });
+        index /= 2;
+        level = level
+            .chunks(2)
+            .map(|pair| {
+                let mut buf = [0u8; 64];
+                buf[..32].copy_from_slice(&pair[0]);
+                buf[32..].copy_from_slice(&pair[1]);
+                double_sha256(&buf)
+            })
+            .collect();
+    }
+    branch
+}
+
+/// Verifies a merkle branch from `leaf` up to `root`.
+pub fn verify_merkle_proof(leaf: Hash256, branch: &[MerkleStep], root: Hash256) -> bool {
+    let mut acc = leaf;
+    for step in branch {
+        let mut buf = [0u8; 64];
+        if step.is_left {
+            buf[..32].copy_from_slice(&step.hash);
+            buf[32..].copy_from_slice(&acc);
+        } else {
+            buf[..32].copy_from_slice(&acc);
+            buf[32..].copy_from_slice(&step.hash);
+        }
+        acc = double_sha256(&buf);
+    }
+    acc == root
+}
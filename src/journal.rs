@@ -0,0 +1,72 @@
+//! Append-only event journal for audit trails.
+//!
+//! Exchanges and other custodial operators have to answer "what did
+//! this node do, and when" long after the fact. The journal records
+//! consequential events — block connects and disconnects, peer
+//! rejections, wallet sends, admin RPC actions — in an append-only
+//! column family (CF_EVENTS in the chain database), each entry stamped
+//! with a wall-clock time, a monotonic sequence number and a reference
+//! to the object it concerns. Entries are only ever appended, never
+//! rewritten: a disconnect does not erase the connect it undoes, it
+//! records a second event pointing at the same block.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::Hash256;
+
+/// Upper bound on events returned per journal query.
+pub const MAX_JOURNAL_EVENTS: usize = 1_000;
+
+/// What happened, with a reference to the object it concerns.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EventKind {
+    /// A block joined the main chain.
+    BlockConnected { hash: Hash256, height: u64 },
+    /// The tip was disconnected (reorg or manual invalidation).
+    BlockDisconnected { hash: Hash256, height: u64 },
+    /// A transaction was accepted for broadcast through this node's
+    /// RPC interface.
+    TransactionSent { txid: Hash256 },
+    /// A peer's block or transaction failed consensus checks.
+    PeerRejected {
+        peer: String,
+        what: String,
+        hash: Hash256,
+        code: String,
+    },
+    /// An admin-scoped RPC method ran successfully.
+    AdminAction { method: String },
+}
+
+impl EventKind {
+    /// Stable event-type name for queries and log output.
+    pub fn name(&self) -> &'static str {
+        match self {
+            EventKind::BlockConnected { .. } => "block_connected",
+            EventKind::BlockDisconnected { .. } => "block_disconnected",
+            EventKind::TransactionSent { .. } => "transaction_sent",
+            EventKind::PeerRejected { .. } => "peer_rejected",
+            EventKind::AdminAction { .. } => "admin_action",
+        }
+    }
+}
+
+/// One journal entry. The sequence number is the CF_EVENTS key, so
+/// entries replay in exactly the order they were written.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Event {
+    pub seq: u64,
+    /// Unix seconds when the event was recorded.
+    pub timestamp: u64,
+    pub kind: EventKind,
+}
+
+/// Wall-clock seconds for event timestamps.
+pub fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,75 @@
+//! Optional OS keystore integration for wallet passwords.
+//!
+//! Users can opt into storing a wallet's encryption password in the
+//! platform keystore (macOS Keychain, Windows Credential Manager,
+//! Linux kernel keyutils) so low-value wallets unlock without a prompt
+//! at startup. The wallet file itself stays encrypted on disk; only
+//! the password lives in the OS store, which never writes it out in
+//! plaintext.
+
+use std::path::Path;
+
+/// Service name every Palicoin credential is registered under.
+const SERVICE: &str = "pali-coin";
+
+/// One keystore entry per wallet file, keyed by its canonical path so
+/// two wallets with the same file name do not collide.
+fn entry_for(wallet_path: &Path) -> Result<keyring::Entry, String> {
+    let account = wallet_path
+        .canonicalize()
+        .unwrap_or_else(|_| wallet_path.to_path_buf())
+        .to_string_lossy()
+        .into_owned();
+    keyring::Entry::new(SERVICE, &account).map_err(|e| format!("keystore unavailable: {}", e))
+}
+
+/// Saves the password for `wallet_path` in the OS keystore,
+/// overwriting any previous entry.
+pub fn store_password(wallet_path: &Path, password: &str) -> Result<(), String> {
+    entry_for(wallet_path)?
+        .set_password(password)
+        .map_err(|e| format!("failed to store password in keystore: {}", e))
+}
+
+/// Returns the stored password for `wallet_path`, or `None` when the
+/// keystore has no entry (or is unavailable on this platform).
+pub fn get_password(wallet_path: &Path) -> Option<String> {
+    entry_for(wallet_path).ok()?.get_password().ok()
+}
+
+/// Account name of the per-machine wallet-binding secret.
+const MACHINE_BINDING: &str = "machine-binding";
+
+/// Returns this machine's wallet-binding secret, minting and storing a
+/// fresh one on first use. The secret lives only in the platform
+/// secure store (Keychain/secure enclave, Credential Manager or kernel
+/// keyutils, TPM-backed where the OS does so), which is what ties a
+/// machine-bound wallet to this hardware.
+pub fn machine_secret() -> Result<[u8; 32], String> {
+    let entry = keyring::Entry::new(SERVICE, MACHINE_BINDING)
+        .map_err(|e| format!("keystore unavailable: {}", e))?;
+    match entry.get_password() {
+        Ok(hex) => hex::decode(hex)
+            .ok()
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| "corrupt machine-binding secret in keystore".to_string()),
+        Err(keyring::Error::NoEntry) => {
+            let mut secret = [0u8; 32];
+            rand::RngCore::fill_bytes(&mut rand::thread_rng(), &mut secret);
+            entry
+                .set_password(&hex::encode(secret))
+                .map_err(|e| format!("failed to store machine-binding secret: {}", e))?;
+            Ok(secret)
+        }
+        Err(e) => Err(format!("keystore unavailable: {}", e)),
+    }
+}
+
+/// Removes any stored password for `wallet_path`. Succeeds if no
+/// entry existed.
+pub fn forget_password(wallet_path: &Path) -> Result<(), String> {
+    match entry_for(wallet_path)?.delete_credential() {
+        Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+        Err(e) => Err(format!("failed to remove keystore entry: {}", e)),
+    }
+}
@@ -0,0 +1,66 @@
+//! Palicoin: a proof-of-work cryptocurrency node, wallet and miner.
+
+pub mod addrman;
+pub mod alerts;
+pub mod audit;
+pub mod backup;
+pub mod blockchain;
+pub mod cfilters;
+pub mod channels;
+pub mod client;
+pub mod coinlock;
+pub mod consensus;
+pub mod crypto;
+pub mod dandelion;
+pub mod deposits;
+pub mod error;
+#[cfg(feature = "explorer")]
+pub mod explorer;
+#[cfg(feature = "faucet")]
+pub mod faucet;
+pub mod forks;
+pub mod getwork;
+pub mod hash;
+pub mod journal;
+pub mod keystore;
+#[cfg(feature = "libp2p")]
+pub mod libp2p_transport;
+pub mod logbuffer;
+pub mod math;
+pub mod mempool;
+pub mod miner;
+pub mod monitor;
+pub mod msgqueue;
+pub mod multiwallet;
+pub mod musig;
+pub mod network;
+pub mod node;
+pub mod notify;
+pub mod pairing;
+pub mod peerstats;
+pub mod poa;
+pub mod pool;
+pub mod pow;
+pub mod preflight;
+pub mod proofs;
+pub mod rejection;
+pub mod reputation;
+pub mod reserves;
+pub mod rpc;
+pub mod rpc_auth;
+pub mod sigcache;
+pub mod sim;
+pub mod snapshot;
+pub mod sse;
+pub mod sync;
+pub mod telemetry;
+pub mod toggles;
+pub mod transport;
+pub mod types;
+pub mod updates;
+pub mod wallet;
+pub mod wallet_store;
+pub mod watch;
+
+/// Mainnet chain identifier; testnet is 2, regtest is 3.
+pub const MAINNET_CHAIN_ID: u8 = 1;
@@ -0,0 +1,336 @@
+//! Experimental libp2p peer transport (feature `libp2p`).
+//!
+//! An alternative to the native TCP transport: connections are
+//! encrypted with noise and multiplexed with yamux, peers are
+//! discovered through kademlia instead of addr gossip, and
+//! [`NetworkMessage`] rides on top — announcements map onto gossipsub
+//! topics, queries onto a request/response protocol. The payoff is
+//! libp2p's battle-tested NAT traversal and discovery; the cost is a
+//! heavy dependency, which is why the whole module sits behind a
+//! feature flag and the native transport remains the default.
+//!
+//! The service runs as an event loop owning the swarm, in the style of
+//! [`Notifier::run`](crate::notify::Notifier::run): callers hand it
+//! [`Outbound`] commands over a channel and receive [`Inbound`] events
+//! over another, so no lock is ever held across swarm I/O.
+
+use libp2p::futures::StreamExt;
+use libp2p::request_response::{self, ProtocolSupport, ResponseChannel};
+use libp2p::swarm::{NetworkBehaviour, SwarmEvent};
+use libp2p::{gossipsub, kad, noise, tcp, yamux, StreamProtocol, Swarm};
+use tokio::sync::mpsc;
+
+use crate::blockchain::Blockchain;
+use crate::network::NetworkMessage;
+use crate::proofs;
+
+pub use libp2p::{Multiaddr, PeerId};
+
+/// Request/response protocol id for block and state queries.
+pub const QUERY_PROTOCOL: &str = "/pali/query/1";
+
+/// Gossipsub topic carrying block announcements for a chain.
+pub fn block_topic(chain_id: u8) -> String {
+    format!("pali/{}/blocks", chain_id)
+}
+
+/// Gossipsub topic carrying transaction announcements for a chain.
+pub fn tx_topic(chain_id: u8) -> String {
+    format!("pali/{}/txs", chain_id)
+}
+
+/// The topic an announcement belongs on, or `None` for messages that
+/// are not announcements (those travel as queries or not at all).
+pub fn topic_for(chain_id: u8, message: &NetworkMessage) -> Option<String> {
+    match message {
+        NetworkMessage::Block(_) | NetworkMessage::Blocks(_) => Some(block_topic(chain_id)),
+        // Stem-phase privacy comes from Dandelion routing on the native
+        // transport; gossipsub floods, so stems fluff immediately here.
+        NetworkMessage::Transaction(_) | NetworkMessage::StemTransaction(_) => {
+            Some(tx_topic(chain_id))
+        }
+        _ => None,
+    }
+}
+
+/// Answers a query received over request/response, mirroring the
+/// native transport's handlers. Queries that need per-peer connection
+/// state have no meaning here and return `None`.
+pub fn answer_query(chain: &Blockchain, message: &NetworkMessage) -> Option<NetworkMessage> {
+    match message {
+        NetworkMessage::Ping(nonce) => Some(NetworkMessage::Pong(*nonce)),
+        NetworkMessage::GetBlockRange { start, count } => {
+            let count = (*count).min(crate::network::MAX_INV_PER_MESSAGE as u32) as u64;
+            let mut blocks = Vec::new();
+            for height in *start..start + count {
+                match chain.get_block_by_height(height) {
+                    Ok(Some(block)) => blocks.push(block),
+                    _ => break,
+                }
+            }
+            Some(NetworkMessage::Blocks(blocks))
+        }
+        NetworkMessage::GetUtxoProof { address } => proofs::build_address_proof(chain, address)
+            .ok()
+            .map(NetworkMessage::UtxoProof),
+        _ => None,
+    }
+}
+
+#[derive(NetworkBehaviour)]
+struct Behaviour {
+    gossipsub: gossipsub::Behaviour,
+    kademlia: kad::Behaviour<kad::store::MemoryStore>,
+    queries: request_response::cbor::Behaviour<NetworkMessage, NetworkMessage>,
+}
+
+/// Listen address, bootstrap peers and chain binding for the service.
+#[derive(Debug, Clone)]
+pub struct Libp2pConfig {
+    pub listen: Multiaddr,
+    /// Peers dialed at startup and seeded into the kademlia table;
+    /// addresses carrying a `/p2p/<peer id>` suffix improve discovery.
+    pub bootstrap: Vec<Multiaddr>,
+    pub chain_id: u8,
+}
+
+/// An event surfaced to the node.
+pub enum Inbound {
+    /// An announcement from a gossipsub topic.
+    Gossip {
+        peer: PeerId,
+        message: NetworkMessage,
+    },
+    /// A query expecting an answer through `Outbound::Respond`.
+    Request {
+        peer: PeerId,
+        message: NetworkMessage,
+        channel: ResponseChannel<NetworkMessage>,
+    },
+    /// The answer to an earlier `Outbound::Request`.
+    Response {
+        peer: PeerId,
+        message: NetworkMessage,
+    },
+}
+
+/// A command for the event loop.
+pub enum Outbound {
+    /// Publish an announcement on its topic; non-announcements are
+    /// dropped.
+    Publish(NetworkMessage),
+    /// Send a query to a specific peer.
+    Request {
+        peer: PeerId,
+        message: NetworkMessage,
+    },
+    /// Answer an `Inbound::Request`.
+    Respond {
+        channel: ResponseChannel<NetworkMessage>,
+        message: NetworkMessage,
+    },
+}
+
+/// The swarm event loop. Construct with [`Libp2pService::new`], then
+/// hand the returned service to a task running [`Libp2pService::run`].
+pub struct Libp2pService {
+    swarm: Swarm<Behaviour>,
+    chain_id: u8,
+    commands: mpsc::UnboundedReceiver<Outbound>,
+    events: mpsc::UnboundedSender<Inbound>,
+}
+
+impl Libp2pService {
+    /// Builds the swarm and returns it with the command sender and
+    /// event receiver the node uses to talk to it.
+    #[allow(clippy::type_complexity)]
+    pub fn new(
+        config: Libp2pConfig,
+    ) -> Result<
+        (
+            Libp2pService,
+            mpsc::UnboundedSender<Outbound>,
+            mpsc::UnboundedReceiver<Inbound>,
+        ),
+        String,
+    > {
+        let mut swarm = libp2p::SwarmBuilder::with_new_identity()
+            .with_tokio()
+            .with_tcp(
+                tcp::Config::default(),
+                noise::Config::new,
+                yamux::Config::default,
+            )
+            .map_err(|e| format!("libp2p transport setup failed: {}", e))?
+            .with_behaviour(|key| {
+                let gossipsub = gossipsub::Behaviour::new(
+                    gossipsub::MessageAuthenticity::Signed(key.clone()),
+                    gossipsub::Config::default(),
+                )?;
+                let peer_id = key.public().to_peer_id();
+                let kademlia =
+                    kad::Behaviour::new(peer_id, kad::store::MemoryStore::new(peer_id));
+                let queries = request_response::cbor::Behaviour::new(
+                    [(
+                        StreamProtocol::new(QUERY_PROTOCOL),
+                        ProtocolSupport::Full,
+                    )],
+                    request_response::Config::default(),
+                );
+                Ok(Behaviour {
+                    gossipsub,
+                    kademlia,
+                    queries,
+                })
+            })
+            .map_err(|e| format!("libp2p behaviour setup failed: {}", e))?
+            .build();
+
+        swarm.behaviour_mut().kademlia.set_mode(Some(kad::Mode::Server));
+        for topic in [block_topic(config.chain_id), tx_topic(config.chain_id)] {
+            swarm
+                .behaviour_mut()
+                .gossipsub
+                .subscribe(&gossipsub::IdentTopic::new(topic))
+                .map_err(|e| format!("gossipsub subscribe failed: {:?}", e))?;
+        }
+        swarm
+            .listen_on(config.listen.clone())
+            .map_err(|e| format!("libp2p listen failed: {}", e))?;
+        for addr in &config.bootstrap {
+            if let Some(peer) = peer_id_of(addr) {
+                swarm
+                    .behaviour_mut()
+                    .kademlia
+                    .add_address(&peer, addr.clone());
+            }
+            if let Err(e) = swarm.dial(addr.clone()) {
+                log::warn!("libp2p bootstrap dial {} failed: {}", addr, e);
+            }
+        }
+        // Fails only when the routing table is empty; discovery then
+        // starts from whoever dials us instead.
+        let _ = swarm.behaviour_mut().kademlia.bootstrap();
+
+        let (command_tx, command_rx) = mpsc::unbounded_channel();
+        let (event_tx, event_rx) = mpsc::unbounded_channel();
+        Ok((
+            Libp2pService {
+                swarm,
+                chain_id: config.chain_id,
+                commands: command_rx,
+                events: event_tx,
+            },
+            command_tx,
+            event_rx,
+        ))
+    }
+
+    /// Local peer id of this swarm.
+    pub fn peer_id(&self) -> PeerId {
+        *self.swarm.local_peer_id()
+    }
+
+    /// Drives the swarm until every command sender is gone.
+    pub async fn run(mut self) {
+        loop {
+            tokio::select! {
+                event = self.swarm.select_next_some() => self.handle_swarm_event(event),
+                command = self.commands.recv() => match command {
+                    Some(command) => self.handle_command(command),
+                    None => return,
+                },
+            }
+        }
+    }
+
+    fn handle_command(&mut self, command: Outbound) {
+        match command {
+            Outbound::Publish(message) => {
+                let Some(topic) = topic_for(self.chain_id, &message) else {
+                    return;
+                };
+                let Ok(payload) = bincode::serialize(&message) else {
+                    return;
+                };
+                // Publishing with no subscribed peers yet is routine
+                // at startup, not worth more than a debug line.
+                if let Err(e) = self
+                    .swarm
+                    .behaviour_mut()
+                    .gossipsub
+                    .publish(gossipsub::IdentTopic::new(topic), payload)
+                {
+                    log::debug!("gossipsub publish failed: {:?}", e);
+                }
+            }
+            Outbound::Request { peer, message } => {
+                self.swarm
+                    .behaviour_mut()
+                    .queries
+                    .send_request(&peer, message);
+            }
+            Outbound::Respond { channel, message } => {
+                if self
+                    .swarm
+                    .behaviour_mut()
+                    .queries
+                    .send_response(channel, message)
+                    .is_err()
+                {
+                    log::debug!("query response dropped: peer went away");
+                }
+            }
+        }
+    }
+
+    fn handle_swarm_event(&mut self, event: SwarmEvent<BehaviourEvent>) {
+        match event {
+            SwarmEvent::NewListenAddr { address, .. } => {
+                log::info!("libp2p listening on {}", address);
+            }
+            SwarmEvent::Behaviour(BehaviourEvent::Gossipsub(gossipsub::Event::Message {
+                propagation_source,
+                message,
+                ..
+            })) => match bincode::deserialize::<NetworkMessage>(&message.data) {
+                Ok(decoded) => {
+                    let _ = self.events.send(Inbound::Gossip {
+                        peer: propagation_source,
+                        message: decoded,
+                    });
+                }
+                Err(e) => log::debug!("undecodable gossip from {}: {}", propagation_source, e),
+            },
+            SwarmEvent::Behaviour(BehaviourEvent::Queries(request_response::Event::Message {
+                peer,
+                message,
+            })) => match message {
+                request_response::Message::Request {
+                    request, channel, ..
+                } => {
+                    let _ = self.events.send(Inbound::Request {
+                        peer,
+                        message: request,
+                        channel,
+                    });
+                }
+                request_response::Message::Response { response, .. } => {
+                    let _ = self.events.send(Inbound::Response {
+                        peer,
+                        message: response,
+                    });
+                }
+            },
+            _ => {}
+        }
+    }
+}
+
+/// The peer id a multiaddr pins with a trailing `/p2p/` component.
+fn peer_id_of(addr: &Multiaddr) -> Option<PeerId> {
+    addr.iter().find_map(|protocol| match protocol {
+        libp2p::multiaddr::Protocol::P2p(peer) => Some(peer),
+        _ => None,
+    })
+}
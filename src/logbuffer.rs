@@ -0,0 +1,58 @@
+//! Ring buffer capturing recent log lines so the RPC layer (and the
+//! monitor TUI) can show them without tailing files.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use log::{Level, Metadata, Record};
+
+/// Lines retained in memory.
+const CAPACITY: usize = 500;
+
+static BUFFER: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+
+struct TeeLogger {
+    inner: env_logger::Logger,
+}
+
+impl log::Log for TeeLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.inner.enabled(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if record.level() <= Level::Info || self.inner.enabled(record.metadata()) {
+            let line = format!(
+                "{} [{}] {}",
+                record.level(),
+                record.target(),
+                record.args()
+            );
+            let mut buf = BUFFER.lock().expect("log buffer lock poisoned");
+            if buf.len() >= CAPACITY {
+                buf.pop_front();
+            }
+            buf.push_back(line);
+        }
+        self.inner.log(record);
+    }
+
+    fn flush(&self) {
+        self.inner.flush();
+    }
+}
+
+/// Installs the buffering logger; call once instead of `env_logger::init`.
+pub fn init() {
+    let inner = env_logger::Builder::from_default_env().build();
+    let max_level = inner.filter();
+    if log::set_boxed_logger(Box::new(TeeLogger { inner })).is_ok() {
+        log::set_max_level(max_level.max(log::LevelFilter::Info));
+    }
+}
+
+/// The most recent `n` captured lines, oldest first.
+pub fn recent(n: usize) -> Vec<String> {
+    let buf = BUFFER.lock().expect("log buffer lock poisoned");
+    buf.iter().rev().take(n).rev().cloned().collect()
+}
@@ -0,0 +1,96 @@
+//! Difficulty, target and work arithmetic.
+
+use crate::types::{Hash256, TARGET_BLOCK_TIME};
+
+/// Highest allowed target (lowest difficulty), compact form.
+pub const MAX_BITS: u32 = 0x1f00ffff;
+
+/// Expands a compact "bits" value into a full 256-bit target.
+pub fn bits_to_target(bits: u32) -> [u8; 32] {
+    let exponent = (bits >> 24) as usize;
+    let mantissa = bits & 0x00ff_ffff;
+    let mut target = [0u8; 32];
+    if exponent <= 3 {
+        let shifted = mantissa >> (8 * (3 - exponent));
+        target[29..].copy_from_slice(&shifted.to_be_bytes()[1..]);
+    } else if exponent <= 32 {
+        let bytes = mantissa.to_be_bytes();
+        let start = 32 - exponent;
+        for (i, b) in bytes[1..].iter().enumerate() {
+            if start + i < 32 {
+                target[start + i] = *b;
+            }
+        }
+    }
+    target
+}
+
+/// True when `hash` interpreted as a big-endian integer meets `bits`.
+pub fn hash_meets_target(hash: &Hash256, bits: u32) -> bool {
+    let target = bits_to_target(bits);
+    hash.as_slice() <= target.as_slice()
+}
+
+/// Approximate work contributed by a block at the given target, as
+/// 2^256 / (target + 1) truncated to u128.
+pub fn block_work(bits: u32) -> u128 {
+    let target = bits_to_target(bits);
+    // Take the most significant 128 bits of the target; work is the
+    // inverse, saturating for very easy targets.
+    let mut hi = [0u8; 16];
+    hi.copy_from_slice(&target[..16]);
+    let t = u128::from_be_bytes(hi);
+    if t == 0 {
+        let mut lo = [0u8; 16];
+        lo.copy_from_slice(&target[16..]);
+        let t = u128::from_be_bytes(lo);
+        u128::MAX / t.saturating_add(1)
+    } else {
+        (u128::MAX / t.saturating_add(1)) >> 64
+    }
+}
+
+/// Estimated network hashrate (hashes/sec) from work accumulated over a
+/// window of blocks solved in `elapsed_secs`.
+pub fn calculate_hashrate(window_work: u128, elapsed_secs: u64) -> f64 {
+    if elapsed_secs == 0 {
+        return 0.0;
+    }
+    window_work as f64 / elapsed_secs as f64
+}
+
+/// Simple per-window retarget: scale the previous target by the ratio of
+/// actual to expected solve time, clamped to a factor of 4 either way.
+pub fn next_bits(prev_bits: u32, actual_timespan: u64, window: u64) -> u32 {
+    let expected = TARGET_BLOCK_TIME * window;
+    let clamped = actual_timespan.clamp(expected / 4, expected * 4);
+    let exponent = prev_bits >> 24;
+    let mantissa = (prev_bits & 0x00ff_ffff) as u64;
+    let mut new_mantissa = mantissa * clamped / expected;
+    let mut new_exponent = exponent;
+    while new_mantissa > 0x00ff_ffff {
+        new_mantissa >>= 8;
+        new_exponent += 1;
+    }
+    while new_mantissa != 0 && new_mantissa < 0x0000_8000 {
+        new_mantissa <<= 8;
+        new_exponent -= 1;
+    }
+    let bits = (new_exponent << 24) | new_mantissa as u32;
+    bits.min(MAX_BITS)
+}
+
+/// Human-facing difficulty: ratio of the maximum target to this target.
+pub fn difficulty(bits: u32) -> f64 {
+    let max = bits_to_target(MAX_BITS);
+    let cur = bits_to_target(bits);
+    let to_f64 = |t: &[u8; 32]| {
+        t.iter()
+            .fold(0.0f64, |acc, b| acc * 256.0 + *b as f64)
+    };
+    let c = to_f64(&cur);
+    if c == 0.0 {
+        return 0.0;
+    }
+    to_f64(&max) / c
+}
@@ -0,0 +1,628 @@
+//! In-memory pool of validated, unconfirmed transactions.
+
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+
+use crate::error::{MempoolError, MempoolErrorKind};
+use crate::types::{Hash256, Transaction};
+
+/// Default ceiling on pooled transactions before low-fee eviction.
+pub const MAX_MEMPOOL_TXS: usize = 50_000;
+
+/// Minimum relay fee rate in units per byte.
+pub const MIN_RELAY_FEE_RATE: f64 = 1.0;
+
+/// Largest transaction the default policy relays, in serialized bytes.
+pub const MAX_STANDARD_TX_BYTES: usize = 100 * 1024;
+
+/// Longest pending same-sender nonce chain the default policy pools.
+pub const MAX_ANCESTOR_CHAIN: usize = 25;
+
+/// Most transactions accepted in one package submission.
+pub const MAX_PACKAGE_TXS: usize = 25;
+
+/// Smallest output amount the default policy relays, in base units.
+pub const DUST_THRESHOLD: u64 = 1_000;
+
+/// Most data-carrier payload bytes the default policy relays.
+pub const MAX_DATA_CARRIER_BYTES: usize = 256;
+
+/// Relay policy: what this node agrees to pool and gossip. These are
+/// operator knobs, not consensus — a block may confirm transactions the
+/// local policy refuses, and `Blockchain::validate_transaction` stays
+/// untouched by any of them.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct Policy {
+    /// Minimum fee rate in units per byte.
+    pub min_relay_fee_rate: f64,
+    /// Largest serialized transaction accepted.
+    pub max_tx_bytes: usize,
+    /// Longest same-sender pending nonce chain.
+    pub max_ancestors: usize,
+    /// Smallest transfer amount accepted.
+    pub dust_threshold: u64,
+    /// Most data-carrier payload bytes accepted.
+    pub max_data_bytes: usize,
+}
+
+impl Default for Policy {
+    fn default() -> Self {
+        Policy {
+            min_relay_fee_rate: MIN_RELAY_FEE_RATE,
+            max_tx_bytes: MAX_STANDARD_TX_BYTES,
+            max_ancestors: MAX_ANCESTOR_CHAIN,
+            dust_threshold: DUST_THRESHOLD,
+            max_data_bytes: MAX_DATA_CARRIER_BYTES,
+        }
+    }
+}
+
+/// Lower bounds of the fee-histogram buckets, in units per byte.
+/// Nothing below the relay minimum can be pooled, so the first bucket
+/// starts there.
+pub const FEE_HISTOGRAM_BUCKETS: &[f64] = &[
+    1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 8.0, 10.0, 12.0, 15.0, 20.0, 30.0, 40.0, 50.0, 70.0, 100.0,
+    150.0, 200.0,
+];
+
+/// Blocks a wallet transaction waits unconfirmed before the node
+/// re-announces it (roughly half an hour at the 3-minute target).
+pub const REBROADCAST_AFTER_BLOCKS: u64 = 10;
+
+/// How a transaction reached this pool. Only wallet-originated
+/// transactions are ever rebroadcast: repeating third-party relay
+/// traffic would tag this node as the likely origin of every
+/// transaction it repeats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxOrigin {
+    /// Submitted locally over RPC (our wallet, the faucet).
+    Wallet,
+    /// Arrived from a peer.
+    Relay,
+}
+
+/// A pooled transaction with its acceptance metadata.
+#[derive(Debug, Clone)]
+pub struct MempoolEntry {
+    pub tx: Transaction,
+    pub fee: u64,
+    pub size: usize,
+    /// Unix time the transaction entered the pool.
+    pub time_added: u64,
+    /// Chain height when the transaction entered the pool.
+    pub height_added: u64,
+    /// Whether we created this transaction or merely relayed it.
+    pub origin: TxOrigin,
+    /// Height at which this transaction was last announced to peers.
+    pub last_broadcast_height: u64,
+}
+
+impl MempoolEntry {
+    pub fn fee_rate(&self) -> f64 {
+        self.fee as f64 / self.size as f64
+    }
+}
+
+/// One fee-histogram bucket: every pooled transaction whose fee rate
+/// is at least `min_rate` and below the next bucket's bound.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct FeeBucket {
+    pub min_rate: f64,
+    pub count: u64,
+    pub size: u64,
+    pub fees: u64,
+}
+
+/// Ancestor-package fee statistics for one pooled transaction. In the
+/// account model a transaction's in-pool ancestors are the same
+/// sender's pending lower nonces — none of them can confirm without
+/// the others, so they are priced as a unit.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PackageInfo {
+    /// Pooled ancestors including the transaction itself.
+    pub ancestor_count: usize,
+    /// Summed fees of the transaction and its pooled ancestors.
+    pub ancestor_fees: u64,
+    /// Summed sizes of the transaction and its pooled ancestors.
+    pub ancestor_size: usize,
+    /// The fee rate block selection actually prices this transaction
+    /// at: the best rate among sender-chain packages containing it, so
+    /// a low-fee parent shows the boost its children pay for.
+    pub effective_fee_rate: f64,
+}
+
+#[derive(Default)]
+pub struct Mempool {
+    entries: HashMap<Hash256, MempoolEntry>,
+    /// Pending spenders per sender address nonce, to reject double-spends
+    /// of the same nonce.
+    by_sender_nonce: HashMap<([u8; 20], u64), Hash256>,
+    /// Operator-set fee deltas (the transaction accelerator): block
+    /// selection and eviction price each transaction at its fee plus
+    /// this delta, without touching the transaction itself. Deltas are
+    /// kept independently of the entries, so one can be set before the
+    /// transaction arrives and survives until it confirms.
+    priority_deltas: HashMap<Hash256, i64>,
+    policy: Policy,
+}
+
+impl Mempool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_policy(policy: Policy) -> Self {
+        Mempool {
+            policy,
+            ..Self::default()
+        }
+    }
+
+    pub fn policy(&self) -> &Policy {
+        &self.policy
+    }
+
+    /// Swaps the relay policy. Already-pooled transactions are kept
+    /// even when the new policy would refuse them; they age out or
+    /// confirm normally.
+    pub fn set_policy(&mut self, policy: Policy) {
+        self.policy = policy;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn contains(&self, tx_hash: &Hash256) -> bool {
+        self.entries.contains_key(tx_hash)
+    }
+
+    pub fn get(&self, tx_hash: &Hash256) -> Option<&MempoolEntry> {
+        self.entries.get(tx_hash)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&Hash256, &MempoolEntry)> {
+        self.entries.iter()
+    }
+
+    /// Total serialized size of all pooled transactions.
+    pub fn total_size(&self) -> usize {
+        self.entries.values().map(|e| e.size).sum()
+    }
+
+    /// Summed fees of all pooled transactions.
+    pub fn total_fees(&self) -> u64 {
+        self.entries.values().map(|e| e.fee).sum()
+    }
+
+    /// Adjusts the fee this transaction is priced at during block
+    /// selection and eviction by `fee_delta` (repeated calls
+    /// accumulate; a negative total deprioritises). Returns the
+    /// accumulated delta; zero clears the entry.
+    pub fn prioritise(&mut self, tx_hash: Hash256, fee_delta: i64) -> i64 {
+        let total = self.priority_deltas.entry(tx_hash).or_insert(0);
+        *total += fee_delta;
+        let total = *total;
+        if total == 0 {
+            self.priority_deltas.remove(&tx_hash);
+        }
+        total
+    }
+
+    /// The operator-set fee delta for a transaction; zero when unset.
+    pub fn priority_delta(&self, tx_hash: &Hash256) -> i64 {
+        self.priority_deltas.get(tx_hash).copied().unwrap_or(0)
+    }
+
+    /// Every transaction with a fee delta set, whether pooled yet or
+    /// not.
+    pub fn prioritised(&self) -> impl Iterator<Item = (&Hash256, i64)> {
+        self.priority_deltas.iter().map(|(hash, delta)| (hash, *delta))
+    }
+
+    /// The fee block selection prices an entry at: its actual fee plus
+    /// the operator delta, floored at zero.
+    fn effective_fee(&self, entry: &MempoolEntry) -> u64 {
+        let delta = self.priority_delta(&entry.tx.hash());
+        entry.fee.saturating_add_signed(delta)
+    }
+
+    /// Distribution of pooled transactions across the fee-rate
+    /// buckets, with per-bucket counts, sizes and fees.
+    pub fn fee_histogram(&self) -> Vec<FeeBucket> {
+        let mut buckets: Vec<FeeBucket> = FEE_HISTOGRAM_BUCKETS
+            .iter()
+            .map(|&min_rate| FeeBucket {
+                min_rate,
+                count: 0,
+                size: 0,
+                fees: 0,
+            })
+            .collect();
+        for entry in self.entries.values() {
+            let rate = entry.fee_rate();
+            let idx = FEE_HISTOGRAM_BUCKETS
+                .iter()
+                .rposition(|&bound| rate >= bound)
+                .unwrap_or(0);
+            let bucket = &mut buckets[idx];
+            bucket.count += 1;
+            bucket.size += entry.size as u64;
+            bucket.fees += entry.fee;
+        }
+        buckets
+    }
+
+    /// Fee rate that should confirm within `target_blocks` blocks of
+    /// `block_bytes` each: the rate of the transaction at the capacity
+    /// boundary when the pool is drained best-rate-first. An
+    /// uncongested pool answers the relay minimum.
+    pub fn estimate_fee_rate(&self, target_blocks: u64, block_bytes: usize) -> f64 {
+        let capacity = (target_blocks as usize).saturating_mul(block_bytes);
+        let mut rates: Vec<(f64, usize)> = self
+            .entries
+            .values()
+            .map(|e| (e.fee_rate(), e.size))
+            .collect();
+        rates.sort_by(|a, b| b.0.partial_cmp(&a.0).unwrap_or(std::cmp::Ordering::Equal));
+        let mut used = 0usize;
+        for (rate, size) in rates {
+            used += size;
+            if used > capacity {
+                return rate.max(self.policy.min_relay_fee_rate);
+            }
+        }
+        self.policy.min_relay_fee_rate
+    }
+
+    /// Inserts an already-validated transaction. The caller is expected
+    /// to have run `Blockchain::validate_transaction` first. A pending
+    /// transaction with the same sender and nonce is replaced when it
+    /// signals replaceability and the newcomer pays a sufficiently
+    /// higher fee (replace-by-fee).
+    /// Pools a transaction that arrived from a peer.
+    pub fn insert(&mut self, tx: Transaction, height: u64) -> Result<Hash256, MempoolError> {
+        self.insert_with_origin(tx, height, TxOrigin::Relay, true)
+    }
+
+    /// Pools a transaction submitted locally, marking it for the
+    /// rebroadcast loop if it lingers unconfirmed.
+    pub fn insert_from_wallet(
+        &mut self,
+        tx: Transaction,
+        height: u64,
+    ) -> Result<Hash256, MempoolError> {
+        self.insert_with_origin(tx, height, TxOrigin::Wallet, true)
+    }
+
+    /// Pools one member of a package that already passed
+    /// [`check_package`](Self::check_package): the package's combined
+    /// rate stands in for the per-transaction fee floor, which is how a
+    /// zero-fee parent gets in at all. The caller owns atomicity —
+    /// remove the members already pooled if a later one fails.
+    pub fn insert_package_member(
+        &mut self,
+        tx: Transaction,
+        height: u64,
+    ) -> Result<Hash256, MempoolError> {
+        self.insert_with_origin(tx, height, TxOrigin::Relay, false)
+    }
+
+    fn insert_with_origin(
+        &mut self,
+        tx: Transaction,
+        height: u64,
+        origin: TxOrigin,
+        enforce_fee_floor: bool,
+    ) -> Result<Hash256, MempoolError> {
+        let tx_hash = tx.hash();
+        if self.entries.contains_key(&tx_hash) {
+            return Err(MempoolError::new(MempoolErrorKind::Duplicate, "transaction already in mempool"));
+        }
+        if tx.size() > self.policy.max_tx_bytes {
+            return Err(MempoolError::new(MempoolErrorKind::OversizedTx, "transaction exceeds the policy size limit"));
+        }
+        if tx.data.len() > self.policy.max_data_bytes {
+            return Err(MempoolError::new(MempoolErrorKind::OversizedData, "data carrier exceeds the policy limit"));
+        }
+        if tx.amount < self.policy.dust_threshold {
+            return Err(MempoolError::new(MempoolErrorKind::Dust, "amount below the dust threshold"));
+        }
+        let sender_key = (tx.from, tx.nonce);
+        if let Some(existing_hash) = self.by_sender_nonce.get(&sender_key).copied() {
+            let existing = self
+                .entries
+                .get(&existing_hash)
+                .expect("sender-nonce index points at live entry");
+            if !existing.tx.replaceable {
+                return Err(MempoolError::new(MempoolErrorKind::NonReplaceable, "sender nonce already pending (not replaceable)"));
+            }
+            // The replacement must pay for its own relay on top of the
+            // fee the original already paid.
+            let min_fee = existing.fee + (tx.size() as f64 * self.policy.min_relay_fee_rate) as u64;
+            if tx.fee < min_fee {
+                return Err(MempoolError::new(
+                    MempoolErrorKind::FeeTooLow,
+                    format!("replacement fee {} below required {}", tx.fee, min_fee),
+                ));
+            }
+            self.remove(&existing_hash);
+        }
+        // After any replacement, so a same-length chain can still be
+        // replaced at the ancestor limit.
+        if self.sender_chain(&tx.from).len() >= self.policy.max_ancestors {
+            return Err(MempoolError::new(MempoolErrorKind::ChainTooLong, "sender's pending ancestor chain is at the policy limit"));
+        }
+        let size = tx.size();
+        let fee = tx.fee;
+        if enforce_fee_floor && (fee as f64 / size as f64) < self.policy.min_relay_fee_rate {
+            return Err(MempoolError::new(MempoolErrorKind::FeeTooLow, "fee rate below relay minimum"));
+        }
+        if self.entries.len() >= MAX_MEMPOOL_TXS {
+            self.evict_lowest_fee_rate();
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        self.by_sender_nonce.insert(sender_key, tx_hash);
+        self.entries.insert(
+            tx_hash,
+            MempoolEntry {
+                tx,
+                fee,
+                size,
+                time_added: now,
+                height_added: height,
+                origin,
+                last_broadcast_height: height,
+            },
+        );
+        Ok(tx_hash)
+    }
+
+    /// Wallet transactions still pooled `REBROADCAST_AFTER_BLOCKS`
+    /// blocks after their last announcement. Each returned transaction
+    /// has its announcement height reset to `height`, so a stuck
+    /// transaction goes out once per interval rather than every call.
+    pub fn rebroadcast_candidates(&mut self, height: u64) -> Vec<Transaction> {
+        let mut due = Vec::new();
+        for entry in self.entries.values_mut() {
+            if entry.origin == TxOrigin::Wallet
+                && height >= entry.last_broadcast_height + REBROADCAST_AFTER_BLOCKS
+            {
+                entry.last_broadcast_height = height;
+                due.push(entry.tx.clone());
+            }
+        }
+        due
+    }
+
+    pub fn remove(&mut self, tx_hash: &Hash256) -> Option<MempoolEntry> {
+        let entry = self.entries.remove(tx_hash)?;
+        self.by_sender_nonce.remove(&(entry.tx.from, entry.tx.nonce));
+        Some(entry)
+    }
+
+    /// Validates a transaction whose nonce is ahead of the confirmed
+    /// one as a chained child: every nonce between must be pending from
+    /// the same sender, and the confirmed balance must cover the whole
+    /// pending chain plus the newcomer.
+    pub fn validate_chained(
+        &self,
+        tx: &Transaction,
+        confirmed_nonce: u64,
+        balance: u64,
+    ) -> Result<(), MempoolError> {
+        if tx.nonce <= confirmed_nonce {
+            return Err(MempoolError::new(MempoolErrorKind::BrokenChain, "nonce is not ahead of the confirmed state"));
+        }
+        let mut chained_spend: u64 = 0;
+        for nonce in confirmed_nonce..tx.nonce {
+            let hash = self
+                .by_sender_nonce
+                .get(&(tx.from, nonce))
+                .ok_or_else(|| {
+                    MempoolError::new(
+                        MempoolErrorKind::BrokenChain,
+                        format!("nonce gap at {}: not a chained transaction", nonce),
+                    )
+                })?;
+            let ancestor = self
+                .entries
+                .get(hash)
+                .expect("sender-nonce index points at live entry");
+            chained_spend = chained_spend
+                .checked_add(ancestor.tx.amount)
+                .and_then(|v| v.checked_add(ancestor.fee))
+                .ok_or_else(|| MempoolError::new(MempoolErrorKind::BrokenChain, "pending chain value overflow"))?;
+        }
+        let needed = chained_spend
+            .checked_add(tx.amount)
+            .and_then(|v| v.checked_add(tx.fee))
+            .ok_or_else(|| MempoolError::new(MempoolErrorKind::BrokenChain, "pending chain value overflow"))?;
+        if balance < needed {
+            return Err(MempoolError::new(
+                MempoolErrorKind::BrokenChain,
+                format!(
+                    "balance {} cannot fund the pending chain ({} needed)",
+                    balance, needed
+                ),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Policy screen for an atomic package submission: one sender,
+    /// contiguous ascending nonces (the CPFP shape this chain's account
+    /// model supports), and a combined fee rate clearing the relay
+    /// minimum. Members are then pooled one by one with
+    /// [`insert_package_member`](Self::insert_package_member); judging
+    /// the fee rate across the package is what lets a zero-fee parent
+    /// required by a high-fee child get in.
+    pub fn check_package(&self, txs: &[Transaction]) -> Result<(), MempoolError> {
+        if txs.is_empty() {
+            return Err(MempoolError::new(MempoolErrorKind::BadPackage, "empty package"));
+        }
+        if txs.len() > MAX_PACKAGE_TXS {
+            return Err(MempoolError::new(
+                MempoolErrorKind::BadPackage,
+                format!("package exceeds {} transactions", MAX_PACKAGE_TXS),
+            ));
+        }
+        for pair in txs.windows(2) {
+            if pair[1].from != pair[0].from {
+                return Err(MempoolError::new(MempoolErrorKind::BadPackage, "package members spend from different senders"));
+            }
+            if pair[1].nonce != pair[0].nonce + 1 {
+                return Err(MempoolError::new(MempoolErrorKind::BadPackage, "package nonces are not contiguous"));
+            }
+        }
+        let total_fee: u64 = txs.iter().map(|tx| tx.fee).sum();
+        let total_size: usize = txs.iter().map(|tx| tx.size()).sum();
+        if (total_fee as f64 / total_size as f64) < self.policy.min_relay_fee_rate {
+            return Err(MempoolError::new(MempoolErrorKind::FeeTooLow, "package fee rate below relay minimum"));
+        }
+        Ok(())
+    }
+
+    /// The sender's pending transactions in nonce order, cut at the
+    /// first gap so a package never references a missing ancestor.
+    fn sender_chain(&self, from: &[u8; 20]) -> Vec<&MempoolEntry> {
+        let mut entries: Vec<&MempoolEntry> = self
+            .entries
+            .values()
+            .filter(|e| e.tx.from == *from)
+            .collect();
+        entries.sort_by_key(|e| e.tx.nonce);
+        let mut chain: Vec<&MempoolEntry> = Vec::new();
+        for entry in entries {
+            match chain.last() {
+                Some(last) if entry.tx.nonce != last.tx.nonce + 1 => break,
+                _ => chain.push(entry),
+            }
+        }
+        chain
+    }
+
+    /// Ancestor-package statistics for `getmempoolentry`.
+    pub fn package_info(&self, tx_hash: &Hash256) -> Option<PackageInfo> {
+        let entry = self.entries.get(tx_hash)?;
+        let chain = self.sender_chain(&entry.tx.from);
+        let position = chain.iter().position(|e| e.tx.hash() == *tx_hash)?;
+        let mut fees: u64 = 0;
+        let mut effective: u64 = 0;
+        let mut size: usize = 0;
+        let mut info = None;
+        let mut best_rate: f64 = 0.0;
+        for (i, e) in chain.iter().enumerate() {
+            fees += e.fee;
+            effective += self.effective_fee(e);
+            size += e.size;
+            let rate = effective as f64 / size as f64;
+            if i == position {
+                info = Some((i + 1, fees, size));
+            }
+            if i >= position && rate > best_rate {
+                best_rate = rate;
+            }
+        }
+        let (ancestor_count, ancestor_fees, ancestor_size) = info?;
+        Some(PackageInfo {
+            ancestor_count,
+            ancestor_fees,
+            ancestor_size,
+            effective_fee_rate: best_rate,
+        })
+    }
+
+    /// Drops every pooled transaction confirmed by `txs`, along with
+    /// any fee delta it was prioritised by — the accelerator did its
+    /// job.
+    pub fn remove_confirmed(&mut self, txs: &[Transaction]) {
+        for tx in txs {
+            let hash = tx.hash();
+            self.remove(&hash);
+            self.priority_deltas.remove(&hash);
+        }
+    }
+
+    /// Evicts the lowest fee-rate entry together with its pending
+    /// descendants, so a sender's nonce chain never ends up with a gap
+    /// the block selector cannot mine across.
+    fn evict_lowest_fee_rate(&mut self) {
+        let effective_rate =
+            |e: &MempoolEntry| self.effective_fee(e) as f64 / e.size as f64;
+        let victim = self
+            .entries
+            .iter()
+            .min_by(|a, b| {
+                effective_rate(a.1)
+                    .partial_cmp(&effective_rate(b.1))
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .map(|(h, e)| (*h, e.tx.from, e.tx.nonce));
+        if let Some((hash, from, nonce)) = victim {
+            let descendants: Vec<Hash256> = self
+                .entries
+                .values()
+                .filter(|e| e.tx.from == from && e.tx.nonce > nonce)
+                .map(|e| e.tx.hash())
+                .collect();
+            self.remove(&hash);
+            for hash in descendants {
+                self.remove(&hash);
+            }
+        }
+    }
+
+    /// Selects transactions for a block template by ancestor-package
+    /// fee rate, up to `max_bytes` of serialized transactions.
+    ///
+    /// Each sender's pending nonces form a chain; the selector
+    /// repeatedly takes the chain prefix with the best combined fee
+    /// rate across all senders (child pays for parent), emitting it in
+    /// nonce order so the block validates.
+    pub fn get_transactions_for_block(&self, max_bytes: usize) -> Vec<Transaction> {
+        let mut senders: Vec<[u8; 20]> = self.entries.values().map(|e| e.tx.from).collect();
+        senders.sort_unstable();
+        senders.dedup();
+        let mut chains: Vec<Vec<&MempoolEntry>> =
+            senders.iter().map(|from| self.sender_chain(from)).collect();
+
+        let mut out = Vec::new();
+        let mut used = 0usize;
+        loop {
+            let mut best: Option<(usize, usize, f64)> = None;
+            for (chain_idx, chain) in chains.iter().enumerate() {
+                let mut fees: u64 = 0;
+                let mut size: usize = 0;
+                for (prefix, entry) in chain.iter().enumerate() {
+                    fees += self.effective_fee(entry);
+                    size += entry.size;
+                    if used + size > max_bytes {
+                        break;
+                    }
+                    let rate = fees as f64 / size as f64;
+                    if best.is_none_or(|(_, _, best_rate)| rate > best_rate) {
+                        best = Some((chain_idx, prefix + 1, rate));
+                    }
+                }
+            }
+            let Some((chain_idx, take, _)) = best else {
+                break;
+            };
+            for entry in chains[chain_idx].drain(..take) {
+                used += entry.size;
+                out.push(entry.tx.clone());
+            }
+        }
+        out
+    }
+}
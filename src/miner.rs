@@ -0,0 +1,239 @@
+//! Mining: solo proof-of-work search plus upstream pool management.
+//!
+//! The miner works against an ordered list of pool upstreams and falls
+//! back to solo mining once every pool is exhausted. Failover triggers
+//! on connection loss (consecutive failures) or a sustained run of
+//! rejected shares; reconnects use capped exponential backoff, and
+//! every switch is recorded in [`MiningStats`] for the operator.
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::pow::{DoubleSha256, PowAlgorithm};
+use crate::types::BlockHeader;
+
+/// Recent shares considered when judging the rejection rate.
+pub const SHARE_WINDOW: usize = 20;
+
+/// Mining behavior knobs, including the pool failover policy.
+#[derive(Debug, Clone)]
+pub struct MiningConfig {
+    /// Ordered pool endpoints, most preferred first. Empty means solo
+    /// mining from the start.
+    pub pools: Vec<String>,
+    /// Consecutive connection failures before leaving an upstream.
+    pub max_failures: u32,
+    /// Rejected fraction of the last [`SHARE_WINDOW`] shares that
+    /// triggers failover (a pool rejecting our work is as useless as a
+    /// dead one).
+    pub max_reject_rate: f64,
+    /// First reconnect delay; doubles per failure up to `backoff_max`.
+    pub backoff_base: Duration,
+    pub backoff_max: Duration,
+}
+
+impl Default for MiningConfig {
+    fn default() -> Self {
+        MiningConfig {
+            pools: Vec::new(),
+            max_failures: 3,
+            max_reject_rate: 0.5,
+            backoff_base: Duration::from_secs(1),
+            backoff_max: Duration::from_secs(60),
+        }
+    }
+}
+
+/// Where work currently comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Upstream {
+    /// Index into `MiningConfig::pools`.
+    Pool(usize),
+    Solo,
+}
+
+/// One recorded upstream switch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailoverEvent {
+    pub timestamp: u64,
+    pub from: String,
+    pub to: String,
+    pub reason: String,
+}
+
+/// Counters and the failover history, surfaced over RPC.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MiningStats {
+    pub hashes: u64,
+    pub blocks_found: u64,
+    pub shares_accepted: u64,
+    pub shares_rejected: u64,
+    pub failovers: Vec<FailoverEvent>,
+}
+
+/// Upstream selection state machine. The network side (connecting to a
+/// pool, submitting shares) reports outcomes in; this decides where the
+/// next work request goes and how long to wait before it.
+pub struct Miner {
+    config: MiningConfig,
+    stats: MiningStats,
+    current: Upstream,
+    consecutive_failures: u32,
+    /// Accept/reject outcomes of the most recent shares, oldest first.
+    recent_shares: Vec<bool>,
+    /// Proof-of-work scheme solutions are ground against; must match
+    /// the chain the miner submits to.
+    pow: &'static dyn PowAlgorithm,
+}
+
+impl Miner {
+    pub fn new(config: MiningConfig) -> Self {
+        let current = if config.pools.is_empty() {
+            Upstream::Solo
+        } else {
+            Upstream::Pool(0)
+        };
+        Miner {
+            config,
+            stats: MiningStats::default(),
+            current,
+            consecutive_failures: 0,
+            recent_shares: Vec::new(),
+            pow: &DoubleSha256,
+        }
+    }
+
+    /// Selects the proof-of-work scheme to grind under.
+    pub fn set_pow_algorithm(&mut self, algorithm: &'static dyn PowAlgorithm) {
+        self.pow = algorithm;
+    }
+
+    pub fn current_upstream(&self) -> Upstream {
+        self.current
+    }
+
+    /// Human-readable name of an upstream for logs and stats.
+    pub fn upstream_name(&self, upstream: Upstream) -> String {
+        match upstream {
+            Upstream::Pool(i) => self.config.pools[i].clone(),
+            Upstream::Solo => "solo".to_string(),
+        }
+    }
+
+    pub fn stats(&self) -> &MiningStats {
+        &self.stats
+    }
+
+    /// A work request or share submission failed at the transport
+    /// level. After `max_failures` in a row the upstream is abandoned.
+    pub fn record_connection_loss(&mut self) {
+        self.consecutive_failures += 1;
+        if self.consecutive_failures >= self.config.max_failures {
+            self.fail_over("connection lost");
+        }
+    }
+
+    /// The upstream answered; resets the failure streak and backoff.
+    pub fn record_connected(&mut self) {
+        self.consecutive_failures = 0;
+    }
+
+    /// Records a share verdict from the current pool. A sustained run
+    /// of rejections across a full window abandons the pool.
+    pub fn record_share(&mut self, accepted: bool) {
+        if accepted {
+            self.stats.shares_accepted += 1;
+        } else {
+            self.stats.shares_rejected += 1;
+        }
+        self.recent_shares.push(accepted);
+        if self.recent_shares.len() > SHARE_WINDOW {
+            self.recent_shares.remove(0);
+        }
+        if self.recent_shares.len() == SHARE_WINDOW {
+            let rejected = self.recent_shares.iter().filter(|a| !**a).count();
+            if rejected as f64 / SHARE_WINDOW as f64 > self.config.max_reject_rate {
+                self.fail_over("sustained share rejections");
+            }
+        }
+    }
+
+    /// How long to wait before the next attempt at the current
+    /// upstream, growing exponentially with the failure streak.
+    pub fn reconnect_delay(&self) -> Duration {
+        if self.consecutive_failures == 0 {
+            return Duration::ZERO;
+        }
+        let exp = self.consecutive_failures.saturating_sub(1).min(16);
+        self.config
+            .backoff_base
+            .saturating_mul(1u32 << exp)
+            .min(self.config.backoff_max)
+    }
+
+    /// Moves to the next pool in order, ending at solo mining, and
+    /// records the event. Failing over from solo is a no-op: there is
+    /// nothing below it.
+    fn fail_over(&mut self, reason: &str) {
+        let next = match self.current {
+            Upstream::Pool(i) if i + 1 < self.config.pools.len() => Upstream::Pool(i + 1),
+            Upstream::Pool(_) => Upstream::Solo,
+            Upstream::Solo => return,
+        };
+        let event = FailoverEvent {
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+            from: self.upstream_name(self.current),
+            to: self.upstream_name(next),
+            reason: reason.to_string(),
+        };
+        log::warn!(
+            "mining upstream failover: {} -> {} ({})",
+            event.from,
+            event.to,
+            event.reason
+        );
+        self.stats.failovers.push(event);
+        self.current = next;
+        self.consecutive_failures = 0;
+        self.recent_shares.clear();
+    }
+
+    /// Retries the preferred pool (called periodically once failed over)
+    /// so a recovered primary wins back the hashrate.
+    pub fn retry_preferred(&mut self) {
+        if self.current != Upstream::Pool(0) && !self.config.pools.is_empty() {
+            let event = FailoverEvent {
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or(0),
+                from: self.upstream_name(self.current),
+                to: self.upstream_name(Upstream::Pool(0)),
+                reason: "retrying preferred pool".to_string(),
+            };
+            self.stats.failovers.push(event);
+            self.current = Upstream::Pool(0);
+            self.consecutive_failures = 0;
+            self.recent_shares.clear();
+        }
+    }
+
+    /// Grinds the header nonce for up to `max_iters` attempts, returning
+    /// true when the hash meets the target. Solo-mining work units call
+    /// this in a loop, checking for new tips between slices.
+    pub fn solve(&mut self, header: &mut BlockHeader, max_iters: u64) -> bool {
+        for _ in 0..max_iters {
+            self.stats.hashes += 1;
+            if self.pow.check(header) {
+                self.stats.blocks_found += 1;
+                return true;
+            }
+            header.nonce = header.nonce.wrapping_add(1);
+        }
+        false
+    }
+}
@@ -0,0 +1,172 @@
+//! `pali-node monitor`: a terminal dashboard for node operators,
+//! polling the local RPC endpoint.
+
+use std::io;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event, KeyCode};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen};
+use ratatui::backend::CrosstermBackend;
+use ratatui::layout::{Constraint, Direction, Layout};
+use ratatui::style::{Color, Style};
+use ratatui::text::Line;
+use ratatui::widgets::{Block, Borders, Paragraph, Row, Table};
+use ratatui::Terminal;
+use serde_json::Value;
+
+use crate::client::RpcClient;
+
+/// Snapshot of everything the dashboard renders.
+#[derive(Default)]
+struct Stats {
+    height: u64,
+    best_hash: String,
+    supply: u64,
+    mempool_txs: u64,
+    mempool_bytes: u64,
+    hashrate: f64,
+    peers: Vec<Value>,
+    logs: Vec<String>,
+    error: Option<String>,
+}
+
+async fn fetch_stats(client: &RpcClient) -> Stats {
+    let mut stats = Stats::default();
+    match client.call("getinfo", Value::Null).await {
+        Ok(info) => {
+            stats.height = info.get("height").and_then(Value::as_u64).unwrap_or(0);
+            stats.best_hash = info
+                .get("best_hash")
+                .and_then(Value::as_str)
+                .unwrap_or("")
+                .to_string();
+            stats.supply = info
+                .get("circulating_supply")
+                .and_then(Value::as_u64)
+                .unwrap_or(0);
+            stats.mempool_txs = info.get("mempool_txs").and_then(Value::as_u64).unwrap_or(0);
+            stats.mempool_bytes = info
+                .get("mempool_bytes")
+                .and_then(Value::as_u64)
+                .unwrap_or(0);
+        }
+        Err(e) => {
+            stats.error = Some(e);
+            return stats;
+        }
+    }
+    if let Ok(rate) = client.call("getnetworkhashrate", serde_json::json!([120])).await {
+        stats.hashrate = rate.as_f64().unwrap_or(0.0);
+    }
+    if let Ok(Value::Array(peers)) = client.call("getpeerinfo", Value::Null).await {
+        stats.peers = peers;
+    }
+    if let Ok(Value::Array(lines)) = client.call("getrecentlogs", serde_json::json!([12])).await {
+        stats.logs = lines
+            .into_iter()
+            .filter_map(|l| l.as_str().map(String::from))
+            .collect();
+    }
+    stats
+}
+
+/// Runs the dashboard until the user presses `q`.
+pub async fn run(rpc_url: &str) -> Result<(), String> {
+    let client = RpcClient::new(rpc_url);
+    enable_raw_mode().map_err(|e| e.to_string())?;
+    let mut stdout = io::stdout();
+    crossterm::execute!(stdout, EnterAlternateScreen).map_err(|e| e.to_string())?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend).map_err(|e| e.to_string())?;
+
+    let result = run_loop(&mut terminal, &client).await;
+
+    disable_raw_mode().ok();
+    crossterm::execute!(terminal.backend_mut(), LeaveAlternateScreen).ok();
+    terminal.show_cursor().ok();
+    result
+}
+
+async fn run_loop(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    client: &RpcClient,
+) -> Result<(), String> {
+    let mut stats = fetch_stats(client).await;
+    let mut last_poll = Instant::now();
+    loop {
+        terminal.draw(|f| draw(f, &stats)).map_err(|e| e.to_string())?;
+        if event::poll(Duration::from_millis(250)).map_err(|e| e.to_string())? {
+            if let Event::Key(key) = event::read().map_err(|e| e.to_string())? {
+                if matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                    return Ok(());
+                }
+            }
+        }
+        if last_poll.elapsed() >= Duration::from_secs(2) {
+            stats = fetch_stats(client).await;
+            last_poll = Instant::now();
+        }
+    }
+}
+
+fn draw(f: &mut ratatui::Frame, stats: &Stats) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(6),
+            Constraint::Min(6),
+            Constraint::Length(8),
+        ])
+        .split(f.area());
+
+    let mut overview = vec![
+        Line::from(format!("Height:    {}", stats.height)),
+        Line::from(format!("Best:      {}", stats.best_hash)),
+        Line::from(format!(
+            "Supply:    {} | Mempool: {} txs / {} bytes",
+            stats.supply, stats.mempool_txs, stats.mempool_bytes
+        )),
+        Line::from(format!("Hashrate:  {:.2e} H/s", stats.hashrate)),
+    ];
+    if let Some(err) = &stats.error {
+        overview.push(Line::styled(err.clone(), Style::default().fg(Color::Red)));
+    }
+    f.render_widget(
+        Paragraph::new(overview).block(Block::default().borders(Borders::ALL).title("Palicoin node")),
+        chunks[0],
+    );
+
+    let rows: Vec<Row> = stats
+        .peers
+        .iter()
+        .map(|p| {
+            let get = |k: &str| p.get(k).map(|v| v.to_string()).unwrap_or_default();
+            Row::new(vec![
+                get("addr"),
+                get("inbound"),
+                get("best_height"),
+                get("ping_ms"),
+                get("user_agent"),
+            ])
+        })
+        .collect();
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(22),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Min(10),
+        ],
+    )
+    .header(Row::new(vec!["addr", "inbound", "height", "ping ms", "agent"]))
+    .block(Block::default().borders(Borders::ALL).title("Peers"));
+    f.render_widget(table, chunks[1]);
+
+    let logs: Vec<Line> = stats.logs.iter().map(|l| Line::from(l.clone())).collect();
+    f.render_widget(
+        Paragraph::new(logs).block(Block::default().borders(Borders::ALL).title("Recent logs")),
+        chunks[2],
+    );
+}
@@ -0,0 +1,94 @@
+//! Prioritized inbound message queue.
+//!
+//! Messages from a peer are queued by class — blocks first, then other
+//! consensus/control traffic, then queries — so a flood of cheap
+//! queries cannot starve block relay. Depth is bounded across all
+//! classes; a peer that overflows its queue is disconnected rather
+//! than buffered without limit.
+
+use std::collections::VecDeque;
+
+use crate::network::NetworkMessage;
+
+/// Maximum queued messages per peer across all priority classes.
+pub const MAX_QUEUE_DEPTH: usize = 128;
+
+/// Processing class, highest priority first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Block announcements and sync batches.
+    Block,
+    /// Transactions, rejections and connection control.
+    Consensus,
+    /// Data requests we answer for the peer.
+    Query,
+}
+
+/// The class a message is processed under.
+pub fn classify(message: &NetworkMessage) -> Priority {
+    match message {
+        NetworkMessage::Block(_) | NetworkMessage::Blocks(_) => Priority::Block,
+        NetworkMessage::Transaction(_)
+        | NetworkMessage::Package(_)
+        | NetworkMessage::Inv { .. }
+        | NetworkMessage::NotFound { .. }
+        | NetworkMessage::StemTransaction(_)
+        | NetworkMessage::Reject { .. }
+        | NetworkMessage::ChainRules(_)
+        | NetworkMessage::Version { .. }
+        | NetworkMessage::VerAck
+        | NetworkMessage::Ping(_)
+        | NetworkMessage::Pong(_)
+        | NetworkMessage::Alert(_) => Priority::Consensus,
+        NetworkMessage::GetBlocks { .. }
+        | NetworkMessage::GetData { .. }
+        | NetworkMessage::GetBlocksLocator { .. }
+        | NetworkMessage::GetBlockRange { .. }
+        | NetworkMessage::GetPeers
+        | NetworkMessage::Peers(_)
+        | NetworkMessage::GetUtxoProof { .. }
+        | NetworkMessage::UtxoProof(_)
+        | NetworkMessage::GetCFHeaders { .. }
+        | NetworkMessage::CFHeaders { .. }
+        | NetworkMessage::GetCFilters { .. }
+        | NetworkMessage::CFilters(_) => Priority::Query,
+    }
+}
+
+/// One peer's pending messages, drained highest class first.
+pub struct MessageQueue {
+    queues: [VecDeque<NetworkMessage>; 3],
+    max_depth: usize,
+}
+
+impl MessageQueue {
+    pub fn new(max_depth: usize) -> Self {
+        MessageQueue {
+            queues: [VecDeque::new(), VecDeque::new(), VecDeque::new()],
+            max_depth,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.queues.iter().map(VecDeque::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queues.iter().all(VecDeque::is_empty)
+    }
+
+    /// Enqueues a message; errors when the peer's total backlog is at
+    /// the depth limit, which callers treat as grounds to disconnect.
+    pub fn push(&mut self, message: NetworkMessage) -> Result<(), String> {
+        if self.len() >= self.max_depth {
+            return Err("peer message queue overflow".to_string());
+        }
+        self.queues[classify(&message) as usize].push_back(message);
+        Ok(())
+    }
+
+    /// Removes the next message, blocks before consensus before queries.
+    pub fn pop(&mut self) -> Option<NetworkMessage> {
+        self.queues.iter_mut().find_map(VecDeque::pop_front)
+    }
+}
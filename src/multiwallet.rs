@@ -0,0 +1,104 @@
+//! Hosted wallets for service deployments.
+//!
+//! A service managing customer wallets should not need one daemon per
+//! customer. The manager holds any number of wallet files loaded by
+//! name over RPC (`loadwallet`/`unloadwallet`/`listwallets`), each
+//! with its own unlock session, spend policy and rolling spend cap —
+//! locking or unlocking one wallet never touches the others. Loading
+//! a wallet also registers its address on the node's watch list, so
+//! every hosted wallet gets its own activity stream through
+//! `getaddressactivity` and the `/events` feed without extra wiring.
+//!
+//! Keys follow the wallet module's vault rules: encrypted in memory,
+//! decrypted only for the duration of an unlock session. A freshly
+//! loaded wallet is locked immediately, so the daemon holds usable
+//! keys only for wallets an operator has explicitly unlocked.
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use crate::error::{WalletError, WalletErrorKind};
+use crate::types::Address;
+use crate::wallet::Wallet;
+
+/// One wallet loaded into the daemon.
+pub struct HostedWallet {
+    /// File the wallet came from, echoed by `listwallets`; unlocking
+    /// never re-reads it — the keys stay encrypted in memory.
+    pub path: PathBuf,
+    pub wallet: Wallet,
+}
+
+/// The named wallets currently loaded. Ordered so `listwallets` output
+/// is stable across calls.
+#[derive(Default)]
+pub struct WalletManager {
+    wallets: BTreeMap<String, HostedWallet>,
+}
+
+impl WalletManager {
+    pub fn new() -> Self {
+        WalletManager::default()
+    }
+
+    /// Loads `wallet` under `name`, locked. Names are unique; loading
+    /// the same file twice under different names is allowed (the
+    /// second load is a distinct session over the same keys).
+    pub fn load(&mut self, name: &str, path: PathBuf, mut wallet: Wallet) -> Result<(), WalletError> {
+        if name.is_empty() {
+            return Err(WalletError::new(
+                WalletErrorKind::InvalidRequest,
+                "wallet name must not be empty",
+            ));
+        }
+        if self.wallets.contains_key(name) {
+            return Err(WalletError::new(
+                WalletErrorKind::InvalidRequest,
+                format!("a wallet named '{}' is already loaded", name),
+            ));
+        }
+        wallet.lock();
+        self.wallets
+            .insert(name.to_string(), HostedWallet { path, wallet });
+        Ok(())
+    }
+
+    /// Drops the wallet and its session, returning it so the caller
+    /// can tear down any per-address wiring.
+    pub fn unload(&mut self, name: &str) -> Option<HostedWallet> {
+        self.wallets.remove(name)
+    }
+
+    /// The named wallet, for unlock, lock and signing.
+    pub fn get_mut(&mut self, name: &str) -> Result<&mut HostedWallet, WalletError> {
+        self.wallets.get_mut(name).ok_or_else(|| {
+            WalletError::new(
+                WalletErrorKind::InvalidRequest,
+                format!("no wallet named '{}' is loaded", name),
+            )
+        })
+    }
+
+    /// Every loaded wallet in name order.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&str, &mut HostedWallet)> {
+        self.wallets
+            .iter_mut()
+            .map(|(name, hosted)| (name.as_str(), hosted))
+    }
+
+    /// Whether any loaded wallet uses `address` — the watch-list entry
+    /// stays as long as one of them does.
+    pub fn watches(&self, address: &Address) -> bool {
+        self.wallets
+            .values()
+            .any(|hosted| hosted.wallet.address() == *address)
+    }
+
+    pub fn len(&self) -> usize {
+        self.wallets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.wallets.is_empty()
+    }
+}
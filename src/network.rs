@@ -0,0 +1,511 @@
+//! Wire protocol: message definitions, stream framing and the byte
+//! stream abstraction peer connections run over.
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, DuplexStream, ReadBuf};
+use tokio::net::TcpStream;
+
+use crate::alerts::SignedAlert;
+use crate::cfilters::CFilterMessage;
+use crate::hash;
+use crate::proofs::AddressProof;
+use crate::types::{Address, Block, Hash256, Transaction};
+
+/// Protocol version spoken by this build. Version 2 added the
+/// post-handshake ChainRules exchange; version 3 added UTXO proofs for
+/// light wallets; version 4 added signed developer alerts; version 5
+/// added locator-based block requests; version 6 wrapped every message
+/// in the magic/command/length/checksum frame envelope; version 7
+/// added compact block filters for light clients; version 8 added
+/// atomic transaction package relay; version 9 added inventory-based
+/// relay (Inv/GetData/NotFound), announcing hashes instead of pushing
+/// full objects.
+pub const PROTOCOL_VERSION: u32 = 9;
+
+/// First protocol version that understands [`NetworkMessage::Package`];
+/// packages are never pushed to older peers.
+pub const PACKAGE_RELAY_VERSION: u32 = 8;
+
+/// First protocol version spoken with inventory announcements. Older
+/// peers still get full blocks and transactions pushed at them.
+pub const INV_RELAY_VERSION: u32 = 9;
+
+/// Frame envelope marker. A connection whose next bytes are not these
+/// is desynchronized or speaking something else entirely, and the only
+/// safe response is to drop it; scanning ahead for the next magic would
+/// let an attacker smuggle bytes past the framing.
+pub const NETWORK_MAGIC: [u8; 4] = [0xF4, 0xA1, 0x1C, 0x01];
+
+/// Bytes in the frame envelope before the payload: magic, command
+/// (wire id) and payload length as big-endian `u32`s, then the payload
+/// checksum.
+pub const FRAME_HEADER_SIZE: usize = 16;
+
+/// Hard cap on a single serialized message.
+pub const MAX_MESSAGE_SIZE: u32 = 4 * 1024 * 1024;
+
+/// Maximum entries in a single inventory announcement.
+pub const MAX_INV_PER_MESSAGE: usize = 500;
+
+/// Maximum hashes in a block locator. Exponential spacing covers any
+/// plausible chain in far fewer.
+pub const MAX_LOCATOR_HASHES: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum NetworkMessage {
+    /// Connection opener: protocol version, chain id, best height and
+    /// user agent.
+    Version {
+        version: u32,
+        chain_id: u8,
+        height: u64,
+        user_agent: String,
+    },
+    /// Acknowledges a Version; the connection is live afterwards.
+    VerAck,
+    /// Keepalive probe carrying a random nonce.
+    Ping(u64),
+    /// Echoes the nonce of the Ping it answers.
+    Pong(u64),
+    /// Announces a new block.
+    Block(Block),
+    /// Announces a new transaction.
+    Transaction(Transaction),
+    /// Requests blocks after the given hash.
+    GetBlocks { from: Hash256, limit: u32 },
+    /// Batch of blocks answering GetBlocks.
+    Blocks(Vec<Block>),
+    /// Requests known peer addresses.
+    GetPeers,
+    /// Peer addresses answering GetPeers.
+    Peers(Vec<String>),
+    /// Requests a contiguous height range of blocks (sync work window).
+    GetBlockRange { start: u64, count: u32 },
+    /// A transaction in its Dandelion++ stem phase: forward along the
+    /// stem (or fluff), never treat as a public announcement.
+    StemTransaction(Transaction),
+    /// Tells a peer why we refused its transaction or block.
+    Reject {
+        /// "tx" or "block".
+        what: String,
+        hash: Hash256,
+        /// Stable rejection code (see the rejection module).
+        code: String,
+    },
+    /// Consensus rule set version, sent right after VerAck (protocol
+    /// version 2 and up). A mismatch ends the connection.
+    ChainRules(u32),
+    /// Asks a full node for an address's UTXOs with merkle proofs.
+    GetUtxoProof { address: Address },
+    /// Provable balance answer to GetUtxoProof (see the proofs module).
+    UtxoProof(AddressProof),
+    /// Developer-signed network notice, relayed once per id while it
+    /// has not expired (see the alerts module).
+    Alert(SignedAlert),
+    /// Requests blocks after the fork point located by `locator`:
+    /// exponentially spaced hashes from the requester's tip backwards,
+    /// so the server finds where the chains diverge even when the
+    /// requester's best hash is not on the server's main chain.
+    GetBlocksLocator { locator: Vec<Hash256>, limit: u32 },
+    /// Asks for the compact-filter headers chain covering
+    /// `start_height` through the block at `stop_hash` (see the
+    /// cfilters module).
+    GetCFHeaders { start_height: u64, stop_hash: Hash256 },
+    /// Answers GetCFHeaders: the header preceding the range, then one
+    /// filter hash per block so the client can extend the chain
+    /// itself.
+    CFHeaders {
+        start_height: u64,
+        prev_header: Hash256,
+        filter_hashes: Vec<Hash256>,
+    },
+    /// Asks for the compact filters covering `start_height` through
+    /// the block at `stop_hash`.
+    GetCFilters { start_height: u64, stop_hash: Hash256 },
+    /// Answers GetCFilters, one entry per block in ascending height
+    /// order.
+    CFilters(Vec<CFilterMessage>),
+    /// A dependent transaction package (one sender, contiguous nonces)
+    /// accepted or refused atomically on its combined fee rate, so a
+    /// zero-fee parent can ride in on a high-fee child.
+    Package(Vec<Transaction>),
+    /// Announces blocks and transactions by hash (protocol version 9
+    /// and up); the receiver asks for what it lacks with GetData. At
+    /// most [`MAX_INV_PER_MESSAGE`] hashes across both lists.
+    Inv {
+        blocks: Vec<Hash256>,
+        txs: Vec<Hash256>,
+    },
+    /// Requests the full objects behind previously announced hashes,
+    /// under the same [`MAX_INV_PER_MESSAGE`] cap.
+    GetData {
+        blocks: Vec<Hash256>,
+        txs: Vec<Hash256>,
+    },
+    /// The part of a GetData the responder could not supply — already
+    /// mined transactions, pruned blocks, or hashes it never had.
+    NotFound {
+        blocks: Vec<Hash256>,
+        txs: Vec<Hash256>,
+    },
+}
+
+/// One row of the protocol reference: wire id, message name, payload
+/// summary and the protocol version that introduced it.
+pub const PROTOCOL_TABLE: &[(u32, &str, &str, u32)] = &[
+    (0, "Version", "version: u32, chain_id: u8, height: u64, user_agent: String", 1),
+    (1, "VerAck", "(empty)", 1),
+    (2, "Ping", "nonce: u64", 1),
+    (3, "Pong", "nonce: u64", 1),
+    (4, "Block", "Block", 1),
+    (5, "Transaction", "Transaction", 1),
+    (6, "GetBlocks", "from: Hash256, limit: u32", 1),
+    (7, "Blocks", "Vec<Block>", 1),
+    (8, "GetPeers", "(empty)", 1),
+    (9, "Peers", "Vec<String>", 1),
+    (10, "GetBlockRange", "start: u64, count: u32", 1),
+    (11, "StemTransaction", "Transaction", 1),
+    (12, "Reject", "what: String, hash: Hash256, code: String", 1),
+    (13, "ChainRules", "version: u32", 2),
+    (14, "GetUtxoProof", "address: Address", 3),
+    (15, "UtxoProof", "AddressProof", 3),
+    (16, "Alert", "SignedAlert", 4),
+    (17, "GetBlocksLocator", "locator: Vec<Hash256>, limit: u32", 5),
+    (18, "GetCFHeaders", "start_height: u64, stop_hash: Hash256", 7),
+    (19, "CFHeaders", "start_height: u64, prev_header: Hash256, filter_hashes: Vec<Hash256>", 7),
+    (20, "GetCFilters", "start_height: u64, stop_hash: Hash256", 7),
+    (21, "CFilters", "Vec<CFilterMessage>", 7),
+    (22, "Package", "Vec<Transaction>", 8),
+    (23, "Inv", "blocks: Vec<Hash256>, txs: Vec<Hash256>", 9),
+    (24, "GetData", "blocks: Vec<Hash256>, txs: Vec<Hash256>", 9),
+    (25, "NotFound", "blocks: Vec<Hash256>, txs: Vec<Hash256>", 9),
+];
+
+impl NetworkMessage {
+    /// Wire id of this message. Bincode encodes the variant index as a
+    /// little-endian u32 prefix; these values state that contract
+    /// explicitly, so a reorder of the enum (which would silently
+    /// change the wire format) fails the serialization suite instead.
+    pub fn wire_id(&self) -> u32 {
+        match self {
+            NetworkMessage::Version { .. } => 0,
+            NetworkMessage::VerAck => 1,
+            NetworkMessage::Ping(_) => 2,
+            NetworkMessage::Pong(_) => 3,
+            NetworkMessage::Block(_) => 4,
+            NetworkMessage::Transaction(_) => 5,
+            NetworkMessage::GetBlocks { .. } => 6,
+            NetworkMessage::Blocks(_) => 7,
+            NetworkMessage::GetPeers => 8,
+            NetworkMessage::Peers(_) => 9,
+            NetworkMessage::GetBlockRange { .. } => 10,
+            NetworkMessage::StemTransaction(_) => 11,
+            NetworkMessage::Reject { .. } => 12,
+            NetworkMessage::ChainRules(_) => 13,
+            NetworkMessage::GetUtxoProof { .. } => 14,
+            NetworkMessage::UtxoProof(_) => 15,
+            NetworkMessage::Alert(_) => 16,
+            NetworkMessage::GetBlocksLocator { .. } => 17,
+            NetworkMessage::GetCFHeaders { .. } => 18,
+            NetworkMessage::CFHeaders { .. } => 19,
+            NetworkMessage::GetCFilters { .. } => 20,
+            NetworkMessage::CFilters(_) => 21,
+            NetworkMessage::Package(_) => 22,
+            NetworkMessage::Inv { .. } => 23,
+            NetworkMessage::GetData { .. } => 24,
+            NetworkMessage::NotFound { .. } => 25,
+        }
+    }
+
+    /// Message name as it appears in the protocol reference.
+    pub fn name(&self) -> &'static str {
+        PROTOCOL_TABLE[self.wire_id() as usize].1
+    }
+}
+
+/// Renders the protocol reference committed at `docs/protocol.md`.
+/// Regenerate with `PALI_BLESS=1 cargo test --test protocol`; the test
+/// fails whenever the table and the document diverge.
+pub fn protocol_reference() -> String {
+    let mut out = String::new();
+    out.push_str("# Palicoin P2P protocol reference
+
+");
+    out.push_str("Generated from `PROTOCOL_TABLE` in `src/network.rs`; do not edit by hand.
+
+");
+    out.push_str(&format!("Protocol version: {}
+
+", PROTOCOL_VERSION));
+    out.push_str("## Framing
+
+");
+    out.push_str(
+        "Every message is a 16-byte envelope followed by the bincode-encoded payload:          the network magic, the wire id and the payload length as big-endian `u32`s,          then the first four bytes of `double_sha256(payload)` as a checksum. The          payload itself starts with the wire id again as a little-endian `u32`          (bincode's variant tag). Magic, wire id, length and checksum are all          verified before deserialization, and any mismatch ends the connection.
+
+",
+    );
+    out.push_str(&format!(
+        "- Network magic: `{}`
+- Maximum message size: {} bytes
+- Maximum inventory entries per message: {}
+
+",
+        hex::encode(NETWORK_MAGIC),
+        MAX_MESSAGE_SIZE,
+        MAX_INV_PER_MESSAGE
+    ));
+    out.push_str("## Messages
+
+");
+    out.push_str("| Wire id | Message | Payload | Since version |
+");
+    out.push_str("|---|---|---|---|
+");
+    for (id, name, payload, since) in PROTOCOL_TABLE {
+        out.push_str(&format!("| {} | {} | `{}` | {} |
+", id, name, payload, since));
+    }
+    out.push_str(
+        "
+New messages append at the end of the enum only; inserting or reordering          variants changes every later wire id and splits the network.
+",
+    );
+    out
+}
+
+/// Byte stream a peer connection runs over. The node's connection
+/// handling is generic over this, so the handshake, relay and framing
+/// logic runs identically over a real socket and over an in-memory
+/// pipe in tests — optionally wrapped in a [`FaultyStream`] to inject
+/// the failures real networks produce.
+pub trait Transport: Send + 'static {
+    type Reader: AsyncRead + Unpin + Send + 'static;
+    type Writer: AsyncWrite + Unpin + Send + 'static;
+
+    /// Splits into independently owned halves: the read loop keeps
+    /// one, the writer task the other.
+    fn into_split(self) -> (Self::Reader, Self::Writer);
+}
+
+impl Transport for TcpStream {
+    type Reader = tokio::net::tcp::OwnedReadHalf;
+    type Writer = tokio::net::tcp::OwnedWriteHalf;
+
+    fn into_split(self) -> (Self::Reader, Self::Writer) {
+        TcpStream::into_split(self)
+    }
+}
+
+impl Transport for DuplexStream {
+    type Reader = tokio::io::ReadHalf<DuplexStream>;
+    type Writer = tokio::io::WriteHalf<DuplexStream>;
+
+    fn into_split(self) -> (Self::Reader, Self::Writer) {
+        tokio::io::split(self)
+    }
+}
+
+/// A connected in-memory pipe pair, one end per "peer". Buffered
+/// generously enough that a whole maximum-size message never deadlocks
+/// a single-threaded test.
+pub fn memory_pair() -> (DuplexStream, DuplexStream) {
+    tokio::io::duplex(MAX_MESSAGE_SIZE as usize + FRAME_HEADER_SIZE)
+}
+
+/// Failure modes a [`FaultyStream`] injects.
+#[derive(Debug, Clone, Default)]
+pub struct Faults {
+    /// Largest chunk a single write accepts, forcing the partial
+    /// writes a congested socket produces.
+    pub max_write: Option<usize>,
+    /// Connection dies after this many bytes have crossed it in either
+    /// direction: reads hit EOF, writes a broken pipe.
+    pub drop_after_bytes: Option<u64>,
+    /// Imposed latency before each read completes.
+    pub read_delay: Option<Duration>,
+}
+
+/// Wraps any stream and injects [`Faults`] into it, so connection
+/// handling can be exercised against drops, partial writes and delays
+/// without a real, misbehaving network.
+pub struct FaultyStream<T> {
+    inner: T,
+    faults: Faults,
+    transferred: u64,
+    delay: Option<Pin<Box<tokio::time::Sleep>>>,
+}
+
+impl<T> FaultyStream<T> {
+    pub fn new(inner: T, faults: Faults) -> Self {
+        FaultyStream {
+            inner,
+            faults,
+            transferred: 0,
+            delay: None,
+        }
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for FaultyStream<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        if let Some(limit) = this.faults.drop_after_bytes {
+            if this.transferred >= limit {
+                // EOF: the "connection" is gone.
+                return Poll::Ready(Ok(()));
+            }
+        }
+        if let Some(duration) = this.faults.read_delay {
+            let delay = this
+                .delay
+                .get_or_insert_with(|| Box::pin(tokio::time::sleep(duration)));
+            match delay.as_mut().poll(cx) {
+                Poll::Pending => return Poll::Pending,
+                Poll::Ready(()) => this.delay = None,
+            }
+        }
+        let before = buf.filled().len();
+        let result = Pin::new(&mut this.inner).poll_read(cx, buf);
+        if let Poll::Ready(Ok(())) = &result {
+            this.transferred += (buf.filled().len() - before) as u64;
+        }
+        result
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for FaultyStream<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if let Some(limit) = this.faults.drop_after_bytes {
+            if this.transferred >= limit {
+                return Poll::Ready(Err(io::ErrorKind::BrokenPipe.into()));
+            }
+        }
+        let chunk = match this.faults.max_write {
+            Some(max) => &buf[..buf.len().min(max.max(1))],
+            None => buf,
+        };
+        let result = Pin::new(&mut this.inner).poll_write(cx, chunk);
+        if let Poll::Ready(Ok(written)) = &result {
+            this.transferred += *written as u64;
+        }
+        result
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().inner).poll_shutdown(cx)
+    }
+}
+
+impl<T> Transport for FaultyStream<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    type Reader = tokio::io::ReadHalf<FaultyStream<T>>;
+    type Writer = tokio::io::WriteHalf<FaultyStream<T>>;
+
+    fn into_split(self) -> (Self::Reader, Self::Writer) {
+        tokio::io::split(self)
+    }
+}
+
+/// Payload checksum carried in the frame header: the first four bytes
+/// of `double_sha256(payload)`, Bitcoin style. Verified before the
+/// payload ever reaches the deserializer, so a flipped bit surfaces as
+/// a clean framing error instead of whatever bincode makes of it.
+pub fn frame_checksum(payload: &[u8]) -> [u8; 4] {
+    let digest = hash::double_sha256(payload);
+    [digest[0], digest[1], digest[2], digest[3]]
+}
+
+/// The [`FRAME_HEADER_SIZE`] bytes framing `payload` on the wire.
+pub fn frame_header(wire_id: u32, payload: &[u8]) -> [u8; FRAME_HEADER_SIZE] {
+    let mut header = [0u8; FRAME_HEADER_SIZE];
+    header[..4].copy_from_slice(&NETWORK_MAGIC);
+    header[4..8].copy_from_slice(&wire_id.to_be_bytes());
+    header[8..12].copy_from_slice(&(payload.len() as u32).to_be_bytes());
+    header[12..].copy_from_slice(&frame_checksum(payload));
+    header
+}
+
+/// Writes one framed bincode message to `stream`.
+pub async fn write_message<W: AsyncWriteExt + Unpin>(
+    stream: &mut W,
+    message: &NetworkMessage,
+) -> Result<(), String> {
+    let payload = bincode::serialize(message).map_err(|e| format!("serialize failed: {}", e))?;
+    if payload.len() as u32 > MAX_MESSAGE_SIZE {
+        return Err("message exceeds MAX_MESSAGE_SIZE".to_string());
+    }
+    stream
+        .write_all(&frame_header(message.wire_id(), &payload))
+        .await
+        .map_err(|e| format!("write failed: {}", e))?;
+    stream
+        .write_all(&payload)
+        .await
+        .map_err(|e| format!("write failed: {}", e))
+}
+
+/// Reads one framed bincode message from `stream`, checking the magic,
+/// the command id, the length cap and the payload checksum — in that
+/// order, and all before deserialization. Every failure is fatal to the
+/// connection: framing carries no resynchronization point, by design.
+pub async fn read_message<R: AsyncReadExt + Unpin>(stream: &mut R) -> Result<NetworkMessage, String> {
+    let mut header = [0u8; FRAME_HEADER_SIZE];
+    stream
+        .read_exact(&mut header)
+        .await
+        .map_err(|e| format!("read failed: {}", e))?;
+    if header[..4] != NETWORK_MAGIC {
+        return Err("bad network magic".to_string());
+    }
+    let wire_id = u32::from_be_bytes(header[4..8].try_into().expect("slice is 4 bytes"));
+    if wire_id as usize >= PROTOCOL_TABLE.len() {
+        return Err(format!("unknown wire id {}", wire_id));
+    }
+    let len = u32::from_be_bytes(header[8..12].try_into().expect("slice is 4 bytes"));
+    if len > MAX_MESSAGE_SIZE {
+        return Err("incoming message exceeds MAX_MESSAGE_SIZE".to_string());
+    }
+    let mut payload = vec![0u8; len as usize];
+    stream
+        .read_exact(&mut payload)
+        .await
+        .map_err(|e| format!("read failed: {}", e))?;
+    if frame_checksum(&payload) != header[12..] {
+        return Err("frame checksum mismatch".to_string());
+    }
+    let message: NetworkMessage =
+        bincode::deserialize(&payload).map_err(|e| format!("malformed message: {}", e))?;
+    // A command id that disagrees with the payload is either corruption
+    // the checksum cannot catch (it was computed over the wrong bytes)
+    // or a peer playing games with the header.
+    if message.wire_id() != wire_id {
+        return Err(format!(
+            "frame command {} does not match payload {}",
+            wire_id,
+            message.wire_id()
+        ));
+    }
+    Ok(message)
+}
@@ -0,0 +1,1732 @@
+//! Peer-to-peer node: connection management, message handling and relay.
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rand::Rng;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+
+use crate::addrman::AddrManager;
+use crate::alerts::AlertStore;
+use crate::backup::BackupStatus;
+use crate::blockchain::{Blockchain, ScrubStatus};
+use crate::cfilters::{CFilterMessage, MAX_CFILTERS_PER_MESSAGE};
+use crate::coinlock::CoinLocks;
+use crate::dandelion::{Dandelion, Route};
+use crate::deposits::DepositTracker;
+use crate::error::PaliError;
+use crate::forks::ForkMonitor;
+use crate::mempool::Mempool;
+use crate::msgqueue::{MessageQueue, MAX_QUEUE_DEPTH};
+use crate::multiwallet::WalletManager;
+use crate::notify::{Notifier, TxEvent};
+use crate::peerstats::PeerStatsBook;
+use crate::snapshot::TipView;
+use crate::pool::ShareLedger;
+use crate::proofs;
+use crate::consensus::{ChainParams, CHAIN_RULES_VERSION};
+use crate::network::{self, NetworkMessage, PROTOCOL_VERSION};
+use crate::rejection::RejectionReason;
+use crate::reputation::{self, ReputationBook};
+use crate::sync::SyncManager;
+use crate::telemetry::{BlockRecord, BlockTelemetry};
+use crate::toggles::Toggles;
+use crate::types::{Block, Transaction};
+use crate::updates::UpdateStatus;
+use crate::sse::EventFeed;
+use crate::watch::{self, WatchList};
+
+/// Maximum simultaneously connected inbound peers.
+pub const MAX_INBOUND_PEERS: usize = 32;
+
+/// Maximum outbound connections we maintain.
+pub const MAX_OUTBOUND_PEERS: usize = 8;
+
+/// Interval between keepalive pings.
+pub const PING_INTERVAL: Duration = Duration::from_secs(30);
+/// A peer must complete the Version exchange within this long.
+pub const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+/// How often the maintenance loop re-checks whether compaction is due.
+pub const MAINTENANCE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+/// Mean seconds between wallet-rebroadcast checks; each wait is
+/// jittered between half and one-and-a-half times this.
+pub const REBROADCAST_CHECK_SECS: u64 = 120;
+/// Blocks moved to the cold tier per maintenance pass, bounding how
+/// long each pass holds the chain lock.
+pub const COLD_MIGRATION_BATCH: usize = 256;
+
+/// Blocks the integrity scrubber re-reads per maintenance tick — small
+/// enough that a pass never competes with validation for the chain
+/// lock.
+pub const SCRUB_BATCH_BLOCKS: usize = 64;
+
+/// Weight of the newest sample in the latency EWMA.
+const PING_EWMA_ALPHA: f64 = 0.25;
+
+/// Inbound queue plus the notifier waking its processing task.
+type SharedQueue = Arc<(Mutex<MessageQueue>, tokio::sync::Notify)>;
+
+/// Live state tracked for each connected peer.
+#[derive(Debug)]
+pub struct PeerInfo {
+    pub addr: SocketAddr,
+    pub inbound: bool,
+    pub version: u32,
+    pub user_agent: String,
+    pub best_height: u64,
+    pub connected_at: u64,
+    pub last_seen: u64,
+    /// Smoothed round-trip time in milliseconds (EWMA over pong samples).
+    pub ping_time: Option<f64>,
+    /// Nonce of the ping awaiting a pong, with the instant it was sent.
+    pub pending_ping: Option<(u64, std::time::Instant)>,
+    /// Channel to the peer's writer task.
+    pub sender: mpsc::UnboundedSender<NetworkMessage>,
+}
+
+/// Shared node handles cloned into every connection task.
+#[derive(Clone)]
+pub struct Node {
+    pub chain: Arc<Mutex<Blockchain>>,
+    pub mempool: Arc<Mutex<Mempool>>,
+    pub peers: Arc<Mutex<HashMap<SocketAddr, PeerInfo>>>,
+    /// Bucketed table of gossiped peer addresses and anchor peers.
+    pub addrman: Arc<Mutex<AddrManager>>,
+    /// External walletnotify/blocknotify hooks; disabled by default.
+    pub notifier: Arc<Notifier>,
+    pub sync: Arc<Mutex<SyncManager>>,
+    pub dandelion: Arc<Mutex<Dandelion>>,
+    /// Count of validation rejections per reject code.
+    pub rejections: Arc<Mutex<HashMap<String, u64>>>,
+    /// Address watch subscriptions registered over RPC.
+    pub watch: Arc<Mutex<WatchList>>,
+    /// In-process feed behind the `/events` SSE endpoint (see the sse
+    /// module).
+    pub events: Arc<EventFeed>,
+    /// Hosted customer wallets loaded over RPC (see the multiwallet
+    /// module).
+    pub wallets: Arc<Mutex<WalletManager>>,
+    /// Fork tips and stale blocks observed since startup.
+    pub forks: Arc<Mutex<ForkMonitor>>,
+    /// Acceptance timing for recently connected blocks.
+    pub telemetry: Arc<Mutex<BlockTelemetry>>,
+    /// Broadcasts the new height whenever the tip advances; mining
+    /// work distribution listens so templates refresh immediately.
+    pub tip_changes: tokio::sync::broadcast::Sender<u64>,
+    /// Mirror of every broadcast for an alternative transport (the
+    /// libp2p service, when enabled); `None` when only the native
+    /// transport runs.
+    pub external_relay: Option<mpsc::UnboundedSender<NetworkMessage>>,
+    /// Chain parameters this node runs under; the alert keys and
+    /// threshold in here gate which signed alerts we accept.
+    pub params: ChainParams,
+    /// Developer alerts accepted so far (see the alerts module).
+    pub alerts: Arc<Mutex<AlertStore>>,
+    /// Exchange deposit registrations and their event log (see the
+    /// deposits module).
+    pub deposits: Arc<Mutex<DepositTracker>>,
+    /// Manually locked outpoints the RPC layer refuses to let a spend
+    /// dip into (see the coinlock module).
+    pub coin_locks: Arc<Mutex<CoinLocks>>,
+    /// Running results of the background integrity scrubber.
+    pub scrub: Arc<Mutex<ScrubStatus>>,
+    /// Running results of the scheduled backup task (see the backup
+    /// module); stays at its default when backups are not configured.
+    pub backups: Arc<Mutex<BackupStatus>>,
+    /// Operator-settable runtime switches (see the toggles module).
+    pub toggles: Arc<Mutex<Toggles>>,
+    /// Why safe mode is on, for RPC warnings; `None` outside safe mode.
+    pub safe_mode_reason: Arc<Mutex<Option<String>>>,
+    /// Mining shares accepted over the `/work` endpoint (see the pool
+    /// module); in-memory unless the daemon attaches a ledger file.
+    pub pool: Arc<Mutex<ShareLedger>>,
+    /// Latest verified release-channel check (see the updates module);
+    /// `None` until an opt-in check succeeds.
+    pub update: Arc<Mutex<Option<UpdateStatus>>>,
+    /// Lifetime per-peer relay statistics, persisted across restarts
+    /// (see the peerstats module).
+    pub peerstats: Arc<Mutex<PeerStatsBook>>,
+    /// Per-IP misbehavior scores with decay and ban review (see the
+    /// reputation module).
+    pub reputation: Arc<Mutex<ReputationBook>>,
+    /// External hash the next mined block's coinbase commits to (see
+    /// `getwork::coinbase_data`); cleared once a block carrying it
+    /// connects.
+    pub next_commitment: Arc<Mutex<Option<crate::types::Hash256>>>,
+    /// Lock-free snapshot of the tip for read-mostly paths (see the
+    /// snapshot module); swapped by `notify_tip_change`.
+    pub tip_view: Arc<TipView>,
+    pub chain_id: u8,
+    pub user_agent: String,
+}
+
+impl Node {
+    pub fn new(chain: Arc<Mutex<Blockchain>>, mempool: Arc<Mutex<Mempool>>, chain_id: u8) -> Self {
+        let tip_view = Arc::new(TipView::new(&chain.lock().expect("chain lock poisoned")));
+        Node {
+            chain,
+            mempool,
+            peers: Arc::new(Mutex::new(HashMap::new())),
+            addrman: Arc::new(Mutex::new(AddrManager::new())),
+            notifier: Arc::new(Notifier::disabled()),
+            sync: Arc::new(Mutex::new(SyncManager::new())),
+            dandelion: Arc::new(Mutex::new(Dandelion::new())),
+            rejections: Arc::new(Mutex::new(HashMap::new())),
+            watch: Arc::new(Mutex::new(WatchList::new())),
+            events: Arc::new(EventFeed::new()),
+            wallets: Arc::new(Mutex::new(WalletManager::new())),
+            forks: Arc::new(Mutex::new(ForkMonitor::new())),
+            telemetry: Arc::new(Mutex::new(BlockTelemetry::new())),
+            tip_changes: tokio::sync::broadcast::channel(16).0,
+            external_relay: None,
+            params: ChainParams::default(),
+            alerts: Arc::new(Mutex::new(AlertStore::new())),
+            deposits: Arc::new(Mutex::new(DepositTracker::new())),
+            coin_locks: Arc::new(Mutex::new(CoinLocks::new())),
+            scrub: Arc::new(Mutex::new(ScrubStatus::default())),
+            backups: Arc::new(Mutex::new(BackupStatus::default())),
+            toggles: Arc::new(Mutex::new(Toggles::default())),
+            safe_mode_reason: Arc::new(Mutex::new(None)),
+            pool: Arc::new(Mutex::new(ShareLedger::new())),
+            update: Arc::new(Mutex::new(None)),
+            peerstats: Arc::new(Mutex::new(PeerStatsBook::new())),
+            reputation: Arc::new(Mutex::new(ReputationBook::new())),
+            next_commitment: Arc::new(Mutex::new(None)),
+            tip_view,
+            chain_id,
+            user_agent: format!("/pali-coin:{}/", env!("CARGO_PKG_VERSION")),
+        }
+    }
+
+    /// Accepts inbound connections forever.
+    pub async fn listen(self: Arc<Self>, bind: SocketAddr) -> Result<(), String> {
+        let listener = TcpListener::bind(bind)
+            .await
+            .map_err(|e| format!("failed to bind P2P listener: {}", e))?;
+        log::info!("P2P listening on {}", bind);
+        loop {
+            let (stream, addr) = match listener.accept().await {
+                Ok(pair) => pair,
+                Err(e) => {
+                    log::warn!("accept failed: {}", e);
+                    continue;
+                }
+            };
+            if self
+                .reputation
+                .lock()
+                .expect("reputation lock poisoned")
+                .is_banned(addr.ip(), unix_now())
+            {
+                log::debug!("refusing banned peer {}", addr);
+                drop(stream);
+                continue;
+            }
+            if !self.admit_inbound() {
+                log::debug!("inbound slots full, rejecting {}", addr);
+                drop(stream);
+                continue;
+            }
+            let node = self.clone();
+            tokio::spawn(async move {
+                if let Err(e) = node.handle_connection(stream, addr, true).await {
+                    log::debug!("peer {} disconnected: {}", addr, e);
+                }
+            });
+        }
+    }
+
+    /// Dials an outbound peer and runs its connection task.
+    pub async fn connect(self: Arc<Self>, addr: SocketAddr) -> Result<(), String> {
+        let stream = TcpStream::connect(addr)
+            .await
+            .map_err(|e| format!("failed to connect to {}: {}", addr, e))?;
+        // An outbound peer we dialed and completed a TCP connection to
+        // becomes an anchor candidate for the next restart.
+        self.addrman
+            .lock()
+            .expect("addrman lock poisoned")
+            .record_good(addr);
+        self.handle_connection(stream, addr, false).await
+    }
+
+    /// When inbound slots are full, evicts the worst-latency inbound
+    /// peer to make room; refuses the connection if none is evictable.
+    fn admit_inbound(&self) -> bool {
+        if !self.toggles.lock().expect("toggles lock poisoned").inbound {
+            return false;
+        }
+        let mut peers = self.peers.lock().expect("peers lock poisoned");
+        let inbound: Vec<_> = peers
+            .values()
+            .filter(|p| p.inbound)
+            .map(|p| (p.addr, p.ping_time))
+            .collect();
+        if inbound.len() < MAX_INBOUND_PEERS {
+            return true;
+        }
+        // Prefer evicting the peer with the worst measured latency;
+        // peers with no measurement yet are spared.
+        let victim = inbound
+            .iter()
+            .filter_map(|(addr, ping)| ping.map(|p| (*addr, p)))
+            .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
+            .map(|(addr, _)| addr);
+        match victim {
+            Some(addr) => {
+                log::info!("evicting worst-latency inbound peer {}", addr);
+                if let Some(peer) = peers.remove(&addr) {
+                    let now = unix_now();
+                    self.peerstats
+                        .lock()
+                        .expect("peerstats lock poisoned")
+                        .record_disconnect(addr.ip(), now.saturating_sub(peer.connected_at), now);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Performs the handshake then runs the read loop for one peer.
+    /// Generic over [`network::Transport`] so tests can drive a node
+    /// over an in-memory pipe instead of a socket.
+    pub async fn handle_connection<T: network::Transport>(
+        &self,
+        stream: T,
+        addr: SocketAddr,
+        inbound: bool,
+    ) -> Result<(), String> {
+        let (mut reader, writer) = stream.into_split();
+        let (tx, rx) = mpsc::unbounded_channel();
+        tokio::spawn(writer_task(writer, rx));
+
+        let height = self.tip_view.current().height;
+        tx.send(NetworkMessage::Version {
+            version: PROTOCOL_VERSION,
+            chain_id: self.chain_id,
+            height,
+            user_agent: self.user_agent.clone(),
+        })
+        .map_err(|_| "writer task gone".to_string())?;
+
+        let first = tokio::time::timeout(HANDSHAKE_TIMEOUT, network::read_message(&mut reader))
+            .await
+            .map_err(|_| "handshake timed out".to_string())??;
+        let (version, peer_height, user_agent) = match first {
+            NetworkMessage::Version {
+                version,
+                chain_id,
+                height,
+                user_agent,
+            } => {
+                if chain_id != self.chain_id {
+                    return Err(format!("peer on wrong chain {}", chain_id));
+                }
+                (version, height, user_agent)
+            }
+            other => return Err(format!("expected Version, got {:?}", other)),
+        };
+        tx.send(NetworkMessage::VerAck)
+            .map_err(|_| "writer task gone".to_string())?;
+
+        let now = unix_now();
+        self.peerstats
+            .lock()
+            .expect("peerstats lock poisoned")
+            .record_connect(addr.ip(), &user_agent, version, now);
+        self.peers.lock().expect("peers lock poisoned").insert(
+            addr,
+            PeerInfo {
+                addr,
+                inbound,
+                version,
+                user_agent,
+                best_height: peer_height,
+                connected_at: now,
+                last_seen: now,
+                ping_time: None,
+                pending_ping: None,
+                sender: tx.clone(),
+            },
+        );
+
+        if version >= 2 {
+            tx.send(NetworkMessage::ChainRules(CHAIN_RULES_VERSION))
+                .map_err(|_| "writer task gone".to_string())?;
+        }
+        if version >= 5 {
+            // Open with a locator request: if our chains diverged while
+            // we were offline, the peer serves us from the actual fork
+            // point instead of assuming our tip is on its main chain.
+            let locator = self
+                .chain
+                .lock()
+                .expect("chain lock poisoned")
+                .block_locator()
+                .map_err(|e| e.to_string())?;
+            tx.send(NetworkMessage::GetBlocksLocator {
+                locator,
+                limit: network::MAX_INV_PER_MESSAGE as u32,
+            })
+            .map_err(|_| "writer task gone".to_string())?;
+        }
+
+        // Inbound messages go through a bounded priority queue drained
+        // by a separate task, so block relay is never stuck behind a
+        // backlog of cheap queries from the same peer.
+        let queue: SharedQueue = Arc::new((
+            Mutex::new(MessageQueue::new(MAX_QUEUE_DEPTH)),
+            tokio::sync::Notify::new(),
+        ));
+        let worker = tokio::spawn(process_queue(self.clone(), queue.clone(), addr));
+
+        let result = self.read_loop(&mut reader, addr, &queue).await;
+        worker.abort();
+        let removed = self.peers.lock().expect("peers lock poisoned").remove(&addr);
+        if let Some(peer) = removed {
+            let now = unix_now();
+            self.peerstats
+                .lock()
+                .expect("peerstats lock poisoned")
+                .record_disconnect(addr.ip(), now.saturating_sub(peer.connected_at), now);
+        }
+        self.sync.lock().expect("sync lock poisoned").release(addr);
+        result
+    }
+
+    async fn read_loop<R: tokio::io::AsyncRead + Unpin>(
+        &self,
+        reader: &mut R,
+        addr: SocketAddr,
+        queue: &SharedQueue,
+    ) -> Result<(), String> {
+        loop {
+            let message = network::read_message(reader).await?;
+            match self.peers.lock().expect("peers lock poisoned").get_mut(&addr) {
+                Some(peer) => peer.last_seen = unix_now(),
+                // The processing task dropped this peer over a protocol
+                // violation; stop reading from it.
+                None => return Err("peer dropped by message processor".to_string()),
+            }
+            queue
+                .0
+                .lock()
+                .expect("queue lock poisoned")
+                .push(message)?;
+            queue.1.notify_one();
+        }
+    }
+
+    /// Dispatches one message from a connected peer.
+    pub fn handle_network_message(
+        &self,
+        addr: SocketAddr,
+        message: NetworkMessage,
+    ) -> Result<(), String> {
+        match message {
+            NetworkMessage::VerAck => Ok(()),
+            NetworkMessage::Ping(nonce) => self.send_to_peer(addr, NetworkMessage::Pong(nonce)),
+            NetworkMessage::Pong(nonce) => {
+                self.record_pong(addr, nonce);
+                Ok(())
+            }
+            NetworkMessage::Transaction(tx) => {
+                // Blocksonly mode: drop loose transactions entirely;
+                // blocks still carry everything that matters.
+                if !self.toggles.lock().expect("toggles lock poisoned").tx_relay {
+                    return Ok(());
+                }
+                // The fluffed copy is on the wire; any embargo we hold
+                // for this transaction has done its job.
+                let tx_hash = tx.hash();
+                self.dandelion
+                    .lock()
+                    .expect("dandelion lock poisoned")
+                    .clear(&tx_hash);
+                let outcome = {
+                    let chain = self.chain.lock().expect("chain lock poisoned");
+                    let mut mempool = self.mempool.lock().expect("mempool lock poisoned");
+                    if mempool.contains(&tx_hash) {
+                        Ok(false)
+                    } else {
+                        let verdict = match chain.validate_transaction(&tx, self.chain_id) {
+                            // A nonce ahead of the confirmed state may
+                            // chain onto the sender's pending
+                            // transactions (the CPFP path).
+                            Err(RejectionReason::BadNonce { expected, got }) if got > expected => {
+                                let balance = chain.get_balance(&tx.from).unwrap_or(0);
+                                mempool
+                                    .validate_chained(&tx, expected, balance)
+                                    .map_err(|_| RejectionReason::BadNonce { expected, got })
+                            }
+                            other => other.map(|_| ()),
+                        };
+                        match verdict {
+                            Err(reason) => Err(reason),
+                            // Mempool policy refusals (fee too low, pool
+                            // full) are not consensus verdicts: log only.
+                            Ok(()) => match mempool.insert(tx.clone(), chain.height()) {
+                                Ok(_) => Ok(true),
+                                Err(e) => {
+                                    log::debug!("mempool refused tx from {}: {}", addr, e);
+                                    Ok(false)
+                                }
+                            },
+                        }
+                    }
+                };
+                match outcome {
+                    Ok(true) => {
+                        self.peerstats
+                            .lock()
+                            .expect("peerstats lock poisoned")
+                            .record_tx(addr.ip(), true);
+                        self.record_watch_tx(&tx);
+                        self.announce_except(Some(addr), NetworkMessage::Transaction(tx))
+                    }
+                    Ok(false) => Ok(()),
+                    Err(reason) => {
+                        self.record_rejection("tx", &tx_hash, addr, &reason);
+                        Ok(())
+                    }
+                }
+            }
+            NetworkMessage::Package(txs) => {
+                let outcome = {
+                    let chain = self.chain.lock().expect("chain lock poisoned");
+                    let mut mempool = self.mempool.lock().expect("mempool lock poisoned");
+                    if txs.iter().all(|tx| mempool.contains(&tx.hash())) {
+                        Ok(false)
+                    } else if let Err(e) = mempool.check_package(&txs) {
+                        // Policy refusals are not consensus verdicts:
+                        // log only, like individual relay.
+                        log::debug!("mempool refused package from {}: {}", addr, e);
+                        Ok(false)
+                    } else {
+                        let height = chain.height();
+                        let mut inserted = Vec::new();
+                        let mut outcome = Ok(true);
+                        for tx in &txs {
+                            let verdict = match chain.validate_transaction(tx, self.chain_id) {
+                                // Members chain onto the ones just
+                                // pooled (the CPFP path).
+                                Err(RejectionReason::BadNonce { expected, got })
+                                    if got > expected =>
+                                {
+                                    let balance = chain.get_balance(&tx.from).unwrap_or(0);
+                                    mempool
+                                        .validate_chained(tx, expected, balance)
+                                        .map_err(|_| RejectionReason::BadNonce { expected, got })
+                                }
+                                other => other.map(|_| ()),
+                            };
+                            match verdict {
+                                Ok(()) => match mempool.insert_package_member(tx.clone(), height)
+                                {
+                                    Ok(tx_hash) => inserted.push(tx_hash),
+                                    Err(e) => {
+                                        log::debug!(
+                                            "mempool refused package member from {}: {}",
+                                            addr,
+                                            e
+                                        );
+                                        outcome = Ok(false);
+                                        break;
+                                    }
+                                },
+                                Err(reason) => {
+                                    outcome = Err((tx.hash(), reason));
+                                    break;
+                                }
+                            }
+                        }
+                        if outcome != Ok(true) {
+                            // All or nothing: one bad member voids the
+                            // whole package.
+                            for tx_hash in &inserted {
+                                mempool.remove(tx_hash);
+                            }
+                        }
+                        outcome
+                    }
+                };
+                match outcome {
+                    Ok(true) => {
+                        {
+                            let mut stats =
+                                self.peerstats.lock().expect("peerstats lock poisoned");
+                            for _ in &txs {
+                                stats.record_tx(addr.ip(), true);
+                            }
+                        }
+                        for tx in &txs {
+                            self.record_watch_tx(tx);
+                        }
+                        self.broadcast_package(Some(addr), txs);
+                        Ok(())
+                    }
+                    Ok(false) => Ok(()),
+                    Err((tx_hash, reason)) => {
+                        self.record_rejection("tx", &tx_hash, addr, &reason);
+                        Ok(())
+                    }
+                }
+            }
+            NetworkMessage::StemTransaction(tx) => {
+                // Stem transactions stay out of the mempool until they
+                // fluff, so getrawmempool cannot leak them early.
+                let tx_hash = tx.hash();
+                let verdict = {
+                    let chain = self.chain.lock().expect("chain lock poisoned");
+                    let mempool = self.mempool.lock().expect("mempool lock poisoned");
+                    if mempool.contains(&tx_hash) {
+                        None
+                    } else {
+                        Some(chain.validate_transaction(&tx, self.chain_id))
+                    }
+                };
+                match verdict {
+                    Some(Ok(_)) => {
+                        self.peerstats
+                            .lock()
+                            .expect("peerstats lock poisoned")
+                            .record_tx(addr.ip(), true);
+                        self.stem_or_fluff(tx)
+                    }
+                    Some(Err(reason)) => self.record_rejection("tx", &tx_hash, addr, &reason),
+                    None => {}
+                }
+                Ok(())
+            }
+            NetworkMessage::Reject { what, hash, code } => {
+                log::debug!(
+                    "peer {} rejected our {} {}: {}",
+                    addr,
+                    what,
+                    hex::encode(hash),
+                    code
+                );
+                Ok(())
+            }
+            NetworkMessage::Block(block) => {
+                let started = std::time::Instant::now();
+                let accepted = {
+                    let mut chain = self.chain.lock().expect("chain lock poisoned");
+                    if chain.get_block(&block.hash()).ok().flatten().is_some() {
+                        Ok(false)
+                    } else {
+                        chain.add_block(&block, self.chain_id).map(|_| true)
+                    }
+                };
+                match accepted {
+                    Ok(true) => {
+                        self.peerstats
+                            .lock()
+                            .expect("peerstats lock poisoned")
+                            .record_block(addr.ip(), true);
+                        // An accepted block means this peer won the
+                        // relay race; duplicates land in Ok(false).
+                        self.reputation
+                            .lock()
+                            .expect("reputation lock poisoned")
+                            .credit_first_relay(addr.ip(), unix_now());
+                        self.record_block_telemetry(&block, Some(addr), started.elapsed());
+                        self.forks
+                            .lock()
+                            .expect("forks lock poisoned")
+                            .record_connected(&block.hash());
+                        self.mempool
+                            .lock()
+                            .expect("mempool lock poisoned")
+                            .remove_confirmed(&block.transactions);
+                        self.record_watch_block(&block);
+                        self.record_deposit_block(&block);
+                        self.notify_tip_change(&block);
+                        self.announce_except(Some(addr), NetworkMessage::Block(block))
+                    }
+                    Ok(false) => Ok(()),
+                    Err(reason) => {
+                        // A parent we do not have means we are behind a
+                        // fork; hand the sender a locator so it can
+                        // find where our chains diverge.
+                        if reason == RejectionReason::UnknownPrevBlock {
+                            self.request_blocks_by_locator(addr);
+                        }
+                        self.record_stale_block(&block, &reason);
+                        self.check_invalid_longer_chain(&block, &reason);
+                        self.record_rejection("block", &block.hash(), addr, &reason);
+                        Ok(())
+                    }
+                }
+            }
+            NetworkMessage::GetBlocks { from, limit } => {
+                let blocks = {
+                    let chain = self.chain.lock().expect("chain lock poisoned");
+                    collect_blocks_after(&chain, &from, limit)?
+                };
+                self.send_to_peer(addr, NetworkMessage::Blocks(blocks))
+            }
+            NetworkMessage::GetBlocksLocator { locator, limit } => {
+                let blocks = {
+                    let chain = self.chain.lock().expect("chain lock poisoned");
+                    let locator = &locator[..locator.len().min(network::MAX_LOCATOR_HASHES)];
+                    // No shared history at all means the requester gets
+                    // everything: an unknown "from" starts at genesis.
+                    let from = chain.locate_fork(locator)?.unwrap_or_default();
+                    collect_blocks_after(&chain, &from, limit)?
+                };
+                self.send_to_peer(addr, NetworkMessage::Blocks(blocks))
+            }
+            NetworkMessage::Blocks(blocks) => {
+                let mut applied: u64 = 0;
+                for block in blocks {
+                    let result = {
+                        let mut chain = self.chain.lock().expect("chain lock poisoned");
+                        if chain.get_block(&block.hash()).ok().flatten().is_some() {
+                            Ok(false)
+                        } else {
+                            chain.add_block(&block, self.chain_id).map(|_| true)
+                        }
+                    };
+                    match result {
+                        Ok(fresh) => {
+                            if fresh {
+                                self.peerstats
+                                    .lock()
+                                    .expect("peerstats lock poisoned")
+                                    .record_block(addr.ip(), true);
+                                self.record_watch_block(&block);
+                                self.record_deposit_block(&block);
+                                self.notify_tip_change(&block);
+                            }
+                            applied += 1;
+                        }
+                        Err(reason) => {
+                            self.record_stale_block(&block, &reason);
+                            self.check_invalid_longer_chain(&block, &reason);
+                            self.record_rejection("block", &block.hash(), addr, &reason);
+                            break;
+                        }
+                    }
+                }
+                // Credit the peer's sync window and hand it the next one
+                // as soon as the current window completes.
+                let finished = self
+                    .sync
+                    .lock()
+                    .expect("sync lock poisoned")
+                    .record_progress(addr, applied);
+                if finished {
+                    self.assign_sync_work(addr)?;
+                }
+                Ok(())
+            }
+            NetworkMessage::GetBlockRange { start, count } => {
+                let blocks = {
+                    let chain = self.chain.lock().expect("chain lock poisoned");
+                    let count = count.min(network::MAX_INV_PER_MESSAGE as u32) as u64;
+                    let mut out = Vec::new();
+                    for height in start..start + count {
+                        match chain.get_block_by_height(height)? {
+                            Some(block) => out.push(block),
+                            None => break,
+                        }
+                    }
+                    out
+                };
+                self.send_to_peer(addr, NetworkMessage::Blocks(blocks))
+            }
+            NetworkMessage::GetPeers => {
+                let mut addrs: Vec<String> = self
+                    .peers
+                    .lock()
+                    .expect("peers lock poisoned")
+                    .keys()
+                    .filter(|a| **a != addr)
+                    .map(|a| a.to_string())
+                    .collect();
+                // Pad the answer with gossiped addresses so small nodes
+                // still spread more of the network than they connect to.
+                for gossiped in self
+                    .addrman
+                    .lock()
+                    .expect("addrman lock poisoned")
+                    .sample(MAX_INBOUND_PEERS)
+                {
+                    let s = gossiped.to_string();
+                    if !addrs.contains(&s) {
+                        addrs.push(s);
+                    }
+                }
+                self.send_to_peer(addr, NetworkMessage::Peers(addrs))
+            }
+            NetworkMessage::ChainRules(version) => {
+                if version != CHAIN_RULES_VERSION {
+                    return Err(format!(
+                        "peer enforces chain rules v{}, we enforce v{}",
+                        version, CHAIN_RULES_VERSION
+                    ));
+                }
+                Ok(())
+            }
+            NetworkMessage::Peers(addrs) => {
+                // Every gossiped address lands in the bucket its
+                // (address, source) net-group pair hashes to, so this
+                // peer cannot claim more of the table than its buckets.
+                let mut addrman = self.addrman.lock().expect("addrman lock poisoned");
+                for gossiped in addrs.iter().filter_map(|a| a.parse().ok()) {
+                    addrman.insert(gossiped, addr);
+                }
+                Ok(())
+            }
+            NetworkMessage::GetUtxoProof { address } => {
+                let proof = {
+                    let chain = self.chain.lock().expect("chain lock poisoned");
+                    proofs::build_address_proof(&chain, &address)?
+                };
+                self.send_to_peer(addr, NetworkMessage::UtxoProof(proof))
+            }
+            // Proof answers are consumed by light wallets; a full node
+            // that receives one unsolicited has nothing to do with it.
+            NetworkMessage::UtxoProof(_) => Ok(()),
+            NetworkMessage::GetCFHeaders {
+                start_height,
+                stop_hash,
+            } => {
+                let (prev_header, filter_hashes) = {
+                    let chain = self.chain.lock().expect("chain lock poisoned");
+                    let stop = filter_range_end(&chain, start_height, &stop_hash)?;
+                    let prev_header = if start_height == 0 {
+                        [0u8; 32]
+                    } else {
+                        let hash = chain
+                            .get_block_hash(start_height - 1)?
+                            .ok_or("filter range start is above the tip")?;
+                        chain.filter_header(&hash)?
+                    };
+                    let mut filter_hashes = Vec::new();
+                    for height in start_height..=stop {
+                        let hash = chain
+                            .get_block_hash(height)?
+                            .ok_or("filter range height disappeared mid-scan")?;
+                        let filter = chain
+                            .get_filter(&hash)?
+                            .ok_or("filter missing for a main-chain block")?;
+                        filter_hashes.push(filter.filter_hash());
+                    }
+                    (prev_header, filter_hashes)
+                };
+                self.send_to_peer(
+                    addr,
+                    NetworkMessage::CFHeaders {
+                        start_height,
+                        prev_header,
+                        filter_hashes,
+                    },
+                )
+            }
+            NetworkMessage::GetCFilters {
+                start_height,
+                stop_hash,
+            } => {
+                let filters = {
+                    let chain = self.chain.lock().expect("chain lock poisoned");
+                    let stop = filter_range_end(&chain, start_height, &stop_hash)?;
+                    let mut filters = Vec::new();
+                    for height in start_height..=stop {
+                        let block_hash = chain
+                            .get_block_hash(height)?
+                            .ok_or("filter range height disappeared mid-scan")?;
+                        let filter = chain
+                            .get_filter(&block_hash)?
+                            .ok_or("filter missing for a main-chain block")?;
+                        filters.push(CFilterMessage { block_hash, filter });
+                    }
+                    filters
+                };
+                self.send_to_peer(addr, NetworkMessage::CFilters(filters))
+            }
+            // Filter answers are consumed by light clients, same as
+            // UTXO proofs above.
+            NetworkMessage::CFHeaders { .. } | NetworkMessage::CFilters(_) => Ok(()),
+            NetworkMessage::Alert(signed) => {
+                let outcome = self
+                    .alerts
+                    .lock()
+                    .expect("alerts lock poisoned")
+                    .accept(signed.clone(), &self.params, unix_now());
+                match outcome {
+                    Ok(true) => {
+                        log::warn!(
+                            "network alert {}: {}",
+                            signed.alert.id,
+                            signed.alert.message
+                        );
+                        self.broadcast_except(addr, NetworkMessage::Alert(signed))
+                    }
+                    Ok(false) => Ok(()),
+                    // Under-signed or expired alerts are quietly
+                    // dropped; they are not a peer protocol violation.
+                    Err(e) => {
+                        log::debug!("ignoring alert from {}: {}", addr, e);
+                        Ok(())
+                    }
+                }
+            }
+            NetworkMessage::Inv { blocks, txs } => {
+                // Ask only for what we lack; hashes past the
+                // announcement cap are ignored rather than trusted.
+                let request_txs =
+                    self.toggles.lock().expect("toggles lock poisoned").tx_relay;
+                let (want_blocks, want_txs) = {
+                    let chain = self.chain.lock().expect("chain lock poisoned");
+                    let mempool = self.mempool.lock().expect("mempool lock poisoned");
+                    let mut considered = 0;
+                    let mut want_blocks = Vec::new();
+                    for hash in &blocks {
+                        if considered == network::MAX_INV_PER_MESSAGE {
+                            break;
+                        }
+                        considered += 1;
+                        if chain.get_block(hash).ok().flatten().is_none() {
+                            want_blocks.push(*hash);
+                        }
+                    }
+                    let mut want_txs = Vec::new();
+                    for hash in &txs {
+                        if considered == network::MAX_INV_PER_MESSAGE || !request_txs {
+                            break;
+                        }
+                        considered += 1;
+                        if !mempool.contains(hash)
+                            && chain.get_transaction(hash).ok().flatten().is_none()
+                        {
+                            want_txs.push(*hash);
+                        }
+                    }
+                    (want_blocks, want_txs)
+                };
+                if want_blocks.is_empty() && want_txs.is_empty() {
+                    return Ok(());
+                }
+                self.send_to_peer(
+                    addr,
+                    NetworkMessage::GetData {
+                        blocks: want_blocks,
+                        txs: want_txs,
+                    },
+                )
+            }
+            NetworkMessage::GetData { blocks, txs } => {
+                let (replies, missing_blocks, missing_txs) = {
+                    let chain = self.chain.lock().expect("chain lock poisoned");
+                    let mempool = self.mempool.lock().expect("mempool lock poisoned");
+                    let mut served = 0;
+                    let mut replies = Vec::new();
+                    let mut missing_blocks = Vec::new();
+                    let mut missing_txs = Vec::new();
+                    for hash in &blocks {
+                        if served == network::MAX_INV_PER_MESSAGE {
+                            break;
+                        }
+                        served += 1;
+                        match chain.get_block(hash).ok().flatten() {
+                            Some(block) => replies.push(NetworkMessage::Block(block)),
+                            None => missing_blocks.push(*hash),
+                        }
+                    }
+                    for hash in &txs {
+                        if served == network::MAX_INV_PER_MESSAGE {
+                            break;
+                        }
+                        served += 1;
+                        // Loose transactions are served from the mempool
+                        // only; once confirmed they travel in their block.
+                        match mempool.get(hash) {
+                            Some(entry) => {
+                                replies.push(NetworkMessage::Transaction(entry.tx.clone()))
+                            }
+                            None => missing_txs.push(*hash),
+                        }
+                    }
+                    (replies, missing_blocks, missing_txs)
+                };
+                for reply in replies {
+                    self.send_to_peer(addr, reply)?;
+                }
+                if !missing_blocks.is_empty() || !missing_txs.is_empty() {
+                    self.send_to_peer(
+                        addr,
+                        NetworkMessage::NotFound {
+                            blocks: missing_blocks,
+                            txs: missing_txs,
+                        },
+                    )?;
+                }
+                Ok(())
+            }
+            NetworkMessage::NotFound { blocks, txs } => {
+                // Best-effort: whoever else announces the object will be
+                // asked in turn.
+                log::debug!(
+                    "peer {} could not supply {} blocks and {} txs we requested",
+                    addr,
+                    blocks.len(),
+                    txs.len()
+                );
+                Ok(())
+            }
+            NetworkMessage::Version { .. } => Ok(()),
+        }
+    }
+
+    /// Periodic task: sends a nonce-tagged ping to every peer and drops
+    /// peers that never answered the previous one.
+    pub fn send_pings_to_peers(&self) {
+        let mut stale = Vec::new();
+        {
+            let mut peers = self.peers.lock().expect("peers lock poisoned");
+            for peer in peers.values_mut() {
+                if peer.pending_ping.is_some() {
+                    // Previous ping went unanswered for a full interval.
+                    stale.push(peer.addr);
+                    continue;
+                }
+                let nonce: u64 = rand::thread_rng().gen();
+                peer.pending_ping = Some((nonce, std::time::Instant::now()));
+                let _ = peer.sender.send(NetworkMessage::Ping(nonce));
+            }
+            for addr in &stale {
+                peers.remove(addr);
+            }
+        }
+        for addr in stale {
+            log::info!("dropping unresponsive peer {}", addr);
+        }
+    }
+
+    /// Folds a pong round-trip sample into the peer's latency EWMA.
+    fn record_pong(&self, addr: SocketAddr, nonce: u64) {
+        let mut peers = self.peers.lock().expect("peers lock poisoned");
+        if let Some(peer) = peers.get_mut(&addr) {
+            match peer.pending_ping.take() {
+                Some((expected, sent)) if expected == nonce => {
+                    let sample = sent.elapsed().as_secs_f64() * 1000.0;
+                    peer.ping_time = Some(match peer.ping_time {
+                        Some(prev) => prev + PING_EWMA_ALPHA * (sample - prev),
+                        None => sample,
+                    });
+                }
+                other => {
+                    // Unsolicited or mismatched pong; restore any pending ping.
+                    peer.pending_ping = other;
+                }
+            }
+        }
+    }
+
+    fn send_to_peer(&self, addr: SocketAddr, message: NetworkMessage) -> Result<(), String> {
+        let peers = self.peers.lock().expect("peers lock poisoned");
+        if let Some(peer) = peers.get(&addr) {
+            peer.sender
+                .send(message)
+                .map_err(|_| "peer writer gone".to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Logs a consensus rejection, bumps its per-code counter and
+    /// tells the offending peer why via a Reject message.
+    fn record_rejection(
+        &self,
+        what: &str,
+        hash: &crate::types::Hash256,
+        addr: SocketAddr,
+        reason: &RejectionReason,
+    ) {
+        log::info!(
+            "rejected {} {} from {}: {}",
+            what,
+            hex::encode(hash),
+            addr,
+            reason
+        );
+        *self
+            .rejections
+            .lock()
+            .expect("rejections lock poisoned")
+            .entry(reason.code().to_string())
+            .or_insert(0) += 1;
+        // Rejections are audit-relevant (they are what a ban decision
+        // would be based on); a journal write failure is not.
+        if let Err(e) = self
+            .chain
+            .lock()
+            .expect("chain lock poisoned")
+            .journal(crate::journal::EventKind::PeerRejected {
+                peer: addr.to_string(),
+                what: what.to_string(),
+                hash: *hash,
+                code: reason.code().to_string(),
+            })
+        {
+            log::debug!("failed to journal rejection: {}", e);
+        }
+        {
+            let mut stats = self.peerstats.lock().expect("peerstats lock poisoned");
+            if what == "block" {
+                stats.record_block(addr.ip(), false);
+            } else {
+                stats.record_tx(addr.ip(), false);
+            }
+        }
+        let _ = self.send_to_peer(
+            addr,
+            NetworkMessage::Reject {
+                what: what.to_string(),
+                hash: *hash,
+                code: reason.code().to_string(),
+            },
+        );
+        let now = unix_now();
+        let banned = self
+            .reputation
+            .lock()
+            .expect("reputation lock poisoned")
+            .penalize(addr.ip(), reputation::INVALID_RELAY_PENALTY, now);
+        if banned {
+            log::warn!("banning {} for repeated invalid relays", addr);
+            if let Some(peer) = self.peers.lock().expect("peers lock poisoned").remove(&addr) {
+                self.peerstats
+                    .lock()
+                    .expect("peerstats lock poisoned")
+                    .record_disconnect(addr.ip(), now.saturating_sub(peer.connected_at), now);
+            }
+        }
+    }
+
+    /// Snapshot of the per-code rejection counters.
+    pub fn rejection_counts(&self) -> HashMap<String, u64> {
+        self.rejections
+            .lock()
+            .expect("rejections lock poisoned")
+            .clone()
+    }
+
+    /// Captures acceptance telemetry for a freshly connected block.
+    /// `peer` is absent for blocks this node mined itself. Blocks
+    /// applied during bulk sync are not recorded (see [`crate::telemetry`]).
+    pub fn record_block_telemetry(
+        &self,
+        block: &Block,
+        peer: Option<SocketAddr>,
+        validation: Duration,
+    ) {
+        let first_seen = unix_now();
+        self.telemetry
+            .lock()
+            .expect("telemetry lock poisoned")
+            .record(BlockRecord {
+                hash: block.hash(),
+                height: block.header.height,
+                first_seen,
+                peer,
+                validation_micros: validation.as_micros() as u64,
+                propagation_delay_secs: first_seen as i64 - block.header.timestamp as i64,
+            });
+    }
+
+    /// Tracks a block that failed to connect as a fork tip. A block on
+    /// another branch trips `UnknownPrevBlock`/`BadHeight` here even
+    /// when internally sound, so its own proof of work decides whether
+    /// the branch counts as a valid fork or an invalid one.
+    /// Protective reaction to a block that claims to extend past our
+    /// tip but fails validation: either a peer is attacking us with a
+    /// fabricated longer chain, or — worse — our own rules disagree
+    /// with the network's. Both warrant stopping wallet sends until a
+    /// human looks (Bitcoin Core's safe-mode behavior). A parent we
+    /// simply have not seen yet is ordinary sync lag, not an anomaly.
+    fn check_invalid_longer_chain(&self, block: &Block, reason: &RejectionReason) {
+        if *reason == RejectionReason::UnknownPrevBlock {
+            return;
+        }
+        let our_height = self
+            .chain
+            .lock()
+            .expect("chain lock poisoned")
+            .height();
+        if block.header.height <= our_height {
+            return;
+        }
+        self.enter_safe_mode(format!(
+            "invalid block {} claims height {} past our tip {}: {}",
+            hex::encode(block.hash()),
+            block.header.height,
+            our_height,
+            reason
+        ));
+    }
+
+    /// Switches safe mode on (idempotently), records the reason for
+    /// RPC warnings and raises the operator alert hook.
+    pub fn enter_safe_mode(&self, reason: String) {
+        {
+            let mut toggles = self.toggles.lock().expect("toggles lock poisoned");
+            if toggles.safe_mode {
+                return;
+            }
+            toggles.safe_mode = true;
+        }
+        log::error!("entering safe mode: {}", reason);
+        self.notifier.alert(format!("safe mode: {}", reason));
+        *self
+            .safe_mode_reason
+            .lock()
+            .expect("safe mode reason lock poisoned") = Some(reason);
+    }
+
+    fn record_stale_block(&self, block: &Block, reason: &RejectionReason) {
+        let valid = matches!(
+            reason,
+            RejectionReason::UnknownPrevBlock | RejectionReason::BadHeight
+        ) && self.params.pow_algorithm.algorithm().check(&block.header);
+        self.forks
+            .lock()
+            .expect("forks lock poisoned")
+            .record_stale(block, valid, unix_now());
+    }
+
+    /// Logs mempool acceptance of `tx` against any watched address and
+    /// fires the wallet hooks when it is relevant.
+    fn record_watch_tx(&self, tx: &Transaction) {
+        let mut watch = self.watch.lock().expect("watch lock poisoned");
+        watch.record_transaction(tx, None, unix_now());
+        if watch.touches(tx) {
+            self.notifier.wallet_tx(tx.hash(), TxEvent::Mempool);
+            self.events.wallet_tx(tx.hash(), TxEvent::Mempool);
+        }
+        self.publish_address_events(&watch, tx, None);
+    }
+
+    /// Logs every transaction of a freshly connected block against the
+    /// watched addresses it touches, firing confirmation hooks.
+    fn record_watch_block(&self, block: &Block) {
+        let mut watch = self.watch.lock().expect("watch lock poisoned");
+        for tx in &block.transactions {
+            watch.record_transaction(tx, Some(block.header.height), block.header.timestamp);
+            if watch.touches(tx) {
+                self.notifier.wallet_tx(tx.hash(), TxEvent::Confirmed);
+                self.events.wallet_tx(tx.hash(), TxEvent::Confirmed);
+            }
+            self.publish_address_events(&watch, tx, Some(block.header.height));
+        }
+    }
+
+    /// Mirrors a transaction's per-address activity onto the SSE feed,
+    /// one event per watched side of the transfer.
+    fn publish_address_events(&self, watch: &WatchList, tx: &Transaction, height: Option<u64>) {
+        for (address, direction, amount) in [
+            (tx.from, watch::Direction::Sent, tx.amount + tx.fee),
+            (tx.to, watch::Direction::Received, tx.amount),
+        ] {
+            if watch.is_watched(&address) {
+                self.events
+                    .address(address, tx.hash(), direction, amount, height);
+            }
+        }
+    }
+
+    /// Logs every transaction of a freshly connected block against the
+    /// registered deposit addresses, crediting what reaches its
+    /// confirmation requirement.
+    fn record_deposit_block(&self, block: &Block) {
+        self.deposits
+            .lock()
+            .expect("deposits lock poisoned")
+            .block_connected(block);
+    }
+
+    /// Announces every wallet-relevant transaction of a disconnected
+    /// block as reorged out. The reorg path calls this per block it
+    /// unwinds, before re-admitting transactions to the mempool.
+    pub fn notify_block_disconnected(&self, block: &Block) {
+        let watch = self.watch.lock().expect("watch lock poisoned");
+        for tx in &block.transactions {
+            if watch.touches(tx) {
+                self.notifier.wallet_tx(tx.hash(), TxEvent::Reorged);
+                self.events.wallet_tx(tx.hash(), TxEvent::Reorged);
+            }
+        }
+        self.deposits
+            .lock()
+            .expect("deposits lock poisoned")
+            .block_disconnected(block);
+    }
+
+    fn broadcast_except(&self, skip: SocketAddr, message: NetworkMessage) -> Result<(), String> {
+        let peers = self.peers.lock().expect("peers lock poisoned");
+        for peer in peers.values() {
+            if peer.addr != skip {
+                let _ = peer.sender.send(message.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// Announces a new best block to subscribers — lagging or absent
+    /// receivers are fine — and fires the blocknotify hook.
+    pub fn notify_tip_change(&self, block: &Block) {
+        {
+            // Holding the chain lock while swapping keeps concurrent
+            // connects from publishing snapshots out of order.
+            let chain = self.chain.lock().expect("chain lock poisoned");
+            self.tip_view.refresh(&chain);
+        }
+        let _ = self.tip_changes.send(block.header.height);
+        self.notifier.block(block.hash(), block.header.height);
+        self.events.block(block.hash(), block.header.height);
+    }
+
+    /// Broadcasts to every connected peer.
+    pub fn broadcast(&self, message: NetworkMessage) {
+        if let Some(relay) = &self.external_relay {
+            let _ = relay.send(message.clone());
+        }
+        let peers = self.peers.lock().expect("peers lock poisoned");
+        for peer in peers.values() {
+            let _ = peer.sender.send(message.clone());
+        }
+    }
+
+    /// Relays an accepted block or transaction: peers that speak
+    /// inventory relay (protocol version 9 and up) get an Inv carrying
+    /// the hash and request the body themselves, older peers still get
+    /// the full object pushed. `skip` is excluded when the object came
+    /// off the wire.
+    fn announce_except(&self, skip: Option<SocketAddr>, message: NetworkMessage) -> Result<(), String> {
+        let inv = match &message {
+            NetworkMessage::Block(block) => NetworkMessage::Inv {
+                blocks: vec![block.hash()],
+                txs: Vec::new(),
+            },
+            NetworkMessage::Transaction(tx) => NetworkMessage::Inv {
+                blocks: Vec::new(),
+                txs: vec![tx.hash()],
+            },
+            other => return Err(format!("{} is not announced by inventory", other.name())),
+        };
+        let peers = self.peers.lock().expect("peers lock poisoned");
+        for peer in peers.values() {
+            if Some(peer.addr) == skip {
+                continue;
+            }
+            if peer.version >= network::INV_RELAY_VERSION {
+                let _ = peer.sender.send(inv.clone());
+            } else {
+                let _ = peer.sender.send(message.clone());
+            }
+        }
+        Ok(())
+    }
+
+    /// [`Node::announce_except`] for objects that originated here,
+    /// additionally mirrored to the external relay like any broadcast.
+    pub fn announce(&self, message: NetworkMessage) {
+        if let Some(relay) = &self.external_relay {
+            let _ = relay.send(message.clone());
+        }
+        let _ = self.announce_except(None, message);
+    }
+
+    /// Relays an accepted package to every package-aware peer
+    /// (protocol version 8 and up), skipping `skip` when it came off
+    /// the wire. Older peers get nothing: sent individually, their
+    /// mempools would refuse the below-floor parent anyway.
+    pub fn broadcast_package(&self, skip: Option<SocketAddr>, txs: Vec<Transaction>) {
+        let peers = self.peers.lock().expect("peers lock poisoned");
+        for peer in peers.values() {
+            if Some(peer.addr) == skip || peer.version < network::PACKAGE_RELAY_VERSION {
+                continue;
+            }
+            let _ = peer.sender.send(NetworkMessage::Package(txs.clone()));
+        }
+    }
+
+    /// Relays a transaction that originated at this node (a wallet RPC
+    /// submission), entering the Dandelion++ stem phase.
+    pub fn relay_local_transaction(&self, tx: Transaction) {
+        self.stem_or_fluff(tx);
+    }
+
+    /// Routes a stem-phase transaction: forward to the epoch's stem
+    /// successor under embargo, or switch to fluff and diffuse.
+    fn stem_or_fluff(&self, tx: Transaction) {
+        let peer_addrs: Vec<SocketAddr> = {
+            let peers = self.peers.lock().expect("peers lock poisoned");
+            peers.keys().copied().collect()
+        };
+        let route = self
+            .dandelion
+            .lock()
+            .expect("dandelion lock poisoned")
+            .route(&peer_addrs);
+        match route {
+            Route::Stem(peer) => {
+                self.dandelion
+                    .lock()
+                    .expect("dandelion lock poisoned")
+                    .embargo(tx.clone());
+                if self
+                    .send_to_peer(peer, NetworkMessage::StemTransaction(tx.clone()))
+                    .is_err()
+                {
+                    // Stem successor vanished mid-send: fall back to fluff.
+                    self.dandelion
+                        .lock()
+                        .expect("dandelion lock poisoned")
+                        .clear(&tx.hash());
+                    self.fluff(tx);
+                }
+            }
+            Route::Fluff => self.fluff(tx),
+        }
+    }
+
+    /// Diffuses a transaction to everyone, inserting it into our own
+    /// mempool first if it is not already there.
+    fn fluff(&self, tx: Transaction) {
+        let inserted = {
+            let chain = self.chain.lock().expect("chain lock poisoned");
+            let mut mempool = self.mempool.lock().expect("mempool lock poisoned");
+            mempool.contains(&tx.hash()) || mempool.insert(tx.clone(), chain.height()).is_ok()
+        };
+        if inserted {
+            self.record_watch_tx(&tx);
+            self.announce(NetworkMessage::Transaction(tx));
+        }
+    }
+
+    /// Sends `peer` a locator-based block request (protocol version 5
+    /// and up), so it can serve us blocks from wherever our main
+    /// chains actually diverge. Best-effort: sync retries anyway.
+    fn request_blocks_by_locator(&self, peer: SocketAddr) {
+        let supported = self
+            .peers
+            .lock()
+            .expect("peers lock poisoned")
+            .get(&peer)
+            .is_some_and(|p| p.version >= 5);
+        if !supported {
+            return;
+        }
+        let locator = match self
+            .chain
+            .lock()
+            .expect("chain lock poisoned")
+            .block_locator()
+        {
+            Ok(locator) => locator,
+            Err(e) => {
+                log::warn!("failed to build block locator: {}", e);
+                return;
+            }
+        };
+        let _ = self.send_to_peer(
+            peer,
+            NetworkMessage::GetBlocksLocator {
+                locator,
+                limit: network::MAX_INV_PER_MESSAGE as u32,
+            },
+        );
+    }
+
+    /// Checks whether peers advertise a longer chain and, if so, fans
+    /// the missing range out across every idle peer as work windows.
+    pub fn check_and_start_sync(&self) {
+        let our_height = self.tip_view.current().height;
+        let peer_best = {
+            let peers = self.peers.lock().expect("peers lock poisoned");
+            peers.values().map(|p| p.best_height).max().unwrap_or(0)
+        };
+        if peer_best > our_height {
+            self.sync
+                .lock()
+                .expect("sync lock poisoned")
+                .begin(our_height, peer_best);
+        }
+        let peer_addrs: Vec<SocketAddr> = self
+            .peers
+            .lock()
+            .expect("peers lock poisoned")
+            .keys()
+            .copied()
+            .collect();
+        for addr in peer_addrs {
+            let _ = self.assign_sync_work(addr);
+        }
+    }
+
+    /// Gives `peer` a sync window if one is available.
+    fn assign_sync_work(&self, peer: SocketAddr) -> Result<(), String> {
+        let assignment = self
+            .sync
+            .lock()
+            .expect("sync lock poisoned")
+            .assign_window(peer);
+        if let Some((start, end)) = assignment {
+            log::debug!("assigning blocks {}..={} to {}", start, end, peer);
+            self.send_to_peer(
+                peer,
+                NetworkMessage::GetBlockRange {
+                    start,
+                    count: (end - start + 1) as u32,
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Periodic sync maintenance: reclaims windows from stalled peers
+    /// and hands out fresh work.
+    pub async fn sync_loop(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            interval.tick().await;
+            let stalled = self.sync.lock().expect("sync lock poisoned").detect_stalls();
+            for addr in stalled {
+                log::info!("sync peer {} stalled; reassigning its window", addr);
+            }
+            self.check_and_start_sync();
+        }
+    }
+
+    /// Fluffs embargoed stem transactions whose timer expired without
+    /// the fluffed copy ever coming back to us.
+    pub async fn dandelion_loop(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(Duration::from_secs(1));
+        loop {
+            interval.tick().await;
+            let expired = self
+                .dandelion
+                .lock()
+                .expect("dandelion lock poisoned")
+                .expired();
+            for tx in expired {
+                log::debug!("embargo expired for {}; fluffing", hex::encode(tx.hash()));
+                self.fluff(tx);
+            }
+        }
+    }
+
+    /// Re-announces our own wallet transactions that have sat
+    /// unconfirmed for a while; relayed third-party traffic is never
+    /// repeated (see [`crate::mempool::TxOrigin`]). The check runs on
+    /// a jittered timer so the repetition pattern itself does not mark
+    /// this node as the transactions' origin.
+    pub async fn rebroadcast_loop(self: Arc<Self>) {
+        loop {
+            let jittered = REBROADCAST_CHECK_SECS / 2
+                + rand::thread_rng().gen_range(0..REBROADCAST_CHECK_SECS);
+            tokio::time::sleep(Duration::from_secs(jittered)).await;
+            let due = {
+                let chain = self.chain.lock().expect("chain lock poisoned");
+                let mut mempool = self.mempool.lock().expect("mempool lock poisoned");
+                mempool.rebroadcast_candidates(chain.height())
+            };
+            for tx in due {
+                log::debug!("rebroadcasting wallet tx {}", hex::encode(tx.hash()));
+                self.announce(NetworkMessage::Transaction(tx));
+            }
+        }
+    }
+
+    /// Runs the ping scheduler until shutdown.
+    pub async fn ping_loop(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(PING_INTERVAL);
+        loop {
+            interval.tick().await;
+            self.send_pings_to_peers();
+        }
+    }
+
+    /// Periodic database maintenance. Every tick scrubs a bounded
+    /// batch of old blocks for silent corruption (see
+    /// `Blockchain::scrub_blocks`); compaction runs every
+    /// `compact_interval` (zero disables it) and, when `cold_after` is
+    /// set, a bounded batch of old block bodies migrates to the cold
+    /// store. Everything defers while a sync is in flight so
+    /// maintenance I/O does not compete with block download.
+    pub async fn maintenance_loop(
+        self: Arc<Self>,
+        compact_interval: Duration,
+        cold_after: Option<Duration>,
+    ) {
+        let mut last_compaction = std::time::Instant::now();
+        let mut interval = tokio::time::interval(MAINTENANCE_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            if self.sync.lock().expect("sync lock poisoned").is_active() {
+                continue;
+            }
+            let cursor = self.scrub.lock().expect("scrub lock poisoned").cursor;
+            let chain = self.chain.clone();
+            let batch = tokio::task::spawn_blocking(move || {
+                chain
+                    .lock()
+                    .expect("chain lock poisoned")
+                    .scrub_blocks(cursor, SCRUB_BATCH_BLOCKS)
+            })
+            .await
+            .unwrap_or_else(|_| Err(PaliError::Invalid("scrub panicked".to_string())));
+            match batch {
+                Ok(batch) => {
+                    for finding in &batch.findings {
+                        if finding.repaired {
+                            log::warn!(
+                                "integrity scrub repaired height {}: {}",
+                                finding.height,
+                                finding.detail
+                            );
+                        } else {
+                            log::error!(
+                                "integrity scrub found damage at height {}: {}",
+                                finding.height,
+                                finding.detail
+                            );
+                        }
+                    }
+                    self.scrub.lock().expect("scrub lock poisoned").absorb(&batch);
+                }
+                Err(e) => log::warn!("integrity scrub failed: {}", e),
+            }
+            if let Some(age) = cold_after {
+                let chain = self.chain.clone();
+                let moved = tokio::task::spawn_blocking(move || {
+                    chain
+                        .lock()
+                        .expect("chain lock poisoned")
+                        .migrate_to_cold(age.as_secs(), COLD_MIGRATION_BATCH)
+                })
+                .await
+                .unwrap_or_else(|_| {
+                    Err(PaliError::Invalid("cold migration panicked".to_string()))
+                });
+                match moved {
+                    Ok(0) => {}
+                    Ok(n) => log::info!("moved {} blocks to cold storage", n),
+                    Err(e) => log::warn!("cold migration failed: {}", e),
+                }
+            }
+            if compact_interval.is_zero() || last_compaction.elapsed() < compact_interval {
+                continue;
+            }
+            let started = std::time::Instant::now();
+            // Compaction holds the chain lock for a long stretch; run
+            // it on the blocking pool like the rest of the DB work.
+            let chain = self.chain.clone();
+            let _ = tokio::task::spawn_blocking(move || {
+                chain.lock().expect("chain lock poisoned").compact_database()
+            })
+            .await;
+            log::info!(
+                "database compaction finished in {:.1}s",
+                started.elapsed().as_secs_f64()
+            );
+            last_compaction = std::time::Instant::now();
+        }
+    }
+}
+
+/// Drains one peer's priority queue, highest class first. A handler
+/// error removes the peer from the table, which the read loop notices
+/// on its next message.
+async fn process_queue(node: Node, queue: SharedQueue, addr: SocketAddr) {
+    loop {
+        let message = queue.0.lock().expect("queue lock poisoned").pop();
+        match message {
+            Some(message) => {
+                // Message handling is synchronous and can hit RocksDB
+                // (block connects, range queries), so it runs on the
+                // blocking pool: a slow disk stalls this peer's queue,
+                // not the async workers serving every other connection.
+                let worker = node.clone();
+                let result =
+                    tokio::task::spawn_blocking(move || worker.handle_network_message(addr, message))
+                        .await
+                        .unwrap_or_else(|e| Err(format!("message handler panicked: {}", e)));
+                if let Err(e) = result {
+                    log::debug!("dropping peer {}: {}", addr, e);
+                    node.peers.lock().expect("peers lock poisoned").remove(&addr);
+                    return;
+                }
+            }
+            None => queue.1.notified().await,
+        }
+    }
+}
+
+async fn writer_task<W: tokio::io::AsyncWrite + Unpin>(
+    mut writer: W,
+    mut rx: mpsc::UnboundedReceiver<NetworkMessage>,
+) {
+    while let Some(message) = rx.recv().await {
+        if network::write_message(&mut writer, &message).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Resolves the last height of a requested filter range: `stop_hash`
+/// must name a main-chain block at or above `start_height`, and the
+/// range is clamped to [`MAX_CFILTERS_PER_MESSAGE`] entries from the
+/// start so one request cannot pin the chain lock for the whole index.
+fn filter_range_end(
+    chain: &Blockchain,
+    start_height: u64,
+    stop_hash: &crate::types::Hash256,
+) -> Result<u64, String> {
+    let stop = chain
+        .get_block(stop_hash)?
+        .ok_or("unknown filter range stop hash")?;
+    if chain.get_block_hash(stop.header.height)? != Some(*stop_hash) {
+        return Err("filter range stop hash is not on the main chain".to_string());
+    }
+    if stop.header.height < start_height {
+        return Err("filter range stop is below its start".to_string());
+    }
+    Ok(stop
+        .header
+        .height
+        .min(start_height + MAX_CFILTERS_PER_MESSAGE as u64 - 1))
+}
+
+fn collect_blocks_after(
+    chain: &Blockchain,
+    from: &crate::types::Hash256,
+    limit: u32,
+) -> Result<Vec<crate::types::Block>, String> {
+    let start_height = match chain.get_block(from)? {
+        Some(block) => block.header.height + 1,
+        None => 0,
+    };
+    let limit = limit.min(network::MAX_INV_PER_MESSAGE as u32) as u64;
+    let mut blocks = Vec::new();
+    for height in start_height..start_height + limit {
+        match chain.get_block_by_height(height)? {
+            Some(block) => blocks.push(block),
+            None => break,
+        }
+    }
+    Ok(blocks)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
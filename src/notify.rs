@@ -0,0 +1,166 @@
+//! External notification hooks, matching bitcoind's `-walletnotify`,
+//! `-blocknotify` and `-alertnotify`.
+//!
+//! Exchange and merchant back-ends poll much less (or not at all) when
+//! the node pushes events to them. Hooks fire for wallet-relevant
+//! transactions — touching a watched address — when first seen in the
+//! mempool, when confirmed, and when reorged back out, and for every
+//! new best block. Delivery is a shell command with `%s` substituted
+//! (bitcoind-compatible), a JSON webhook POST, or both. Events queue
+//! through a channel and are delivered by a background task, so a slow
+//! hook never stalls message handling.
+
+use serde_json::json;
+use tokio::sync::mpsc;
+
+use crate::types::Hash256;
+
+/// Which delivery mechanisms are configured.
+#[derive(Debug, Clone, Default)]
+pub struct HookConfig {
+    /// Shell command run for wallet-relevant transactions; `%s` is
+    /// replaced with the txid.
+    pub walletnotify: Option<String>,
+    /// Shell command run for each new best block; `%s` is replaced
+    /// with the block hash.
+    pub blocknotify: Option<String>,
+    /// Shell command run for node alerts (safe mode, consensus
+    /// anomalies); `%s` is replaced with the alert message.
+    pub alertnotify: Option<String>,
+    /// URL every event is POSTed to as JSON.
+    pub webhook_url: Option<String>,
+}
+
+impl HookConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.walletnotify.is_some()
+            || self.blocknotify.is_some()
+            || self.alertnotify.is_some()
+            || self.webhook_url.is_some()
+    }
+}
+
+/// Lifecycle stage of a wallet-relevant transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxEvent {
+    /// Accepted into the mempool.
+    Mempool,
+    /// Confirmed in a connected block.
+    Confirmed,
+    /// Its block was disconnected in a reorg.
+    Reorged,
+}
+
+impl TxEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TxEvent::Mempool => "mempool",
+            TxEvent::Confirmed => "confirmed",
+            TxEvent::Reorged => "reorged",
+        }
+    }
+}
+
+/// One queued event.
+#[derive(Debug, Clone)]
+pub enum Notification {
+    WalletTx { txid: Hash256, event: TxEvent },
+    Block { hash: Hash256, height: u64 },
+    Alert { message: String },
+}
+
+/// Substitutes the event argument into a hook command template,
+/// bitcoind style.
+pub fn render_command(template: &str, argument: &str) -> String {
+    template.replace("%s", argument)
+}
+
+/// Queues notifications for the background delivery task. Cheap to
+/// call from synchronous message handlers; a disabled notifier drops
+/// everything at the door.
+pub struct Notifier {
+    config: HookConfig,
+    queue: mpsc::UnboundedSender<Notification>,
+}
+
+impl Notifier {
+    /// A notifier plus the receiving end to hand to [`Notifier::run`].
+    pub fn new(config: HookConfig) -> (Self, mpsc::UnboundedReceiver<Notification>) {
+        let (queue, rx) = mpsc::unbounded_channel();
+        (Notifier { config, queue }, rx)
+    }
+
+    /// A notifier with no hooks configured; every event is a no-op.
+    pub fn disabled() -> Self {
+        Notifier::new(HookConfig::default()).0
+    }
+
+    pub fn wallet_tx(&self, txid: Hash256, event: TxEvent) {
+        if self.config.walletnotify.is_some() || self.config.webhook_url.is_some() {
+            let _ = self.queue.send(Notification::WalletTx { txid, event });
+        }
+    }
+
+    pub fn block(&self, hash: Hash256, height: u64) {
+        if self.config.blocknotify.is_some() || self.config.webhook_url.is_some() {
+            let _ = self.queue.send(Notification::Block { hash, height });
+        }
+    }
+
+    /// Something an operator should see immediately, bitcoind's
+    /// `-alertnotify` equivalent.
+    pub fn alert(&self, message: String) {
+        if self.config.alertnotify.is_some() || self.config.webhook_url.is_some() {
+            let _ = self.queue.send(Notification::Alert { message });
+        }
+    }
+
+    /// Drains the queue, executing hooks until every sender is gone.
+    pub async fn run(config: HookConfig, mut rx: mpsc::UnboundedReceiver<Notification>) {
+        let client = reqwest::Client::new();
+        while let Some(notification) = rx.recv().await {
+            deliver(&config, &client, &notification).await;
+        }
+    }
+}
+
+async fn deliver(config: &HookConfig, client: &reqwest::Client, notification: &Notification) {
+    let (template, argument) = match notification {
+        Notification::WalletTx { txid, .. } => (config.walletnotify.as_ref(), hex::encode(txid)),
+        Notification::Block { hash, .. } => (config.blocknotify.as_ref(), hex::encode(hash)),
+        Notification::Alert { message } => (config.alertnotify.as_ref(), message.clone()),
+    };
+    if let Some(template) = template {
+        let command = render_command(template, &argument);
+        match std::process::Command::new("sh").arg("-c").arg(&command).spawn() {
+            // Reap the child off-thread so hooks never leave zombies.
+            Ok(mut child) => {
+                std::thread::spawn(move || {
+                    let _ = child.wait();
+                });
+            }
+            Err(e) => log::warn!("notification hook failed to start: {}", e),
+        }
+    }
+    if let Some(url) = &config.webhook_url {
+        let body = match notification {
+            Notification::WalletTx { txid, event } => json!({
+                "kind": "wallet-tx",
+                "txid": hex::encode(txid),
+                "event": event.as_str(),
+            }),
+            Notification::Block { hash, height } => json!({
+                "kind": "block",
+                "hash": hex::encode(hash),
+                "height": height,
+            }),
+            Notification::Alert { message } => json!({
+                "kind": "alert",
+                "message": message,
+            }),
+        };
+        if let Err(e) = client.post(url).json(&body).send().await {
+            log::warn!("webhook delivery failed: {}", e);
+        }
+    }
+}
@@ -0,0 +1,188 @@
+//! Multi-device wallet sync: encrypted state bundles and pairing codes.
+//!
+//! A bundle carries everything two devices running the same wallet must
+//! agree on besides the key itself — the wallet's identity (address and
+//! public key), its spend policy limits and the labelled transaction
+//! history — so a laptop can pick up where the desktop left off without
+//! re-scanning the chain or re-entering labels. The bundle is encrypted
+//! under a one-time pairing code the user reads off the exporting
+//! device; private keys never enter it, each device keeps its own
+//! wallet file.
+
+use serde::{Deserialize, Serialize};
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngCore;
+
+use crate::error::{WalletError, WalletErrorKind};
+use crate::types::Address;
+use crate::wallet::{derive_key, KdfParams, SpendPolicy, Wallet, MIN_KDF_MEMORY_KIB};
+use crate::wallet_store::{TxRecord, WalletStore};
+
+/// Leading bytes of every sealed bundle.
+pub const BUNDLE_MAGIC: [u8; 8] = *b"PALISYNC";
+
+/// Bundle layout version.
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Pairing-code alphabet: base32 without the lookalikes 0/O/1/I, so a
+/// code survives being read aloud or scribbled on paper.
+const CODE_ALPHABET: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ23456789";
+
+/// `XXXX-XXXX-XXXX-XXXX`: 16 alphabet characters, 80 bits of entropy.
+pub fn generate_pairing_code() -> String {
+    let mut rng = rand::thread_rng();
+    let mut groups = Vec::with_capacity(4);
+    for _ in 0..4 {
+        let group: String = (0..4)
+            .map(|_| CODE_ALPHABET[rng.next_u32() as usize % CODE_ALPHABET.len()] as char)
+            .collect();
+        groups.push(group);
+    }
+    groups.join("-")
+}
+
+/// Case and separator differences must not fail a pairing.
+fn normalize_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+/// The serializable half of a [`SpendPolicy`] — the approval hook is a
+/// per-device callback and stays behind.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PolicySnapshot {
+    pub max_per_tx: Option<u64>,
+    pub daily_cap: Option<u64>,
+    pub allowlist: Option<Vec<Address>>,
+    pub approval_threshold: Option<u64>,
+}
+
+impl PolicySnapshot {
+    pub fn capture(policy: &SpendPolicy) -> PolicySnapshot {
+        PolicySnapshot {
+            max_per_tx: policy.max_per_tx,
+            daily_cap: policy.daily_cap,
+            allowlist: policy
+                .allowlist
+                .as_ref()
+                .map(|set| set.iter().copied().collect()),
+            approval_threshold: policy.approval_threshold,
+        }
+    }
+
+    /// Applies the limits onto `policy`, leaving the approval hook as
+    /// the receiving device configured it.
+    pub fn apply(&self, policy: &mut SpendPolicy) {
+        policy.max_per_tx = self.max_per_tx;
+        policy.daily_cap = self.daily_cap;
+        policy.allowlist = self.allowlist.as_ref().map(|v| v.iter().copied().collect());
+        policy.approval_threshold = self.approval_threshold;
+    }
+}
+
+/// Everything a bundle transports.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateBundle {
+    pub address: Address,
+    /// Compressed secp256k1 public key, letting the importer check the
+    /// bundle belongs to the same wallet before touching anything.
+    pub public_key: Vec<u8>,
+    pub policy: PolicySnapshot,
+    pub records: Vec<TxRecord>,
+    pub exported_at: u64,
+}
+
+impl StateBundle {
+    /// Snapshots a wallet and its history store.
+    pub fn capture(wallet: &Wallet, store: &WalletStore) -> StateBundle {
+        StateBundle {
+            address: wallet.address(),
+            public_key: wallet.public_key().serialize().to_vec(),
+            policy: PolicySnapshot::capture(wallet.policy()),
+            records: store.history().into_iter().cloned().collect(),
+            exported_at: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0),
+        }
+    }
+}
+
+/// On-the-wire framing of a sealed bundle.
+#[derive(Serialize, Deserialize)]
+struct SealedBundle {
+    magic: [u8; 8],
+    version: u32,
+    kdf: KdfParams,
+    salt: [u8; 16],
+    nonce: [u8; 12],
+    ciphertext: Vec<u8>,
+}
+
+/// Argon2 cost for bundle keys. The pairing code carries 80 bits of
+/// real entropy, so the security-floor parameters suffice and pairing
+/// stays snappy on both devices.
+fn bundle_kdf() -> KdfParams {
+    KdfParams {
+        memory_kib: MIN_KDF_MEMORY_KIB,
+        iterations: 3,
+        parallelism: 1,
+    }
+}
+
+/// Encrypts `bundle` under `code`, producing the bytes to hand to the
+/// other device (file, QR, whatever carries them).
+pub fn seal_bundle(bundle: &StateBundle, code: &str) -> Result<Vec<u8>, WalletError> {
+    let kdf = bundle_kdf();
+    let mut salt = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let mut nonce = [0u8; 12];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    let key = derive_key(&normalize_code(code), &salt, &kdf)?;
+    let plaintext = bincode::serialize(bundle)
+        .map_err(|e| WalletError::new(WalletErrorKind::Corrupt, format!("bundle serialization failed: {}", e)))?;
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext.as_ref())
+        .map_err(|_| WalletError::new(WalletErrorKind::Corrupt, "bundle encryption failed"))?;
+    let sealed = SealedBundle {
+        magic: BUNDLE_MAGIC,
+        version: BUNDLE_VERSION,
+        kdf,
+        salt,
+        nonce,
+        ciphertext,
+    };
+    bincode::serialize(&sealed)
+        .map_err(|e| WalletError::new(WalletErrorKind::Corrupt, format!("bundle serialization failed: {}", e)))
+}
+
+/// Decrypts a sealed bundle with the pairing code read off the
+/// exporting device.
+pub fn open_bundle(bytes: &[u8], code: &str) -> Result<StateBundle, WalletError> {
+    let sealed: SealedBundle = bincode::deserialize(bytes)
+        .map_err(|e| WalletError::with_source(WalletErrorKind::Corrupt, "not a wallet sync bundle", e))?;
+    if sealed.magic != BUNDLE_MAGIC {
+        return Err(WalletError::new(
+            WalletErrorKind::Corrupt,
+            "not a wallet sync bundle",
+        ));
+    }
+    if sealed.version != BUNDLE_VERSION {
+        return Err(WalletError::new(
+            WalletErrorKind::InvalidRequest,
+            format!("unsupported bundle version {}", sealed.version),
+        ));
+    }
+    let key = derive_key(&normalize_code(code), &sealed.salt, &sealed.kdf)?;
+    let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(&sealed.nonce), sealed.ciphertext.as_ref())
+        .map_err(|_| WalletError::new(WalletErrorKind::BadPassword, "wrong pairing code"))?;
+    bincode::deserialize(&plaintext)
+        .map_err(|e| WalletError::with_source(WalletErrorKind::Corrupt, "corrupt bundle contents", e))
+}
@@ -0,0 +1,211 @@
+//! Persistent per-peer statistics and network composition reports.
+//!
+//! `getpeerinfo` shows the connections of the moment; this book keeps
+//! the longer story. Every peer that completes a handshake gets a
+//! record keyed by IP — sessions, cumulative uptime, and how many
+//! valid and invalid blocks and transactions it relayed — persisted in
+//! the data directory so restarts do not reset the ledger. On top of
+//! the raw records sit the `getpeerstats` RPC (per-peer historical
+//! averages) and a network report grouping the population by user
+//! agent, the closest thing a node has to a crawler's view of what
+//! software the network runs.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::node::Node;
+
+/// How often the in-memory counters are written back to disk; relay
+/// counters tick far too often to persist individually.
+pub const STATS_FLUSH_SECS: u64 = 300;
+
+/// Lifetime record for one peer IP.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PeerRecord {
+    /// User agent from the most recent handshake.
+    pub user_agent: String,
+    /// Protocol version from the most recent handshake.
+    pub version: u32,
+    /// Completed handshakes.
+    pub sessions: u64,
+    /// Seconds of connection time across closed sessions.
+    pub total_uptime_secs: u64,
+    pub blocks_valid: u64,
+    pub blocks_invalid: u64,
+    pub txs_valid: u64,
+    pub txs_invalid: u64,
+    pub first_seen: u64,
+    pub last_seen: u64,
+}
+
+impl PeerRecord {
+    /// Mean length of this peer's closed sessions, in seconds.
+    pub fn average_session_secs(&self) -> u64 {
+        self.total_uptime_secs.checked_div(self.sessions).unwrap_or(0)
+    }
+
+    /// Share of this peer's relayed blocks and transactions that
+    /// validated, in [0, 1]; `None` until it has relayed anything.
+    pub fn valid_ratio(&self) -> Option<f64> {
+        let valid = self.blocks_valid + self.txs_valid;
+        let total = valid + self.blocks_invalid + self.txs_invalid;
+        if total == 0 {
+            return None;
+        }
+        Some(valid as f64 / total as f64)
+    }
+}
+
+/// One row of the network composition report: every peer IP sharing a
+/// user agent, with their pooled counters.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentReport {
+    pub user_agent: String,
+    /// Distinct protocol versions seen under this agent, ascending.
+    pub versions: Vec<u32>,
+    pub peers: u64,
+    pub sessions: u64,
+    pub blocks_valid: u64,
+    pub blocks_invalid: u64,
+    pub txs_valid: u64,
+    pub txs_invalid: u64,
+}
+
+/// The stats book itself: records keyed by IP, persisted as one
+/// bincode blob like the share ledger.
+#[derive(Default)]
+pub struct PeerStatsBook {
+    records: HashMap<String, PeerRecord>,
+    path: Option<PathBuf>,
+}
+
+impl PeerStatsBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opens a book persisted at `path`; a missing or unreadable file
+    /// just means a fresh book that will save there.
+    pub fn with_path<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let records: HashMap<String, PeerRecord> = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| bincode::deserialize(&bytes).ok())
+            .unwrap_or_default();
+        PeerStatsBook {
+            records,
+            path: Some(path),
+        }
+    }
+
+    /// Writes the book back to its file, if it has one.
+    pub fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let bytes = bincode::serialize(&self.records).expect("stats serialization cannot fail");
+        if let Err(e) = std::fs::write(path, bytes) {
+            log::warn!("failed to write peer stats {}: {}", path.display(), e);
+        }
+    }
+
+    fn entry(&mut self, ip: IpAddr) -> &mut PeerRecord {
+        self.records.entry(ip.to_string()).or_default()
+    }
+
+    /// Logs a completed handshake, refreshing the agent and version.
+    pub fn record_connect(&mut self, ip: IpAddr, user_agent: &str, version: u32, now: u64) {
+        let record = self.entry(ip);
+        record.user_agent = user_agent.to_string();
+        record.version = version;
+        record.sessions += 1;
+        if record.first_seen == 0 {
+            record.first_seen = now;
+        }
+        record.last_seen = now;
+    }
+
+    /// Closes a session, folding its duration into the uptime total.
+    pub fn record_disconnect(&mut self, ip: IpAddr, session_secs: u64, now: u64) {
+        let record = self.entry(ip);
+        record.total_uptime_secs += session_secs;
+        record.last_seen = now;
+        self.save();
+    }
+
+    pub fn record_block(&mut self, ip: IpAddr, valid: bool) {
+        let record = self.entry(ip);
+        if valid {
+            record.blocks_valid += 1;
+        } else {
+            record.blocks_invalid += 1;
+        }
+    }
+
+    pub fn record_tx(&mut self, ip: IpAddr, valid: bool) {
+        let record = self.entry(ip);
+        if valid {
+            record.txs_valid += 1;
+        } else {
+            record.txs_invalid += 1;
+        }
+    }
+
+    /// All records, keyed by IP string.
+    pub fn records(&self) -> &HashMap<String, PeerRecord> {
+        &self.records
+    }
+
+    /// Groups the book by user agent, busiest group first — a summary
+    /// of what software the network around this node runs.
+    pub fn network_report(&self) -> Vec<AgentReport> {
+        let mut groups: HashMap<&str, AgentReport> = HashMap::new();
+        for record in self.records.values() {
+            let group = groups
+                .entry(record.user_agent.as_str())
+                .or_insert_with(|| AgentReport {
+                    user_agent: record.user_agent.clone(),
+                    versions: Vec::new(),
+                    peers: 0,
+                    sessions: 0,
+                    blocks_valid: 0,
+                    blocks_invalid: 0,
+                    txs_valid: 0,
+                    txs_invalid: 0,
+                });
+            if !group.versions.contains(&record.version) {
+                group.versions.push(record.version);
+            }
+            group.peers += 1;
+            group.sessions += record.sessions;
+            group.blocks_valid += record.blocks_valid;
+            group.blocks_invalid += record.blocks_invalid;
+            group.txs_valid += record.txs_valid;
+            group.txs_invalid += record.txs_invalid;
+        }
+        let mut out: Vec<AgentReport> = groups.into_values().collect();
+        for group in &mut out {
+            group.versions.sort_unstable();
+        }
+        out.sort_by(|a, b| b.peers.cmp(&a.peers).then(a.user_agent.cmp(&b.user_agent)));
+        out
+    }
+}
+
+/// Periodically writes the relay counters to disk; connects and
+/// disconnects save immediately, everything else rides this.
+pub async fn flush_loop(node: Arc<Node>) {
+    let mut ticker = tokio::time::interval(std::time::Duration::from_secs(STATS_FLUSH_SECS));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        node.peerstats
+            .lock()
+            .expect("peerstats lock poisoned")
+            .save();
+    }
+}
@@ -0,0 +1,170 @@
+//! Proof-of-authority block signing for private deployments.
+//!
+//! Consortium and test chains rarely want to burn electricity: with
+//! `validators` configured in the chain parameters, blocks carry an
+//! ECDSA signature from the validator whose turn it is instead of
+//! proof-of-work. Turns rotate round-robin by height, so a stalled
+//! validator delays only its own slots. The signature rides in the
+//! coinbase's otherwise-unused `signature`/`public_key` fields — the
+//! wire format is unchanged, and a PoW node that somehow receives a
+//! PoA block still rejects it (no work).
+//!
+//! The validator set itself lives on chain: a transaction paying
+//! [`VALIDATOR_ROTATION_ADDRESS`] with a [`ValidatorRotation`] payload
+//! in its data field, sent from a current validator's address, replaces
+//! the set from the next block on. The blockchain module persists
+//! rotation history so reorgs rewind the set along with everything
+//! else.
+
+use secp256k1::ecdsa::Signature;
+use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+use serde::{Deserialize, Serialize};
+
+use crate::hash;
+use crate::rejection::RejectionReason;
+use crate::types::{Address, Block, BlockHeader, Hash256, Transaction};
+
+/// Domain separation tag for validator block signatures.
+pub const POA_SIGNING_DOMAIN_TAG: &[u8] = b"pali-coin/poa-block/v1";
+
+/// Burn address validator-set rotations pay. Chosen as printable ASCII
+/// so it is recognizable in explorers; nobody holds a key for it.
+pub const VALIDATOR_ROTATION_ADDRESS: Address = *b"pali-poa-validators!";
+
+/// On-chain payload replacing the validator set, carried in the data
+/// field of a transaction to [`VALIDATOR_ROTATION_ADDRESS`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorRotation {
+    /// The complete new set, hex-encoded compressed secp256k1 keys.
+    /// Full replacement rather than a delta: what you sign is what
+    /// runs, with no dependence on the set it applied against.
+    pub validators: Vec<String>,
+}
+
+/// Canonical signing payload: the block's position in the chain plus a
+/// merkle root computed with the coinbase signature fields cleared.
+/// The signature cannot commit to a merkle root that contains itself,
+/// so validators sign the block as it looked before sealing — which
+/// still pins every transaction and the payout.
+fn signing_payload(header: &BlockHeader, transactions: &[Transaction]) -> Vec<u8> {
+    let mut hashes: Vec<Hash256> = Vec::with_capacity(transactions.len());
+    for (i, tx) in transactions.iter().enumerate() {
+        if i == 0 {
+            let mut unsealed = tx.clone();
+            unsealed.signature = Vec::new();
+            unsealed.public_key = Vec::new();
+            hashes.push(unsealed.hash());
+        } else {
+            hashes.push(tx.hash());
+        }
+    }
+    let mut out = Vec::with_capacity(80);
+    out.extend_from_slice(&header.prev_hash);
+    out.extend_from_slice(&header.height.to_be_bytes());
+    out.extend_from_slice(&header.timestamp.to_be_bytes());
+    out.extend_from_slice(&hash::merkle_root(&hashes));
+    out
+}
+
+/// The digest a validator signs (tagged like transaction signing
+/// hashes: `SHA256(tag_hash ‖ tag_hash ‖ payload)`).
+pub fn signing_hash(header: &BlockHeader, transactions: &[Transaction]) -> Hash256 {
+    let tag_hash = hash::sha256(POA_SIGNING_DOMAIN_TAG);
+    let payload = signing_payload(header, transactions);
+    let mut input = Vec::with_capacity(64 + payload.len());
+    input.extend_from_slice(&tag_hash);
+    input.extend_from_slice(&tag_hash);
+    input.extend_from_slice(&payload);
+    hash::sha256(&input)
+}
+
+/// The validator whose turn it is to seal `height`, by round-robin
+/// over the current set. `None` only when the set is empty.
+pub fn scheduled_validator(validators: &[String], height: u64) -> Option<&str> {
+    if validators.is_empty() {
+        return None;
+    }
+    validators
+        .get((height % validators.len() as u64) as usize)
+        .map(String::as_str)
+}
+
+/// Seals a block with the validator's signature: fills the coinbase
+/// signature fields and recomputes the merkle root they perturb. Call
+/// once the transaction list is final.
+pub fn sign_block(block: &mut Block, key: &SecretKey) {
+    let secp = Secp256k1::signing_only();
+    let digest = signing_hash(&block.header, &block.transactions);
+    let sig = secp.sign_ecdsa(&Message::from_digest(digest), key);
+    let coinbase = &mut block.transactions[0];
+    coinbase.public_key = PublicKey::from_secret_key(&secp, key).serialize().to_vec();
+    coinbase.signature = sig.serialize_compact().to_vec();
+    let hashes: Vec<Hash256> = block.transactions.iter().map(|tx| tx.hash()).collect();
+    block.header.merkle_root = hash::merkle_root(&hashes);
+}
+
+/// Consensus check for a PoA block: the coinbase must carry the
+/// scheduled validator's key and a valid signature under it. The
+/// caller has already verified the merkle root and coinbase structure.
+pub fn verify_block(block: &Block, validators: &[String]) -> Result<(), RejectionReason> {
+    let coinbase = block.coinbase().ok_or(RejectionReason::MissingCoinbase)?;
+    let expected = scheduled_validator(validators, block.header.height)
+        .ok_or(RejectionReason::BadValidatorSignature)?;
+    if hex::encode(&coinbase.public_key) != expected {
+        return Err(RejectionReason::NotValidatorsTurn);
+    }
+    let key = PublicKey::from_slice(&coinbase.public_key)
+        .map_err(|_| RejectionReason::BadValidatorSignature)?;
+    let sig = Signature::from_compact(&coinbase.signature)
+        .map_err(|_| RejectionReason::BadValidatorSignature)?;
+    let digest = signing_hash(&block.header, &block.transactions);
+    Secp256k1::verification_only()
+        .verify_ecdsa(&Message::from_digest(digest), &sig, &key)
+        .map_err(|_| RejectionReason::BadValidatorSignature)
+}
+
+/// Encodes a rotation payload for a transaction's data field.
+pub fn rotation_data(validators: &[String]) -> Vec<u8> {
+    bincode::serialize(&ValidatorRotation {
+        validators: validators.to_vec(),
+    })
+    .expect("rotation serialization cannot fail")
+}
+
+/// Decodes a rotation payload from a transaction to the rotation
+/// address, without judging it.
+pub fn decode_rotation(tx: &Transaction) -> Option<ValidatorRotation> {
+    if tx.to != VALIDATOR_ROTATION_ADDRESS {
+        return None;
+    }
+    bincode::deserialize(&tx.data).ok()
+}
+
+/// Admission check for a rotation transaction against the current
+/// validator set: the payload must decode to a non-empty set of
+/// well-formed keys, and the sender must be a current validator —
+/// outsiders cannot vote themselves in.
+pub fn check_rotation(
+    tx: &Transaction,
+    validators: &[String],
+) -> Result<ValidatorRotation, RejectionReason> {
+    let rotation = decode_rotation(tx).ok_or(RejectionReason::BadValidatorRotation)?;
+    if rotation.validators.is_empty() {
+        return Err(RejectionReason::BadValidatorRotation);
+    }
+    for key in &rotation.validators {
+        hex::decode(key)
+            .ok()
+            .and_then(|bytes| PublicKey::from_slice(&bytes).ok())
+            .ok_or(RejectionReason::BadValidatorRotation)?;
+    }
+    let authorized = validators.iter().any(|key| {
+        hex::decode(key)
+            .ok()
+            .is_some_and(|bytes| hash::pubkey_to_address(&bytes) == tx.from)
+    });
+    if !authorized {
+        return Err(RejectionReason::BadValidatorRotation);
+    }
+    Ok(rotation)
+}
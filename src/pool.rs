@@ -0,0 +1,211 @@
+//! Pool share accounting for the push-mining endpoint.
+//!
+//! A pool operator runs the `/work` endpoint for many miners, but full
+//! blocks are rare; to measure each worker's contribution the node also
+//! accepts *shares* — submissions meeting a relaxed target — and logs
+//! them, difficulty-weighted, in a ledger that survives restarts. The
+//! PPLNS and proportional calculators turn the ledger plus a block
+//! reward into a payout table, so an operator can settle miners from
+//! node data alone.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::math;
+use crate::types::Address;
+
+/// How much easier a share is than a block: the share target is the
+/// block target shifted up by this many bits.
+pub const SHARE_TARGET_SHIFT: u32 = 16;
+
+/// Most shares the ledger retains; the oldest fall off once every
+/// payout scheme has had ample history to settle from.
+pub const SHARES_KEPT: usize = 100_000;
+
+/// Shares the PPLNS calculator looks back over when the caller does
+/// not say otherwise.
+pub const PPLNS_DEFAULT_WINDOW: usize = 4096;
+
+/// Compact target a share must meet for a job mined at block `bits`.
+/// Raising the compact exponent by two multiplies the target by 2^16,
+/// clamped so a share is never harder than [`math::MAX_BITS`] allows.
+pub fn share_bits(bits: u32) -> u32 {
+    let eased = bits.saturating_add((SHARE_TARGET_SHIFT / 8) << 24);
+    let target = math::bits_to_target(eased);
+    // A zero target means the exponent left the representable range.
+    if target == [0u8; 32] || target > math::bits_to_target(math::MAX_BITS) {
+        math::MAX_BITS
+    } else {
+        eased
+    }
+}
+
+/// One accepted share.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Share {
+    /// The payout address the miner subscribed with.
+    pub worker: Address,
+    pub timestamp: u64,
+    /// Work the share proves — the share target's [`math::block_work`]
+    /// — so shares from different difficulty epochs weigh fairly.
+    pub weight: u128,
+    /// Height of the job the share was mined against.
+    pub height: u64,
+}
+
+/// One line of a settlement: pay `amount` to `worker`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Payout {
+    pub worker: Address,
+    pub amount: u64,
+}
+
+/// Everything worth surviving a restart, in one bincode blob.
+#[derive(Serialize, Deserialize, Default)]
+struct PersistedLedger {
+    shares: Vec<Share>,
+    round_start: usize,
+    blocks_found: u64,
+}
+
+/// The ledger itself: accepted shares in arrival order, plus where the
+/// current round (shares since the pool last found a block) begins.
+#[derive(Default)]
+pub struct ShareLedger {
+    shares: Vec<Share>,
+    round_start: usize,
+    blocks_found: u64,
+    path: Option<PathBuf>,
+}
+
+impl ShareLedger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Opens a ledger persisted at `path`; a missing or unreadable
+    /// file just means a fresh ledger that will save there.
+    pub fn with_path<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let state: PersistedLedger = std::fs::read(&path)
+            .ok()
+            .and_then(|bytes| bincode::deserialize(&bytes).ok())
+            .unwrap_or_default();
+        ShareLedger {
+            round_start: state.round_start.min(state.shares.len()),
+            shares: state.shares,
+            blocks_found: state.blocks_found,
+            path: Some(path),
+        }
+    }
+
+    fn save(&self) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        let state = PersistedLedger {
+            shares: self.shares.clone(),
+            round_start: self.round_start,
+            blocks_found: self.blocks_found,
+        };
+        let bytes = bincode::serialize(&state).expect("ledger serialization cannot fail");
+        if let Err(e) = std::fs::write(path, bytes) {
+            log::warn!("failed to write share ledger {}: {}", path.display(), e);
+        }
+    }
+
+    /// Logs an accepted share, evicting the oldest past [`SHARES_KEPT`].
+    pub fn record_share(&mut self, worker: Address, weight: u128, height: u64, timestamp: u64) {
+        self.shares.push(Share {
+            worker,
+            timestamp,
+            weight,
+            height,
+        });
+        if self.shares.len() > SHARES_KEPT {
+            let excess = self.shares.len() - SHARES_KEPT;
+            self.shares.drain(..excess);
+            self.round_start = self.round_start.saturating_sub(excess);
+        }
+        self.save();
+    }
+
+    /// Marks a found block: the current round settles and a new one
+    /// starts with the next share.
+    pub fn record_block(&mut self) {
+        self.blocks_found += 1;
+        self.round_start = self.shares.len();
+        self.save();
+    }
+
+    pub fn shares(&self) -> &[Share] {
+        &self.shares
+    }
+
+    /// Shares of the round in progress.
+    pub fn round_shares(&self) -> &[Share] {
+        &self.shares[self.round_start..]
+    }
+
+    pub fn blocks_found(&self) -> u64 {
+        self.blocks_found
+    }
+
+    /// Distinct workers with at least one share on record.
+    pub fn workers(&self) -> usize {
+        let mut seen: Vec<Address> = self.shares.iter().map(|s| s.worker).collect();
+        seen.sort_unstable();
+        seen.dedup();
+        seen.len()
+    }
+
+    /// Proportional settlement: `reward` split across the current
+    /// round's shares by weight. Call after [`Self::record_block`] has
+    /// NOT yet rolled the round, i.e. with the round the block closed.
+    pub fn proportional_payouts(&self, reward: u64) -> Vec<Payout> {
+        split(reward, self.round_shares())
+    }
+
+    /// PPLNS settlement: `reward` split across the last `window`
+    /// shares by weight, ignoring round boundaries so pool-hoppers
+    /// gain nothing from leaving mid-round.
+    pub fn pplns_payouts(&self, reward: u64, window: usize) -> Vec<Payout> {
+        let start = self.shares.len().saturating_sub(window);
+        split(reward, &self.shares[start..])
+    }
+}
+
+/// Splits `reward` across `shares` proportional to weight. Integer
+/// truncation leaves at most one base unit per worker with the pool.
+/// Output is sorted by worker so settlements diff cleanly.
+fn split(reward: u64, shares: &[Share]) -> Vec<Payout> {
+    let mut weights: HashMap<Address, u128> = HashMap::new();
+    let mut total: u128 = 0;
+    for share in shares {
+        *weights.entry(share.worker).or_default() += share.weight;
+        total += share.weight;
+    }
+    if total == 0 {
+        return Vec::new();
+    }
+    let mut payouts: Vec<Payout> = weights
+        .into_iter()
+        .map(|(worker, weight)| Payout {
+            worker,
+            amount: (reward as u128 * weight / total) as u64,
+        })
+        .collect();
+    payouts.sort_unstable_by_key(|p| p.worker);
+    payouts
+}
+
+/// Renders payouts as `address,amount` CSV for settlement tooling.
+pub fn payouts_csv(payouts: &[Payout]) -> String {
+    let mut out = String::from("address,amount\n");
+    for payout in payouts {
+        out.push_str(&format!("{},{}\n", hex::encode(payout.worker), payout.amount));
+    }
+    out
+}
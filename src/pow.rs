@@ -0,0 +1,154 @@
+//! Proof-of-work algorithm abstraction.
+//!
+//! Consensus code historically assumed double-SHA256 everywhere a
+//! header's work was hashed, checked or summed. This module puts that
+//! assumption behind [`PowAlgorithm`] so a chain can select a
+//! different algorithm in its parameters — in particular a memory-hard
+//! one, where commodity hardware stays competitive with ASICs. The
+//! algorithm owns all three PoW judgments: the hash itself, whether a
+//! header meets its target, and how much work a block at a given
+//! target contributes to chain selection, since a memory-hard hash
+//! attempt represents far more effort than one SHA256 pass.
+//!
+//! Mainnet is and stays double-SHA256; the block hash used for ids and
+//! linking is double-SHA256 under every algorithm — only the proof
+//! itself varies.
+
+use serde::{Deserialize, Serialize};
+
+use crate::math;
+use crate::types::{BlockHeader, Hash256};
+
+/// Argon2id memory per PoW hash, in KiB. Small enough that validating
+/// a block is cheap, large enough to defeat on-die SRAM.
+pub const ARGON2_POW_MEMORY_KIB: u32 = 8 * 1024;
+
+/// Argon2id passes per PoW hash.
+pub const ARGON2_POW_ITERATIONS: u32 = 1;
+
+/// How many double-SHA256 attempts one Argon2id attempt is worth in
+/// chain-work accounting, calibrated from their relative throughput on
+/// commodity hardware. Only the ratio matters, and only within one
+/// chain — work is never compared across algorithms.
+pub const ARGON2_WORK_FACTOR: u128 = 1 << 16;
+
+/// One proof-of-work scheme: hashing, target checking and work
+/// accounting in a single place.
+pub trait PowAlgorithm: Send + Sync {
+    /// Identifier used in logs and RPC output.
+    fn name(&self) -> &'static str;
+
+    /// The digest a header's proof of work is judged by. Not
+    /// necessarily the block hash: ids and prev-hash links stay
+    /// double-SHA256 regardless of algorithm.
+    fn pow_hash(&self, header: &BlockHeader) -> Hash256;
+
+    /// Whether the header carries a valid proof at its target.
+    fn check(&self, header: &BlockHeader) -> bool {
+        math::hash_meets_target(&self.pow_hash(header), header.bits)
+    }
+
+    /// Work a block at `bits` adds to the chain, in this algorithm's
+    /// own units.
+    fn block_work(&self, bits: u32) -> u128 {
+        math::block_work(bits)
+    }
+}
+
+/// The original scheme: the double-SHA256 block hash is the proof.
+pub struct DoubleSha256;
+
+impl PowAlgorithm for DoubleSha256 {
+    fn name(&self) -> &'static str {
+        "double-sha256"
+    }
+
+    fn pow_hash(&self, header: &BlockHeader) -> Hash256 {
+        header.hash()
+    }
+}
+
+/// Memory-hard scheme for ASIC-resistant private networks: Argon2id
+/// over the serialized header, salted with the header's own
+/// double-SHA256 hash.
+pub struct Argon2Pow;
+
+impl PowAlgorithm for Argon2Pow {
+    fn name(&self) -> &'static str {
+        "argon2id"
+    }
+
+    fn pow_hash(&self, header: &BlockHeader) -> Hash256 {
+        let bytes = bincode::serialize(header).expect("header serialization cannot fail");
+        let params = argon2::Params::new(
+            ARGON2_POW_MEMORY_KIB,
+            ARGON2_POW_ITERATIONS,
+            1,
+            Some(32),
+        )
+        .expect("fixed parameters are valid");
+        let argon = argon2::Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+        let mut out = [0u8; 32];
+        argon
+            .hash_password_into(&bytes, &header.hash(), &mut out)
+            .expect("fixed-size hashing cannot fail");
+        out
+    }
+
+    fn block_work(&self, bits: u32) -> u128 {
+        math::block_work(bits).saturating_mul(ARGON2_WORK_FACTOR)
+    }
+}
+
+/// Nonce interval at which [`TestPow`] accepts a header.
+#[cfg(any(test, feature = "test-pow"))]
+pub const TEST_POW_INTERVAL: u64 = 8;
+
+/// Deterministic stub for pipeline tests: a header "solves" exactly
+/// when its nonce is a multiple of [`TEST_POW_INTERVAL`], regardless of
+/// target, and every block contributes one unit of work. Grinding from
+/// any starting nonce therefore succeeds within eight attempts, so a
+/// full template → worker → accept → relay round trip runs in
+/// milliseconds. Deliberately absent from [`PowAlgorithmId`]: it
+/// cannot be selected by chain parameters and the `test-pow` feature
+/// keeps it out of release consensus entirely.
+#[cfg(any(test, feature = "test-pow"))]
+pub struct TestPow;
+
+#[cfg(any(test, feature = "test-pow"))]
+impl PowAlgorithm for TestPow {
+    fn name(&self) -> &'static str {
+        "test-stub"
+    }
+
+    fn pow_hash(&self, header: &BlockHeader) -> Hash256 {
+        header.hash()
+    }
+
+    fn check(&self, header: &BlockHeader) -> bool {
+        header.nonce.is_multiple_of(TEST_POW_INTERVAL)
+    }
+
+    fn block_work(&self, _bits: u32) -> u128 {
+        1
+    }
+}
+
+/// Which algorithm a chain runs under, as stored in its parameters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PowAlgorithmId {
+    #[default]
+    DoubleSha256,
+    Argon2id,
+}
+
+impl PowAlgorithmId {
+    /// The algorithm implementation behind the identifier.
+    pub fn algorithm(&self) -> &'static dyn PowAlgorithm {
+        match self {
+            PowAlgorithmId::DoubleSha256 => &DoubleSha256,
+            PowAlgorithmId::Argon2id => &Argon2Pow,
+        }
+    }
+}
@@ -0,0 +1,204 @@
+//! Startup environment checks.
+//!
+//! A node that dies an hour in because the disk filled, or corrupts
+//! its database because two instances shared a data directory, is far
+//! more expensive than refusing to start. Every check here fails fast
+//! with the command that fixes it.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Minimum free space in the data directory's filesystem.
+pub const MIN_DISK_HEADROOM: u64 = 1024 * 1024 * 1024;
+
+/// Minimum open-file limit; RocksDB alone keeps hundreds of SSTs open.
+pub const MIN_FILE_DESCRIPTORS: u64 = 1024;
+
+/// Largest tolerated offset from NTP before refusing to start. The
+/// consensus timestamp rule allows two hours; a clock off by more than
+/// this is drifting toward producing or rejecting bad blocks.
+pub const MAX_CLOCK_SKEW: Duration = Duration::from_secs(60);
+
+/// How long the SNTP probe waits before the check is skipped.
+const NTP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// On-disk database layout version; bump alongside any column-family
+/// or key-encoding migration.
+pub const DB_FORMAT_VERSION: u32 = 1;
+
+/// Exclusive hold on a data directory, released on drop. Keep the
+/// returned guard alive for the life of the process.
+pub struct DirLock {
+    path: PathBuf,
+}
+
+impl DirLock {
+    /// Takes the data-dir lock, refusing when another live process
+    /// holds it. A lock left behind by a dead process is reclaimed.
+    pub fn acquire(data_dir: &Path) -> Result<Self, String> {
+        std::fs::create_dir_all(data_dir)
+            .map_err(|e| format!("cannot create data directory {}: {}", data_dir.display(), e))?;
+        let path = data_dir.join("pali-node.lock");
+        if let Ok(contents) = std::fs::read_to_string(&path) {
+            if let Ok(pid) = contents.trim().parse::<u32>() {
+                if pid != std::process::id() && process_alive(pid) {
+                    return Err(format!(
+                        "data directory {} is in use by pid {} — stop that node or use a \
+                         different --datadir",
+                        data_dir.display(),
+                        pid
+                    ));
+                }
+            }
+            log::warn!("reclaiming stale lock file {}", path.display());
+        }
+        std::fs::write(&path, format!("{}\n", std::process::id()))
+            .map_err(|e| format!("cannot write lock file {}: {}", path.display(), e))?;
+        Ok(DirLock { path })
+    }
+}
+
+impl Drop for DirLock {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(unix)]
+fn process_alive(pid: u32) -> bool {
+    // Signal 0 performs the permission and existence checks without
+    // delivering anything.
+    unsafe { libc::kill(pid as libc::pid_t, 0) == 0 }
+}
+
+#[cfg(not(unix))]
+fn process_alive(_pid: u32) -> bool {
+    // Without a portable liveness probe, assume the holder is alive;
+    // the operator can delete the lock file after verifying.
+    true
+}
+
+/// Runs every preflight check against `data_dir` and takes its lock.
+/// NTP unreachability only warns; everything else is fatal.
+pub fn run_checks(data_dir: &Path) -> Result<DirLock, String> {
+    let lock = DirLock::acquire(data_dir)?;
+    check_disk_headroom(data_dir)?;
+    check_file_descriptors()?;
+    check_db_format(data_dir)?;
+    match ntp_offset() {
+        Some(offset) if offset > MAX_CLOCK_SKEW => {
+            return Err(format!(
+                "system clock is {}s away from NTP (limit {}s) — enable NTP synchronization \
+                 before starting",
+                offset.as_secs(),
+                MAX_CLOCK_SKEW.as_secs()
+            ));
+        }
+        Some(_) => {}
+        None => log::warn!("NTP unreachable, skipping clock-skew check"),
+    }
+    Ok(lock)
+}
+
+#[cfg(unix)]
+fn check_disk_headroom(data_dir: &Path) -> Result<(), String> {
+    use std::os::unix::ffi::OsStrExt;
+    let c_path = std::ffi::CString::new(data_dir.as_os_str().as_bytes())
+        .map_err(|_| "data directory path contains a NUL byte".to_string())?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        log::warn!("statvfs failed, skipping disk-headroom check");
+        return Ok(());
+    }
+    let available = stat.f_bavail as u64 * stat.f_frsize as u64;
+    if available < MIN_DISK_HEADROOM {
+        return Err(format!(
+            "only {} MB free under {} — the node needs at least {} MB of headroom; free space \
+             or move --datadir",
+            available / (1024 * 1024),
+            data_dir.display(),
+            MIN_DISK_HEADROOM / (1024 * 1024)
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_disk_headroom(_data_dir: &Path) -> Result<(), String> {
+    Ok(())
+}
+
+#[cfg(unix)]
+// rlim_t is not u64 on every libc target.
+#[allow(clippy::unnecessary_cast)]
+fn check_file_descriptors() -> Result<(), String> {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } != 0 {
+        log::warn!("getrlimit failed, skipping file-descriptor check");
+        return Ok(());
+    }
+    if (limit.rlim_cur as u64) < MIN_FILE_DESCRIPTORS {
+        return Err(format!(
+            "open-file limit is {} but the node needs at least {} — raise it with \
+             `ulimit -n {}` (hard limit {})",
+            limit.rlim_cur, MIN_FILE_DESCRIPTORS, MIN_FILE_DESCRIPTORS, limit.rlim_max
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn check_file_descriptors() -> Result<(), String> {
+    Ok(())
+}
+
+/// Refuses to open a database written by an incompatible layout. The
+/// marker is created on first run so existing directories adopt it.
+fn check_db_format(data_dir: &Path) -> Result<(), String> {
+    let path = data_dir.join("db_format");
+    match std::fs::read_to_string(&path) {
+        Ok(contents) => {
+            let found: u32 = contents
+                .trim()
+                .parse()
+                .map_err(|_| format!("corrupt database format marker {}", path.display()))?;
+            if found != DB_FORMAT_VERSION {
+                return Err(format!(
+                    "database format {} in {} but this build expects {} — run the matching \
+                     release or resync from a backup",
+                    found,
+                    data_dir.display(),
+                    DB_FORMAT_VERSION
+                ));
+            }
+            Ok(())
+        }
+        Err(_) => std::fs::write(&path, format!("{}\n", DB_FORMAT_VERSION))
+            .map_err(|e| format!("cannot write {}: {}", path.display(), e)),
+    }
+}
+
+/// Offset between the local clock and an SNTP server, if reachable.
+fn ntp_offset() -> Option<Duration> {
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.set_read_timeout(Some(NTP_TIMEOUT)).ok()?;
+    socket.connect("pool.ntp.org:123").ok()?;
+    // SNTP v4 client request: only the first byte (LI/VN/Mode) matters.
+    let mut packet = [0u8; 48];
+    packet[0] = 0b00_100_011;
+    socket.send(&packet).ok()?;
+    let mut response = [0u8; 48];
+    let n = socket.recv(&mut response).ok()?;
+    if n < 48 {
+        return None;
+    }
+    // Transmit timestamp: seconds since 1900-01-01 at offset 40.
+    let ntp_seconds = u32::from_be_bytes(response[40..44].try_into().ok()?) as u64;
+    const NTP_UNIX_EPOCH_DELTA: u64 = 2_208_988_800;
+    let server_unix = ntp_seconds.checked_sub(NTP_UNIX_EPOCH_DELTA)?;
+    let local_unix = SystemTime::now().duration_since(UNIX_EPOCH).ok()?.as_secs();
+    Some(Duration::from_secs(server_unix.abs_diff(local_unix)))
+}
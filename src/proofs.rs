@@ -0,0 +1,230 @@
+//! Trust-minimized balance proofs for light wallets.
+//!
+//! A full node answers `GetUtxoProof` with the address's UTXOs, each
+//! accompanied by its funding transaction, the merkle branch linking
+//! that transaction to a block, and the block's header. A wallet that
+//! synced headers independently can then check every claimed coin
+//! against its own view of the chain instead of trusting the server's
+//! balance answer.
+//!
+//! Two limits are inherent to this scheme and spelled out in
+//! [`VerifiedBalance`]: a proof shows a coin was *created*, not that it
+//! is still unspent, and the amount of a change output (output index 1)
+//! is not derivable from the funding transaction alone, so it stays a
+//! server claim.
+
+use serde::{Deserialize, Serialize};
+
+use crate::blockchain::Blockchain;
+use crate::hash::{self, MerkleStep};
+use crate::types::{Address, BlockHeader, Hash256, OutPoint, Transaction, UtxoEntry};
+
+/// One UTXO with everything needed to tie it to a block header.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UtxoProofEntry {
+    pub outpoint: OutPoint,
+    /// The server's claim about the output; verification re-derives
+    /// what it can from `tx`.
+    pub entry: UtxoEntry,
+    /// The transaction that created the output.
+    pub tx: Transaction,
+    /// Merkle branch from `tx` to `header.merkle_root`.
+    pub branch: Vec<MerkleStep>,
+    pub header: BlockHeader,
+}
+
+/// Everything a full node knows about one address's coins, provable
+/// piece by piece against the header chain.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AddressProof {
+    pub address: Address,
+    pub utxos: Vec<UtxoProofEntry>,
+}
+
+/// Outcome of verifying an [`AddressProof`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VerifiedBalance {
+    /// Units whose amounts are proven by an included transaction:
+    /// direct payments and coinbase outputs.
+    pub proven: u64,
+    /// Units in change outputs: their existence is proven, their
+    /// amounts are still the server's word.
+    pub claimed_change: u64,
+}
+
+/// Builds the proof a node sends back for `GetUtxoProof`.
+pub fn build_address_proof(
+    chain: &Blockchain,
+    address: &Address,
+) -> Result<AddressProof, String> {
+    let mut utxos = Vec::new();
+    for (outpoint, entry) in chain.get_utxos_for_address(address)? {
+        let location = chain
+            .get_tx_location(&outpoint.tx_hash)?
+            .ok_or_else(|| "utxo references an unindexed transaction".to_string())?;
+        let block = chain
+            .get_block(&location.block_hash)?
+            .ok_or_else(|| "tx index references a missing block".to_string())?;
+        let hashes: Vec<Hash256> = block.transactions.iter().map(|tx| tx.hash()).collect();
+        let tx = block
+            .transactions
+            .get(location.index as usize)
+            .ok_or_else(|| "tx index points past the block".to_string())?
+            .clone();
+        utxos.push(UtxoProofEntry {
+            outpoint,
+            entry,
+            tx,
+            branch: hash::merkle_branch(&hashes, location.index as usize),
+            header: block.header,
+        });
+    }
+    Ok(AddressProof {
+        address: *address,
+        utxos,
+    })
+}
+
+/// Verifies a proof against locally known headers. `header_at` is the
+/// wallet's own header chain: the header it synced for a height, or
+/// `None` when that height is beyond its tip. Any entry that fails —
+/// unknown header, broken branch, transaction that does not pay the
+/// address — fails the whole proof; a server with nothing to hide never
+/// sends one.
+pub fn verify_address_proof(
+    proof: &AddressProof,
+    header_at: impl Fn(u64) -> Option<BlockHeader>,
+) -> Result<VerifiedBalance, String> {
+    let mut balance = VerifiedBalance {
+        proven: 0,
+        claimed_change: 0,
+    };
+    for utxo in &proof.utxos {
+        let local = header_at(utxo.header.height)
+            .ok_or_else(|| format!("no local header at height {}", utxo.header.height))?;
+        if local != utxo.header {
+            return Err(format!(
+                "header at height {} does not match the local chain",
+                utxo.header.height
+            ));
+        }
+        let tx_hash = utxo.tx.hash();
+        if utxo.outpoint.tx_hash != tx_hash {
+            return Err("outpoint does not reference the included transaction".to_string());
+        }
+        if !hash::verify_merkle_proof(tx_hash, &utxo.branch, utxo.header.merkle_root) {
+            return Err("merkle branch does not reach the header root".to_string());
+        }
+        match utxo.outpoint.index {
+            // Output 0 pays `tx.to` exactly `tx.amount`; that covers
+            // coinbase outputs too.
+            0 => {
+                if utxo.tx.to != proof.address {
+                    return Err("funding transaction does not pay the address".to_string());
+                }
+                if utxo.entry.amount != utxo.tx.amount {
+                    return Err("claimed amount differs from the transaction".to_string());
+                }
+                balance.proven += utxo.entry.amount;
+            }
+            // Output 1 is change back to the sender. The amount depends
+            // on which inputs were gathered, so it cannot be checked
+            // from the transaction alone.
+            1 => {
+                if utxo.tx.from != proof.address {
+                    return Err("change output claimed for a non-sender".to_string());
+                }
+                balance.claimed_change += utxo.entry.amount;
+            }
+            _ => return Err("transactions only create outputs 0 and 1".to_string()),
+        }
+    }
+    Ok(balance)
+}
+
+/// A confirmed transaction's merkle branch plus the header it commits
+/// to — everything `verifytxoutproof` needs, and small enough to hand
+/// to an external system that synced headers on its own.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TxInclusionProof {
+    pub txid: Hash256,
+    /// Header of the block holding the transaction.
+    pub header: BlockHeader,
+    /// Merkle branch from `txid` to `header.merkle_root`.
+    pub branch: Vec<MerkleStep>,
+}
+
+/// Builds the inclusion proof a node sends back for `gettxoutproof`.
+pub fn build_tx_proof(chain: &Blockchain, txid: &Hash256) -> Result<TxInclusionProof, String> {
+    let location = chain
+        .get_tx_location(txid)?
+        .ok_or_else(|| "transaction is not in a confirmed block".to_string())?;
+    let block = chain
+        .get_block(&location.block_hash)?
+        .ok_or_else(|| "tx index references a missing block".to_string())?;
+    let hashes: Vec<Hash256> = block.transactions.iter().map(|tx| tx.hash()).collect();
+    Ok(TxInclusionProof {
+        txid: *txid,
+        header: block.header,
+        branch: hash::merkle_branch(&hashes, location.index as usize),
+    })
+}
+
+/// Checks that the branch ties the txid to the embedded header. The
+/// header itself is the caller's to judge: compare it (or its hash)
+/// against an independently synced chain before trusting the result.
+pub fn verify_tx_proof(proof: &TxInclusionProof) -> bool {
+    hash::verify_merkle_proof(proof.txid, &proof.branch, proof.header.merkle_root)
+}
+
+/// Proof that a block's coinbase commits to an external 32-byte hash
+/// (see `getwork::coinbase_data`). Carrying the whole coinbase lets a
+/// verifier re-derive the commitment from the tagged payload instead
+/// of taking the prover's word for what the data bytes mean.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommitmentProof {
+    /// The committed hash, re-derivable from `coinbase.data`.
+    pub commitment: Hash256,
+    /// The block's coinbase transaction, commitment and all.
+    pub coinbase: Transaction,
+    /// Merkle branch from the coinbase to `header.merkle_root`.
+    pub branch: Vec<MerkleStep>,
+    pub header: BlockHeader,
+}
+
+/// Builds the proof a node sends back for `getcommitmentproof`.
+pub fn build_commitment_proof(
+    chain: &Blockchain,
+    block_hash: &Hash256,
+) -> Result<CommitmentProof, String> {
+    let block = chain
+        .get_block(block_hash)?
+        .ok_or_else(|| "block not found".to_string())?;
+    let coinbase = block
+        .coinbase()
+        .ok_or_else(|| "block has no coinbase".to_string())?
+        .clone();
+    let commitment = crate::getwork::coinbase_commitment(&coinbase.data)
+        .ok_or_else(|| "coinbase carries no commitment".to_string())?;
+    let hashes: Vec<Hash256> = block.transactions.iter().map(|tx| tx.hash()).collect();
+    Ok(CommitmentProof {
+        commitment,
+        coinbase,
+        branch: hash::merkle_branch(&hashes, 0),
+        header: block.header,
+    })
+}
+
+/// Checks that the claimed commitment really sits in the coinbase and
+/// that the coinbase hashes into the embedded header. As with
+/// [`verify_tx_proof`], whether that header is part of the real chain
+/// is for the caller to establish.
+pub fn verify_commitment_proof(proof: &CommitmentProof) -> bool {
+    crate::getwork::coinbase_commitment(&proof.coinbase.data) == Some(proof.commitment)
+        && proof.coinbase.is_coinbase()
+        && hash::verify_merkle_proof(
+            proof.coinbase.hash(),
+            &proof.branch,
+            proof.header.merkle_root,
+        )
+}
@@ -0,0 +1,116 @@
+//! Structured reasons for rejecting transactions and blocks.
+//!
+//! Validation returns these instead of ad-hoc strings so the node can
+//! log them consistently, count them per code, and echo them to the
+//! offending peer in a Reject message. `code()` strings are stable
+//! identifiers in the bitcoind tradition; the Display form adds human
+//! detail where there is any.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Why a transaction or block failed validation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum RejectionReason {
+    /// Transaction or block belongs to a different chain id.
+    WrongChain,
+    /// Locktime says the transaction may not be mined yet.
+    NonFinal,
+    /// Signature missing, malformed, or not by the sender key.
+    BadSignature,
+    /// Signature scheme is not active at the spend height.
+    SignatureTypeNotActive,
+    /// Sender nonce does not match the expected account nonce.
+    BadNonce { expected: u64, got: u64 },
+    /// Sender's spendable balance cannot cover amount plus fee.
+    InsufficientFunds,
+    /// A coinbase transaction arrived outside a block.
+    CoinbaseOutsideBlock,
+    /// Block does not build on our current tip.
+    UnknownPrevBlock,
+    /// Block height does not follow the tip.
+    BadHeight,
+    /// Declared difficulty bits disagree with the retarget schedule.
+    BadDifficultyBits,
+    /// Header hash does not meet its own target.
+    BadPow,
+    /// Block timestamp is too far in the future.
+    TimeTooNew,
+    /// Merkle root does not commit to the block's transactions.
+    BadMerkleRoot,
+    /// First transaction is not a coinbase.
+    MissingCoinbase,
+    /// More than one coinbase transaction.
+    MultipleCoinbase,
+    /// Coinbase data field exceeds the consensus limit.
+    OversizedCoinbaseData,
+    /// Coinbase pays more than subsidy plus fees.
+    BadCoinbaseValue,
+    /// Coinbase payout is not derived from a configured PoA miner key.
+    UnauthorizedMiner,
+    /// Block is sealed by a validator out of round-robin order.
+    NotValidatorsTurn,
+    /// Validator signature on the block is missing or invalid.
+    BadValidatorSignature,
+    /// Validator-set rotation payload is malformed or unauthorized.
+    BadValidatorRotation,
+    /// Connecting the block would exceed the supply cap.
+    SupplyOverflow,
+    /// An amount or fee calculation overflowed.
+    ValueOverflow,
+    /// A storage or serialization failure, not a verdict on the data.
+    Internal(String),
+}
+
+impl RejectionReason {
+    /// Stable identifier used in logs, metrics and Reject messages.
+    pub fn code(&self) -> &'static str {
+        match self {
+            RejectionReason::WrongChain => "bad-chain-id",
+            RejectionReason::NonFinal => "bad-txns-nonfinal",
+            RejectionReason::BadSignature => "bad-txns-signature",
+            RejectionReason::SignatureTypeNotActive => "bad-txns-sigtype",
+            RejectionReason::BadNonce { .. } => "bad-txns-nonce",
+            RejectionReason::InsufficientFunds => "bad-txns-inputs-missing",
+            RejectionReason::CoinbaseOutsideBlock => "coinbase-outside-block",
+            RejectionReason::UnknownPrevBlock => "prev-blk-not-found",
+            RejectionReason::BadHeight => "bad-height",
+            RejectionReason::BadDifficultyBits => "bad-diffbits",
+            RejectionReason::BadPow => "bad-pow",
+            RejectionReason::TimeTooNew => "time-too-new",
+            RejectionReason::BadMerkleRoot => "bad-txnmrklroot",
+            RejectionReason::MissingCoinbase => "bad-cb-missing",
+            RejectionReason::MultipleCoinbase => "bad-cb-multiple",
+            RejectionReason::OversizedCoinbaseData => "bad-cb-data-size",
+            RejectionReason::BadCoinbaseValue => "bad-cb-amount",
+            RejectionReason::UnauthorizedMiner => "bad-cb-miner",
+            RejectionReason::NotValidatorsTurn => "bad-poa-turn",
+            RejectionReason::BadValidatorSignature => "bad-poa-sig",
+            RejectionReason::BadValidatorRotation => "bad-poa-rotation",
+            RejectionReason::SupplyOverflow => "bad-supply-cap",
+            RejectionReason::ValueOverflow => "bad-txns-value-overflow",
+            RejectionReason::Internal(_) => "internal-error",
+        }
+    }
+}
+
+impl fmt::Display for RejectionReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RejectionReason::BadNonce { expected, got } => {
+                write!(f, "{} (expected {}, got {})", self.code(), expected, got)
+            }
+            RejectionReason::Internal(detail) => write!(f, "{}: {}", self.code(), detail),
+            _ => f.write_str(self.code()),
+        }
+    }
+}
+
+/// Lets storage-layer `String` errors bubble through validation with
+/// `?` without each call site wrapping them by hand.
+impl From<String> for RejectionReason {
+    fn from(detail: String) -> Self {
+        RejectionReason::Internal(detail)
+    }
+}
@@ -0,0 +1,169 @@
+//! Peer reputation with decay and rehabilitation.
+//!
+//! Every consensus rejection a peer earns (see `Node::record_rejection`)
+//! subtracts from a per-IP score; crossing the ban threshold gets the
+//! peer disconnected and further connections refused. Unlike a pure
+//! banscore, the score is not a one-way ratchet: it decays toward
+//! neutral over time, relaying a valid block first earns a bounded
+//! credit, and a periodic review lifts bans once the decayed score
+//! recovers — so a transiently buggy client is excluded for hours, not
+//! forever, while a peer that keeps misbehaving stays out.
+//!
+//! Scores live in memory only. A restart forgives everyone, which is
+//! the cheap end of the same rehabilitation policy.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::Arc;
+
+use crate::node::Node;
+
+/// Seconds for a score to halve toward neutral.
+pub const DECAY_HALF_LIFE_SECS: u64 = 3_600;
+
+/// Points subtracted for relaying an invalid block or transaction.
+pub const INVALID_RELAY_PENALTY: i64 = 20;
+
+/// Score at (or below) which a peer is banned.
+pub const BAN_THRESHOLD: i64 = -100;
+
+/// A banned peer is re-admitted once decay lifts its score above this.
+/// At the default half-life a fresh ban lasts about two hours.
+pub const REHAB_THRESHOLD: i64 = BAN_THRESHOLD / 2;
+
+/// Credit for being the first peer to relay a block we accepted.
+pub const FIRST_RELAY_BONUS: i64 = 5;
+
+/// Ceiling on accumulated credit, so a long run of good relays cannot
+/// bankroll unlimited abuse later.
+pub const MAX_CREDIT: i64 = 50;
+
+/// How often the background review re-examines banned peers.
+pub const REVIEW_INTERVAL_SECS: u64 = 600;
+
+/// One peer's current standing.
+#[derive(Debug, Clone, Default)]
+struct Standing {
+    score: i64,
+    /// Unix time the score was last decayed to.
+    last_update: u64,
+    banned: bool,
+}
+
+impl Standing {
+    /// Halves the score toward zero once per elapsed half-life,
+    /// keeping the remainder so short accesses still accumulate.
+    fn decay(&mut self, now: u64) {
+        let elapsed = now.saturating_sub(self.last_update);
+        let halvings = elapsed / DECAY_HALF_LIFE_SECS;
+        if halvings > 0 {
+            self.score /= 1i64 << halvings.min(62);
+            self.last_update += halvings * DECAY_HALF_LIFE_SECS;
+        }
+    }
+}
+
+/// Scores keyed by IP. Takes the clock as an argument like the stats
+/// book, so tests can replay months of behavior in microseconds.
+#[derive(Default)]
+pub struct ReputationBook {
+    standings: HashMap<IpAddr, Standing>,
+}
+
+impl ReputationBook {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn standing(&mut self, ip: IpAddr, now: u64) -> &mut Standing {
+        let standing = self.standings.entry(ip).or_insert(Standing {
+            score: 0,
+            last_update: now,
+            banned: false,
+        });
+        standing.decay(now);
+        standing
+    }
+
+    /// Subtracts `points` and reports whether this penalty crossed the
+    /// ban threshold — the caller's cue to drop the connection.
+    pub fn penalize(&mut self, ip: IpAddr, points: i64, now: u64) -> bool {
+        let standing = self.standing(ip, now);
+        standing.score -= points;
+        if standing.score <= BAN_THRESHOLD && !standing.banned {
+            standing.banned = true;
+            return true;
+        }
+        false
+    }
+
+    /// Credits a peer for winning a block relay race, up to the cap.
+    pub fn credit_first_relay(&mut self, ip: IpAddr, now: u64) {
+        let standing = self.standing(ip, now);
+        standing.score = (standing.score + FIRST_RELAY_BONUS).min(MAX_CREDIT);
+    }
+
+    /// Whether connections from `ip` should be refused. Applies the
+    /// same rehabilitation rule as [`review`](Self::review), so a
+    /// reconnecting peer does not have to wait for the next pass.
+    pub fn is_banned(&mut self, ip: IpAddr, now: u64) -> bool {
+        let Some(standing) = self.standings.get_mut(&ip) else {
+            return false;
+        };
+        standing.decay(now);
+        if standing.banned && standing.score > REHAB_THRESHOLD {
+            standing.banned = false;
+        }
+        standing.banned
+    }
+
+    /// Current score, decayed to `now`. Untracked peers are neutral.
+    pub fn score(&mut self, ip: IpAddr, now: u64) -> i64 {
+        self.standings
+            .get_mut(&ip)
+            .map(|standing| {
+                standing.decay(now);
+                standing.score
+            })
+            .unwrap_or(0)
+    }
+
+    /// Re-examines every ban and lifts those whose score has decayed
+    /// past the rehabilitation threshold, returning the unbanned IPs.
+    pub fn review(&mut self, now: u64) -> Vec<IpAddr> {
+        let mut unbanned = Vec::new();
+        for (ip, standing) in &mut self.standings {
+            if !standing.banned {
+                continue;
+            }
+            standing.decay(now);
+            if standing.score > REHAB_THRESHOLD {
+                standing.banned = false;
+                unbanned.push(*ip);
+            }
+        }
+        unbanned
+    }
+}
+
+/// Periodically lifts bans whose scores have recovered, so rehabilitation
+/// does not depend on the banned peer knocking again.
+pub async fn review_loop(node: Arc<Node>) {
+    let mut ticker = tokio::time::interval(std::time::Duration::from_secs(REVIEW_INTERVAL_SECS));
+    ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    loop {
+        ticker.tick().await;
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        for ip in node
+            .reputation
+            .lock()
+            .expect("reputation lock poisoned")
+            .review(now)
+        {
+            log::info!("ban on {} lifted after score recovery", ip);
+        }
+    }
+}
@@ -0,0 +1,1733 @@
+//! JSON-RPC server exposed over HTTP for wallets, explorers and tooling.
+
+use std::sync::{Arc, Mutex};
+
+use axum::extract::State;
+use axum::routing::post;
+use axum::{Json, Router};
+use serde_json::{json, Value};
+
+use crate::blockchain::Blockchain;
+use crate::mempool::Mempool;
+use crate::node::Node;
+use crate::rejection::RejectionReason;
+use crate::rpc_auth::{method_scope, AuthConfig, Scope};
+use crate::types::{Block, Hash256, Transaction};
+use crate::wallet::{SendRequest, Wallet};
+
+/// Shared handles the RPC layer operates on.
+#[derive(Clone)]
+pub struct RpcContext {
+    pub chain: Arc<Mutex<Blockchain>>,
+    pub mempool: Arc<Mutex<Mempool>>,
+    /// Present when the P2P layer is running.
+    pub node: Option<Arc<Node>>,
+    pub chain_id: u8,
+    pub auth: Arc<AuthConfig>,
+}
+
+/// Starts the RPC server on `addr`. Runs until the process exits.
+pub async fn serve(ctx: RpcContext, addr: std::net::SocketAddr) -> Result<(), String> {
+    let mut app = Router::new()
+        .route("/", post(handle_request))
+        .with_state(ctx.clone());
+    app = app.merge(crate::getwork::router(ctx.clone()));
+    app = app.merge(crate::sse::router(ctx.clone()));
+    #[cfg(feature = "explorer")]
+    {
+        app = app.merge(crate::explorer::router(ctx.clone()));
+    }
+    let app = app.layer(axum::middleware::from_fn_with_state(
+        ctx.clone(),
+        auth_middleware,
+    ));
+    let listener = tokio::net::TcpListener::bind(addr)
+        .await
+        .map_err(|e| format!("failed to bind RPC listener: {}", e))?;
+    log::info!("RPC server listening on {}", addr);
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await
+    .map_err(|e| format!("RPC server error: {}", e))
+}
+
+/// Enforces the IP allowlist and resolves credentials to a [`Scope`]
+/// before any handler (RPC or explorer) runs. The granted scope rides
+/// along in the request extensions for per-method checks.
+async fn auth_middleware(
+    State(ctx): State<RpcContext>,
+    axum::extract::ConnectInfo(remote): axum::extract::ConnectInfo<std::net::SocketAddr>,
+    mut request: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    use axum::http::StatusCode;
+    use axum::response::IntoResponse;
+
+    if !ctx.auth.ip_allowed(remote.ip()) {
+        return (StatusCode::FORBIDDEN, "source address not allowed").into_response();
+    }
+    let header = request
+        .headers()
+        .get(axum::http::header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_owned);
+    match ctx.auth.authenticate(header.as_deref()) {
+        Some(scope) => {
+            request.extensions_mut().insert(scope);
+            next.run(request).await
+        }
+        None => (StatusCode::UNAUTHORIZED, "invalid credentials").into_response(),
+    }
+}
+
+async fn handle_request(
+    State(ctx): State<RpcContext>,
+    axum::Extension(scope): axum::Extension<Scope>,
+    Json(req): Json<Value>,
+) -> Json<Value> {
+    let id = req.get("id").cloned().unwrap_or(Value::Null);
+    let method = req.get("method").and_then(Value::as_str).unwrap_or("");
+    let params = req.get("params").cloned().unwrap_or(Value::Null);
+    if scope < method_scope(method) {
+        return Json(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -32001, "message": format!("scope does not permit '{}'", method) }
+        }));
+    }
+    // A long-polled getblocktemplate is held open until the template
+    // meaningfully changes; every other method answers immediately.
+    if method == "getblocktemplate" {
+        if let Some(longpollid) = params.get(1).and_then(Value::as_str) {
+            if let Err(message) = crate::getwork::wait_for_template_change(&ctx, longpollid).await {
+                return Json(json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": -1, "message": message }
+                }));
+            }
+        }
+    }
+    match dispatch_audited(&ctx, method, &params) {
+        Ok(result) => Json(json!({ "jsonrpc": "2.0", "id": id, "result": result })),
+        Err(message) => Json(json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": -1, "message": message }
+        })),
+    }
+}
+
+/// Routes a call and journals it if it was a successful admin action.
+/// Queries (get-prefixed methods) stay out of the journal: an audit
+/// trail of reads would drown the writes it exists to record.
+pub fn dispatch_audited(ctx: &RpcContext, method: &str, params: &Value) -> Result<Value, String> {
+    let result = dispatch(ctx, method, params)?;
+    if method_scope(method) == Scope::Admin && !method.starts_with("get") {
+        let outcome = ctx
+            .chain
+            .lock()
+            .map_err(|_| "chain lock poisoned")?
+            .journal(crate::journal::EventKind::AdminAction {
+                method: method.to_string(),
+            });
+        if let Err(e) = outcome {
+            log::debug!("failed to journal admin action: {}", e);
+        }
+    }
+    Ok(result)
+}
+
+/// Routes a single RPC call. Each method locks only what it needs.
+pub fn dispatch(ctx: &RpcContext, method: &str, params: &Value) -> Result<Value, String> {
+    match method {
+        "getinfo" => getinfo(ctx),
+        "getblockcount" => {
+            // The hottest polling target; answer from the lock-free
+            // snapshot when the node is running.
+            if let Some(node) = &ctx.node {
+                return Ok(json!(node.tip_view.current().height));
+            }
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            Ok(json!(chain.height()))
+        }
+        "getblockhash" => {
+            let height = param_u64(params, 0)?;
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            let hash = chain
+                .get_block_hash(height)?
+                .ok_or_else(|| "block height out of range".to_string())?;
+            Ok(json!(hex::encode(hash)))
+        }
+        "getblock" => {
+            let hash = param_hash(params, 0)?;
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            let block = chain
+                .get_block(&hash)?
+                .ok_or_else(|| "block not found".to_string())?;
+            Ok(block_to_json(&block))
+        }
+        "getblockfilter" => {
+            let hash = param_hash(params, 0)?;
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            let filter = chain
+                .get_filter(&hash)?
+                .ok_or_else(|| "block not found".to_string())?;
+            Ok(json!({
+                "filter": hex::encode(&filter.data),
+                "filter_hash": hex::encode(filter.filter_hash()),
+                "header": hex::encode(chain.filter_header(&hash)?),
+            }))
+        }
+        "getbalance" => {
+            let address = param_address(params, 0)?;
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            Ok(json!(chain.get_balance(&address)?))
+        }
+        "getdifficultyhistory" => {
+            let start = param_u64(params, 0)?;
+            let end = param_u64(params, 1)?;
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            let history = chain.get_difficulty_history(start, end)?;
+            Ok(json!(history
+                .iter()
+                .map(|(height, r)| json!({
+                    "height": height,
+                    "bits": r.bits,
+                    "difficulty": r.difficulty,
+                    "solve_time": r.solve_time,
+                    "timestamp": r.timestamp,
+                }))
+                .collect::<Vec<_>>()))
+        }
+        "getnetworkhashrate" => {
+            let window = param_u64(params, 0).unwrap_or(120);
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            Ok(json!(chain.estimated_hashrate(window)?))
+        }
+        "getpeerinfo" => getpeerinfo(ctx),
+        "getpeerstats" => getpeerstats(ctx),
+        "getnetworkreport" => getnetworkreport(ctx),
+        "getblockstats" => {
+            let count = param_u64(params, 0).unwrap_or(20) as usize;
+            let node = require_node(ctx)?;
+            let telemetry = node.telemetry.lock().map_err(|_| "telemetry lock poisoned")?;
+            Ok(json!({
+                "stats": telemetry.stats(),
+                "recent": telemetry
+                    .recent(count)
+                    .iter()
+                    .map(|r| json!({
+                        "hash": hex::encode(r.hash),
+                        "height": r.height,
+                        "first_seen": r.first_seen,
+                        "peer": r.peer.map(|p| p.to_string()),
+                        "validation_micros": r.validation_micros,
+                        "propagation_delay_secs": r.propagation_delay_secs,
+                    }))
+                    .collect::<Vec<_>>(),
+            }))
+        }
+        "getchaintips" => getchaintips(ctx),
+        "getrawmempool" => getrawmempool(ctx, params),
+        "getrecentlogs" => {
+            let n = param_u64(params, 0).unwrap_or(50) as usize;
+            Ok(json!(crate::logbuffer::recent(n)))
+        }
+        "getjournal" => {
+            let start = param_u64(params, 0).unwrap_or(0);
+            let count =
+                param_u64(params, 1).unwrap_or(crate::journal::MAX_JOURNAL_EVENTS as u64) as usize;
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            let events = chain.journal_events(start, count)?;
+            Ok(json!({
+                "events": events.iter().map(event_to_json).collect::<Vec<_>>(),
+                "next_seq": events.last().map(|e| e.seq + 1).unwrap_or(start),
+            }))
+        }
+        "gettransactionstatus" => {
+            let tx_hash = param_hash(params, 0)?;
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            let mempool = ctx.mempool.lock().map_err(|_| "mempool lock poisoned")?;
+            if let Some(loc) = chain.get_tx_location(&tx_hash)? {
+                Ok(json!({
+                    "status": "confirmed",
+                    "height": loc.height,
+                    "confirmations": chain.height().saturating_sub(loc.height) + 1,
+                }))
+            } else if mempool.contains(&tx_hash) {
+                Ok(json!({ "status": "pending" }))
+            } else {
+                Ok(json!({ "status": "unknown" }))
+            }
+        }
+        "getaddressinfo" => {
+            let address = param_address(params, 0)?;
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            let utxos = chain.get_utxos_for_address(&address)?;
+            Ok(json!({
+                "address": hex::encode(address),
+                "balance": utxos.iter().map(|(_, e)| e.amount).sum::<u64>(),
+                "nonce": chain.get_nonce(&address)?,
+                "utxo_count": utxos.len(),
+                "seen": chain.address_has_history(&address)?,
+            }))
+        }
+        "listunspent" => {
+            let address = param_address(params, 0)?;
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            let height = chain.height();
+            let mut utxos = chain.get_utxos_for_address(&address)?;
+            utxos.sort_by_key(|(outpoint, entry)| (entry.height, outpoint.tx_hash, outpoint.index));
+            Ok(json!(utxos
+                .iter()
+                .map(|(outpoint, entry)| json!({
+                    "txid": hex::encode(outpoint.tx_hash),
+                    "vout": outpoint.index,
+                    "amount": entry.amount,
+                    "height": entry.height,
+                    "coinbase": entry.is_coinbase,
+                    // Whether the next block may spend it.
+                    "mature": !entry.is_coinbase
+                        || height + 1 >= entry.height + crate::blockchain::COINBASE_MATURITY,
+                }))
+                .collect::<Vec<_>>()))
+        }
+        "watchaddress" => {
+            let address = param_address(params, 0)?;
+            let node = require_node(ctx)?;
+            let mut watch = node.watch.lock().map_err(|_| "watch lock poisoned")?;
+            Ok(json!({ "watched": watch.watch(address), "cursor": watch.cursor() }))
+        }
+        "importaccountxpub" => {
+            let export = params
+                .get(0)
+                .and_then(Value::as_str)
+                .ok_or_else(|| "missing xpub hex".to_string())?;
+            let count = params.get(1).and_then(Value::as_u64).unwrap_or(100);
+            if count == 0 || count > crate::audit::MAX_XPUB_IMPORT {
+                return Err(format!(
+                    "count must be between 1 and {}",
+                    crate::audit::MAX_XPUB_IMPORT
+                ));
+            }
+            let xpub = crate::audit::decode(export)?;
+            let node = require_node(ctx)?;
+            let mut watch = node.watch.lock().map_err(|_| "watch lock poisoned")?;
+            let mut addresses = Vec::with_capacity(count as usize);
+            for index in 0..count as u32 {
+                let address = crate::audit::derive_receive_address(&xpub, index)?;
+                watch.watch(address);
+                addresses.push(hex::encode(address));
+            }
+            Ok(json!({
+                "fingerprint": xpub.fingerprint(),
+                "watched": addresses,
+            }))
+        }
+        "unwatchaddress" => {
+            let address = param_address(params, 0)?;
+            let node = require_node(ctx)?;
+            let mut watch = node.watch.lock().map_err(|_| "watch lock poisoned")?;
+            Ok(json!({ "removed": watch.unwatch(&address) }))
+        }
+        "listwatchedaddresses" => {
+            let node = require_node(ctx)?;
+            let watch = node.watch.lock().map_err(|_| "watch lock poisoned")?;
+            Ok(json!(watch
+                .watched()
+                .iter()
+                .map(hex::encode)
+                .collect::<Vec<_>>()))
+        }
+        "getaddressactivity" => {
+            let address = param_address(params, 0)?;
+            let since = param_u64(params, 1).unwrap_or(0);
+            let node = require_node(ctx)?;
+            let watch = node.watch.lock().map_err(|_| "watch lock poisoned")?;
+            let entries = watch
+                .activity(&address, since)
+                .ok_or_else(|| "address is not watched".to_string())?;
+            Ok(json!({
+                "cursor": watch.cursor(),
+                "activity": entries
+                    .iter()
+                    .map(|e| json!({
+                        "seq": e.seq,
+                        "txid": hex::encode(e.txid),
+                        "direction": e.direction,
+                        "amount": e.amount,
+                        "height": e.height,
+                        "timestamp": e.timestamp,
+                    }))
+                    .collect::<Vec<_>>(),
+            }))
+        }
+        "getnonce" => {
+            let address = param_address(params, 0)?;
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            Ok(json!(chain.get_nonce(&address)?))
+        }
+        // sendrawtransaction is the conventional name for the same
+        // hex-in broadcast; scripts using either reach one path.
+        "sendtransaction" | "sendrawtransaction" => {
+            if let Some(node) = &ctx.node {
+                if node.toggles.lock().expect("toggles lock poisoned").safe_mode {
+                    return Err(
+                        "node is in safe mode; wallet sends are disabled until it is lifted"
+                            .to_string(),
+                    );
+                }
+            }
+            let tx_hex = params
+                .get(0)
+                .and_then(Value::as_str)
+                .ok_or_else(|| "missing transaction hex".to_string())?;
+            let bytes = hex::decode(tx_hex).map_err(|e| format!("bad hex: {}", e))?;
+            let tx: Transaction = bincode::deserialize(&bytes)
+                .map_err(|e| format!("malformed transaction: {}", e))?;
+            let tx_hash = {
+                let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+                let mut mempool = ctx.mempool.lock().map_err(|_| "mempool lock poisoned")?;
+                match chain.validate_transaction(&tx, ctx.chain_id) {
+                    Ok(_) => {}
+                    // A nonce ahead of the confirmed state may chain
+                    // onto the sender's pending transactions (the CPFP
+                    // path); anything else is fatal.
+                    Err(RejectionReason::BadNonce { expected, got }) if got > expected => {
+                        let balance = chain.get_balance(&tx.from)?;
+                        mempool.validate_chained(&tx, expected, balance)?;
+                    }
+                    Err(reason) => return Err(reason.to_string()),
+                }
+                // Manual locks reserve value: refuse a spend that only
+                // clears by dipping into locked outputs.
+                if let Some(node) = &ctx.node {
+                    let mut locks = node.coin_locks.lock().expect("coin locks lock poisoned");
+                    let locked = locks.locked_amount(&chain, &tx.from)?;
+                    if locked > 0 {
+                        let spendable = chain.get_balance(&tx.from)?.saturating_sub(locked);
+                        let needed = tx.amount.saturating_add(tx.fee);
+                        if needed > spendable {
+                            return Err(format!(
+                                "spend of {} would consume locked coins ({} spendable after {} locked)",
+                                needed, spendable, locked
+                            ));
+                        }
+                    }
+                }
+                mempool.insert_from_wallet(tx.clone(), chain.height())?
+            };
+            if let Some(node) = &ctx.node {
+                // Dandelion++ stem phase hides which node the wallet
+                // submitted through.
+                node.relay_local_transaction(tx);
+            }
+            if let Err(e) = ctx
+                .chain
+                .lock()
+                .map_err(|_| "chain lock poisoned")?
+                .journal(crate::journal::EventKind::TransactionSent { txid: tx_hash })
+            {
+                log::debug!("failed to journal wallet send: {}", e);
+            }
+            Ok(json!(hex::encode(tx_hash)))
+        }
+        "getspendinginfo" => {
+            let tx_hash = param_hash(params, 0)?;
+            let index = param_u64(params, 1)? as u32;
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            let outpoint = crate::types::OutPoint { tx_hash, index };
+            match chain.get_spending_info(&outpoint)? {
+                Some(info) => Ok(json!({
+                    "spent": true,
+                    "spending_tx": hex::encode(info.spending_tx),
+                    "height": info.height,
+                })),
+                None => Ok(json!({ "spent": false })),
+            }
+        }
+        "getstorageinfo" => getstorageinfo(ctx),
+        "gettxoutsetinfo" => gettxoutsetinfo(ctx),
+        "getrejectionstats" => {
+            let node = require_node(ctx)?;
+            Ok(json!(node.rejection_counts()))
+        }
+        "gettoggles" => {
+            let node = require_node(ctx)?;
+            Ok(toggles_json(node))
+        }
+        "settoggle" => {
+            let node = require_node(ctx)?;
+            let usage = || "settoggle takes a toggle name and a boolean".to_string();
+            let name = params.get(0).and_then(Value::as_str).ok_or_else(usage)?;
+            let enabled = params.get(1).and_then(Value::as_bool).ok_or_else(usage)?;
+            node.toggles
+                .lock()
+                .expect("toggles lock poisoned")
+                .set(name, enabled)?;
+            // Lifting safe mode also retires its warning.
+            if name == "safe_mode" && !enabled {
+                *node
+                    .safe_mode_reason
+                    .lock()
+                    .expect("safe mode reason lock poisoned") = None;
+            }
+            log::warn!("operator toggled {} {}", name, if enabled { "on" } else { "off" });
+            Ok(toggles_json(node))
+        }
+        "getblocktemplate" => {
+            crate::getwork::ensure_mining_open(ctx)?;
+            let payout = param_address(params, 0)?;
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            let mempool = ctx.mempool.lock().map_err(|_| "mempool lock poisoned")?;
+            let commitment = crate::getwork::pending_commitment(ctx);
+            let template = crate::getwork::build_template(
+                &chain,
+                &mempool,
+                ctx.chain_id,
+                payout,
+                commitment.as_ref(),
+            )?;
+            let mut value = block_to_json(&template);
+            // Echo the token a miner long-polls with next time.
+            value["longpollid"] = json!(crate::getwork::longpoll_id(&chain, &mempool));
+            Ok(value)
+        }
+        "submitblock" => {
+            let block_hex = params
+                .get(0)
+                .and_then(Value::as_str)
+                .ok_or_else(|| "missing block hex".to_string())?;
+            let bytes = hex::decode(block_hex).map_err(|e| format!("bad hex: {}", e))?;
+            let block: Block =
+                bincode::deserialize(&bytes).map_err(|e| format!("malformed block: {}", e))?;
+            crate::getwork::submit_block(ctx, &block)?;
+            Ok(json!(hex::encode(block.hash())))
+        }
+        "getfeehistogram" => {
+            let mempool = ctx.mempool.lock().map_err(|_| "mempool lock poisoned")?;
+            let block_bytes = crate::getwork::MAX_TEMPLATE_TX_BYTES;
+            // 3-minute blocks: 10 blocks ≈ 30 minutes, 20 ≈ an hour.
+            Ok(json!({
+                "buckets": mempool.fee_histogram(),
+                "estimates": {
+                    "next_block": mempool.estimate_fee_rate(1, block_bytes),
+                    "30_min": mempool.estimate_fee_rate(10, block_bytes),
+                    "1_hour": mempool.estimate_fee_rate(20, block_bytes),
+                },
+            }))
+        }
+        "getmempoolpolicy" => {
+            let mempool = ctx.mempool.lock().map_err(|_| "mempool lock poisoned")?;
+            Ok(json!(mempool.policy()))
+        }
+        "setmempoolpolicy" => {
+            let fields = params
+                .get(0)
+                .and_then(Value::as_object)
+                .ok_or_else(|| "expected a policy object".to_string())?;
+            let mut mempool = ctx.mempool.lock().map_err(|_| "mempool lock poisoned")?;
+            let mut policy = *mempool.policy();
+            for (key, value) in fields {
+                match key.as_str() {
+                    "min_relay_fee_rate" => {
+                        policy.min_relay_fee_rate = value
+                            .as_f64()
+                            .filter(|rate| *rate >= 0.0)
+                            .ok_or_else(|| "min_relay_fee_rate must be a non-negative number".to_string())?;
+                    }
+                    "max_tx_bytes" => {
+                        policy.max_tx_bytes = value
+                            .as_u64()
+                            .ok_or_else(|| "max_tx_bytes must be an integer".to_string())?
+                            as usize;
+                    }
+                    "max_ancestors" => {
+                        policy.max_ancestors = value
+                            .as_u64()
+                            .filter(|n| *n >= 1)
+                            .ok_or_else(|| "max_ancestors must be at least 1".to_string())?
+                            as usize;
+                    }
+                    "dust_threshold" => {
+                        policy.dust_threshold = value
+                            .as_u64()
+                            .ok_or_else(|| "dust_threshold must be an integer".to_string())?;
+                    }
+                    "max_data_bytes" => {
+                        policy.max_data_bytes = value
+                            .as_u64()
+                            .ok_or_else(|| "max_data_bytes must be an integer".to_string())?
+                            as usize;
+                    }
+                    other => return Err(format!("unknown policy field '{}'", other)),
+                }
+            }
+            mempool.set_policy(policy);
+            Ok(json!(policy))
+        }
+        "sendalert" => sendalert(ctx, params),
+        "getmempoolentry" => getmempoolentry(ctx, params),
+        "prioritisetransaction" => prioritisetransaction(ctx, params),
+        "getprioritisedtransactions" => getprioritisedtransactions(ctx),
+        "registerdeposit" => registerdeposit(ctx, params),
+        "unregisterdeposit" => unregisterdeposit(ctx, params),
+        "listdeposits" => listdeposits(ctx),
+        "getdepositevents" => getdepositevents(ctx, params),
+        "loadwallet" => loadwallet(ctx, params),
+        "unloadwallet" => unloadwallet(ctx, params),
+        "listwallets" => listwallets(ctx),
+        "lockwallet" => lockwallet(ctx, params),
+        "unlockwallet" => unlockwallet(ctx, params),
+        "sendfromwallet" => sendfromwallet(ctx, params),
+        "submitpackage" => submitpackage(ctx, params),
+        "gettxoutproof" => {
+            let tx_hash = param_hash(params, 0)?;
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            let proof = crate::proofs::build_tx_proof(&chain, &tx_hash)?;
+            let bytes = bincode::serialize(&proof).expect("proof serialization cannot fail");
+            Ok(json!({
+                "proof": hex::encode(bytes),
+                "block_hash": hex::encode(proof.header.hash()),
+                "height": proof.header.height,
+            }))
+        }
+        "verifytxoutproof" => {
+            let proof_hex = params
+                .get(0)
+                .and_then(Value::as_str)
+                .ok_or_else(|| "missing proof hex".to_string())?;
+            let bytes = hex::decode(proof_hex).map_err(|e| format!("bad hex: {}", e))?;
+            let proof: crate::proofs::TxInclusionProof = bincode::deserialize(&bytes)
+                .map_err(|e| format!("malformed proof: {}", e))?;
+            // The merkle check is self-contained; whether the embedded
+            // header belongs to the best chain is reported alongside so
+            // callers without their own header chain can still ask.
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            let in_best_chain = chain
+                .get_block_hash(proof.header.height)?
+                .is_some_and(|hash| hash == proof.header.hash());
+            Ok(json!({
+                "valid": crate::proofs::verify_tx_proof(&proof),
+                "txid": hex::encode(proof.txid),
+                "block_hash": hex::encode(proof.header.hash()),
+                "height": proof.header.height,
+                "in_best_chain": in_best_chain,
+            }))
+        }
+        "verifyreserveproof" => {
+            let proof_hex = params
+                .get(0)
+                .and_then(Value::as_str)
+                .ok_or_else(|| "missing proof hex".to_string())?;
+            let bytes = hex::decode(proof_hex).map_err(|e| format!("bad hex: {}", e))?;
+            let proof: crate::reserves::ReserveProof = bincode::deserialize(&bytes)
+                .map_err(|e| format!("malformed proof: {}", e))?;
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            // A failed check is an answer about the proof, not an RPC
+            // error: auditors need the reason, not a dead line.
+            match crate::reserves::verify_reserve_proof(&chain, &proof) {
+                Ok(total) => Ok(json!({
+                    "valid": true,
+                    "address": hex::encode(proof.address),
+                    "total": total,
+                    "utxo_count": proof.utxos.len(),
+                    "height": proof.height,
+                    "claim": proof.claim,
+                })),
+                Err(reason) => Ok(json!({
+                    "valid": false,
+                    "address": hex::encode(proof.address),
+                    "reason": reason,
+                })),
+            }
+        }
+        "setblockcommitment" => {
+            let node = require_node(ctx)?;
+            // A null (or absent) parameter clears a queued commitment.
+            let commitment = match params.get(0) {
+                None | Some(Value::Null) => None,
+                Some(_) => Some(param_hash(params, 0)?),
+            };
+            *node
+                .next_commitment
+                .lock()
+                .map_err(|_| "commitment lock poisoned")? = commitment;
+            Ok(json!({ "commitment": commitment.map(hex::encode) }))
+        }
+        "getcommitmentproof" => {
+            let block_hash = param_hash(params, 0)?;
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            let proof = crate::proofs::build_commitment_proof(&chain, &block_hash)?;
+            let bytes = bincode::serialize(&proof).expect("proof serialization cannot fail");
+            Ok(json!({
+                "proof": hex::encode(bytes),
+                "commitment": hex::encode(proof.commitment),
+                "block_hash": hex::encode(proof.header.hash()),
+                "height": proof.header.height,
+            }))
+        }
+        "verifycommitmentproof" => {
+            let proof_hex = params
+                .get(0)
+                .and_then(Value::as_str)
+                .ok_or_else(|| "missing proof hex".to_string())?;
+            let bytes = hex::decode(proof_hex).map_err(|e| format!("bad hex: {}", e))?;
+            let proof: crate::proofs::CommitmentProof = bincode::deserialize(&bytes)
+                .map_err(|e| format!("malformed proof: {}", e))?;
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            let in_best_chain = chain
+                .get_block_hash(proof.header.height)?
+                .is_some_and(|hash| hash == proof.header.hash());
+            Ok(json!({
+                "valid": crate::proofs::verify_commitment_proof(&proof),
+                "commitment": hex::encode(proof.commitment),
+                "block_hash": hex::encode(proof.header.hash()),
+                "height": proof.header.height,
+                "in_best_chain": in_best_chain,
+            }))
+        }
+        "getintegrityinfo" => {
+            let node = require_node(ctx)?;
+            let scrub = node.scrub.lock().expect("scrub lock poisoned");
+            Ok(json!({
+                "cursor": scrub.cursor,
+                "blocks_checked": scrub.blocks_checked,
+                "passes_completed": scrub.passes_completed,
+                "findings": scrub
+                    .findings
+                    .iter()
+                    .map(|f| json!({
+                        "height": f.height,
+                        "detail": f.detail,
+                        "repaired": f.repaired,
+                    }))
+                    .collect::<Vec<_>>(),
+            }))
+        }
+        "getbackupinfo" => {
+            let node = require_node(ctx)?;
+            let status = {
+                let status = node.backups.lock().expect("backup status lock poisoned");
+                status.clone()
+            };
+            let now = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or(0);
+            Ok(json!({
+                "last_success": status.last_success,
+                "age_secs": status.age_secs(now),
+                "last_height": status.last_height,
+                "archives": status.archives,
+                "failures": status.failures,
+            }))
+        }
+        "lockunspent" => lockunspent(ctx, params),
+        "listlockunspent" => listlockunspent(ctx),
+        "getpoolinfo" => getpoolinfo(ctx),
+        "getpoolpayouts" => getpoolpayouts(ctx, params),
+        "testmempoolaccept" => testmempoolaccept(ctx, params),
+        "createrawtransaction" => createrawtransaction(ctx, params),
+        "decoderawtransaction" => decoderawtransaction(params),
+        "signrawtransactionwithkey" => signrawtransactionwithkey(params),
+        _ => Err(format!("unknown method '{}'", method)),
+    }
+}
+
+/// Methods that touch P2P state need a running node.
+fn require_node(ctx: &RpcContext) -> Result<&Arc<Node>, String> {
+    ctx.node
+        .as_ref()
+        .ok_or_else(|| "P2P layer is not running".to_string())
+}
+
+fn getinfo(ctx: &RpcContext) -> Result<Value, String> {
+    let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+    let mempool = ctx.mempool.lock().map_err(|_| "mempool lock poisoned")?;
+    Ok(json!({
+        "chain_id": ctx.chain_id,
+        "height": chain.height(),
+        "best_hash": hex::encode(chain.best_hash()),
+        "circulating_supply": chain.state().circulating_supply,
+        "mempool_txs": mempool.len(),
+        "mempool_bytes": mempool.total_size(),
+        "syncing": ctx
+            .node
+            .as_ref()
+            .is_some_and(|node| !node.params.chain_work_sufficient(chain.state().total_work)),
+        "toggles": ctx
+            .node
+            .as_ref()
+            .map(|node| toggles_json(node))
+            .unwrap_or(Value::Null),
+        "warnings": ctx
+            .node
+            .as_ref()
+            .and_then(|node| {
+                node.safe_mode_reason
+                    .lock()
+                    .expect("safe mode reason lock poisoned")
+                    .clone()
+            })
+            .unwrap_or_default(),
+        "alerts": active_alerts(ctx),
+        "version": crate::updates::running_version(),
+        "update": ctx
+            .node
+            .as_ref()
+            .and_then(|node| {
+                node.update
+                    .lock()
+                    .expect("update lock poisoned")
+                    .as_ref()
+                    .map(|status| json!(status))
+            })
+            .unwrap_or(Value::Null),
+    }))
+}
+
+/// Current runtime-toggle states as the RPC layer reports them.
+fn toggles_json(node: &Node) -> Value {
+    let toggles = node.toggles.lock().expect("toggles lock poisoned");
+    json!({
+        "tx_relay": toggles.tx_relay,
+        "mining": toggles.mining,
+        "inbound": toggles.inbound,
+        "safe_mode": toggles.safe_mode,
+    })
+}
+
+/// Unexpired developer alerts for display, empty without a node.
+fn active_alerts(ctx: &RpcContext) -> Vec<Value> {
+    let Some(node) = &ctx.node else {
+        return Vec::new();
+    };
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let alerts = node.alerts.lock().expect("alerts lock poisoned");
+    alerts
+        .active(now)
+        .into_iter()
+        .map(|alert| {
+            json!({
+                "id": alert.id,
+                "message": alert.message,
+                "expires": alert.expires,
+            })
+        })
+        .collect()
+}
+
+/// `sendalert` — injects a developer-signed alert (hex bincode
+/// `SignedAlert`), verifying it against this node's alert keys before
+/// relaying it to peers.
+fn sendalert(ctx: &RpcContext, params: &Value) -> Result<Value, String> {
+    let node = require_node(ctx)?;
+    let hex = params
+        .get(0)
+        .and_then(Value::as_str)
+        .ok_or_else(|| "sendalert takes a hex-encoded SignedAlert".to_string())?;
+    let bytes = hex::decode(hex).map_err(|e| format!("invalid hex: {}", e))?;
+    let signed: crate::alerts::SignedAlert =
+        bincode::deserialize(&bytes).map_err(|e| format!("malformed alert: {}", e))?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let accepted = node
+        .alerts
+        .lock()
+        .expect("alerts lock poisoned")
+        .accept(signed.clone(), &node.params, now)?;
+    if accepted {
+        log::warn!("network alert {}: {}", signed.alert.id, signed.alert.message);
+        node.broadcast(crate::network::NetworkMessage::Alert(signed.clone()));
+    }
+    Ok(json!({ "id": signed.alert.id, "relayed": accepted }))
+}
+
+/// `getstorageinfo` — per-column-family disk usage and compaction
+/// backlog, for capacity planning and the monitor dashboard.
+fn getstorageinfo(ctx: &RpcContext) -> Result<Value, String> {
+    let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+    let stats = chain.storage_info();
+    let total_sst: u64 = stats.iter().map(|s| s.sst_files_size).sum();
+    let total_pending: u64 = stats.iter().map(|s| s.pending_compaction_bytes).sum();
+    let column_families: Vec<Value> = stats
+        .iter()
+        .map(|s| {
+            json!({
+                "name": s.name,
+                "sst_files_size": s.sst_files_size,
+                "live_data_size": s.live_data_size,
+                "num_keys": s.num_keys,
+                "pending_compaction_bytes": s.pending_compaction_bytes,
+                "space_amplification": s.space_amplification(),
+            })
+        })
+        .collect();
+    Ok(json!({
+        "column_families": column_families,
+        "total_sst_files_size": total_sst,
+        "total_pending_compaction_bytes": total_pending,
+    }))
+}
+
+/// `gettxoutsetinfo` — full UTXO set scan with an audit verdict: does
+/// the on-disk set match both the tracked circulating supply and what
+/// the emission schedule allows?
+fn gettxoutsetinfo(ctx: &RpcContext) -> Result<Value, String> {
+    let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+    let info = chain.utxo_set_info()?;
+    let circulating = chain.state().circulating_supply;
+    let expected = crate::types::expected_supply(info.height);
+    Ok(json!({
+        "height": info.height,
+        "best_hash": hex::encode(info.best_hash),
+        "txouts": info.txouts,
+        "total_amount": info.total_amount,
+        "hash_serialized": hex::encode(info.hash_serialized),
+        "circulating_supply": circulating,
+        "expected_subsidy": expected,
+        "matches_state": info.total_amount == circulating,
+        // A premine makes supply legitimately exceed the subsidy
+        // schedule; anything above both is an emission bug.
+        "within_emission_schedule": info.total_amount <= expected
+            || info.total_amount == circulating,
+    }))
+}
+
+/// `getpeerinfo` — one entry per connected peer, including smoothed
+/// round-trip latency in milliseconds.
+/// Active tip plus every competing branch the node has observed, in
+/// Bitcoin Core's `getchaintips` shape. Branch work is decimal text
+/// because it can exceed JSON's integer range.
+fn getchaintips(ctx: &RpcContext) -> Result<Value, String> {
+    let node = require_node(ctx)?;
+    let (height, best_hash) = {
+        let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+        (chain.height(), chain.best_hash())
+    };
+    let forks = node.forks.lock().map_err(|_| "forks lock poisoned")?;
+    let mut out = vec![json!({
+        "height": height,
+        "hash": hex::encode(best_hash),
+        "branchlen": 0,
+        "status": "active",
+    })];
+    for tip in forks.tips() {
+        out.push(json!({
+            "height": tip.height,
+            "hash": hex::encode(tip.hash),
+            "branchlen": tip.branch_len,
+            "branchwork": tip.branch_work.to_string(),
+            "status": tip.status,
+            "first_seen": tip.first_seen,
+        }));
+    }
+    Ok(json!({
+        "tips": out,
+        "stats": forks.stats(),
+    }))
+}
+
+fn getpeerinfo(ctx: &RpcContext) -> Result<Value, String> {
+    let node = require_node(ctx)?;
+    let peers = node.peers.lock().map_err(|_| "peers lock poisoned")?;
+    let out: Vec<Value> = peers
+        .values()
+        .map(|p| {
+            json!({
+                "addr": p.addr.to_string(),
+                "inbound": p.inbound,
+                "version": p.version,
+                "user_agent": p.user_agent,
+                "best_height": p.best_height,
+                "connected_at": p.connected_at,
+                "last_seen": p.last_seen,
+                "ping_ms": p.ping_time,
+            })
+        })
+        .collect();
+    Ok(json!(out))
+}
+
+/// `getpeerstats` — lifetime relay statistics per peer IP, persisted
+/// across restarts, with historical averages derived from the raw
+/// counters (see the peerstats module).
+fn getpeerstats(ctx: &RpcContext) -> Result<Value, String> {
+    let node = require_node(ctx)?;
+    let stats = node.peerstats.lock().map_err(|_| "peerstats lock poisoned")?;
+    let mut out: Vec<Value> = stats
+        .records()
+        .iter()
+        .map(|(ip, record)| {
+            json!({
+                "address": ip,
+                "user_agent": record.user_agent,
+                "version": record.version,
+                "sessions": record.sessions,
+                "total_uptime_secs": record.total_uptime_secs,
+                "average_session_secs": record.average_session_secs(),
+                "blocks_valid": record.blocks_valid,
+                "blocks_invalid": record.blocks_invalid,
+                "txs_valid": record.txs_valid,
+                "txs_invalid": record.txs_invalid,
+                "valid_ratio": record.valid_ratio(),
+                "first_seen": record.first_seen,
+                "last_seen": record.last_seen,
+            })
+        })
+        .collect();
+    out.sort_by(|a, b| a["address"].as_str().cmp(&b["address"].as_str()));
+    Ok(json!(out))
+}
+
+/// `getnetworkreport` — the peer population grouped by user agent,
+/// busiest first: the node's own crawler's-eye view of what software
+/// its corner of the network runs.
+fn getnetworkreport(ctx: &RpcContext) -> Result<Value, String> {
+    let node = require_node(ctx)?;
+    let stats = node.peerstats.lock().map_err(|_| "peerstats lock poisoned")?;
+    Ok(json!(stats.network_report()))
+}
+
+/// `getrawmempool [verbose]` — tx hashes, or full entries when verbose.
+fn getrawmempool(ctx: &RpcContext, params: &Value) -> Result<Value, String> {
+    let verbose = params
+        .get(0)
+        .and_then(Value::as_bool)
+        .unwrap_or(false);
+    let mempool = ctx.mempool.lock().map_err(|_| "mempool lock poisoned")?;
+    if !verbose {
+        let hashes: Vec<String> = mempool.iter().map(|(h, _)| hex::encode(h)).collect();
+        return Ok(json!(hashes));
+    }
+    let mut out = serde_json::Map::new();
+    for (hash, entry) in mempool.iter() {
+        out.insert(hex::encode(hash), mempool_entry_to_json(&mempool, entry));
+    }
+    Ok(Value::Object(out))
+}
+
+/// `getmempoolentry <txid>` — detailed entry for one pooled transaction.
+fn getmempoolentry(ctx: &RpcContext, params: &Value) -> Result<Value, String> {
+    let hash = param_hash(params, 0)?;
+    let mempool = ctx.mempool.lock().map_err(|_| "mempool lock poisoned")?;
+    let entry = mempool
+        .get(&hash)
+        .ok_or_else(|| "transaction not in mempool".to_string())?;
+    Ok(mempool_entry_to_json(&mempool, entry))
+}
+
+/// `prioritisetransaction <txid> <fee-delta>` — the transaction
+/// accelerator: adjusts the fee block selection prices the transaction
+/// at, in base units (negative deprioritises). Deltas accumulate
+/// across calls and may be set before the transaction arrives.
+fn prioritisetransaction(ctx: &RpcContext, params: &Value) -> Result<Value, String> {
+    let hash = param_hash(params, 0)?;
+    let fee_delta = params
+        .get(1)
+        .and_then(Value::as_i64)
+        .ok_or_else(|| "missing numeric parameter 1".to_string())?;
+    let mut mempool = ctx.mempool.lock().map_err(|_| "mempool lock poisoned")?;
+    let total = mempool.prioritise(hash, fee_delta);
+    Ok(json!({
+        "txid": hex::encode(hash),
+        "fee_delta": total,
+        "in_mempool": mempool.contains(&hash),
+    }))
+}
+
+/// `getprioritisedtransactions` — every operator-set fee delta,
+/// including ones for transactions not yet (or no longer) pooled.
+fn getprioritisedtransactions(ctx: &RpcContext) -> Result<Value, String> {
+    let mempool = ctx.mempool.lock().map_err(|_| "mempool lock poisoned")?;
+    let mut out = serde_json::Map::new();
+    for (hash, fee_delta) in mempool.prioritised() {
+        out.insert(
+            hex::encode(hash),
+            json!({
+                "fee_delta": fee_delta,
+                "in_mempool": mempool.contains(hash),
+            }),
+        );
+    }
+    Ok(Value::Object(out))
+}
+
+/// `registerdeposit <address> <min-confirmations>` — starts reorg-safe
+/// deposit tracking on an address (see the deposits module).
+fn registerdeposit(ctx: &RpcContext, params: &Value) -> Result<Value, String> {
+    let node = require_node(ctx)?;
+    let address = param_address(params, 0)?;
+    let min_confirmations = param_u64(params, 1)?;
+    let fresh = node
+        .deposits
+        .lock()
+        .expect("deposits lock poisoned")
+        .register(address, min_confirmations);
+    Ok(json!({
+        "address": hex::encode(address),
+        "min_confirmations": min_confirmations.max(1),
+        "already_registered": !fresh,
+    }))
+}
+
+/// `unregisterdeposit <address>` — stops picking up new deposits for
+/// an address; in-flight ones still run to completion.
+fn unregisterdeposit(ctx: &RpcContext, params: &Value) -> Result<Value, String> {
+    let node = require_node(ctx)?;
+    let address = param_address(params, 0)?;
+    let removed = node
+        .deposits
+        .lock()
+        .expect("deposits lock poisoned")
+        .unregister(&address);
+    Ok(json!({ "removed": removed }))
+}
+
+/// `listdeposits` — every registration and tracked deposit with its
+/// current status.
+fn listdeposits(ctx: &RpcContext) -> Result<Value, String> {
+    let node = require_node(ctx)?;
+    let deposits = node.deposits.lock().expect("deposits lock poisoned");
+    let registrations: Vec<Value> = deposits
+        .registrations()
+        .map(|(address, min_confirmations)| {
+            json!({
+                "address": hex::encode(address),
+                "min_confirmations": min_confirmations,
+            })
+        })
+        .collect();
+    let tracked: Vec<Value> = deposits
+        .deposits()
+        .map(|record| {
+            json!({
+                "txid": hex::encode(record.txid),
+                "address": hex::encode(record.address),
+                "amount": record.amount,
+                "height": record.height,
+                "status": record.status,
+                "credit_round": record.credit_round,
+            })
+        })
+        .collect();
+    Ok(json!({ "registrations": registrations, "deposits": tracked }))
+}
+
+/// `loadwallet <name> <path> <password>` — opens a wallet file and
+/// hosts it under `name`, locked (see the multiwallet module). The
+/// wallet's address joins the watch list so its activity reaches
+/// `getaddressactivity` and the `/events` feed.
+fn loadwallet(ctx: &RpcContext, params: &Value) -> Result<Value, String> {
+    let node = require_node(ctx)?;
+    let name = param_str(params, 0)?;
+    let path = param_str(params, 1)?;
+    let password = param_str(params, 2)?;
+    let wallet = Wallet::from_file(path, password).map_err(String::from)?;
+    let address = wallet.address();
+    node.wallets
+        .lock()
+        .expect("wallets lock poisoned")
+        .load(name, std::path::PathBuf::from(path), wallet)
+        .map_err(String::from)?;
+    node.watch
+        .lock()
+        .expect("watch lock poisoned")
+        .watch(address);
+    Ok(json!({
+        "name": name,
+        "address": hex::encode(address),
+        "locked": true,
+    }))
+}
+
+/// `unloadwallet <name>` — drops a hosted wallet and its unlock
+/// session. The address leaves the watch list unless another loaded
+/// wallet still uses it.
+fn unloadwallet(ctx: &RpcContext, params: &Value) -> Result<Value, String> {
+    let node = require_node(ctx)?;
+    let name = param_str(params, 0)?;
+    let mut wallets = node.wallets.lock().expect("wallets lock poisoned");
+    let Some(unloaded) = wallets.unload(name) else {
+        return Ok(json!({ "removed": false }));
+    };
+    let address = unloaded.wallet.address();
+    if !wallets.watches(&address) {
+        node.watch
+            .lock()
+            .expect("watch lock poisoned")
+            .unwatch(&address);
+    }
+    Ok(json!({ "removed": true }))
+}
+
+/// `listwallets` — every hosted wallet with its current lock state.
+fn listwallets(ctx: &RpcContext) -> Result<Value, String> {
+    let node = require_node(ctx)?;
+    let mut wallets = node.wallets.lock().expect("wallets lock poisoned");
+    let list: Vec<Value> = wallets
+        .iter_mut()
+        .map(|(name, hosted)| {
+            json!({
+                "name": name,
+                "address": hex::encode(hosted.wallet.address()),
+                "path": hosted.path.display().to_string(),
+                "locked": hosted.wallet.is_locked(),
+            })
+        })
+        .collect();
+    Ok(json!(list))
+}
+
+/// `lockwallet <name>` — ends one hosted wallet's unlock session now;
+/// the others are untouched.
+fn lockwallet(ctx: &RpcContext, params: &Value) -> Result<Value, String> {
+    let node = require_node(ctx)?;
+    let name = param_str(params, 0)?;
+    let mut wallets = node.wallets.lock().expect("wallets lock poisoned");
+    wallets.get_mut(name).map_err(String::from)?.wallet.lock();
+    Ok(json!({ "locked": true }))
+}
+
+/// `unlockwallet <name> <password> [timeout-secs]` — starts an unlock
+/// session on one hosted wallet, walletpassphrase style.
+fn unlockwallet(ctx: &RpcContext, params: &Value) -> Result<Value, String> {
+    let node = require_node(ctx)?;
+    let name = param_str(params, 0)?;
+    let password = param_str(params, 1)?;
+    let timeout = params
+        .get(2)
+        .and_then(Value::as_u64)
+        .unwrap_or(crate::wallet::DEFAULT_UNLOCK_SECS);
+    let mut wallets = node.wallets.lock().expect("wallets lock poisoned");
+    wallets
+        .get_mut(name)
+        .map_err(String::from)?
+        .wallet
+        .unlock(password, timeout)
+        .map_err(String::from)?;
+    Ok(json!({ "locked": false, "timeout_secs": timeout }))
+}
+
+/// `sendfromwallet <name> <to> <amount> <fee>` — builds and signs a
+/// spend from a hosted wallet, subject to its unlock session and
+/// spend policy, then relays through the `sendtransaction` path.
+fn sendfromwallet(ctx: &RpcContext, params: &Value) -> Result<Value, String> {
+    let node = require_node(ctx)?;
+    let name = param_str(params, 0)?;
+    let to = param_address(params, 1)?;
+    let amount = param_u64(params, 2)?;
+    let fee = param_u64(params, 3)?;
+    let tx = {
+        let mut wallets = node.wallets.lock().expect("wallets lock poisoned");
+        let hosted = wallets.get_mut(name).map_err(String::from)?;
+        let (nonce, tip) = {
+            let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+            (chain.get_nonce(&hosted.wallet.address())?, chain.height())
+        };
+        hosted
+            .wallet
+            .create_transaction(SendRequest {
+                to,
+                amount,
+                fee,
+                nonce,
+                chain_id: ctx.chain_id,
+                replaceable: false,
+                tip_height: tip,
+            })
+            .map_err(String::from)?
+    };
+    let tx_hex = hex::encode(bincode::serialize(&tx).expect("serialization cannot fail"));
+    dispatch(ctx, "sendtransaction", &json!([tx_hex]))
+}
+
+/// `submitpackage <tx_hex> <tx_hex> ...` — pools a dependent package
+/// (one sender, contiguous nonces) atomically, judged on its combined
+/// fee rate, so a zero-fee parent required by a high-fee child can
+/// enter the mempool (CPFP).
+fn submitpackage(ctx: &RpcContext, params: &Value) -> Result<Value, String> {
+    if let Some(node) = &ctx.node {
+        if node.toggles.lock().expect("toggles lock poisoned").safe_mode {
+            return Err(
+                "node is in safe mode; wallet sends are disabled until it is lifted".to_string(),
+            );
+        }
+    }
+    let list = params
+        .as_array()
+        .ok_or_else(|| "expected an array of transaction hex".to_string())?;
+    let mut txs = Vec::with_capacity(list.len());
+    for (i, item) in list.iter().enumerate() {
+        let tx_hex = item
+            .as_str()
+            .ok_or_else(|| format!("missing transaction hex at {}", i))?;
+        let bytes = hex::decode(tx_hex).map_err(|e| format!("bad hex at {}: {}", i, e))?;
+        let tx: Transaction = bincode::deserialize(&bytes)
+            .map_err(|e| format!("malformed transaction at {}: {}", i, e))?;
+        txs.push(tx);
+    }
+    let txids = {
+        let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+        let mut mempool = ctx.mempool.lock().map_err(|_| "mempool lock poisoned")?;
+        mempool.check_package(&txs)?;
+        let height = chain.height();
+        let mut inserted: Vec<Hash256> = Vec::new();
+        for tx in &txs {
+            let admitted = match chain.validate_transaction(tx, ctx.chain_id) {
+                Ok(_) => Ok(()),
+                // Later members chain onto the ones just pooled — the
+                // same CPFP allowance sendtransaction gives.
+                Err(RejectionReason::BadNonce { expected, got }) if got > expected => {
+                    let balance = chain.get_balance(&tx.from)?;
+                    mempool
+                        .validate_chained(tx, expected, balance)
+                        .map_err(String::from)
+                }
+                Err(reason) => Err(reason.to_string()),
+            }
+            .and_then(|_| {
+                mempool
+                    .insert_package_member(tx.clone(), height)
+                    .map_err(String::from)
+            });
+            match admitted {
+                Ok(tx_hash) => inserted.push(tx_hash),
+                Err(e) => {
+                    // All or nothing: pull the members already pooled.
+                    for tx_hash in &inserted {
+                        mempool.remove(tx_hash);
+                    }
+                    return Err(format!(
+                        "package member {} rejected: {}",
+                        hex::encode(tx.hash()),
+                        e
+                    ));
+                }
+            }
+        }
+        inserted
+    };
+    if let Some(node) = &ctx.node {
+        node.broadcast_package(None, txs);
+    }
+    {
+        let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+        for txid in &txids {
+            if let Err(e) = chain.journal(crate::journal::EventKind::TransactionSent { txid: *txid })
+            {
+                log::debug!("failed to journal package send: {}", e);
+            }
+        }
+    }
+    Ok(json!({ "txids": txids.iter().map(hex::encode).collect::<Vec<_>>() }))
+}
+
+/// `getdepositevents [since]` — credited/reorged-out/re-credited
+/// events from the given cursor, plus the cursor to poll next.
+fn getdepositevents(ctx: &RpcContext, params: &Value) -> Result<Value, String> {
+    let node = require_node(ctx)?;
+    let since = params.get(0).and_then(Value::as_u64).unwrap_or(0);
+    let deposits = node.deposits.lock().expect("deposits lock poisoned");
+    let events: Vec<Value> = deposits
+        .events_since(since)
+        .into_iter()
+        .map(|event| {
+            json!({
+                "seq": event.seq,
+                "kind": event.kind,
+                "txid": hex::encode(event.txid),
+                "address": hex::encode(event.address),
+                "amount": event.amount,
+                "confirmations": event.confirmations,
+                "idempotency_key": hex::encode(event.idempotency_key),
+            })
+        })
+        .collect();
+    Ok(json!({ "events": events, "cursor": deposits.cursor() }))
+}
+
+/// `lockunspent <txid> <vout> [lock]` — reserves (or, with `lock` set
+/// to false, releases) an unspent output so the node refuses spends
+/// that could only be funded by it. Locks are in-memory and node-local.
+fn lockunspent(ctx: &RpcContext, params: &Value) -> Result<Value, String> {
+    let node = require_node(ctx)?;
+    let tx_hash = param_hash(params, 0)?;
+    let index = param_u64(params, 1)? as u32;
+    let lock = params.get(2).and_then(Value::as_bool).unwrap_or(true);
+    let outpoint = crate::types::OutPoint { tx_hash, index };
+    let mut locks = node.coin_locks.lock().expect("coin locks lock poisoned");
+    let changed = if lock {
+        let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+        if chain.get_utxo(&outpoint)?.is_none() {
+            return Err("outpoint is not an unspent output".to_string());
+        }
+        locks.lock(outpoint)
+    } else {
+        locks.unlock(&outpoint)
+    };
+    Ok(json!({ "locked": lock, "changed": changed }))
+}
+
+/// `listlockunspent` — the locked outpoints still standing, with the
+/// address and value each one reserves.
+fn listlockunspent(ctx: &RpcContext) -> Result<Value, String> {
+    let node = require_node(ctx)?;
+    let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+    let locks = node.coin_locks.lock().expect("coin locks lock poisoned");
+    let mut entries = Vec::new();
+    for outpoint in locks.outpoints() {
+        // Spent-out locks linger until the next locked_amount sweep;
+        // don't report value that no longer exists.
+        if let Some(entry) = chain.get_utxo(outpoint)? {
+            entries.push(json!({
+                "txid": hex::encode(outpoint.tx_hash),
+                "vout": outpoint.index,
+                "address": hex::encode(entry.address),
+                "amount": entry.amount,
+            }));
+        }
+    }
+    Ok(json!(entries))
+}
+
+/// `getpoolinfo` — share ledger totals for the `/work` mining pool.
+fn getpoolinfo(ctx: &RpcContext) -> Result<Value, String> {
+    let node = require_node(ctx)?;
+    let pool = node.pool.lock().expect("pool lock poisoned");
+    Ok(json!({
+        "shares": pool.shares().len(),
+        "round_shares": pool.round_shares().len(),
+        "workers": pool.workers(),
+        "blocks_found": pool.blocks_found(),
+    }))
+}
+
+/// `getpoolpayouts <scheme> <reward> [window]` — settles `reward` base
+/// units across the share ledger. Scheme is `pplns` (over the last
+/// `window` shares) or `proportional` (over the round in progress);
+/// the reply carries both a JSON table and ready-to-file CSV.
+fn getpoolpayouts(ctx: &RpcContext, params: &Value) -> Result<Value, String> {
+    let node = require_node(ctx)?;
+    let scheme = params.get(0).and_then(Value::as_str).unwrap_or("pplns");
+    let reward = param_u64(params, 1)?;
+    let pool = node.pool.lock().expect("pool lock poisoned");
+    let payouts = match scheme {
+        "pplns" => {
+            let window = params
+                .get(2)
+                .and_then(Value::as_u64)
+                .map(|w| w as usize)
+                .unwrap_or(crate::pool::PPLNS_DEFAULT_WINDOW);
+            pool.pplns_payouts(reward, window)
+        }
+        "proportional" => pool.proportional_payouts(reward),
+        other => {
+            return Err(format!(
+                "unknown payout scheme '{}'; expected pplns or proportional",
+                other
+            ))
+        }
+    };
+    Ok(json!({
+        "scheme": scheme,
+        "reward": reward,
+        "payouts": payouts
+            .iter()
+            .map(|p| json!({
+                "worker": hex::encode(p.worker),
+                "amount": p.amount,
+            }))
+            .collect::<Vec<_>>(),
+        "csv": crate::pool::payouts_csv(&payouts),
+    }))
+}
+
+/// `testmempoolaccept <tx-hex>` — full acceptance validation without
+/// inserting, so services can verify a transaction before broadcast.
+fn testmempoolaccept(ctx: &RpcContext, params: &Value) -> Result<Value, String> {
+    let tx_hex = params
+        .get(0)
+        .and_then(Value::as_str)
+        .ok_or_else(|| "missing transaction hex".to_string())?;
+    let bytes = hex::decode(tx_hex).map_err(|e| format!("bad hex: {}", e))?;
+    let tx: Transaction =
+        bincode::deserialize(&bytes).map_err(|e| format!("malformed transaction: {}", e))?;
+    let tx_hash = tx.hash();
+    let chain = ctx.chain.lock().map_err(|_| "chain lock poisoned")?;
+    let mempool = ctx.mempool.lock().map_err(|_| "mempool lock poisoned")?;
+    if mempool.contains(&tx_hash) {
+        return Ok(json!({
+            "txid": hex::encode(tx_hash),
+            "allowed": false,
+            "reject-reason": "already in mempool",
+        }));
+    }
+    match chain.validate_transaction(&tx, ctx.chain_id) {
+        Ok(fee) => {
+            let mut warnings: Vec<&str> = Vec::new();
+            if chain.address_has_history(&tx.to).unwrap_or(false) {
+                warnings.push("destination-address-reuse");
+            }
+            Ok(json!({
+                "txid": hex::encode(tx_hash),
+                "allowed": true,
+                "size": tx.size(),
+                "fee": fee,
+                "fee_rate": tx.fee_rate(),
+                "warnings": warnings,
+            }))
+        }
+        Err(reason) => Ok(json!({
+            "txid": hex::encode(tx_hash),
+            "allowed": false,
+            "reject-reason": reason.to_string(),
+            "reject-code": reason.code(),
+        })),
+    }
+}
+
+/// Decodes the canonical hex serialization of a transaction from a
+/// positional parameter.
+fn param_transaction(params: &Value, index: usize) -> Result<Transaction, String> {
+    let tx_hex = params
+        .get(index)
+        .and_then(Value::as_str)
+        .ok_or_else(|| "missing transaction hex".to_string())?;
+    let bytes = hex::decode(tx_hex).map_err(|e| format!("bad hex: {}", e))?;
+    bincode::deserialize(&bytes).map_err(|e| format!("malformed transaction: {}", e))
+}
+
+/// `createrawtransaction {from, to, amount, ...}` — encodes an unsigned
+/// transaction as canonical hex, the entry point of the raw workflow
+/// for scripts that construct spends outside the wallet. `fee`,
+/// `data`, `replaceable` and `lock_time` are optional; a missing
+/// `nonce` is looked up from the confirmed chain state.
+fn createrawtransaction(ctx: &RpcContext, params: &Value) -> Result<Value, String> {
+    let spec = params
+        .get(0)
+        .filter(|v| v.is_object())
+        .ok_or_else(|| "missing transaction spec object".to_string())?;
+    let address = |field: &str| -> Result<[u8; 20], String> {
+        spec.get(field)
+            .and_then(Value::as_str)
+            .and_then(|s| hex::decode(s).ok())
+            .and_then(|b| b.try_into().ok())
+            .ok_or_else(|| format!("{} must be a 20-byte hex address", field))
+    };
+    let from = address("from")?;
+    let to = address("to")?;
+    let amount = spec
+        .get("amount")
+        .and_then(Value::as_u64)
+        .ok_or_else(|| "amount must be an integer".to_string())?;
+    let nonce = match spec.get("nonce").and_then(Value::as_u64) {
+        Some(nonce) => nonce,
+        None => ctx
+            .chain
+            .lock()
+            .map_err(|_| "chain lock poisoned")?
+            .get_nonce(&from)?,
+    };
+    let data = match spec.get("data").and_then(Value::as_str) {
+        Some(data) => hex::decode(data).map_err(|e| format!("bad data hex: {}", e))?,
+        None => Vec::new(),
+    };
+    let tx = Transaction {
+        chain_id: ctx.chain_id,
+        nonce,
+        from,
+        to,
+        amount,
+        fee: spec.get("fee").and_then(Value::as_u64).unwrap_or(0),
+        data,
+        replaceable: spec
+            .get("replaceable")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        lock_time: spec.get("lock_time").and_then(Value::as_u64).unwrap_or(0),
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    };
+    Ok(json!(hex::encode(
+        bincode::serialize(&tx).expect("serialization cannot fail")
+    )))
+}
+
+/// `decoderawtransaction <tx-hex>` — decodes canonical hex back into
+/// its fields without touching chain state, so a signer can inspect
+/// exactly what it is about to commit to.
+fn decoderawtransaction(params: &Value) -> Result<Value, String> {
+    let tx = param_transaction(params, 0)?;
+    Ok(json!({
+        "txid": hex::encode(tx.hash()),
+        "size": tx.size(),
+        "chain_id": tx.chain_id,
+        "nonce": tx.nonce,
+        "from": hex::encode(tx.from),
+        "to": hex::encode(tx.to),
+        "amount": tx.amount,
+        "fee": tx.fee,
+        "fee_rate": tx.fee_rate(),
+        "data": hex::encode(&tx.data),
+        "replaceable": tx.replaceable,
+        "lock_time": tx.lock_time,
+        "signed": !tx.signature.is_empty(),
+        "signature": hex::encode(&tx.signature),
+        "public_key": hex::encode(&tx.public_key),
+    }))
+}
+
+/// `signrawtransactionwithkey <tx-hex> <privkey> [scheme]` — signs a
+/// raw transaction with a caller-supplied key (hex or WIF), ECDSA
+/// unless `"schnorr"` is asked for. The signature is verified before
+/// the hex is handed back: a key that does not hash to the sender
+/// address could only ever produce an unbroadcastable transaction.
+fn signrawtransactionwithkey(params: &Value) -> Result<Value, String> {
+    let mut tx = param_transaction(params, 0)?;
+    let key = params
+        .get(1)
+        .and_then(Value::as_str)
+        .ok_or_else(|| "missing private key".to_string())?;
+    let key = crate::crypto::parse_private_key(key)?;
+    match params.get(2).and_then(Value::as_str).unwrap_or("ecdsa") {
+        "ecdsa" => crate::crypto::sign_transaction(&mut tx, &key)?,
+        "schnorr" => crate::crypto::sign_transaction_schnorr(&mut tx, &key)?,
+        other => return Err(format!("unknown signature scheme '{}'", other)),
+    }
+    crate::crypto::verify_transaction_signature(&tx)
+        .map_err(|e| format!("key does not sign for the sender address: {}", e))?;
+    Ok(json!({
+        "hex": hex::encode(bincode::serialize(&tx).expect("serialization cannot fail")),
+        "txid": hex::encode(tx.hash()),
+        "complete": true,
+    }))
+}
+
+/// Flattens a journal entry for getjournal: common envelope fields
+/// plus the kind's own, under a stable "type" discriminator.
+fn event_to_json(event: &crate::journal::Event) -> Value {
+    use crate::journal::EventKind;
+    let mut value = json!({
+        "seq": event.seq,
+        "timestamp": event.timestamp,
+        "type": event.kind.name(),
+    });
+    match &event.kind {
+        EventKind::BlockConnected { hash, height }
+        | EventKind::BlockDisconnected { hash, height } => {
+            value["hash"] = json!(hex::encode(hash));
+            value["height"] = json!(height);
+        }
+        EventKind::TransactionSent { txid } => {
+            value["txid"] = json!(hex::encode(txid));
+        }
+        EventKind::PeerRejected {
+            peer,
+            what,
+            hash,
+            code,
+        } => {
+            value["peer"] = json!(peer);
+            value["what"] = json!(what);
+            value["hash"] = json!(hex::encode(hash));
+            value["code"] = json!(code);
+        }
+        EventKind::AdminAction { method } => {
+            value["method"] = json!(method);
+        }
+    }
+    value
+}
+
+/// In the account model a pooled transaction's ancestors are the same
+/// sender's pending lower nonces; descendants are its higher nonces.
+fn mempool_entry_to_json(mempool: &Mempool, entry: &crate::mempool::MempoolEntry) -> Value {
+    let mut ancestors = Vec::new();
+    let mut descendants = Vec::new();
+    for (other_hash, other) in mempool.iter() {
+        if other.tx.from != entry.tx.from || other.tx.nonce == entry.tx.nonce {
+            continue;
+        }
+        if other.tx.nonce < entry.tx.nonce {
+            ancestors.push(hex::encode(other_hash));
+        } else {
+            descendants.push(hex::encode(other_hash));
+        }
+    }
+    let package = mempool.package_info(&entry.tx.hash());
+    json!({
+        "size": entry.size,
+        "fee": entry.fee,
+        "fee_delta": mempool.priority_delta(&entry.tx.hash()),
+        "fee_rate": entry.fee_rate(),
+        "effective_fee_rate": package.map(|p| p.effective_fee_rate),
+        "ancestor_count": package.map(|p| p.ancestor_count),
+        "ancestor_fees": package.map(|p| p.ancestor_fees),
+        "ancestor_size": package.map(|p| p.ancestor_size),
+        "time": entry.time_added,
+        "height": entry.height_added,
+        "ancestors": ancestors,
+        "descendants": descendants,
+    })
+}
+
+pub fn block_to_json(block: &Block) -> Value {
+    json!({
+        "hash": hex::encode(block.hash()),
+        "height": block.header.height,
+        "prev_hash": hex::encode(block.header.prev_hash),
+        "merkle_root": hex::encode(block.header.merkle_root),
+        "timestamp": block.header.timestamp,
+        "bits": block.header.bits,
+        "nonce": block.header.nonce,
+        "size": block.size(),
+        "coinbase_data": block.coinbase_data().map(hex::encode),
+        "tx": block.transactions.iter().map(|tx| hex::encode(tx.hash())).collect::<Vec<_>>(),
+    })
+}
+
+fn param_str(params: &Value, index: usize) -> Result<&str, String> {
+    params
+        .get(index)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("missing string parameter {}", index))
+}
+
+fn param_u64(params: &Value, index: usize) -> Result<u64, String> {
+    params
+        .get(index)
+        .and_then(Value::as_u64)
+        .ok_or_else(|| format!("missing numeric parameter {}", index))
+}
+
+fn param_hash(params: &Value, index: usize) -> Result<Hash256, String> {
+    let s = params
+        .get(index)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("missing hash parameter {}", index))?;
+    let bytes = hex::decode(s).map_err(|e| format!("bad hex: {}", e))?;
+    bytes
+        .try_into()
+        .map_err(|_| "hash must be 32 bytes".to_string())
+}
+
+fn param_address(params: &Value, index: usize) -> Result<[u8; 20], String> {
+    let s = params
+        .get(index)
+        .and_then(Value::as_str)
+        .ok_or_else(|| format!("missing address parameter {}", index))?;
+    let bytes = hex::decode(s).map_err(|e| format!("bad hex: {}", e))?;
+    bytes
+        .try_into()
+        .map_err(|_| "address must be 20 bytes".to_string())
+}
@@ -0,0 +1,96 @@
+//! Global cache of already-verified transaction signatures.
+//!
+//! Signature checks dominate transaction validation, and almost every
+//! transaction is checked at least twice: once at mempool admission
+//! and again when its block connects. Each successful check is
+//! remembered under a digest binding the signing hash, public key and
+//! signature bytes together, so a hit proves this exact check already
+//! passed — any change to the transaction, the key or the signature
+//! produces a different digest and misses. Parsed ECDSA public keys
+//! are cached separately, since senders repeat and decoding a SEC
+//! point is not free. Only successes are cached; a failed check stays
+//! expensive, which is fine — it costs the peer that relayed it.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+
+use secp256k1::PublicKey;
+
+use crate::hash;
+use crate::types::Hash256;
+
+/// Verified checks remembered before the oldest are forgotten. A miss
+/// only costs the re-verification it would have cost anyway.
+pub const VERIFIED_CACHE_CAPACITY: usize = 100_000;
+
+/// Distinct parsed public keys kept.
+pub const PUBKEY_CACHE_CAPACITY: usize = 10_000;
+
+struct Caches {
+    verified: HashSet<Hash256>,
+    verified_order: VecDeque<Hash256>,
+    pubkeys: HashMap<Vec<u8>, PublicKey>,
+    pubkey_order: VecDeque<Vec<u8>>,
+}
+
+static CACHES: Mutex<Option<Caches>> = Mutex::new(None);
+
+fn with<T>(f: impl FnOnce(&mut Caches) -> T) -> T {
+    let mut guard = CACHES.lock().expect("sigcache lock poisoned");
+    let caches = guard.get_or_insert_with(|| Caches {
+        verified: HashSet::new(),
+        verified_order: VecDeque::new(),
+        pubkeys: HashMap::new(),
+        pubkey_order: VecDeque::new(),
+    });
+    f(caches)
+}
+
+/// The digest under which a successful check is remembered: the
+/// signing hash with the exact key and signature bytes that verified.
+pub fn cache_key(signing_hash: &Hash256, public_key: &[u8], signature: &[u8]) -> Hash256 {
+    let mut input = Vec::with_capacity(32 + public_key.len() + signature.len());
+    input.extend_from_slice(signing_hash);
+    input.extend_from_slice(public_key);
+    input.extend_from_slice(signature);
+    hash::sha256(&input)
+}
+
+/// Whether this exact signature check already passed.
+pub fn is_verified(key: &Hash256) -> bool {
+    with(|caches| caches.verified.contains(key))
+}
+
+/// Remembers a signature check that just passed, evicting the oldest
+/// entry once the cache is full.
+pub fn record_verified(key: Hash256) {
+    with(|caches| {
+        if caches.verified.insert(key) {
+            caches.verified_order.push_back(key);
+            if caches.verified_order.len() > VERIFIED_CACHE_CAPACITY {
+                if let Some(oldest) = caches.verified_order.pop_front() {
+                    caches.verified.remove(&oldest);
+                }
+            }
+        }
+    })
+}
+
+/// Parses a SEC-encoded ECDSA public key through the cache.
+pub fn parse_pubkey(bytes: &[u8]) -> Result<PublicKey, String> {
+    with(|caches| {
+        if let Some(key) = caches.pubkeys.get(bytes) {
+            return Ok(*key);
+        }
+        let key =
+            PublicKey::from_slice(bytes).map_err(|e| format!("malformed public key: {}", e))?;
+        caches.pubkeys.insert(bytes.to_vec(), key);
+        caches.pubkey_order.push_back(bytes.to_vec());
+        if caches.pubkey_order.len() > PUBKEY_CACHE_CAPACITY {
+            if let Some(oldest) = caches.pubkey_order.pop_front() {
+                caches.pubkeys.remove(&oldest);
+            }
+        }
+        Ok(key)
+    })
+}
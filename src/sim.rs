@@ -0,0 +1,163 @@
+//! Economic model simulation: fast-forwards the chain analytically —
+//! no proof of work, no database — to validate the emission schedule,
+//! fee revenue and difficulty behavior over thousands of blocks.
+//!
+//! The model is deterministic: each block's solve time is the expected
+//! value given the current target and the configured hashrate, so two
+//! runs with the same config produce the same report.
+
+use serde::Serialize;
+
+use crate::math;
+use crate::types::{COIN, TARGET_BLOCK_TIME};
+
+/// Knobs for one simulation run. Halving parameters are configurable
+/// so alternative schedules can be compared against the shipped one.
+#[derive(Debug, Clone)]
+pub struct SimConfig {
+    /// How many blocks to fast-forward.
+    pub blocks: u64,
+    /// Synthetic transactions included per block.
+    pub txs_per_block: u64,
+    /// Flat fee each synthetic transaction pays, in base units.
+    pub fee_per_tx: u64,
+    /// Aggregate network hashrate at height 0, in hashes per second.
+    pub hashrate: f64,
+    /// Multiplicative hashrate change applied at every retarget
+    /// window (1.0 = flat, 1.05 = five percent growth per window).
+    pub hashrate_growth: f64,
+    /// Blocks between subsidy halvings.
+    pub halving_interval: u64,
+    /// Subsidy before the first halving, in base units.
+    pub initial_reward: u64,
+    /// Difficulty retarget window, in blocks.
+    pub retarget_window: u64,
+    /// Record a sample every this many blocks.
+    pub sample_every: u64,
+}
+
+impl Default for SimConfig {
+    fn default() -> Self {
+        SimConfig {
+            blocks: 10_000,
+            txs_per_block: 50,
+            fee_per_tx: 10_000,
+            hashrate: 1_000_000.0,
+            hashrate_growth: 1.0,
+            halving_interval: crate::types::HALVING_INTERVAL,
+            initial_reward: crate::types::INITIAL_REWARD,
+            retarget_window: crate::blockchain::RETARGET_WINDOW,
+            sample_every: 1_000,
+        }
+    }
+}
+
+/// One sampled point along the simulated chain.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimSample {
+    pub height: u64,
+    /// Cumulative minted supply in base units.
+    pub supply: u64,
+    /// Subsidy in force at this height.
+    pub reward: u64,
+    /// Cumulative fee revenue paid to miners.
+    pub fees: u64,
+    pub difficulty: f64,
+    pub hashrate: f64,
+    /// Expected solve time at this point, in seconds.
+    pub solve_time: f64,
+}
+
+/// Outcome of a simulation run.
+#[derive(Debug, Clone, Serialize)]
+pub struct SimReport {
+    pub samples: Vec<SimSample>,
+    pub final_supply: u64,
+    pub total_fees: u64,
+    pub final_difficulty: f64,
+    /// Simulated wall-clock duration of the whole run, in seconds.
+    pub elapsed: f64,
+}
+
+/// Subsidy at `height` under a configurable halving schedule.
+fn reward_at(config: &SimConfig, height: u64) -> u64 {
+    let halvings = height / config.halving_interval;
+    if halvings >= 64 {
+        return 0;
+    }
+    config.initial_reward >> halvings
+}
+
+/// Runs the simulation and returns the collected report.
+pub fn run(config: &SimConfig) -> SimReport {
+    let mut bits = math::MAX_BITS;
+    let mut hashrate = config.hashrate;
+    let mut supply: u64 = 0;
+    let mut fees: u64 = 0;
+    let mut elapsed = 0.0f64;
+    let mut window_time = 0.0f64;
+    let mut samples = Vec::new();
+
+    for height in 1..=config.blocks {
+        let reward = reward_at(config, height);
+        supply = supply.saturating_add(reward);
+        fees = fees.saturating_add(config.txs_per_block * config.fee_per_tx);
+
+        // Expected solve time: work per block over hashes per second.
+        let solve_time = math::block_work(bits) as f64 / hashrate.max(1.0);
+        elapsed += solve_time;
+        window_time += solve_time;
+
+        if config.sample_every > 0 && height % config.sample_every == 0 {
+            samples.push(SimSample {
+                height,
+                supply,
+                reward,
+                fees,
+                difficulty: math::difficulty(bits),
+                hashrate,
+                solve_time,
+            });
+        }
+
+        if height.is_multiple_of(config.retarget_window) {
+            bits = math::next_bits(bits, window_time as u64, config.retarget_window);
+            window_time = 0.0;
+            hashrate *= config.hashrate_growth;
+        }
+    }
+
+    SimReport {
+        samples,
+        final_supply: supply,
+        total_fees: fees,
+        final_difficulty: math::difficulty(bits),
+        elapsed,
+    }
+}
+
+/// Renders the report as a human-readable table.
+pub fn format_report(report: &SimReport) -> String {
+    let mut out = String::new();
+    out.push_str("height       supply (PALI)   reward      cum. fees   difficulty    solve (s)\n");
+    for s in &report.samples {
+        out.push_str(&format!(
+            "{:<10} {:>14.4} {:>9} {:>12} {:>12.4} {:>11.1}\n",
+            s.height,
+            s.supply as f64 / COIN as f64,
+            s.reward,
+            s.fees,
+            s.difficulty,
+            s.solve_time,
+        ));
+    }
+    out.push_str(&format!(
+        "final: supply {:.4} PALI, fees {}, difficulty {:.4}, {:.1} simulated days (target block time {}s)\n",
+        report.final_supply as f64 / COIN as f64,
+        report.total_fees,
+        report.final_difficulty,
+        report.elapsed / 86_400.0,
+        TARGET_BLOCK_TIME,
+    ));
+    out
+}
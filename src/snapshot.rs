@@ -0,0 +1,69 @@
+//! Lock-free snapshots of read-mostly chain tip state.
+//!
+//! Peer handshakes, sync checks and height RPCs used to take the
+//! blockchain mutex just to read a counter, queueing every reader
+//! behind whichever task was connecting a block at the time. A
+//! [`TipSnapshot`] freezes those tip facts into an immutable value
+//! that readers load atomically through `arc-swap` without touching
+//! the mutex at all. Writes stay funnelled where they always were:
+//! block connects serialize on the chain mutex and announce
+//! themselves through `Node::notify_tip_change`, which is the one
+//! place the snapshot is swapped.
+//!
+//! A reader can observe the previous tip for the instant between a
+//! connect and its swap — the same view it would have had by taking
+//! the lock a moment earlier, so nothing downstream can tell the
+//! difference. Anything that must be exact relative to a write (UTXO
+//! lookups, block fetches, template assembly over the mempool) keeps
+//! going through the mutex.
+
+use std::sync::Arc;
+
+use arc_swap::ArcSwap;
+
+use crate::blockchain::Blockchain;
+use crate::types::Hash256;
+
+/// Immutable copy of the tip facts hot read paths ask for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TipSnapshot {
+    pub height: u64,
+    pub best_hash: Hash256,
+    /// Cumulative work of the best chain, the quantity
+    /// `minimum_chain_work` gates mining on.
+    pub total_work: u128,
+}
+
+/// The atomically swappable cell readers share.
+#[derive(Debug)]
+pub struct TipView {
+    current: ArcSwap<TipSnapshot>,
+}
+
+impl TipView {
+    /// Captures the chain's tip as the initial snapshot.
+    pub fn new(chain: &Blockchain) -> Self {
+        TipView {
+            current: ArcSwap::from_pointee(snapshot_of(chain)),
+        }
+    }
+
+    /// The latest published snapshot: a pointer load, never a lock.
+    pub fn current(&self) -> Arc<TipSnapshot> {
+        self.current.load_full()
+    }
+
+    /// Publishes the chain's tip. The caller must hold the chain lock
+    /// so concurrent connects cannot publish out of order.
+    pub fn refresh(&self, chain: &Blockchain) {
+        self.current.store(Arc::new(snapshot_of(chain)));
+    }
+}
+
+fn snapshot_of(chain: &Blockchain) -> TipSnapshot {
+    TipSnapshot {
+        height: chain.height(),
+        best_hash: chain.best_hash(),
+        total_work: chain.state().total_work,
+    }
+}
@@ -0,0 +1,220 @@
+//! Server-sent-events fallback for real-time notifications.
+//!
+//! Not every integration can hold a WebSocket open or run a message
+//! queue; a shell script with curl cannot. The `/events` endpoint
+//! streams the node's block, transaction and address activity as
+//! standard SSE frames, one JSON object per event. Every event carries
+//! a monotonic id, so a client that reconnects with the browser-native
+//! `Last-Event-ID` header (or a `?since=` query) first replays what it
+//! missed from a bounded buffer, then rides the live feed.
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use axum::extract::{Query, State};
+use axum::response::sse::{Event, KeepAlive, Sse};
+use axum::response::IntoResponse;
+use axum::routing::get;
+use axum::Router;
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+use tokio_stream::StreamExt;
+
+use crate::notify::TxEvent;
+use crate::rpc::RpcContext;
+use crate::rpc_auth::Scope;
+use crate::types::{Address, Hash256};
+use crate::watch::Direction;
+
+/// Events kept for `Last-Event-ID` replay; a client further behind
+/// than this must resynchronize through the regular RPCs.
+pub const REPLAY_BUFFER: usize = 1_000;
+
+/// One event on the feed, already shaped for the wire.
+#[derive(Debug, Clone)]
+pub struct FeedEvent {
+    /// Monotonic id, sent as the SSE `id:` field.
+    pub id: u64,
+    /// SSE event name: `block`, `tx` or `address`.
+    pub kind: &'static str,
+    pub data: Value,
+}
+
+struct Buffer {
+    next_id: u64,
+    events: VecDeque<FeedEvent>,
+}
+
+/// Publish side of `/events`: a bounded replay buffer for reconnecting
+/// clients plus a broadcast channel for connected ones. Publishing is
+/// cheap with no subscribers, so the node feeds it unconditionally.
+pub struct EventFeed {
+    buffer: Mutex<Buffer>,
+    live: broadcast::Sender<FeedEvent>,
+}
+
+impl Default for EventFeed {
+    fn default() -> Self {
+        EventFeed::new()
+    }
+}
+
+impl EventFeed {
+    pub fn new() -> Self {
+        EventFeed {
+            buffer: Mutex::new(Buffer {
+                next_id: 0,
+                events: VecDeque::new(),
+            }),
+            live: broadcast::channel(REPLAY_BUFFER).0,
+        }
+    }
+
+    /// A new best block.
+    pub fn block(&self, hash: Hash256, height: u64) {
+        self.publish(
+            "block",
+            json!({ "hash": hex::encode(hash), "height": height }),
+        );
+    }
+
+    /// A wallet-relevant transaction changing lifecycle stage, the SSE
+    /// twin of the walletnotify hook.
+    pub fn wallet_tx(&self, txid: Hash256, event: TxEvent) {
+        self.publish(
+            "tx",
+            json!({ "txid": hex::encode(txid), "event": event.as_str() }),
+        );
+    }
+
+    /// Activity on a watched address; `height` is `None` while the
+    /// transaction sits in the mempool.
+    pub fn address(
+        &self,
+        address: Address,
+        txid: Hash256,
+        direction: Direction,
+        amount: u64,
+        height: Option<u64>,
+    ) {
+        self.publish(
+            "address",
+            json!({
+                "address": hex::encode(address),
+                "txid": hex::encode(txid),
+                "direction": direction,
+                "amount": amount,
+                "height": height,
+            }),
+        );
+    }
+
+    fn publish(&self, kind: &'static str, data: Value) {
+        let event = {
+            let mut buffer = self.buffer.lock().expect("event buffer lock poisoned");
+            let event = FeedEvent {
+                id: buffer.next_id,
+                kind,
+                data,
+            };
+            buffer.next_id += 1;
+            buffer.events.push_back(event.clone());
+            if buffer.events.len() > REPLAY_BUFFER {
+                buffer.events.pop_front();
+            }
+            event
+        };
+        // No receivers is the common case and not an error.
+        let _ = self.live.send(event);
+    }
+
+    /// Buffered events a client resuming after `last_seen` has missed.
+    /// `None` means no resume point: the client starts live.
+    pub fn replay(&self, last_seen: Option<u64>) -> Vec<FeedEvent> {
+        let Some(last_seen) = last_seen else {
+            return Vec::new();
+        };
+        let buffer = self.buffer.lock().expect("event buffer lock poisoned");
+        buffer
+            .events
+            .iter()
+            .filter(|event| event.id > last_seen)
+            .cloned()
+            .collect()
+    }
+
+    /// A receiver for everything published from this point on.
+    pub fn subscribe(&self) -> broadcast::Receiver<FeedEvent> {
+        self.live.subscribe()
+    }
+}
+
+/// The `/events` SSE route, mounted on the RPC listener.
+pub fn router(ctx: RpcContext) -> Router {
+    Router::new().route("/events", get(stream)).with_state(ctx)
+}
+
+#[derive(serde::Deserialize)]
+struct ResumeQuery {
+    /// Id of the last event already seen, for clients that hold a
+    /// cursor but cannot set headers.
+    since: Option<u64>,
+}
+
+async fn stream(
+    State(ctx): State<RpcContext>,
+    axum::Extension(scope): axum::Extension<Scope>,
+    headers: axum::http::HeaderMap,
+    Query(query): Query<ResumeQuery>,
+) -> axum::response::Response {
+    if scope < Scope::Wallet {
+        return (
+            axum::http::StatusCode::FORBIDDEN,
+            "the event stream requires wallet scope",
+        )
+            .into_response();
+    }
+    let Some(node) = &ctx.node else {
+        return (
+            axum::http::StatusCode::SERVICE_UNAVAILABLE,
+            "P2P layer is not running",
+        )
+            .into_response();
+    };
+    let feed = node.events.clone();
+    let resume = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.parse().ok())
+        .or(query.since);
+    // Subscribe before snapshotting the backlog so nothing published
+    // in between is lost; the id filter below drops the overlap.
+    let live = feed.subscribe();
+    let backlog = feed.replay(resume);
+    let mut last_sent = backlog.last().map(|event| event.id).or(resume);
+    let backlog = tokio_stream::iter(
+        backlog
+            .into_iter()
+            .map(|event| Ok::<_, std::convert::Infallible>(to_sse(&event))),
+    );
+    let live = tokio_stream::wrappers::BroadcastStream::new(live).filter_map(move |event| {
+        // A lagged reader has lost its replay window anyway; skip the
+        // error and let it pick up from the live edge.
+        let event = event.ok()?;
+        if last_sent.is_some_and(|id| event.id <= id) {
+            return None;
+        }
+        last_sent = Some(event.id);
+        Some(Ok(to_sse(&event)))
+    });
+    Sse::new(backlog.chain(live))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+fn to_sse(event: &FeedEvent) -> Event {
+    Event::default()
+        .id(event.id.to_string())
+        .event(event.kind)
+        .data(event.data.to_string())
+}
@@ -0,0 +1,164 @@
+//! Initial block download across multiple peers.
+//!
+//! The requested height range is split into fixed-size work windows
+//! assigned to different peers. Per-window progress is timestamped so a
+//! stalled peer (no blocks while others deliver) loses its window,
+//! which goes back into the pending queue for reassignment.
+
+use std::collections::{HashMap, VecDeque};
+use std::net::SocketAddr;
+use std::time::{Duration, Instant};
+
+/// Blocks per work window.
+pub const SYNC_WINDOW_SIZE: u64 = 128;
+
+/// A window is considered stalled after this long without a block,
+/// provided another peer delivered within the same period.
+pub const STALL_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// One in-flight download window.
+#[derive(Debug, Clone)]
+pub struct SyncWindow {
+    pub start: u64,
+    pub end: u64,
+    /// Next height expected within the window.
+    pub cursor: u64,
+    pub assigned_at: Instant,
+    pub last_progress: Instant,
+    /// Smoothed blocks/sec for this peer.
+    pub rate: f64,
+}
+
+/// Tracks an in-progress chain download.
+#[derive(Debug, Default)]
+pub struct SyncManager {
+    /// Height we are syncing towards (best advertised by peers).
+    target: u64,
+    /// Next unassigned height.
+    next_height: u64,
+    /// Ranges recovered from stalled peers, handed out first.
+    pending: VecDeque<(u64, u64)>,
+    windows: HashMap<SocketAddr, SyncWindow>,
+}
+
+impl SyncManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts (or extends) a sync run towards `target`, beginning at
+    /// `current + 1`. No-op if already covering the target.
+    pub fn begin(&mut self, current: u64, target: u64) {
+        if target > self.target {
+            if self.windows.is_empty() && self.pending.is_empty() {
+                self.next_height = current + 1;
+            }
+            self.target = target;
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        !self.windows.is_empty()
+            || !self.pending.is_empty()
+            || (self.target > 0 && self.next_height <= self.target)
+    }
+
+    pub fn target(&self) -> u64 {
+        self.target
+    }
+
+    /// Hands the next work window to `peer` if it has none and work
+    /// remains. Returns the (start, end) range to request.
+    pub fn assign_window(&mut self, peer: SocketAddr) -> Option<(u64, u64)> {
+        if self.windows.contains_key(&peer) {
+            return None;
+        }
+        let (start, end) = if let Some(range) = self.pending.pop_front() {
+            range
+        } else if self.next_height <= self.target {
+            let start = self.next_height;
+            let end = (start + SYNC_WINDOW_SIZE - 1).min(self.target);
+            self.next_height = end + 1;
+            (start, end)
+        } else {
+            return None;
+        };
+        let now = Instant::now();
+        self.windows.insert(
+            peer,
+            SyncWindow {
+                start,
+                end,
+                cursor: start,
+                assigned_at: now,
+                last_progress: now,
+                rate: 0.0,
+            },
+        );
+        Some((start, end))
+    }
+
+    /// Records `count` delivered blocks from `peer`. Returns true when
+    /// the peer's window is finished (and removed).
+    pub fn record_progress(&mut self, peer: SocketAddr, count: u64) -> bool {
+        let Some(window) = self.windows.get_mut(&peer) else {
+            return false;
+        };
+        let now = Instant::now();
+        let elapsed = now.duration_since(window.last_progress).as_secs_f64();
+        if elapsed > 0.0 {
+            let sample = count as f64 / elapsed;
+            window.rate = if window.rate == 0.0 {
+                sample
+            } else {
+                window.rate + 0.3 * (sample - window.rate)
+            };
+        }
+        window.cursor = (window.cursor + count).min(window.end + 1);
+        window.last_progress = now;
+        if window.cursor > window.end {
+            self.windows.remove(&peer);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Drops a disconnected peer's window back into the pending queue.
+    pub fn release(&mut self, peer: SocketAddr) {
+        if let Some(window) = self.windows.remove(&peer) {
+            if window.cursor <= window.end {
+                self.pending.push_back((window.cursor, window.end));
+            }
+        }
+    }
+
+    /// Identifies stalled peers: no progress within STALL_TIMEOUT while
+    /// at least one other window advanced in that period. Their windows
+    /// are reclaimed and the peers returned for demotion.
+    pub fn detect_stalls(&mut self) -> Vec<SocketAddr> {
+        let now = Instant::now();
+        let someone_progressing = self
+            .windows
+            .values()
+            .any(|w| now.duration_since(w.last_progress) < STALL_TIMEOUT);
+        if !someone_progressing {
+            return Vec::new();
+        }
+        let stalled: Vec<SocketAddr> = self
+            .windows
+            .iter()
+            .filter(|(_, w)| now.duration_since(w.last_progress) >= STALL_TIMEOUT)
+            .map(|(addr, _)| *addr)
+            .collect();
+        for addr in &stalled {
+            self.release(*addr);
+        }
+        stalled
+    }
+
+    /// Per-peer download rates for diagnostics.
+    pub fn rates(&self) -> Vec<(SocketAddr, f64)> {
+        self.windows.iter().map(|(a, w)| (*a, w.rate)).collect()
+    }
+}
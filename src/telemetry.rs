@@ -0,0 +1,115 @@
+//! Block acceptance telemetry.
+//!
+//! For every block accepted over relay (or mined locally) this records
+//! when it was first seen, which peer delivered it, how long
+//! validation took, and how far behind the miner's timestamp it
+//! arrived. The aggregates drive `getblockstats`, which is how
+//! operators check whether block intervals track the target and how
+//! much relay latency there is left to squeeze out. Blocks applied
+//! during bulk sync are deliberately excluded: their arrival time
+//! reflects our own download speed, not network propagation.
+
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+
+use serde::Serialize;
+
+use crate::types::{Hash256, TARGET_BLOCK_TIME};
+
+/// Cap on retained per-block records; aggregates are computed over
+/// this rolling window.
+pub const MAX_BLOCK_RECORDS: usize = 1024;
+
+/// Telemetry captured when one block was accepted.
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockRecord {
+    pub hash: Hash256,
+    pub height: u64,
+    /// Unix time the block was first seen.
+    pub first_seen: u64,
+    /// Peer that delivered the block; absent for locally mined blocks.
+    pub peer: Option<SocketAddr>,
+    /// Wall-clock time spent validating and connecting the block, in
+    /// microseconds.
+    pub validation_micros: u64,
+    /// `first_seen` minus the header timestamp. Negative when the
+    /// miner's clock ran ahead of ours.
+    pub propagation_delay_secs: i64,
+}
+
+/// Aggregates over the retained window.
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct BlockStats {
+    /// Blocks in the window.
+    pub blocks: u64,
+    pub avg_validation_micros: f64,
+    pub max_validation_micros: u64,
+    pub avg_propagation_delay_secs: f64,
+    pub max_propagation_delay_secs: i64,
+    /// Mean seconds between consecutive accepted blocks, by our clock.
+    pub avg_interval_secs: f64,
+    /// The consensus target, for comparison against `avg_interval_secs`.
+    pub target_interval_secs: u64,
+}
+
+/// Rolling window of block-acceptance records.
+#[derive(Default)]
+pub struct BlockTelemetry {
+    records: VecDeque<BlockRecord>,
+}
+
+impl BlockTelemetry {
+    pub fn new() -> Self {
+        BlockTelemetry::default()
+    }
+
+    pub fn record(&mut self, record: BlockRecord) {
+        self.records.push_back(record);
+        if self.records.len() > MAX_BLOCK_RECORDS {
+            self.records.pop_front();
+        }
+    }
+
+    /// The `count` most recently accepted blocks, newest first.
+    pub fn recent(&self, count: usize) -> Vec<BlockRecord> {
+        self.records.iter().rev().take(count).cloned().collect()
+    }
+
+    pub fn stats(&self) -> BlockStats {
+        let mut stats = BlockStats {
+            target_interval_secs: TARGET_BLOCK_TIME,
+            ..BlockStats::default()
+        };
+        if self.records.is_empty() {
+            return stats;
+        }
+        let n = self.records.len() as f64;
+        stats.blocks = self.records.len() as u64;
+        stats.avg_validation_micros =
+            self.records.iter().map(|r| r.validation_micros).sum::<u64>() as f64 / n;
+        stats.max_validation_micros = self
+            .records
+            .iter()
+            .map(|r| r.validation_micros)
+            .max()
+            .unwrap_or(0);
+        stats.avg_propagation_delay_secs =
+            self.records.iter().map(|r| r.propagation_delay_secs).sum::<i64>() as f64 / n;
+        stats.max_propagation_delay_secs = self
+            .records
+            .iter()
+            .map(|r| r.propagation_delay_secs)
+            .max()
+            .unwrap_or(0);
+        if self.records.len() > 1 {
+            let intervals: u64 = self
+                .records
+                .iter()
+                .zip(self.records.iter().skip(1))
+                .map(|(a, b)| b.first_seen.saturating_sub(a.first_seen))
+                .sum();
+            stats.avg_interval_secs = intervals as f64 / (n - 1.0);
+        }
+        stats
+    }
+}
@@ -0,0 +1,54 @@
+//! Runtime subsystem toggles.
+//!
+//! Operators can switch off parts of a running node over admin RPC —
+//! relay, mining, inbound connections — or drop it into safe mode,
+//! without a restart and without touching the parts that still work.
+//! Everything here is process state: toggles reset to their defaults
+//! on restart, which is the right failure mode for an emergency
+//! switch someone forgot to flip back.
+
+/// On/off switches for the node's optional subsystems.
+#[derive(Debug, Clone, Copy)]
+pub struct Toggles {
+    /// Accept and relay loose transactions. Off is blocksonly mode:
+    /// peers' transaction announcements are dropped, blocks still flow.
+    pub tx_relay: bool,
+    /// Hand out block templates and accept submitted blocks.
+    pub mining: bool,
+    /// Admit new inbound P2P connections (existing peers are kept).
+    pub inbound: bool,
+    /// Protective mode for suspected consensus trouble: wallet sends
+    /// are refused and RPC responses carry a warning.
+    pub safe_mode: bool,
+}
+
+impl Default for Toggles {
+    fn default() -> Self {
+        Toggles {
+            tx_relay: true,
+            mining: true,
+            inbound: true,
+            safe_mode: false,
+        }
+    }
+}
+
+impl Toggles {
+    /// Flips the named toggle, rejecting unknown names so a typo in an
+    /// emergency does not silently do nothing.
+    pub fn set(&mut self, name: &str, enabled: bool) -> Result<(), String> {
+        match name {
+            "tx_relay" => self.tx_relay = enabled,
+            "mining" => self.mining = enabled,
+            "inbound" => self.inbound = enabled,
+            "safe_mode" => self.safe_mode = enabled,
+            other => {
+                return Err(format!(
+                    "unknown toggle '{}' (tx_relay, mining, inbound, safe_mode)",
+                    other
+                ))
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+//! Encrypted peer sessions: rekeying and abbreviated resumption.
+//!
+//! A session starts from an ephemeral ECDH exchange and encrypts each
+//! direction with ChaCha20-Poly1305 under its own key. Long-lived
+//! connections ratchet: after [`REKEY_AFTER_MESSAGES`] messages or
+//! [`REKEY_AFTER_BYTES`] plaintext bytes in one direction, that
+//! direction's key is hashed forward and the old key discarded, so a
+//! key compromised later cannot decrypt earlier traffic. Both ends
+//! count identically and ratchet in lockstep — there is no rekey
+//! message on the wire.
+//!
+//! Tearing down a session yields a resumption secret. A peer
+//! reconnecting within [`RESUMPTION_TTL`] presents the ticket id and a
+//! fresh salt and both sides derive new session keys from the cached
+//! secret — one round trip and no ECDH, which matters during network
+//! churn when the same peers reconnect over and over.
+
+use std::collections::HashMap;
+
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use secp256k1::ecdh::SharedSecret;
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+use crate::hash;
+use crate::types::Hash256;
+
+/// Domain tag all transport keys are derived under.
+pub const TRANSPORT_KEY_TAG: &[u8] = b"pali-coin/transport-key/v1";
+
+/// Messages one direction may send under a single key.
+pub const REKEY_AFTER_MESSAGES: u64 = 4_096;
+
+/// Plaintext bytes one direction may send under a single key.
+pub const REKEY_AFTER_BYTES: u64 = 64 * 1024 * 1024;
+
+/// Seconds a resumption secret stays redeemable after session close.
+pub const RESUMPTION_TTL: u64 = 30 * 60;
+
+/// `SHA256(tag_hash ‖ tag_hash ‖ secret ‖ label)` — every transport key
+/// is this far from its parent, so no derived key reveals another.
+fn derive(secret: &[u8], label: &[u8]) -> Hash256 {
+    let tag_hash = hash::sha256(TRANSPORT_KEY_TAG);
+    let mut input = Vec::with_capacity(64 + secret.len() + label.len());
+    input.extend_from_slice(&tag_hash);
+    input.extend_from_slice(&tag_hash);
+    input.extend_from_slice(secret);
+    input.extend_from_slice(label);
+    hash::sha256(&input)
+}
+
+/// One direction of a session: its current key and the counters that
+/// decide when to ratchet.
+struct Direction {
+    key: Hash256,
+    /// Ratchet generation, part of the nonce.
+    generation: u32,
+    /// Messages under the current key, part of the nonce.
+    messages: u64,
+    /// Plaintext bytes under the current key.
+    bytes: u64,
+}
+
+impl Direction {
+    fn new(key: Hash256) -> Self {
+        Direction {
+            key,
+            generation: 0,
+            messages: 0,
+            bytes: 0,
+        }
+    }
+
+    /// Generation plus message counter: unique for every message ever
+    /// sent in this direction.
+    fn nonce(&self) -> Nonce {
+        let mut bytes = [0u8; 12];
+        bytes[..4].copy_from_slice(&self.generation.to_be_bytes());
+        bytes[4..].copy_from_slice(&self.messages.to_be_bytes());
+        Nonce::from(bytes)
+    }
+
+    /// Counts one message of `len` plaintext bytes and ratchets the
+    /// key forward once a limit is reached.
+    fn advance(&mut self, len: usize, limit_messages: u64, limit_bytes: u64) {
+        self.messages += 1;
+        self.bytes += len as u64;
+        if self.messages >= limit_messages || self.bytes >= limit_bytes {
+            self.key = derive(&self.key, b"ratchet");
+            self.generation += 1;
+            self.messages = 0;
+            self.bytes = 0;
+        }
+    }
+}
+
+/// The ephemeral half of a full handshake. Create one per connection
+/// attempt, send the public key, feed the peer's back in.
+pub struct Handshake {
+    secret: SecretKey,
+}
+
+impl Handshake {
+    pub fn new() -> (Handshake, PublicKey) {
+        let secp = Secp256k1::signing_only();
+        let secret = SecretKey::new(&mut rand::thread_rng());
+        let public = secret.public_key(&secp);
+        (Handshake { secret }, public)
+    }
+
+    /// Completes the handshake against the peer's ephemeral key. The
+    /// dialing side passes `initiator = true`; the roles pick which
+    /// directional key each side sends under.
+    pub fn establish(self, peer: &PublicKey, initiator: bool) -> Session {
+        let shared = SharedSecret::new(peer, &self.secret);
+        Session::from_root(&shared.secret_bytes(), initiator)
+    }
+}
+
+/// An established encrypted session.
+pub struct Session {
+    send: Direction,
+    recv: Direction,
+    /// Secret the next connection to this peer can resume from.
+    resumption: Hash256,
+    limit_messages: u64,
+    limit_bytes: u64,
+}
+
+impl Session {
+    /// Derives both directional keys and the resumption secret from a
+    /// root secret. Initiator and responder label the directions
+    /// opposite ways, so the two ends agree.
+    fn from_root(root: &[u8], initiator: bool) -> Session {
+        let initiator_key = derive(root, b"initiator");
+        let responder_key = derive(root, b"responder");
+        let (send_key, recv_key) = if initiator {
+            (initiator_key, responder_key)
+        } else {
+            (responder_key, initiator_key)
+        };
+        Session {
+            send: Direction::new(send_key),
+            recv: Direction::new(recv_key),
+            resumption: derive(root, b"resumption"),
+            limit_messages: REKEY_AFTER_MESSAGES,
+            limit_bytes: REKEY_AFTER_BYTES,
+        }
+    }
+
+    /// Overrides the rekey limits. Both ends must agree or they fall
+    /// out of sync at the first divergent ratchet.
+    pub fn set_rekey_limits(&mut self, messages: u64, bytes: u64) {
+        self.limit_messages = messages.max(1);
+        self.limit_bytes = bytes.max(1);
+    }
+
+    /// Ratchet generation of the sending direction.
+    pub fn send_generation(&self) -> u32 {
+        self.send.generation
+    }
+
+    /// Encrypts one message to the peer.
+    pub fn seal(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&self.send.key));
+        let sealed = cipher
+            .encrypt(&self.send.nonce(), plaintext)
+            .map_err(|_| "encryption failed".to_string())?;
+        self.send
+            .advance(plaintext.len(), self.limit_messages, self.limit_bytes);
+        Ok(sealed)
+    }
+
+    /// Decrypts one message from the peer. Messages must arrive in the
+    /// order they were sealed; anything reordered, replayed or
+    /// tampered with fails authentication.
+    pub fn open(&mut self, sealed: &[u8]) -> Result<Vec<u8>, String> {
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&self.recv.key));
+        let plaintext = cipher
+            .decrypt(&self.recv.nonce(), sealed)
+            .map_err(|_| "message failed authentication".to_string())?;
+        self.recv
+            .advance(plaintext.len(), self.limit_messages, self.limit_bytes);
+        Ok(plaintext)
+    }
+
+    /// The ticket id identifying this session's resumption secret;
+    /// safe to send in the clear, useless without the secret.
+    pub fn ticket_id(&self) -> Hash256 {
+        derive(&self.resumption, b"ticket-id")
+    }
+
+    /// The secret to cache for an abbreviated re-handshake; call when
+    /// the connection closes cleanly.
+    pub fn into_resumption_secret(self) -> Hash256 {
+        self.resumption
+    }
+
+    /// Abbreviated re-handshake: both sides hold `secret` from the
+    /// previous session and mix in the fresh `salt` exchanged on
+    /// reconnect, so a transcript of one resumed session says nothing
+    /// about the next.
+    pub fn resume(secret: &Hash256, salt: &[u8; 32], initiator: bool) -> Session {
+        let mut root = Vec::with_capacity(64);
+        root.extend_from_slice(secret);
+        root.extend_from_slice(salt);
+        Session::from_root(&derive(&root, b"resume"), initiator)
+    }
+}
+
+/// Server-side cache of resumption secrets for recently seen peers.
+#[derive(Default)]
+pub struct ResumptionCache {
+    secrets: HashMap<Hash256, (Hash256, u64)>,
+}
+
+impl ResumptionCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Caches a closed session's secret under its ticket id.
+    pub fn store(&mut self, session: Session, now: u64) {
+        let ticket = session.ticket_id();
+        self.secrets
+            .insert(ticket, (session.into_resumption_secret(), now));
+    }
+
+    /// Redeems a ticket. Single use — a replayed ticket id finds
+    /// nothing and the peer falls back to a full handshake.
+    pub fn take(&mut self, ticket: &Hash256, now: u64) -> Option<Hash256> {
+        self.secrets
+            .retain(|_, (_, stored)| now.saturating_sub(*stored) < RESUMPTION_TTL);
+        self.secrets.remove(ticket).map(|(secret, _)| secret)
+    }
+
+    pub fn len(&self) -> usize {
+        self.secrets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.secrets.is_empty()
+    }
+}
@@ -0,0 +1,304 @@
+//! Core chain data structures shared by the node, wallet and miner.
+
+use serde::{Deserialize, Serialize};
+
+use crate::hash;
+
+/// 32-byte double-SHA256 digest used for block and transaction ids.
+pub type Hash256 = [u8; 32];
+
+/// 20-byte address: RIPEMD160(SHA256(public key)).
+pub type Address = [u8; 20];
+
+/// Smallest unit. 1 PALI = 100_000_000 units.
+pub const COIN: u64 = 100_000_000;
+
+/// Hard supply cap, matching Bitcoin's scarcity model.
+pub const MAX_SUPPLY: u64 = 21_000_000 * COIN;
+
+/// Target spacing between blocks, in seconds (3-minute blocks).
+pub const TARGET_BLOCK_TIME: u64 = 180;
+
+/// Blocks between subsidy halvings.
+pub const HALVING_INTERVAL: u64 = 700_000;
+
+/// Initial block subsidy.
+pub const INITIAL_REWARD: u64 = 15 * COIN;
+
+/// Address that coinbase transactions spend "from".
+pub const COINBASE_ADDRESS: Address = [0u8; 20];
+
+/// Maximum bytes of arbitrary data a coinbase transaction may carry.
+/// Miners use it for pool tags, version signaling, and as extranonce
+/// space: the data is part of the transaction hash and therefore of
+/// the merkle root, so varying it varies the header search space.
+pub const MAX_COINBASE_DATA: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct OutPoint {
+    pub tx_hash: Hash256,
+    pub index: u32,
+}
+
+/// An unspent output as tracked in the chain state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UtxoEntry {
+    pub address: Address,
+    pub amount: u64,
+    pub height: u64,
+    pub is_coinbase: bool,
+}
+
+/// A single-output value transfer. `from` spends enough of its UTXOs to
+/// cover `amount + fee`; change returns to `from` automatically during
+/// UTXO application.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transaction {
+    /// Network identifier, preventing mainnet/testnet replay.
+    pub chain_id: u8,
+    /// Strictly increasing per-sender counter.
+    pub nonce: u64,
+    pub from: Address,
+    pub to: Address,
+    pub amount: u64,
+    pub fee: u64,
+    /// Free-form payload; empty for plain transfers.
+    pub data: Vec<u8>,
+    /// Opt-in replace-by-fee: while unconfirmed, a higher-fee
+    /// transaction with the same sender and nonce may replace this one.
+    pub replaceable: bool,
+    /// Earliest block height this transaction may be mined at; 0
+    /// disables the check. The wallet sets this near the current tip as
+    /// fee-sniping protection.
+    pub lock_time: u64,
+    /// ECDSA signature over the signing payload.
+    pub signature: Vec<u8>,
+    /// Sender public key (33-byte compressed SEC encoding).
+    pub public_key: Vec<u8>,
+}
+
+/// Signature scheme a transaction uses. The v1 wire layout is frozen,
+/// so the flag rides in the public key encoding instead of a field: a
+/// 33-byte SEC key means ECDSA, a 32-byte x-only key means BIP340
+/// Schnorr — possibly MuSig-aggregated; by design the chain cannot
+/// tell an aggregate from a lone key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SignatureType {
+    Ecdsa,
+    Schnorr,
+}
+
+impl Transaction {
+    pub fn hash(&self) -> Hash256 {
+        let bytes = bincode::serialize(self).expect("transaction serialization cannot fail");
+        hash::double_sha256(&bytes)
+    }
+
+    pub fn is_coinbase(&self) -> bool {
+        self.from == COINBASE_ADDRESS
+    }
+
+    /// The signature scheme this transaction carries, read off the
+    /// public key length.
+    pub fn signature_type(&self) -> SignatureType {
+        if self.public_key.len() == 32 {
+            SignatureType::Schnorr
+        } else {
+            SignatureType::Ecdsa
+        }
+    }
+
+    /// Serialized size in bytes, used for fee-rate accounting.
+    pub fn size(&self) -> usize {
+        bincode::serialized_size(self).expect("transaction serialization cannot fail") as usize
+    }
+
+    /// Fee rate in units per byte.
+    pub fn fee_rate(&self) -> f64 {
+        self.fee as f64 / self.size() as f64
+    }
+}
+
+/// First byte of a serialized v2 transaction. A v1 transaction starts
+/// with its chain id, so this value is reserved: no network may use
+/// chain id 0xFF.
+pub const TX_V2_MARKER: u8 = 0xFF;
+
+/// One recipient of a v2 transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TxOutput {
+    pub to: Address,
+    pub amount: u64,
+}
+
+/// The multi-output transaction format, not yet active on any chain
+/// (see `ChainParams::v2_tx_activation_height`). Everything but the
+/// output list matches v1, so migration is mechanical.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TransactionV2 {
+    pub chain_id: u8,
+    pub nonce: u64,
+    pub from: Address,
+    pub outputs: Vec<TxOutput>,
+    pub fee: u64,
+    pub data: Vec<u8>,
+    pub replaceable: bool,
+    pub lock_time: u64,
+    pub signature: Vec<u8>,
+    pub public_key: Vec<u8>,
+}
+
+impl TransactionV2 {
+    pub fn hash(&self) -> Hash256 {
+        hash::double_sha256(&self.wire_bytes())
+    }
+
+    /// Marker-prefixed serialization; the hash covers the marker so v1
+    /// and v2 encodings of the same transfer never share a txid.
+    pub fn wire_bytes(&self) -> Vec<u8> {
+        let mut bytes = vec![TX_V2_MARKER];
+        bytes.extend(bincode::serialize(self).expect("transaction serialization cannot fail"));
+        bytes
+    }
+
+    pub fn total_output(&self) -> Option<u64> {
+        self.outputs
+            .iter()
+            .try_fold(0u64, |sum, out| sum.checked_add(out.amount))
+    }
+
+    /// Lossless upgrade of a legacy transaction: one output.
+    pub fn from_legacy(tx: &Transaction) -> Self {
+        TransactionV2 {
+            chain_id: tx.chain_id,
+            nonce: tx.nonce,
+            from: tx.from,
+            outputs: vec![TxOutput {
+                to: tx.to,
+                amount: tx.amount,
+            }],
+            fee: tx.fee,
+            data: tx.data.clone(),
+            replaceable: tx.replaceable,
+            lock_time: tx.lock_time,
+            signature: tx.signature.clone(),
+            public_key: tx.public_key.clone(),
+        }
+    }
+}
+
+/// A transaction in either wire format. Deserialization keys off the
+/// first byte, so every v1 transaction ever recorded keeps decoding
+/// byte-identically after v2 activates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AnyTransaction {
+    V1(Transaction),
+    V2(TransactionV2),
+}
+
+impl AnyTransaction {
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, String> {
+        match bytes.first() {
+            None => Err("empty transaction".to_string()),
+            Some(&TX_V2_MARKER) => bincode::deserialize(&bytes[1..])
+                .map(AnyTransaction::V2)
+                .map_err(|e| format!("malformed v2 transaction: {}", e)),
+            Some(_) => bincode::deserialize(bytes)
+                .map(AnyTransaction::V1)
+                .map_err(|e| format!("malformed transaction: {}", e)),
+        }
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        match self {
+            AnyTransaction::V1(tx) => {
+                bincode::serialize(tx).expect("transaction serialization cannot fail")
+            }
+            AnyTransaction::V2(tx) => tx.wire_bytes(),
+        }
+    }
+
+    pub fn hash(&self) -> Hash256 {
+        match self {
+            AnyTransaction::V1(tx) => tx.hash(),
+            AnyTransaction::V2(tx) => tx.hash(),
+        }
+    }
+
+    pub fn version(&self) -> u32 {
+        match self {
+            AnyTransaction::V1(_) => 1,
+            AnyTransaction::V2(_) => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BlockHeader {
+    pub version: u32,
+    pub prev_hash: Hash256,
+    pub merkle_root: Hash256,
+    pub timestamp: u64,
+    /// Compact difficulty target (Bitcoin "bits" encoding).
+    pub bits: u32,
+    pub nonce: u64,
+    pub height: u64,
+}
+
+impl BlockHeader {
+    pub fn hash(&self) -> Hash256 {
+        let bytes = bincode::serialize(self).expect("header serialization cannot fail");
+        hash::double_sha256(&bytes)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Block {
+    pub header: BlockHeader,
+    pub transactions: Vec<Transaction>,
+}
+
+impl Block {
+    pub fn hash(&self) -> Hash256 {
+        self.header.hash()
+    }
+
+    pub fn coinbase(&self) -> Option<&Transaction> {
+        self.transactions.first().filter(|tx| tx.is_coinbase())
+    }
+
+    /// Arbitrary data the miner embedded in the coinbase, if any.
+    pub fn coinbase_data(&self) -> Option<&[u8]> {
+        self.coinbase()
+            .filter(|tx| !tx.data.is_empty())
+            .map(|tx| tx.data.as_slice())
+    }
+
+    pub fn size(&self) -> usize {
+        bincode::serialized_size(self).expect("block serialization cannot fail") as usize
+    }
+}
+
+/// Block subsidy at a given height under the halving schedule.
+pub fn block_reward(height: u64) -> u64 {
+    let halvings = height / HALVING_INTERVAL;
+    if halvings >= 64 {
+        return 0;
+    }
+    INITIAL_REWARD >> halvings
+}
+
+/// Total coinbase subsidy the emission schedule allows through
+/// `height`, excluding any genesis premine. Walks the halving eras
+/// rather than every height.
+pub fn expected_supply(height: u64) -> u64 {
+    let mut total = 0u64;
+    let mut era_start = 1u64;
+    while era_start <= height {
+        let era_end = (era_start / HALVING_INTERVAL + 1) * HALVING_INTERVAL - 1;
+        let end = era_end.min(height);
+        total += block_reward(era_start) * (end - era_start + 1);
+        era_start = end + 1;
+    }
+    total
+}
@@ -0,0 +1,240 @@
+//! Opt-in signed release channel checks.
+//!
+//! A node that falls far behind on releases can miss a consensus
+//! change and fork itself off the network. Operators who pass
+//! `-updatecheck-url` get a periodic fetch of a version manifest,
+//! verified against maintainer keys embedded in the binary, and the
+//! result — "update available", or "mandatory before height H" —
+//! surfaces through `getinfo` and the log. Nothing is ever downloaded
+//! or installed; the check is purely informational and entirely off by
+//! default.
+
+use secp256k1::{ecdsa::Signature, Message, PublicKey, Secp256k1, SecretKey};
+use serde::{Deserialize, Serialize};
+
+use crate::hash;
+use crate::node::Node;
+use crate::types::Hash256;
+
+/// Domain tag manifest signatures commit to; keeps them disjoint from
+/// transaction and alert signatures under the same keys.
+pub const UPDATE_SIGNING_DOMAIN_TAG: &[u8] = b"pali-coin/update-manifest/v1";
+
+/// Compressed maintainer release keys baked into the binary. A
+/// manifest needs [`UPDATE_KEY_THRESHOLD`] of these to count.
+pub const MAINTAINER_KEYS: &[&str] = &[
+    "0351ea732f2c4aa035ec660bb5fb0ecc440ff0455205766010a889d848afb94432",
+    "03746d8a0cca5231d481f35ebbff399b4cf93384c91ee8bdfafa822d61144e9cd1",
+];
+
+/// Distinct maintainer signatures a manifest must carry.
+pub const UPDATE_KEY_THRESHOLD: usize = 1;
+
+/// How often the manifest is re-fetched; the first check runs at
+/// startup.
+pub const UPDATE_CHECK_SECS: u64 = 24 * 3600;
+
+/// What the maintainers publish about the latest release.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VersionManifest {
+    /// Latest released version, `major.minor.patch`.
+    pub version: String,
+    /// Height by which nodes must run `version` to stay in consensus;
+    /// zero for a routine release.
+    #[serde(default)]
+    pub mandatory_before_height: u64,
+    /// Where to read the release notes.
+    #[serde(default)]
+    pub url: String,
+}
+
+/// A manifest plus the maintainer signatures vouching for it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignedManifest {
+    pub manifest: VersionManifest,
+    /// Compact ECDSA signatures, one per cooperating maintainer key.
+    pub signatures: Vec<Vec<u8>>,
+}
+
+/// Canonical signing payload: every field in declaration order,
+/// integers big-endian, strings length-prefixed — same discipline as
+/// the alert payload.
+fn signing_payload(manifest: &VersionManifest) -> Vec<u8> {
+    let mut out = Vec::with_capacity(24 + manifest.version.len() + manifest.url.len());
+    out.extend_from_slice(&(manifest.version.len() as u64).to_be_bytes());
+    out.extend_from_slice(manifest.version.as_bytes());
+    out.extend_from_slice(&manifest.mandatory_before_height.to_be_bytes());
+    out.extend_from_slice(&(manifest.url.len() as u64).to_be_bytes());
+    out.extend_from_slice(manifest.url.as_bytes());
+    out
+}
+
+/// The digest manifest signatures commit to (tagged like alert and
+/// transaction signing hashes: `SHA256(tag_hash ‖ tag_hash ‖ payload)`).
+pub fn signing_hash(manifest: &VersionManifest) -> Hash256 {
+    let tag_hash = hash::sha256(UPDATE_SIGNING_DOMAIN_TAG);
+    let payload = signing_payload(manifest);
+    let mut input = Vec::with_capacity(64 + payload.len());
+    input.extend_from_slice(&tag_hash);
+    input.extend_from_slice(&tag_hash);
+    input.extend_from_slice(&payload);
+    hash::sha256(&input)
+}
+
+impl SignedManifest {
+    pub fn new(manifest: VersionManifest) -> Self {
+        SignedManifest {
+            manifest,
+            signatures: Vec::new(),
+        }
+    }
+
+    /// Appends one maintainer's signature over the manifest.
+    pub fn sign(&mut self, key: &SecretKey) {
+        let secp = Secp256k1::signing_only();
+        let msg = Message::from_digest(signing_hash(&self.manifest));
+        let sig = secp.sign_ecdsa(&msg, key);
+        self.signatures.push(sig.serialize_compact().to_vec());
+    }
+
+    /// Checks the manifest against `keys`: at least `threshold`
+    /// distinct keys must have signed it. Production callers pass
+    /// [`MAINTAINER_KEYS`] and [`UPDATE_KEY_THRESHOLD`].
+    pub fn verify(&self, keys: &[&str], threshold: usize) -> Result<(), String> {
+        if keys.is_empty() || threshold == 0 {
+            return Err("no maintainer keys configured".to_string());
+        }
+        let keys: Vec<PublicKey> = keys
+            .iter()
+            .map(|hex| {
+                hex::decode(hex)
+                    .map_err(|e| format!("bad maintainer key '{}': {}", hex, e))
+                    .and_then(|bytes| {
+                        PublicKey::from_slice(&bytes)
+                            .map_err(|e| format!("bad maintainer key '{}': {}", hex, e))
+                    })
+            })
+            .collect::<Result<_, _>>()?;
+
+        let secp = Secp256k1::verification_only();
+        let msg = Message::from_digest(signing_hash(&self.manifest));
+        let mut signed = vec![false; keys.len()];
+        for bytes in &self.signatures {
+            let Ok(sig) = Signature::from_compact(bytes) else {
+                continue;
+            };
+            for (i, key) in keys.iter().enumerate() {
+                if !signed[i] && secp.verify_ecdsa(&msg, &sig, key).is_ok() {
+                    signed[i] = true;
+                    break;
+                }
+            }
+        }
+        let count = signed.iter().filter(|s| **s).count();
+        if count < threshold.min(keys.len()) {
+            return Err(format!(
+                "manifest carries {} of {} required signatures",
+                count,
+                threshold.min(keys.len())
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Parses `major.minor.patch`; the tuple ordering is the SemVer
+/// precedence ordering.
+pub fn parse_version(version: &str) -> Result<(u64, u64, u64), String> {
+    let mut parts = version.split('.');
+    let mut next = |name: &str| {
+        parts
+            .next()
+            .and_then(|p| p.parse::<u64>().ok())
+            .ok_or_else(|| format!("bad {} in version '{}'", name, version))
+    };
+    let parsed = (next("major")?, next("minor")?, next("patch")?);
+    if parts.next().is_some() {
+        return Err(format!("bad version '{}'", version));
+    }
+    Ok(parsed)
+}
+
+/// The version this binary was built as.
+pub fn running_version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+/// The latest verified check result, held on the node for `getinfo`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpdateStatus {
+    /// Latest release per the manifest.
+    pub latest: String,
+    /// True when `latest` is newer than the running version.
+    pub update_available: bool,
+    /// Set when an available update is consensus-mandatory by a height.
+    pub mandatory_before_height: Option<u64>,
+    /// Release notes URL from the manifest.
+    pub url: String,
+}
+
+impl UpdateStatus {
+    /// Compares a verified manifest against the running version.
+    pub fn from_manifest(manifest: &VersionManifest) -> Result<Self, String> {
+        let latest = parse_version(&manifest.version)?;
+        let running = parse_version(running_version())?;
+        let update_available = latest > running;
+        Ok(UpdateStatus {
+            latest: manifest.version.clone(),
+            update_available,
+            mandatory_before_height: (update_available && manifest.mandatory_before_height > 0)
+                .then_some(manifest.mandatory_before_height),
+            url: manifest.url.clone(),
+        })
+    }
+}
+
+/// Fetches, verifies and applies one manifest check.
+async fn check_once(node: &Node, client: &reqwest::Client, url: &str) -> Result<(), String> {
+    let signed: SignedManifest = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("manifest fetch failed: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("malformed manifest: {}", e))?;
+    signed.verify(MAINTAINER_KEYS, UPDATE_KEY_THRESHOLD)?;
+    let status = UpdateStatus::from_manifest(&signed.manifest)?;
+    if status.update_available {
+        match status.mandatory_before_height {
+            Some(height) => log::error!(
+                "release {} is mandatory before height {} (running {}): {}",
+                status.latest,
+                height,
+                running_version(),
+                status.url
+            ),
+            None => log::warn!(
+                "release {} is available (running {}): {}",
+                status.latest,
+                running_version(),
+                status.url
+            ),
+        }
+    }
+    *node.update.lock().expect("update lock poisoned") = Some(status);
+    Ok(())
+}
+
+/// Background task behind `-updatecheck-url`: one check at startup,
+/// then every [`UPDATE_CHECK_SECS`].
+pub async fn check_loop(node: std::sync::Arc<Node>, url: String) {
+    let client = reqwest::Client::new();
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(UPDATE_CHECK_SECS));
+    loop {
+        interval.tick().await;
+        if let Err(e) = check_once(&node, &client, &url).await {
+            log::warn!("update check failed: {}", e);
+        }
+    }
+}
@@ -0,0 +1,796 @@
+//! Key management, transaction construction and spend policy.
+
+use std::collections::{HashSet, VecDeque};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use argon2::Argon2;
+use chacha20poly1305::aead::{Aead, KeyInit};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use rand::RngCore;
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use serde::{Deserialize, Serialize};
+
+use crate::crypto;
+use crate::error::{WalletError, WalletErrorKind};
+use crate::hash;
+use crate::types::{Address, Hash256, OutPoint, Transaction};
+
+/// Argon2id parameters used to derive the wallet file key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KdfParams {
+    pub memory_kib: u32,
+    pub iterations: u32,
+    pub parallelism: u32,
+}
+
+impl Default for KdfParams {
+    fn default() -> Self {
+        KdfParams {
+            memory_kib: 128 * 1024,
+            iterations: 3,
+            parallelism: 1,
+        }
+    }
+}
+
+/// Wall-clock time one key derivation should take after auto-tuning.
+pub const TARGET_DERIVE_MILLIS: u64 = 500;
+
+/// Security floor for auto-tuned memory, regardless of how slow the
+/// machine is.
+pub const MIN_KDF_MEMORY_KIB: u32 = 16 * 1024;
+
+impl KdfParams {
+    /// Benchmarks this machine and picks parameters whose single
+    /// derivation lands near [`TARGET_DERIVE_MILLIS`]. The default
+    /// 128 MiB takes many seconds on low-end hardware; scaling memory
+    /// to the machine keeps unlocks tolerable without dropping below
+    /// the security floor.
+    pub fn auto_tune() -> KdfParams {
+        let probe = KdfParams {
+            memory_kib: MIN_KDF_MEMORY_KIB,
+            iterations: 3,
+            parallelism: 1,
+        };
+        let started = std::time::Instant::now();
+        if derive_key("auto-tune probe", &[0u8; 16], &probe).is_err() {
+            return KdfParams::default();
+        }
+        let elapsed_ms = (started.elapsed().as_secs_f64() * 1000.0).max(1.0);
+        // Argon2 cost grows roughly linearly with memory at fixed
+        // iteration count.
+        let scaled = MIN_KDF_MEMORY_KIB as f64 * (TARGET_DERIVE_MILLIS as f64 / elapsed_ms);
+        KdfParams {
+            memory_kib: (scaled as u32).clamp(MIN_KDF_MEMORY_KIB, KdfParams::default().memory_kib),
+            iterations: 3,
+            parallelism: 1,
+        }
+    }
+
+    /// Auto-tuned parameters, benchmarked once per process.
+    pub fn tuned() -> KdfParams {
+        static TUNED: std::sync::OnceLock<KdfParams> = std::sync::OnceLock::new();
+        TUNED.get_or_init(KdfParams::auto_tune).clone()
+    }
+}
+
+/// On-disk wallet file: the private key encrypted under a password.
+#[derive(Debug, Serialize, Deserialize)]
+struct WalletFile {
+    version: u32,
+    kdf: KdfParams,
+    salt: [u8; 16],
+    nonce: [u8; 12],
+    ciphertext: Vec<u8>,
+    /// v2: the file key is additionally wrapped by this machine's
+    /// platform-keystore secret, so the password alone is not enough.
+    machine_bound: bool,
+}
+
+/// The v1 layout, before machine binding existed. Still readable; v1
+/// files are never machine-bound.
+#[derive(Debug, Deserialize)]
+struct WalletFileV1 {
+    #[allow(dead_code)]
+    version: u32,
+    kdf: KdfParams,
+    salt: [u8; 16],
+    nonce: [u8; 12],
+    ciphertext: Vec<u8>,
+}
+
+const WALLET_FILE_VERSION: u32 = 2;
+
+/// Seconds in the rolling spend-cap window.
+const SPEND_WINDOW_SECS: u64 = 24 * 60 * 60;
+
+/// Everything needed to build one outgoing transaction.
+#[derive(Debug, Clone)]
+pub struct SendRequest {
+    pub to: Address,
+    pub amount: u64,
+    pub fee: u64,
+    pub nonce: u64,
+    pub chain_id: u8,
+    /// Signal replace-by-fee while unconfirmed.
+    pub replaceable: bool,
+    /// Current chain tip, used for the anti-fee-sniping locktime.
+    pub tip_height: u64,
+}
+
+/// Callback invoked for spends above the approval threshold.
+pub type ApprovalHook = Box<dyn Fn(&Transaction) -> bool + Send + Sync>;
+
+/// Limits applied to outgoing transactions before signing. All fields
+/// are optional; an empty policy permits everything.
+#[derive(Default)]
+pub struct SpendPolicy {
+    /// Maximum value (amount + fee) of a single transaction.
+    pub max_per_tx: Option<u64>,
+    /// Maximum total value spent within any rolling 24h window.
+    pub daily_cap: Option<u64>,
+    /// When set, sends are only permitted to these addresses.
+    pub allowlist: Option<HashSet<Address>>,
+    /// Transactions at or above this value must pass the approval hook.
+    pub approval_threshold: Option<u64>,
+    /// Called for transactions above `approval_threshold`; returning
+    /// false rejects the spend. Typical implementations prompt for a
+    /// second password or TOTP code.
+    pub approval_hook: Option<ApprovalHook>,
+}
+
+impl std::fmt::Debug for SpendPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpendPolicy")
+            .field("max_per_tx", &self.max_per_tx)
+            .field("daily_cap", &self.daily_cap)
+            .field("allowlist", &self.allowlist.as_ref().map(|a| a.len()))
+            .field("approval_threshold", &self.approval_threshold)
+            .field("approval_hook", &self.approval_hook.is_some())
+            .finish()
+    }
+}
+
+/// Default unlock session length.
+pub const DEFAULT_UNLOCK_SECS: u64 = 300;
+
+/// Holds the private key either in plaintext (fresh, never-encrypted
+/// wallets) or encrypted in memory with a time-limited unlock session.
+enum KeyVault {
+    /// Unencrypted key; used only until a passphrase is set.
+    Plain(SecretKey),
+    /// Key encrypted under the wallet passphrase. `session` caches the
+    /// decrypted key until the deadline, after which the wallet is
+    /// effectively locked again.
+    Encrypted {
+        kdf: KdfParams,
+        salt: [u8; 16],
+        nonce: [u8; 12],
+        ciphertext: Vec<u8>,
+        session: Option<(SecretKey, std::time::Instant)>,
+    },
+}
+
+pub struct Wallet {
+    vault: KeyVault,
+    public_key: PublicKey,
+    address: Address,
+    policy: SpendPolicy,
+    /// Recent outgoing spends (unix time, amount + fee) for the rolling cap.
+    recent_spends: VecDeque<(u64, u64)>,
+    /// Argon2-derived key cached for the unlock session, keyed by the
+    /// salt and a hash of the password it came from, so saves and
+    /// re-unlocks during a session skip the expensive derivation.
+    /// Cleared whenever the wallet locks.
+    derived_key_cache: Option<([u8; 16], [u8; 32], [u8; 32])>,
+}
+
+impl Wallet {
+    /// Generates a wallet with a fresh random key.
+    pub fn new() -> Self {
+        let secp = Secp256k1::new();
+        let (secret_key, public_key) = secp.generate_keypair(&mut rand::thread_rng());
+        Self::from_keypair(secret_key, public_key)
+    }
+
+    fn from_keypair(secret_key: SecretKey, public_key: PublicKey) -> Self {
+        let address = hash::pubkey_to_address(&public_key.serialize());
+        Wallet {
+            vault: KeyVault::Plain(secret_key),
+            public_key,
+            address,
+            policy: SpendPolicy::default(),
+            recent_spends: VecDeque::new(),
+            derived_key_cache: None,
+        }
+    }
+
+    /// Derives the plain file key, consulting the session cache first.
+    fn derive_cached(
+        &mut self,
+        password: &str,
+        salt: &[u8; 16],
+        kdf: &KdfParams,
+    ) -> Result<[u8; 32], WalletError> {
+        let password_hash = hash::sha256(password.as_bytes());
+        if let Some((cached_salt, cached_password, key)) = &self.derived_key_cache {
+            if cached_salt == salt && cached_password == &password_hash {
+                return Ok(*key);
+            }
+        }
+        let key = derive_key(password, salt, kdf)?;
+        self.derived_key_cache = Some((*salt, password_hash, key));
+        Ok(key)
+    }
+
+    /// Re-encrypts the in-memory key under `password`. After this call
+    /// the plaintext key only exists during unlock sessions.
+    pub fn encrypt_in_memory(&mut self, password: &str) -> Result<(), WalletError> {
+        let secret_key = self.require_key()?;
+        let kdf = KdfParams::tuned();
+        let mut salt = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut salt);
+        let mut nonce = [0u8; 12];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let key = self.derive_cached(password, &salt, &kdf)?;
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), secret_key.secret_bytes().as_ref())
+            .map_err(|_| WalletError::new(WalletErrorKind::Corrupt, "in-memory key encryption failed"))?;
+        self.vault = KeyVault::Encrypted {
+            kdf,
+            salt,
+            nonce,
+            ciphertext,
+            session: None,
+        };
+        Ok(())
+    }
+
+    /// Starts an unlock session: decrypts the key into memory until
+    /// `timeout_secs` elapses (walletpassphrase semantics).
+    pub fn unlock(&mut self, password: &str, timeout_secs: u64) -> Result<(), WalletError> {
+        let (kdf, salt) = match &self.vault {
+            KeyVault::Plain(_) => return Ok(()),
+            KeyVault::Encrypted { kdf, salt, .. } => (kdf.clone(), *salt),
+        };
+        let key = self.derive_cached(password, &salt, &kdf)?;
+        match &mut self.vault {
+            KeyVault::Plain(_) => Ok(()),
+            KeyVault::Encrypted {
+                nonce,
+                ciphertext,
+                session,
+                ..
+            } => {
+                let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+                let plaintext = cipher
+                    .decrypt(Nonce::from_slice(nonce), ciphertext.as_ref())
+                    .map_err(|_| WalletError::new(WalletErrorKind::BadPassword, "wrong passphrase"))?;
+                let secret_key = SecretKey::from_slice(&plaintext)
+                    .map_err(|e| WalletError::with_source(WalletErrorKind::Corrupt, "corrupt private key", e))?;
+                let deadline =
+                    std::time::Instant::now() + std::time::Duration::from_secs(timeout_secs);
+                *session = Some((secret_key, deadline));
+                Ok(())
+            }
+        }
+    }
+
+    /// Ends any unlock session immediately (walletlock semantics).
+    pub fn lock(&mut self) {
+        if let KeyVault::Encrypted { session, .. } = &mut self.vault {
+            *session = None;
+        }
+        self.derived_key_cache = None;
+    }
+
+    /// True when signing would currently fail. Expired sessions are
+    /// cleared as a side effect, implementing the auto-lock timeout.
+    pub fn is_locked(&mut self) -> bool {
+        self.require_key().is_err()
+    }
+
+    /// Returns the signing key, enforcing the session deadline.
+    fn require_key(&mut self) -> Result<SecretKey, WalletError> {
+        let result = match &mut self.vault {
+            KeyVault::Plain(key) => Ok(*key),
+            KeyVault::Encrypted { session, .. } => match session {
+                Some((key, deadline)) if std::time::Instant::now() < *deadline => Ok(*key),
+                _ => {
+                    *session = None;
+                    Err(WalletError::new(WalletErrorKind::Locked, "wallet is locked; call unlock first"))
+                }
+            },
+        };
+        // An expired session takes the cached derived key with it.
+        if result.is_err() {
+            self.derived_key_cache = None;
+        }
+        result
+    }
+
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// Installs a spend policy enforced by `create_transaction`.
+    pub fn set_policy(&mut self, policy: SpendPolicy) {
+        self.policy = policy;
+    }
+
+    pub fn policy(&self) -> &SpendPolicy {
+        &self.policy
+    }
+
+    /// Builds and signs a transaction after the spend policy approves
+    /// it. The anti-fee-sniping locktime derives from `tip_height`.
+    pub fn create_transaction(&mut self, request: SendRequest) -> Result<Transaction, WalletError> {
+        let SendRequest {
+            to,
+            amount,
+            fee,
+            nonce,
+            chain_id,
+            replaceable,
+            tip_height,
+        } = request;
+        let mut tx = Transaction {
+            chain_id,
+            nonce,
+            from: self.address,
+            to,
+            amount,
+            fee,
+            data: Vec::new(),
+            replaceable,
+            lock_time: anti_fee_sniping_locktime(tip_height),
+            signature: Vec::new(),
+            public_key: Vec::new(),
+        };
+        let secret_key = self.require_key()?;
+        self.check_policy(&tx)?;
+        crypto::sign_transaction(&mut tx, &secret_key)
+            .map_err(|e| WalletError::new(WalletErrorKind::InvalidRequest, e))?;
+        self.record_spend(amount + fee);
+        Ok(tx)
+    }
+
+    /// Signs an externally built transaction spending from this
+    /// wallet, subject to the same policy checks as
+    /// [`create_transaction`](Self::create_transaction).
+    pub fn sign_transaction(&mut self, tx: &mut Transaction) -> Result<(), WalletError> {
+        if tx.from != self.address {
+            return Err(WalletError::new(WalletErrorKind::InvalidRequest, "transaction does not spend from this wallet"));
+        }
+        let secret_key = self.require_key()?;
+        self.check_policy(tx)?;
+        crypto::sign_transaction(tx, &secret_key)
+            .map_err(|e| WalletError::new(WalletErrorKind::InvalidRequest, e))?;
+        self.record_spend(tx.amount + tx.fee);
+        Ok(())
+    }
+
+    /// Signs a proof-of-reserves attestation over the given coins (see
+    /// the reserves module). Reads the key but moves nothing, so the
+    /// spend policy does not apply — only the unlock session does.
+    pub fn prove_reserves(
+        &mut self,
+        block_hash: Hash256,
+        height: u64,
+        claim: &str,
+        utxos: &[(OutPoint, u64)],
+    ) -> Result<crate::reserves::ReserveProof, WalletError> {
+        let secret_key = self.require_key()?;
+        Ok(crate::reserves::sign_reserve_proof(
+            &secret_key,
+            block_hash,
+            height,
+            claim,
+            utxos,
+        ))
+    }
+
+    /// Exports the account-level extended public key (see the audit
+    /// module): the public key plus a chain code from which an auditor
+    /// derives every receive address read-only. Reads the key — the
+    /// chain code derives from it — but reveals nothing that can spend.
+    pub fn export_account_xpub(&mut self) -> Result<crate::audit::AccountXpub, WalletError> {
+        let secret_key = self.require_key()?;
+        Ok(crate::audit::AccountXpub {
+            version: crate::audit::XPUB_VERSION,
+            public_key: self.public_key.serialize().to_vec(),
+            chain_code: crate::audit::chain_code_for(&secret_key),
+        })
+    }
+
+    /// Rebuilds a stuck transaction with a higher fee, reusing the same
+    /// nonce and destination so it replaces the original in mempools.
+    pub fn bump_fee(&mut self, original: &Transaction, new_fee: u64) -> Result<Transaction, WalletError> {
+        if original.from != self.address {
+            return Err(WalletError::new(WalletErrorKind::InvalidRequest, "transaction was not sent by this wallet"));
+        }
+        if !original.replaceable {
+            return Err(WalletError::new(WalletErrorKind::InvalidRequest, "original transaction does not signal replaceability"));
+        }
+        if new_fee <= original.fee {
+            return Err(WalletError::new(
+                WalletErrorKind::InvalidRequest,
+                format!(
+                    "new fee {} must exceed original fee {}",
+                    new_fee, original.fee
+                ),
+            ));
+        }
+        let mut tx = Transaction {
+            fee: new_fee,
+            signature: Vec::new(),
+            public_key: Vec::new(),
+            ..original.clone()
+        };
+        let secret_key = self.require_key()?;
+        self.check_policy(&tx)?;
+        crypto::sign_transaction(&mut tx, &secret_key)
+            .map_err(|e| WalletError::new(WalletErrorKind::InvalidRequest, e))?;
+        // Only the fee delta counts against the rolling cap; the
+        // original spend was already recorded.
+        self.record_spend(new_fee - original.fee);
+        Ok(tx)
+    }
+
+    /// Applies every configured policy rule to an unsigned transaction.
+    fn check_policy(&mut self, tx: &Transaction) -> Result<(), WalletError> {
+        let total = tx
+            .amount
+            .checked_add(tx.fee)
+            .ok_or_else(|| WalletError::new(WalletErrorKind::InvalidRequest, "amount + fee overflows"))?;
+        if let Some(max) = self.policy.max_per_tx {
+            if total > max {
+                return Err(WalletError::new(
+                    WalletErrorKind::Policy,
+                    format!(
+                        "transaction value {} exceeds per-transaction limit {}",
+                        total, max
+                    ),
+                ));
+            }
+        }
+        if let Some(allowlist) = &self.policy.allowlist {
+            if !allowlist.contains(&tx.to) {
+                return Err(WalletError::new(
+                    WalletErrorKind::Policy,
+                    format!("destination {} is not on the allowlist", hex::encode(tx.to)),
+                ));
+            }
+        }
+        if let Some(cap) = self.policy.daily_cap {
+            let spent = self.spent_in_window();
+            if spent + total > cap {
+                return Err(WalletError::new(
+                    WalletErrorKind::Policy,
+                    format!(
+                        "spend of {} would exceed 24h cap {} ({} already spent)",
+                        total, cap, spent
+                    ),
+                ));
+            }
+        }
+        if let Some(threshold) = self.policy.approval_threshold {
+            if total >= threshold {
+                let hook = self
+                    .policy
+                    .approval_hook
+                    .as_ref()
+                    .ok_or_else(|| {
+                        WalletError::new(
+                            WalletErrorKind::Policy,
+                            "approval required but no approval hook configured",
+                        )
+                    })?;
+                if !hook(tx) {
+                    return Err(WalletError::new(WalletErrorKind::Policy, "spend rejected by approval hook"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Total spent inside the rolling window, pruning expired entries.
+    fn spent_in_window(&mut self) -> u64 {
+        let now = unix_now();
+        while let Some((t, _)) = self.recent_spends.front() {
+            if now.saturating_sub(*t) > SPEND_WINDOW_SECS {
+                self.recent_spends.pop_front();
+            } else {
+                break;
+            }
+        }
+        self.recent_spends.iter().map(|(_, v)| v).sum()
+    }
+
+    fn record_spend(&mut self, total: u64) {
+        self.recent_spends.push_back((unix_now(), total));
+    }
+
+    /// Encrypts the private key under `password` and writes the wallet
+    /// file. Requires the wallet to be unlocked.
+    pub fn save_to_file<P: AsRef<Path>>(&mut self, path: P, password: &str) -> Result<(), WalletError> {
+        self.save_to_file_bound(path, password, None)
+    }
+
+    /// Saves the wallet, optionally wrapping the file key with a
+    /// machine secret so decryption requires both the password and the
+    /// hardware-backed secret (see `keystore::machine_secret`).
+    pub fn save_to_file_bound<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+        password: &str,
+        binding: Option<&[u8; 32]>,
+    ) -> Result<(), WalletError> {
+        let secret_key = self.require_key()?;
+        // Reuse the vault's parameters and salt so the session cache
+        // applies; only a never-encrypted wallet tunes fresh ones.
+        let (kdf, salt) = match &self.vault {
+            KeyVault::Encrypted { kdf, salt, .. } => (kdf.clone(), *salt),
+            KeyVault::Plain(_) => {
+                let mut salt = [0u8; 16];
+                rand::thread_rng().fill_bytes(&mut salt);
+                (KdfParams::tuned(), salt)
+            }
+        };
+        let key = mix_binding(self.derive_cached(password, &salt, &kdf)?, binding);
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+        let mut nonce = [0u8; 12];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = cipher
+            .encrypt(Nonce::from_slice(&nonce), secret_key.secret_bytes().as_ref())
+            .map_err(|_| WalletError::new(WalletErrorKind::Corrupt, "wallet encryption failed"))?;
+        let file = WalletFile {
+            version: WALLET_FILE_VERSION,
+            kdf,
+            salt,
+            nonce,
+            ciphertext,
+            machine_bound: binding.is_some(),
+        };
+        let bytes = bincode::serialize(&file).expect("wallet file serialization cannot fail");
+        std::fs::write(path, bytes)
+            .map_err(|e| WalletError::with_source(WalletErrorKind::Io, "failed to write wallet file", e))
+    }
+
+    /// Whether a wallet file on disk requires the machine secret (or
+    /// its recovery code) in addition to the password.
+    pub fn file_is_machine_bound<P: AsRef<Path>>(path: P) -> Result<bool, WalletError> {
+        let bytes =
+            std::fs::read(path)
+            .map_err(|e| WalletError::with_source(WalletErrorKind::Io, "failed to read wallet file", e))?;
+        Ok(parse_wallet_file(&bytes)?.machine_bound)
+    }
+
+    /// Loads and decrypts a wallet file. Fails on machine-bound files;
+    /// those need `from_file_bound` with the secret or recovery code.
+    pub fn from_file<P: AsRef<Path>>(path: P, password: &str) -> Result<Self, WalletError> {
+        Self::from_file_bound(path, password, None)
+    }
+
+    /// Loads a wallet file, supplying the machine secret (or the
+    /// recovery code printed when binding was enabled) for bound files.
+    pub fn from_file_bound<P: AsRef<Path>>(
+        path: P,
+        password: &str,
+        binding: Option<&[u8; 32]>,
+    ) -> Result<Self, WalletError> {
+        let bytes =
+            std::fs::read(path)
+            .map_err(|e| WalletError::with_source(WalletErrorKind::Io, "failed to read wallet file", e))?;
+        let file = parse_wallet_file(&bytes)?;
+        if file.version > WALLET_FILE_VERSION {
+            return Err(WalletError::new(
+                WalletErrorKind::Corrupt,
+                format!("unsupported wallet file version {}", file.version),
+            ));
+        }
+        if file.machine_bound && binding.is_none() {
+            return Err(WalletError::new(
+                WalletErrorKind::BadPassword,
+                "wallet is machine-bound; this machine's secret or the recovery code is required",
+            ));
+        }
+        let binding = if file.machine_bound { binding } else { None };
+        let plain_key = derive_key(password, &file.salt, &file.kdf)?;
+        let key = mix_binding(plain_key, binding);
+        let cipher = ChaCha20Poly1305::new(Key::from_slice(&key));
+        let plaintext = cipher
+            .decrypt(Nonce::from_slice(&file.nonce), file.ciphertext.as_ref())
+            .map_err(|_| WalletError::new(WalletErrorKind::BadPassword, "wrong password or corrupt wallet file"))?;
+        let secret_key = SecretKey::from_slice(&plaintext)
+            .map_err(|e| WalletError::with_source(WalletErrorKind::Corrupt, "corrupt private key", e))?;
+        let public_key = PublicKey::from_secret_key(&Secp256k1::new(), &secret_key);
+        let address = hash::pubkey_to_address(&public_key.serialize());
+        // The key stays encrypted in memory; loading grants a default
+        // unlock session since the password was just presented. The
+        // in-memory vault is always password-only — the machine secret
+        // wraps the file, not the running wallet — so re-wrap bound
+        // ciphertext under the plain derived key.
+        let (nonce, ciphertext) = if binding.is_some() {
+            let cipher = ChaCha20Poly1305::new(Key::from_slice(&plain_key));
+            let mut nonce = [0u8; 12];
+            rand::thread_rng().fill_bytes(&mut nonce);
+            let ciphertext = cipher
+                .encrypt(Nonce::from_slice(&nonce), secret_key.secret_bytes().as_ref())
+                .map_err(|_| WalletError::new(WalletErrorKind::Corrupt, "in-memory key encryption failed"))?;
+            (nonce, ciphertext)
+        } else {
+            (file.nonce, file.ciphertext)
+        };
+        let deadline =
+            std::time::Instant::now() + std::time::Duration::from_secs(DEFAULT_UNLOCK_SECS);
+        Ok(Wallet {
+            vault: KeyVault::Encrypted {
+                kdf: file.kdf,
+                salt: file.salt,
+                nonce,
+                ciphertext,
+                session: Some((secret_key, deadline)),
+            },
+            public_key,
+            address,
+            policy: SpendPolicy::default(),
+            recent_spends: VecDeque::new(),
+            derived_key_cache: Some((file.salt, hash::sha256(password.as_bytes()), plain_key)),
+        })
+    }
+}
+
+impl Default for Wallet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Locktime discouraging fee sniping: the next block height, walked
+/// back a random distance 10% of the time (matching Bitcoin Core) so
+/// transactions don't fingerprint the wallet.
+pub fn anti_fee_sniping_locktime(tip_height: u64) -> u64 {
+    let mut lock_time = tip_height;
+    let mut rng = rand::thread_rng();
+    if rand::Rng::gen_ratio(&mut rng, 1, 10) {
+        lock_time = lock_time.saturating_sub(rand::Rng::gen_range(&mut rng, 0..100));
+    }
+    lock_time
+}
+
+/// Summary of a dust consolidation before anything is signed: how many
+/// outputs fold into one and what the merge costs. Serialized as-is by
+/// the wallet's `consolidate --dry-run`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsolidationPlan {
+    /// Mature outputs the transaction will consume.
+    pub utxos: usize,
+    /// How many of them sit at or below the dust threshold.
+    pub dust_utxos: usize,
+    /// Sum of the consumed outputs.
+    pub spendable: u64,
+    pub fee: u64,
+    /// Amount arriving back in the single merged output.
+    pub amount: u64,
+}
+
+/// Plans merging `utxo_amounts` (the wallet's mature outputs) into one
+/// output at the given flat fee. Fails when there is nothing worth
+/// merging or the fee would eat the balance.
+pub fn plan_consolidation(
+    utxo_amounts: &[u64],
+    dust_threshold: u64,
+    fee: u64,
+) -> Result<ConsolidationPlan, WalletError> {
+    if utxo_amounts.len() < 2 {
+        return Err(WalletError::new(
+            WalletErrorKind::InvalidRequest,
+            "nothing to consolidate: fewer than two mature outputs",
+        ));
+    }
+    let spendable: u64 = utxo_amounts.iter().sum();
+    if spendable <= fee {
+        return Err(WalletError::new(
+            WalletErrorKind::InsufficientFunds,
+            format!("balance {} does not cover the {} fee", spendable, fee),
+        ));
+    }
+    Ok(ConsolidationPlan {
+        utxos: utxo_amounts.len(),
+        dust_utxos: utxo_amounts
+            .iter()
+            .filter(|amount| **amount <= dust_threshold)
+            .count(),
+        spendable,
+        fee,
+        amount: spendable - fee,
+    })
+}
+
+/// One credited deposit as `pali-wallet await` sees it after decoding
+/// the node's deposit-event feed.
+#[derive(Debug, Clone)]
+pub struct CreditedPayment {
+    pub txid: Hash256,
+    /// Address the payment arrived on.
+    pub address: Address,
+    pub amount: u64,
+    /// Confirmations the deposit had when the node credited it.
+    pub confirmations: u64,
+}
+
+/// Whether a credited payment settles an `await`: the watched address,
+/// the confirmation target met, and — when a minimum is asked for — at
+/// least that amount. Overpaying settles; underpaying keeps the wait
+/// going, since partial payments are a dispute, not a completion.
+pub fn payment_settles(
+    payment: &CreditedPayment,
+    address: &Address,
+    min_confirmations: u64,
+    min_amount: Option<u64>,
+) -> bool {
+    payment.address == *address
+        && payment.confirmations >= min_confirmations
+        && min_amount.is_none_or(|floor| payment.amount >= floor)
+}
+
+/// Wraps a derived key with the machine secret when one applies: the
+/// file key becomes a function of both, so neither alone can decrypt.
+fn mix_binding(key: [u8; 32], binding: Option<&[u8; 32]>) -> [u8; 32] {
+    match binding {
+        None => key,
+        Some(secret) => {
+            let mut input = Vec::with_capacity(64);
+            input.extend_from_slice(&key);
+            input.extend_from_slice(secret);
+            hash::sha256(&input)
+        }
+    }
+}
+
+/// Parses either wallet file layout, reading v1 files as unbound.
+fn parse_wallet_file(bytes: &[u8]) -> Result<WalletFile, WalletError> {
+    if let Ok(file) = bincode::deserialize::<WalletFile>(bytes) {
+        return Ok(file);
+    }
+    let v1: WalletFileV1 =
+        bincode::deserialize(bytes)
+        .map_err(|e| WalletError::with_source(WalletErrorKind::Corrupt, "corrupt wallet file", e))?;
+    Ok(WalletFile {
+        version: 1,
+        kdf: v1.kdf,
+        salt: v1.salt,
+        nonce: v1.nonce,
+        ciphertext: v1.ciphertext,
+        machine_bound: false,
+    })
+}
+
+pub(crate) fn derive_key(
+    password: &str,
+    salt: &[u8],
+    kdf: &KdfParams,
+) -> Result<[u8; 32], WalletError> {
+    let params = argon2::Params::new(kdf.memory_kib, kdf.iterations, kdf.parallelism, Some(32))
+        .map_err(|e| WalletError::new(WalletErrorKind::Corrupt, format!("bad KDF parameters: {}", e)))?;
+    let argon = Argon2::new(argon2::Algorithm::Argon2id, argon2::Version::V0x13, params);
+    let mut key = [0u8; 32];
+    argon
+        .hash_password_into(password.as_bytes(), salt, &mut key)
+        .map_err(|e| WalletError::new(WalletErrorKind::Corrupt, format!("key derivation failed: {}", e)))?;
+    Ok(key)
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
@@ -0,0 +1,191 @@
+//! Persistent per-wallet transaction history, kept next to the wallet
+//! file and synced against the node.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::Hash256;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TxStatus {
+    /// Broadcast but not yet in a block.
+    Pending,
+    /// Confirmed at the given height.
+    Confirmed { height: u64 },
+    /// Replaced or invalidated; will not confirm.
+    Conflicted,
+}
+
+/// One wallet-relevant transaction as recorded locally.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TxRecord {
+    pub tx_hash: Hash256,
+    pub direction: Direction,
+    pub amount: u64,
+    pub fee: u64,
+    /// The other party: recipient for sends, sender for receives.
+    pub counterparty: [u8; 20],
+    pub status: TxStatus,
+    /// First time the wallet saw this transaction (unix seconds).
+    pub first_seen: u64,
+    pub label: String,
+}
+
+impl TxRecord {
+    pub fn confirmations(&self, tip_height: u64) -> u64 {
+        match self.status {
+            TxStatus::Confirmed { height } => tip_height.saturating_sub(height) + 1,
+            _ => 0,
+        }
+    }
+}
+
+/// JSON-file-backed store of the wallet's transaction history.
+pub struct WalletStore {
+    path: PathBuf,
+    records: HashMap<Hash256, TxRecord>,
+}
+
+impl WalletStore {
+    /// Opens the store, creating an empty one if the file is missing.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref().to_path_buf();
+        let records = match std::fs::read(&path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|e| format!("corrupt wallet history: {}", e))?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(e) => return Err(format!("failed to read wallet history: {}", e)),
+        };
+        Ok(WalletStore { path, records })
+    }
+
+    pub fn save(&self) -> Result<(), String> {
+        let bytes = serde_json::to_vec_pretty(&self.records)
+            .expect("history serialization cannot fail");
+        std::fs::write(&self.path, bytes)
+            .map_err(|e| format!("failed to write wallet history: {}", e))
+    }
+
+    pub fn get(&self, tx_hash: &Hash256) -> Option<&TxRecord> {
+        self.records.get(tx_hash)
+    }
+
+    /// Inserts or updates a record, keeping the earliest `first_seen`.
+    pub fn upsert(&mut self, record: TxRecord) {
+        self.records
+            .entry(record.tx_hash)
+            .and_modify(|existing| {
+                existing.status = record.status;
+                if !record.label.is_empty() {
+                    existing.label = record.label.clone();
+                }
+            })
+            .or_insert(record);
+    }
+
+    /// Folds records from another device into this store, returning
+    /// how many were added or changed. Local knowledge wins where it
+    /// is better: labels are only filled in where missing, `first_seen`
+    /// keeps the earliest sighting, and a final status (confirmed or
+    /// conflicted) is never downgraded back to pending.
+    pub fn merge(&mut self, records: &[TxRecord]) -> usize {
+        let mut changed = 0;
+        for incoming in records {
+            match self.records.get_mut(&incoming.tx_hash) {
+                None => {
+                    self.records.insert(incoming.tx_hash, incoming.clone());
+                    changed += 1;
+                }
+                Some(existing) => {
+                    let mut touched = false;
+                    if existing.label.is_empty() && !incoming.label.is_empty() {
+                        existing.label = incoming.label.clone();
+                        touched = true;
+                    }
+                    if incoming.first_seen < existing.first_seen {
+                        existing.first_seen = incoming.first_seen;
+                        touched = true;
+                    }
+                    if existing.status == TxStatus::Pending && incoming.status != TxStatus::Pending
+                    {
+                        existing.status = incoming.status;
+                        touched = true;
+                    }
+                    if touched {
+                        changed += 1;
+                    }
+                }
+            }
+        }
+        changed
+    }
+
+    pub fn set_label(&mut self, tx_hash: &Hash256, label: &str) -> bool {
+        match self.records.get_mut(tx_hash) {
+            Some(record) => {
+                record.label = label.to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn set_status(&mut self, tx_hash: &Hash256, status: TxStatus) {
+        if let Some(record) = self.records.get_mut(tx_hash) {
+            record.status = status;
+        }
+    }
+
+    /// All pending transactions, for status refresh against the node.
+    pub fn pending(&self) -> Vec<Hash256> {
+        self.records
+            .iter()
+            .filter(|(_, r)| r.status == TxStatus::Pending)
+            .map(|(h, _)| *h)
+            .collect()
+    }
+
+    /// History ordered newest first.
+    pub fn history(&self) -> Vec<&TxRecord> {
+        let mut out: Vec<&TxRecord> = self.records.values().collect();
+        out.sort_by_key(|r| std::cmp::Reverse(r.first_seen));
+        out
+    }
+
+    /// Renders the history as CSV for accounting exports.
+    pub fn to_csv(&self, tip_height: u64) -> String {
+        let mut out =
+            String::from("txid,direction,amount,fee,counterparty,status,confirmations,first_seen,label\n");
+        for record in self.history() {
+            let status = match record.status {
+                TxStatus::Pending => "pending".to_string(),
+                TxStatus::Confirmed { height } => format!("confirmed@{}", height),
+                TxStatus::Conflicted => "conflicted".to_string(),
+            };
+            out.push_str(&format!(
+                "{},{},{},{},{},{},{},{},{}\n",
+                hex::encode(record.tx_hash),
+                match record.direction {
+                    Direction::Sent => "sent",
+                    Direction::Received => "received",
+                },
+                record.amount,
+                record.fee,
+                hex::encode(record.counterparty),
+                status,
+                record.confirmations(tip_height),
+                record.first_seen,
+                record.label.replace(',', ";"),
+            ));
+        }
+        out
+    }
+}
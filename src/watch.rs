@@ -0,0 +1,122 @@
+//! Server-side address watch subscriptions.
+//!
+//! RPC clients register addresses they care about; the node then
+//! records mempool and block activity touching those addresses in a
+//! compact per-address log. Each entry carries a node-wide monotonic
+//! sequence number, so a light wallet polls `getaddressactivity` with
+//! its last-seen cursor instead of rescanning full history.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::types::{Address, Hash256, Transaction};
+
+/// Entries kept per watched address; older activity is dropped first.
+pub const MAX_LOG_ENTRIES: usize = 1_000;
+
+/// Whether the watched address spent or received in a transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    Sent,
+    Received,
+}
+
+/// One observed event on a watched address.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ActivityEntry {
+    /// Node-wide monotonic cursor; poll with `since` > last seen.
+    pub seq: u64,
+    pub txid: Hash256,
+    pub direction: Direction,
+    pub amount: u64,
+    /// `None` while the transaction sits in the mempool.
+    pub height: Option<u64>,
+    pub timestamp: u64,
+}
+
+/// The set of watched addresses and their activity logs.
+#[derive(Default)]
+pub struct WatchList {
+    next_seq: u64,
+    logs: HashMap<Address, Vec<ActivityEntry>>,
+}
+
+impl WatchList {
+    pub fn new() -> Self {
+        WatchList::default()
+    }
+
+    /// Registers an address; returns false if it was already watched.
+    pub fn watch(&mut self, address: Address) -> bool {
+        if self.logs.contains_key(&address) {
+            return false;
+        }
+        self.logs.insert(address, Vec::new());
+        true
+    }
+
+    /// Drops an address and its log; returns false if it was not watched.
+    pub fn unwatch(&mut self, address: &Address) -> bool {
+        self.logs.remove(address).is_some()
+    }
+
+    pub fn watched(&self) -> Vec<Address> {
+        self.logs.keys().copied().collect()
+    }
+
+    /// Whether any watched address appears in `tx`.
+    pub fn touches(&self, tx: &Transaction) -> bool {
+        self.is_watched(&tx.from) || self.is_watched(&tx.to)
+    }
+
+    /// Whether `address` is on the watch list.
+    pub fn is_watched(&self, address: &Address) -> bool {
+        self.logs.contains_key(address)
+    }
+
+    /// Records a transaction touching any watched address. `height` is
+    /// `None` for mempool acceptance, `Some` once confirmed in a block;
+    /// a confirmation produces a fresh entry rather than mutating the
+    /// mempool one, so cursors never miss the state change.
+    pub fn record_transaction(&mut self, tx: &Transaction, height: Option<u64>, timestamp: u64) {
+        let txid = tx.hash();
+        for (address, direction, amount) in [
+            (tx.from, Direction::Sent, tx.amount + tx.fee),
+            (tx.to, Direction::Received, tx.amount),
+        ] {
+            let Some(log) = self.logs.get_mut(&address) else {
+                continue;
+            };
+            let seq = self.next_seq;
+            self.next_seq += 1;
+            log.push(ActivityEntry {
+                seq,
+                txid,
+                direction,
+                amount,
+                height,
+                timestamp,
+            });
+            if log.len() > MAX_LOG_ENTRIES {
+                let excess = log.len() - MAX_LOG_ENTRIES;
+                log.drain(..excess);
+            }
+        }
+    }
+
+    /// Activity for `address` with sequence numbers above `since`.
+    /// Returns `None` when the address is not watched.
+    pub fn activity(&self, address: &Address, since: u64) -> Option<Vec<ActivityEntry>> {
+        self.logs
+            .get(address)
+            .map(|log| log.iter().filter(|e| e.seq >= since).cloned().collect())
+    }
+
+    /// The next sequence number that will be assigned; clients resume
+    /// polling from here after draining a batch.
+    pub fn cursor(&self) -> u64 {
+        self.next_seq
+    }
+}
@@ -0,0 +1,65 @@
+//! Addr bucketing and anchor persistence.
+
+use std::net::SocketAddr;
+
+use pali_coin::addrman::{net_group, AddrManager, ADDR_BUCKET_SIZE, ANCHOR_COUNT};
+
+fn addr(s: &str) -> SocketAddr {
+    s.parse().unwrap()
+}
+
+#[test]
+fn net_groups_follow_slash_16() {
+    assert_eq!(
+        net_group("10.1.2.3".parse().unwrap()),
+        net_group("10.1.99.99".parse().unwrap())
+    );
+    assert_ne!(
+        net_group("10.1.2.3".parse().unwrap()),
+        net_group("10.2.2.3".parse().unwrap())
+    );
+}
+
+#[test]
+fn one_source_group_cannot_dominate_the_table() {
+    let mut addrman = AddrManager::new();
+    let source = addr("203.0.113.7:8535");
+    // A flood of addresses from one /16, announced by one peer, all
+    // hash to a single bucket and displace each other there.
+    for i in 0..1_000u32 {
+        let [_, _, c, d] = i.to_be_bytes();
+        addrman.insert(addr(&format!("198.51.{}.{}:8535", c, d)), source);
+    }
+    assert_eq!(addrman.len(), ADDR_BUCKET_SIZE);
+
+    // Addresses spread across ranges from honest sources coexist.
+    for i in 0..8u32 {
+        addrman.insert(
+            addr(&format!("10.{}.0.1:8535", i)),
+            addr(&format!("172.{}.0.1:8535", 16 + i)),
+        );
+    }
+    assert!(addrman.len() > ADDR_BUCKET_SIZE);
+    assert!(!addrman.sample(usize::MAX).is_empty());
+}
+
+#[test]
+fn anchors_persist_across_sessions() {
+    let dir = std::env::temp_dir().join(format!("pali-anchors-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("anchors.txt");
+
+    let mut addrman = AddrManager::new();
+    addrman.set_anchor_path(&path);
+    addrman.record_good(addr("192.0.2.1:8535"));
+    addrman.record_good(addr("192.0.2.2:8535"));
+    addrman.record_good(addr("192.0.2.3:8535"));
+    // Only the newest two survive, and re-marking rotates, not duplicates.
+    addrman.record_good(addr("192.0.2.2:8535"));
+    assert_eq!(addrman.anchors().len(), ANCHOR_COUNT);
+
+    let restored = AddrManager::load_anchors(&path);
+    assert_eq!(restored, vec![addr("192.0.2.3:8535"), addr("192.0.2.2:8535")]);
+
+    std::fs::remove_dir_all(&dir).ok();
+}
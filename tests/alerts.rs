@@ -0,0 +1,119 @@
+//! Signed network alerts: signature thresholds and store behavior.
+
+use pali_coin::alerts::{Alert, AlertStore, SignedAlert};
+use pali_coin::consensus::ChainParams;
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+fn keypair(seed: u8) -> (SecretKey, String) {
+    let key = SecretKey::from_slice(&[seed; 32]).unwrap();
+    let public = PublicKey::from_secret_key(&Secp256k1::signing_only(), &key);
+    (key, hex::encode(public.serialize()))
+}
+
+fn alert(id: u64) -> Alert {
+    Alert {
+        id,
+        expires: 2_000_000_000,
+        message: format!("critical notice {}", id),
+    }
+}
+
+#[test]
+fn alerts_need_a_threshold_of_distinct_developer_keys() {
+    let (key_a, pub_a) = keypair(0x01);
+    let (key_b, pub_b) = keypair(0x02);
+    let params = ChainParams {
+        alert_keys: vec![pub_a, pub_b],
+        alert_threshold: 2,
+        ..ChainParams::default()
+    };
+
+    let mut signed = SignedAlert::new(alert(1));
+    signed.sign(&key_a);
+    let err = signed.verify(&params).unwrap_err();
+    assert!(err.contains("1 of 2"));
+
+    // The same key signing twice still counts once.
+    signed.sign(&key_a);
+    assert!(signed.verify(&params).is_err());
+
+    signed.sign(&key_b);
+    signed.verify(&params).unwrap();
+
+    // A threshold of zero means every configured key.
+    let all_keys = ChainParams {
+        alert_threshold: 0,
+        ..params.clone()
+    };
+    signed.verify(&all_keys).unwrap();
+
+    // One configured key suffices when the threshold says so.
+    let one_of_two = ChainParams {
+        alert_threshold: 1,
+        ..params
+    };
+    let mut single = SignedAlert::new(alert(2));
+    single.sign(&key_b);
+    single.verify(&one_of_two).unwrap();
+}
+
+#[test]
+fn unauthorized_and_tampered_alerts_are_rejected() {
+    let (key, public) = keypair(0x03);
+    let (outsider, _) = keypair(0x04);
+    let params = ChainParams {
+        alert_keys: vec![public],
+        alert_threshold: 1,
+        ..ChainParams::default()
+    };
+
+    // A key outside the configured set carries no weight.
+    let mut forged = SignedAlert::new(alert(1));
+    forged.sign(&outsider);
+    assert!(forged.verify(&params).is_err());
+
+    // Editing the message after signing invalidates the signature.
+    let mut tampered = SignedAlert::new(alert(2));
+    tampered.sign(&key);
+    tampered.alert.message = "send your coins here".to_string();
+    assert!(tampered.verify(&params).is_err());
+
+    // A node with no alert keys ignores the mechanism entirely.
+    let mut legit = SignedAlert::new(alert(3));
+    legit.sign(&key);
+    legit.verify(&params).unwrap();
+    let err = legit.verify(&ChainParams::default()).unwrap_err();
+    assert!(err.contains("no alert keys"));
+}
+
+#[test]
+fn the_store_relays_each_alert_once_and_drops_expired_ones() {
+    let (key, public) = keypair(0x05);
+    let params = ChainParams {
+        alert_keys: vec![public],
+        alert_threshold: 1,
+        ..ChainParams::default()
+    };
+    let mut store = AlertStore::new();
+    let now = 1_700_000_000;
+
+    let mut signed = SignedAlert::new(alert(1));
+    signed.sign(&key);
+    // First sight relays, a duplicate does not.
+    assert!(store.accept(signed.clone(), &params, now).unwrap());
+    assert!(!store.accept(signed, &params, now).unwrap());
+    assert_eq!(store.active(now).len(), 1);
+
+    // An alert past its expiry is refused outright.
+    let mut expired = SignedAlert::new(Alert {
+        id: 2,
+        expires: now,
+        message: "too late".to_string(),
+    });
+    expired.sign(&key);
+    let err = store.accept(expired, &params, now).unwrap_err();
+    assert!(err.contains("expired"));
+
+    // The held alert stops displaying once its own expiry passes.
+    assert!(store.active(2_000_000_000).is_empty());
+}
@@ -0,0 +1,146 @@
+//! Account xpub export: read-only address derivation for auditors,
+//! the watch-only import RPC, and rejection of bad exports.
+
+use std::sync::{Arc, Mutex};
+
+use pali_coin::audit::{
+    chain_code_for, decode, derive_child_pubkey, derive_child_secret, derive_receive_address,
+    encode, AccountXpub, MAX_XPUB_IMPORT, XPUB_VERSION,
+};
+use pali_coin::blockchain::{Blockchain, GenesisConfig, PremineAllocation};
+use pali_coin::mempool::Mempool;
+use pali_coin::node::Node;
+use pali_coin::rpc::{dispatch, RpcContext};
+use pali_coin::rpc_auth::{method_scope, AuthConfig, Scope};
+use pali_coin::wallet::Wallet;
+use pali_coin::{hash, math, MAINNET_CHAIN_ID};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use serde_json::json;
+
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("pali-audit-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn test_ctx(name: &str) -> (RpcContext, Arc<Node>) {
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "audit test".to_string(),
+        bits: math::MAX_BITS,
+        premine: vec![PremineAllocation {
+            address: hex::encode([0xA9; 20]),
+            amount: 10_000,
+        }],
+    };
+    let chain = Arc::new(Mutex::new(
+        Blockchain::init_chain(test_dir(name), &config).unwrap(),
+    ));
+    let mempool = Arc::new(Mutex::new(Mempool::new()));
+    let node = Arc::new(Node::new(chain.clone(), mempool.clone(), MAINNET_CHAIN_ID));
+    let ctx = RpcContext {
+        chain,
+        mempool,
+        node: Some(node.clone()),
+        chain_id: MAINNET_CHAIN_ID,
+        auth: Arc::new(AuthConfig::default()),
+    };
+    (ctx, node)
+}
+
+#[test]
+fn the_export_derives_the_same_addresses_the_key_holder_can_spend_from() {
+    let mut wallet = Wallet::new();
+    let xpub = wallet.export_account_xpub().unwrap();
+    assert_eq!(xpub.version, XPUB_VERSION);
+
+    // Re-exports are identical: the chain code carries no fresh state.
+    assert_eq!(wallet.export_account_xpub().unwrap(), xpub);
+
+    // A decoded copy of the export derives deterministic, distinct
+    // receive addresses.
+    let roundtrip = decode(&encode(&xpub)).unwrap();
+    assert_eq!(roundtrip, xpub);
+    let first = derive_receive_address(&roundtrip, 0).unwrap();
+    let second = derive_receive_address(&roundtrip, 1).unwrap();
+    assert_ne!(first, second);
+    assert_eq!(derive_receive_address(&xpub, 0).unwrap(), first);
+
+    // The wallet-side child secret lands on the same public key, so
+    // funds sent to a derived address stay spendable. Checked against
+    // a known key, since the wallet never hands its secret out.
+    let secp = Secp256k1::new();
+    let secret = SecretKey::from_slice(&[0x41; 32]).unwrap();
+    let known = AccountXpub {
+        version: XPUB_VERSION,
+        public_key: PublicKey::from_secret_key(&secp, &secret).serialize().to_vec(),
+        chain_code: chain_code_for(&secret),
+    };
+    for index in 0..5 {
+        let child_secret = derive_child_secret(&secret, &known, index).unwrap();
+        let child_pubkey = derive_child_pubkey(&known, index).unwrap();
+        assert_eq!(PublicKey::from_secret_key(&secp, &child_secret), child_pubkey);
+        assert_eq!(
+            hash::pubkey_to_address(&child_pubkey.serialize()),
+            derive_receive_address(&known, index).unwrap()
+        );
+    }
+}
+
+#[test]
+fn importing_an_export_watches_its_derived_addresses_read_only() {
+    let (ctx, node) = test_ctx("import");
+    let mut wallet = Wallet::new();
+    let xpub = wallet.export_account_xpub().unwrap();
+
+    let reply = dispatch(&ctx, "importaccountxpub", &json!([encode(&xpub), 3])).unwrap();
+    assert_eq!(reply["fingerprint"], json!(xpub.fingerprint()));
+    let watched = reply["watched"].as_array().unwrap();
+    assert_eq!(watched.len(), 3);
+    for index in 0..3 {
+        let address = derive_receive_address(&xpub, index).unwrap();
+        assert_eq!(watched[index as usize], json!(hex::encode(address)));
+        assert!(node.watch.lock().unwrap().is_watched(&address));
+    }
+
+    // Importing derives addresses; it never grants more than the
+    // watch-scoped methods it parallels.
+    assert_eq!(method_scope("importaccountxpub"), Scope::Wallet);
+    assert_eq!(method_scope("watchaddress"), Scope::Wallet);
+}
+
+#[test]
+fn bad_exports_and_bad_counts_are_refused() {
+    let (ctx, _node) = test_ctx("refuse");
+    let mut wallet = Wallet::new();
+    let xpub = wallet.export_account_xpub().unwrap();
+
+    assert!(decode("zz").unwrap_err().contains("bad hex"));
+    assert!(decode("00ff00").unwrap_err().contains("malformed export"));
+
+    // A version from the future is refused rather than misread.
+    let future = AccountXpub {
+        version: XPUB_VERSION + 1,
+        ..xpub.clone()
+    };
+    assert!(decode(&encode(&future)).unwrap_err().contains("newer"));
+
+    // A truncated key is caught before any derivation happens.
+    let mut clipped = xpub.clone();
+    clipped.public_key.truncate(16);
+    assert!(decode(&encode(&clipped))
+        .unwrap_err()
+        .contains("malformed account public key"));
+
+    // Counts outside 1..=MAX_XPUB_IMPORT never start deriving.
+    let err = dispatch(&ctx, "importaccountxpub", &json!([encode(&xpub), 0])).unwrap_err();
+    assert!(err.contains("count"), "got: {}", err);
+    let err = dispatch(
+        &ctx,
+        "importaccountxpub",
+        &json!([encode(&xpub), MAX_XPUB_IMPORT + 1]),
+    )
+    .unwrap_err();
+    assert!(err.contains("count"), "got: {}", err);
+}
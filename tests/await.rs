@@ -0,0 +1,112 @@
+//! Settlement matching behind `pali-wallet await`: which deposit
+//! events finish the wait, and how the cursor keeps history out.
+
+use pali_coin::deposits::{DepositEvent, DepositEventKind, DepositTracker};
+use pali_coin::math;
+use pali_coin::types::{Block, BlockHeader, Transaction};
+use pali_coin::wallet::{payment_settles, CreditedPayment};
+
+const MERCHANT: [u8; 20] = [0xAB; 20];
+
+fn deposit_tx(nonce: u64, amount: u64) -> Transaction {
+    Transaction {
+        chain_id: 1,
+        nonce,
+        from: [0x11; 20],
+        to: MERCHANT,
+        amount,
+        fee: 100,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+fn block(height: u64, nonce: u64, transactions: Vec<Transaction>) -> Block {
+    Block {
+        header: BlockHeader {
+            version: 1,
+            prev_hash: [height as u8; 32],
+            merkle_root: [0u8; 32],
+            timestamp: 1_700_000_000,
+            bits: math::MAX_BITS,
+            nonce,
+            height,
+        },
+        transactions,
+    }
+}
+
+fn payment_from(event: &DepositEvent) -> CreditedPayment {
+    CreditedPayment {
+        txid: event.txid,
+        address: event.address,
+        amount: event.amount,
+        confirmations: event.confirmations,
+    }
+}
+
+#[test]
+fn settlement_needs_the_address_the_confirmations_and_the_amount() {
+    let payment = CreditedPayment {
+        txid: [0xCC; 32],
+        address: MERCHANT,
+        amount: 100_000,
+        confirmations: 3,
+    };
+
+    assert!(payment_settles(&payment, &MERCHANT, 3, None));
+    // Overpaying settles; underpaying does not.
+    assert!(payment_settles(&payment, &MERCHANT, 3, Some(100_000)));
+    assert!(payment_settles(&payment, &MERCHANT, 1, Some(50_000)));
+    assert!(!payment_settles(&payment, &MERCHANT, 3, Some(100_001)));
+    // Short on confirmations or aimed elsewhere never settles.
+    assert!(!payment_settles(&payment, &MERCHANT, 4, None));
+    assert!(!payment_settles(&payment, &[0x99; 20], 1, None));
+}
+
+#[test]
+fn tracker_credits_decode_into_settling_payments() {
+    let mut tracker = DepositTracker::new();
+    tracker.register(MERCHANT, 2);
+    let tx = deposit_tx(0, 75_000);
+
+    tracker.block_connected(&block(10, 1, vec![tx.clone()]));
+    assert!(tracker.events_since(0).is_empty());
+    tracker.block_connected(&block(11, 2, Vec::new()));
+
+    let events = tracker.events_since(0);
+    assert_eq!(events.len(), 1);
+    assert_eq!(events[0].kind, DepositEventKind::Credited);
+    let payment = payment_from(&events[0]);
+    assert_eq!(payment.txid, tx.hash());
+    assert!(payment_settles(&payment, &MERCHANT, 2, Some(75_000)));
+    // The same credit asked to cover a larger invoice keeps waiting.
+    assert!(!payment_settles(&payment, &MERCHANT, 2, Some(80_000)));
+}
+
+#[test]
+fn a_cursor_taken_before_registering_skips_stale_credits() {
+    let mut tracker = DepositTracker::new();
+    tracker.register(MERCHANT, 1);
+    // An old payment credited long before this wait began.
+    tracker.block_connected(&block(10, 1, vec![deposit_tx(0, 30_000)]));
+
+    // The CLI snapshots the cursor at startup, then only reads forward.
+    let cursor = tracker.cursor();
+    tracker.block_connected(&block(11, 2, vec![deposit_tx(1, 60_000)]));
+
+    let fresh = tracker.events_since(cursor);
+    assert_eq!(fresh.len(), 1);
+    assert_eq!(fresh[0].amount, 60_000);
+    assert!(payment_settles(
+        &payment_from(&fresh[0]),
+        &MERCHANT,
+        1,
+        Some(60_000)
+    ));
+    // The stale credit exists in full history but is before the cursor.
+    assert_eq!(tracker.events_since(0).len(), 2);
+}
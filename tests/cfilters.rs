@@ -0,0 +1,251 @@
+//! Compact block filters: Golomb-coded matching, the persisted filter
+//! index and the P2P serving paths.
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use pali_coin::blockchain::{Blockchain, GenesisConfig, PremineAllocation};
+use pali_coin::cfilters::{self, BlockFilter};
+use pali_coin::mempool::Mempool;
+use pali_coin::network::{NetworkMessage, PROTOCOL_VERSION};
+use pali_coin::node::{Node, PeerInfo};
+use pali_coin::rpc::{dispatch, RpcContext};
+use pali_coin::rpc_auth::AuthConfig;
+use pali_coin::types::{
+    block_reward, Address, Block, BlockHeader, Hash256, Transaction, COINBASE_ADDRESS,
+};
+use pali_coin::{hash, math, MAINNET_CHAIN_ID};
+use serde_json::json;
+use tokio::sync::mpsc;
+
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("pali-cfilters-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn new_chain(name: &str) -> Blockchain {
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "cfilters test".to_string(),
+        bits: math::MAX_BITS,
+        premine: vec![PremineAllocation {
+            address: hex::encode([0xAB; 20]),
+            amount: 1_000,
+        }],
+    };
+    Blockchain::init_chain(test_dir(name), &config).unwrap()
+}
+
+fn coinbase(height: u64, to: Address) -> Transaction {
+    Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: COINBASE_ADDRESS,
+        to,
+        amount: block_reward(height),
+        fee: 0,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+fn seal(chain: &Blockchain, transactions: Vec<Transaction>) -> Block {
+    let hashes: Vec<Hash256> = transactions.iter().map(|tx| tx.hash()).collect();
+    let mut header = BlockHeader {
+        version: 1,
+        prev_hash: chain.best_hash(),
+        merkle_root: hash::merkle_root(&hashes),
+        timestamp: 1_700_000_000 + (chain.height() + 1) * 180,
+        bits: chain.next_bits().unwrap(),
+        nonce: 0,
+        height: chain.height() + 1,
+    };
+    while !math::hash_meets_target(&header.hash(), header.bits) {
+        header.nonce = header.nonce.wrapping_add(1);
+    }
+    Block {
+        header,
+        transactions,
+    }
+}
+
+/// Mines `count` coinbase-only blocks, each paying a distinct address
+/// derived from its height.
+fn mine(chain: &mut Blockchain, count: u64) {
+    for _ in 0..count {
+        let height = chain.height() + 1;
+        let block = seal(chain, vec![coinbase(height, [height as u8; 20])]);
+        chain.add_block(&block, MAINNET_CHAIN_ID).unwrap();
+    }
+}
+
+#[test]
+fn filters_match_touched_addresses_and_chain_into_headers() {
+    let mut chain = new_chain("matching");
+    mine(&mut chain, 3);
+    let hash2 = chain.get_block_hash(2).unwrap().unwrap();
+    let block2 = chain.get_block(&hash2).unwrap().unwrap();
+    let filter = BlockFilter::build(&block2);
+
+    // The block pays [2; 20]: no false negative for it, and neither of
+    // the neighbouring payouts shows up.
+    assert!(filter.matches_any(&hash2, &[&[2u8; 20]]));
+    assert!(!filter.matches_any(&hash2, &[&[1u8; 20]]));
+    assert!(!filter.matches_any(&hash2, &[&[1u8; 20], &[3u8; 20]]));
+    assert!(filter.matches_any(&hash2, &[&[9u8; 20], &[2u8; 20]]));
+
+    // Headers commit to both the filter and its predecessor, so either
+    // input changing changes the header.
+    let a = cfilters::filter_header(&filter.filter_hash(), &[0u8; 32]);
+    let b = cfilters::filter_header(&filter.filter_hash(), &[1u8; 32]);
+    let c = cfilters::filter_header(&[0u8; 32], &[0u8; 32]);
+    assert_ne!(a, b);
+    assert_ne!(a, c);
+}
+
+#[test]
+fn filter_index_persists_and_rebuilds_after_a_disconnect() {
+    let mut chain = new_chain("index");
+    mine(&mut chain, 4);
+
+    // The stored index matches a from-scratch recomputation of the
+    // whole headers chain, genesis included.
+    let mut expected = [0u8; 32];
+    for height in 0..=chain.height() {
+        let hash = chain.get_block_hash(height).unwrap().unwrap();
+        let filter = chain.get_filter(&hash).unwrap().unwrap();
+        let block = chain.get_block(&hash).unwrap().unwrap();
+        assert_eq!(filter, BlockFilter::build(&block));
+        expected = cfilters::filter_header(&filter.filter_hash(), &expected);
+        assert_eq!(chain.filter_header(&hash).unwrap(), expected);
+    }
+
+    // Disconnecting the tip drops its index entries; asking again goes
+    // through the lazy backfill path and lands on the same header.
+    let tip = chain.best_hash();
+    let before = chain.filter_header(&tip).unwrap();
+    chain.disconnect_tip().unwrap();
+    assert_eq!(chain.filter_header(&tip).unwrap(), before);
+
+    // getblockfilter serves the same bytes over RPC.
+    let ctx = RpcContext {
+        chain: Arc::new(Mutex::new(chain)),
+        mempool: Arc::new(Mutex::new(Mempool::new())),
+        node: None,
+        chain_id: MAINNET_CHAIN_ID,
+        auth: Arc::new(AuthConfig::default()),
+    };
+    let reply = dispatch(&ctx, "getblockfilter", &json!([hex::encode(tip)])).unwrap();
+    assert_eq!(reply["header"], json!(hex::encode(before)));
+    let chain = ctx.chain.lock().unwrap();
+    let filter = chain.get_filter(&tip).unwrap().unwrap();
+    assert_eq!(reply["filter"], json!(hex::encode(&filter.data)));
+    assert_eq!(
+        reply["filter_hash"],
+        json!(hex::encode(filter.filter_hash()))
+    );
+}
+
+#[test]
+fn node_serves_filter_headers_and_filters_to_peers() {
+    let dir = test_dir("serving");
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "cfilters test".to_string(),
+        bits: math::MAX_BITS,
+        premine: Vec::new(),
+    };
+    let chain = Arc::new(Mutex::new(Blockchain::init_chain(&dir, &config).unwrap()));
+    mine(&mut chain.lock().unwrap(), 3);
+    let mempool = Arc::new(Mutex::new(Mempool::new()));
+    let node = Node::new(chain.clone(), mempool, MAINNET_CHAIN_ID);
+
+    // Stand in for a connected light client: replies land on the
+    // writer channel where we can read them back.
+    let peer: SocketAddr = "192.0.2.9:7777".parse().unwrap();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    node.peers.lock().unwrap().insert(
+        peer,
+        PeerInfo {
+            addr: peer,
+            inbound: true,
+            version: PROTOCOL_VERSION,
+            user_agent: String::new(),
+            best_height: 0,
+            connected_at: 0,
+            last_seen: 0,
+            ping_time: None,
+            pending_ping: None,
+            sender: tx,
+        },
+    );
+
+    let (tip, hashes) = {
+        let chain = chain.lock().unwrap();
+        let hashes: Vec<Hash256> = (0..=3)
+            .map(|h| chain.get_block_hash(h).unwrap().unwrap())
+            .collect();
+        (chain.best_hash(), hashes)
+    };
+
+    node.handle_network_message(
+        peer,
+        NetworkMessage::GetCFHeaders {
+            start_height: 0,
+            stop_hash: tip,
+        },
+    )
+    .unwrap();
+    let NetworkMessage::CFHeaders {
+        start_height,
+        prev_header,
+        filter_hashes,
+    } = rx.try_recv().unwrap()
+    else {
+        panic!("expected a CFHeaders reply");
+    };
+    assert_eq!(start_height, 0);
+    assert_eq!(prev_header, [0u8; 32]);
+    assert_eq!(filter_hashes.len(), 4);
+    // Extending the chain client-side from the reply reproduces the
+    // node's own header for the tip.
+    let mut header = prev_header;
+    for filter_hash in &filter_hashes {
+        header = cfilters::filter_header(filter_hash, &header);
+    }
+    assert_eq!(header, chain.lock().unwrap().filter_header(&tip).unwrap());
+
+    node.handle_network_message(
+        peer,
+        NetworkMessage::GetCFilters {
+            start_height: 2,
+            stop_hash: tip,
+        },
+    )
+    .unwrap();
+    let NetworkMessage::CFilters(entries) = rx.try_recv().unwrap() else {
+        panic!("expected a CFilters reply");
+    };
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].block_hash, hashes[2]);
+    assert_eq!(entries[1].block_hash, tip);
+    assert!(entries[1].filter.matches_any(&tip, &[&[3u8; 20]]));
+
+    // A stop hash off the main chain (or unknown) is refused.
+    let err = node
+        .handle_network_message(
+            peer,
+            NetworkMessage::GetCFilters {
+                start_height: 0,
+                stop_hash: [0x77; 32],
+            },
+        )
+        .unwrap_err();
+    assert!(err.contains("unknown filter range stop"), "unexpected error: {}", err);
+}
@@ -0,0 +1,156 @@
+//! Chain parameter override files for private consortium networks.
+
+use pali_coin::blockchain::{Blockchain, GenesisConfig};
+use pali_coin::consensus::ChainParams;
+use pali_coin::rejection::RejectionReason;
+use pali_coin::types::{
+    block_reward, Address, Block, BlockHeader, Hash256, Transaction, COINBASE_ADDRESS,
+};
+use pali_coin::{hash, math, MAINNET_CHAIN_ID};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "pali-chainparams-{}-{}",
+        std::process::id(),
+        name
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn miner_key(seed: u8) -> (String, Address) {
+    let key = SecretKey::from_slice(&[seed; 32]).unwrap();
+    let public = PublicKey::from_secret_key(&Secp256k1::signing_only(), &key);
+    let bytes = public.serialize();
+    (hex::encode(bytes), hash::pubkey_to_address(&bytes))
+}
+
+fn mine_one(chain: &mut Blockchain, payout: Address) -> Result<(), RejectionReason> {
+    let height = chain.height() + 1;
+    let transactions = vec![Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: COINBASE_ADDRESS,
+        to: payout,
+        amount: chain.chain_params().block_reward_at(height),
+        fee: 0,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }];
+    let hashes: Vec<Hash256> = transactions.iter().map(|tx| tx.hash()).collect();
+    let mut header = BlockHeader {
+        version: 1,
+        prev_hash: chain.best_hash(),
+        merkle_root: hash::merkle_root(&hashes),
+        timestamp: 1_700_000_000 + height * 180,
+        bits: chain.next_bits().unwrap(),
+        nonce: 0,
+        height,
+    };
+    while !math::hash_meets_target(&header.hash(), header.bits) {
+        header.nonce = header.nonce.wrapping_add(1);
+    }
+    chain
+        .add_block(
+            &Block {
+                header,
+                transactions,
+            },
+            MAINNET_CHAIN_ID,
+        )
+        .map(|_| ())
+}
+
+#[test]
+fn override_file_loads_and_rejects_typos() {
+    let dir = test_dir("file");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("chainparams.json");
+    let (key, _) = miner_key(0x21);
+    std::fs::write(
+        &path,
+        format!(
+            r#"{{
+                "chain_id": 3,
+                "target_block_time_secs": 30,
+                "halving_interval": 1000,
+                "initial_block_reward": 5000,
+                "miner_keys": ["{}"]
+            }}"#,
+            key
+        ),
+    )
+    .unwrap();
+    let params = ChainParams::from_file(&path).unwrap();
+    assert_eq!(params.chain_id, Some(3));
+    assert_eq!(params.target_block_time_secs, 30);
+    assert_eq!(params.halving_interval, 1_000);
+    assert_eq!(params.initial_block_reward, 5_000);
+    assert_eq!(params.miner_keys.len(), 1);
+    // Unset fields keep their mainnet defaults.
+    assert_eq!(params.alert_threshold, 0);
+    assert!(params.miner_allowed(&miner_key(0x21).1));
+
+    // A typo'd miner key fails loading instead of minting a chain
+    // nobody can extend.
+    std::fs::write(&path, r#"{ "miner_keys": ["not-a-key"] }"#).unwrap();
+    let err = ChainParams::from_file(&path).unwrap_err();
+    assert!(err.contains("not-a-key"), "unexpected error: {}", err);
+
+    std::fs::write(&path, r#"{ "halving_interval": 0 }"#).unwrap();
+    assert!(ChainParams::from_file(&path).is_err());
+}
+
+#[test]
+fn reward_schedule_follows_the_overridden_halvings() {
+    // The defaults reproduce the compiled-in mainnet schedule.
+    let mainnet = ChainParams::default();
+    for height in [0, 1, 699_999, 700_000, 1_400_000, 44_800_000 + 1] {
+        assert_eq!(mainnet.block_reward_at(height), block_reward(height));
+    }
+
+    let private = ChainParams {
+        halving_interval: 10,
+        initial_block_reward: 1_000,
+        ..ChainParams::default()
+    };
+    assert_eq!(private.block_reward_at(9), 1_000);
+    assert_eq!(private.block_reward_at(10), 500);
+    assert_eq!(private.block_reward_at(25), 250);
+    // 64 halvings in, the subsidy is exactly zero.
+    assert_eq!(private.block_reward_at(640), 0);
+}
+
+#[test]
+fn poa_miner_set_gates_block_connection() {
+    let (key, payout) = miner_key(0x22);
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "consortium test".to_string(),
+        bits: math::MAX_BITS,
+        premine: Vec::new(),
+    };
+    let mut chain = Blockchain::init_chain(test_dir("poa"), &config).unwrap();
+    chain.set_chain_params(ChainParams {
+        miner_keys: vec![key],
+        ..ChainParams::default()
+    });
+
+    // An outsider's coinbase is rejected with a stable code.
+    let err = mine_one(&mut chain, [0xEE; 20]).unwrap_err();
+    assert_eq!(err, RejectionReason::UnauthorizedMiner);
+    assert_eq!(err.code(), "bad-cb-miner");
+
+    // The authorized key's payout address connects fine.
+    mine_one(&mut chain, payout).unwrap();
+    assert_eq!(chain.height(), 1);
+
+    // Without a miner set, mining stays open to anyone.
+    chain.set_chain_params(ChainParams::default());
+    mine_one(&mut chain, [0xEE; 20]).unwrap();
+}
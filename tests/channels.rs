@@ -0,0 +1,130 @@
+//! Payment channel state machine behavior.
+
+use pali_coin::channels::{
+    channel_address, sign_state, Channel, SignedState, DISPUTE_WINDOW,
+};
+use pali_coin::types::Transaction;
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+fn keypair() -> (SecretKey, PublicKey) {
+    Secp256k1::new().generate_keypair(&mut rand::thread_rng())
+}
+
+fn funding_tx(to: [u8; 20], amount: u64) -> Transaction {
+    Transaction {
+        chain_id: 3,
+        nonce: 0,
+        from: [0x11; 20],
+        to,
+        amount,
+        fee: 10_000,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+#[test]
+fn channel_address_is_order_independent() {
+    let (_, pk_a) = keypair();
+    let (_, pk_b) = keypair();
+    assert_eq!(
+        channel_address(&pk_a, &pk_b).unwrap(),
+        channel_address(&pk_b, &pk_a).unwrap()
+    );
+}
+
+#[test]
+fn updates_require_both_signatures_and_newer_sequence() {
+    let (sk_a, pk_a) = keypair();
+    let (sk_b, pk_b) = keypair();
+    let joint = channel_address(&pk_a, &pk_b).unwrap();
+    let mut channel = Channel::open(&funding_tx(joint, 1_000_000), pk_a, pk_b).unwrap();
+
+    let state = channel.propose_update(700_000, 300_000).unwrap();
+    let signed = SignedState {
+        sig_a: sign_state(&state, &sk_a),
+        sig_b: sign_state(&state, &sk_b),
+        state: state.clone(),
+    };
+    channel.apply_update(signed.clone()).unwrap();
+
+    // Replaying the same sequence is stale.
+    assert!(channel.apply_update(signed).is_err());
+
+    // A single signature is not enough.
+    let next = channel.propose_update(600_000, 400_000).unwrap();
+    let half_signed = SignedState {
+        sig_a: sign_state(&next, &sk_a),
+        sig_b: sign_state(&next, &sk_a),
+        state: next,
+    };
+    assert!(channel.apply_update(half_signed).is_err());
+}
+
+#[test]
+fn balances_must_sum_to_capacity() {
+    let (_, pk_a) = keypair();
+    let (_, pk_b) = keypair();
+    let joint = channel_address(&pk_a, &pk_b).unwrap();
+    let channel = Channel::open(&funding_tx(joint, 1_000_000), pk_a, pk_b).unwrap();
+    assert!(channel.propose_update(1_000_000, 1).is_err());
+    assert!(channel.propose_update(999_999, 0).is_err());
+}
+
+#[test]
+fn dispute_supersedes_older_state() {
+    let (sk_a, pk_a) = keypair();
+    let (sk_b, pk_b) = keypair();
+    let joint = channel_address(&pk_a, &pk_b).unwrap();
+    let mut channel = Channel::open(&funding_tx(joint, 1_000_000), pk_a, pk_b).unwrap();
+
+    let mut states = Vec::new();
+    for split in [(900_000, 100_000), (500_000, 500_000)] {
+        let state = channel.propose_update(split.0, split.1).unwrap();
+        let signed = SignedState {
+            sig_a: sign_state(&state, &sk_a),
+            sig_b: sign_state(&state, &sk_b),
+            state,
+        };
+        channel.apply_update(signed.clone()).unwrap();
+        states.push(signed);
+    }
+
+    // The closer tries to settle on the older, more favorable state;
+    // the counterparty's newer state wins the dispute.
+    let mut observer = Channel::open(&funding_tx(joint, 1_000_000), pk_a, pk_b).unwrap();
+    observer.apply_update(states[0].clone()).unwrap();
+    observer.apply_update(states[1].clone()).unwrap();
+    assert_eq!(observer.best.as_ref().unwrap().state.balance_b, 500_000);
+}
+
+#[test]
+fn unilateral_close_is_timelocked_past_the_dispute_window() {
+    let (sk_a, pk_a) = keypair();
+    let (sk_b, pk_b) = keypair();
+    let joint = channel_address(&pk_a, &pk_b).unwrap();
+    let channel = Channel::open(&funding_tx(joint, 1_000_000), pk_a, pk_b).unwrap();
+    let state = channel.propose_update(600_000, 400_000).unwrap();
+    let _ = (sign_state(&state, &sk_a), sign_state(&state, &sk_b));
+
+    let close_height = 5_000;
+    let txs = channel
+        .settlement_transactions(&state, 3, 0, 10_000, Some(close_height))
+        .unwrap();
+    assert_eq!(txs.len(), 2);
+    for tx in &txs {
+        assert_eq!(tx.lock_time, close_height + DISPUTE_WINDOW);
+        assert_eq!(tx.from, joint);
+    }
+    assert_eq!(txs[0].amount, 590_000);
+    assert_eq!(txs[1].amount, 390_000);
+
+    // Cooperative close settles immediately.
+    let coop = channel
+        .settlement_transactions(&state, 3, 0, 10_000, None)
+        .unwrap();
+    assert!(coop.iter().all(|tx| tx.lock_time == 0));
+}
@@ -0,0 +1,89 @@
+//! Manual UTXO locks: reserving outputs against node-side spends.
+
+use pali_coin::blockchain::{Blockchain, GenesisConfig, PremineAllocation};
+use pali_coin::coinlock::CoinLocks;
+use pali_coin::types::OutPoint;
+use pali_coin::{math, MAINNET_CHAIN_ID};
+
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("pali-coinlock-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+const ALICE: [u8; 20] = [0xA1; 20];
+const BOB: [u8; 20] = [0xB0; 20];
+
+fn funded_chain(name: &str) -> Blockchain {
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "coinlock test".to_string(),
+        bits: math::MAX_BITS,
+        premine: vec![
+            PremineAllocation {
+                address: hex::encode(ALICE),
+                amount: 50_000,
+            },
+            PremineAllocation {
+                address: hex::encode(BOB),
+                amount: 70_000,
+            },
+        ],
+    };
+    Blockchain::init_chain(test_dir(name), &config).unwrap()
+}
+
+#[test]
+fn locks_are_idempotent_and_releasable() {
+    let mut locks = CoinLocks::new();
+    let outpoint = OutPoint {
+        tx_hash: [0x11; 32],
+        index: 0,
+    };
+    assert!(locks.lock(outpoint));
+    assert!(!locks.lock(outpoint));
+    assert!(locks.is_locked(&outpoint));
+    assert!(locks.unlock(&outpoint));
+    assert!(!locks.unlock(&outpoint));
+
+    locks.lock(outpoint);
+    locks.lock(OutPoint {
+        tx_hash: [0x22; 32],
+        index: 1,
+    });
+    assert_eq!(locks.unlock_all(), 2);
+    assert!(locks.is_empty());
+}
+
+#[test]
+fn locked_amount_counts_only_the_addressed_outputs() {
+    let chain = funded_chain("amounts");
+    let mut locks = CoinLocks::new();
+    for address in [ALICE, BOB] {
+        for (outpoint, _) in chain.get_utxos_for_address(&address).unwrap() {
+            locks.lock(outpoint);
+        }
+    }
+    assert_eq!(locks.locked_amount(&chain, &ALICE).unwrap(), 50_000);
+    assert_eq!(locks.locked_amount(&chain, &BOB).unwrap(), 70_000);
+    assert_eq!(locks.locked_amount(&chain, &[0xCC; 20]).unwrap(), 0);
+}
+
+#[test]
+fn stale_locks_are_swept_once_the_output_is_gone() {
+    let chain = funded_chain("stale");
+    let mut locks = CoinLocks::new();
+    let (live, _) = chain.get_utxos_for_address(&ALICE).unwrap()[0];
+    locks.lock(live);
+    // An outpoint that never existed models a lock whose output a
+    // confirmed block has since consumed.
+    locks.lock(OutPoint {
+        tx_hash: [0xDE; 32],
+        index: 7,
+    });
+    assert_eq!(locks.len(), 2);
+    assert_eq!(locks.locked_amount(&chain, &ALICE).unwrap(), 50_000);
+    assert_eq!(locks.len(), 1);
+    assert!(locks.is_locked(&live));
+}
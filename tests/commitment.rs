@@ -0,0 +1,166 @@
+//! Sign-to-contract commitments: embedding an external hash in the
+//! coinbase and proving its inclusion to third parties.
+
+use std::sync::{Arc, Mutex};
+
+use pali_coin::blockchain::{Blockchain, GenesisConfig};
+use pali_coin::getwork::{
+    assemble, build_template, coinbase_commitment, coinbase_data, template_tree,
+};
+use pali_coin::mempool::Mempool;
+use pali_coin::node::Node;
+use pali_coin::proofs::{build_commitment_proof, verify_commitment_proof, CommitmentProof};
+use pali_coin::rpc::{dispatch, RpcContext};
+use pali_coin::rpc_auth::AuthConfig;
+use pali_coin::types::{Block, Hash256};
+use pali_coin::{math, MAINNET_CHAIN_ID};
+use serde_json::{json, Value};
+
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("pali-commit-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn genesis() -> GenesisConfig {
+    GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "commitment test".to_string(),
+        bits: math::MAX_BITS,
+        premine: Vec::new(),
+    }
+}
+
+fn grind(block: &mut Block) {
+    while !math::hash_meets_target(&block.header.hash(), block.header.bits) {
+        block.header.nonce = block.header.nonce.wrapping_add(1);
+    }
+}
+
+#[test]
+fn commitments_round_trip_the_coinbase_payload() {
+    let commitment: Hash256 = [0xC0; 32];
+    let data = coinbase_data(9, 4, Some(&commitment));
+    assert_eq!(coinbase_commitment(&data), Some(commitment));
+    // Plain mining payloads and arbitrary bytes carry no commitment.
+    assert_eq!(coinbase_commitment(&coinbase_data(9, 4, None)), None);
+    assert_eq!(coinbase_commitment(b"s2c: lookalike genesis message!!!!!!!"), None);
+
+    // Rolling the extranonce keeps the commitment in place.
+    let dir = test_dir("roll");
+    let chain = Blockchain::init_chain(&dir, &genesis()).unwrap();
+    let template = build_template(
+        &chain,
+        &Mempool::new(),
+        MAINNET_CHAIN_ID,
+        [0xAA; 20],
+        Some(&commitment),
+    )
+    .unwrap();
+    let mut tree = template_tree(&template);
+    let rolled = assemble(&template, &mut tree, 3, template.header.timestamp, 7);
+    assert_eq!(
+        coinbase_commitment(&rolled.transactions[0].data),
+        Some(commitment)
+    );
+    assert_eq!(
+        rolled.transactions[0].data,
+        coinbase_data(rolled.header.height, 7, Some(&commitment))
+    );
+}
+
+#[test]
+fn proofs_pin_the_commitment_to_a_block() {
+    let dir = test_dir("proof");
+    let mut chain = Blockchain::init_chain(&dir, &genesis()).unwrap();
+    let commitment: Hash256 = [0x5C; 32];
+    let mut block = build_template(
+        &chain,
+        &Mempool::new(),
+        MAINNET_CHAIN_ID,
+        [0xAA; 20],
+        Some(&commitment),
+    )
+    .unwrap();
+    grind(&mut block);
+    chain.add_block(&block, MAINNET_CHAIN_ID).unwrap();
+
+    let proof = build_commitment_proof(&chain, &block.hash()).unwrap();
+    assert_eq!(proof.commitment, commitment);
+    assert!(verify_commitment_proof(&proof));
+
+    // A reworded commitment no longer matches the coinbase bytes, and
+    // a tampered coinbase no longer hashes into the header.
+    let mut lied = proof.clone();
+    lied.commitment = [0xEE; 32];
+    assert!(!verify_commitment_proof(&lied));
+    let mut forged = CommitmentProof {
+        commitment: [0xEE; 32],
+        ..proof.clone()
+    };
+    forged.coinbase.data = coinbase_data(1, 0, Some(&[0xEE; 32]));
+    assert!(!verify_commitment_proof(&forged));
+
+    // Blocks whose coinbase carries no commitment have nothing to prove.
+    assert!(build_commitment_proof(&chain, &chain.get_block_hash(0).unwrap().unwrap()).is_err());
+}
+
+#[test]
+fn rpc_queues_one_commitment_per_mined_block() {
+    let dir = test_dir("rpc");
+    let chain = Arc::new(Mutex::new(
+        Blockchain::init_chain(&dir, &genesis()).unwrap(),
+    ));
+    let mempool = Arc::new(Mutex::new(Mempool::new()));
+    let node = Arc::new(Node::new(chain.clone(), mempool.clone(), MAINNET_CHAIN_ID));
+    let ctx = RpcContext {
+        chain: chain.clone(),
+        mempool,
+        node: Some(node.clone()),
+        chain_id: MAINNET_CHAIN_ID,
+        auth: Arc::new(AuthConfig::default()),
+    };
+
+    let commitment: Hash256 = [0x77; 32];
+    let set = dispatch(&ctx, "setblockcommitment", &json!([hex::encode(commitment)])).unwrap();
+    assert_eq!(set["commitment"], json!(hex::encode(commitment)));
+
+    // The queued hash rides in the next template's coinbase.
+    let template = dispatch(&ctx, "getblocktemplate", &json!([hex::encode([0xAA; 20])])).unwrap();
+    let data = hex::decode(template["coinbase_data"].as_str().unwrap()).unwrap();
+    assert_eq!(coinbase_commitment(&data), Some(commitment));
+
+    // Mining the block consumes the commitment: it is provable from
+    // the chain and gone from the queue.
+    let mut block = {
+        let chain = chain.lock().unwrap();
+        build_template(
+            &chain,
+            &Mempool::new(),
+            MAINNET_CHAIN_ID,
+            [0xAA; 20],
+            Some(&commitment),
+        )
+        .unwrap()
+    };
+    grind(&mut block);
+    let bytes = bincode::serialize(&block).unwrap();
+    dispatch(&ctx, "submitblock", &json!([hex::encode(bytes)])).unwrap();
+    assert_eq!(*node.next_commitment.lock().unwrap(), None);
+    let next = dispatch(&ctx, "getblocktemplate", &json!([hex::encode([0xAA; 20])])).unwrap();
+    let data = hex::decode(next["coinbase_data"].as_str().unwrap()).unwrap();
+    assert_eq!(coinbase_commitment(&data), None);
+
+    let proof = dispatch(&ctx, "getcommitmentproof", &json!([hex::encode(block.hash())])).unwrap();
+    assert_eq!(proof["commitment"], json!(hex::encode(commitment)));
+    let checked = dispatch(&ctx, "verifycommitmentproof", &json!([proof["proof"]])).unwrap();
+    assert_eq!(checked["valid"], Value::Bool(true));
+    assert_eq!(checked["in_best_chain"], Value::Bool(true));
+
+    // null clears a queued commitment without mining it.
+    dispatch(&ctx, "setblockcommitment", &json!([hex::encode([0x88; 32])])).unwrap();
+    let cleared = dispatch(&ctx, "setblockcommitment", &json!([null])).unwrap();
+    assert_eq!(cleared["commitment"], Value::Null);
+    assert_eq!(*node.next_commitment.lock().unwrap(), None);
+}
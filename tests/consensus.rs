@@ -0,0 +1,68 @@
+//! Golden vectors for the versioned consensus rules: block hashing,
+//! merkle construction and compact-bits difficulty checks. These pin
+//! down what CHAIN_RULES_VERSION promises; a change here requires a
+//! version bump alongside the re-bless.
+
+use pali_coin::consensus::CHAIN_RULES_VERSION;
+use pali_coin::hash;
+use pali_coin::math;
+
+fn check_golden(name: &str, bytes: &[u8]) {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.hex", name));
+    let actual = hex::encode(bytes);
+    if std::env::var("PALI_BLESS").is_ok() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, format!("{}\n", actual)).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("missing golden file {}: {}", path.display(), e));
+    assert_eq!(
+        expected.trim(),
+        actual,
+        "consensus rule output of {} changed — bump CHAIN_RULES_VERSION",
+        name
+    );
+}
+
+#[test]
+fn chain_rules_version_matches_the_vectors() {
+    // The golden files in this suite describe rule set v1; bless new
+    // vectors and bump together.
+    assert_eq!(CHAIN_RULES_VERSION, 1);
+}
+
+#[test]
+fn merkle_construction_is_frozen() {
+    let leaves: Vec<[u8; 32]> = (0u8..4).map(|i| [i; 32]).collect();
+    // Odd level duplication, power-of-two and single-leaf cases.
+    check_golden("merkle_1", &hash::merkle_root(&leaves[..1]));
+    check_golden("merkle_2", &hash::merkle_root(&leaves[..2]));
+    check_golden("merkle_3", &hash::merkle_root(&leaves[..3]));
+    check_golden("merkle_4", &hash::merkle_root(&leaves));
+    assert_eq!(hash::merkle_root(&[]), [0u8; 32]);
+}
+
+#[test]
+fn compact_bits_expansion_is_frozen() {
+    check_golden("target_max_bits", &math::bits_to_target(math::MAX_BITS));
+    check_golden("target_mid_bits", &math::bits_to_target(0x1d00ffff));
+}
+
+#[test]
+fn target_boundary_is_inclusive() {
+    let target = math::bits_to_target(math::MAX_BITS);
+    assert!(math::hash_meets_target(&target, math::MAX_BITS));
+    let mut above = target;
+    // Increment the big-endian target by one to step just past it.
+    for byte in above.iter_mut().rev() {
+        let (v, carry) = byte.overflowing_add(1);
+        *byte = v;
+        if !carry {
+            break;
+        }
+    }
+    assert!(!math::hash_meets_target(&above, math::MAX_BITS));
+}
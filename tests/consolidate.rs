@@ -0,0 +1,129 @@
+//! Dust consolidation planning and the listunspent RPC behind it.
+
+use std::sync::{Arc, Mutex};
+
+use pali_coin::blockchain::{Blockchain, GenesisConfig, PremineAllocation, COINBASE_MATURITY};
+use pali_coin::error::WalletErrorKind;
+use pali_coin::mempool::Mempool;
+use pali_coin::rpc::{dispatch, RpcContext};
+use pali_coin::rpc_auth::AuthConfig;
+use pali_coin::types::{
+    Block, BlockHeader, Hash256, Transaction, COIN, COINBASE_ADDRESS,
+};
+use pali_coin::wallet::plan_consolidation;
+use pali_coin::{hash, math, MAINNET_CHAIN_ID};
+use serde_json::{json, Value};
+
+#[test]
+fn plans_count_dust_and_price_the_merge() {
+    let amounts = [500, 900, 40_000, 2 * COIN];
+    let plan = plan_consolidation(&amounts, 1_000, 750).unwrap();
+    assert_eq!(plan.utxos, 4);
+    assert_eq!(plan.dust_utxos, 2);
+    assert_eq!(plan.spendable, 2 * COIN + 41_400);
+    assert_eq!(plan.fee, 750);
+    assert_eq!(plan.amount, plan.spendable - 750);
+}
+
+#[test]
+fn hopeless_merges_are_refused() {
+    // A single output has nothing to merge with.
+    let err = plan_consolidation(&[5 * COIN], 1_000, 100).unwrap_err();
+    assert_eq!(err.kind, WalletErrorKind::InvalidRequest);
+
+    // A fee larger than the dust it frees is a net loss the wallet
+    // refuses to sign.
+    let err = plan_consolidation(&[300, 400], 1_000, 700).unwrap_err();
+    assert_eq!(err.kind, WalletErrorKind::InsufficientFunds);
+}
+
+#[test]
+fn listunspent_reports_amounts_and_maturity() {
+    let dir = std::env::temp_dir().join(format!(
+        "pali-consolidate-{}-rpc",
+        std::process::id()
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    let holder = [0xAA; 20];
+    let miner = [0xBB; 20];
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "consolidate test".to_string(),
+        bits: math::MAX_BITS,
+        premine: vec![PremineAllocation {
+            address: hex::encode(holder),
+            amount: 3 * COIN,
+        }],
+    };
+    let chain = Arc::new(Mutex::new(Blockchain::init_chain(&dir, &config).unwrap()));
+    let ctx = RpcContext {
+        chain: chain.clone(),
+        mempool: Arc::new(Mutex::new(Mempool::new())),
+        node: None,
+        chain_id: MAINNET_CHAIN_ID,
+        auth: Arc::new(AuthConfig::default()),
+    };
+
+    // The premine is a coinbase output and starts out immature.
+    let unspent = dispatch(&ctx, "listunspent", &json!([hex::encode(holder)])).unwrap();
+    let entry = &unspent.as_array().unwrap()[0];
+    assert_eq!(entry["amount"], json!(3 * COIN));
+    assert_eq!(entry["height"], json!(0));
+    assert_eq!(entry["coinbase"], json!(true));
+    assert_eq!(entry["mature"], json!(false));
+
+    // Burying it under the maturity window flips the flag.
+    {
+        let mut chain = chain.lock().unwrap();
+        for _ in 0..COINBASE_MATURITY - 1 {
+            let height = chain.height() + 1;
+            let transactions = vec![Transaction {
+                chain_id: MAINNET_CHAIN_ID,
+                nonce: 0,
+                from: COINBASE_ADDRESS,
+                to: miner,
+                amount: chain.chain_params().block_reward_at(height),
+                fee: 0,
+                // Height in the coinbase data keeps otherwise identical
+                // reward transactions from hashing to the same outpoint.
+                data: height.to_be_bytes().to_vec(),
+                replaceable: false,
+                lock_time: 0,
+                signature: Vec::new(),
+                public_key: Vec::new(),
+            }];
+            let hashes: Vec<Hash256> = transactions.iter().map(|tx| tx.hash()).collect();
+            let mut header = BlockHeader {
+                version: 1,
+                prev_hash: chain.best_hash(),
+                merkle_root: hash::merkle_root(&hashes),
+                timestamp: 1_700_000_000 + height * 180,
+                bits: chain.next_bits().unwrap(),
+                nonce: 0,
+                height,
+            };
+            while !math::hash_meets_target(&header.hash(), header.bits) {
+                header.nonce = header.nonce.wrapping_add(1);
+            }
+            chain
+                .add_block(
+                    &Block {
+                        header,
+                        transactions,
+                    },
+                    MAINNET_CHAIN_ID,
+                )
+                .unwrap();
+        }
+    }
+    let unspent = dispatch(&ctx, "listunspent", &json!([hex::encode(holder)])).unwrap();
+    assert_eq!(unspent.as_array().unwrap()[0]["mature"], json!(true));
+
+    // The miner's rewards list oldest first, all still immature.
+    let unspent = dispatch(&ctx, "listunspent", &json!([hex::encode(miner)])).unwrap();
+    let rewards = unspent.as_array().unwrap();
+    assert_eq!(rewards.len(), (COINBASE_MATURITY - 1) as usize);
+    assert_eq!(rewards[0]["height"], json!(1));
+    assert!(rewards.iter().all(|u| u["mature"] == Value::Bool(false)));
+}
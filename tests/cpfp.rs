@@ -0,0 +1,78 @@
+//! Ancestor-package (child-pays-for-parent) mempool behavior.
+
+use pali_coin::mempool::Mempool;
+use pali_coin::types::Transaction;
+
+/// An unsigned transaction padded so fee rates are easy to reason
+/// about; the mempool trusts the caller's validation.
+fn tx(from: u8, nonce: u64, fee: u64) -> Transaction {
+    Transaction {
+        chain_id: 1,
+        nonce,
+        from: [from; 20],
+        to: [0xEE; 20],
+        amount: 1_000,
+        fee,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+#[test]
+fn child_fee_lifts_a_low_fee_parent_into_the_block() {
+    let mut pool = Mempool::new();
+    let parent = tx(1, 0, 200); // low rate on its own
+    let child = tx(1, 1, 20_000); // pays for both
+    let rival = tx(2, 0, 2_000); // beats the parent alone, not the package
+    let parent_size = parent.size();
+    pool.insert(parent.clone(), 0).unwrap();
+    pool.insert(child.clone(), 0).unwrap();
+    pool.insert(rival.clone(), 0).unwrap();
+
+    // Room for exactly the parent/child package: the low-fee parent is
+    // selected first because its child's fee prices the pair above the
+    // rival, and it precedes the child so the block validates.
+    let selected = pool.get_transactions_for_block(parent_size + child.size());
+    assert_eq!(selected, vec![parent.clone(), child.clone()]);
+
+    // With room for everything the rival still follows the package.
+    let selected = pool.get_transactions_for_block(usize::MAX);
+    assert_eq!(selected, vec![parent, child, rival]);
+}
+
+#[test]
+fn package_info_reports_the_effective_rate() {
+    let mut pool = Mempool::new();
+    let parent = tx(1, 0, 200);
+    let child = tx(1, 1, 20_000);
+    pool.insert(parent.clone(), 0).unwrap();
+    pool.insert(child.clone(), 0).unwrap();
+
+    let parent_info = pool.package_info(&parent.hash()).unwrap();
+    assert_eq!(parent_info.ancestor_count, 1);
+    assert_eq!(parent_info.ancestor_fees, 200);
+    // The parent's effective rate is the pair's rate, not its own.
+    let package_rate = 20_200.0 / (parent.size() + child.size()) as f64;
+    assert!((parent_info.effective_fee_rate - package_rate).abs() < 1e-9);
+
+    let child_info = pool.package_info(&child.hash()).unwrap();
+    assert_eq!(child_info.ancestor_count, 2);
+    assert_eq!(child_info.ancestor_fees, 20_200);
+    assert!((child_info.effective_fee_rate - package_rate).abs() < 1e-9);
+}
+
+#[test]
+fn chained_nonce_validation_checks_gaps_and_funding() {
+    let mut pool = Mempool::new();
+    pool.insert(tx(1, 0, 200), 0).unwrap();
+
+    // nonce 1 pending? no gap, funded.
+    assert!(pool.validate_chained(&tx(1, 1, 200), 0, 10_000).is_ok());
+    // nonce 2 would leave a gap at 1.
+    assert!(pool.validate_chained(&tx(1, 2, 200), 0, 10_000).is_err());
+    // Balance must cover the pending chain plus the newcomer.
+    assert!(pool.validate_chained(&tx(1, 1, 200), 0, 1_500).is_err());
+}
@@ -0,0 +1,107 @@
+//! Typed errors: callers match on kinds instead of substrings.
+
+use std::error::Error;
+
+use pali_coin::error::{MempoolErrorKind, PaliError, WalletErrorKind};
+use pali_coin::mempool::Mempool;
+use pali_coin::rejection::RejectionReason;
+use pali_coin::types::Transaction;
+use pali_coin::wallet::Wallet;
+
+fn tx(amount: u64, fee: u64) -> Transaction {
+    Transaction {
+        chain_id: 1,
+        nonce: 0,
+        from: [0x11; 20],
+        to: [0x22; 20],
+        amount,
+        fee,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+#[test]
+fn mempool_refusals_carry_a_matchable_kind() {
+    let mut pool = Mempool::new();
+    let dust = pool.insert(tx(0, 10_000), 0).unwrap_err();
+    assert_eq!(dust.kind, MempoolErrorKind::Dust);
+    // The Display form keeps the human-readable detail the logs rely on.
+    assert!(dust.to_string().contains("dust"));
+
+    pool.insert(tx(10_000, 10_000), 0).unwrap();
+    let duplicate = pool.insert(tx(10_000, 10_000), 0).unwrap_err();
+    assert_eq!(duplicate.kind, MempoolErrorKind::Duplicate);
+}
+
+#[test]
+fn wallet_failures_distinguish_locked_from_wrong_password() {
+    let dir = std::env::temp_dir().join(format!("pali-errors-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("wallet.dat");
+
+    let mut wallet = Wallet::new();
+    wallet.save_to_file(&path, "hunter2").unwrap();
+    let wrong = match Wallet::from_file(&path, "letmein") {
+        Err(e) => e,
+        Ok(_) => panic!("wrong password accepted"),
+    };
+    assert_eq!(wrong.kind, WalletErrorKind::BadPassword);
+
+    wallet.encrypt_in_memory("hunter2").unwrap();
+    let request = pali_coin::wallet::SendRequest {
+        to: [0x22; 20],
+        amount: 1_000,
+        fee: 100,
+        nonce: 0,
+        chain_id: 1,
+        replaceable: false,
+        tip_height: 0,
+    };
+    let locked = wallet.create_transaction(request).unwrap_err();
+    assert_eq!(locked.kind, WalletErrorKind::Locked);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn a_corrupt_wallet_file_preserves_the_decode_error_as_source() {
+    let dir = std::env::temp_dir().join(format!("pali-errors-src-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("garbage.dat");
+    std::fs::write(&path, b"not a wallet").unwrap();
+
+    let err = match Wallet::from_file(&path, "hunter2") {
+        Err(e) => e,
+        Ok(_) => panic!("garbage parsed as a wallet"),
+    };
+    assert_eq!(err.kind, WalletErrorKind::Corrupt);
+    assert!(err.source().is_some());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn consensus_verdicts_pass_through_the_taxonomy_unchanged() {
+    let wrapped = PaliError::from(RejectionReason::InsufficientFunds);
+    assert!(matches!(
+        &wrapped,
+        PaliError::Consensus(RejectionReason::InsufficientFunds)
+    ));
+    // And collapse back losslessly for the reject-message path.
+    assert_eq!(
+        RejectionReason::from(wrapped),
+        RejectionReason::InsufficientFunds
+    );
+    // Everything else becomes an internal rejection with its message.
+    let internal = RejectionReason::from(PaliError::Invalid("bad config".to_string()));
+    assert_eq!(internal, RejectionReason::Internal("bad config".to_string()));
+}
+
+#[test]
+fn string_call_sites_still_convert_for_the_rpc_layer() {
+    let mut pool = Mempool::new();
+    let as_string: String = pool.insert(tx(0, 10_000), 0).map_err(String::from).unwrap_err();
+    assert_eq!(as_string, "amount below the dust threshold");
+}
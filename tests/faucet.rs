@@ -0,0 +1,134 @@
+//! Faucet claims: token gate, cooldowns and the payout path.
+#![cfg(feature = "faucet")]
+
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+
+use pali_coin::blockchain::{
+    Blockchain, GenesisConfig, PremineAllocation, COINBASE_MATURITY,
+};
+use pali_coin::faucet::{Faucet, FaucetConfig};
+use pali_coin::mempool::Mempool;
+use pali_coin::rpc::RpcContext;
+use pali_coin::rpc_auth::AuthConfig;
+use pali_coin::types::{
+    block_reward, Block, BlockHeader, Hash256, Transaction, COINBASE_ADDRESS, COIN,
+};
+use pali_coin::wallet::Wallet;
+use pali_coin::{hash, math, MAINNET_CHAIN_ID};
+
+fn seal(chain: &Blockchain, transactions: Vec<Transaction>) -> Block {
+    let hashes: Vec<Hash256> = transactions.iter().map(|tx| tx.hash()).collect();
+    let mut header = BlockHeader {
+        version: 1,
+        prev_hash: chain.best_hash(),
+        merkle_root: hash::merkle_root(&hashes),
+        timestamp: 1_700_000_000 + (chain.height() + 1) * 180,
+        bits: chain.next_bits().unwrap(),
+        nonce: 0,
+        height: chain.height() + 1,
+    };
+    while !math::hash_meets_target(&header.hash(), header.bits) {
+        header.nonce = header.nonce.wrapping_add(1);
+    }
+    Block {
+        header,
+        transactions,
+    }
+}
+
+fn coinbase(height: u64) -> Transaction {
+    Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: COINBASE_ADDRESS,
+        to: [0x11; 20],
+        amount: block_reward(height),
+        fee: 0,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+/// A faucet over a chain whose wallet holds a matured premine.
+fn funded_faucet(name: &str, config: FaucetConfig) -> Faucet {
+    let dir = std::env::temp_dir().join(format!("pali-faucet-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    let wallet = Wallet::new();
+    let genesis = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "faucet test".to_string(),
+        bits: math::MAX_BITS,
+        premine: vec![PremineAllocation {
+            address: hex::encode(wallet.address()),
+            amount: 1_000 * COIN,
+        }],
+    };
+    let mut chain = Blockchain::init_chain(dir, &genesis).unwrap();
+    for height in 1..=COINBASE_MATURITY {
+        let block = seal(&chain, vec![coinbase(height)]);
+        chain.add_block(&block, MAINNET_CHAIN_ID).unwrap();
+    }
+    let ctx = RpcContext {
+        chain: Arc::new(Mutex::new(chain)),
+        mempool: Arc::new(Mutex::new(Mempool::new())),
+        node: None,
+        chain_id: MAINNET_CHAIN_ID,
+        auth: Arc::new(AuthConfig::default()),
+    };
+    Faucet::new(ctx, wallet, config)
+}
+
+#[test]
+fn claims_pay_out_and_chain_between_blocks() {
+    let faucet = funded_faucet("payout", FaucetConfig::default());
+    let ip: IpAddr = "203.0.113.1".parse().unwrap();
+    let first = faucet.pay(ip, &[0xAA; 20], None).unwrap();
+
+    // A different client and recipient claim before the next block;
+    // the faucet chains onto its own pending nonce.
+    let other: IpAddr = "203.0.113.2".parse().unwrap();
+    let second = faucet.pay(other, &[0xBB; 20], None).unwrap();
+    assert_ne!(first, second);
+}
+
+#[test]
+fn cooldown_limits_ip_and_address_separately() {
+    let faucet = funded_faucet("cooldown", FaucetConfig::default());
+    let ip: IpAddr = "203.0.113.1".parse().unwrap();
+    faucet.pay(ip, &[0xAA; 20], None).unwrap();
+
+    // Same IP, fresh address: blocked.
+    let err = faucet.pay(ip, &[0xBB; 20], None).unwrap_err();
+    assert!(err.contains("cooldown"));
+    // Fresh IP, same address: also blocked.
+    let other: IpAddr = "203.0.113.9".parse().unwrap();
+    let err = faucet.pay(other, &[0xAA; 20], None).unwrap_err();
+    assert!(err.contains("cooldown"));
+}
+
+#[test]
+fn access_tokens_gate_claims() {
+    let config = FaucetConfig {
+        token: Some("sesame".to_string()),
+        ..FaucetConfig::default()
+    };
+    let mut faucet = funded_faucet("token", config);
+    let ip: IpAddr = "203.0.113.1".parse().unwrap();
+
+    assert!(faucet.pay(ip, &[0xAA; 20], None).is_err());
+    assert!(faucet.pay(ip, &[0xAA; 20], Some("wrong")).is_err());
+    faucet.pay(ip, &[0xAA; 20], Some("sesame")).unwrap();
+
+    // A custom verifier (the captcha hook) overrides the static token.
+    faucet.set_token_verifier(Box::new(|token| token.starts_with("captcha:")));
+    let other: IpAddr = "203.0.113.2".parse().unwrap();
+    assert!(faucet.pay(other, &[0xBB; 20], Some("sesame")).is_err());
+    faucet
+        .pay(other, &[0xBB; 20], Some("captcha:solved"))
+        .unwrap();
+}
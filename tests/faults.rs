@@ -0,0 +1,177 @@
+//! The transport abstraction: peer handling over in-memory pipes and
+//! fault injection without real sockets.
+
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use pali_coin::blockchain::{Blockchain, GenesisConfig, PremineAllocation};
+use pali_coin::mempool::Mempool;
+use pali_coin::network::{
+    self, memory_pair, Faults, FaultyStream, NetworkMessage, PROTOCOL_VERSION,
+};
+use pali_coin::node::Node;
+use pali_coin::{math, MAINNET_CHAIN_ID};
+
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("pali-faults-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn test_node(name: &str) -> Arc<Node> {
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "faults test".to_string(),
+        bits: math::MAX_BITS,
+        premine: vec![PremineAllocation {
+            address: hex::encode([0xF7; 20]),
+            amount: 10_000,
+        }],
+    };
+    let chain = Blockchain::init_chain(test_dir(name), &config).unwrap();
+    Arc::new(Node::new(
+        Arc::new(Mutex::new(chain)),
+        Arc::new(Mutex::new(Mempool::new())),
+        MAINNET_CHAIN_ID,
+    ))
+}
+
+#[tokio::test]
+async fn the_handshake_completes_over_an_in_memory_pipe() {
+    let node = test_node("handshake");
+    let (server_end, mut client) = memory_pair();
+    let addr = "127.0.0.1:18444".parse().unwrap();
+    let server = {
+        let node = node.clone();
+        tokio::spawn(async move { node.handle_connection(server_end, addr, true).await })
+    };
+
+    // The node opens with its Version.
+    let opener = network::read_message(&mut client).await.unwrap();
+    assert!(matches!(
+        opener,
+        NetworkMessage::Version { chain_id: MAINNET_CHAIN_ID, .. }
+    ));
+    network::write_message(
+        &mut client,
+        &NetworkMessage::Version {
+            version: PROTOCOL_VERSION,
+            chain_id: MAINNET_CHAIN_ID,
+            height: 0,
+            user_agent: "/test:0/".to_string(),
+        },
+    )
+    .await
+    .unwrap();
+    assert!(matches!(
+        network::read_message(&mut client).await.unwrap(),
+        NetworkMessage::VerAck
+    ));
+    assert!(matches!(
+        network::read_message(&mut client).await.unwrap(),
+        NetworkMessage::ChainRules(_)
+    ));
+    // Modern peers get a locator request straight after the handshake.
+    assert!(matches!(
+        network::read_message(&mut client).await.unwrap(),
+        NetworkMessage::GetBlocksLocator { .. }
+    ));
+    assert_eq!(node.peers.lock().unwrap().len(), 1);
+
+    // A live connection answers pings.
+    network::write_message(&mut client, &NetworkMessage::Ping(7))
+        .await
+        .unwrap();
+    assert!(matches!(
+        network::read_message(&mut client).await.unwrap(),
+        NetworkMessage::Pong(7)
+    ));
+
+    // Hanging up tears the peer entry down.
+    drop(client);
+    server.await.unwrap().unwrap_err();
+    assert!(node.peers.lock().unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn a_wrong_chain_peer_is_refused() {
+    let node = test_node("wrongchain");
+    let (server_end, mut client) = memory_pair();
+    let addr = "127.0.0.1:18445".parse().unwrap();
+    let server = tokio::spawn(async move { node.handle_connection(server_end, addr, true).await });
+
+    network::read_message(&mut client).await.unwrap();
+    network::write_message(
+        &mut client,
+        &NetworkMessage::Version {
+            version: PROTOCOL_VERSION,
+            chain_id: MAINNET_CHAIN_ID.wrapping_add(1),
+            height: 0,
+            user_agent: "/test:0/".to_string(),
+        },
+    )
+    .await
+    .unwrap();
+    let err = server.await.unwrap().unwrap_err();
+    assert!(err.contains("wrong chain"), "got: {}", err);
+}
+
+#[tokio::test]
+async fn partial_writes_still_deliver_whole_messages() {
+    let (near, mut far) = memory_pair();
+    // Every write is chopped to a single byte.
+    let mut near = FaultyStream::new(
+        near,
+        Faults {
+            max_write: Some(1),
+            ..Faults::default()
+        },
+    );
+    let sent = NetworkMessage::Ping(0xDEAD_BEEF);
+    let writer = {
+        let sent = sent.clone();
+        tokio::spawn(async move { network::write_message(&mut near, &sent).await })
+    };
+    assert_eq!(network::read_message(&mut far).await.unwrap(), sent);
+    writer.await.unwrap().unwrap();
+}
+
+#[tokio::test]
+async fn a_mid_message_drop_fails_both_ends() {
+    let (near, mut far) = memory_pair();
+    let mut near = FaultyStream::new(
+        near,
+        Faults {
+            drop_after_bytes: Some(4),
+            ..Faults::default()
+        },
+    );
+    // The length prefix crosses, then the pipe breaks under the
+    // writer, leaving the reader with a truncated message.
+    network::write_message(&mut near, &NetworkMessage::Ping(1))
+        .await
+        .unwrap_err();
+    drop(near);
+    network::read_message(&mut far).await.unwrap_err();
+}
+
+#[tokio::test]
+async fn read_delays_slow_but_do_not_corrupt() {
+    let (near, far) = memory_pair();
+    let mut near = near;
+    let mut far = FaultyStream::new(
+        far,
+        Faults {
+            read_delay: Some(Duration::from_millis(30)),
+            ..Faults::default()
+        },
+    );
+    network::write_message(&mut near, &NetworkMessage::Pong(42))
+        .await
+        .unwrap();
+    let started = std::time::Instant::now();
+    let message = network::read_message(&mut far).await.unwrap();
+    assert_eq!(message, NetworkMessage::Pong(42));
+    assert!(started.elapsed() >= Duration::from_millis(30));
+}
@@ -0,0 +1,56 @@
+//! Mempool fee histogram and confirmation-target estimates.
+
+use pali_coin::mempool::{Mempool, FEE_HISTOGRAM_BUCKETS, MIN_RELAY_FEE_RATE};
+use pali_coin::types::Transaction;
+
+fn tx(from: u8, fee: u64) -> Transaction {
+    Transaction {
+        chain_id: 1,
+        nonce: 0,
+        from: [from; 20],
+        to: [0xEE; 20],
+        amount: 1_000,
+        fee,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+#[test]
+fn histogram_buckets_by_fee_rate() {
+    let mut pool = Mempool::new();
+    let cheap = tx(1, 150); // rate just above the relay floor
+    let dear = tx(2, 50_000); // lands in a high bucket
+    let size = cheap.size() as u64;
+    pool.insert(cheap, 0).unwrap();
+    pool.insert(dear, 0).unwrap();
+
+    let histogram = pool.fee_histogram();
+    assert_eq!(histogram.len(), FEE_HISTOGRAM_BUCKETS.len());
+    assert_eq!(histogram.iter().map(|b| b.count).sum::<u64>(), 2);
+    assert_eq!(histogram.iter().map(|b| b.fees).sum::<u64>(), 50_150);
+    // The cheap transaction sits in the lowest bucket.
+    assert_eq!(histogram[0].count, 1);
+    assert_eq!(histogram[0].size, size);
+}
+
+#[test]
+fn estimates_track_congestion() {
+    let mut pool = Mempool::new();
+    assert_eq!(pool.estimate_fee_rate(1, 1_000_000), MIN_RELAY_FEE_RATE);
+
+    for i in 0..10 {
+        pool.insert(tx(i, 10_000), 0).unwrap();
+    }
+    // Everything fits in one block: no congestion premium.
+    assert_eq!(pool.estimate_fee_rate(1, 1_000_000), MIN_RELAY_FEE_RATE);
+    // A one-transaction block leaves nine waiting; confirming next
+    // block means beating one of them.
+    let squeezed = pool.estimate_fee_rate(1, pool.iter().next().unwrap().1.size);
+    assert!(squeezed > MIN_RELAY_FEE_RATE);
+    // A looser target needs less.
+    assert!(pool.estimate_fee_rate(20, 1_000_000) <= squeezed);
+}
@@ -0,0 +1,72 @@
+//! Fork tip tracking behind `getchaintips`.
+
+use pali_coin::forks::{ForkMonitor, TipStatus, MAX_TRACKED_TIPS};
+use pali_coin::math;
+use pali_coin::types::{Block, BlockHeader, Hash256};
+
+fn block(height: u64, prev_hash: Hash256, nonce: u64) -> Block {
+    Block {
+        header: BlockHeader {
+            version: 1,
+            prev_hash,
+            merkle_root: [0u8; 32],
+            timestamp: 1_700_000_000,
+            bits: math::MAX_BITS,
+            nonce,
+            height,
+        },
+        transactions: Vec::new(),
+    }
+}
+
+#[test]
+fn extending_a_fork_grows_one_branch() {
+    let mut monitor = ForkMonitor::new();
+    let first = block(10, [1u8; 32], 1);
+    let second = block(11, first.hash(), 2);
+    monitor.record_stale(&first, true, 100);
+    monitor.record_stale(&second, true, 160);
+
+    let tips = monitor.tips();
+    assert_eq!(tips.len(), 1);
+    assert_eq!(tips[0].hash, second.hash());
+    assert_eq!(tips[0].height, 11);
+    assert_eq!(tips[0].branch_len, 2);
+    assert_eq!(tips[0].branch_work, 2 * math::block_work(math::MAX_BITS));
+    assert_eq!(tips[0].status, TipStatus::ValidFork);
+    // The branch keeps the time its first block appeared.
+    assert_eq!(tips[0].first_seen, 100);
+    assert_eq!(monitor.stats().stale_blocks_seen, 2);
+}
+
+#[test]
+fn invalidity_poisons_the_branch() {
+    let mut monitor = ForkMonitor::new();
+    let first = block(10, [1u8; 32], 1);
+    let second = block(11, first.hash(), 2);
+    monitor.record_stale(&first, false, 100);
+    monitor.record_stale(&second, true, 160);
+
+    let tips = monitor.tips();
+    assert_eq!(tips[0].status, TipStatus::Invalid);
+    assert_eq!(monitor.stats().invalid_blocks_seen, 1);
+    assert_eq!(monitor.stats().stale_blocks_seen, 1);
+}
+
+#[test]
+fn connected_tips_are_forgotten_and_depth_is_bounded() {
+    let mut monitor = ForkMonitor::new();
+    let first = block(10, [1u8; 32], 1);
+    monitor.record_stale(&first, true, 100);
+    monitor.record_connected(&first.hash());
+    assert!(monitor.tips().is_empty());
+
+    // Unrelated junk tips evict from the lowest height, keeping the
+    // deepest (most interesting) branches.
+    for i in 0..(MAX_TRACKED_TIPS as u64 + 8) {
+        monitor.record_stale(&block(i, [2u8; 32], i), false, 100 + i);
+    }
+    let tips = monitor.tips();
+    assert_eq!(tips.len(), MAX_TRACKED_TIPS);
+    assert!(tips.iter().all(|tip| tip.height >= 8));
+}
@@ -0,0 +1,86 @@
+//! The frame envelope: magic, command, length and checksum checks.
+
+use pali_coin::network::{
+    self, frame_checksum, frame_header, memory_pair, NetworkMessage, FRAME_HEADER_SIZE,
+    MAX_MESSAGE_SIZE, NETWORK_MAGIC,
+};
+use tokio::io::AsyncWriteExt;
+
+#[tokio::test]
+async fn a_frame_round_trips_and_carries_the_wire_id() {
+    let (mut near, mut far) = memory_pair();
+    let sent = NetworkMessage::Ping(0x70616C69);
+    network::write_message(&mut near, &sent).await.unwrap();
+    assert_eq!(network::read_message(&mut far).await.unwrap(), sent);
+
+    // The envelope is exactly what frame_header describes.
+    let payload = bincode::serialize(&sent).unwrap();
+    let header = frame_header(sent.wire_id(), &payload);
+    assert_eq!(header.len(), FRAME_HEADER_SIZE);
+    assert_eq!(&header[..4], &NETWORK_MAGIC);
+    assert_eq!(&header[4..8], &sent.wire_id().to_be_bytes());
+    assert_eq!(&header[8..12], &(payload.len() as u32).to_be_bytes());
+    assert_eq!(&header[12..], &frame_checksum(&payload));
+}
+
+#[tokio::test]
+async fn a_single_corrupted_byte_fails_the_checksum() {
+    let (mut near, mut far) = memory_pair();
+    let message = NetworkMessage::Pong(42);
+    let payload = bincode::serialize(&message).unwrap();
+    let mut frame = frame_header(message.wire_id(), &payload).to_vec();
+    frame.extend(payload);
+    let last = frame.len() - 1;
+    frame[last] ^= 0x01;
+    near.write_all(&frame).await.unwrap();
+    drop(near);
+    let err = network::read_message(&mut far).await.unwrap_err();
+    assert!(err.contains("checksum"), "unexpected error: {}", err);
+}
+
+#[tokio::test]
+async fn bad_headers_are_rejected_before_the_payload() {
+    // Wrong magic.
+    let (mut near, mut far) = memory_pair();
+    let payload = bincode::serialize(&NetworkMessage::Ping(1)).unwrap();
+    let mut frame = frame_header(2, &payload).to_vec();
+    frame[0] ^= 0xFF;
+    frame.extend(payload.clone());
+    near.write_all(&frame).await.unwrap();
+    drop(near);
+    let err = network::read_message(&mut far).await.unwrap_err();
+    assert!(err.contains("magic"), "unexpected error: {}", err);
+
+    // Unknown wire id.
+    let (mut near, mut far) = memory_pair();
+    let mut frame = frame_header(9_999, &payload).to_vec();
+    frame.extend(payload.clone());
+    near.write_all(&frame).await.unwrap();
+    drop(near);
+    let err = network::read_message(&mut far).await.unwrap_err();
+    assert!(err.contains("wire id"), "unexpected error: {}", err);
+
+    // A command id that disagrees with the payload.
+    let (mut near, mut far) = memory_pair();
+    let mut frame = frame_header(3, &payload).to_vec(); // Pong header, Ping payload
+    frame.extend(payload.clone());
+    near.write_all(&frame).await.unwrap();
+    drop(near);
+    let err = network::read_message(&mut far).await.unwrap_err();
+    assert!(err.contains("does not match"), "unexpected error: {}", err);
+
+    // An oversized length, rejected without allocating the payload.
+    let (mut near, mut far) = memory_pair();
+    let mut header = NETWORK_MAGIC.to_vec();
+    header.extend(2u32.to_be_bytes());
+    header.extend((MAX_MESSAGE_SIZE + 1).to_be_bytes());
+    header.extend([0u8; 4]);
+    near.write_all(&header).await.unwrap();
+    drop(near);
+    let err = network::read_message(&mut far).await.unwrap_err();
+    assert!(
+        err.contains("MAX_MESSAGE_SIZE"),
+        "unexpected error: {}",
+        err
+    );
+}
@@ -0,0 +1,105 @@
+//! Work assembly for the push-based mining protocol.
+
+use std::sync::{Arc, Mutex};
+
+use pali_coin::blockchain::{Blockchain, GenesisConfig};
+use pali_coin::getwork::{assemble, coinbase_data, ensure_chain_synced, template_tree};
+use pali_coin::hash;
+use pali_coin::mempool::Mempool;
+use pali_coin::node::Node;
+use pali_coin::rpc::RpcContext;
+use pali_coin::rpc_auth::AuthConfig;
+use pali_coin::types::{Block, BlockHeader, Hash256, Transaction, COINBASE_ADDRESS};
+use pali_coin::{math, MAINNET_CHAIN_ID};
+
+fn template() -> Block {
+    let coinbase = Transaction {
+        chain_id: 1,
+        nonce: 0,
+        from: COINBASE_ADDRESS,
+        to: [0xAA; 20],
+        amount: 1_000,
+        fee: 0,
+        data: coinbase_data(5, 0, None),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    };
+    let hashes: Vec<Hash256> = vec![coinbase.hash()];
+    Block {
+        header: BlockHeader {
+            version: 1,
+            prev_hash: [0x11; 32],
+            merkle_root: hash::merkle_root(&hashes),
+            timestamp: 1_700_000_000,
+            bits: pali_coin::math::MAX_BITS,
+            nonce: 0,
+            height: 5,
+        },
+        transactions: vec![coinbase],
+    }
+}
+
+#[test]
+fn mining_work_waits_for_minimum_chain_work() {
+    let dir = std::env::temp_dir().join(format!("pali-getwork-{}-minwork", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "minwork test".to_string(),
+        bits: math::MAX_BITS,
+        premine: Vec::new(),
+    };
+    let chain = Arc::new(Mutex::new(Blockchain::init_chain(&dir, &config).unwrap()));
+    let mempool = Arc::new(Mutex::new(Mempool::new()));
+    let genesis_work = chain.lock().unwrap().state().total_work;
+
+    let mut node = Node::new(chain.clone(), mempool.clone(), MAINNET_CHAIN_ID);
+    node.params.minimum_chain_work = genesis_work + 1;
+    let mut ctx = RpcContext {
+        chain,
+        mempool,
+        node: Some(Arc::new(node)),
+        chain_id: MAINNET_CHAIN_ID,
+        auth: Arc::new(AuthConfig::default()),
+    };
+
+    // Below the floor the node refuses to hand out or accept work.
+    let err = ensure_chain_synced(&ctx).unwrap_err();
+    assert!(err.contains("syncing"), "unexpected error: {}", err);
+
+    // At the floor (and with the default of zero) mining opens up.
+    let mut node = Node::new(ctx.chain.clone(), ctx.mempool.clone(), MAINNET_CHAIN_ID);
+    node.params.minimum_chain_work = genesis_work;
+    ctx.node = Some(Arc::new(node));
+    assert!(ensure_chain_synced(&ctx).is_ok());
+}
+
+#[test]
+fn coinbase_data_pins_height_and_extranonce() {
+    let data = coinbase_data(5, 7, None);
+    assert_eq!(data.len(), 16);
+    assert_eq!(&data[..8], &5u64.to_be_bytes());
+    assert_eq!(&data[8..], &7u64.to_le_bytes());
+    // Different extranonces give the coinbase distinct txids.
+    assert_ne!(coinbase_data(5, 7, None), coinbase_data(5, 8, None));
+}
+
+#[test]
+fn assemble_recomputes_the_merkle_root_for_the_extranonce() {
+    let template = template();
+    let mut tree = template_tree(&template);
+    let same = assemble(&template, &mut tree, 42, 1_700_000_060, 0);
+    assert_eq!(same.header.merkle_root, template.header.merkle_root);
+    assert_eq!(same.header.nonce, 42);
+    assert_eq!(same.header.timestamp, 1_700_000_060);
+
+    let rolled = assemble(&template, &mut tree, 42, 1_700_000_060, 1);
+    assert_ne!(rolled.header.merkle_root, template.header.merkle_root);
+    assert_eq!(rolled.transactions[0].data, coinbase_data(5, 1, None));
+    // The root matches a client-side recomputation.
+    let hashes: Vec<Hash256> = rolled.transactions.iter().map(|tx| tx.hash()).collect();
+    assert_eq!(rolled.header.merkle_root, hash::merkle_root(&hashes));
+}
@@ -0,0 +1,240 @@
+//! Inventory-based relay: Inv announcements, GetData service with
+//! NotFound answers, and the version-gated switch away from pushing
+//! full objects.
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use pali_coin::blockchain::{Blockchain, GenesisConfig, PremineAllocation};
+use pali_coin::mempool::Mempool;
+use pali_coin::network::{NetworkMessage, INV_RELAY_VERSION, MAX_INV_PER_MESSAGE};
+use pali_coin::node::{Node, PeerInfo};
+use pali_coin::types::{
+    block_reward, Block, BlockHeader, Hash256, Transaction, COINBASE_ADDRESS,
+};
+use pali_coin::{hash, math, MAINNET_CHAIN_ID};
+use tokio::sync::mpsc;
+
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("pali-inv-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn test_node(name: &str) -> Arc<Node> {
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "inv test".to_string(),
+        bits: math::MAX_BITS,
+        premine: vec![PremineAllocation {
+            address: hex::encode([0xE1; 20]),
+            amount: 50_000,
+        }],
+    };
+    let chain = Blockchain::init_chain(test_dir(name), &config).unwrap();
+    Arc::new(Node::new(
+        Arc::new(Mutex::new(chain)),
+        Arc::new(Mutex::new(Mempool::new())),
+        MAINNET_CHAIN_ID,
+    ))
+}
+
+fn peer_at(node: &Node, addr: &str, version: u32) -> mpsc::UnboundedReceiver<NetworkMessage> {
+    let addr: SocketAddr = addr.parse().unwrap();
+    let (tx, rx) = mpsc::unbounded_channel();
+    node.peers.lock().unwrap().insert(
+        addr,
+        PeerInfo {
+            addr,
+            inbound: true,
+            version,
+            user_agent: String::new(),
+            best_height: 0,
+            connected_at: 0,
+            last_seen: 0,
+            ping_time: None,
+            pending_ping: None,
+            sender: tx,
+        },
+    );
+    rx
+}
+
+fn pool_tx(fee: u64) -> Transaction {
+    Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: [0xE1; 20],
+        to: [0xE2; 20],
+        amount: 1_000,
+        fee,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+fn sealed_block(chain: &Blockchain) -> Block {
+    let height = chain.height() + 1;
+    let coinbase = Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: COINBASE_ADDRESS,
+        to: [0x11; 20],
+        amount: block_reward(height),
+        fee: 0,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    };
+    let hashes: Vec<Hash256> = vec![coinbase.hash()];
+    let mut header = BlockHeader {
+        version: 1,
+        prev_hash: chain.best_hash(),
+        merkle_root: hash::merkle_root(&hashes),
+        timestamp: 1_700_000_000 + height * 180,
+        bits: chain.next_bits().unwrap(),
+        nonce: 0,
+        height,
+    };
+    while !math::hash_meets_target(&header.hash(), header.bits) {
+        header.nonce = header.nonce.wrapping_add(1);
+    }
+    Block {
+        header,
+        transactions: vec![coinbase],
+    }
+}
+
+#[test]
+fn an_announcement_is_answered_with_a_request_for_what_we_lack() {
+    let node = test_node("announce");
+    let mut rx = peer_at(&node, "192.0.2.20:7777", INV_RELAY_VERSION);
+    let addr: SocketAddr = "192.0.2.20:7777".parse().unwrap();
+
+    let known_block = node.chain.lock().unwrap().best_hash();
+    let pooled = pool_tx(10_000);
+    node.mempool.lock().unwrap().insert(pooled.clone(), 0).unwrap();
+    let unknown_block = [0xAB; 32];
+    let unknown_tx = [0xCD; 32];
+
+    node.handle_network_message(
+        addr,
+        NetworkMessage::Inv {
+            blocks: vec![known_block, unknown_block],
+            txs: vec![pooled.hash(), unknown_tx],
+        },
+    )
+    .unwrap();
+    match rx.try_recv().unwrap() {
+        NetworkMessage::GetData { blocks, txs } => {
+            assert_eq!(blocks, vec![unknown_block]);
+            assert_eq!(txs, vec![unknown_tx]);
+        }
+        other => panic!("expected GetData, got {:?}", other),
+    }
+
+    // An announcement carrying nothing new stays unanswered.
+    node.handle_network_message(
+        addr,
+        NetworkMessage::Inv {
+            blocks: vec![known_block],
+            txs: vec![pooled.hash()],
+        },
+    )
+    .unwrap();
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn getdata_serves_bodies_and_reports_the_rest_capped() {
+    let node = test_node("serve");
+    let mut rx = peer_at(&node, "192.0.2.21:7777", INV_RELAY_VERSION);
+    let addr: SocketAddr = "192.0.2.21:7777".parse().unwrap();
+
+    let genesis = node.chain.lock().unwrap().best_hash();
+    let pooled = pool_tx(10_000);
+    node.mempool.lock().unwrap().insert(pooled.clone(), 0).unwrap();
+
+    node.handle_network_message(
+        addr,
+        NetworkMessage::GetData {
+            blocks: vec![genesis, [0xAB; 32]],
+            txs: vec![pooled.hash(), [0xCD; 32]],
+        },
+    )
+    .unwrap();
+    match rx.try_recv().unwrap() {
+        NetworkMessage::Block(block) => assert_eq!(block.hash(), genesis),
+        other => panic!("expected Block, got {:?}", other),
+    }
+    match rx.try_recv().unwrap() {
+        NetworkMessage::Transaction(tx) => assert_eq!(tx.hash(), pooled.hash()),
+        other => panic!("expected Transaction, got {:?}", other),
+    }
+    match rx.try_recv().unwrap() {
+        NetworkMessage::NotFound { blocks, txs } => {
+            assert_eq!(blocks, vec![[0xAB; 32]]);
+            assert_eq!(txs, vec![[0xCD; 32]]);
+        }
+        other => panic!("expected NotFound, got {:?}", other),
+    }
+
+    // A request past the inventory cap is served only up to the cap.
+    let greedy: Vec<Hash256> = (0..=MAX_INV_PER_MESSAGE)
+        .map(|i| {
+            let mut hash = [0xEE; 32];
+            hash[..8].copy_from_slice(&(i as u64).to_le_bytes());
+            hash
+        })
+        .collect();
+    node.handle_network_message(
+        addr,
+        NetworkMessage::GetData {
+            blocks: greedy,
+            txs: Vec::new(),
+        },
+    )
+    .unwrap();
+    match rx.try_recv().unwrap() {
+        NetworkMessage::NotFound { blocks, .. } => {
+            assert_eq!(blocks.len(), MAX_INV_PER_MESSAGE);
+        }
+        other => panic!("expected NotFound, got {:?}", other),
+    }
+}
+
+#[test]
+fn relay_announces_to_modern_peers_and_pushes_to_legacy_ones() {
+    let node = test_node("relay");
+    let mut modern = peer_at(&node, "192.0.2.22:7777", INV_RELAY_VERSION);
+    let mut legacy = peer_at(&node, "192.0.2.23:7777", INV_RELAY_VERSION - 1);
+    let mut source_rx = peer_at(&node, "192.0.2.24:7777", INV_RELAY_VERSION);
+    let source: SocketAddr = "192.0.2.24:7777".parse().unwrap();
+
+    let block = {
+        let chain = node.chain.lock().unwrap();
+        sealed_block(&chain)
+    };
+    node.handle_network_message(source, NetworkMessage::Block(block.clone()))
+        .unwrap();
+    assert_eq!(node.chain.lock().unwrap().best_hash(), block.hash());
+
+    match modern.try_recv().unwrap() {
+        NetworkMessage::Inv { blocks, txs } => {
+            assert_eq!(blocks, vec![block.hash()]);
+            assert!(txs.is_empty());
+        }
+        other => panic!("expected Inv, got {:?}", other),
+    }
+    match legacy.try_recv().unwrap() {
+        NetworkMessage::Block(pushed) => assert_eq!(pushed.hash(), block.hash()),
+        other => panic!("expected Block, got {:?}", other),
+    }
+    assert!(source_rx.try_recv().is_err(), "never echoed to the sender");
+}
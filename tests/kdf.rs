@@ -0,0 +1,41 @@
+//! Argon2 auto-tuning and session key caching.
+
+use std::time::Instant;
+
+use pali_coin::wallet::{KdfParams, Wallet, MIN_KDF_MEMORY_KIB};
+
+#[test]
+fn auto_tune_stays_within_bounds() {
+    let tuned = KdfParams::auto_tune();
+    assert!(tuned.memory_kib >= MIN_KDF_MEMORY_KIB);
+    assert!(tuned.memory_kib <= KdfParams::default().memory_kib);
+    assert_eq!(tuned.iterations, 3);
+}
+
+#[test]
+fn session_cache_skips_rederivation_on_resave() {
+    let dir = std::env::temp_dir().join(format!("pali-kdf-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("wallet.dat");
+
+    let mut wallet = Wallet::new();
+    let address = wallet.address();
+    wallet.save_to_file(&path, "hunter2").unwrap();
+
+    let mut loaded = Wallet::from_file(&path, "hunter2").unwrap();
+    assert_eq!(loaded.address(), address);
+    // Loading cached the derived key for the session, so an immediate
+    // re-save performs no Argon2 work at all.
+    let started = Instant::now();
+    loaded.save_to_file(&path, "hunter2").unwrap();
+    assert!(started.elapsed().as_millis() < 100);
+
+    // The cache is password-checked: a different password still has to
+    // derive, and the rewritten file then opens under it.
+    loaded.save_to_file(&path, "changed").unwrap();
+    let reloaded = Wallet::from_file(&path, "changed").unwrap();
+    assert_eq!(reloaded.address(), address);
+    assert!(Wallet::from_file(&path, "hunter2").is_err());
+
+    std::fs::remove_dir_all(&dir).ok();
+}
@@ -0,0 +1,103 @@
+//! The experimental libp2p transport: topic mapping and query answers.
+#![cfg(feature = "libp2p")]
+
+use pali_coin::blockchain::{Blockchain, GenesisConfig, PremineAllocation};
+use pali_coin::libp2p_transport::{answer_query, block_topic, topic_for, tx_topic};
+use pali_coin::network::NetworkMessage;
+use pali_coin::types::{Transaction, COIN};
+use pali_coin::{math, MAINNET_CHAIN_ID};
+
+fn empty_tx() -> Transaction {
+    Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: [0x01; 20],
+        to: [0x02; 20],
+        amount: 1,
+        fee: 0,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+fn premined_chain(name: &str, address: &[u8; 20]) -> Blockchain {
+    let dir = std::env::temp_dir().join(format!("pali-libp2p-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "libp2p test".to_string(),
+        bits: math::MAX_BITS,
+        premine: vec![PremineAllocation {
+            address: hex::encode(address),
+            amount: COIN,
+        }],
+    };
+    Blockchain::init_chain(dir, &config).unwrap()
+}
+
+#[test]
+fn announcements_map_onto_chain_scoped_topics() {
+    assert_ne!(block_topic(1), block_topic(2));
+    assert_ne!(block_topic(1), tx_topic(1));
+
+    let tx = empty_tx();
+    assert_eq!(
+        topic_for(1, &NetworkMessage::Transaction(tx.clone())),
+        Some(tx_topic(1))
+    );
+    // Stems have no private route on a flooding transport.
+    assert_eq!(
+        topic_for(1, &NetworkMessage::StemTransaction(tx)),
+        Some(tx_topic(1))
+    );
+    assert_eq!(
+        topic_for(1, &NetworkMessage::Blocks(Vec::new())),
+        Some(block_topic(1))
+    );
+    // Queries and control traffic never hit a gossip topic.
+    assert_eq!(topic_for(1, &NetworkMessage::GetPeers), None);
+    assert_eq!(topic_for(1, &NetworkMessage::Ping(7)), None);
+}
+
+#[test]
+fn queries_are_answered_from_the_chain() {
+    let address = [0xAA; 20];
+    let chain = premined_chain("queries", &address);
+
+    assert_eq!(
+        answer_query(&chain, &NetworkMessage::Ping(42)),
+        Some(NetworkMessage::Pong(42))
+    );
+
+    match answer_query(&chain, &NetworkMessage::GetBlockRange { start: 0, count: 5 }) {
+        Some(NetworkMessage::Blocks(blocks)) => {
+            assert_eq!(blocks.len(), 1);
+            assert_eq!(blocks[0].hash(), chain.best_hash());
+        }
+        other => panic!("expected Blocks, got {:?}", other),
+    }
+
+    match answer_query(&chain, &NetworkMessage::GetUtxoProof { address }) {
+        Some(NetworkMessage::UtxoProof(proof)) => {
+            let balance = pali_coin::proofs::verify_address_proof(&proof, |height| {
+                chain
+                    .get_block_by_height(height)
+                    .unwrap()
+                    .map(|block| block.header)
+            })
+            .unwrap();
+            assert_eq!(balance.proven, COIN);
+        }
+        other => panic!("expected UtxoProof, got {:?}", other),
+    }
+
+    // Announcements are not queries.
+    assert_eq!(
+        answer_query(&chain, &NetworkMessage::Transaction(empty_tx())),
+        None
+    );
+}
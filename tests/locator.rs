@@ -0,0 +1,102 @@
+//! Block locators: exponential spacing and fork-point lookup.
+
+use pali_coin::blockchain::{Blockchain, GenesisConfig, PremineAllocation};
+use pali_coin::types::{block_reward, Block, BlockHeader, Hash256, Transaction, COINBASE_ADDRESS};
+use pali_coin::{hash, math, MAINNET_CHAIN_ID};
+
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("pali-locator-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn new_chain(name: &str) -> Blockchain {
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "locator test".to_string(),
+        bits: math::MAX_BITS,
+        premine: vec![PremineAllocation {
+            address: hex::encode([0xAB; 20]),
+            amount: 1_000,
+        }],
+    };
+    Blockchain::init_chain(test_dir(name), &config).unwrap()
+}
+
+fn coinbase(height: u64) -> Transaction {
+    Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: COINBASE_ADDRESS,
+        to: [0x11; 20],
+        amount: block_reward(height),
+        fee: 0,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+fn mine_one(chain: &mut Blockchain) {
+    let height = chain.height() + 1;
+    let transactions = vec![coinbase(height)];
+    let hashes: Vec<Hash256> = transactions.iter().map(|tx| tx.hash()).collect();
+    let mut header = BlockHeader {
+        version: 1,
+        prev_hash: chain.best_hash(),
+        merkle_root: hash::merkle_root(&hashes),
+        timestamp: 1_700_000_000 + height * 180,
+        bits: chain.next_bits().unwrap(),
+        nonce: 0,
+        height,
+    };
+    while !math::hash_meets_target(&header.hash(), header.bits) {
+        header.nonce = header.nonce.wrapping_add(1);
+    }
+    let block = Block {
+        header,
+        transactions,
+    };
+    chain.add_block(&block, MAINNET_CHAIN_ID).unwrap();
+}
+
+#[test]
+fn locator_steps_by_one_near_the_tip_then_doubles_to_genesis() {
+    let mut chain = new_chain("spacing");
+    for _ in 0..14 {
+        mine_one(&mut chain);
+    }
+    let locator = chain.block_locator().unwrap();
+    assert_eq!(locator[0], chain.best_hash());
+    assert_eq!(
+        *locator.last().unwrap(),
+        chain.get_block_by_height(0).unwrap().unwrap().hash()
+    );
+    // The first ten entries walk back one height at a time.
+    let heights: Vec<u64> = locator
+        .iter()
+        .map(|hash| chain.get_block(hash).unwrap().unwrap().header.height)
+        .collect();
+    assert_eq!(&heights[..10], &[14, 13, 12, 11, 10, 9, 8, 7, 6, 5]);
+    // After that the stride doubles: far fewer entries than blocks.
+    assert!(heights.len() < 14);
+    assert!(heights.windows(2).all(|w| w[0] > w[1]));
+}
+
+#[test]
+fn the_first_known_main_chain_entry_is_the_fork_point() {
+    let mut chain = new_chain("fork");
+    for _ in 0..5 {
+        mine_one(&mut chain);
+    }
+    let third = chain.get_block_by_height(3).unwrap().unwrap().hash();
+    // A requester on a fork: its newest hashes are unknown to us, the
+    // first shared entry wins.
+    let locator = vec![[0xEE; 32], [0xEF; 32], third, chain.best_hash()];
+    assert_eq!(chain.locate_fork(&locator).unwrap(), Some(third));
+    // Nothing shared at all.
+    assert_eq!(chain.locate_fork(&[[0xEE; 32]]).unwrap(), None);
+}
@@ -0,0 +1,33 @@
+//! Anti-fee-sniping locktime behavior.
+
+use pali_coin::wallet::{anti_fee_sniping_locktime, SendRequest, Wallet};
+
+#[test]
+fn locktime_never_exceeds_tip_height() {
+    for tip in [0u64, 1, 99, 100, 10_000] {
+        for _ in 0..50 {
+            let lock_time = anti_fee_sniping_locktime(tip);
+            assert!(lock_time <= tip);
+            // The random walk-back is bounded at 100 blocks.
+            assert!(lock_time >= tip.saturating_sub(100));
+        }
+    }
+}
+
+#[test]
+fn wallet_transactions_carry_anti_sniping_locktime() {
+    let mut wallet = Wallet::new();
+    let tip = 5_000;
+    let tx = wallet
+        .create_transaction(SendRequest {
+            to: [0x42; 20],
+            amount: 1_000_000,
+            fee: 10_000,
+            nonce: 0,
+            chain_id: 3,
+            replaceable: false,
+            tip_height: tip,
+        })
+        .expect("fresh wallet can sign");
+    assert!(tx.lock_time <= tip && tx.lock_time >= tip - 100);
+}
@@ -0,0 +1,57 @@
+//! Incremental merkle tree maintenance for block templates.
+
+use pali_coin::hash::{merkle_root, IncrementalMerkle};
+use pali_coin::types::Hash256;
+
+fn leaves(n: usize) -> Vec<Hash256> {
+    (0..n).map(|i| [i as u8; 32]).collect()
+}
+
+#[test]
+fn matches_the_batch_construction_at_every_size() {
+    // Every size from empty through a few levels, covering the
+    // duplicated-last-hash rule on odd counts.
+    for n in 0..=17 {
+        let leaves = leaves(n);
+        let tree = IncrementalMerkle::new(&leaves);
+        assert_eq!(tree.root(), merkle_root(&leaves), "size {}", n);
+        assert_eq!(tree.len(), n);
+    }
+}
+
+#[test]
+fn leaf_edits_track_the_batch_root() {
+    let mut leaves = leaves(11);
+    let mut tree = IncrementalMerkle::new(&leaves);
+    // Rolling the coinbase is a leaf-0 edit.
+    for round in 0..3u8 {
+        leaves[0] = [0xC0 + round; 32];
+        tree.set(0, leaves[0]);
+        assert_eq!(tree.root(), merkle_root(&leaves), "round {}", round);
+    }
+    // An interior edit rehashes only that path but lands on the same
+    // root as a full rebuild.
+    leaves[7] = [0xEE; 32];
+    tree.set(7, leaves[7]);
+    assert_eq!(tree.root(), merkle_root(&leaves));
+}
+
+#[test]
+fn pushes_and_pops_cross_level_boundaries() {
+    let mut leaves: Vec<Hash256> = Vec::new();
+    let mut tree = IncrementalMerkle::new(&leaves);
+    assert_eq!(tree.root(), [0u8; 32]);
+
+    // Grow past a power-of-two boundary one leaf at a time.
+    for i in 0..9u8 {
+        leaves.push([i; 32]);
+        tree.push([i; 32]);
+        assert_eq!(tree.root(), merkle_root(&leaves), "after push {}", i);
+    }
+    // And shrink all the way back down.
+    while let Some(leaf) = tree.pop() {
+        assert_eq!(Some(leaf), leaves.pop());
+        assert_eq!(tree.root(), merkle_root(&leaves));
+    }
+    assert!(tree.is_empty());
+}
@@ -0,0 +1,82 @@
+//! Mining upstream failover behavior.
+
+use std::time::Duration;
+
+use pali_coin::miner::{Miner, MiningConfig, Upstream, SHARE_WINDOW};
+
+fn config() -> MiningConfig {
+    MiningConfig {
+        pools: vec!["pool-a:3333".to_string(), "pool-b:3333".to_string()],
+        ..MiningConfig::default()
+    }
+}
+
+#[test]
+fn connection_losses_walk_the_pool_list_then_go_solo() {
+    let mut miner = Miner::new(config());
+    assert_eq!(miner.current_upstream(), Upstream::Pool(0));
+
+    for _ in 0..3 {
+        miner.record_connection_loss();
+    }
+    assert_eq!(miner.current_upstream(), Upstream::Pool(1));
+
+    for _ in 0..3 {
+        miner.record_connection_loss();
+    }
+    assert_eq!(miner.current_upstream(), Upstream::Solo);
+
+    // Solo is the floor; further failures change nothing.
+    for _ in 0..10 {
+        miner.record_connection_loss();
+    }
+    assert_eq!(miner.current_upstream(), Upstream::Solo);
+    assert_eq!(miner.stats().failovers.len(), 2);
+}
+
+#[test]
+fn sustained_share_rejections_trigger_failover() {
+    let mut miner = Miner::new(config());
+    for _ in 0..SHARE_WINDOW {
+        miner.record_share(false);
+    }
+    assert_eq!(miner.current_upstream(), Upstream::Pool(1));
+    assert_eq!(miner.stats().shares_rejected, SHARE_WINDOW as u64);
+
+    // Mostly accepted shares keep the upstream.
+    for i in 0..SHARE_WINDOW * 2 {
+        miner.record_share(i % 4 != 0);
+    }
+    assert_eq!(miner.current_upstream(), Upstream::Pool(1));
+}
+
+#[test]
+fn reconnect_backoff_doubles_and_caps() {
+    let mut miner = Miner::new(MiningConfig {
+        pools: vec!["pool-a:3333".to_string()],
+        max_failures: 100,
+        ..MiningConfig::default()
+    });
+    assert_eq!(miner.reconnect_delay(), Duration::ZERO);
+    miner.record_connection_loss();
+    assert_eq!(miner.reconnect_delay(), Duration::from_secs(1));
+    miner.record_connection_loss();
+    assert_eq!(miner.reconnect_delay(), Duration::from_secs(2));
+    for _ in 0..20 {
+        miner.record_connection_loss();
+    }
+    assert_eq!(miner.reconnect_delay(), Duration::from_secs(60));
+    miner.record_connected();
+    assert_eq!(miner.reconnect_delay(), Duration::ZERO);
+}
+
+#[test]
+fn preferred_pool_wins_back_after_recovery() {
+    let mut miner = Miner::new(config());
+    for _ in 0..3 {
+        miner.record_connection_loss();
+    }
+    assert_eq!(miner.current_upstream(), Upstream::Pool(1));
+    miner.retry_preferred();
+    assert_eq!(miner.current_upstream(), Upstream::Pool(0));
+}
@@ -0,0 +1,45 @@
+//! Inbound message prioritization and backpressure.
+
+use pali_coin::msgqueue::{classify, MessageQueue, Priority};
+use pali_coin::network::NetworkMessage;
+
+#[test]
+fn blocks_jump_the_queue() {
+    let mut queue = MessageQueue::new(16);
+    queue.push(NetworkMessage::GetPeers).unwrap();
+    queue.push(NetworkMessage::Ping(1)).unwrap();
+    queue
+        .push(NetworkMessage::GetBlockRange { start: 0, count: 1 })
+        .unwrap();
+    queue.push(NetworkMessage::Blocks(Vec::new())).unwrap();
+
+    assert!(matches!(queue.pop(), Some(NetworkMessage::Blocks(_))));
+    assert!(matches!(queue.pop(), Some(NetworkMessage::Ping(1))));
+    assert!(matches!(queue.pop(), Some(NetworkMessage::GetPeers)));
+    assert!(matches!(
+        queue.pop(),
+        Some(NetworkMessage::GetBlockRange { .. })
+    ));
+    assert!(queue.pop().is_none());
+}
+
+#[test]
+fn overflow_is_an_error_across_classes() {
+    let mut queue = MessageQueue::new(2);
+    queue.push(NetworkMessage::GetPeers).unwrap();
+    queue.push(NetworkMessage::Ping(1)).unwrap();
+    // The depth limit is shared: even a high-priority message bounces.
+    assert!(queue.push(NetworkMessage::Blocks(Vec::new())).is_err());
+    queue.pop();
+    queue.push(NetworkMessage::Blocks(Vec::new())).unwrap();
+}
+
+#[test]
+fn classification_covers_the_tiers() {
+    assert_eq!(
+        classify(&NetworkMessage::Blocks(Vec::new())),
+        Priority::Block
+    );
+    assert_eq!(classify(&NetworkMessage::Ping(0)), Priority::Consensus);
+    assert_eq!(classify(&NetworkMessage::GetPeers), Priority::Query);
+}
@@ -0,0 +1,223 @@
+//! Hosted wallets: per-wallet lock state, watch-list wiring and spends
+//! through the loadwallet/unloadwallet/listwallets RPC family.
+
+use std::sync::{Arc, Mutex};
+
+use pali_coin::blockchain::{
+    Blockchain, GenesisConfig, PremineAllocation, COINBASE_MATURITY,
+};
+use pali_coin::mempool::Mempool;
+use pali_coin::multiwallet::WalletManager;
+use pali_coin::node::Node;
+use pali_coin::rpc::{dispatch, RpcContext};
+use pali_coin::rpc_auth::{method_scope, AuthConfig, Scope};
+use pali_coin::types::{
+    block_reward, Block, BlockHeader, Hash256, Transaction, COIN, COINBASE_ADDRESS,
+};
+use pali_coin::wallet::Wallet;
+use pali_coin::{hash, math, MAINNET_CHAIN_ID};
+use serde_json::json;
+
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+        "pali-multiwallet-{}-{}",
+        std::process::id(),
+        name
+    ));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn saved_wallet(dir: &std::path::Path, file: &str, password: &str) -> (Wallet, std::path::PathBuf) {
+    let path = dir.join(file);
+    let mut wallet = Wallet::new();
+    wallet.save_to_file(&path, password).unwrap();
+    (Wallet::from_file(&path, password).unwrap(), path)
+}
+
+fn mine_one(chain: &mut Blockchain) {
+    let height = chain.height() + 1;
+    let coinbase = Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: COINBASE_ADDRESS,
+        to: [0x11; 20],
+        amount: block_reward(height),
+        fee: 0,
+        data: height.to_be_bytes().to_vec(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    };
+    let hashes: Vec<Hash256> = vec![coinbase.hash()];
+    let mut header = BlockHeader {
+        version: 1,
+        prev_hash: chain.best_hash(),
+        merkle_root: hash::merkle_root(&hashes),
+        timestamp: 1_700_000_000 + height * 180,
+        bits: chain.next_bits().unwrap(),
+        nonce: 0,
+        height,
+    };
+    while !math::hash_meets_target(&header.hash(), header.bits) {
+        header.nonce = header.nonce.wrapping_add(1);
+    }
+    let block = Block {
+        header,
+        transactions: vec![coinbase],
+    };
+    chain.add_block(&block, MAINNET_CHAIN_ID).unwrap();
+}
+
+#[test]
+fn the_manager_isolates_lock_state_between_wallets() {
+    let dir = test_dir("manager");
+    let (alice, alice_path) = saved_wallet(&dir, "alice.dat", "alice-pw");
+    let (bob, bob_path) = saved_wallet(&dir, "bob.dat", "bob-pw");
+    let alice_addr = alice.address();
+
+    let mut manager = WalletManager::new();
+    manager.load("alice", alice_path, alice).unwrap();
+    manager.load("bob", bob_path, bob).unwrap();
+    assert_eq!(manager.len(), 2);
+
+    // Freshly loaded wallets hold no usable keys.
+    assert!(manager.get_mut("alice").unwrap().wallet.is_locked());
+    assert!(manager.get_mut("bob").unwrap().wallet.is_locked());
+
+    // Unlocking one wallet leaves the other locked.
+    manager
+        .get_mut("alice")
+        .unwrap()
+        .wallet
+        .unlock("alice-pw", 60)
+        .unwrap();
+    assert!(!manager.get_mut("alice").unwrap().wallet.is_locked());
+    assert!(manager.get_mut("bob").unwrap().wallet.is_locked());
+
+    // Names are unique; unknown names are errors; unloading frees one.
+    let (dup, dup_path) = saved_wallet(&dir, "dup.dat", "x");
+    assert!(manager.load("alice", dup_path, dup).is_err());
+    assert!(manager.get_mut("carol").is_err());
+    assert!(manager.watches(&alice_addr));
+    let unloaded = manager.unload("alice").unwrap();
+    assert_eq!(unloaded.wallet.address(), alice_addr);
+    assert!(!manager.watches(&alice_addr));
+    assert!(manager.unload("alice").is_none());
+    assert_eq!(manager.len(), 1);
+}
+
+#[test]
+fn rpc_loading_wires_the_watch_list_and_tracks_lock_state() {
+    let dir = test_dir("rpc");
+    let (wallet, path) = saved_wallet(&dir, "hosted.dat", "secret");
+    let address = wallet.address();
+    drop(wallet);
+
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "multiwallet test".to_string(),
+        bits: math::MAX_BITS,
+        premine: Vec::new(),
+    };
+    let chain = Arc::new(Mutex::new(
+        Blockchain::init_chain(dir.join("chain"), &config).unwrap(),
+    ));
+    let mempool = Arc::new(Mutex::new(Mempool::new()));
+    let node = Arc::new(Node::new(chain.clone(), mempool.clone(), MAINNET_CHAIN_ID));
+    let ctx = RpcContext {
+        chain,
+        mempool,
+        node: Some(node.clone()),
+        chain_id: MAINNET_CHAIN_ID,
+        auth: Arc::new(AuthConfig::default()),
+    };
+    let path_str = path.display().to_string();
+
+    let loaded = dispatch(&ctx, "loadwallet", &json!(["customer", path_str, "secret"])).unwrap();
+    assert_eq!(loaded["address"], json!(hex::encode(address)));
+    assert_eq!(loaded["locked"], json!(true));
+    assert!(node.watch.lock().unwrap().is_watched(&address));
+    // A second load under the same name is refused.
+    assert!(dispatch(&ctx, "loadwallet", &json!(["customer", path_str, "secret"])).is_err());
+
+    // Lock state flows through listwallets per wallet.
+    let list = dispatch(&ctx, "listwallets", &json!([])).unwrap();
+    assert_eq!(list.as_array().unwrap().len(), 1);
+    assert_eq!(list[0]["name"], json!("customer"));
+    assert_eq!(list[0]["locked"], json!(true));
+    assert!(dispatch(&ctx, "unlockwallet", &json!(["customer", "wrong"])).is_err());
+    dispatch(&ctx, "unlockwallet", &json!(["customer", "secret", 60])).unwrap();
+    let list = dispatch(&ctx, "listwallets", &json!([])).unwrap();
+    assert_eq!(list[0]["locked"], json!(false));
+    dispatch(&ctx, "lockwallet", &json!(["customer"])).unwrap();
+    let list = dispatch(&ctx, "listwallets", &json!([])).unwrap();
+    assert_eq!(list[0]["locked"], json!(true));
+
+    // Unloading releases the watch-list entry with it.
+    let removed = dispatch(&ctx, "unloadwallet", &json!(["customer"])).unwrap();
+    assert_eq!(removed["removed"], json!(true));
+    assert!(!node.watch.lock().unwrap().is_watched(&address));
+    let removed = dispatch(&ctx, "unloadwallet", &json!(["customer"])).unwrap();
+    assert_eq!(removed["removed"], json!(false));
+
+    // Management carries passwords and key sessions: admin-only.
+    assert_eq!(method_scope("loadwallet"), Scope::Admin);
+    assert_eq!(method_scope("unlockwallet"), Scope::Admin);
+    assert_eq!(method_scope("listwallets"), Scope::Wallet);
+}
+
+#[test]
+fn sendfromwallet_spends_only_through_an_unlock_session() {
+    let dir = test_dir("send");
+    let (wallet, path) = saved_wallet(&dir, "spender.dat", "secret");
+    let address = wallet.address();
+    drop(wallet);
+
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "multiwallet test".to_string(),
+        bits: math::MAX_BITS,
+        premine: vec![PremineAllocation {
+            address: hex::encode(address),
+            amount: 5 * COIN,
+        }],
+    };
+    let chain = Arc::new(Mutex::new(
+        Blockchain::init_chain(dir.join("chain"), &config).unwrap(),
+    ));
+    {
+        // Mature the premine so the spend passes full validation.
+        let mut chain = chain.lock().unwrap();
+        for _ in 0..COINBASE_MATURITY {
+            mine_one(&mut chain);
+        }
+    }
+    let mempool = Arc::new(Mutex::new(Mempool::new()));
+    let node = Arc::new(Node::new(chain.clone(), mempool.clone(), MAINNET_CHAIN_ID));
+    let ctx = RpcContext {
+        chain,
+        mempool: mempool.clone(),
+        node: Some(node),
+        chain_id: MAINNET_CHAIN_ID,
+        auth: Arc::new(AuthConfig::default()),
+    };
+
+    let path_str = path.display().to_string();
+    dispatch(&ctx, "loadwallet", &json!(["spender", path_str, "secret"])).unwrap();
+    let send = json!(["spender", hex::encode([0xBB; 20]), COIN, 1_000]);
+
+    // Locked means no signing key, so nothing reaches the mempool.
+    let err = dispatch(&ctx, "sendfromwallet", &send).unwrap_err();
+    assert!(err.contains("locked"), "unexpected error: {}", err);
+    assert_eq!(mempool.lock().unwrap().len(), 0);
+
+    dispatch(&ctx, "unlockwallet", &json!(["spender", "secret", 60])).unwrap();
+    let txid = dispatch(&ctx, "sendfromwallet", &send).unwrap();
+    assert_eq!(txid.as_str().unwrap().len(), 64);
+    assert_eq!(mempool.lock().unwrap().len(), 1);
+}
@@ -0,0 +1,245 @@
+//! MuSig key aggregation, two-round signing, and Schnorr transactions.
+
+use pali_coin::blockchain::{Blockchain, GenesisConfig, PremineAllocation};
+use pali_coin::consensus::ChainParams;
+use pali_coin::crypto::{
+    sign_transaction, sign_transaction_schnorr, signing_hash, verify_transaction_signature,
+};
+use pali_coin::musig::{aggregate_public_keys, combine_partials, verify, Cosigner};
+use pali_coin::rejection::RejectionReason;
+use pali_coin::types::{
+    block_reward, Block, BlockHeader, Hash256, SignatureType, Transaction, COINBASE_ADDRESS,
+};
+use pali_coin::{hash, math, MAINNET_CHAIN_ID};
+use secp256k1::SecretKey;
+
+fn cosigners(n: u8) -> Vec<Cosigner> {
+    (1..=n)
+        .map(|seed| Cosigner::new(SecretKey::from_slice(&[seed; 32]).unwrap()))
+        .collect()
+}
+
+fn unsigned(from: [u8; 20]) -> Transaction {
+    Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from,
+        to: [0x22; 20],
+        amount: 5_000,
+        fee: 100,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+#[test]
+fn aggregation_is_order_independent_and_rogue_key_resistant() {
+    let signers = cosigners(3);
+    let keys: Vec<_> = signers.iter().map(|s| s.public_key()).collect();
+
+    let forward = aggregate_public_keys(&keys).unwrap();
+    let mut reversed = keys.clone();
+    reversed.reverse();
+    assert_eq!(forward, aggregate_public_keys(&reversed).unwrap());
+
+    // A different key set aggregates to a different key, and no single
+    // member equals the aggregate.
+    let smaller = aggregate_public_keys(&keys[..2]).unwrap();
+    assert_ne!(forward, smaller);
+    assert!(keys.iter().all(|k| *k != forward));
+
+    assert!(aggregate_public_keys(&[]).is_err());
+}
+
+#[test]
+fn full_session_produces_a_plain_schnorr_signature() {
+    let mut signers = cosigners(3);
+    let keys: Vec<_> = signers.iter().map(|s| s.public_key()).collect();
+    let aggregate = aggregate_public_keys(&keys).unwrap();
+    let msg = [0x42u8; 32];
+
+    let nonces: Vec<_> = signers.iter_mut().map(|s| s.nonce_commitment()).collect();
+    let partials: Vec<_> = signers
+        .iter_mut()
+        .map(|s| s.partial_sign(&msg, &keys, &nonces).unwrap())
+        .collect();
+    let sig = combine_partials(&nonces, &partials).unwrap();
+    verify(&sig, &msg, &aggregate).unwrap();
+
+    // The signature commits to the message and the full signer set.
+    assert!(verify(&sig, &[0x43u8; 32], &aggregate).is_err());
+    let smaller = aggregate_public_keys(&keys[..2]).unwrap();
+    assert!(verify(&sig, &msg, &smaller).is_err());
+}
+
+#[test]
+fn a_missing_cosigner_cannot_be_papered_over() {
+    let mut signers = cosigners(2);
+    let keys: Vec<_> = signers.iter().map(|s| s.public_key()).collect();
+    let aggregate = aggregate_public_keys(&keys).unwrap();
+    let msg = [0x11u8; 32];
+
+    let nonces: Vec<_> = signers.iter_mut().map(|s| s.nonce_commitment()).collect();
+    let partial = signers[0].partial_sign(&msg, &keys, &nonces).unwrap();
+
+    // One share alone does not verify, and a nonce can only be spent
+    // on one partial signature.
+    let sig = combine_partials(&nonces, &[partial]).unwrap();
+    assert!(verify(&sig, &msg, &aggregate).is_err());
+    assert!(signers[0].partial_sign(&msg, &keys, &nonces).is_err());
+}
+
+#[test]
+fn schnorr_transactions_verify_and_carry_the_type_in_the_key() {
+    let key = SecretKey::from_slice(&[0x07; 32]).unwrap();
+    let xonly = Cosigner::new(key).public_key();
+    let from = pali_coin::hash::pubkey_to_address(&xonly.serialize());
+
+    let mut tx = unsigned(from);
+    sign_transaction_schnorr(&mut tx, &key).unwrap();
+    assert_eq!(tx.signature_type(), SignatureType::Schnorr);
+    assert_eq!(tx.public_key.len(), 32);
+    verify_transaction_signature(&tx).unwrap();
+
+    // Tampering breaks it, same as ECDSA.
+    let mut tampered = tx.clone();
+    tampered.amount += 1;
+    assert!(verify_transaction_signature(&tampered).is_err());
+
+    // An aggregated session signs the very same digest and slots into
+    // the same fields.
+    let mut signers = cosigners(2);
+    let keys: Vec<_> = signers.iter().map(|s| s.public_key()).collect();
+    let aggregate = aggregate_public_keys(&keys).unwrap();
+    let mut multi = unsigned(pali_coin::hash::pubkey_to_address(&aggregate.serialize()));
+    multi.public_key = aggregate.serialize().to_vec();
+    let digest = signing_hash(&multi);
+    let nonces: Vec<_> = signers.iter_mut().map(|s| s.nonce_commitment()).collect();
+    let partials: Vec<_> = signers
+        .iter_mut()
+        .map(|s| s.partial_sign(&digest, &keys, &nonces).unwrap())
+        .collect();
+    multi.signature = combine_partials(&nonces, &partials)
+        .unwrap()
+        .as_ref()
+        .to_vec();
+    assert_eq!(multi.signature_type(), SignatureType::Schnorr);
+    verify_transaction_signature(&multi).unwrap();
+}
+
+#[test]
+fn activation_height_gates_schnorr_spends() {
+    let key = SecretKey::from_slice(&[0x09; 32]).unwrap();
+    let unscheduled = ChainParams::default();
+    let scheduled = ChainParams {
+        schnorr_tx_activation_height: Some(100),
+        ..ChainParams::default()
+    };
+
+    let mut ecdsa = unsigned([0x33; 20]);
+    sign_transaction(&mut ecdsa, &key).unwrap();
+    let mut schnorr = unsigned([0x44; 20]);
+    sign_transaction_schnorr(&mut schnorr, &key).unwrap();
+
+    // ECDSA is valid everywhere, forever.
+    unscheduled.check_signature_type(&ecdsa, 0).unwrap();
+    scheduled.check_signature_type(&ecdsa, 1_000_000).unwrap();
+
+    // Schnorr needs a scheduled height and only counts from there.
+    assert!(unscheduled.check_signature_type(&schnorr, 1_000_000).is_err());
+    assert!(scheduled.check_signature_type(&schnorr, 99).is_err());
+    scheduled.check_signature_type(&schnorr, 100).unwrap();
+}
+
+/// Seals a proof-of-work block over the coinbase and `tx` at the
+/// chain's next height.
+fn sealed_with(chain: &Blockchain, tx: Transaction) -> Block {
+    let height = chain.height() + 1;
+    let coinbase = Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: COINBASE_ADDRESS,
+        to: [0x11; 20],
+        amount: block_reward(height) + tx.fee,
+        fee: 0,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    };
+    let hashes: Vec<Hash256> = vec![coinbase.hash(), tx.hash()];
+    let mut header = BlockHeader {
+        version: 1,
+        prev_hash: chain.best_hash(),
+        merkle_root: hash::merkle_root(&hashes),
+        timestamp: 1_700_000_000 + height * 180,
+        bits: chain.next_bits().unwrap(),
+        nonce: 0,
+        height,
+    };
+    while !math::hash_meets_target(&header.hash(), header.bits) {
+        header.nonce = header.nonce.wrapping_add(1);
+    }
+    Block {
+        header,
+        transactions: vec![coinbase, tx],
+    }
+}
+
+#[test]
+fn an_early_schnorr_spend_is_rejected_by_block_validation() {
+    let dir = std::env::temp_dir().join(format!("pali-musig-{}-gate", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let key = SecretKey::from_slice(&[0x09; 32]).unwrap();
+    let from = hash::pubkey_to_address(&Cosigner::new(key).public_key().serialize());
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "musig gate test".to_string(),
+        bits: math::MAX_BITS,
+        premine: vec![PremineAllocation {
+            address: hex::encode(from),
+            amount: 50_000,
+        }],
+    };
+    let mut chain = Blockchain::init_chain(dir, &config).unwrap();
+
+    let mut tx = unsigned(from);
+    sign_transaction_schnorr(&mut tx, &key).unwrap();
+
+    // A funded, correctly signed Schnorr spend is refused at mempool
+    // admission while no activation height is scheduled...
+    assert_eq!(
+        chain.validate_transaction(&tx, MAINNET_CHAIN_ID),
+        Err(RejectionReason::SignatureTypeNotActive)
+    );
+
+    // ...and a sealed block carrying it fails full validation too, so
+    // it cannot be mined or connected.
+    let block = sealed_with(&chain, tx);
+    assert_eq!(
+        chain.validate_block(&block, MAINNET_CHAIN_ID),
+        Err(RejectionReason::SignatureTypeNotActive)
+    );
+
+    // An activation height still in the future changes nothing yet.
+    chain.set_chain_params(ChainParams {
+        schnorr_tx_activation_height: Some(1_000),
+        ..ChainParams::default()
+    });
+    assert_eq!(
+        chain.validate_block(&block, MAINNET_CHAIN_ID),
+        Err(RejectionReason::SignatureTypeNotActive)
+    );
+
+    // Once the block's height is past activation, the same block stands.
+    chain.set_chain_params(ChainParams {
+        schnorr_tx_activation_height: Some(1),
+        ..ChainParams::default()
+    });
+    chain.validate_block(&block, MAINNET_CHAIN_ID).unwrap();
+}
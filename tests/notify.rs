@@ -0,0 +1,66 @@
+//! Notification hook gating and command rendering.
+
+use pali_coin::notify::{render_command, HookConfig, Notification, Notifier, TxEvent};
+
+#[test]
+fn command_template_substitutes_the_argument() {
+    assert_eq!(
+        render_command("notify-tx.sh %s", "abc123"),
+        "notify-tx.sh abc123"
+    );
+    // A template without %s runs unchanged.
+    assert_eq!(render_command("poke-backend.sh", "abc123"), "poke-backend.sh");
+}
+
+#[test]
+fn events_queue_only_for_configured_hooks() {
+    // blocknotify only: wallet events are dropped at the door, block
+    // events queue.
+    let (notifier, mut rx) = Notifier::new(HookConfig {
+        blocknotify: Some("true %s".to_string()),
+        ..HookConfig::default()
+    });
+    notifier.wallet_tx([1u8; 32], TxEvent::Mempool);
+    notifier.block([2u8; 32], 7);
+    match rx.try_recv() {
+        Ok(Notification::Block { hash, height }) => {
+            assert_eq!(hash, [2u8; 32]);
+            assert_eq!(height, 7);
+        }
+        other => panic!("expected the block event, got {:?}", other),
+    }
+    assert!(rx.try_recv().is_err());
+
+    // A webhook subscribes to everything.
+    let (notifier, mut rx) = Notifier::new(HookConfig {
+        webhook_url: Some("http://127.0.0.1:1/hook".to_string()),
+        ..HookConfig::default()
+    });
+    notifier.wallet_tx([1u8; 32], TxEvent::Confirmed);
+    notifier.block([2u8; 32], 8);
+    assert!(matches!(rx.try_recv(), Ok(Notification::WalletTx { .. })));
+    assert!(matches!(rx.try_recv(), Ok(Notification::Block { .. })));
+}
+
+#[test]
+fn alerts_queue_only_with_alertnotify_or_webhook() {
+    let (notifier, mut rx) = Notifier::new(HookConfig {
+        alertnotify: Some("page-oncall.sh %s".to_string()),
+        ..HookConfig::default()
+    });
+    notifier.block([2u8; 32], 7);
+    notifier.alert("safe mode: test".to_string());
+    match rx.try_recv() {
+        Ok(Notification::Alert { message }) => assert_eq!(message, "safe mode: test"),
+        other => panic!("expected the alert event, got {:?}", other),
+    }
+    assert!(rx.try_recv().is_err());
+}
+
+#[test]
+fn disabled_notifier_drops_everything() {
+    let notifier = Notifier::disabled();
+    // No receiver and no hooks: these must be silent no-ops.
+    notifier.wallet_tx([3u8; 32], TxEvent::Reorged);
+    notifier.block([4u8; 32], 9);
+}
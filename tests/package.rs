@@ -0,0 +1,247 @@
+//! Transaction package relay: combined fee rates, atomic admission and
+//! the version-gated P2P broadcast.
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use pali_coin::blockchain::{
+    Blockchain, GenesisConfig, PremineAllocation, COINBASE_MATURITY,
+};
+use pali_coin::error::MempoolErrorKind;
+use pali_coin::mempool::Mempool;
+use pali_coin::network::{NetworkMessage, PACKAGE_RELAY_VERSION};
+use pali_coin::node::{Node, PeerInfo};
+use pali_coin::rpc::{dispatch, RpcContext};
+use pali_coin::rpc_auth::AuthConfig;
+use pali_coin::types::{
+    block_reward, Address, Block, BlockHeader, Hash256, Transaction, COIN, COINBASE_ADDRESS,
+};
+use pali_coin::{crypto, hash, math, MAINNET_CHAIN_ID};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use serde_json::json;
+use tokio::sync::mpsc;
+
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("pali-package-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn keypair(seed: u8) -> (SecretKey, Address) {
+    let secp = Secp256k1::new();
+    let secret = SecretKey::from_slice(&[seed; 32]).unwrap();
+    let public = PublicKey::from_secret_key(&secp, &secret);
+    (secret, hash::pubkey_to_address(&public.serialize()))
+}
+
+fn unsigned(from: Address, nonce: u64, amount: u64, fee: u64) -> Transaction {
+    Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce,
+        from,
+        to: [0xBB; 20],
+        amount,
+        fee,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+fn signed(secret: &SecretKey, from: Address, nonce: u64, amount: u64, fee: u64) -> Transaction {
+    let mut tx = unsigned(from, nonce, amount, fee);
+    crypto::sign_transaction(&mut tx, secret).unwrap();
+    tx
+}
+
+fn matured_chain(name: &str, address: &Address, amount: u64) -> Blockchain {
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "package test".to_string(),
+        bits: math::MAX_BITS,
+        premine: vec![PremineAllocation {
+            address: hex::encode(address),
+            amount,
+        }],
+    };
+    let mut chain = Blockchain::init_chain(test_dir(name), &config).unwrap();
+    for height in 1..=COINBASE_MATURITY {
+        let coinbase = Transaction {
+            chain_id: MAINNET_CHAIN_ID,
+            nonce: 0,
+            from: COINBASE_ADDRESS,
+            to: [0x11; 20],
+            amount: block_reward(height),
+            fee: 0,
+            data: Vec::new(),
+            replaceable: false,
+            lock_time: 0,
+            signature: Vec::new(),
+            public_key: Vec::new(),
+        };
+        let hashes: Vec<Hash256> = vec![coinbase.hash()];
+        let mut header = BlockHeader {
+            version: 1,
+            prev_hash: chain.best_hash(),
+            merkle_root: hash::merkle_root(&hashes),
+            timestamp: 1_700_000_000 + height * 180,
+            bits: chain.next_bits().unwrap(),
+            nonce: 0,
+            height,
+        };
+        while !math::hash_meets_target(&header.hash(), header.bits) {
+            header.nonce = header.nonce.wrapping_add(1);
+        }
+        let block = Block {
+            header,
+            transactions: vec![coinbase],
+        };
+        chain.add_block(&block, MAINNET_CHAIN_ID).unwrap();
+    }
+    chain
+}
+
+fn tx_hex(tx: &Transaction) -> String {
+    hex::encode(bincode::serialize(tx).unwrap())
+}
+
+#[test]
+fn packages_clear_the_relay_floor_together() {
+    let mut mempool = Mempool::new();
+    let sender = [0xAA; 20];
+    let parent = unsigned(sender, 0, 5_000, 0);
+    let child = unsigned(sender, 1, 5_000, 50_000);
+
+    // Alone, the zero-fee parent is under the relay floor.
+    let refused = mempool.insert(parent.clone(), 0).unwrap_err();
+    assert_eq!(refused.kind, MempoolErrorKind::FeeTooLow);
+
+    // As a package, the child's fee carries it.
+    let package = vec![parent, child];
+    mempool.check_package(&package).unwrap();
+    for tx in &package {
+        mempool.insert_package_member(tx.clone(), 0).unwrap();
+    }
+    assert_eq!(mempool.len(), 2);
+
+    // Shape violations: empty, mixed senders, a nonce gap, and a
+    // package that still cannot pay for itself.
+    assert_eq!(
+        mempool.check_package(&[]).unwrap_err().kind,
+        MempoolErrorKind::BadPackage
+    );
+    let stranger = unsigned([0xCC; 20], 1, 5_000, 50_000);
+    assert_eq!(
+        mempool
+            .check_package(&[unsigned(sender, 0, 5_000, 0), stranger])
+            .unwrap_err()
+            .kind,
+        MempoolErrorKind::BadPackage
+    );
+    assert_eq!(
+        mempool
+            .check_package(&[unsigned(sender, 0, 5_000, 0), unsigned(sender, 2, 5_000, 50_000)])
+            .unwrap_err()
+            .kind,
+        MempoolErrorKind::BadPackage
+    );
+    assert_eq!(
+        mempool
+            .check_package(&[unsigned(sender, 0, 5_000, 0), unsigned(sender, 1, 5_000, 0)])
+            .unwrap_err()
+            .kind,
+        MempoolErrorKind::FeeTooLow
+    );
+}
+
+#[test]
+fn submitpackage_admits_all_members_or_none() {
+    let (secret, address) = keypair(0x21);
+    let chain = matured_chain("rpc", &address, 5 * COIN);
+    let mempool = Arc::new(Mutex::new(Mempool::new()));
+    let ctx = RpcContext {
+        chain: Arc::new(Mutex::new(chain)),
+        mempool: mempool.clone(),
+        node: None,
+        chain_id: MAINNET_CHAIN_ID,
+        auth: Arc::new(AuthConfig::default()),
+    };
+
+    // A child overdrawing the chain voids the zero-fee parent with it.
+    let parent = signed(&secret, address, 0, COIN, 0);
+    let greedy = signed(&secret, address, 1, 100 * COIN, 20_000);
+    let err = dispatch(
+        &ctx,
+        "submitpackage",
+        &json!([tx_hex(&parent), tx_hex(&greedy)]),
+    )
+    .unwrap_err();
+    assert!(err.contains("rejected"), "unexpected error: {}", err);
+    assert!(mempool.lock().unwrap().is_empty());
+
+    // A package that pays its way lands whole.
+    let child = signed(&secret, address, 1, COIN, 20_000);
+    let reply = dispatch(
+        &ctx,
+        "submitpackage",
+        &json!([tx_hex(&parent), tx_hex(&child)]),
+    )
+    .unwrap();
+    let txids = reply["txids"].as_array().unwrap();
+    assert_eq!(txids.len(), 2);
+    assert_eq!(txids[0], json!(hex::encode(parent.hash())));
+    let mempool = mempool.lock().unwrap();
+    assert!(mempool.contains(&parent.hash()));
+    assert!(mempool.contains(&child.hash()));
+}
+
+#[test]
+fn packages_relay_only_to_package_aware_peers() {
+    let (secret, address) = keypair(0x22);
+    let chain = Arc::new(Mutex::new(matured_chain("relay", &address, 5 * COIN)));
+    let mempool = Arc::new(Mutex::new(Mempool::new()));
+    let node = Node::new(chain, mempool.clone(), MAINNET_CHAIN_ID);
+
+    let peer_at = |addr: &str, version: u32| {
+        let addr: SocketAddr = addr.parse().unwrap();
+        let (tx, rx) = mpsc::unbounded_channel();
+        node.peers.lock().unwrap().insert(
+            addr,
+            PeerInfo {
+                addr,
+                inbound: true,
+                version,
+                user_agent: String::new(),
+                best_height: 0,
+                connected_at: 0,
+                last_seen: 0,
+                ping_time: None,
+                pending_ping: None,
+                sender: tx,
+            },
+        );
+        rx
+    };
+    let mut modern = peer_at("192.0.2.8:7777", PACKAGE_RELAY_VERSION);
+    let mut legacy = peer_at("192.0.2.9:7777", PACKAGE_RELAY_VERSION - 1);
+
+    let source: SocketAddr = "192.0.2.10:7777".parse().unwrap();
+    let package = vec![
+        signed(&secret, address, 0, COIN, 0),
+        signed(&secret, address, 1, COIN, 20_000),
+    ];
+    node.handle_network_message(source, NetworkMessage::Package(package.clone()))
+        .unwrap();
+    assert_eq!(mempool.lock().unwrap().len(), 2);
+
+    // The package goes out whole to peers that can parse it; a peer on
+    // the previous protocol version hears nothing.
+    match modern.try_recv().unwrap() {
+        NetworkMessage::Package(relayed) => assert_eq!(relayed, package),
+        other => panic!("expected Package, got {:?}", other),
+    }
+    assert!(legacy.try_recv().is_err());
+}
@@ -0,0 +1,94 @@
+//! Multi-device sync bundles: sealing, pairing codes and history merge.
+
+use pali_coin::pairing::{generate_pairing_code, open_bundle, seal_bundle, StateBundle};
+use pali_coin::wallet::Wallet;
+use pali_coin::wallet_store::{Direction, TxRecord, TxStatus, WalletStore};
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("pali-test-{}-{}", std::process::id(), name))
+}
+
+fn record(tx_hash: [u8; 32], status: TxStatus, first_seen: u64, label: &str) -> TxRecord {
+    TxRecord {
+        tx_hash,
+        direction: Direction::Sent,
+        amount: 1_000,
+        fee: 10,
+        counterparty: [3u8; 20],
+        status,
+        first_seen,
+        label: label.to_string(),
+    }
+}
+
+#[test]
+fn pairing_codes_are_grouped_and_unambiguous() {
+    let code = generate_pairing_code();
+    assert_eq!(code.len(), 19);
+    for (i, c) in code.chars().enumerate() {
+        if i % 5 == 4 {
+            assert_eq!(c, '-');
+        } else {
+            assert!(c.is_ascii_uppercase() || c.is_ascii_digit());
+            assert!(!"0O1I".contains(c), "lookalike {} in pairing code", c);
+        }
+    }
+}
+
+#[test]
+fn bundle_round_trips_under_its_code() {
+    let path = temp_path("pairing-export.history.json");
+    let wallet = Wallet::new();
+    let mut store = WalletStore::open(&path).unwrap();
+    store.upsert(record([1u8; 32], TxStatus::Pending, 100, "coffee"));
+
+    let bundle = StateBundle::capture(&wallet, &store);
+    let code = generate_pairing_code();
+    let sealed = seal_bundle(&bundle, &code).unwrap();
+
+    let opened = open_bundle(&sealed, &code).unwrap();
+    assert_eq!(opened.address, wallet.address());
+    assert_eq!(opened.records.len(), 1);
+    assert_eq!(opened.records[0].label, "coffee");
+
+    // Case and separators must not matter when the user retypes it.
+    let sloppy = code.replace('-', " ").to_ascii_lowercase();
+    assert!(open_bundle(&sealed, &sloppy).is_ok());
+    // A wrong code must not.
+    assert!(open_bundle(&sealed, "AAAA-AAAA-AAAA-AAAA").is_err());
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn merge_prefers_local_knowledge() {
+    let path = temp_path("pairing-merge.history.json");
+    let mut store = WalletStore::open(&path).unwrap();
+    store.upsert(record([1u8; 32], TxStatus::Confirmed { height: 5 }, 50, "rent"));
+    store.upsert(record([2u8; 32], TxStatus::Pending, 200, ""));
+
+    let incoming = vec![
+        // Already confirmed locally: the stale pending copy must not
+        // downgrade it, but its earlier sighting is kept.
+        record([1u8; 32], TxStatus::Pending, 40, ""),
+        // Pending locally and confirmed remotely: status and label
+        // both come across.
+        record([2u8; 32], TxStatus::Confirmed { height: 7 }, 300, "groceries"),
+        // Unknown locally: inserted as-is.
+        record([3u8; 32], TxStatus::Pending, 400, "books"),
+    ];
+    let changed = store.merge(&incoming);
+    assert_eq!(changed, 3);
+
+    let first = store.get(&[1u8; 32]).unwrap();
+    assert_eq!(first.status, TxStatus::Confirmed { height: 5 });
+    assert_eq!(first.first_seen, 40);
+    assert_eq!(first.label, "rent");
+
+    let second = store.get(&[2u8; 32]).unwrap();
+    assert_eq!(second.status, TxStatus::Confirmed { height: 7 });
+    assert_eq!(second.first_seen, 200);
+    assert_eq!(second.label, "groceries");
+
+    assert!(store.get(&[3u8; 32]).is_some());
+    let _ = std::fs::remove_file(&path);
+}
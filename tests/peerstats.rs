@@ -0,0 +1,132 @@
+//! Persistent per-peer statistics, their RPC surface and the network
+//! composition report.
+
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+
+use pali_coin::blockchain::{Blockchain, GenesisConfig};
+use pali_coin::mempool::Mempool;
+use pali_coin::network::NetworkMessage;
+use pali_coin::node::Node;
+use pali_coin::peerstats::PeerStatsBook;
+use pali_coin::rpc::{dispatch, RpcContext};
+use pali_coin::rpc_auth::AuthConfig;
+use pali_coin::types::{Block, BlockHeader};
+use pali_coin::{math, MAINNET_CHAIN_ID};
+use serde_json::Value;
+
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("pali-peerstats-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn ip(last: u8) -> IpAddr {
+    IpAddr::from([192, 0, 2, last])
+}
+
+#[test]
+fn records_survive_a_restart_and_average_sessions() {
+    let dir = test_dir("persist");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("peerstats.dat");
+
+    let mut book = PeerStatsBook::with_path(&path);
+    book.record_connect(ip(1), "/pali-coin:0.9.0/", 6, 1_000);
+    book.record_block(ip(1), true);
+    book.record_block(ip(1), true);
+    book.record_tx(ip(1), false);
+    book.record_disconnect(ip(1), 300, 1_300);
+    book.record_connect(ip(1), "/pali-coin:0.9.0/", 6, 2_000);
+    book.record_disconnect(ip(1), 100, 2_100);
+    drop(book);
+
+    let book = PeerStatsBook::with_path(&path);
+    let record = &book.records()["192.0.2.1"];
+    assert_eq!(record.sessions, 2);
+    assert_eq!(record.total_uptime_secs, 400);
+    assert_eq!(record.average_session_secs(), 200);
+    assert_eq!(record.blocks_valid, 2);
+    assert_eq!(record.txs_invalid, 1);
+    assert_eq!(record.first_seen, 1_000);
+    assert_eq!(record.last_seen, 2_100);
+    assert_eq!(record.valid_ratio(), Some(2.0 / 3.0));
+}
+
+#[test]
+fn network_report_groups_peers_by_user_agent() {
+    let mut book = PeerStatsBook::new();
+    for (last, agent, version) in [
+        (1, "/pali-coin:0.9.0/", 6),
+        (2, "/pali-coin:0.9.0/", 5),
+        (3, "/pali-coin:0.8.0/", 5),
+        (4, "/pali-coin:0.9.0/", 6),
+    ] {
+        book.record_connect(ip(last), agent, version, 1_000);
+        book.record_tx(ip(last), true);
+    }
+
+    let report = book.network_report();
+    assert_eq!(report.len(), 2);
+    // The larger cohort leads.
+    assert_eq!(report[0].user_agent, "/pali-coin:0.9.0/");
+    assert_eq!(report[0].peers, 3);
+    assert_eq!(report[0].versions, vec![5, 6]);
+    assert_eq!(report[0].txs_valid, 3);
+    assert_eq!(report[1].user_agent, "/pali-coin:0.8.0/");
+    assert_eq!(report[1].peers, 1);
+}
+
+#[test]
+fn invalid_relays_are_booked_and_served_over_rpc() {
+    let dir = test_dir("rpc");
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "peerstats test".to_string(),
+        bits: math::MAX_BITS,
+        premine: Vec::new(),
+    };
+    let chain = Arc::new(Mutex::new(Blockchain::init_chain(&dir, &config).unwrap()));
+    let mempool = Arc::new(Mutex::new(Mempool::new()));
+    let node = Arc::new(Node::new(chain.clone(), mempool.clone(), MAINNET_CHAIN_ID));
+    let peer: SocketAddr = "192.0.2.9:7777".parse().unwrap();
+    node.peerstats
+        .lock()
+        .unwrap()
+        .record_connect(peer.ip(), "/pali-coin:0.9.0/", 6, 1_000);
+
+    // A block that builds on nothing we know is an invalid relay.
+    let orphan = Block {
+        header: BlockHeader {
+            version: 1,
+            prev_hash: [0xAB; 32],
+            merkle_root: [0; 32],
+            timestamp: 1_700_000_360,
+            bits: math::MAX_BITS,
+            nonce: 0,
+            height: 9,
+        },
+        transactions: Vec::new(),
+    };
+    node.handle_network_message(peer, NetworkMessage::Block(orphan))
+        .unwrap();
+
+    let ctx = RpcContext {
+        chain,
+        mempool,
+        node: Some(node),
+        chain_id: MAINNET_CHAIN_ID,
+        auth: Arc::new(AuthConfig::default()),
+    };
+    let stats = dispatch(&ctx, "getpeerstats", &Value::Null).unwrap();
+    let entry = &stats.as_array().unwrap()[0];
+    assert_eq!(entry["address"], "192.0.2.9");
+    assert_eq!(entry["blocks_invalid"], 1);
+    assert_eq!(entry["blocks_valid"], 0);
+
+    let report = dispatch(&ctx, "getnetworkreport", &Value::Null).unwrap();
+    let group = &report.as_array().unwrap()[0];
+    assert_eq!(group["user_agent"], "/pali-coin:0.9.0/");
+    assert_eq!(group["blocks_invalid"], 1);
+}
@@ -0,0 +1,288 @@
+//! Proof-of-authority sealing, round-robin scheduling and on-chain
+//! validator rotation.
+
+use pali_coin::blockchain::{Blockchain, GenesisConfig, PremineAllocation, COINBASE_MATURITY};
+use pali_coin::consensus::ChainParams;
+use pali_coin::rejection::RejectionReason;
+use pali_coin::types::{
+    Address, Block, BlockHeader, Hash256, Transaction, COIN, COINBASE_ADDRESS,
+};
+use pali_coin::{crypto, hash, math, poa, MAINNET_CHAIN_ID};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("pali-poa-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn validator(seed: u8) -> (SecretKey, String, Address) {
+    let key = SecretKey::from_slice(&[seed; 32]).unwrap();
+    let public = PublicKey::from_secret_key(&Secp256k1::signing_only(), &key);
+    let bytes = public.serialize();
+    (key, hex::encode(bytes), hash::pubkey_to_address(&bytes))
+}
+
+fn poa_chain(name: &str, validators: &[&str], premine: &Address) -> Blockchain {
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "poa test".to_string(),
+        bits: math::MAX_BITS,
+        premine: vec![PremineAllocation {
+            address: hex::encode(premine),
+            amount: 5 * COIN,
+        }],
+    };
+    let mut chain = Blockchain::init_chain(test_dir(name), &config).unwrap();
+    chain.set_chain_params(ChainParams {
+        validators: validators.iter().map(|key| key.to_string()).collect(),
+        ..ChainParams::default()
+    });
+    chain
+}
+
+fn coinbase(chain: &Blockchain, height: u64, fees: u64) -> Transaction {
+    Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: COINBASE_ADDRESS,
+        to: [0x11; 20],
+        amount: chain.chain_params().block_reward_at(height) + fees,
+        fee: 0,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+/// Builds a block on the tip without any proof-of-work grinding; the
+/// signer's signature is all that should admit it.
+fn seal_poa(chain: &Blockchain, signer: &SecretKey, transactions: Vec<Transaction>) -> Block {
+    let height = chain.height() + 1;
+    let hashes: Vec<Hash256> = transactions.iter().map(|tx| tx.hash()).collect();
+    let header = BlockHeader {
+        version: 1,
+        prev_hash: chain.best_hash(),
+        merkle_root: hash::merkle_root(&hashes),
+        timestamp: 1_700_000_000 + height * 180,
+        bits: math::MAX_BITS,
+        nonce: 0,
+        height,
+    };
+    let mut block = Block {
+        header,
+        transactions,
+    };
+    poa::sign_block(&mut block, signer);
+    block
+}
+
+/// Seals the next block with whichever validator is scheduled for it.
+fn seal_scheduled(chain: &Blockchain, keys: &[&SecretKey]) -> Block {
+    let height = chain.height() + 1;
+    let index = (height % keys.len() as u64) as usize;
+    let txs = vec![coinbase(chain, height, 0)];
+    seal_poa(chain, keys[index], txs)
+}
+
+#[test]
+fn validator_signatures_replace_proof_of_work() {
+    let (key_a, hex_a, _) = validator(0x31);
+    let (key_b, hex_b, _) = validator(0x32);
+    let mut chain = poa_chain("engine", &[&hex_a, &hex_b], &[0xAA; 20]);
+
+    // Height 1 is validator B's turn; A sealing it is rejected even
+    // with a perfectly valid signature.
+    let out_of_turn = seal_poa(&chain, &key_a, vec![coinbase(&chain, 1, 0)]);
+    let err = chain.add_block(&out_of_turn, MAINNET_CHAIN_ID).unwrap_err();
+    assert_eq!(err, RejectionReason::NotValidatorsTurn);
+    assert_eq!(err.code(), "bad-poa-turn");
+
+    // The right key with a missing signature fails too.
+    let mut unsigned = Block {
+        header: out_of_turn.header,
+        transactions: vec![coinbase(&chain, 1, 0)],
+    };
+    unsigned.transactions[0].public_key = hex::decode(&hex_b).unwrap();
+    let hashes: Vec<Hash256> = unsigned.transactions.iter().map(|tx| tx.hash()).collect();
+    unsigned.header.merkle_root = hash::merkle_root(&hashes);
+    assert_eq!(
+        chain.add_block(&unsigned, MAINNET_CHAIN_ID).unwrap_err(),
+        RejectionReason::BadValidatorSignature
+    );
+
+    // The scheduled validator connects with no work at all (nonce 0).
+    chain
+        .add_block(&seal_scheduled(&chain, &[&key_a, &key_b]), MAINNET_CHAIN_ID)
+        .unwrap();
+    chain
+        .add_block(&seal_scheduled(&chain, &[&key_a, &key_b]), MAINNET_CHAIN_ID)
+        .unwrap();
+    assert_eq!(chain.height(), 2);
+
+    // The signature commits to the header: tampering after sealing
+    // voids it.
+    let mut tampered = seal_scheduled(&chain, &[&key_a, &key_b]);
+    tampered.header.timestamp += 1;
+    assert_eq!(
+        chain.add_block(&tampered, MAINNET_CHAIN_ID).unwrap_err(),
+        RejectionReason::BadValidatorSignature
+    );
+}
+
+#[test]
+fn rotation_transactions_swap_the_validator_set() {
+    let (key_a, hex_a, addr_a) = validator(0x33);
+    let (key_b, hex_b, _) = validator(0x34);
+    let (key_c, hex_c, addr_c) = validator(0x35);
+    let mut chain = poa_chain("rotate", &[&hex_a, &hex_b], &addr_a);
+    let keys = [&key_a, &key_b];
+
+    // Mature the premine so validator A can fund a rotation.
+    for _ in 0..COINBASE_MATURITY {
+        chain
+            .add_block(&seal_scheduled(&chain, &keys), MAINNET_CHAIN_ID)
+            .unwrap();
+    }
+
+    // An outsider cannot vote itself in, however well-signed.
+    let mut intruder = Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: addr_c,
+        to: poa::VALIDATOR_ROTATION_ADDRESS,
+        amount: 1,
+        fee: 0,
+        data: poa::rotation_data(std::slice::from_ref(&hex_c)),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    };
+    crypto::sign_transaction(&mut intruder, &key_c).unwrap();
+    let err = chain
+        .validate_transaction(&intruder, MAINNET_CHAIN_ID)
+        .unwrap_err();
+    assert_eq!(err, RejectionReason::BadValidatorRotation);
+    assert_eq!(err.code(), "bad-poa-rotation");
+
+    // A garbled payload from a real validator is rejected, not skipped.
+    let mut garbled = intruder.clone();
+    garbled.from = addr_a;
+    garbled.data = vec![0xFF; 3];
+    crypto::sign_transaction(&mut garbled, &key_a).unwrap();
+    assert_eq!(
+        chain
+            .validate_transaction(&garbled, MAINNET_CHAIN_ID)
+            .unwrap_err(),
+        RejectionReason::BadValidatorRotation
+    );
+
+    // Validator A rotates the set to {B, C}.
+    let mut rotation = garbled.clone();
+    rotation.data = poa::rotation_data(&[hex_b.clone(), hex_c.clone()]);
+    crypto::sign_transaction(&mut rotation, &key_a).unwrap();
+    let height = chain.height() + 1;
+    let index = (height % 2) as usize;
+    let block = seal_poa(
+        &chain,
+        keys[index],
+        vec![coinbase(&chain, height, 0), rotation],
+    );
+    chain.add_block(&block, MAINNET_CHAIN_ID).unwrap();
+    assert_eq!(chain.validator_set(), [hex_b.clone(), hex_c.clone()]);
+
+    // A is out: its seals no longer connect, the new roster's do.
+    let stale = seal_poa(&chain, &key_a, vec![coinbase(&chain, chain.height() + 1, 0)]);
+    assert_eq!(
+        chain.add_block(&stale, MAINNET_CHAIN_ID).unwrap_err(),
+        RejectionReason::NotValidatorsTurn
+    );
+    chain
+        .add_block(&seal_scheduled(&chain, &[&key_b, &key_c]), MAINNET_CHAIN_ID)
+        .unwrap();
+}
+
+#[test]
+fn reorgs_rewind_validator_rotations() {
+    let (key_a, hex_a, addr_a) = validator(0x36);
+    let (_, hex_b, _) = validator(0x37);
+    let dir = test_dir("rewind");
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "poa rewind".to_string(),
+        bits: math::MAX_BITS,
+        premine: vec![PremineAllocation {
+            address: hex::encode(addr_a),
+            amount: 5 * COIN,
+        }],
+    };
+    let mut chain = Blockchain::init_chain(&dir, &config).unwrap();
+    let params = ChainParams {
+        validators: vec![hex_a.clone()],
+        ..ChainParams::default()
+    };
+    chain.set_chain_params(params.clone());
+    for _ in 0..COINBASE_MATURITY {
+        chain
+            .add_block(&seal_scheduled(&chain, &[&key_a]), MAINNET_CHAIN_ID)
+            .unwrap();
+    }
+
+    let mut rotation = Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: addr_a,
+        to: poa::VALIDATOR_ROTATION_ADDRESS,
+        amount: 1,
+        fee: 0,
+        data: poa::rotation_data(std::slice::from_ref(&hex_b)),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    };
+    crypto::sign_transaction(&mut rotation, &key_a).unwrap();
+    let height = chain.height() + 1;
+    let block = seal_poa(&chain, &key_a, vec![coinbase(&chain, height, 0), rotation]);
+    chain.add_block(&block, MAINNET_CHAIN_ID).unwrap();
+    assert_eq!(chain.validator_set(), std::slice::from_ref(&hex_b));
+
+    // Disconnecting the rotation block restores the configured set,
+    // and A may seal the replacement.
+    chain.disconnect_tip().unwrap();
+    assert_eq!(chain.validator_set(), std::slice::from_ref(&hex_a));
+    chain
+        .add_block(&seal_scheduled(&chain, &[&key_a]), MAINNET_CHAIN_ID)
+        .unwrap();
+
+    // The history survives a restart: reconnect the rotation, reopen,
+    // and the rotated set is still in force.
+    // The disconnect rewound A's nonce, so this reuses nonce 0.
+    let mut rotation = Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: addr_a,
+        to: poa::VALIDATOR_ROTATION_ADDRESS,
+        amount: 2,
+        fee: 0,
+        data: poa::rotation_data(std::slice::from_ref(&hex_b)),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    };
+    crypto::sign_transaction(&mut rotation, &key_a).unwrap();
+    let height = chain.height() + 1;
+    let block = seal_poa(&chain, &key_a, vec![coinbase(&chain, height, 0), rotation]);
+    chain.add_block(&block, MAINNET_CHAIN_ID).unwrap();
+    drop(chain);
+
+    let mut reopened = Blockchain::open(&dir, MAINNET_CHAIN_ID).unwrap();
+    reopened.set_chain_params(params);
+    assert_eq!(reopened.validator_set(), [hex_b]);
+}
@@ -0,0 +1,81 @@
+//! Relay policy: operator-tunable mempool acceptance rules.
+
+use pali_coin::error::MempoolErrorKind;
+use pali_coin::mempool::{
+    Mempool, Policy, DUST_THRESHOLD, MAX_ANCESTOR_CHAIN, MAX_DATA_CARRIER_BYTES,
+    MAX_STANDARD_TX_BYTES, MIN_RELAY_FEE_RATE,
+};
+use pali_coin::types::Transaction;
+
+fn tx(from: u8, nonce: u64, fee: u64) -> Transaction {
+    Transaction {
+        chain_id: 1,
+        nonce,
+        from: [from; 20],
+        to: [0xEE; 20],
+        amount: 10_000,
+        fee,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+#[test]
+fn default_policy_matches_the_historic_constants() {
+    let policy = Policy::default();
+    assert_eq!(policy.min_relay_fee_rate, MIN_RELAY_FEE_RATE);
+    assert_eq!(policy.max_tx_bytes, MAX_STANDARD_TX_BYTES);
+    assert_eq!(policy.max_ancestors, MAX_ANCESTOR_CHAIN);
+    assert_eq!(policy.dust_threshold, DUST_THRESHOLD);
+    assert_eq!(policy.max_data_bytes, MAX_DATA_CARRIER_BYTES);
+}
+
+#[test]
+fn policy_limits_gate_admission() {
+    let mut pool = Mempool::new();
+
+    let mut dusty = tx(1, 0, 10_000);
+    dusty.amount = DUST_THRESHOLD - 1;
+    assert!(pool.insert(dusty, 0).is_err());
+
+    let mut carrier = tx(2, 0, 1_000_000);
+    carrier.data = vec![0u8; MAX_DATA_CARRIER_BYTES + 1];
+    assert!(pool.insert(carrier, 0).is_err());
+
+    for nonce in 0..MAX_ANCESTOR_CHAIN as u64 {
+        pool.insert(tx(3, nonce, 10_000), 0).unwrap();
+    }
+    assert_eq!(
+        pool.insert(tx(3, MAX_ANCESTOR_CHAIN as u64, 10_000), 0)
+            .unwrap_err()
+            .kind,
+        MempoolErrorKind::ChainTooLong
+    );
+}
+
+#[test]
+fn runtime_policy_changes_apply_to_new_transactions_only() {
+    let mut pool = Mempool::new();
+    let pooled = pool.insert(tx(1, 0, 1_000), 0).unwrap();
+
+    let mut tightened = *pool.policy();
+    tightened.min_relay_fee_rate = 100.0;
+    pool.set_policy(tightened);
+
+    // New transactions face the tightened rate...
+    assert!(pool.insert(tx(2, 0, 1_000), 0).is_err());
+    // ...while what was pooled under the old policy stays pooled.
+    assert!(pool.contains(&pooled));
+    // Estimates answer from the new floor immediately.
+    assert_eq!(pool.estimate_fee_rate(1, 1_000_000), 100.0);
+
+    let relaxed = Policy {
+        min_relay_fee_rate: 0.5,
+        ..Policy::default()
+    };
+    pool.set_policy(relaxed);
+    assert!(pool.insert(tx(2, 0, 100), 0).is_ok());
+}
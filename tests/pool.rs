@@ -0,0 +1,76 @@
+//! Pool share ledger: weighting, payout schemes, persistence.
+
+use pali_coin::math;
+use pali_coin::pool::{payouts_csv, share_bits, ShareLedger};
+
+const W1: [u8; 20] = [0x11; 20];
+const W2: [u8; 20] = [0x22; 20];
+
+#[test]
+fn share_target_is_easier_than_the_block_target_and_clamped() {
+    let bits = 0x1d00ffff;
+    assert!(math::bits_to_target(share_bits(bits)) > math::bits_to_target(bits));
+    // Easing past the floor difficulty clamps instead of overflowing.
+    assert_eq!(share_bits(math::MAX_BITS), math::MAX_BITS);
+}
+
+#[test]
+fn proportional_payouts_split_the_current_round_by_weight() {
+    let mut ledger = ShareLedger::new();
+    ledger.record_share(W1, 100, 1, 1000);
+    ledger.record_block();
+    // The settled round is gone; only these two shares count now.
+    ledger.record_share(W1, 300, 2, 1001);
+    ledger.record_share(W2, 100, 2, 1002);
+    let payouts = ledger.proportional_payouts(1_000);
+    assert_eq!(payouts.len(), 2);
+    assert_eq!(payouts[0].worker, W1);
+    assert_eq!(payouts[0].amount, 750);
+    assert_eq!(payouts[1].amount, 250);
+}
+
+#[test]
+fn pplns_ignores_round_boundaries() {
+    let mut ledger = ShareLedger::new();
+    ledger.record_share(W1, 100, 1, 1000);
+    ledger.record_block();
+    ledger.record_share(W2, 100, 2, 1001);
+    // Window of 2 reaches back across the block into W1's share.
+    let payouts = ledger.pplns_payouts(600, 2);
+    assert_eq!(payouts.len(), 2);
+    assert_eq!(payouts[0].amount, 300);
+    assert_eq!(payouts[1].amount, 300);
+    // Window of 1 sees only the newest share.
+    let payouts = ledger.pplns_payouts(600, 1);
+    assert_eq!(payouts.len(), 1);
+    assert_eq!(payouts[0].worker, W2);
+}
+
+#[test]
+fn the_ledger_survives_a_restart() {
+    let path = std::env::temp_dir().join(format!("pali-pool-{}.dat", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    {
+        let mut ledger = ShareLedger::with_path(&path);
+        ledger.record_share(W1, 100, 1, 1000);
+        ledger.record_block();
+        ledger.record_share(W2, 200, 2, 1001);
+    }
+    let ledger = ShareLedger::with_path(&path);
+    assert_eq!(ledger.shares().len(), 2);
+    assert_eq!(ledger.round_shares().len(), 1);
+    assert_eq!(ledger.blocks_found(), 1);
+    assert_eq!(ledger.workers(), 2);
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn csv_export_lists_every_worker() {
+    let mut ledger = ShareLedger::new();
+    ledger.record_share(W1, 100, 1, 1000);
+    ledger.record_share(W2, 100, 1, 1001);
+    let csv = payouts_csv(&ledger.proportional_payouts(100));
+    assert!(csv.starts_with("address,amount\n"));
+    assert!(csv.contains(&format!("{},50", hex::encode(W1))));
+    assert!(csv.contains(&format!("{},50", hex::encode(W2))));
+}
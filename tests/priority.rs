@@ -0,0 +1,85 @@
+//! Operator fee deltas (prioritisetransaction) in block selection.
+
+use pali_coin::mempool::Mempool;
+use pali_coin::types::Transaction;
+
+/// An unsigned transaction padded so fee rates are easy to reason
+/// about; the mempool trusts the caller's validation.
+fn tx(from: u8, nonce: u64, fee: u64) -> Transaction {
+    Transaction {
+        chain_id: 1,
+        nonce,
+        from: [from; 20],
+        to: [0xEE; 20],
+        amount: 1_000,
+        fee,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+#[test]
+fn a_fee_delta_reorders_block_selection_without_touching_the_tx() {
+    let mut pool = Mempool::new();
+    let cheap = tx(1, 0, 200);
+    let rich = tx(2, 0, 2_000);
+    pool.insert(cheap.clone(), 0).unwrap();
+    pool.insert(rich.clone(), 0).unwrap();
+
+    // Room for one: the rich transaction wins on real fees.
+    let selected = pool.get_transactions_for_block(cheap.size());
+    assert_eq!(selected, vec![rich.clone()]);
+
+    // Accelerated, the cheap one outranks it — its own fee unchanged.
+    pool.prioritise(cheap.hash(), 10_000);
+    let selected = pool.get_transactions_for_block(cheap.size());
+    assert_eq!(selected, vec![cheap.clone()]);
+    assert_eq!(selected[0].fee, 200);
+
+    // Deltas accumulate; cancelling back to zero clears the entry.
+    assert_eq!(pool.prioritise(cheap.hash(), -10_000), 0);
+    assert_eq!(pool.prioritised().count(), 0);
+    let selected = pool.get_transactions_for_block(cheap.size());
+    assert_eq!(selected, vec![rich]);
+}
+
+#[test]
+fn a_negative_delta_deprioritises_and_marks_for_eviction() {
+    let mut pool = Mempool::new();
+    let victim = tx(1, 0, 5_000);
+    let survivor = tx(2, 0, 200);
+    pool.insert(victim.clone(), 0).unwrap();
+    pool.insert(survivor.clone(), 0).unwrap();
+
+    // The delta floors the effective fee at zero, dropping the
+    // transaction behind every honest payer.
+    pool.prioritise(victim.hash(), -1_000_000);
+    let selected = pool.get_transactions_for_block(survivor.size());
+    assert_eq!(selected, vec![survivor]);
+
+    let info = pool.package_info(&victim.hash()).unwrap();
+    assert_eq!(info.ancestor_fees, 5_000);
+    assert_eq!(info.effective_fee_rate, 0.0);
+}
+
+#[test]
+fn deltas_survive_arrival_and_clear_on_confirmation() {
+    let mut pool = Mempool::new();
+    let tx1 = tx(1, 0, 200);
+
+    // Set before the transaction arrives.
+    pool.prioritise(tx1.hash(), 7_000);
+    pool.insert(tx1.clone(), 0).unwrap();
+    assert_eq!(pool.priority_delta(&tx1.hash()), 7_000);
+    let info = pool.package_info(&tx1.hash()).unwrap();
+    let boosted_rate = 7_200.0 / tx1.size() as f64;
+    assert!((info.effective_fee_rate - boosted_rate).abs() < 1e-9);
+
+    // Confirmation retires the delta with the transaction.
+    pool.remove_confirmed(std::slice::from_ref(&tx1));
+    assert_eq!(pool.priority_delta(&tx1.hash()), 0);
+    assert_eq!(pool.prioritised().count(), 0);
+}
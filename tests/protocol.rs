@@ -0,0 +1,156 @@
+//! The protocol table, wire ids and the generated reference document.
+//!
+//! Regenerate docs/protocol.md with: PALI_BLESS=1 cargo test --test protocol
+
+use pali_coin::alerts::{Alert, SignedAlert};
+use pali_coin::network::{protocol_reference, NetworkMessage, PROTOCOL_TABLE};
+use pali_coin::proofs::AddressProof;
+use pali_coin::types::{Block, BlockHeader, Transaction};
+
+fn empty_transaction() -> Transaction {
+    Transaction {
+        chain_id: 0,
+        nonce: 0,
+        from: [0u8; 20],
+        to: [0u8; 20],
+        amount: 0,
+        fee: 0,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+fn empty_block() -> Block {
+    Block {
+        header: BlockHeader {
+            version: 0,
+            prev_hash: [0u8; 32],
+            merkle_root: [0u8; 32],
+            timestamp: 0,
+            bits: 0,
+            nonce: 0,
+            height: 0,
+        },
+        transactions: Vec::new(),
+    }
+}
+
+/// One sample of every message variant, in enum order.
+fn samples() -> Vec<NetworkMessage> {
+    vec![
+        NetworkMessage::Version {
+            version: 1,
+            chain_id: 1,
+            height: 0,
+            user_agent: String::new(),
+        },
+        NetworkMessage::VerAck,
+        NetworkMessage::Ping(0),
+        NetworkMessage::Pong(0),
+        NetworkMessage::Block(empty_block()),
+        NetworkMessage::Transaction(empty_transaction()),
+        NetworkMessage::GetBlocks {
+            from: [0u8; 32],
+            limit: 0,
+        },
+        NetworkMessage::Blocks(Vec::new()),
+        NetworkMessage::GetPeers,
+        NetworkMessage::Peers(Vec::new()),
+        NetworkMessage::GetBlockRange { start: 0, count: 0 },
+        NetworkMessage::StemTransaction(empty_transaction()),
+        NetworkMessage::Reject {
+            what: String::new(),
+            hash: [0u8; 32],
+            code: String::new(),
+        },
+        NetworkMessage::ChainRules(1),
+        NetworkMessage::GetUtxoProof { address: [0u8; 20] },
+        NetworkMessage::UtxoProof(AddressProof {
+            address: [0u8; 20],
+            utxos: Vec::new(),
+        }),
+        NetworkMessage::Alert(SignedAlert {
+            alert: Alert {
+                id: 0,
+                expires: 0,
+                message: String::new(),
+            },
+            signatures: Vec::new(),
+        }),
+        NetworkMessage::GetBlocksLocator {
+            locator: Vec::new(),
+            limit: 0,
+        },
+        NetworkMessage::GetCFHeaders {
+            start_height: 0,
+            stop_hash: [0u8; 32],
+        },
+        NetworkMessage::CFHeaders {
+            start_height: 0,
+            prev_header: [0u8; 32],
+            filter_hashes: Vec::new(),
+        },
+        NetworkMessage::GetCFilters {
+            start_height: 0,
+            stop_hash: [0u8; 32],
+        },
+        NetworkMessage::CFilters(Vec::new()),
+        NetworkMessage::Package(Vec::new()),
+        NetworkMessage::Inv {
+            blocks: Vec::new(),
+            txs: Vec::new(),
+        },
+        NetworkMessage::GetData {
+            blocks: Vec::new(),
+            txs: Vec::new(),
+        },
+        NetworkMessage::NotFound {
+            blocks: Vec::new(),
+            txs: Vec::new(),
+        },
+    ]
+}
+
+#[test]
+fn wire_ids_match_the_bincode_encoding() {
+    for message in samples() {
+        let bytes = bincode::serialize(&message).unwrap();
+        let encoded = u32::from_le_bytes(bytes[..4].try_into().unwrap());
+        assert_eq!(
+            message.wire_id(),
+            encoded,
+            "wire id of {} diverged from its encoded discriminant",
+            message.name()
+        );
+    }
+}
+
+#[test]
+fn protocol_table_covers_every_variant() {
+    let samples = samples();
+    assert_eq!(PROTOCOL_TABLE.len(), samples.len());
+    for (i, (id, name, _, _)) in PROTOCOL_TABLE.iter().enumerate() {
+        assert_eq!(*id, i as u32, "wire ids must be contiguous from zero");
+        assert_eq!(samples[i].name(), *name);
+    }
+}
+
+#[test]
+fn reference_document_is_current() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("docs/protocol.md");
+    let rendered = protocol_reference();
+    if std::env::var("PALI_BLESS").is_ok() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &rendered).unwrap();
+        return;
+    }
+    let committed = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("missing {}: {} (re-bless to generate)", path.display(), e));
+    assert_eq!(
+        committed, rendered,
+        "docs/protocol.md is stale — regenerate with PALI_BLESS=1 cargo test --test protocol"
+    );
+}
@@ -0,0 +1,220 @@
+//! The raw transaction workflow: create, decode, sign and broadcast
+//! canonical hex outside the wallet.
+
+use std::sync::{Arc, Mutex};
+
+use pali_coin::blockchain::{
+    Blockchain, GenesisConfig, PremineAllocation, COINBASE_MATURITY,
+};
+use pali_coin::mempool::Mempool;
+use pali_coin::rpc::{dispatch, RpcContext};
+use pali_coin::rpc_auth::AuthConfig;
+use pali_coin::types::{
+    block_reward, Address, Block, BlockHeader, Hash256, Transaction, COIN, COINBASE_ADDRESS,
+};
+use pali_coin::{crypto, hash, math, MAINNET_CHAIN_ID};
+use secp256k1::{Secp256k1, SecretKey};
+use serde_json::{json, Value};
+
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("pali-rawtx-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn keypair() -> (SecretKey, Address) {
+    let secp = Secp256k1::new();
+    let (secret, public) = secp.generate_keypair(&mut rand::thread_rng());
+    (secret, hash::pubkey_to_address(&public.serialize()))
+}
+
+fn ctx_with_premine(name: &str, address: &Address) -> RpcContext {
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "rawtx test".to_string(),
+        bits: math::MAX_BITS,
+        premine: vec![PremineAllocation {
+            address: hex::encode(address),
+            amount: 5 * COIN,
+        }],
+    };
+    let chain = Blockchain::init_chain(test_dir(name), &config).unwrap();
+    RpcContext {
+        chain: Arc::new(Mutex::new(chain)),
+        mempool: Arc::new(Mutex::new(Mempool::new())),
+        node: None,
+        chain_id: MAINNET_CHAIN_ID,
+        auth: Arc::new(AuthConfig::default()),
+    }
+}
+
+fn seal(chain: &Blockchain, transactions: Vec<Transaction>) -> Block {
+    let hashes: Vec<Hash256> = transactions.iter().map(|tx| tx.hash()).collect();
+    let mut header = BlockHeader {
+        version: 1,
+        prev_hash: chain.best_hash(),
+        merkle_root: hash::merkle_root(&hashes),
+        timestamp: 1_700_000_000 + (chain.height() + 1) * 180,
+        bits: chain.next_bits().unwrap(),
+        nonce: 0,
+        height: chain.height() + 1,
+    };
+    while !math::hash_meets_target(&header.hash(), header.bits) {
+        header.nonce = header.nonce.wrapping_add(1);
+    }
+    Block {
+        header,
+        transactions,
+    }
+}
+
+fn coinbase(height: u64) -> Transaction {
+    Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: COINBASE_ADDRESS,
+        to: [0x11; 20],
+        amount: block_reward(height),
+        fee: 0,
+        // Height keeps otherwise identical rewards from colliding.
+        data: height.to_be_bytes().to_vec(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+#[test]
+fn create_and_decode_round_trip() {
+    let sender = [0xAA; 20];
+    let ctx = ctx_with_premine("roundtrip", &sender);
+    let spec = json!([{
+        "from": hex::encode(sender),
+        "to": hex::encode([0xBB; 20]),
+        "amount": 2 * COIN,
+        "fee": 1_500,
+        "data": hex::encode(b"memo"),
+        "lock_time": 42,
+    }]);
+    let tx_hex = dispatch(&ctx, "createrawtransaction", &spec).unwrap();
+
+    let decoded = dispatch(&ctx, "decoderawtransaction", &json!([tx_hex])).unwrap();
+    assert_eq!(decoded["from"], json!(hex::encode(sender)));
+    assert_eq!(decoded["to"], json!(hex::encode([0xBB; 20])));
+    assert_eq!(decoded["amount"], json!(2 * COIN));
+    assert_eq!(decoded["fee"], json!(1_500));
+    // The nonce came from chain state, not the caller.
+    assert_eq!(decoded["nonce"], json!(0));
+    assert_eq!(decoded["data"], json!(hex::encode(b"memo")));
+    assert_eq!(decoded["lock_time"], json!(42));
+    assert_eq!(decoded["replaceable"], Value::Bool(false));
+    assert_eq!(decoded["signed"], Value::Bool(false));
+
+    // An explicit nonce overrides the lookup; malformed addresses are
+    // refused before anything is encoded.
+    let spec = json!([{
+        "from": hex::encode(sender),
+        "to": hex::encode([0xBB; 20]),
+        "amount": 1u64,
+        "nonce": 9,
+    }]);
+    let tx_hex = dispatch(&ctx, "createrawtransaction", &spec).unwrap();
+    let decoded = dispatch(&ctx, "decoderawtransaction", &json!([tx_hex])).unwrap();
+    assert_eq!(decoded["nonce"], json!(9));
+    let err = dispatch(
+        &ctx,
+        "createrawtransaction",
+        &json!([{ "from": "zz", "to": hex::encode([0xBB; 20]), "amount": 1u64 }]),
+    )
+    .unwrap_err();
+    assert!(err.contains("20-byte hex address"), "unexpected error: {}", err);
+}
+
+#[test]
+fn signing_requires_the_matching_key() {
+    let (secret, sender) = keypair();
+    let ctx = ctx_with_premine("sign", &sender);
+    let tx_hex = dispatch(
+        &ctx,
+        "createrawtransaction",
+        &json!([{
+            "from": hex::encode(sender),
+            "to": hex::encode([0xBB; 20]),
+            "amount": COIN,
+            "fee": 1_000,
+        }]),
+    )
+    .unwrap();
+
+    let signed = dispatch(
+        &ctx,
+        "signrawtransactionwithkey",
+        &json!([tx_hex, hex::encode(secret.secret_bytes())]),
+    )
+    .unwrap();
+    assert_eq!(signed["complete"], Value::Bool(true));
+    let bytes = hex::decode(signed["hex"].as_str().unwrap()).unwrap();
+    let tx: Transaction = bincode::deserialize(&bytes).unwrap();
+    crypto::verify_transaction_signature(&tx).unwrap();
+    assert_eq!(signed["txid"], json!(hex::encode(tx.hash())));
+
+    // A key for a different address cannot produce a broadcastable
+    // signature, and an unknown scheme is refused outright.
+    let (stranger, _) = keypair();
+    let err = dispatch(
+        &ctx,
+        "signrawtransactionwithkey",
+        &json!([tx_hex, hex::encode(stranger.secret_bytes())]),
+    )
+    .unwrap_err();
+    assert!(err.contains("does not sign for the sender"), "unexpected error: {}", err);
+    let err = dispatch(
+        &ctx,
+        "signrawtransactionwithkey",
+        &json!([tx_hex, hex::encode(secret.secret_bytes()), "dsa"]),
+    )
+    .unwrap_err();
+    assert!(err.contains("unknown signature scheme"), "unexpected error: {}", err);
+}
+
+#[test]
+fn raw_workflow_broadcasts_end_to_end() {
+    let (secret, sender) = keypair();
+    let ctx = ctx_with_premine("broadcast", &sender);
+    // Mature the premine so the spend passes full validation.
+    {
+        let mut chain = ctx.chain.lock().unwrap();
+        for height in 1..=COINBASE_MATURITY {
+            let block = seal(&chain, vec![coinbase(height)]);
+            chain.add_block(&block, MAINNET_CHAIN_ID).unwrap();
+        }
+    }
+
+    let tx_hex = dispatch(
+        &ctx,
+        "createrawtransaction",
+        &json!([{
+            "from": hex::encode(sender),
+            "to": hex::encode([0xBB; 20]),
+            "amount": 2 * COIN,
+            "fee": 1_000,
+        }]),
+    )
+    .unwrap();
+    let signed = dispatch(
+        &ctx,
+        "signrawtransactionwithkey",
+        &json!([tx_hex, hex::encode(secret.secret_bytes())]),
+    )
+    .unwrap();
+    let txid = dispatch(&ctx, "sendrawtransaction", &json!([signed["hex"]])).unwrap();
+    assert_eq!(txid, signed["txid"]);
+
+    let hash: Hash256 = hex::decode(txid.as_str().unwrap())
+        .unwrap()
+        .try_into()
+        .unwrap();
+    assert!(ctx.mempool.lock().unwrap().contains(&hash));
+}
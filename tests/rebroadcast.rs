@@ -0,0 +1,57 @@
+//! Wallet-transaction rebroadcast: origin tracking and due selection.
+
+use pali_coin::mempool::{Mempool, TxOrigin, REBROADCAST_AFTER_BLOCKS};
+use pali_coin::types::Transaction;
+
+fn tx(from: u8, nonce: u64) -> Transaction {
+    Transaction {
+        chain_id: 1,
+        nonce,
+        from: [from; 20],
+        to: [0xEE; 20],
+        amount: 10_000,
+        fee: 10_000,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+#[test]
+fn insert_paths_record_the_origin() {
+    let mut pool = Mempool::new();
+    let relayed = pool.insert(tx(1, 0), 100).unwrap();
+    let ours = pool.insert_from_wallet(tx(2, 0), 100).unwrap();
+    assert_eq!(pool.get(&relayed).unwrap().origin, TxOrigin::Relay);
+    assert_eq!(pool.get(&ours).unwrap().origin, TxOrigin::Wallet);
+}
+
+#[test]
+fn only_stuck_wallet_transactions_come_due() {
+    let mut pool = Mempool::new();
+    pool.insert(tx(1, 0), 100).unwrap();
+    let ours = pool.insert_from_wallet(tx(2, 0), 100).unwrap();
+
+    // Not yet: the interval has not elapsed.
+    assert!(pool
+        .rebroadcast_candidates(100 + REBROADCAST_AFTER_BLOCKS - 1)
+        .is_empty());
+
+    // Due: only the wallet transaction goes out, never relayed traffic.
+    let due = pool.rebroadcast_candidates(100 + REBROADCAST_AFTER_BLOCKS);
+    assert_eq!(due.len(), 1);
+    assert_eq!(due[0].hash(), ours);
+
+    // The announcement height was reset, so the same height yields
+    // nothing until another full interval passes.
+    assert!(pool
+        .rebroadcast_candidates(100 + REBROADCAST_AFTER_BLOCKS)
+        .is_empty());
+    assert_eq!(
+        pool.rebroadcast_candidates(100 + 2 * REBROADCAST_AFTER_BLOCKS)
+            .len(),
+        1
+    );
+}
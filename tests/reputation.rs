@@ -0,0 +1,89 @@
+//! Peer reputation: decay toward neutral, first-relay credit, and
+//! rehabilitation of banned peers.
+
+use std::net::IpAddr;
+
+use pali_coin::reputation::{
+    ReputationBook, BAN_THRESHOLD, DECAY_HALF_LIFE_SECS, FIRST_RELAY_BONUS, INVALID_RELAY_PENALTY,
+    MAX_CREDIT,
+};
+
+fn ip(last: u8) -> IpAddr {
+    IpAddr::from([10, 0, 0, last])
+}
+
+#[test]
+fn scores_decay_toward_neutral_from_both_sides() {
+    let mut book = ReputationBook::new();
+    let offender = ip(1);
+    let veteran = ip(2);
+    let start = 1_000_000;
+
+    book.penalize(offender, 40, start);
+    assert_eq!(book.score(offender, start), -40);
+    assert_eq!(book.score(offender, start + DECAY_HALF_LIFE_SECS), -20);
+    assert_eq!(book.score(offender, start + 2 * DECAY_HALF_LIFE_SECS), -10);
+
+    // Credit is capped, then decays by the same halving schedule.
+    for _ in 0..100 {
+        book.credit_first_relay(veteran, start);
+    }
+    assert_eq!(book.score(veteran, start), MAX_CREDIT);
+    assert_eq!(
+        book.score(veteran, start + DECAY_HALF_LIFE_SECS),
+        MAX_CREDIT / 2
+    );
+
+    // Peers the book has never heard of are neutral.
+    assert_eq!(book.score(ip(3), start), 0);
+    assert!(!book.is_banned(ip(3), start));
+}
+
+#[test]
+fn bans_trip_once_and_decay_lifts_them() {
+    let mut book = ReputationBook::new();
+    let peer = ip(4);
+    let start = 1_000_000;
+
+    // Enough invalid relays in quick succession cross the threshold;
+    // only the crossing penalty reports the ban.
+    let mut tripped = 0;
+    for _ in 0..6 {
+        if book.penalize(peer, INVALID_RELAY_PENALTY, start) {
+            tripped += 1;
+        }
+    }
+    assert_eq!(tripped, 1);
+    assert!(book.is_banned(peer, start));
+    assert!(book.score(peer, start) <= BAN_THRESHOLD);
+
+    // Still banned after one half-life, rehabilitated after two, and
+    // the review pass reports exactly who came back.
+    assert!(book.is_banned(peer, start + DECAY_HALF_LIFE_SECS));
+    let unbanned = book.review(start + 2 * DECAY_HALF_LIFE_SECS);
+    assert_eq!(unbanned, vec![peer]);
+    assert!(!book.is_banned(peer, start + 2 * DECAY_HALF_LIFE_SECS));
+}
+
+#[test]
+fn first_relay_credit_buffers_honest_peers() {
+    let mut book = ReputationBook::new();
+    let honest = ip(5);
+    let fresh = ip(6);
+    let start = 1_000_000;
+
+    // A history of winning relay races earns a cushion.
+    for _ in 0..(MAX_CREDIT / FIRST_RELAY_BONUS) {
+        book.credit_first_relay(honest, start);
+    }
+
+    // The same burst of invalid relays that bans a fresh peer leaves
+    // the proven relayer connected — transient bugs cost less when the
+    // record says the peer is normally honest.
+    for _ in 0..5 {
+        book.penalize(honest, INVALID_RELAY_PENALTY, start);
+        book.penalize(fresh, INVALID_RELAY_PENALTY, start);
+    }
+    assert!(book.is_banned(fresh, start));
+    assert!(!book.is_banned(honest, start));
+}
@@ -0,0 +1,207 @@
+//! Proof of reserves: signing UTXO attestations and verifying them
+//! against the chain, including how proofs go stale once coins move.
+
+use std::sync::{Arc, Mutex};
+
+use pali_coin::blockchain::{
+    Blockchain, GenesisConfig, PremineAllocation, COINBASE_MATURITY,
+};
+use pali_coin::mempool::Mempool;
+use pali_coin::reserves::{sign_reserve_proof, verify_reserve_proof};
+use pali_coin::rpc::{dispatch, RpcContext};
+use pali_coin::rpc_auth::AuthConfig;
+use pali_coin::types::{
+    block_reward, Address, Block, BlockHeader, Hash256, OutPoint, Transaction, COIN,
+    COINBASE_ADDRESS,
+};
+use pali_coin::{crypto, hash, math, MAINNET_CHAIN_ID};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+use serde_json::json;
+
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("pali-reserves-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn keypair(seed: u8) -> (SecretKey, Address) {
+    let secp = Secp256k1::new();
+    let secret = SecretKey::from_slice(&[seed; 32]).unwrap();
+    let public = PublicKey::from_secret_key(&secp, &secret);
+    (secret, hash::pubkey_to_address(&public.serialize()))
+}
+
+fn funded_chain(name: &str, address: &Address, amount: u64) -> Blockchain {
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "reserves test".to_string(),
+        bits: math::MAX_BITS,
+        premine: vec![PremineAllocation {
+            address: hex::encode(address),
+            amount,
+        }],
+    };
+    Blockchain::init_chain(test_dir(name), &config).unwrap()
+}
+
+fn seal(chain: &Blockchain, transactions: Vec<Transaction>) -> Block {
+    let hashes: Vec<Hash256> = transactions.iter().map(|tx| tx.hash()).collect();
+    let mut header = BlockHeader {
+        version: 1,
+        prev_hash: chain.best_hash(),
+        merkle_root: hash::merkle_root(&hashes),
+        timestamp: 1_700_000_000 + (chain.height() + 1) * 180,
+        bits: chain.next_bits().unwrap(),
+        nonce: 0,
+        height: chain.height() + 1,
+    };
+    while !math::hash_meets_target(&header.hash(), header.bits) {
+        header.nonce = header.nonce.wrapping_add(1);
+    }
+    Block {
+        header,
+        transactions,
+    }
+}
+
+fn coinbase(height: u64, fees: u64) -> Transaction {
+    Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: COINBASE_ADDRESS,
+        to: [0x11; 20],
+        amount: block_reward(height) + fees,
+        fee: 0,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+fn snapshot(chain: &Blockchain, address: &Address) -> Vec<(OutPoint, u64)> {
+    chain
+        .get_utxos_for_address(address)
+        .unwrap()
+        .into_iter()
+        .map(|(outpoint, entry)| (outpoint, entry.amount))
+        .collect()
+}
+
+#[test]
+fn a_signed_snapshot_verifies_against_the_chain() {
+    let (secret, address) = keypair(0x01);
+    let chain = funded_chain("verify", &address, 3 * COIN);
+
+    let utxos = snapshot(&chain, &address);
+    assert_eq!(utxos.len(), 1);
+    let proof = sign_reserve_proof(
+        &secret,
+        chain.best_hash(),
+        chain.height(),
+        "audit 2026-08, nonce 7f3a",
+        &utxos,
+    );
+    assert_eq!(proof.address, address);
+    assert_eq!(proof.total(), 3 * COIN);
+    assert_eq!(verify_reserve_proof(&chain, &proof).unwrap(), 3 * COIN);
+}
+
+#[test]
+fn forged_proofs_fail_every_check() {
+    let (secret, address) = keypair(0x02);
+    let chain = funded_chain("forge", &address, 3 * COIN);
+    let utxos = snapshot(&chain, &address);
+    let proof = sign_reserve_proof(&secret, chain.best_hash(), chain.height(), "audit", &utxos);
+
+    // Inflating an attested amount no longer matches the UTXO set.
+    let mut inflated = proof.clone();
+    inflated.utxos[0].amount += 1;
+    assert!(verify_reserve_proof(&chain, &inflated).is_err());
+
+    // Rewriting the claim breaks every signature made under it.
+    let mut relabeled = proof.clone();
+    relabeled.claim = "a different audit".to_string();
+    assert!(verify_reserve_proof(&chain, &relabeled).is_err());
+
+    // Listing the same coin twice would double-count it.
+    let mut doubled = proof.clone();
+    let dup = doubled.utxos[0].clone();
+    doubled.utxos.push(dup);
+    assert!(verify_reserve_proof(&chain, &doubled).is_err());
+
+    // A coin the chain has never seen proves nothing.
+    let mut invented = proof.clone();
+    invented.utxos[0].outpoint.tx_hash = [0xEE; 32];
+    assert!(verify_reserve_proof(&chain, &invented).is_err());
+
+    // Claiming someone else's coins fails the key-to-address check.
+    let (intruder, _) = keypair(0x03);
+    let stolen = sign_reserve_proof(&intruder, chain.best_hash(), chain.height(), "audit", &utxos);
+    let mut stolen = stolen;
+    stolen.address = address;
+    assert!(verify_reserve_proof(&chain, &stolen).is_err());
+
+    // An anchor off the best chain is rejected before any coin check.
+    let mut unanchored = proof.clone();
+    unanchored.block_hash = [0xDD; 32];
+    assert!(verify_reserve_proof(&chain, &unanchored).is_err());
+}
+
+#[test]
+fn spending_attested_coins_stales_the_proof() {
+    let (secret, address) = keypair(0x04);
+    let mut chain = funded_chain("stale", &address, 5 * COIN);
+    for height in 1..=COINBASE_MATURITY {
+        let block = seal(&chain, vec![coinbase(height, 0)]);
+        chain.add_block(&block, MAINNET_CHAIN_ID).unwrap();
+    }
+
+    let utxos = snapshot(&chain, &address);
+    let proof = sign_reserve_proof(&secret, chain.best_hash(), chain.height(), "audit", &utxos);
+    assert_eq!(verify_reserve_proof(&chain, &proof).unwrap(), 5 * COIN);
+
+    // The verifier RPC agrees while the coins sit still.
+    let proof_hex = hex::encode(bincode::serialize(&proof).unwrap());
+    let chain = Arc::new(Mutex::new(chain));
+    let ctx = RpcContext {
+        chain: chain.clone(),
+        mempool: Arc::new(Mutex::new(Mempool::new())),
+        node: None,
+        chain_id: MAINNET_CHAIN_ID,
+        auth: Arc::new(AuthConfig::default()),
+    };
+    let reply = dispatch(&ctx, "verifyreserveproof", &json!([proof_hex])).unwrap();
+    assert_eq!(reply["valid"], json!(true));
+    assert_eq!(reply["total"], json!(5 * COIN));
+
+    // Spending any attested coin invalidates the whole snapshot.
+    {
+        let mut chain = chain.lock().unwrap();
+        let mut tx = Transaction {
+            chain_id: MAINNET_CHAIN_ID,
+            nonce: 0,
+            from: address,
+            to: [0xBB; 20],
+            amount: 2 * COIN,
+            fee: 1_000,
+            data: Vec::new(),
+            replaceable: false,
+            lock_time: 0,
+            signature: Vec::new(),
+            public_key: Vec::new(),
+        };
+        crypto::sign_transaction(&mut tx, &secret).unwrap();
+        let height = chain.height() + 1;
+        let block = seal(&chain, vec![coinbase(height, tx.fee), tx]);
+        chain.add_block(&block, MAINNET_CHAIN_ID).unwrap();
+    }
+    let reply = dispatch(&ctx, "verifyreserveproof", &json!([proof_hex])).unwrap();
+    assert_eq!(reply["valid"], json!(false));
+    assert!(reply["reason"]
+        .as_str()
+        .unwrap()
+        .contains("spent or never existed"));
+}
@@ -0,0 +1,119 @@
+//! The background integrity scrubber: clean chains scrub clean and
+//! the running status folds batches in correctly.
+
+use pali_coin::blockchain::{
+    Blockchain, GenesisConfig, PremineAllocation, ScrubBatch, ScrubFinding, ScrubStatus,
+    CF_BLOCKS, CF_DIFFICULTY, CF_EVENTS, CF_FILTERS, CF_HEIGHTS, CF_SPENT, CF_STATE, CF_TXINDEX,
+    CF_UNDO, CF_UTXOS, SCRUB_FINDINGS_KEPT,
+};
+use pali_coin::{math, MAINNET_CHAIN_ID};
+
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("pali-scrub-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn funded_chain(name: &str) -> Blockchain {
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "scrub test".to_string(),
+        bits: math::MAX_BITS,
+        premine: vec![PremineAllocation {
+            address: hex::encode([0xC4; 20]),
+            amount: 90_000,
+        }],
+    };
+    Blockchain::init_chain(test_dir(name), &config).unwrap()
+}
+
+#[test]
+fn a_healthy_chain_scrubs_clean_and_wraps_to_zero() {
+    let chain = funded_chain("clean");
+    let batch = chain.scrub_blocks(0, 64).unwrap();
+    assert_eq!(batch.checked, 1);
+    assert_eq!(batch.next_height, 0, "past the tip the cursor wraps");
+    assert!(batch.findings.is_empty());
+}
+
+#[test]
+fn absorb_counts_passes_and_caps_findings() {
+    let mut status = ScrubStatus::default();
+    status.absorb(&ScrubBatch {
+        checked: 64,
+        next_height: 64,
+        findings: Vec::new(),
+    });
+    assert_eq!(status.cursor, 64);
+    assert_eq!(status.passes_completed, 0);
+
+    // Wrapping back to height 0 completes a full circle.
+    status.absorb(&ScrubBatch {
+        checked: 10,
+        next_height: 0,
+        findings: Vec::new(),
+    });
+    assert_eq!(status.blocks_checked, 74);
+    assert_eq!(status.passes_completed, 1);
+
+    let noisy = ScrubBatch {
+        checked: 1,
+        next_height: 1,
+        findings: (0..SCRUB_FINDINGS_KEPT + 25)
+            .map(|i| ScrubFinding {
+                height: i as u64,
+                detail: "synthetic".to_string(),
+                repaired: false,
+            })
+            .collect(),
+    };
+    status.absorb(&noisy);
+    assert_eq!(status.findings.len(), SCRUB_FINDINGS_KEPT);
+    // Oldest findings were dropped first.
+    assert_eq!(status.findings[0].height, 25);
+}
+
+#[test]
+fn a_damaged_tx_index_entry_is_rebuilt() {
+    let dir = test_dir("repair");
+    let (txid, genesis_hash) = {
+        let chain = funded_chain("repair");
+        let genesis = chain.get_block_by_height(0).unwrap().unwrap();
+        (genesis.transactions[0].hash(), genesis.hash())
+    };
+
+    // Rot the index entry behind the chain's back, the way a bad
+    // sector would.
+    {
+        let cfs: Vec<_> = [
+            CF_BLOCKS,
+            CF_HEIGHTS,
+            CF_UTXOS,
+            CF_TXINDEX,
+            CF_STATE,
+            CF_DIFFICULTY,
+            CF_SPENT,
+            CF_UNDO,
+            CF_FILTERS,
+            CF_EVENTS,
+        ]
+        .iter()
+        .map(|name| rocksdb::ColumnFamilyDescriptor::new(*name, rocksdb::Options::default()))
+        .collect();
+        let db =
+            rocksdb::DB::open_cf_descriptors(&rocksdb::Options::default(), &dir, cfs).unwrap();
+        let cf = db.cf_handle(CF_TXINDEX).unwrap();
+        db.put_cf(cf, txid, b"not a tx location").unwrap();
+    }
+
+    let chain = Blockchain::open(&dir, MAINNET_CHAIN_ID).unwrap();
+    let batch = chain.scrub_blocks(0, 64).unwrap();
+    assert_eq!(batch.findings.len(), 1);
+    assert!(batch.findings[0].repaired);
+
+    // The repaired entry resolves again and a second pass is clean.
+    let loc = chain.get_tx_location(&txid).unwrap().unwrap();
+    assert_eq!(loc.block_hash, genesis_hash);
+    assert!(chain.scrub_blocks(0, 64).unwrap().findings.is_empty());
+}
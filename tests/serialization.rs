@@ -0,0 +1,211 @@
+//! Golden-file tests freezing the bincode wire layout of consensus and
+//! protocol types. A change in any of these vectors is a hard fork or a
+//! protocol break: do not re-bless without a migration plan.
+//!
+//! Regenerate intentionally with: PALI_BLESS=1 cargo test --test serialization
+
+use pali_coin::alerts::{Alert, SignedAlert};
+use pali_coin::hash::MerkleStep;
+use pali_coin::network::NetworkMessage;
+use pali_coin::proofs::{AddressProof, UtxoProofEntry};
+use pali_coin::types::{Block, BlockHeader, OutPoint, Transaction, UtxoEntry};
+
+fn fixture_transaction() -> Transaction {
+    Transaction {
+        chain_id: 1,
+        nonce: 7,
+        from: [0x11; 20],
+        to: [0x22; 20],
+        amount: 5_000_000_000,
+        fee: 10_000,
+        data: b"golden".to_vec(),
+        replaceable: true,
+        lock_time: 98,
+        signature: vec![0xAB; 64],
+        public_key: vec![0xCD; 33],
+    }
+}
+
+fn fixture_header() -> BlockHeader {
+    BlockHeader {
+        version: 1,
+        prev_hash: [0x33; 32],
+        merkle_root: [0x44; 32],
+        timestamp: 1_700_000_123,
+        bits: 0x1f00ffff,
+        nonce: 424242,
+        height: 99,
+    }
+}
+
+fn fixture_block() -> Block {
+    Block {
+        header: fixture_header(),
+        transactions: vec![fixture_transaction()],
+    }
+}
+
+fn fixture_messages() -> Vec<(&'static str, NetworkMessage)> {
+    vec![
+        (
+            "msg_version",
+            NetworkMessage::Version {
+                version: 1,
+                chain_id: 1,
+                height: 42,
+                user_agent: "/pali-coin:test/".to_string(),
+            },
+        ),
+        ("msg_verack", NetworkMessage::VerAck),
+        ("msg_ping", NetworkMessage::Ping(0xDEADBEEF)),
+        ("msg_pong", NetworkMessage::Pong(0xDEADBEEF)),
+        ("msg_block", NetworkMessage::Block(fixture_block())),
+        (
+            "msg_transaction",
+            NetworkMessage::Transaction(fixture_transaction()),
+        ),
+        (
+            "msg_getblocks",
+            NetworkMessage::GetBlocks {
+                from: [0x55; 32],
+                limit: 500,
+            },
+        ),
+        ("msg_blocks", NetworkMessage::Blocks(vec![fixture_block()])),
+        ("msg_getpeers", NetworkMessage::GetPeers),
+        (
+            "msg_peers",
+            NetworkMessage::Peers(vec!["127.0.0.1:8535".to_string()]),
+        ),
+        (
+            "msg_getblockrange",
+            NetworkMessage::GetBlockRange {
+                start: 1000,
+                count: 128,
+            },
+        ),
+        (
+            "msg_stemtransaction",
+            NetworkMessage::StemTransaction(fixture_transaction()),
+        ),
+        (
+            "msg_reject",
+            NetworkMessage::Reject {
+                what: "tx".to_string(),
+                hash: [0x66; 32],
+                code: "bad-txns-nonce".to_string(),
+            },
+        ),
+        ("msg_chainrules", NetworkMessage::ChainRules(1)),
+        (
+            "msg_getutxoproof",
+            NetworkMessage::GetUtxoProof { address: [0x77; 20] },
+        ),
+        (
+            "msg_utxoproof",
+            NetworkMessage::UtxoProof(AddressProof {
+                address: [0x77; 20],
+                utxos: vec![UtxoProofEntry {
+                    outpoint: OutPoint {
+                        tx_hash: [0x88; 32],
+                        index: 0,
+                    },
+                    entry: UtxoEntry {
+                        address: [0x77; 20],
+                        amount: 5_000_000_000,
+                        height: 99,
+                        is_coinbase: false,
+                    },
+                    tx: fixture_transaction(),
+                    branch: vec![MerkleStep {
+                        hash: [0x99; 32],
+                        is_left: true,
+                    }],
+                    header: fixture_header(),
+                }],
+            }),
+        ),
+        (
+            "msg_alert",
+            NetworkMessage::Alert(SignedAlert {
+                alert: Alert {
+                    id: 7,
+                    expires: 1_700_000_000,
+                    message: "upgrade before height 100000".to_string(),
+                },
+                signatures: vec![vec![0xAB; 64]],
+            }),
+        ),
+        (
+            "msg_getblockslocator",
+            NetworkMessage::GetBlocksLocator {
+                locator: vec![[0x55; 32], [0x56; 32]],
+                limit: 500,
+            },
+        ),
+    ]
+}
+
+fn check_golden(name: &str, bytes: &[u8]) {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.hex", name));
+    let actual = hex::encode(bytes);
+    if std::env::var("PALI_BLESS").is_ok() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, format!("{}\n", actual)).unwrap();
+        return;
+    }
+    let expected = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("missing golden file {}: {}", path.display(), e));
+    assert_eq!(
+        expected.trim(),
+        actual,
+        "wire layout of {} changed — this breaks consensus/protocol compatibility",
+        name
+    );
+}
+
+#[test]
+fn transaction_layout_is_frozen() {
+    let tx = fixture_transaction();
+    check_golden("transaction", &bincode::serialize(&tx).unwrap());
+    // Round trip must reproduce the identical value.
+    let decoded: Transaction =
+        bincode::deserialize(&bincode::serialize(&tx).unwrap()).unwrap();
+    assert_eq!(tx, decoded);
+}
+
+#[test]
+fn block_header_layout_is_frozen() {
+    let header = fixture_header();
+    check_golden("block_header", &bincode::serialize(&header).unwrap());
+    let decoded: BlockHeader =
+        bincode::deserialize(&bincode::serialize(&header).unwrap()).unwrap();
+    assert_eq!(header, decoded);
+}
+
+#[test]
+fn block_layout_is_frozen() {
+    let block = fixture_block();
+    check_golden("block", &bincode::serialize(&block).unwrap());
+    let decoded: Block = bincode::deserialize(&bincode::serialize(&block).unwrap()).unwrap();
+    assert_eq!(block, decoded);
+}
+
+#[test]
+fn network_message_layouts_are_frozen() {
+    for (name, message) in fixture_messages() {
+        check_golden(name, &bincode::serialize(&message).unwrap());
+        let decoded: NetworkMessage =
+            bincode::deserialize(&bincode::serialize(&message).unwrap()).unwrap();
+        assert_eq!(message, decoded);
+    }
+}
+
+#[test]
+fn fixture_hashes_are_stable() {
+    // Hashes derive from the serialization, so freeze them too.
+    check_golden("tx_hash", &fixture_transaction().hash());
+    check_golden("block_hash", &fixture_block().hash());
+}
@@ -0,0 +1,92 @@
+//! The verified-signature cache: exact-bytes binding, the lookup
+//! short-circuit between mempool entry and block connect, and eviction.
+
+use std::sync::Mutex;
+
+use pali_coin::types::Transaction;
+use pali_coin::{crypto, hash, sigcache, MAINNET_CHAIN_ID};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+// The cache under test is process-global; serialize the tests so one
+// test's flood cannot evict another's freshly recorded entry.
+static CACHE_GUARD: Mutex<()> = Mutex::new(());
+
+fn signed_tx(seed: u8, amount: u64) -> Transaction {
+    let secp = Secp256k1::new();
+    let secret = SecretKey::from_slice(&[seed; 32]).unwrap();
+    let public = PublicKey::from_secret_key(&secp, &secret);
+    let mut tx = Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: hash::pubkey_to_address(&public.serialize()),
+        to: [0xD2; 20],
+        amount,
+        fee: 1_000,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    };
+    crypto::sign_transaction(&mut tx, &secret).unwrap();
+    tx
+}
+
+fn key_of(tx: &Transaction) -> [u8; 32] {
+    sigcache::cache_key(&crypto::signing_hash(tx), &tx.public_key, &tx.signature)
+}
+
+#[test]
+fn a_passed_check_is_remembered_under_its_exact_bytes() {
+    let _guard = CACHE_GUARD.lock().unwrap();
+    let tx = signed_tx(0x31, 5_000);
+    crypto::verify_transaction_signature(&tx).unwrap();
+    assert!(sigcache::is_verified(&key_of(&tx)));
+
+    // Any change to what was signed lands on a different cache key and
+    // fails verification outright.
+    let mut tampered = tx.clone();
+    tampered.amount += 1;
+    assert_ne!(key_of(&tx), key_of(&tampered));
+    assert!(!sigcache::is_verified(&key_of(&tampered)));
+    crypto::verify_transaction_signature(&tampered).unwrap_err();
+}
+
+#[test]
+fn a_recorded_check_short_circuits_reverification_until_evicted() {
+    let _guard = CACHE_GUARD.lock().unwrap();
+    // A garbage signature fails — until its exact check is recorded as
+    // passed, which is precisely what block connect relies on after
+    // mempool admission already did the work.
+    let mut tx = signed_tx(0x32, 5_000);
+    tx.signature = vec![0u8; 64];
+    crypto::verify_transaction_signature(&tx).unwrap_err();
+    sigcache::record_verified(key_of(&tx));
+    crypto::verify_transaction_signature(&tx).unwrap();
+
+    // A full cache forgets oldest first; the forgotten check is simply
+    // performed again.
+    for i in 0..sigcache::VERIFIED_CACHE_CAPACITY as u64 {
+        let mut filler = [0xF5; 32];
+        filler[..8].copy_from_slice(&i.to_le_bytes());
+        sigcache::record_verified(filler);
+    }
+    assert!(!sigcache::is_verified(&key_of(&tx)));
+    crypto::verify_transaction_signature(&tx).unwrap_err();
+}
+
+#[test]
+fn parsed_public_keys_come_back_identical_and_bad_ones_still_fail() {
+    let _guard = CACHE_GUARD.lock().unwrap();
+    let secp = Secp256k1::new();
+    let secret = SecretKey::from_slice(&[0x33; 32]).unwrap();
+    let bytes = PublicKey::from_secret_key(&secp, &secret).serialize();
+
+    let first = sigcache::parse_pubkey(&bytes).unwrap();
+    let second = sigcache::parse_pubkey(&bytes).unwrap();
+    assert_eq!(first, second);
+    assert_eq!(first, PublicKey::from_slice(&bytes).unwrap());
+
+    let err = sigcache::parse_pubkey(&[0u8; 33]).unwrap_err();
+    assert!(err.contains("malformed public key"), "got: {}", err);
+}
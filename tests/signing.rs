@@ -0,0 +1,98 @@
+//! Signing domain separation and cross-chain replay protection.
+
+use pali_coin::crypto::{
+    sign_transaction, signing_hash, verify_transaction_signature, SIGNING_DOMAIN_TAG,
+};
+use pali_coin::types::Transaction;
+use pali_coin::MAINNET_CHAIN_ID;
+use secp256k1::{Message, Secp256k1, SecretKey};
+
+const TESTNET_CHAIN_ID: u8 = 2;
+
+fn keypair() -> (SecretKey, [u8; 20]) {
+    let secp = Secp256k1::new();
+    let (secret, public) = secp.generate_keypair(&mut rand::thread_rng());
+    (secret, pali_coin::hash::pubkey_to_address(&public.serialize()))
+}
+
+fn unsigned(chain_id: u8, from: [u8; 20]) -> Transaction {
+    Transaction {
+        chain_id,
+        nonce: 3,
+        from,
+        to: [0x22; 20],
+        amount: 5_000,
+        fee: 100,
+        data: b"memo".to_vec(),
+        replaceable: false,
+        lock_time: 42,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+#[test]
+fn signature_round_trips_and_covers_every_field() {
+    let (key, from) = keypair();
+    let mut tx = unsigned(MAINNET_CHAIN_ID, from);
+    sign_transaction(&mut tx, &key).unwrap();
+    verify_transaction_signature(&tx).unwrap();
+
+    // Tampering with any signed field invalidates the signature.
+    for tamper in [
+        |t: &mut Transaction| t.nonce += 1,
+        |t: &mut Transaction| t.amount += 1,
+        |t: &mut Transaction| t.fee += 1,
+        |t: &mut Transaction| t.data.push(0),
+        |t: &mut Transaction| t.replaceable = true,
+        |t: &mut Transaction| t.lock_time += 1,
+        |t: &mut Transaction| t.to[0] ^= 1,
+    ] {
+        let mut tampered = tx.clone();
+        tamper(&mut tampered);
+        assert!(verify_transaction_signature(&tampered).is_err());
+    }
+}
+
+#[test]
+fn mainnet_signature_does_not_replay_on_testnet() {
+    let (key, from) = keypair();
+    let mut tx = unsigned(MAINNET_CHAIN_ID, from);
+    sign_transaction(&mut tx, &key).unwrap();
+
+    // Same sender, same fields, different network: replaying the
+    // captured transaction with the chain id flipped must fail.
+    let mut replayed = tx.clone();
+    replayed.chain_id = TESTNET_CHAIN_ID;
+    assert!(verify_transaction_signature(&replayed).is_err());
+
+    // And a signature made for testnet is only good there.
+    let mut testnet_tx = unsigned(TESTNET_CHAIN_ID, from);
+    sign_transaction(&mut testnet_tx, &key).unwrap();
+    verify_transaction_signature(&testnet_tx).unwrap();
+    assert_ne!(signing_hash(&tx), signing_hash(&testnet_tx));
+}
+
+#[test]
+fn untagged_signatures_are_rejected() {
+    let (key, from) = keypair();
+    let mut tx = unsigned(MAINNET_CHAIN_ID, from);
+
+    // Forge a signature the pre-domain-separation way: over a plain
+    // double-SHA256 of the serialized transaction, no tag.
+    let mut unsigned_clone = tx.clone();
+    unsigned_clone.signature = Vec::new();
+    unsigned_clone.public_key = Vec::new();
+    let untagged =
+        pali_coin::hash::double_sha256(&bincode::serialize(&unsigned_clone).unwrap());
+    let secp = Secp256k1::new();
+    let sig = secp.sign_ecdsa(&Message::from_digest(untagged), &key);
+    tx.signature = sig.serialize_compact().to_vec();
+    tx.public_key = secp256k1::PublicKey::from_secret_key(&secp, &key)
+        .serialize()
+        .to_vec();
+
+    assert!(verify_transaction_signature(&tx).is_err());
+    assert_ne!(untagged, signing_hash(&tx));
+    assert!(!SIGNING_DOMAIN_TAG.is_empty());
+}
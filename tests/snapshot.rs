@@ -0,0 +1,133 @@
+//! Lock-free tip snapshots and the paths that read through them.
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use pali_coin::blockchain::{Blockchain, GenesisConfig};
+use pali_coin::getwork::{build_template, ensure_chain_synced};
+use pali_coin::mempool::Mempool;
+use pali_coin::network::NetworkMessage;
+use pali_coin::node::Node;
+use pali_coin::rpc::{dispatch, RpcContext};
+use pali_coin::rpc_auth::AuthConfig;
+use pali_coin::snapshot::TipView;
+use pali_coin::types::Block;
+use pali_coin::{math, MAINNET_CHAIN_ID};
+use serde_json::{json, Value};
+
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("pali-snapshot-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn genesis() -> GenesisConfig {
+    GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "snapshot test".to_string(),
+        bits: math::MAX_BITS,
+        premine: Vec::new(),
+    }
+}
+
+fn mined_block(chain: &Blockchain) -> Block {
+    let mut block =
+        build_template(chain, &Mempool::new(), MAINNET_CHAIN_ID, [0xAA; 20], None).unwrap();
+    while !math::hash_meets_target(&block.header.hash(), block.header.bits) {
+        block.header.nonce = block.header.nonce.wrapping_add(1);
+    }
+    block
+}
+
+#[test]
+fn snapshots_freeze_the_tip_until_refreshed() {
+    let dir = test_dir("refresh");
+    let mut chain = Blockchain::init_chain(&dir, &genesis()).unwrap();
+    let view = TipView::new(&chain);
+    let at_genesis = view.current();
+    assert_eq!(at_genesis.height, 0);
+    assert_eq!(at_genesis.best_hash, chain.best_hash());
+    assert_eq!(at_genesis.total_work, chain.state().total_work);
+
+    // The snapshot is immutable: connecting a block changes nothing
+    // until a refresh publishes the new tip.
+    let block = mined_block(&chain);
+    chain.add_block(&block, MAINNET_CHAIN_ID).unwrap();
+    assert_eq!(view.current().height, 0);
+    view.refresh(&chain);
+    let at_tip = view.current();
+    assert_eq!(at_tip.height, 1);
+    assert_eq!(at_tip.best_hash, block.hash());
+    assert_eq!(at_tip.total_work, chain.state().total_work);
+    // Readers holding the old Arc keep a coherent stale view.
+    assert_eq!(at_genesis.height, 0);
+}
+
+#[test]
+fn connected_blocks_publish_through_the_node_funnel() {
+    let dir = test_dir("funnel");
+    let chain = Arc::new(Mutex::new(
+        Blockchain::init_chain(&dir, &genesis()).unwrap(),
+    ));
+    let mempool = Arc::new(Mutex::new(Mempool::new()));
+    let node = Arc::new(Node::new(chain.clone(), mempool.clone(), MAINNET_CHAIN_ID));
+    let peer: SocketAddr = "192.0.2.5:7777".parse().unwrap();
+
+    let block = mined_block(&chain.lock().unwrap());
+    node.handle_network_message(peer, NetworkMessage::Block(block.clone()))
+        .unwrap();
+    let tip = node.tip_view.current();
+    assert_eq!(tip.height, 1);
+    assert_eq!(tip.best_hash, block.hash());
+
+    // getblockcount answers from the snapshot when the node runs.
+    let ctx = RpcContext {
+        chain,
+        mempool,
+        node: Some(node),
+        chain_id: MAINNET_CHAIN_ID,
+        auth: Arc::new(AuthConfig::default()),
+    };
+    assert_eq!(
+        dispatch(&ctx, "getblockcount", &Value::Null).unwrap(),
+        json!(1)
+    );
+}
+
+#[test]
+fn snapshot_readers_never_queue_behind_the_chain_lock() {
+    let dir = test_dir("nolock");
+    let chain = Arc::new(Mutex::new(
+        Blockchain::init_chain(&dir, &genesis()).unwrap(),
+    ));
+    let mempool = Arc::new(Mutex::new(Mempool::new()));
+    let node = Arc::new(Node::new(chain.clone(), mempool.clone(), MAINNET_CHAIN_ID));
+    let ctx = RpcContext {
+        chain: chain.clone(),
+        mempool,
+        node: Some(node.clone()),
+        chain_id: MAINNET_CHAIN_ID,
+        auth: Arc::new(AuthConfig::default()),
+    };
+
+    // Hold the chain mutex the way a long block connect would and let
+    // the snapshot paths answer from another thread: the mining gate
+    // and the height RPC must come back while the lock is still held.
+    let guard = chain.lock().unwrap();
+    let (tx, rx) = std::sync::mpsc::channel();
+    let reader = std::thread::spawn(move || {
+        let synced = ensure_chain_synced(&ctx).is_ok();
+        let height = dispatch(&ctx, "getblockcount", &Value::Null).unwrap();
+        let snapshot = node.tip_view.current().height;
+        tx.send((synced, height, snapshot)).unwrap();
+    });
+    let (synced, height, snapshot) = rx
+        .recv_timeout(std::time::Duration::from_secs(5))
+        .expect("snapshot reads blocked behind the chain lock");
+    drop(guard);
+    reader.join().unwrap();
+    assert!(synced);
+    assert_eq!(height, json!(0));
+    assert_eq!(snapshot, 0);
+}
@@ -0,0 +1,147 @@
+//! The event feed behind the `/events` SSE endpoint: ids, replay and
+//! what the node publishes onto it.
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use pali_coin::blockchain::{Blockchain, GenesisConfig};
+use pali_coin::mempool::Mempool;
+use pali_coin::network::NetworkMessage;
+use pali_coin::node::Node;
+use pali_coin::notify::TxEvent;
+use pali_coin::sse::{EventFeed, REPLAY_BUFFER};
+use pali_coin::types::{block_reward, Block, BlockHeader, Hash256, Transaction, COINBASE_ADDRESS};
+use pali_coin::{hash, math, MAINNET_CHAIN_ID};
+use serde_json::json;
+
+#[test]
+fn ids_are_monotonic_and_replay_honours_the_cursor() {
+    let feed = EventFeed::new();
+    for height in 0..(REPLAY_BUFFER as u64 + 10) {
+        feed.block([height as u8; 32], height);
+    }
+
+    // No resume point means no backlog: the client starts live.
+    assert!(feed.replay(None).is_empty());
+
+    // The buffer is bounded; the oldest events age out, and what
+    // remains replays strictly after the cursor in id order.
+    let replayed = feed.replay(Some(0));
+    assert_eq!(replayed.len(), REPLAY_BUFFER);
+    assert_eq!(replayed[0].id, 10);
+    assert_eq!(replayed.last().unwrap().id, REPLAY_BUFFER as u64 + 9);
+    assert!(replayed.windows(2).all(|pair| pair[0].id < pair[1].id));
+
+    // A client that saw everything has nothing to replay.
+    let last = replayed.last().unwrap().id;
+    assert!(feed.replay(Some(last)).is_empty());
+    assert_eq!(feed.replay(Some(last - 2)).len(), 2);
+}
+
+#[test]
+fn live_subscribers_receive_shaped_events() {
+    let feed = EventFeed::new();
+    // Subscribing is forward-only: this event is missed...
+    feed.block([0x11; 32], 1);
+    let mut rx = feed.subscribe();
+    feed.wallet_tx([0x22; 32], TxEvent::Confirmed);
+    feed.address([0xAB; 20], [0x22; 32], pali_coin::watch::Direction::Received, 500, Some(1));
+
+    let tx_event = rx.try_recv().unwrap();
+    assert_eq!(tx_event.kind, "tx");
+    assert_eq!(tx_event.data["txid"], json!(hex::encode([0x22; 32])));
+    assert_eq!(tx_event.data["event"], json!("confirmed"));
+
+    let addr_event = rx.try_recv().unwrap();
+    assert_eq!(addr_event.kind, "address");
+    assert_eq!(addr_event.data["address"], json!(hex::encode([0xAB; 20])));
+    assert_eq!(addr_event.data["direction"], json!("received"));
+    assert_eq!(addr_event.data["amount"], json!(500));
+    assert_eq!(addr_event.data["height"], json!(1));
+    assert!(rx.try_recv().is_err());
+
+    // ...but replay from the tx event's predecessor recovers both.
+    assert_eq!(feed.replay(Some(tx_event.id - 1)).len(), 2);
+}
+
+#[test]
+fn connected_blocks_flow_onto_the_feed() {
+    let dir = std::env::temp_dir().join(format!("pali-sse-{}-node", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "sse test".to_string(),
+        bits: math::MAX_BITS,
+        premine: Vec::new(),
+    };
+    let chain = Arc::new(Mutex::new(Blockchain::init_chain(&dir, &config).unwrap()));
+    let mempool = Arc::new(Mutex::new(Mempool::new()));
+    let node = Node::new(chain.clone(), mempool, MAINNET_CHAIN_ID);
+    let miner_addr = [0x11; 20];
+    node.watch
+        .lock()
+        .unwrap()
+        .watch(miner_addr);
+    let mut rx = node.events.subscribe();
+
+    let coinbase = Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: COINBASE_ADDRESS,
+        to: miner_addr,
+        amount: block_reward(1),
+        fee: 0,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    };
+    let hashes: Vec<Hash256> = vec![coinbase.hash()];
+    let (prev_hash, bits) = {
+        let chain = chain.lock().unwrap();
+        (chain.best_hash(), chain.next_bits().unwrap())
+    };
+    let mut header = BlockHeader {
+        version: 1,
+        prev_hash,
+        merkle_root: hash::merkle_root(&hashes),
+        timestamp: 1_700_000_180,
+        bits,
+        nonce: 0,
+        height: 1,
+    };
+    while !math::hash_meets_target(&header.hash(), header.bits) {
+        header.nonce = header.nonce.wrapping_add(1);
+    }
+    let block = Block {
+        header,
+        transactions: vec![coinbase.clone()],
+    };
+
+    let peer: SocketAddr = "192.0.2.9:7777".parse().unwrap();
+    node.handle_network_message(peer, NetworkMessage::Block(block.clone()))
+        .unwrap();
+
+    // The watched coinbase payout yields a tx event, its address
+    // event with the confirming height, then the new-block event.
+    let tx_event = rx.try_recv().unwrap();
+    assert_eq!(tx_event.kind, "tx");
+    assert_eq!(tx_event.data["txid"], json!(hex::encode(coinbase.hash())));
+    assert_eq!(tx_event.data["event"], json!("confirmed"));
+    let addr_event = rx.try_recv().unwrap();
+    assert_eq!(addr_event.kind, "address");
+    assert_eq!(addr_event.data["address"], json!(hex::encode(miner_addr)));
+    assert_eq!(addr_event.data["height"], json!(1));
+    let block_event = rx.try_recv().unwrap();
+    assert_eq!(block_event.kind, "block");
+    assert_eq!(block_event.data["hash"], json!(hex::encode(block.hash())));
+    assert_eq!(block_event.data["height"], json!(1));
+
+    // A reorg announcement flows too.
+    node.notify_block_disconnected(&block);
+    let reorg_event = rx.try_recv().unwrap();
+    assert_eq!(reorg_event.kind, "tx");
+    assert_eq!(reorg_event.data["event"], json!("reorged"));
+}
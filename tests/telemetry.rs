@@ -0,0 +1,47 @@
+//! Block acceptance telemetry aggregation.
+
+use pali_coin::telemetry::{BlockRecord, BlockTelemetry, MAX_BLOCK_RECORDS};
+use pali_coin::types::TARGET_BLOCK_TIME;
+
+fn record(height: u64, first_seen: u64, validation_micros: u64, delay: i64) -> BlockRecord {
+    BlockRecord {
+        hash: [height as u8; 32],
+        height,
+        first_seen,
+        peer: None,
+        validation_micros,
+        propagation_delay_secs: delay,
+    }
+}
+
+#[test]
+fn stats_aggregate_the_window() {
+    let mut telemetry = BlockTelemetry::new();
+    assert_eq!(telemetry.stats().blocks, 0);
+    assert_eq!(telemetry.stats().target_interval_secs, TARGET_BLOCK_TIME);
+
+    telemetry.record(record(1, 1_000, 200, 2));
+    telemetry.record(record(2, 1_180, 400, -1));
+    telemetry.record(record(3, 1_360, 600, 5));
+
+    let stats = telemetry.stats();
+    assert_eq!(stats.blocks, 3);
+    assert!((stats.avg_validation_micros - 400.0).abs() < 1e-9);
+    assert_eq!(stats.max_validation_micros, 600);
+    assert!((stats.avg_propagation_delay_secs - 2.0).abs() < 1e-9);
+    assert_eq!(stats.max_propagation_delay_secs, 5);
+    // Two 180s gaps: right on target.
+    assert!((stats.avg_interval_secs - TARGET_BLOCK_TIME as f64).abs() < 1e-9);
+}
+
+#[test]
+fn window_is_bounded_and_recent_is_newest_first() {
+    let mut telemetry = BlockTelemetry::new();
+    for height in 0..(MAX_BLOCK_RECORDS as u64 + 10) {
+        telemetry.record(record(height, height * 180, 100, 0));
+    }
+    assert_eq!(telemetry.stats().blocks, MAX_BLOCK_RECORDS as u64);
+    let recent = telemetry.recent(2);
+    assert_eq!(recent.len(), 2);
+    assert!(recent[0].height > recent[1].height);
+}
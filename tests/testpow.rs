@@ -0,0 +1,157 @@
+//! The deterministic PoW stub and the full mining pipeline it unlocks:
+//! template, worker, shares, acceptance and relay in milliseconds.
+
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+use pali_coin::blockchain::{Blockchain, GenesisConfig};
+use pali_coin::getwork::build_template;
+use pali_coin::mempool::Mempool;
+use pali_coin::miner::{Miner, MiningConfig};
+use pali_coin::network::{NetworkMessage, PROTOCOL_VERSION};
+use pali_coin::node::{Node, PeerInfo};
+use pali_coin::pool::ShareLedger;
+use pali_coin::pow::{PowAlgorithm, TestPow, TEST_POW_INTERVAL};
+use pali_coin::types::{BlockHeader, COIN};
+use pali_coin::{math, MAINNET_CHAIN_ID};
+use tokio::sync::mpsc;
+
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("pali-testpow-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn stub_chain(name: &str) -> Blockchain {
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "testpow test".to_string(),
+        bits: math::MAX_BITS,
+        premine: Vec::new(),
+    };
+    let mut chain = Blockchain::init_chain(test_dir(name), &config).unwrap();
+    chain.set_pow_algorithm(&TestPow);
+    chain
+}
+
+fn peer_info(addr: SocketAddr, sender: mpsc::UnboundedSender<NetworkMessage>) -> PeerInfo {
+    PeerInfo {
+        addr,
+        inbound: true,
+        version: PROTOCOL_VERSION,
+        user_agent: String::new(),
+        best_height: 0,
+        connected_at: 0,
+        last_seen: 0,
+        ping_time: None,
+        pending_ping: None,
+        sender,
+    }
+}
+
+#[test]
+fn solving_is_a_deterministic_counter() {
+    let mut miner = Miner::new(MiningConfig::default());
+    miner.set_pow_algorithm(&TestPow);
+    let mut header = BlockHeader {
+        version: 1,
+        prev_hash: [0u8; 32],
+        merkle_root: [0u8; 32],
+        timestamp: 0,
+        bits: 0, // the stub ignores the target entirely
+        nonce: 1,
+        height: 1,
+    };
+
+    // Too small a slice stops one short of the solution; the next
+    // slice lands on it. Attempt counts are exact, not probabilistic.
+    assert!(!miner.solve(&mut header, TEST_POW_INTERVAL - 2));
+    assert_eq!(header.nonce, TEST_POW_INTERVAL - 1);
+    assert!(miner.solve(&mut header, 2));
+    assert_eq!(header.nonce, TEST_POW_INTERVAL);
+    assert_eq!(miner.stats().hashes, TEST_POW_INTERVAL);
+    assert_eq!(miner.stats().blocks_found, 1);
+    // Work accounting is one unit per block at any target.
+    assert_eq!(TestPow.block_work(0), 1);
+    assert_eq!(TestPow.block_work(math::MAX_BITS), 1);
+}
+
+#[test]
+fn template_to_acceptance_with_share_accounting() {
+    let mut chain = stub_chain("pipeline");
+    let base_work = chain.state().total_work;
+    let mut miner = Miner::new(MiningConfig::default());
+    miner.set_pow_algorithm(&TestPow);
+    let worker = [0xAA; 20];
+    let mut ledger = ShareLedger::new();
+
+    for round in 1..=3u64 {
+        let mut block =
+            build_template(&chain, &Mempool::new(), MAINNET_CHAIN_ID, worker, None).unwrap();
+        block.header.nonce = 1;
+        assert!(miner.solve(&mut block.header, TEST_POW_INTERVAL));
+        ledger.record_share(worker, 1, block.header.height, block.header.timestamp);
+        chain.add_block(&block, MAINNET_CHAIN_ID).unwrap();
+        // Settle while the round the block closed is still current.
+        let payouts = ledger.proportional_payouts(50 * COIN);
+        assert_eq!(payouts.len(), 1);
+        assert_eq!(payouts[0].worker, worker);
+        assert_eq!(payouts[0].amount, 50 * COIN);
+        ledger.record_block();
+        assert_eq!(chain.height(), round);
+        // Each stub block adds exactly one unit of chain work, so the
+        // selection metric is observable even at the easiest target.
+        assert_eq!(chain.state().total_work, base_work + round as u128);
+    }
+    assert_eq!(miner.stats().blocks_found, 3);
+    assert_eq!(ledger.blocks_found(), 3);
+}
+
+#[test]
+fn accepted_stub_blocks_relay_to_other_peers() {
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "testpow test".to_string(),
+        bits: math::MAX_BITS,
+        premine: Vec::new(),
+    };
+    let chain = Arc::new(Mutex::new(
+        Blockchain::init_chain(test_dir("relay"), &config).unwrap(),
+    ));
+    chain.lock().unwrap().set_pow_algorithm(&TestPow);
+    let mempool = Arc::new(Mutex::new(Mempool::new()));
+    let node = Node::new(chain.clone(), mempool, MAINNET_CHAIN_ID);
+
+    let finder: SocketAddr = "192.0.2.6:7777".parse().unwrap();
+    let listener: SocketAddr = "192.0.2.7:7777".parse().unwrap();
+    let (finder_tx, mut finder_rx) = mpsc::unbounded_channel();
+    let (listener_tx, mut listener_rx) = mpsc::unbounded_channel();
+    {
+        let mut peers = node.peers.lock().unwrap();
+        peers.insert(finder, peer_info(finder, finder_tx));
+        peers.insert(listener, peer_info(listener, listener_tx));
+    }
+
+    let mut block = {
+        let chain = chain.lock().unwrap();
+        build_template(&chain, &Mempool::new(), MAINNET_CHAIN_ID, [0xBB; 20], None).unwrap()
+    };
+    block.header.nonce = 1;
+    let mut miner = Miner::new(MiningConfig::default());
+    miner.set_pow_algorithm(&TestPow);
+    assert!(miner.solve(&mut block.header, TEST_POW_INTERVAL));
+
+    node.handle_network_message(finder, NetworkMessage::Block(block.clone()))
+        .unwrap();
+    assert_eq!(chain.lock().unwrap().best_hash(), block.hash());
+    // The block is announced to everyone except the peer it came from;
+    // an up-to-date peer gets the hash and asks for the body itself.
+    let NetworkMessage::Inv { blocks, txs } = listener_rx.try_recv().unwrap() else {
+        panic!("expected the block to be announced to the other peer");
+    };
+    assert_eq!(blocks, vec![block.hash()]);
+    assert!(txs.is_empty());
+    assert!(finder_rx.try_recv().is_err());
+}
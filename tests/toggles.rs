@@ -0,0 +1,99 @@
+//! Runtime subsystem toggles and their RPC surface.
+
+use std::sync::{Arc, Mutex};
+
+use pali_coin::blockchain::{Blockchain, GenesisConfig};
+use pali_coin::getwork::ensure_mining_open;
+use pali_coin::mempool::Mempool;
+use pali_coin::node::Node;
+use pali_coin::rpc::{dispatch, RpcContext};
+use pali_coin::rpc_auth::AuthConfig;
+use pali_coin::toggles::Toggles;
+use pali_coin::{math, MAINNET_CHAIN_ID};
+use serde_json::{json, Value};
+
+fn node_ctx(name: &str) -> RpcContext {
+    let dir = std::env::temp_dir().join(format!("pali-toggles-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "toggles test".to_string(),
+        bits: math::MAX_BITS,
+        premine: Vec::new(),
+    };
+    let chain = Arc::new(Mutex::new(Blockchain::init_chain(&dir, &config).unwrap()));
+    let mempool = Arc::new(Mutex::new(Mempool::new()));
+    let node = Node::new(chain.clone(), mempool.clone(), MAINNET_CHAIN_ID);
+    RpcContext {
+        chain,
+        mempool,
+        node: Some(Arc::new(node)),
+        chain_id: MAINNET_CHAIN_ID,
+        auth: Arc::new(AuthConfig::default()),
+    }
+}
+
+#[test]
+fn defaults_run_everything_except_safe_mode() {
+    let toggles = Toggles::default();
+    assert!(toggles.tx_relay);
+    assert!(toggles.mining);
+    assert!(toggles.inbound);
+    assert!(!toggles.safe_mode);
+
+    let mut toggles = Toggles::default();
+    assert!(toggles.set("bogus", true).is_err());
+    toggles.set("safe_mode", true).unwrap();
+    assert!(toggles.safe_mode);
+}
+
+#[test]
+fn settoggle_pauses_mining_and_getinfo_reflects_it() {
+    let ctx = node_ctx("mining");
+    assert!(ensure_mining_open(&ctx).is_ok());
+
+    let result = dispatch(&ctx, "settoggle", &json!(["mining", false])).unwrap();
+    assert_eq!(result.get("mining"), Some(&Value::Bool(false)));
+    let err = ensure_mining_open(&ctx).unwrap_err();
+    assert!(err.contains("paused"), "unexpected error: {}", err);
+
+    let info = dispatch(&ctx, "getinfo", &Value::Null).unwrap();
+    assert_eq!(
+        info.get("toggles").and_then(|t| t.get("mining")),
+        Some(&Value::Bool(false))
+    );
+
+    dispatch(&ctx, "settoggle", &json!(["mining", true])).unwrap();
+    assert!(ensure_mining_open(&ctx).is_ok());
+}
+
+#[test]
+fn automatic_safe_mode_warns_until_lifted() {
+    let ctx = node_ctx("autosafe");
+    let node = ctx.node.as_ref().unwrap().clone();
+    node.enter_safe_mode("invalid longer chain from peer".to_string());
+    assert!(node.toggles.lock().unwrap().safe_mode);
+
+    let info = dispatch(&ctx, "getinfo", &Value::Null).unwrap();
+    let warnings = info.get("warnings").and_then(Value::as_str).unwrap();
+    assert!(warnings.contains("invalid longer chain"), "got: {}", warnings);
+
+    // A second trigger must not clobber the original reason.
+    node.enter_safe_mode("second anomaly".to_string());
+    let info = dispatch(&ctx, "getinfo", &Value::Null).unwrap();
+    assert!(info.get("warnings").and_then(Value::as_str).unwrap().contains("invalid longer chain"));
+
+    // Lifting safe mode over RPC retires the warning too.
+    dispatch(&ctx, "settoggle", &json!(["safe_mode", false])).unwrap();
+    let info = dispatch(&ctx, "getinfo", &Value::Null).unwrap();
+    assert_eq!(info.get("warnings").and_then(Value::as_str), Some(""));
+}
+
+#[test]
+fn safe_mode_refuses_wallet_sends() {
+    let ctx = node_ctx("safemode");
+    dispatch(&ctx, "settoggle", &json!(["safe_mode", true])).unwrap();
+    let err = dispatch(&ctx, "sendtransaction", &json!(["00"])).unwrap_err();
+    assert!(err.contains("safe mode"), "unexpected error: {}", err);
+}
@@ -0,0 +1,106 @@
+//! Encrypted transport sessions: rekeying and resumption.
+
+use pali_coin::transport::{Handshake, ResumptionCache, Session};
+
+fn establish() -> (Session, Session) {
+    let (dialer, dialer_pub) = Handshake::new();
+    let (listener, listener_pub) = Handshake::new();
+    (
+        dialer.establish(&listener_pub, true),
+        listener.establish(&dialer_pub, false),
+    )
+}
+
+#[test]
+fn sessions_round_trip_both_directions() {
+    let (mut alice, mut bob) = establish();
+
+    let sealed = alice.seal(b"block at height 9000").unwrap();
+    assert_ne!(sealed, b"block at height 9000");
+    assert_eq!(bob.open(&sealed).unwrap(), b"block at height 9000");
+
+    let reply = bob.seal(b"ack").unwrap();
+    assert_eq!(alice.open(&reply).unwrap(), b"ack");
+}
+
+#[test]
+fn tampering_reordering_and_replay_fail_authentication() {
+    let (mut alice, mut bob) = establish();
+
+    let mut sealed = alice.seal(b"payload").unwrap();
+    sealed[0] ^= 1;
+    assert!(bob.open(&sealed).is_err());
+
+    // Delivering the second message first desynchronizes the nonce.
+    let (mut alice, mut bob) = establish();
+    let first = alice.seal(b"one").unwrap();
+    let second = alice.seal(b"two").unwrap();
+    assert!(bob.open(&second).is_err());
+    // And a replay of an already-opened message is just as dead.
+    let (mut alice, mut bob) = establish();
+    let only = alice.seal(b"once").unwrap();
+    bob.open(&only).unwrap();
+    assert!(bob.open(&only).is_err());
+    let _ = first;
+}
+
+#[test]
+fn both_ends_ratchet_in_lockstep() {
+    let (mut alice, mut bob) = establish();
+    alice.set_rekey_limits(4, u64::MAX);
+    bob.set_rekey_limits(4, u64::MAX);
+
+    for i in 0..10u32 {
+        let sealed = alice.seal(format!("msg {}", i).as_bytes()).unwrap();
+        bob.open(&sealed).unwrap();
+    }
+    // Ten messages at four per generation: two ratchets so far.
+    assert_eq!(alice.send_generation(), 2);
+
+    // Traffic still flows after the rekeys, in both directions.
+    let sealed = alice.seal(b"after rekey").unwrap();
+    assert_eq!(bob.open(&sealed).unwrap(), b"after rekey");
+    let reply = bob.seal(b"ack").unwrap();
+    assert_eq!(alice.open(&reply).unwrap(), b"ack");
+}
+
+#[test]
+fn the_byte_budget_also_triggers_a_rekey() {
+    let (mut alice, mut bob) = establish();
+    alice.set_rekey_limits(u64::MAX, 64);
+    bob.set_rekey_limits(u64::MAX, 64);
+
+    let sealed = alice.seal(&[0u8; 100]).unwrap();
+    bob.open(&sealed).unwrap();
+    assert_eq!(alice.send_generation(), 1);
+    let sealed = alice.seal(b"still fine").unwrap();
+    assert_eq!(bob.open(&sealed).unwrap(), b"still fine");
+}
+
+#[test]
+fn resumption_skips_the_key_exchange_once() {
+    let (alice, bob) = establish();
+    let ticket = alice.ticket_id();
+    assert_eq!(ticket, bob.ticket_id());
+
+    // The listener caches the closing session's secret; the dialer
+    // keeps its own copy.
+    let mut cache = ResumptionCache::new();
+    cache.store(bob, 1_000);
+    let dialer_secret = alice.into_resumption_secret();
+
+    // Reconnect: ticket plus a fresh salt, no ECDH.
+    let salt = [0x5A; 32];
+    let secret = cache.take(&ticket, 1_200).unwrap();
+    let mut resumed_listener = Session::resume(&secret, &salt, false);
+    let mut resumed_dialer = Session::resume(&dialer_secret, &salt, true);
+    let sealed = resumed_dialer.seal(b"hello again").unwrap();
+    assert_eq!(resumed_listener.open(&sealed).unwrap(), b"hello again");
+
+    // Tickets are single use and age out.
+    assert!(cache.take(&ticket, 1_200).is_none());
+    let (_, bob2) = establish();
+    let ticket2 = bob2.ticket_id();
+    cache.store(bob2, 1_000);
+    assert!(cache.take(&ticket2, 1_000 + 31 * 60).is_none());
+}
@@ -0,0 +1,64 @@
+//! Transaction inclusion proofs: merkle branches against headers.
+
+use pali_coin::blockchain::{Blockchain, GenesisConfig, PremineAllocation};
+use pali_coin::proofs::{build_tx_proof, verify_tx_proof};
+use pali_coin::{math, MAINNET_CHAIN_ID};
+
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("pali-txproof-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn funded_chain(name: &str) -> Blockchain {
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "txproof test".to_string(),
+        bits: math::MAX_BITS,
+        premine: vec![
+            PremineAllocation {
+                address: hex::encode([0xA1; 20]),
+                amount: 50_000,
+            },
+            PremineAllocation {
+                address: hex::encode([0xB0; 20]),
+                amount: 70_000,
+            },
+        ],
+    };
+    Blockchain::init_chain(test_dir(name), &config).unwrap()
+}
+
+#[test]
+fn a_confirmed_transaction_proves_against_its_header() {
+    let chain = funded_chain("roundtrip");
+    let genesis = chain.get_block_by_height(0).unwrap().unwrap();
+    for tx in &genesis.transactions {
+        let proof = build_tx_proof(&chain, &tx.hash()).unwrap();
+        assert_eq!(proof.header, genesis.header);
+        assert!(verify_tx_proof(&proof));
+    }
+}
+
+#[test]
+fn tampering_with_the_branch_or_txid_breaks_the_proof() {
+    let chain = funded_chain("tamper");
+    let genesis = chain.get_block_by_height(0).unwrap().unwrap();
+    let txid = genesis.transactions[1].hash();
+
+    let mut proof = build_tx_proof(&chain, &txid).unwrap();
+    proof.branch[0].hash[0] ^= 1;
+    assert!(!verify_tx_proof(&proof));
+
+    // A proof transplanted onto a different txid proves nothing.
+    let mut proof = build_tx_proof(&chain, &txid).unwrap();
+    proof.txid = genesis.transactions[0].hash();
+    assert!(!verify_tx_proof(&proof));
+}
+
+#[test]
+fn unconfirmed_transactions_have_no_proof() {
+    let chain = funded_chain("unknown");
+    assert!(build_tx_proof(&chain, &[0x99; 32]).is_err());
+}
@@ -0,0 +1,84 @@
+//! Signed release-channel manifests and version comparison.
+
+use pali_coin::updates::{
+    parse_version, running_version, SignedManifest, UpdateStatus, VersionManifest,
+};
+use secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+fn keypair(seed: u8) -> (SecretKey, String) {
+    let key = SecretKey::from_slice(&[seed; 32]).unwrap();
+    let public = PublicKey::from_secret_key(&Secp256k1::signing_only(), &key);
+    (key, hex::encode(public.serialize()))
+}
+
+fn manifest(version: &str, mandatory_before_height: u64) -> VersionManifest {
+    VersionManifest {
+        version: version.to_string(),
+        mandatory_before_height,
+        url: "https://example.invalid/releases".to_string(),
+    }
+}
+
+#[test]
+fn manifests_need_a_threshold_of_distinct_maintainer_keys() {
+    let (key_a, pub_a) = keypair(0x11);
+    let (key_b, pub_b) = keypair(0x12);
+    let keys = [pub_a.as_str(), pub_b.as_str()];
+
+    let mut signed = SignedManifest::new(manifest("1.0.0", 0));
+    signed.sign(&key_a);
+    let err = signed.verify(&keys, 2).unwrap_err();
+    assert!(err.contains("1 of 2"));
+
+    // The same key signing twice still counts once.
+    signed.sign(&key_a);
+    assert!(signed.verify(&keys, 2).is_err());
+
+    signed.sign(&key_b);
+    signed.verify(&keys, 2).unwrap();
+
+    // An outsider key carries no weight, and tampering after signing
+    // invalidates the whole manifest.
+    let (outsider, _) = keypair(0x13);
+    let mut forged = SignedManifest::new(manifest("9.9.9", 0));
+    forged.sign(&outsider);
+    assert!(forged.verify(&keys, 1).is_err());
+
+    let mut tampered = SignedManifest::new(manifest("1.0.0", 0));
+    tampered.sign(&key_a);
+    tampered.manifest.version = "99.0.0".to_string();
+    assert!(tampered.verify(&keys, 1).is_err());
+}
+
+#[test]
+fn version_parsing_orders_by_semver_precedence() {
+    assert_eq!(parse_version("1.2.3").unwrap(), (1, 2, 3));
+    assert!(parse_version("1.2").is_err());
+    assert!(parse_version("1.2.3.4").is_err());
+    assert!(parse_version("1.2.x").is_err());
+
+    // Tuple comparison is SemVer precedence: 0.10.0 > 0.9.1 > 0.9.0.
+    assert!(parse_version("0.10.0").unwrap() > parse_version("0.9.1").unwrap());
+    assert!(parse_version("0.9.1").unwrap() > parse_version("0.9.0").unwrap());
+
+    // The binary's own version must always parse.
+    parse_version(running_version()).unwrap();
+}
+
+#[test]
+fn status_flags_updates_and_mandatory_heights() {
+    // The running version itself is not an update.
+    let current = UpdateStatus::from_manifest(&manifest(running_version(), 500)).unwrap();
+    assert!(!current.update_available);
+    assert_eq!(current.mandatory_before_height, None);
+
+    // A newer routine release is advisory only.
+    let newer = UpdateStatus::from_manifest(&manifest("99.0.0", 0)).unwrap();
+    assert!(newer.update_available);
+    assert_eq!(newer.mandatory_before_height, None);
+
+    // A newer release with a deadline surfaces the height.
+    let mandatory = UpdateStatus::from_manifest(&manifest("99.0.0", 1_000)).unwrap();
+    assert!(mandatory.update_available);
+    assert_eq!(mandatory.mandatory_before_height, Some(1_000));
+}
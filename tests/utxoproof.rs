@@ -0,0 +1,158 @@
+//! UTXO merkle proofs: building on the full node, verifying against a
+//! locally synced header chain.
+
+use pali_coin::blockchain::{
+    Blockchain, GenesisConfig, PremineAllocation, COINBASE_MATURITY,
+};
+use pali_coin::proofs::{build_address_proof, verify_address_proof};
+use pali_coin::types::{
+    block_reward, Address, Block, BlockHeader, Hash256, Transaction, COINBASE_ADDRESS, COIN,
+};
+use pali_coin::{crypto, hash, math, MAINNET_CHAIN_ID};
+use secp256k1::{Secp256k1, SecretKey};
+
+fn test_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("pali-proof-{}-{}", std::process::id(), name));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+fn keypair() -> (SecretKey, Address) {
+    let secp = Secp256k1::new();
+    let (secret, public) = secp.generate_keypair(&mut rand::thread_rng());
+    (secret, hash::pubkey_to_address(&public.serialize()))
+}
+
+fn funded_chain(name: &str, address: &Address, amount: u64) -> Blockchain {
+    let config = GenesisConfig {
+        chain_id: MAINNET_CHAIN_ID,
+        timestamp: 1_700_000_000,
+        message: "proof test".to_string(),
+        bits: math::MAX_BITS,
+        premine: vec![PremineAllocation {
+            address: hex::encode(address),
+            amount,
+        }],
+    };
+    Blockchain::init_chain(test_dir(name), &config).unwrap()
+}
+
+fn seal(chain: &Blockchain, transactions: Vec<Transaction>) -> Block {
+    let hashes: Vec<Hash256> = transactions.iter().map(|tx| tx.hash()).collect();
+    let mut header = BlockHeader {
+        version: 1,
+        prev_hash: chain.best_hash(),
+        merkle_root: hash::merkle_root(&hashes),
+        timestamp: 1_700_000_000 + (chain.height() + 1) * 180,
+        bits: chain.next_bits().unwrap(),
+        nonce: 0,
+        height: chain.height() + 1,
+    };
+    while !math::hash_meets_target(&header.hash(), header.bits) {
+        header.nonce = header.nonce.wrapping_add(1);
+    }
+    Block {
+        header,
+        transactions,
+    }
+}
+
+fn coinbase(height: u64, fees: u64) -> Transaction {
+    Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: COINBASE_ADDRESS,
+        to: [0x11; 20],
+        amount: block_reward(height) + fees,
+        fee: 0,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    }
+}
+
+/// Header lookup a light wallet would answer from its own sync.
+fn local_headers(chain: &Blockchain) -> impl Fn(u64) -> Option<BlockHeader> + '_ {
+    |height| {
+        chain
+            .get_block_by_height(height)
+            .unwrap()
+            .map(|block| block.header)
+    }
+}
+
+#[test]
+fn premined_balance_proves_against_local_headers() {
+    let address = [0xAA; 20];
+    let chain = funded_chain("premine", &address, 3 * COIN);
+
+    let proof = build_address_proof(&chain, &address).unwrap();
+    assert_eq!(proof.utxos.len(), 1);
+    let balance = verify_address_proof(&proof, local_headers(&chain)).unwrap();
+    assert_eq!(balance.proven, 3 * COIN);
+    assert_eq!(balance.claimed_change, 0);
+}
+
+#[test]
+fn tampered_proofs_are_rejected() {
+    let address = [0xAA; 20];
+    let chain = funded_chain("tamper", &address, 3 * COIN);
+    let proof = build_address_proof(&chain, &address).unwrap();
+
+    // A wallet with no header for the height cannot verify anything.
+    assert!(verify_address_proof(&proof, |_| None).is_err());
+
+    // A header that differs from the local chain is an attempted lie.
+    let mut forged = proof.clone();
+    forged.utxos[0].header.timestamp += 1;
+    assert!(verify_address_proof(&forged, local_headers(&chain)).is_err());
+
+    // So is an amount the funding transaction does not back.
+    let mut inflated = proof.clone();
+    inflated.utxos[0].entry.amount += 1;
+    assert!(verify_address_proof(&inflated, local_headers(&chain)).is_err());
+}
+
+#[test]
+fn change_outputs_stay_server_claims() {
+    let (secret, sender) = keypair();
+    let recipient = [0xBB; 20];
+    let mut chain = funded_chain("change", &sender, 5 * COIN);
+
+    // Mature the premine, then spend part of it so change comes back.
+    for height in 1..=COINBASE_MATURITY {
+        let block = seal(&chain, vec![coinbase(height, 0)]);
+        chain.add_block(&block, MAINNET_CHAIN_ID).unwrap();
+    }
+    let mut tx = Transaction {
+        chain_id: MAINNET_CHAIN_ID,
+        nonce: 0,
+        from: sender,
+        to: recipient,
+        amount: 2 * COIN,
+        fee: 1_000,
+        data: Vec::new(),
+        replaceable: false,
+        lock_time: 0,
+        signature: Vec::new(),
+        public_key: Vec::new(),
+    };
+    crypto::sign_transaction(&mut tx, &secret).unwrap();
+    let height = chain.height() + 1;
+    let block = seal(&chain, vec![coinbase(height, tx.fee), tx]);
+    chain.add_block(&block, MAINNET_CHAIN_ID).unwrap();
+
+    // The recipient's coin is fully proven by the included transaction.
+    let received = build_address_proof(&chain, &recipient).unwrap();
+    let balance = verify_address_proof(&received, local_headers(&chain)).unwrap();
+    assert_eq!(balance.proven, 2 * COIN);
+    assert_eq!(balance.claimed_change, 0);
+
+    // The sender's change exists provably, but its amount is a claim.
+    let change = build_address_proof(&chain, &sender).unwrap();
+    let balance = verify_address_proof(&change, local_headers(&chain)).unwrap();
+    assert_eq!(balance.proven, 0);
+    assert_eq!(balance.claimed_change, 5 * COIN - 2 * COIN - 1_000);
+}
@@ -0,0 +1,64 @@
+//! Machine-bound wallet files: v1 compatibility and binding round trips.
+
+use pali_coin::wallet::{SendRequest, Wallet};
+
+fn temp_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("pali-test-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn unbound_round_trip_is_not_machine_bound() {
+    let path = temp_path("unbound.wallet");
+    let mut wallet = Wallet::new();
+    wallet.save_to_file(&path, "hunter2").unwrap();
+    assert!(!Wallet::file_is_machine_bound(&path).unwrap());
+    let loaded = Wallet::from_file(&path, "hunter2").unwrap();
+    assert_eq!(loaded.address(), wallet.address());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn bound_wallet_requires_the_secret() {
+    let path = temp_path("bound.wallet");
+    let secret = [7u8; 32];
+    let mut wallet = Wallet::new();
+    wallet
+        .save_to_file_bound(&path, "hunter2", Some(&secret))
+        .unwrap();
+    assert!(Wallet::file_is_machine_bound(&path).unwrap());
+
+    // Password alone is refused outright.
+    assert!(Wallet::from_file(&path, "hunter2").is_err());
+    // The wrong secret fails decryption.
+    assert!(Wallet::from_file_bound(&path, "hunter2", Some(&[8u8; 32])).is_err());
+
+    let loaded = Wallet::from_file_bound(&path, "hunter2", Some(&secret)).unwrap();
+    assert_eq!(loaded.address(), wallet.address());
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn loaded_bound_wallet_can_sign() {
+    let path = temp_path("bound-sign.wallet");
+    let secret = [9u8; 32];
+    let mut wallet = Wallet::new();
+    wallet
+        .save_to_file_bound(&path, "hunter2", Some(&secret))
+        .unwrap();
+    // The in-memory vault is password-only: the load session must be
+    // usable without the machine secret in hand.
+    let mut loaded = Wallet::from_file_bound(&path, "hunter2", Some(&secret)).unwrap();
+    let tx = loaded
+        .create_transaction(SendRequest {
+            to: [2u8; 20],
+            amount: 100,
+            fee: 10,
+            nonce: 0,
+            chain_id: 1,
+            replaceable: false,
+            tip_height: 0,
+        })
+        .unwrap();
+    pali_coin::crypto::verify_transaction_signature(&tx).unwrap();
+    std::fs::remove_file(&path).unwrap();
+}